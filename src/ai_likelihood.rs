@@ -0,0 +1,484 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Heuristic estimate of how likely code is to be AI-generated.
+//!
+//! The estimate helps teams decide which files to review first. It is reported
+//! as metadata next to the detections and never fails a quality gate.
+//!
+//! Signals come from the text of the file:
+//!
+//! - comments that narrate the next line ("Create a new client", "Step 2:")
+//! - an unusually high ratio of comment lines to code lines
+//! - characteristic placeholder phrases ("In a real implementation", "For
+//!   simplicity", "Replace with your API key")
+//! - decorative output: emoji in strings and banner comments
+//!
+//! When agent session transcripts are available, lines that a session wrote
+//! into the file (with `Write`, `Edit` or `MultiEdit`) are matched back to the
+//! file and reported as hunks. Session evidence outweighs the heuristics.
+
+use crate::analysis::is_comment_line;
+use crate::error::Result;
+use crate::session::Session;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tracing::warn;
+
+/// Comments describing what the next line does, in tutorial style.
+static NARRATING_COMMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^(?:step\s+\d+\b|(?:first|next|then|finally),?\s|(?:create|initialize|define|import|check|get|set|return|loop|iterate|calculate|handle|add|update|validate|convert|parse|call|store|print|log)s?\s+(?:the|a|an|new|all|each|if|over|through|whether)\b)",
+    )
+    .expect("narrating comment pattern is valid")
+});
+
+/// Placeholder phrases typical of generated code.
+static PLACEHOLDER_PHRASE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(?:in a (?:real|production) (?:implementation|application|app|scenario|system)|for (?:now|simplicity|demonstration purposes)|this is a (?:simplified|placeholder|mock|basic) (?:implementation|version|example)|you (?:can|could|might|may) (?:want to )?(?:replace|customize|extend|add)|replace (?:this|with your)|your[_ -]api[_ -]key|example usage)\b",
+    )
+    .expect("placeholder phrase pattern is valid")
+});
+
+/// Emoji commonly printed by generated scripts.
+static DECORATIVE_EMOJI: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"[\x{2705}\x{274C}\x{26A0}\x{2728}\x{1F680}\x{1F4CA}\x{1F50D}\x{1F389}\x{1F4DD}\x{1F527}\x{1F4A1}]",
+    )
+    .expect("emoji pattern is valid")
+});
+
+/// Banner comments such as `// ===== Helpers =====`.
+static BANNER_COMMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?://|#)\s*[=\-*#]{5,}").expect("banner comment pattern is valid")
+});
+
+/// Code lines a file needs before comment ratios say anything.
+const MIN_CODE_LINES: usize = 10;
+
+/// Shortest line (without whitespace) that counts as evidence of a session write.
+const MIN_MATCHED_LINE_CHARS: usize = 4;
+
+/// Coarse likelihood bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LikelihoodLevel {
+    /// Score below 0.4.
+    Low,
+    /// Score from 0.4 to 0.7.
+    Medium,
+    /// Score of 0.7 or more.
+    High,
+}
+
+impl LikelihoodLevel {
+    /// Returns the level of a score between 0 and 1.
+    #[must_use]
+    pub fn of(score: f64) -> Self {
+        if score >= 0.7 {
+            Self::High
+        } else if score >= 0.4 {
+            Self::Medium
+        } else {
+            Self::Low
+        }
+    }
+
+    /// Gets the string representation of the level.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+/// Kind of evidence behind an estimate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    /// Comments narrate what the next line does.
+    NarratingComments,
+    /// Comment lines are unusually frequent relative to code lines.
+    CommentDensity,
+    /// Placeholder phrases typical of generated code.
+    PlaceholderPhrases,
+    /// Emoji in strings or banner comments.
+    DecorativeOutput,
+    /// An agent session wrote lines of the file.
+    SessionWrite,
+}
+
+/// One piece of evidence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AiSignal {
+    /// Kind of evidence.
+    pub kind: SignalKind,
+    /// Contribution to the score (0.0-1.0).
+    pub weight: f64,
+    /// Human-readable description of the evidence.
+    pub evidence: String,
+    /// First line showing the evidence, if it is tied to a line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+/// Lines of a file written by an agent session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratedHunk {
+    /// First line of the hunk (1-based).
+    pub start_line: usize,
+    /// Last line of the hunk (1-based, inclusive).
+    pub end_line: usize,
+    /// Session that wrote the lines.
+    pub session_id: String,
+}
+
+/// Estimated likelihood that a file is AI-generated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AiLikelihood {
+    /// Likelihood between 0.0 and 1.0.
+    pub score: f64,
+    /// Bucket of the score.
+    pub level: LikelihoodLevel,
+    /// Evidence behind the score, strongest first.
+    pub signals: Vec<AiSignal>,
+    /// Line ranges written by agent sessions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hunks: Vec<GeneratedHunk>,
+}
+
+/// Code written by agent sessions, by target file.
+#[derive(Debug, Clone, Default)]
+pub struct SessionWrites {
+    files: HashMap<PathBuf, Vec<SessionWrite>>,
+}
+
+/// Code written to a file by one tool use of a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionWrite {
+    /// Session that wrote the code.
+    pub session_id: String,
+    /// Code that was written.
+    pub content: String,
+}
+
+impl SessionWrites {
+    /// Loads the writes of every session transcript under a projects directory.
+    /// Transcripts that cannot be read are skipped with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the projects directory cannot be listed.
+    pub fn load(projects_dir: &Path) -> Result<Self> {
+        let mut sessions = Vec::new();
+        for path in Session::discover(projects_dir)? {
+            match Session::load(&path) {
+                Ok(session) => sessions.push(session),
+                Err(e) => warn!("Skipping session {}: {}", path.display(), e),
+            }
+        }
+        Ok(Self::from_sessions(&sessions))
+    }
+
+    /// Collects the writes of sessions. Relative target paths are resolved
+    /// against the session's working directory.
+    #[must_use]
+    pub fn from_sessions(sessions: &[Session]) -> Self {
+        let mut files: HashMap<PathBuf, Vec<SessionWrite>> = HashMap::new();
+        for session in sessions {
+            let project_dir = session.project_dir().unwrap_or_default();
+            for write in session.code_writes() {
+                let path = normalize(&project_dir.join(write.file_path));
+                files.entry(path).or_default().push(SessionWrite {
+                    session_id: session.id.clone(),
+                    content: write.content.to_string(),
+                });
+            }
+        }
+        Self { files }
+    }
+
+    /// Returns true if no session wrote any file.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns the writes to a file, in session order.
+    #[must_use]
+    pub fn for_file(&self, path: &Path) -> &[SessionWrite] {
+        let path =
+            std::env::current_dir().map_or_else(|_| path.to_path_buf(), |dir| dir.join(path));
+        self.files.get(&normalize(&path)).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Canonicalizes a path if it exists, so symlinked checkouts still match.
+fn normalize(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Estimates how likely `content` is to be AI-generated, using the session
+/// writes to the file as additional evidence.
+#[must_use]
+pub fn estimate(content: &str, writes: &[SessionWrite]) -> AiLikelihood {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut signals = heuristic_signals(&lines);
+    let heuristic_score = 1.0
+        - signals
+            .iter()
+            .map(|signal| 1.0 - signal.weight)
+            .product::<f64>();
+
+    let hunks = session_hunks(&lines, writes);
+    let mut score = heuristic_score;
+    if !hunks.is_empty() {
+        let code_lines = lines.iter().filter(|line| !line.trim().is_empty()).count();
+        let written = hunks
+            .iter()
+            .flat_map(|hunk| &lines[hunk.start_line - 1..hunk.end_line])
+            .filter(|line| !line.trim().is_empty())
+            .count();
+        #[allow(clippy::cast_precision_loss)] // Line counts are far below 2^52
+        let covered = written as f64 / code_lines.max(1) as f64;
+        let weight = 0.5 + 0.5 * covered;
+        let sessions: HashSet<&str> = hunks.iter().map(|hunk| hunk.session_id.as_str()).collect();
+        signals.push(AiSignal {
+            kind: SignalKind::SessionWrite,
+            weight,
+            evidence: format!(
+                "{written} of {code_lines} non-blank lines written by {} agent session(s)",
+                sessions.len()
+            ),
+            line: hunks.first().map(|hunk| hunk.start_line),
+        });
+        score = score.max(weight);
+    }
+
+    signals.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+    let score = (score * 100.0).round() / 100.0;
+    AiLikelihood {
+        score,
+        level: LikelihoodLevel::of(score),
+        signals,
+        hunks,
+    }
+}
+
+/// Collects the signals found in the text of a file.
+fn heuristic_signals(lines: &[&str]) -> Vec<AiSignal> {
+    let mut signals = Vec::new();
+    let mut comment_lines = 0;
+    let mut code_lines = 0;
+    let mut narrating = Vec::new();
+    let mut placeholders = Vec::new();
+    let mut decorative = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let line_number = index + 1;
+        if is_comment_line(trimmed) {
+            comment_lines += 1;
+            let text = trimmed.trim_start_matches(['/', '#', '*', ' ', '!']);
+            if NARRATING_COMMENT.is_match(text) {
+                narrating.push(line_number);
+            }
+            if BANNER_COMMENT.is_match(trimmed) {
+                decorative.push(line_number);
+            }
+        } else {
+            code_lines += 1;
+            if DECORATIVE_EMOJI.is_match(trimmed) {
+                decorative.push(line_number);
+            }
+        }
+        if PLACEHOLDER_PHRASE.is_match(trimmed) {
+            placeholders.push(line_number);
+        }
+    }
+
+    if narrating.len() >= 3 && narrating.len() * 4 >= comment_lines {
+        signals.push(AiSignal {
+            kind: SignalKind::NarratingComments,
+            weight: 0.3,
+            evidence: format!(
+                "{} of {comment_lines} comments narrate the next line",
+                narrating.len()
+            ),
+            line: narrating.first().copied(),
+        });
+    }
+    if code_lines >= MIN_CODE_LINES && comment_lines * 10 >= code_lines * 4 {
+        signals.push(AiSignal {
+            kind: SignalKind::CommentDensity,
+            weight: 0.15,
+            evidence: format!("{comment_lines} comment lines for {code_lines} code lines"),
+            line: None,
+        });
+    }
+    if !placeholders.is_empty() {
+        signals.push(AiSignal {
+            kind: SignalKind::PlaceholderPhrases,
+            weight: 0.35,
+            evidence: format!("{} placeholder phrase(s)", placeholders.len()),
+            line: placeholders.first().copied(),
+        });
+    }
+    if decorative.len() >= 2 {
+        signals.push(AiSignal {
+            kind: SignalKind::DecorativeOutput,
+            weight: 0.15,
+            evidence: format!("{} lines with emoji or banner comments", decorative.len()),
+            line: decorative.first().copied(),
+        });
+    }
+    signals
+}
+
+/// Finds the line ranges of a file that session writes contain.
+///
+/// A line is attributed to the first session that wrote it. Hunks need at
+/// least two lines long enough to be distinctive; short lines such as `}`
+/// and blank lines only extend a hunk.
+fn session_hunks(lines: &[&str], writes: &[SessionWrite]) -> Vec<GeneratedHunk> {
+    let mut written: HashMap<&str, &str> = HashMap::new();
+    for write in writes {
+        for line in write
+            .content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            written.entry(line).or_insert(write.session_id.as_str());
+        }
+    }
+    if written.is_empty() {
+        return Vec::new();
+    }
+
+    let distinctive =
+        |line: &str| line.chars().filter(|c| !c.is_whitespace()).count() >= MIN_MATCHED_LINE_CHARS;
+    let mut hunks = Vec::new();
+    let mut current: Option<(usize, usize, &str, usize)> = None;
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        let session = written.get(trimmed).copied();
+        let extends = current
+            .as_ref()
+            .is_some_and(|&(_, _, id, _)| session == Some(id));
+        if extends {
+            if let Some((_, end, _, evidence)) = current.as_mut() {
+                *end = index + 1;
+                if distinctive(trimmed) {
+                    *evidence += 1;
+                }
+            }
+        } else if current.is_some() && trimmed.is_empty() {
+            // Blank lines do not end a hunk
+        } else {
+            if let Some(hunk) = current.take() {
+                push_hunk(&mut hunks, hunk);
+            }
+            if let Some(session) = session.filter(|_| distinctive(trimmed)) {
+                current = Some((index + 1, index + 1, session, 1));
+            }
+        }
+    }
+    if let Some(hunk) = current {
+        push_hunk(&mut hunks, hunk);
+    }
+    hunks
+}
+
+fn push_hunk(
+    hunks: &mut Vec<GeneratedHunk>,
+    (start, end, session, evidence): (usize, usize, &str, usize),
+) {
+    if evidence >= 2 {
+        hunks.push(GeneratedHunk {
+            start_line: start,
+            end_line: end,
+            session_id: session.to_string(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_signals() {
+        let generated = "\
+// Import the required modules
+use std::fs;
+
+// ===== Helpers =====
+// Define the configuration loader
+fn load() -> String {
+    // Read the file contents
+    // In a real implementation, this would come from a secret store
+    let key = \"YOUR_API_KEY\";
+    // Return the result
+    println!(\"\u{2705} Loaded\");
+    key.to_string()
+}
+";
+        let likelihood = estimate(generated, &[]);
+        let kinds: Vec<SignalKind> = likelihood
+            .signals
+            .iter()
+            .map(|signal| signal.kind)
+            .collect();
+        assert!(kinds.contains(&SignalKind::NarratingComments), "{kinds:?}");
+        assert!(kinds.contains(&SignalKind::PlaceholderPhrases), "{kinds:?}");
+        assert!(kinds.contains(&SignalKind::DecorativeOutput), "{kinds:?}");
+        assert_eq!(likelihood.level, LikelihoodLevel::Medium);
+
+        let handwritten =
+            "fn load(path: &Path) -> io::Result<String> {\n    fs::read_to_string(path)\n}\n";
+        let likelihood = estimate(handwritten, &[]);
+        assert!(likelihood.signals.is_empty());
+        assert_eq!(likelihood.level, LikelihoodLevel::Low);
+    }
+
+    #[test]
+    fn test_session_writes_become_hunks() {
+        let content = "\
+fn handwritten() {}
+
+fn parse(input: &str) -> u32 {
+    let trimmed = input.trim();
+
+    trimmed.parse().unwrap_or(0)
+}
+";
+        let writes = [SessionWrite {
+            session_id: "3f2c9a1e".to_string(),
+            content: "fn parse(input: &str) -> u32 {\n    let trimmed = input.trim();\n\n    \
+                      trimmed.parse().unwrap_or(0)\n}\n"
+                .to_string(),
+        }];
+        let likelihood = estimate(content, &writes);
+        assert_eq!(
+            likelihood.hunks,
+            vec![GeneratedHunk {
+                start_line: 3,
+                end_line: 7,
+                session_id: "3f2c9a1e".to_string(),
+            }]
+        );
+        assert_eq!(likelihood.signals[0].kind, SignalKind::SessionWrite);
+        assert_eq!(likelihood.level, LikelihoodLevel::High);
+    }
+}
@@ -13,13 +13,19 @@
 #![allow(clippy::needless_range_loop)]
 #![allow(clippy::trivially_copy_pass_by_ref)]
 
+use crate::ast_query::{AstMatch, AstQueryEngine};
 use crate::error::{Result, SniffError};
-use crate::playbook::{DetectionRule, PatternScope, PatternType, PlaybookManager, Severity};
+use crate::playbook::{
+    CompiledConditions, DetectionRule, PatternScope, PatternType, PlaybookManager, Remediation,
+    RuleConditions, RuleOverride, RuleSource, SecurityTaxonomy, Severity,
+};
+use crate::plugin::{PluginInput, PluginMatch, PluginRegistry};
+use crate::structural::{StructuralAnalysis, StructuralMatch};
 use rayon::prelude::*;
 use regex::Regex;
 use rust_tree_sitter::{
     ai_analysis::{AIAnalysisResult, AIAnalyzer, AIConfig},
-    analyzer::{AnalysisConfig, AnalysisResult, CodebaseAnalyzer, FileInfo},
+    analyzer::{AnalysisConfig, AnalysisResult, CodebaseAnalyzer, FileInfo, Symbol},
     complexity_analysis::{ComplexityAnalyzer, ComplexityMetrics},
     detect_language_from_path,
     performance_analysis::{PerformanceAnalysisResult, PerformanceAnalyzer},
@@ -28,8 +34,9 @@ use rust_tree_sitter::{
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
+use tracing::warn;
 
 /// Represents a language supported by the analysis system.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -48,9 +55,35 @@ pub enum SupportedLanguage {
     C,
     /// C++ programming language
     Cpp,
+    /// Swift programming language
+    Swift,
+    /// Terraform configuration language (HCL)
+    Terraform,
+    /// Scala programming language
+    Scala,
+    /// YAML and JSON configuration files
+    Config,
+    /// Markdown documentation
+    Markdown,
 }
 
 impl SupportedLanguage {
+    /// Every supported language.
+    pub const ALL: [Self; 12] = [
+        Self::Rust,
+        Self::Python,
+        Self::TypeScript,
+        Self::JavaScript,
+        Self::Go,
+        Self::C,
+        Self::Cpp,
+        Self::Swift,
+        Self::Terraform,
+        Self::Scala,
+        Self::Config,
+        Self::Markdown,
+    ];
+
     /// Gets the string representation of the language.
     #[must_use]
     pub fn name(&self) -> &'static str {
@@ -62,20 +95,90 @@ impl SupportedLanguage {
             Self::Go => "go",
             Self::C => "c",
             Self::Cpp => "cpp",
+            Self::Swift => "swift",
+            Self::Terraform => "terraform",
+            Self::Scala => "scala",
+            Self::Config => "config",
+            Self::Markdown => "markdown",
+        }
+    }
+
+    /// Gets the usual file extension of the language.
+    #[must_use]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Rust => "rs",
+            Self::Python => "py",
+            Self::JavaScript => "js",
+            Self::TypeScript => "ts",
+            Self::Go => "go",
+            Self::C => "c",
+            Self::Cpp => "cpp",
+            Self::Swift => "swift",
+            Self::Terraform => "tf",
+            Self::Scala => "scala",
+            Self::Config => "yaml",
+            Self::Markdown => "md",
+        }
+    }
+
+    /// Parses a language name as returned by [`Self::name`], ignoring case.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rust" => Some(Self::Rust),
+            "python" => Some(Self::Python),
+            "javascript" => Some(Self::JavaScript),
+            "typescript" => Some(Self::TypeScript),
+            "go" => Some(Self::Go),
+            "c" => Some(Self::C),
+            "cpp" => Some(Self::Cpp),
+            "swift" => Some(Self::Swift),
+            "terraform" | "hcl" => Some(Self::Terraform),
+            "scala" => Some(Self::Scala),
+            "config" | "yaml" | "json" => Some(Self::Config),
+            "markdown" | "md" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+
+    /// Detects a language without a tree-sitter grammar from a file extension.
+    ///
+    /// Languages with a grammar are detected by rust-treesitter-agent-code-utility.
+    #[must_use]
+    pub fn from_text_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "swift" => Some(Self::Swift),
+            "tf" | "tfvars" | "hcl" => Some(Self::Terraform),
+            "scala" | "sc" => Some(Self::Scala),
+            "yaml" | "yml" | "json" => Some(Self::Config),
+            "md" | "markdown" => Some(Self::Markdown),
+            _ => None,
         }
     }
 
-    /// Converts to rust-treesitter-agent-code-utility Language enum.
+    /// Returns true if files of the language are parsed with a tree-sitter
+    /// grammar.
+    ///
+    /// Files of other languages have no symbols: only file-scoped regex rules,
+    /// scripts and plugins apply to them.
+    #[must_use]
+    pub fn has_grammar(&self) -> bool {
+        self.to_agent_language().is_some()
+    }
+
+    /// Converts to rust-treesitter-agent-code-utility Language enum, if it has one.
     #[must_use]
-    pub fn to_agent_language(&self) -> Language {
+    pub fn to_agent_language(&self) -> Option<Language> {
         match self {
-            Self::Rust => Language::Rust,
-            Self::Python => Language::Python,
-            Self::JavaScript => Language::JavaScript,
-            Self::TypeScript => Language::TypeScript,
-            Self::Go => Language::Go,
-            Self::C => Language::C,
-            Self::Cpp => Language::Cpp,
+            Self::Rust => Some(Language::Rust),
+            Self::Python => Some(Language::Python),
+            Self::JavaScript => Some(Language::JavaScript),
+            Self::TypeScript => Some(Language::TypeScript),
+            Self::Go => Some(Language::Go),
+            Self::C => Some(Language::C),
+            Self::Cpp => Some(Language::Cpp),
+            Self::Swift | Self::Terraform | Self::Scala | Self::Config | Self::Markdown => None,
         }
     }
 
@@ -107,6 +210,10 @@ pub struct MisalignmentDetection {
     pub severity: Severity,
     /// File path where the detection occurred.
     pub file_path: String,
+    /// Path of the file relative to its project root, set when results are
+    /// assembled. Fingerprints use it when present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
     /// Line number where the detection occurred.
     pub line_number: usize,
     /// Column number where the detection occurred.
@@ -119,16 +226,53 @@ pub struct MisalignmentDetection {
     pub context: String,
     /// Tags associated with this detection.
     pub tags: Vec<String>,
+    /// Remediation guidance from the rule (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<Remediation>,
+    /// CWE/OWASP taxonomy from the rule, for security rules (optional).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taxonomy: Option<SecurityTaxonomy>,
     /// Performance impact assessment (optional).
     pub performance_impact: Option<PerformanceImpact>,
     /// Test file classification and context information.
     pub test_context: Option<TestContext>,
 }
 
+impl MisalignmentDetection {
+    /// Returns a stable fingerprint identifying this detection.
+    ///
+    /// The fingerprint is derived from the rule, file path and trimmed code snippet,
+    /// so it survives unrelated edits that only shift line numbers. The path is
+    /// [`Self::project_path`] when set, so fingerprints do not depend on the
+    /// directory sniff was run from, and `./src/lib.rs` and `src/lib.rs`
+    /// fingerprint alike.
+    #[must_use]
+    pub fn fingerprint(&self) -> String {
+        let path = self.project_path.as_deref().unwrap_or(&self.file_path);
+        crate::hash::stable_hash([
+            self.rule_id.as_str(),
+            normalize_path(path).as_str(),
+            self.code_snippet.trim(),
+        ])
+    }
+}
+
+/// Normalizes a relative path for fingerprinting: `/` separators and no
+/// leading `./`.
+fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut normalized = path.as_str();
+    while let Some(rest) = normalized.strip_prefix("./") {
+        normalized = rest.trim_start_matches('/');
+    }
+    normalized.to_string()
+}
+
 /// Enhanced analysis result that includes performance metrics.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnhancedMisalignmentAnalysis {
     /// Basic bullshit detections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub detections: Vec<MisalignmentDetection>,
     /// Performance analysis results (simplified for serialization)
     pub performance_score: u8,
@@ -179,6 +323,211 @@ pub struct QualityAssessment {
     pub completeness_score: f64,
 }
 
+impl QualityAssessment {
+    /// Calculates the assessment of a file's detections.
+    ///
+    /// Each detection costs its severity's penalty in the category it affects;
+    /// the overall score is the weighted average of the category scores.
+    #[must_use]
+    pub fn calculate(
+        detections: &[MisalignmentDetection],
+        performance_score: f64,
+        weights: &ScoreWeights,
+    ) -> Self {
+        let mut maintainability_score: f64 = 100.0;
+        let mut reliability_score: f64 = 100.0;
+        let mut security_score: f64 = 100.0;
+        let mut completeness_score: f64 = 100.0;
+
+        for detection in detections {
+            let penalty = weights.penalties.penalty(detection.severity);
+            let rule_id = detection.rule_id.as_str();
+            if rule_id.contains("security") {
+                security_score -= penalty;
+            } else if rule_id.contains("unimplemented") || rule_id.contains("todo") {
+                completeness_score -= penalty;
+            } else if matches!(detection.severity, Severity::Critical | Severity::High) {
+                reliability_score -= penalty;
+            } else {
+                maintainability_score -= penalty;
+            }
+        }
+
+        let mut assessment = Self {
+            overall_score: 0.0,
+            maintainability_score: maintainability_score.max(0.0),
+            reliability_score: reliability_score.max(0.0),
+            performance_score,
+            security_score: security_score.max(0.0),
+            completeness_score: completeness_score.max(0.0),
+        };
+        assessment.overall_score = weights.categories.overall(&assessment);
+        assessment
+    }
+}
+
+/// Weights of the quality score formula, set under `[scoring]` in `.sniff.toml`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScoreWeights {
+    /// Points deducted per detection of each severity.
+    pub penalties: SeverityPenalties,
+    /// Weights of the assessment categories in the overall score.
+    pub categories: CategoryWeights,
+}
+
+/// Points deducted from a score per detection of each severity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeverityPenalties {
+    /// Penalty of a critical detection.
+    pub critical: f64,
+    /// Penalty of a high severity detection.
+    pub high: f64,
+    /// Penalty of a medium severity detection.
+    pub medium: f64,
+    /// Penalty of a low severity detection.
+    pub low: f64,
+    /// Penalty of an informational detection.
+    pub info: f64,
+}
+
+impl Default for SeverityPenalties {
+    fn default() -> Self {
+        Self {
+            critical: 25.0,
+            high: 15.0,
+            medium: 8.0,
+            low: 3.0,
+            info: 1.0,
+        }
+    }
+}
+
+impl SeverityPenalties {
+    /// Gets the penalty of a detection with this severity.
+    #[must_use]
+    pub fn penalty(&self, severity: Severity) -> f64 {
+        match severity {
+            Severity::Critical => self.critical,
+            Severity::High => self.high,
+            Severity::Medium => self.medium,
+            Severity::Low => self.low,
+            Severity::Info => self.info,
+        }
+    }
+
+    /// Calculates a 0-100 score by deducting the penalty of each detection.
+    #[must_use]
+    pub fn score(&self, detections: &[MisalignmentDetection]) -> f64 {
+        let penalty: f64 = detections
+            .iter()
+            .map(|detection| self.penalty(detection.severity))
+            .sum();
+        (100.0 - penalty).max(0.0)
+    }
+}
+
+/// Relative weights of the assessment categories in the overall score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CategoryWeights {
+    /// Weight of the maintainability score.
+    pub maintainability: f64,
+    /// Weight of the reliability score.
+    pub reliability: f64,
+    /// Weight of the performance score.
+    pub performance: f64,
+    /// Weight of the security score.
+    pub security: f64,
+    /// Weight of the completeness score.
+    pub completeness: f64,
+}
+
+impl Default for CategoryWeights {
+    fn default() -> Self {
+        Self {
+            maintainability: 0.25,
+            reliability: 0.30,
+            performance: 0.20,
+            security: 0.15,
+            completeness: 0.10,
+        }
+    }
+}
+
+impl CategoryWeights {
+    /// Calculates the weighted average of the category scores of an assessment.
+    ///
+    /// Weights are relative and need not sum to 1; if they are all zero, every
+    /// category counts the same.
+    #[must_use]
+    pub fn overall(&self, assessment: &QualityAssessment) -> f64 {
+        let scores = [
+            (self.maintainability, assessment.maintainability_score),
+            (self.reliability, assessment.reliability_score),
+            (self.performance, assessment.performance_score),
+            (self.security, assessment.security_score),
+            (self.completeness, assessment.completeness_score),
+        ];
+        let total_weight: f64 = scores.iter().map(|(weight, _)| weight).sum();
+        if total_weight <= 0.0 {
+            return scores.iter().map(|(_, score)| score).sum::<f64>() / scores.len() as f64;
+        }
+        let weighted: f64 = scores.iter().map(|(weight, score)| weight * score).sum();
+        (weighted / total_weight).max(0.0)
+    }
+}
+
+/// Performance hotspot report for a single file (simplified for serialization).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceReport {
+    /// File path that was analyzed
+    pub file_path: std::path::PathBuf,
+    /// Overall performance score (0-100)
+    pub performance_score: u8,
+    /// Average complexity across the file's functions
+    pub average_complexity: f64,
+    /// Performance hotspots ordered as reported by the analyzer
+    pub hotspots: Vec<PerformanceHotspot>,
+    /// Nested loops found in the file
+    pub nested_loops: Vec<NestedLoopReport>,
+    /// Number of memory allocation hotspots
+    pub allocation_hotspots: usize,
+    /// Opportunities for parallelizing work
+    pub parallelization_opportunities: Vec<ParallelizationOpportunity>,
+    /// Optimization recommendations
+    pub recommendations: Vec<String>,
+}
+
+/// A single performance hotspot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceHotspot {
+    /// Short title of the hotspot
+    pub title: String,
+    /// Performance severity (Critical, High, Medium, Low, Info)
+    pub severity: String,
+    /// Suggested optimization
+    pub optimization: String,
+    /// Expected performance gain in percent
+    pub expected_gain: f64,
+}
+
+/// A nested loop found during performance analysis.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NestedLoopReport {
+    /// Nesting depth of the loop
+    pub depth: usize,
+    /// Line where the outer loop starts
+    pub line: usize,
+}
+
+/// An opportunity to parallelize work.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParallelizationOpportunity {
+    /// Suggested parallelization approach
+    pub approach: String,
+    /// Expected speedup factor
+    pub expected_speedup: f64,
+}
+
 /// Test file classification and context information.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TestContext {
@@ -196,6 +545,37 @@ pub struct TestContext {
     pub should_suppress: bool,
 }
 
+/// A detection withheld from results because of its test context.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuppressedDetection {
+    /// The detection as it would have been reported.
+    #[serde(flatten)]
+    pub detection: MisalignmentDetection,
+    /// Why the detection was suppressed.
+    pub suppression_reason: String,
+}
+
+impl SuppressedDetection {
+    /// Wraps a detection suppressed by the test-file classifier.
+    fn from_test_context(detection: MisalignmentDetection) -> Self {
+        let suppression_reason = detection.test_context.as_ref().map_or_else(
+            || "suppressed in test context".to_string(),
+            |context| {
+                format!(
+                    "rule '{}' is not reported in test files ({:?}, {:.0}% confidence)",
+                    detection.rule_id,
+                    context.test_type,
+                    context.confidence * 100.0
+                )
+            },
+        );
+        Self {
+            detection,
+            suppression_reason,
+        }
+    }
+}
+
 /// Types of test files detected.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TestFileType {
@@ -410,6 +790,10 @@ impl TestFileClassifier {
                 || name_str.ends_with("-test.js")
                 || name_str.ends_with("_test.ts")
                 || name_str.ends_with("-test.ts")
+                || file_name.to_string_lossy().ends_with("Tests.swift")
+                || file_name.to_string_lossy().ends_with("Test.scala")
+                || file_name.to_string_lossy().ends_with("Spec.scala")
+                || file_name.to_string_lossy().ends_with("Suite.scala")
             {
                 indicators.push(TestIndicator::NamingConvention("_test suffix".to_string()));
                 confidence += 0.3;
@@ -547,6 +931,24 @@ impl TestFileClassifier {
                     &mut test_type,
                 );
             }
+            SupportedLanguage::Swift => {
+                self.analyze_swift_test_content(
+                    &content_lower,
+                    &lines,
+                    &mut indicators,
+                    &mut confidence,
+                    &mut test_type,
+                );
+            }
+            SupportedLanguage::Scala => {
+                self.analyze_scala_test_content(
+                    &content_lower,
+                    &lines,
+                    &mut indicators,
+                    &mut confidence,
+                    &mut test_type,
+                );
+            }
             _ => {
                 // Generic test pattern detection
                 self.analyze_generic_test_content(
@@ -578,6 +980,11 @@ impl TestFileClassifier {
                 "go" => SupportedLanguage::Go,
                 "c" => SupportedLanguage::C,
                 "cpp" | "cc" | "cxx" => SupportedLanguage::Cpp,
+                "swift" => SupportedLanguage::Swift,
+                "tf" | "tfvars" | "hcl" => SupportedLanguage::Terraform,
+                "scala" | "sc" => SupportedLanguage::Scala,
+                "yaml" | "yml" | "json" => SupportedLanguage::Config,
+                "md" | "markdown" => SupportedLanguage::Markdown,
                 _ => SupportedLanguage::Rust, // Default fallback
             }
         } else {
@@ -783,6 +1190,96 @@ impl TestFileClassifier {
         }
     }
 
+    /// Analyzes Swift-specific test content (XCTest and Swift Testing).
+    fn analyze_swift_test_content(
+        &self,
+        content_lower: &str,
+        lines: &[&str],
+        indicators: &mut Vec<TestIndicator>,
+        confidence: &mut f64,
+        test_type: &mut TestFileType,
+    ) {
+        // Swift test imports
+        if content_lower.contains("import xctest") {
+            indicators.push(TestIndicator::TestFramework("XCTest".to_string()));
+            *confidence += 0.4;
+            *test_type = TestFileType::UnitTest;
+        } else if content_lower.contains("import testing") {
+            indicators.push(TestIndicator::TestFramework("Swift Testing".to_string()));
+            *confidence += 0.4;
+            *test_type = TestFileType::UnitTest;
+        }
+
+        // XCTestCase subclasses, test methods and @Test functions
+        for line in lines {
+            let line_lower = line.trim().to_lowercase();
+            if line_lower.contains(": xctestcase") {
+                indicators.push(TestIndicator::TestPattern(
+                    "XCTestCase subclass".to_string(),
+                ));
+                *confidence += 0.4;
+                *test_type = TestFileType::UnitTest;
+            }
+            if line_lower.starts_with("func test") || line_lower.starts_with("@test") {
+                indicators.push(TestIndicator::TestPattern("test function".to_string()));
+                *confidence += 0.2;
+                *test_type = TestFileType::UnitTest;
+            }
+            if line_lower.starts_with("func testperformance") || line_lower.contains("measure {") {
+                indicators.push(TestIndicator::TestPattern("performance test".to_string()));
+                *confidence += 0.2;
+                *test_type = TestFileType::BenchmarkTest;
+            }
+        }
+    }
+
+    /// Analyzes Scala-specific test content (ScalaTest and munit).
+    fn analyze_scala_test_content(
+        &self,
+        content_lower: &str,
+        lines: &[&str],
+        indicators: &mut Vec<TestIndicator>,
+        confidence: &mut f64,
+        test_type: &mut TestFileType,
+    ) {
+        // Scala test framework imports
+        if content_lower.contains("import org.scalatest") {
+            indicators.push(TestIndicator::TestFramework("ScalaTest".to_string()));
+            *confidence += 0.4;
+            *test_type = TestFileType::UnitTest;
+        } else if content_lower.contains("import munit") {
+            indicators.push(TestIndicator::TestFramework("munit".to_string()));
+            *confidence += 0.4;
+            *test_type = TestFileType::UnitTest;
+        }
+
+        // Suite classes, test("...") cases and "..." should "..." in specs
+        for line in lines {
+            let line_lower = line.trim().to_lowercase();
+            if line_lower.contains("extends anyfunsuite")
+                || line_lower.contains("extends anyflatspec")
+                || line_lower.contains("extends anywordspec")
+                || line_lower.contains("extends anyfunspec")
+                || line_lower.contains("extends munit.funsuite")
+                || line_lower.contains("extends funsuite")
+                || line_lower.contains("extends scalacheck")
+            {
+                indicators.push(TestIndicator::TestPattern("test suite class".to_string()));
+                *confidence += 0.4;
+                *test_type = TestFileType::UnitTest;
+            }
+            if line_lower.starts_with("test(\"")
+                || line_lower.starts_with("property(\"")
+                || (line_lower.contains("\" should ") && line_lower.ends_with(" in {"))
+                || line_lower.starts_with("it should ")
+            {
+                indicators.push(TestIndicator::TestPattern("test case".to_string()));
+                *confidence += 0.2;
+                *test_type = TestFileType::UnitTest;
+            }
+        }
+    }
+
     /// Analyzes generic test content patterns.
     fn analyze_generic_test_content(
         &self,
@@ -943,11 +1440,31 @@ pub struct MisalignmentAnalyzer {
     parser: Option<Parser>,
     /// Playbook manager for loading and managing detection rules.
     playbook_manager: PlaybookManager,
-    /// Cache for compiled regex patterns.
-    #[allow(dead_code)]
+    /// Compiled regexes of regex rules, keyed by pattern.
     compiled_patterns: HashMap<String, Regex>,
+    /// Parsed analyses of structural rules, keyed by a hash of the analysis
+    /// type and parameters.
+    structural_analyses: HashMap<u64, StructuralAnalysis>,
+    /// Compiled conditions of rules, shared by parallel analysis.
+    compiled_conditions: ConditionsCache,
     /// Test file classifier for identifying test files and adjusting severity.
     test_classifier: TestFileClassifier,
+    /// Tree-sitter query engine for `AstQuery` rules.
+    ast_queries: AstQueryEngine,
+    /// WASM modules for `Wasm` rules.
+    plugins: PluginRegistry,
+    /// Whether detections suppressed by test context are kept for [`Self::take_suppressed`].
+    collect_suppressed: bool,
+    /// Whether YAML and JSON files are analyzed as config files.
+    config_files: bool,
+    /// Whether Markdown files are analyzed.
+    markdown: bool,
+    /// Detections suppressed by test context since the last [`Self::take_suppressed`].
+    suppressed: Vec<SuppressedDetection>,
+    /// Whether the symbols of the last analyzed file are kept for [`Self::take_symbols`].
+    collect_symbols: bool,
+    /// Symbols of the last analyzed file.
+    symbols: Vec<Symbol>,
 }
 
 impl MisalignmentAnalyzer {
@@ -994,26 +1511,38 @@ impl MisalignmentAnalyzer {
             parser,
             playbook_manager,
             compiled_patterns: HashMap::new(),
+            structural_analyses: HashMap::new(),
+            compiled_conditions: ConditionsCache::default(),
             test_classifier: TestFileClassifier::new(),
+            ast_queries: AstQueryEngine::new(),
+            plugins: PluginRegistry::default(),
+            collect_suppressed: false,
+            config_files: false,
+            markdown: false,
+            suppressed: Vec::new(),
+            collect_symbols: false,
+            symbols: Vec::new(),
         })
     }
 
     /// Loads default playbooks for all supported languages.
     fn load_default_playbooks(playbook_manager: &mut PlaybookManager) {
-        let languages = [
-            SupportedLanguage::Rust,
-            SupportedLanguage::Python,
-            SupportedLanguage::JavaScript,
-            SupportedLanguage::TypeScript,
-            SupportedLanguage::Go,
-            SupportedLanguage::C,
-            SupportedLanguage::Cpp,
-        ];
-
-        for language in &languages {
+        for language in &SupportedLanguage::ALL {
             let playbook = PlaybookManager::create_default_playbook(*language);
             playbook_manager.add_playbook(*language, playbook);
         }
+
+        playbook_manager.add_session_playbook(PlaybookManager::create_session_default_playbook());
+    }
+
+    /// Returns the active rules that apply to assistant thinking blocks.
+    #[must_use]
+    pub fn thinking_rules(&self) -> Vec<DetectionRule> {
+        self.playbook_manager
+            .get_active_thinking_rules()
+            .into_iter()
+            .cloned()
+            .collect()
     }
 
     /// Creates a new bullshit analyzer with custom configuration.
@@ -1059,7 +1588,17 @@ impl MisalignmentAnalyzer {
             parser,
             playbook_manager,
             compiled_patterns: HashMap::new(),
+            structural_analyses: HashMap::new(),
+            compiled_conditions: ConditionsCache::default(),
             test_classifier: TestFileClassifier::new(),
+            ast_queries: AstQueryEngine::new(),
+            plugins: PluginRegistry::default(),
+            collect_suppressed: false,
+            config_files: false,
+            markdown: false,
+            suppressed: Vec::new(),
+            collect_symbols: false,
+            symbols: Vec::new(),
         })
     }
 
@@ -1105,7 +1644,17 @@ impl MisalignmentAnalyzer {
             parser,
             playbook_manager,
             compiled_patterns: HashMap::new(),
+            structural_analyses: HashMap::new(),
+            compiled_conditions: ConditionsCache::default(),
             test_classifier: TestFileClassifier::new(),
+            ast_queries: AstQueryEngine::new(),
+            plugins: PluginRegistry::default(),
+            collect_suppressed: false,
+            config_files: false,
+            markdown: false,
+            suppressed: Vec::new(),
+            collect_symbols: false,
+            symbols: Vec::new(),
         })
     }
 
@@ -1118,29 +1667,52 @@ impl MisalignmentAnalyzer {
         self.playbook_manager.load_playbooks_from_dir(playbook_dir)
     }
 
+    /// Returns the playbook files that failed to load, with the error.
+    #[must_use]
+    pub fn playbook_load_failures(&self) -> &[(PathBuf, String)] {
+        self.playbook_manager.load_failures()
+    }
+
+    /// Loads WASM rule plugins: the `.wasm` modules and the playbooks declaring
+    /// their rules, both from `plugins_dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory exists but cannot be read.
+    pub fn load_plugins(&mut self, plugins_dir: &Path) -> Result<()> {
+        self.plugins.load_dir(plugins_dir)?;
+        self.playbook_manager.load_playbooks_from_dir(plugins_dir)
+    }
+
+    /// Returns the WASM modules that failed to load, with the error.
+    #[must_use]
+    pub fn plugin_load_failures(&self) -> &[(PathBuf, String)] {
+        self.plugins.load_failures()
+    }
+
     /// Loads learned patterns from .sniff folder and integrates them with playbooks.
     ///
+    /// With `read_only`, the folder is only read and never created.
+    ///
     /// # Errors
     ///
     /// Returns an error if the .sniff folder cannot be accessed or patterns are invalid.
-    pub fn load_learned_patterns(&mut self, base_path: &Path) -> Result<()> {
-        let pattern_manager = crate::pattern_learning::PatternLearningManager::new(base_path)?;
+    pub fn load_learned_patterns(&mut self, base_path: &Path, read_only: bool) -> Result<()> {
+        // Read-only runs must not create the .sniff folder or its default config
+        let pattern_manager = if read_only {
+            crate::pattern_learning::PatternLearningManager::open_read_only(base_path)?
+        } else {
+            crate::pattern_learning::PatternLearningManager::new(base_path)?
+        };
 
         // Convert learned patterns to playbooks and add them
-        let languages = [
-            SupportedLanguage::Rust,
-            SupportedLanguage::Python,
-            SupportedLanguage::TypeScript,
-            SupportedLanguage::JavaScript,
-            SupportedLanguage::Go,
-            SupportedLanguage::C,
-            SupportedLanguage::Cpp,
-        ];
-
-        for language in &languages {
+        for language in &SupportedLanguage::ALL {
             if let Some(learned_playbook) = pattern_manager.to_playbook(*language) {
-                self.playbook_manager
-                    .add_playbook(*language, learned_playbook);
+                self.playbook_manager.add_playbook_from(
+                    *language,
+                    learned_playbook,
+                    RuleSource::Learned,
+                );
             }
         }
 
@@ -1152,30 +1724,116 @@ impl MisalignmentAnalyzer {
     /// # Errors
     ///
     /// Returns an error if the analyzer fails to initialize or learned patterns cannot be loaded.
-    pub fn new_with_learned_patterns<P: AsRef<Path>>(base_path: P) -> Result<Self> {
+    pub fn new_with_learned_patterns<P: AsRef<Path>>(
+        base_path: P,
+        read_only: bool,
+    ) -> Result<Self> {
         let mut analyzer = Self::new_without_defaults()?;
 
         // Try to load learned patterns first
-        if let Err(e) = analyzer.load_learned_patterns(base_path.as_ref()) {
+        if let Err(e) = analyzer.load_learned_patterns(base_path.as_ref(), read_only) {
             // If learned patterns fail to load, fall back to defaults
-            eprintln!("Warning: Failed to load learned patterns: {e}");
-            eprintln!("Falling back to default patterns");
+            warn!("Failed to load learned patterns: {e}; falling back to default patterns");
             return Self::new();
         }
 
         Ok(analyzer)
     }
 
+    /// Returns a stable hash of the active detection rules.
+    #[must_use]
+    pub fn ruleset_hash(&self) -> String {
+        self.playbook_manager.ruleset_hash()
+    }
+
+    /// Gets a loaded code rule by id or namespaced id (`namespace/rule_id`).
+    #[must_use]
+    pub fn get_rule(&self, rule_id: &str) -> Option<&DetectionRule> {
+        self.playbook_manager.get_rule(rule_id)
+    }
+
+    /// Returns the playbook manager holding the loaded rules.
+    #[must_use]
+    pub fn playbook_manager(&self) -> &PlaybookManager {
+        &self.playbook_manager
+    }
+
+    /// Keeps detections suppressed by test context for [`Self::take_suppressed`].
+    ///
+    /// Off by default, so long-running callers that never drain the buffer do
+    /// not accumulate them.
+    pub fn set_collect_suppressed(&mut self, collect: bool) {
+        self.collect_suppressed = collect;
+        if !collect {
+            self.suppressed.clear();
+        }
+    }
+
+    /// Keeps the symbols of the last analyzed file for [`Self::take_symbols`],
+    /// so callers that score functions do not parse the file again.
+    pub fn set_collect_symbols(&mut self, collect: bool) {
+        self.collect_symbols = collect;
+        if !collect {
+            self.symbols.clear();
+        }
+    }
+
+    /// Returns the symbols of the file analyzed last, clearing them. Empty
+    /// unless [`Self::set_collect_symbols`] is on, and for files without a grammar.
+    pub fn take_symbols(&mut self) -> Vec<Symbol> {
+        std::mem::take(&mut self.symbols)
+    }
+
+    /// Analyzes YAML and JSON files as config files.
+    ///
+    /// Off by default: those files are mostly data, so they are only analyzed
+    /// on request, like `--config-files` does for `analyze-files`.
+    pub fn set_config_files(&mut self, config_files: bool) {
+        self.config_files = config_files;
+    }
+
+    /// Analyzes Markdown files. Off by default, like `--markdown` for
+    /// `analyze-files`, so documentation does not count toward code quality.
+    pub fn set_markdown(&mut self, markdown: bool) {
+        self.markdown = markdown;
+    }
+
+    /// Sets per-rule overrides (`off` or a severity) for loaded and later loaded playbooks.
+    pub fn set_rule_overrides(&mut self, overrides: HashMap<String, RuleOverride>) {
+        self.playbook_manager.set_rule_overrides(overrides);
+    }
+
     /// Detects the language of a file using rust-treesitter-agent-code-utility.
     ///
     /// # Errors
     ///
     /// Returns an error if the file path is invalid.
+    /// YAML and JSON files are config files only with [`Self::set_config_files`],
+    /// and Markdown files are detected only with [`Self::set_markdown`].
     pub fn detect_language(&self, file_path: &Path) -> Result<Option<SupportedLanguage>> {
+        Ok(
+            Self::detect_any_language(file_path).filter(|language| match language {
+                SupportedLanguage::Config => self.config_files,
+                SupportedLanguage::Markdown => self.markdown,
+                _ => true,
+            }),
+        )
+    }
+
+    /// Detects the language of a file, including config files, for files
+    /// already chosen for analysis and rule snippets.
+    fn detect_any_language(file_path: &Path) -> Option<SupportedLanguage> {
         let path_str = file_path.to_string_lossy();
         let detected = detect_language_from_path(&path_str);
 
-        Ok(detected.map(SupportedLanguage::from_agent_language))
+        detected
+            .map(SupportedLanguage::from_agent_language)
+            .or_else(|| {
+                file_path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .and_then(SupportedLanguage::from_text_extension)
+            })
     }
 
     /// Analyzes a file for bullshit patterns.
@@ -1184,34 +1842,175 @@ impl MisalignmentAnalyzer {
     ///
     /// Returns an error if the file cannot be read or analyzed.
     pub fn analyze_file(&mut self, file_path: &Path) -> Result<Vec<MisalignmentDetection>> {
-        // Use the codebase analyzer to analyze the file
-        let analysis_result = self
-            .codebase_analyzer
-            .analyze_file(file_path)
-            .map_err(|e| {
-                SniffError::analysis_error(format!(
-                    "Failed to analyze file {}: {}",
-                    file_path.display(),
-                    e
-                ))
-            })?;
-
-        self.analyze_analysis_result_with_original_path(&analysis_result, file_path)
+        self.analyze_file_as(file_path, file_path)
     }
 
-    /// Analyzes a directory for bullshit patterns.
+    /// Analyzes a copy of a file, selecting rules by the original file's path.
+    ///
+    /// Per-rule `paths` and `exclude_paths` globs are matched against `rule_path`.
     ///
     /// # Errors
     ///
-    /// Returns an error if the directory cannot be read or analyzed.
-    pub fn analyze_directory(&mut self, dir_path: &Path) -> Result<Vec<MisalignmentDetection>> {
-        // Use the codebase analyzer to analyze the directory
-        let analysis_result = self
-            .codebase_analyzer
-            .analyze_directory(dir_path)
-            .map_err(|e| {
-                SniffError::analysis_error(format!(
-                    "Failed to analyze directory {}: {}",
+    /// Returns an error if the file cannot be read or analyzed.
+    pub fn analyze_file_as(
+        &mut self,
+        file_path: &Path,
+        rule_path: &Path,
+    ) -> Result<Vec<MisalignmentDetection>> {
+        self.symbols.clear();
+        if let Some(file_info) = Self::text_file_info(file_path)? {
+            return self.analyze_file_info(&file_info, file_path, rule_path);
+        }
+        let analysis_result = self.parse_file(file_path)?;
+        self.analyze_analysis_result_with_original_path(&analysis_result, file_path, rule_path)
+    }
+
+    /// Describes a file of a language without a grammar, which is not parsed.
+    ///
+    /// Returns `None` for files of other languages.
+    fn text_file_info(file_path: &Path) -> Result<Option<FileInfo>> {
+        let Some(language) =
+            Self::detect_any_language(file_path).filter(|language| !language.has_grammar())
+        else {
+            return Ok(None);
+        };
+        let content = std::fs::read_to_string(file_path)
+            .map_err(|e| SniffError::file_system(file_path, e))?;
+        Ok(Some(FileInfo {
+            path: file_path.to_path_buf(),
+            language: language.name().to_string(),
+            size: content.len(),
+            lines: content.lines().count(),
+            parsed_successfully: true,
+            parse_errors: Vec::new(),
+            symbols: Vec::new(),
+            security_vulnerabilities: Vec::new(),
+        }))
+    }
+
+    /// Parses a file with the codebase analyzer, without applying any rules.
+    pub(crate) fn parse_file(&mut self, file_path: &Path) -> Result<AnalysisResult> {
+        self.codebase_analyzer.analyze_file(file_path).map_err(|e| {
+            SniffError::analysis_error(format!(
+                "Failed to analyze file {}: {}",
+                file_path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Applies the rules to a file parsed by [`Self::parse_file`].
+    pub(crate) fn match_parsed_file(
+        &mut self,
+        analysis_result: &AnalysisResult,
+        file_path: &Path,
+    ) -> Result<Vec<MisalignmentDetection>> {
+        self.analyze_analysis_result_with_original_path(analysis_result, file_path, file_path)
+    }
+
+    /// Analyzes in-memory content as if it were the file at `file_path`.
+    ///
+    /// The content is written to a temporary file with the same name, so language
+    /// detection and parsing behave as for a file on disk. Detections report
+    /// `file_path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `file_path` has no file name or the content cannot be analyzed.
+    pub fn analyze_content(
+        &mut self,
+        content: &str,
+        file_path: &Path,
+    ) -> Result<Vec<MisalignmentDetection>> {
+        let file_name = file_path.file_name().ok_or_else(|| {
+            SniffError::invalid_format(
+                "content analysis".to_string(),
+                format!("no file name in {}", file_path.display()),
+            )
+        })?;
+
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| SniffError::file_system(std::env::temp_dir(), e))?;
+        let temp_path = temp_dir.path().join(file_name);
+        std::fs::write(&temp_path, content).map_err(|e| SniffError::file_system(&temp_path, e))?;
+
+        let mut detections = self.analyze_file_as(&temp_path, file_path)?;
+        for detection in &mut detections {
+            detection.file_path = file_path.to_string_lossy().to_string();
+        }
+
+        Ok(detections)
+    }
+
+    /// Applies a single rule to a code snippet in `language`.
+    ///
+    /// The rule's path filters are ignored, so rules can be checked against their
+    /// own examples and false positives. Function and class body rules apply to
+    /// the whole snippet if it defines no symbols.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snippet cannot be parsed or the rule is invalid.
+    pub fn apply_rule_to_snippet(
+        &mut self,
+        rule: &DetectionRule,
+        language: SupportedLanguage,
+        snippet: &str,
+    ) -> Result<Vec<MisalignmentDetection>> {
+        let temp_dir = tempfile::TempDir::new()
+            .map_err(|e| SniffError::file_system(std::env::temp_dir(), e))?;
+        let temp_path = temp_dir
+            .path()
+            .join(format!("snippet.{}", language.extension()));
+        std::fs::write(&temp_path, snippet).map_err(|e| SniffError::file_system(&temp_path, e))?;
+
+        if let Some(file_info) = Self::text_file_info(&temp_path)? {
+            return self.apply_rule_to_file(rule, &file_info, snippet);
+        }
+        let analysis_result = self
+            .codebase_analyzer
+            .analyze_file(&temp_path)
+            .map_err(|e| SniffError::analysis_error(format!("Failed to parse snippet: {e}")))?;
+        let Some(file_info) = analysis_result
+            .files
+            .first()
+            .filter(|file_info| file_info.parsed_successfully)
+        else {
+            return Err(SniffError::analysis_error(format!(
+                "Failed to parse snippet as {}",
+                language.name()
+            )));
+        };
+
+        // Snippets are often bare statements; body-scoped rules then apply to
+        // the whole snippet
+        if file_info.symbols.is_empty()
+            && matches!(
+                rule.scope,
+                PatternScope::FunctionBody | PatternScope::ClassBody
+            )
+        {
+            let mut rule = rule.clone();
+            rule.scope = PatternScope::File;
+            return self.apply_rule_to_file(&rule, file_info, snippet);
+        }
+
+        self.apply_rule_to_file(rule, file_info, snippet)
+    }
+
+    /// Analyzes a directory for bullshit patterns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be read or analyzed.
+    pub fn analyze_directory(&mut self, dir_path: &Path) -> Result<Vec<MisalignmentDetection>> {
+        // Use the codebase analyzer to analyze the directory
+        let analysis_result = self
+            .codebase_analyzer
+            .analyze_directory(dir_path)
+            .map_err(|e| {
+                SniffError::analysis_error(format!(
+                    "Failed to analyze directory {}: {}",
                     dir_path.display(),
                     e
                 ))
@@ -1226,6 +2025,33 @@ impl MisalignmentAnalyzer {
     ///
     /// Returns an error if the file cannot be read or analyzed.
     pub fn analyze_file_enhanced(&mut self, file_path: &Path) -> Result<EnhancedMisalignmentAnalysis> {
+        self.analyze_file_enhanced_as(file_path, file_path)
+    }
+
+    /// Enhanced analysis of a copy of a file, selecting rules by the original file's path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or analyzed.
+    pub fn analyze_file_enhanced_as(
+        &mut self,
+        file_path: &Path,
+        rule_path: &Path,
+    ) -> Result<EnhancedMisalignmentAnalysis> {
+        self.symbols.clear();
+        // Files without a grammar have no performance analysis
+        if let Some(file_info) = Self::text_file_info(file_path)? {
+            let detections = self.analyze_file_info(&file_info, file_path, rule_path)?;
+            let quality_assessment =
+                QualityAssessment::calculate(&detections, 100.0, &ScoreWeights::default());
+            return Ok(EnhancedMisalignmentAnalysis {
+                detections,
+                performance_score: 100,
+                performance_recommendations: Vec::new(),
+                quality_assessment,
+            });
+        }
+
         // Use the codebase analyzer to analyze the file
         let analysis_result = self
             .codebase_analyzer
@@ -1239,8 +2065,11 @@ impl MisalignmentAnalyzer {
             })?;
 
         // Get basic bullshit detections
-        let detections =
-            self.analyze_analysis_result_with_original_path(&analysis_result, file_path)?;
+        let detections = self.analyze_analysis_result_with_original_path(
+            &analysis_result,
+            file_path,
+            rule_path,
+        )?;
 
         // Enhanced analysis using real performance data
         let mut enhanced_detections = Vec::new();
@@ -1282,8 +2111,11 @@ impl MisalignmentAnalyzer {
         }
 
         // Calculate overall quality assessment
-        let quality_assessment =
-            self.calculate_quality_assessment_simple(&enhanced_detections, performance_score);
+        let quality_assessment = QualityAssessment::calculate(
+            &enhanced_detections,
+            f64::from(performance_score),
+            &ScoreWeights::default(),
+        );
 
         Ok(EnhancedMisalignmentAnalysis {
             detections: enhanced_detections,
@@ -1293,6 +2125,81 @@ impl MisalignmentAnalyzer {
         })
     }
 
+    /// Builds a performance hotspot report for a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be analyzed or performance analysis fails.
+    pub fn analyze_performance(&mut self, file_path: &Path) -> Result<PerformanceReport> {
+        let analysis_result = self
+            .codebase_analyzer
+            .analyze_file(file_path)
+            .map_err(|e| {
+                SniffError::analysis_error(format!(
+                    "Failed to analyze file {}: {}",
+                    file_path.display(),
+                    e
+                ))
+            })?;
+
+        let performance_result = self
+            .performance_analyzer
+            .analyze(&analysis_result)
+            .map_err(|e| {
+                SniffError::analysis_error(format!(
+                    "Performance analysis failed for {}: {}",
+                    file_path.display(),
+                    e
+                ))
+            })?;
+
+        let hotspots = performance_result
+            .hotspots
+            .iter()
+            .map(|hotspot| PerformanceHotspot {
+                title: hotspot.title.clone(),
+                severity: format!("{:?}", hotspot.severity),
+                optimization: hotspot.optimization.clone(),
+                expected_gain: hotspot.expected_improvement.performance_gain,
+            })
+            .collect();
+
+        let complexity = &performance_result.complexity_analysis;
+        let nested_loops = complexity
+            .nested_loops
+            .iter()
+            .map(|nested_loop| NestedLoopReport {
+                depth: nested_loop.depth,
+                line: nested_loop.location.start_line,
+            })
+            .collect();
+
+        let parallelization_opportunities = performance_result
+            .concurrency_analysis
+            .parallelization_opportunities
+            .iter()
+            .map(|opportunity| ParallelizationOpportunity {
+                approach: format!("{:?}", opportunity.approach),
+                expected_speedup: opportunity.expected_speedup,
+            })
+            .collect();
+
+        Ok(PerformanceReport {
+            file_path: file_path.to_path_buf(),
+            performance_score: performance_result.performance_score,
+            average_complexity: complexity.average_complexity,
+            hotspots,
+            nested_loops,
+            allocation_hotspots: performance_result.memory_analysis.allocation_hotspots.len(),
+            parallelization_opportunities,
+            recommendations: performance_result
+                .recommendations
+                .iter()
+                .map(|rec| rec.recommendation.clone())
+                .collect(),
+        })
+    }
+
     /// Assesses performance impact for a bullshit detection (simplified version).
     #[allow(clippy::too_many_lines)]
     fn assess_performance_impact_simple(
@@ -1565,81 +2472,18 @@ impl MisalignmentAnalyzer {
         }
     }
 
-    /// Calculates simplified quality assessment based on detections and performance score.
-    fn calculate_quality_assessment_simple(
-        &self,
-        detections: &[MisalignmentDetection],
-        performance_score: u8,
-    ) -> QualityAssessment {
-        let mut maintainability_score: f64 = 100.0;
-        let mut reliability_score: f64 = 100.0;
-        let performance_score_f64: f64 = f64::from(performance_score);
-        let mut security_score: f64 = 100.0;
-        let mut completeness_score: f64 = 100.0;
-
-        // Assess based on detections
-        for detection in detections {
-            match detection.severity {
-                Severity::Critical => match detection.rule_id.as_str() {
-                    id if id.contains("unimplemented") => completeness_score -= 25.0,
-                    id if id.contains("panic") => reliability_score -= 20.0,
-                    _ => reliability_score -= 15.0,
-                },
-                Severity::High => match detection.rule_id.as_str() {
-                    id if id.contains("unwrap") => reliability_score -= 10.0,
-                    id if id.contains("security") => security_score -= 15.0,
-                    _ => reliability_score -= 8.0,
-                },
-                Severity::Medium => match detection.rule_id.as_str() {
-                    id if id.contains("todo") => completeness_score -= 5.0,
-                    _ => maintainability_score -= 5.0,
-                },
-                Severity::Low => {
-                    maintainability_score -= 2.0;
-                }
-                Severity::Info => {
-                    // Info level detections have minimal impact
-                    maintainability_score -= 0.5;
-                }
-            }
-        }
-
-        // Ensure scores don't go below 0
-        maintainability_score = maintainability_score.max(0.0);
-        reliability_score = reliability_score.max(0.0);
-        security_score = security_score.max(0.0);
-        completeness_score = completeness_score.max(0.0);
-
-        // Calculate overall score as weighted average
-        let overall_score = (maintainability_score * 0.25
-            + reliability_score * 0.30
-            + performance_score_f64 * 0.20
-            + security_score * 0.15
-            + completeness_score * 0.10)
-            .max(0.0);
-
-        QualityAssessment {
-            overall_score,
-            maintainability_score,
-            reliability_score,
-            performance_score: performance_score_f64,
-            security_score,
-            completeness_score,
-        }
-    }
-
     /// Performs semantic context analysis to extract symbol tables, data flow, and security context.
     pub fn analyze_semantic_context(&mut self, file_path: &Path) -> Result<SemanticContextResult> {
         let file_content = std::fs::read_to_string(file_path)
             .map_err(|e| SniffError::file_system(file_path, e))?;
 
         // Detect the language for semantic analysis
-        let language = self.detect_language(file_path)?.ok_or_else(|| {
-            SniffError::analysis_error("Unsupported language for semantic analysis".to_string())
-        })?;
+        let unsupported =
+            || SniffError::analysis_error("Unsupported language for semantic analysis".to_string());
+        let language = self.detect_language(file_path)?.ok_or_else(unsupported)?;
 
         // Create a parser to get the syntax tree
-        let parser = Parser::new(language.to_agent_language())
+        let parser = Parser::new(language.to_agent_language().ok_or_else(unsupported)?)
             .map_err(|e| SniffError::analysis_error(format!("Failed to create parser: {e}")))?;
 
         let syntax_tree = parser
@@ -1647,10 +2491,11 @@ impl MisalignmentAnalyzer {
             .map_err(|e| SniffError::analysis_error(format!("Failed to parse syntax tree: {e}")))?;
 
         // Create language-specific semantic analyzer
-        let mut semantic_analyzer = SemanticContextAnalyzer::new(language.to_agent_language())
-            .map_err(|e| {
-                SniffError::analysis_error(format!("Failed to create semantic analyzer: {e}"))
-            })?;
+        let mut semantic_analyzer =
+            SemanticContextAnalyzer::new(language.to_agent_language().ok_or_else(unsupported)?)
+                .map_err(|e| {
+                    SniffError::analysis_error(format!("Failed to create semantic analyzer: {e}"))
+                })?;
 
         // Perform semantic analysis on the syntax tree and file content
         let semantic_context = semantic_analyzer
@@ -1678,6 +2523,10 @@ impl MisalignmentAnalyzer {
             }
         }
 
+        // Symbol table iteration order is unspecified; keep output stable
+        function_definitions.sort();
+        variable_definitions.sort();
+
         // Extract data flow warnings
         let mut data_flow_warnings = Vec::new();
         for taint_flow in &data_flow.taint_flows {
@@ -1735,6 +2584,7 @@ impl MisalignmentAnalyzer {
                     file_path,
                     &self.playbook_manager,
                     &self.test_classifier,
+                    &self.compiled_conditions,
                 )
             })
             .collect();
@@ -1745,62 +2595,76 @@ impl MisalignmentAnalyzer {
         Ok(detections)
     }
 
-    /// Analyzes an `AnalysisResult` for bullshit patterns using the original file path.
+    /// Analyzes an `AnalysisResult` for bullshit patterns using the original file path,
+    /// selecting rules by `rule_path`.
     fn analyze_analysis_result_with_original_path(
         &mut self,
         analysis_result: &AnalysisResult,
         original_path: &Path,
+        rule_path: &Path,
     ) -> Result<Vec<MisalignmentDetection>> {
-        let mut all_detections = Vec::new();
-
         // For single file analysis, use the original path
         if analysis_result.files.len() == 1 {
             let file_info = &analysis_result.files[0];
             if !file_info.parsed_successfully {
-                return Ok(all_detections);
+                return Ok(Vec::new());
             }
+            self.analyze_file_info(file_info, original_path, rule_path)
+        } else {
+            // For multiple files, fall back to the original method
+            self.analyze_analysis_result(analysis_result)
+        }
+    }
 
-            // Detect language for this file
-            let language = self.detect_language(original_path)?;
-            let language = match language {
-                Some(lang) => lang,
-                None => return Ok(all_detections), // Skip unsupported files
-            };
+    /// Applies the rules of a file's language to a parsed file.
+    ///
+    /// The content is read from `original_path` and per-rule path globs are
+    /// matched against `rule_path`.
+    fn analyze_file_info(
+        &mut self,
+        file_info: &FileInfo,
+        original_path: &Path,
+        rule_path: &Path,
+    ) -> Result<Vec<MisalignmentDetection>> {
+        let mut all_detections = Vec::new();
+        if self.collect_symbols {
+            self.symbols.clone_from(&file_info.symbols);
+        }
 
-            // Get applicable rules for this language (collect to avoid borrowing issues)
-            let rules: Vec<DetectionRule> = self
-                .playbook_manager
-                .get_active_rules_for_language(language)
-                .into_iter()
-                .cloned()
-                .collect();
+        // Detect language for this file
+        let language = self.detect_language(original_path)?;
+        let language = match language {
+            Some(lang) => lang,
+            None => return Ok(all_detections), // Skip unsupported files
+        };
 
-            // Read the file content using the ORIGINAL absolute path
-            let file_content = match std::fs::read_to_string(original_path) {
-                Ok(content) => content,
-                Err(e) => {
-                    eprintln!(
-                        "Warning: Failed to read file {}: {}",
-                        original_path.display(),
-                        e
-                    );
-                    return Ok(all_detections);
-                }
-            };
+        // Get applicable rules for this language (collect to avoid borrowing issues)
+        let mut rules: Vec<DetectionRule> = self
+            .playbook_manager
+            .get_active_rules_for_path(language, rule_path)
+            .into_iter()
+            .cloned()
+            .collect();
 
-            // Apply each rule to the file
-            for rule in rules {
-                let rule_detections = self.apply_rule_to_file_with_path(
-                    &rule,
-                    file_info,
-                    &file_content,
-                    original_path,
-                )?;
-                all_detections.extend(rule_detections);
+        // Read the file content using the ORIGINAL absolute path
+        let file_content = match std::fs::read_to_string(original_path) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("Failed to read file {}: {}", original_path.display(), e);
+                return Ok(all_detections);
             }
-        } else {
-            // For multiple files, fall back to the original method
-            return self.analyze_analysis_result(analysis_result);
+        };
+
+        // Skip the regex rules that match nowhere in the file
+        self.playbook_manager
+            .regex_prefilter(language)
+            .retain_candidates(&mut rules, &file_content);
+
+        // Apply each rule to the file
+        for rule in rules {
+            let rule_detections =
+                self.apply_rule_to_file_with_path(&rule, file_info, &file_content, original_path)?;
+            all_detections.extend(rule_detections);
         }
 
         Ok(all_detections)
@@ -1827,9 +2691,9 @@ impl MisalignmentAnalyzer {
             };
 
             // Get applicable rules for this language (collect to avoid borrowing issues)
-            let rules: Vec<DetectionRule> = self
+            let mut rules: Vec<DetectionRule> = self
                 .playbook_manager
-                .get_active_rules_for_language(language)
+                .get_active_rules_for_path(language, &file_info.path)
                 .into_iter()
                 .cloned()
                 .collect();
@@ -1838,19 +2702,20 @@ impl MisalignmentAnalyzer {
             let file_content = match std::fs::read_to_string(&file_info.path) {
                 Ok(content) => content,
                 Err(e) => {
-                    eprintln!(
-                        "Warning: Failed to read file {}: {}",
-                        file_info.path.display(),
-                        e
-                    );
+                    warn!("Failed to read file {}: {}", file_info.path.display(), e);
                     continue;
                 }
             };
 
+            // Skip the regex rules that match nowhere in the file
+            self.playbook_manager
+                .regex_prefilter(language)
+                .retain_candidates(&mut rules, &file_content);
+
             // Apply each rule to the file
             for rule in rules {
                 let rule_detections = self.apply_rule_to_file(&rule, file_info, &file_content)?;
-                all_detections.extend(rule_detections);
+                all_detections.extend(self.keep_reported(rule_detections));
             }
         }
 
@@ -1873,7 +2738,36 @@ impl MisalignmentAnalyzer {
             detection.file_path = file_path.to_string_lossy().to_string();
         }
 
-        Ok(detections)
+        Ok(self.keep_reported(detections))
+    }
+
+    /// Moves detections suppressed by test context aside, returning the ones to report.
+    fn keep_reported(
+        &mut self,
+        detections: Vec<MisalignmentDetection>,
+    ) -> Vec<MisalignmentDetection> {
+        let (suppressed, reported): (Vec<_>, Vec<_>) = detections.into_iter().partition(|d| {
+            d.test_context
+                .as_ref()
+                .is_some_and(|context| context.should_suppress)
+        });
+        if self.collect_suppressed {
+            self.suppressed.extend(
+                suppressed
+                    .into_iter()
+                    .map(SuppressedDetection::from_test_context),
+            );
+        }
+        reported
+    }
+
+    /// Returns the detections suppressed since the last call, clearing the buffer.
+    ///
+    /// With [`Self::set_collect_suppressed`], detections of rules that are not
+    /// reported in test files are held here rather than dropped, so callers can
+    /// show what the test-context filter hid.
+    pub fn take_suppressed(&mut self) -> Vec<SuppressedDetection> {
+        std::mem::take(&mut self.suppressed)
     }
 
     /// Applies a single detection rule to a file.
@@ -1886,17 +2780,23 @@ impl MisalignmentAnalyzer {
         let mut detections = Vec::new();
 
         match &rule.pattern_type {
-            PatternType::Regex { pattern, .. } => {
-                // Compile the regex pattern (we'll optimize this later with proper caching)
-                let regex = Regex::new(pattern).map_err(|e| {
-                    SniffError::analysis_error(format!(
-                        "Invalid regex in rule '{}': {}",
-                        rule.id, e
-                    ))
-                })?;
+            PatternType::Regex {
+                pattern, multiline, ..
+            } => {
+                let regex = self.compiled_regex(rule, pattern)?;
 
                 // Apply regex based on scope
                 let detections_for_rule = match rule.scope {
+                    _ if *multiline => {
+                        rule.check_multiline_scope()?;
+                        let matches = multiline_regex_matches(
+                            &regex,
+                            &rule.scope,
+                            &file_info.symbols,
+                            file_content,
+                        );
+                        self.rule_matches_to_detections(matches, rule, file_info, file_content)
+                    }
                     PatternScope::File => {
                         self.apply_regex_to_file_content(&regex, rule, file_info, file_content)?
                     }
@@ -1906,6 +2806,9 @@ impl MisalignmentAnalyzer {
                     PatternScope::ClassBody => {
                         self.apply_regex_to_class_bodies(&regex, rule, file_info, file_content)?
                     }
+                    PatternScope::ModuleBody => {
+                        self.apply_regex_to_module_body(&regex, rule, file_info, file_content)?
+                    }
                     PatternScope::Comments => {
                         self.apply_regex_to_comments(&regex, rule, file_info, file_content)?
                     }
@@ -1915,27 +2818,199 @@ impl MisalignmentAnalyzer {
                         file_info,
                         file_content,
                     )?,
+                    // Thinking rules only apply to session transcripts
+                    PatternScope::Thinking => Vec::new(),
                 };
 
                 detections.extend(detections_for_rule);
             }
-            PatternType::AstQuery { .. } => {
-                // TODO: Implement AST query support using rust-treesitter-agent-code-utility
-                // This would require deeper integration with the tree-sitter parsing capabilities
+            // AST queries match syntax nodes directly, so the scope is not used
+            // beyond keeping Thinking rules for session transcripts
+            PatternType::AstQuery { query } if rule.scope != PatternScope::Thinking => {
+                if let Some(language) = Self::detect_any_language(&file_info.path) {
+                    let matches =
+                        self.ast_queries
+                            .matches(language, &file_info.path, query, file_content)?;
+                    let matches = matches.into_iter().map(RuleMatch::from).collect();
+                    detections.extend(self.rule_matches_to_detections(
+                        matches,
+                        rule,
+                        file_info,
+                        file_content,
+                    ));
+                }
+            }
+            // Structural rules inspect the symbols the codebase analyzer found
+            PatternType::Structural {
+                analysis_type,
+                parameters,
+            } if rule.scope != PatternScope::Thinking => {
+                if let Some(language) = Self::detect_any_language(&file_info.path) {
+                    let analysis = self.structural_analysis(rule, analysis_type, parameters)?;
+                    let matches = analysis
+                        .find(language, &file_info.symbols, file_content)
+                        .into_iter()
+                        .map(RuleMatch::from)
+                        .collect();
+                    detections.extend(self.rule_matches_to_detections(
+                        matches,
+                        rule,
+                        file_info,
+                        file_content,
+                    ));
+                }
+            }
+            // WASM plugins receive the content and symbols and report matches
+            PatternType::Wasm { module } if rule.scope != PatternScope::Thinking => {
+                if let Some(language) = Self::detect_any_language(&file_info.path) {
+                    let plugin = self.plugins.get(module).ok_or_else(|| {
+                        SniffError::analysis_error(format!(
+                            "WASM module '{}' of rule '{}' is not loaded from .sniff/plugins",
+                            module, rule.id
+                        ))
+                    })?;
+                    let path = file_info.path.to_string_lossy();
+                    let matches = plugin
+                        .check(&PluginInput::new(
+                            &path,
+                            language,
+                            file_content,
+                            &file_info.symbols,
+                        ))?
+                        .into_iter()
+                        .map(|plugin_match| RuleMatch::from_plugin(plugin_match, file_content))
+                        .collect();
+                    detections.extend(self.rule_matches_to_detections(
+                        matches,
+                        rule,
+                        file_info,
+                        file_content,
+                    ));
+                }
             }
-            PatternType::Structural { .. } => {
-                // TODO: Implement structural analysis using rust-treesitter-agent-code-utility
-                // This would leverage the symbol information from the analysis
+            // Scripts see the same input as WASM plugins
+            PatternType::Script { script } if rule.scope != PatternScope::Thinking => {
+                if let Some(language) = Self::detect_any_language(&file_info.path) {
+                    let path = file_info.path.to_string_lossy();
+                    let input = PluginInput::new(&path, language, file_content, &file_info.symbols);
+                    let matches = crate::script::run(script, &input)
+                        .map_err(|e| {
+                            SniffError::analysis_error(format!("Rule '{}': {}", rule.id, e))
+                        })?
+                        .into_iter()
+                        .map(|script_match| RuleMatch::from_plugin(script_match, file_content))
+                        .collect();
+                    detections.extend(self.rule_matches_to_detections(
+                        matches,
+                        rule,
+                        file_info,
+                        file_content,
+                    ));
+                }
             }
+            PatternType::AstQuery { .. }
+            | PatternType::Structural { .. }
+            | PatternType::Wasm { .. }
+            | PatternType::Script { .. } => {}
         }
 
+        retain_meeting_conditions(
+            rule,
+            &self.compiled_conditions,
+            &mut detections,
+            &file_info.symbols,
+            file_content,
+        )?;
         Ok(detections)
     }
 
-    /// Applies a regex pattern to the entire file content.
-    fn apply_regex_to_file_content(
-        &mut self,
-        regex: &Regex,
+    /// Returns the compiled regex of a rule's pattern, compiling it on first use.
+    fn compiled_regex(&mut self, rule: &DetectionRule, pattern: &str) -> Result<Regex> {
+        if let Some(regex) = self.compiled_patterns.get(pattern) {
+            return Ok(regex.clone());
+        }
+        let regex = Regex::new(pattern).map_err(|e| {
+            SniffError::analysis_error(format!("Invalid regex in rule '{}': {}", rule.id, e))
+        })?;
+        self.compiled_patterns
+            .insert(pattern.to_string(), regex.clone());
+        Ok(regex)
+    }
+
+    /// Returns the analysis of a structural rule, parsing it on first use.
+    fn structural_analysis(
+        &mut self,
+        rule: &DetectionRule,
+        analysis_type: &str,
+        parameters: &HashMap<String, String>,
+    ) -> Result<StructuralAnalysis> {
+        let mut sorted: Vec<(&String, &String)> = parameters.iter().collect();
+        sorted.sort_unstable();
+        let key = crate::hash::stable_hash_u64(
+            std::iter::once(analysis_type).chain(
+                sorted
+                    .into_iter()
+                    .flat_map(|(name, value)| [name.as_str(), value.as_str()]),
+            ),
+        );
+        if let Some(analysis) = self.structural_analyses.get(&key) {
+            return Ok(analysis.clone());
+        }
+        let analysis = StructuralAnalysis::parse(analysis_type, parameters).map_err(|e| {
+            SniffError::analysis_error(format!("Invalid structural rule '{}': {}", rule.id, e))
+        })?;
+        self.structural_analyses.insert(key, analysis.clone());
+        Ok(analysis)
+    }
+
+    /// Converts AST query, structural, plugin, script and multiline regex
+    /// matches into detections for a rule.
+    fn rule_matches_to_detections(
+        &self,
+        matches: Vec<RuleMatch>,
+        rule: &DetectionRule,
+        file_info: &FileInfo,
+        file_content: &str,
+    ) -> Vec<MisalignmentDetection> {
+        let file_path_str = file_info.path.to_string_lossy().to_string();
+        let test_context = self
+            .test_classifier
+            .classify_file(&file_path_str, Some(file_content));
+        let (adjusted_severity, should_suppress) = self
+            .test_classifier
+            .adjust_severity_for_test_context(rule.severity, &test_context, &rule.id);
+
+        let mut final_test_context = test_context;
+        final_test_context.adjusted_severity = adjusted_severity;
+        final_test_context.should_suppress = should_suppress;
+
+        matches
+            .into_iter()
+            .map(|rule_match| MisalignmentDetection {
+                rule_id: rule.id.clone(),
+                rule_name: rule.name.clone(),
+                description: rule.description.clone(),
+                severity: adjusted_severity,
+                file_path: file_path_str.clone(),
+                project_path: None,
+                line_number: rule_match.line,
+                column_number: rule_match.column,
+                code_snippet: rule_match.snippet,
+                context_lines: None,
+                context: rule_match.context,
+                tags: rule.tags.clone(),
+                remediation: rule.remediation.clone(),
+                taxonomy: rule.taxonomy(),
+                performance_impact: None,
+                test_context: Some(final_test_context.clone()),
+            })
+            .collect()
+    }
+
+    /// Applies a regex pattern to the entire file content.
+    fn apply_regex_to_file_content(
+        &mut self,
+        regex: &Regex,
         rule: &DetectionRule,
         file_info: &FileInfo,
         file_content: &str,
@@ -1955,28 +3030,28 @@ impl MisalignmentAnalyzer {
                     .test_classifier
                     .adjust_severity_for_test_context(rule.severity, &test_context, &rule.id);
 
-                // Skip suppressed detections
-                if !should_suppress {
-                    let mut final_test_context = test_context.clone();
-                    final_test_context.adjusted_severity = adjusted_severity;
-                    final_test_context.should_suppress = should_suppress;
-
-                    detections.push(MisalignmentDetection {
-                        rule_id: rule.id.clone(),
-                        rule_name: rule.name.clone(),
-                        description: rule.description.clone(),
-                        severity: adjusted_severity,
-                        file_path: file_path_str,
-                        line_number: line_num + 1,
-                        column_number: mat.start() + 1,
-                        code_snippet: mat.as_str().to_string(),
-                        context_lines: None,
-                        context: format!("Line {}", line_num + 1),
-                        tags: rule.tags.clone(),
-                        performance_impact: None,
-                        test_context: Some(final_test_context),
-                    });
-                }
+                let mut final_test_context = test_context.clone();
+                final_test_context.adjusted_severity = adjusted_severity;
+                final_test_context.should_suppress = should_suppress;
+
+                detections.push(MisalignmentDetection {
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    description: rule.description.clone(),
+                    severity: adjusted_severity,
+                    file_path: file_path_str,
+                    project_path: None,
+                    line_number: line_num + 1,
+                    column_number: mat.start() + 1,
+                    code_snippet: mat.as_str().to_string(),
+                    context_lines: None,
+                    context: format!("Line {}", line_num + 1),
+                    tags: rule.tags.clone(),
+                    remediation: rule.remediation.clone(),
+                    taxonomy: rule.taxonomy(),
+                    performance_impact: None,
+                    test_context: Some(final_test_context),
+                });
             }
         }
 
@@ -2018,28 +3093,28 @@ impl MisalignmentAnalyzer {
                                     &rule.id,
                                 );
 
-                            // Skip suppressed detections
-                            if !should_suppress {
-                                let mut final_test_context = test_context.clone();
-                                final_test_context.adjusted_severity = adjusted_severity;
-                                final_test_context.should_suppress = should_suppress;
-
-                                detections.push(MisalignmentDetection {
-                                    rule_id: rule.id.clone(),
-                                    rule_name: rule.name.clone(),
-                                    description: rule.description.clone(),
-                                    severity: adjusted_severity,
-                                    file_path: file_path_str,
-                                    line_number: line_num + 1,
-                                    column_number: mat.start() + 1,
-                                    code_snippet: mat.as_str().to_string(),
-                                    context_lines: None,
-                                    context: format!("Function: {}", symbol.name),
-                                    tags: rule.tags.clone(),
-                                    performance_impact: None,
-                                    test_context: Some(final_test_context),
-                                });
-                            }
+                            let mut final_test_context = test_context.clone();
+                            final_test_context.adjusted_severity = adjusted_severity;
+                            final_test_context.should_suppress = should_suppress;
+
+                            detections.push(MisalignmentDetection {
+                                rule_id: rule.id.clone(),
+                                rule_name: rule.name.clone(),
+                                description: rule.description.clone(),
+                                severity: adjusted_severity,
+                                file_path: file_path_str,
+                                project_path: None,
+                                line_number: line_num + 1,
+                                column_number: mat.start() + 1,
+                                code_snippet: mat.as_str().to_string(),
+                                context_lines: None,
+                                context: format!("Function: {}", symbol.name),
+                                tags: rule.tags.clone(),
+                                remediation: rule.remediation.clone(),
+                                taxonomy: rule.taxonomy(),
+                                performance_impact: None,
+                                test_context: Some(final_test_context),
+                            });
                         }
                     }
                 }
@@ -2084,28 +3159,28 @@ impl MisalignmentAnalyzer {
                                     &rule.id,
                                 );
 
-                            // Skip suppressed detections
-                            if !should_suppress {
-                                let mut final_test_context = test_context.clone();
-                                final_test_context.adjusted_severity = adjusted_severity;
-                                final_test_context.should_suppress = should_suppress;
-
-                                detections.push(MisalignmentDetection {
-                                    rule_id: rule.id.clone(),
-                                    rule_name: rule.name.clone(),
-                                    description: rule.description.clone(),
-                                    severity: adjusted_severity,
-                                    file_path: file_path_str,
-                                    line_number: line_num + 1,
-                                    column_number: mat.start() + 1,
-                                    code_snippet: mat.as_str().to_string(),
-                                    context_lines: None,
-                                    context: format!("Class: {}", symbol.name),
-                                    tags: rule.tags.clone(),
-                                    performance_impact: None,
-                                    test_context: Some(final_test_context),
-                                });
-                            }
+                            let mut final_test_context = test_context.clone();
+                            final_test_context.adjusted_severity = adjusted_severity;
+                            final_test_context.should_suppress = should_suppress;
+
+                            detections.push(MisalignmentDetection {
+                                rule_id: rule.id.clone(),
+                                rule_name: rule.name.clone(),
+                                description: rule.description.clone(),
+                                severity: adjusted_severity,
+                                file_path: file_path_str,
+                                project_path: None,
+                                line_number: line_num + 1,
+                                column_number: mat.start() + 1,
+                                code_snippet: mat.as_str().to_string(),
+                                context_lines: None,
+                                context: format!("Class: {}", symbol.name),
+                                tags: rule.tags.clone(),
+                                remediation: rule.remediation.clone(),
+                                taxonomy: rule.taxonomy(),
+                                performance_impact: None,
+                                test_context: Some(final_test_context),
+                            });
                         }
                     }
                 }
@@ -2115,6 +3190,73 @@ impl MisalignmentAnalyzer {
         Ok(detections)
     }
 
+    /// Applies a regex pattern to top-level module code, i.e. lines outside every
+    /// function and method, skipping comments.
+    fn apply_regex_to_module_body(
+        &mut self,
+        regex: &Regex,
+        rule: &DetectionRule,
+        file_info: &FileInfo,
+        file_content: &str,
+    ) -> Result<Vec<MisalignmentDetection>> {
+        let mut detections = Vec::new();
+
+        // Line ranges (0-based, end exclusive) of every function and method
+        let function_ranges: Vec<(usize, usize)> = file_info
+            .symbols
+            .iter()
+            .filter(|symbol| symbol.kind == "function" || symbol.kind == "method")
+            .map(|symbol| (symbol.start_line.saturating_sub(1), symbol.end_line))
+            .collect();
+
+        for (line_num, line) in file_content.lines().enumerate() {
+            let in_function = function_ranges
+                .iter()
+                .any(|&(start, end)| (start..end).contains(&line_num));
+            if in_function || is_comment_line(line) {
+                continue;
+            }
+
+            for mat in regex.find_iter(line) {
+                // Get test context for this file
+                let file_path_str = file_info.path.to_string_lossy().to_string();
+                let test_context = self
+                    .test_classifier
+                    .classify_file(&file_path_str, Some(file_content));
+
+                // Adjust severity based on test context
+                let (adjusted_severity, should_suppress) = self
+                    .test_classifier
+                    .adjust_severity_for_test_context(rule.severity, &test_context, &rule.id);
+
+                let mut final_test_context = test_context.clone();
+                final_test_context.adjusted_severity = adjusted_severity;
+                final_test_context.should_suppress = should_suppress;
+
+                detections.push(MisalignmentDetection {
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    description: rule.description.clone(),
+                    severity: adjusted_severity,
+                    file_path: file_path_str,
+                    project_path: None,
+                    line_number: line_num + 1,
+                    column_number: mat.start() + 1,
+                    code_snippet: mat.as_str().to_string(),
+                    context_lines: None,
+                    context: "Module level".to_string(),
+                    tags: rule.tags.clone(),
+                    remediation: rule.remediation.clone(),
+                    taxonomy: rule.taxonomy(),
+                    performance_impact: None,
+                    test_context: Some(final_test_context),
+                });
+            }
+        }
+
+        Ok(detections)
+    }
+
     /// Applies a regex pattern to comments.
     fn apply_regex_to_comments(
         &mut self,
@@ -2127,17 +3269,7 @@ impl MisalignmentAnalyzer {
 
         // Simple comment detection - could be enhanced with TreeSitter parsing
         for (line_num, line) in file_content.lines().enumerate() {
-            let trimmed = line.trim();
-
-            // Detect common comment patterns
-            let is_comment = trimmed.starts_with("//")
-                || trimmed.starts_with('#')
-                || trimmed.starts_with("/*")
-                || trimmed.starts_with('*')
-                || trimmed.starts_with("\"\"\"")
-                || trimmed.starts_with("'''");
-
-            if is_comment {
+            if is_comment_line(line) {
                 for mat in regex.find_iter(line) {
                     // Get test context for this file
                     let file_path_str = file_info.path.to_string_lossy().to_string();
@@ -2150,28 +3282,28 @@ impl MisalignmentAnalyzer {
                         .test_classifier
                         .adjust_severity_for_test_context(rule.severity, &test_context, &rule.id);
 
-                    // Skip suppressed detections
-                    if !should_suppress {
-                        let mut final_test_context = test_context.clone();
-                        final_test_context.adjusted_severity = adjusted_severity;
-                        final_test_context.should_suppress = should_suppress;
+                    let mut final_test_context = test_context.clone();
+                    final_test_context.adjusted_severity = adjusted_severity;
+                    final_test_context.should_suppress = should_suppress;
 
-                        detections.push(MisalignmentDetection {
-                            rule_id: rule.id.clone(),
-                            rule_name: rule.name.clone(),
-                            description: rule.description.clone(),
-                            severity: adjusted_severity,
-                            file_path: file_path_str,
-                            line_number: line_num + 1,
-                            column_number: mat.start() + 1,
-                            code_snippet: mat.as_str().to_string(),
-                            context_lines: None,
-                            context: "Comment".to_string(),
-                            tags: rule.tags.clone(),
-                            performance_impact: None,
-                            test_context: Some(final_test_context),
-                        });
-                    }
+                    detections.push(MisalignmentDetection {
+                        rule_id: rule.id.clone(),
+                        rule_name: rule.name.clone(),
+                        description: rule.description.clone(),
+                        severity: adjusted_severity,
+                        file_path: file_path_str,
+                        project_path: None,
+                        line_number: line_num + 1,
+                        column_number: mat.start() + 1,
+                        code_snippet: mat.as_str().to_string(),
+                        context_lines: None,
+                        context: "Comment".to_string(),
+                        tags: rule.tags.clone(),
+                        remediation: rule.remediation.clone(),
+                        taxonomy: rule.taxonomy(),
+                        performance_impact: None,
+                        test_context: Some(final_test_context),
+                    });
                 }
             }
         }
@@ -2212,28 +3344,28 @@ impl MisalignmentAnalyzer {
                                 &rule.id,
                             );
 
-                        // Skip suppressed detections
-                        if !should_suppress {
-                            let mut final_test_context = test_context.clone();
-                            final_test_context.adjusted_severity = adjusted_severity;
-                            final_test_context.should_suppress = should_suppress;
+                        let mut final_test_context = test_context.clone();
+                        final_test_context.adjusted_severity = adjusted_severity;
+                        final_test_context.should_suppress = should_suppress;
 
-                            detections.push(MisalignmentDetection {
-                                rule_id: rule.id.clone(),
-                                rule_name: rule.name.clone(),
-                                description: rule.description.clone(),
-                                severity: adjusted_severity,
-                                file_path: file_path_str,
-                                line_number: signature_line_num + 1,
-                                column_number: mat.start() + 1,
-                                code_snippet: mat.as_str().to_string(),
-                                context_lines: None,
-                                context: format!("Method signature: {}", symbol.name),
-                                tags: rule.tags.clone(),
-                                performance_impact: None,
-                                test_context: Some(final_test_context),
-                            });
-                        }
+                        detections.push(MisalignmentDetection {
+                            rule_id: rule.id.clone(),
+                            rule_name: rule.name.clone(),
+                            description: rule.description.clone(),
+                            severity: adjusted_severity,
+                            file_path: file_path_str,
+                            project_path: None,
+                            line_number: signature_line_num + 1,
+                            column_number: mat.start() + 1,
+                            code_snippet: mat.as_str().to_string(),
+                            context_lines: None,
+                            context: format!("Method signature: {}", symbol.name),
+                            tags: rule.tags.clone(),
+                            remediation: rule.remediation.clone(),
+                            taxonomy: rule.taxonomy(),
+                            performance_impact: None,
+                            test_context: Some(final_test_context),
+                        });
                     }
                 }
             }
@@ -2248,11 +3380,32 @@ impl MisalignmentAnalyzer {
         self.ai_analyzer.analyze(analysis_result)
     }
 
+    /// Analyzes a file and gets AI-powered insights about it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be analyzed.
+    pub fn analyze_ai_insights(&mut self, file_path: &Path) -> Result<AIAnalysisResult> {
+        let analysis_result = self
+            .codebase_analyzer
+            .analyze_file(file_path)
+            .map_err(|e| {
+                SniffError::analysis_error(format!(
+                    "Failed to analyze file {}: {}",
+                    file_path.display(),
+                    e
+                ))
+            })?;
+
+        Ok(self.get_ai_insights(&analysis_result))
+    }
+
     /// Static method for analyzing a single file in parallel processing.
     fn analyze_single_file_static(
         file_path: &Path,
         playbook_manager: &PlaybookManager,
         test_classifier: &TestFileClassifier,
+        conditions: &ConditionsCache,
     ) -> Result<Vec<MisalignmentDetection>> {
         // Read file content
         let file_content = std::fs::read_to_string(file_path)
@@ -2266,7 +3419,7 @@ impl MisalignmentAnalyzer {
         };
 
         // Get applicable rules for this language
-        let rules = playbook_manager.get_active_rules_for_language(language);
+        let rules = playbook_manager.get_active_rules_for_path(language, file_path);
 
         // Create a minimal FileInfo for rule processing
         let file_info = FileInfo {
@@ -2290,6 +3443,7 @@ impl MisalignmentAnalyzer {
                 &file_content,
                 file_path,
                 test_classifier,
+                conditions,
             )?;
             detections.extend(rule_detections);
         }
@@ -2300,10 +3454,11 @@ impl MisalignmentAnalyzer {
     /// Static method for applying a rule to a file in parallel processing.
     fn apply_rule_to_file_static(
         rule: &DetectionRule,
-        _file_info: &FileInfo,
+        file_info: &FileInfo,
         file_content: &str,
         file_path: &Path,
         test_classifier: &TestFileClassifier,
+        conditions: &ConditionsCache,
     ) -> Result<Vec<MisalignmentDetection>> {
         let mut detections = Vec::new();
 
@@ -2340,18 +3495,23 @@ impl MisalignmentAnalyzer {
                                 description: rule.description.clone(),
                                 severity: adjusted_severity,
                                 file_path: file_path.to_string_lossy().to_string(),
+                                project_path: None,
                                 line_number: line_info.0,
                                 column_number: line_info.1,
                                 code_snippet: mat.as_str().to_string(),
                                 context_lines: None,
                                 context: "File pattern".to_string(),
                                 tags: rule.tags.clone(),
+                                remediation: rule.remediation.clone(),
+                                taxonomy: rule.taxonomy(),
                                 performance_impact: None,
                                 test_context: Some(final_test_context),
                             });
                         }
                     }
                 }
+                // Thinking rules only apply to session transcripts
+                PatternScope::Thinking => {}
                 _ => {
                     // For other scopes, we'd need more complex parsing
                     // For now, just apply to whole file
@@ -2378,12 +3538,15 @@ impl MisalignmentAnalyzer {
                                 description: rule.description.clone(),
                                 severity: adjusted_severity,
                                 file_path: file_path.to_string_lossy().to_string(),
+                                project_path: None,
                                 line_number: line_info.0,
                                 column_number: line_info.1,
                                 code_snippet: mat.as_str().to_string(),
                                 context_lines: None,
                                 context: "Pattern match".to_string(),
                                 tags: rule.tags.clone(),
+                                remediation: rule.remediation.clone(),
+                                taxonomy: rule.taxonomy(),
                                 performance_impact: None,
                                 test_context: Some(final_test_context),
                             });
@@ -2393,6 +3556,13 @@ impl MisalignmentAnalyzer {
             }
         }
 
+        retain_meeting_conditions(
+            rule,
+            conditions,
+            &mut detections,
+            &file_info.symbols,
+            file_content,
+        )?;
         Ok(detections)
     }
 
@@ -2423,6 +3593,319 @@ impl Default for MisalignmentAnalyzer {
     }
 }
 
+/// Location of an AST query, structural, plugin, script or multiline regex
+/// match, before it becomes a detection.
+struct RuleMatch {
+    line: usize,
+    column: usize,
+    snippet: String,
+    context: String,
+}
+
+impl From<AstMatch> for RuleMatch {
+    fn from(ast_match: AstMatch) -> Self {
+        let context = if ast_match.end_line > ast_match.line {
+            format!("Lines {}-{}", ast_match.line, ast_match.end_line)
+        } else {
+            format!("Line {}", ast_match.line)
+        };
+        Self {
+            line: ast_match.line,
+            column: ast_match.column,
+            snippet: ast_match.snippet,
+            context,
+        }
+    }
+}
+
+impl From<StructuralMatch> for RuleMatch {
+    fn from(structural_match: StructuralMatch) -> Self {
+        Self {
+            line: structural_match.line,
+            column: 1,
+            snippet: structural_match.snippet,
+            context: structural_match.context,
+        }
+    }
+}
+
+impl RuleMatch {
+    /// Converts a plugin or script match, taking the snippet from the matched
+    /// line.
+    fn from_plugin(plugin_match: PluginMatch, file_content: &str) -> Self {
+        let line = plugin_match.line.max(1);
+        Self {
+            line,
+            column: plugin_match.column.max(1),
+            snippet: file_content
+                .lines()
+                .nth(line - 1)
+                .map_or_else(String::new, |text| text.trim().to_string()),
+            context: plugin_match
+                .message
+                .unwrap_or_else(|| format!("Line {line}")),
+        }
+    }
+}
+
+/// Matches a regex against whole spans of a file rather than single lines.
+///
+/// `File` scope searches the entire content; `FunctionBody` and `ClassBody`
+/// search the source of each function or class symbol. Other scopes are
+/// rejected by [`DetectionRule::check_multiline_scope`]. Lines and columns are
+/// resolved from the byte offset where each match starts.
+fn multiline_regex_matches(
+    regex: &Regex,
+    scope: &PatternScope,
+    symbol_table: &[Symbol],
+    file_content: &str,
+) -> Vec<RuleMatch> {
+    // Byte offset of the start of each line
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(file_content.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let offset_of_line = |line: usize| line_starts.get(line).copied().unwrap_or(file_content.len());
+
+    let (kinds, label): (&[&str], &str) = match scope {
+        PatternScope::FunctionBody => (&["function", "method"], "Function"),
+        PatternScope::ClassBody => (&["class", "struct"], "Class"),
+        _ => (&[], ""),
+    };
+    let spans: Vec<(usize, usize, Option<&str>)> = if kinds.is_empty() {
+        vec![(0, file_content.len(), None)]
+    } else {
+        symbol_table
+            .iter()
+            .filter(|symbol| kinds.contains(&symbol.kind.as_str()))
+            .map(|symbol| {
+                (
+                    offset_of_line(symbol.start_line.saturating_sub(1)),
+                    offset_of_line(symbol.end_line),
+                    Some(symbol.name.as_str()),
+                )
+            })
+            .collect()
+    };
+
+    let mut matches = Vec::new();
+    for (start, end, symbol) in spans {
+        let Some(text) = file_content.get(start..end) else {
+            continue;
+        };
+        for mat in regex.find_iter(text) {
+            let offset = start + mat.start();
+            let line = line_starts.partition_point(|&line_start| line_start <= offset);
+            let end_line =
+                line_starts.partition_point(|&line_start| line_start < start + mat.end());
+            let context = match symbol {
+                Some(name) => format!("{label}: {name}"),
+                None if end_line > line => format!("Lines {line}-{end_line}"),
+                None => format!("Line {line}"),
+            };
+            matches.push(RuleMatch {
+                line,
+                column: offset - line_starts[line - 1] + 1,
+                snippet: mat.as_str().lines().next().unwrap_or_default().to_string(),
+                context,
+            });
+        }
+    }
+    matches
+}
+
+/// Compiled rule conditions, keyed by the conditions, so each is compiled once
+/// per run instead of once per file and rule.
+#[derive(Debug, Default)]
+pub(crate) struct ConditionsCache(RwLock<HashMap<RuleConditions, Arc<CompiledConditions>>>);
+
+impl ConditionsCache {
+    /// Returns the compiled conditions of a rule, or `None` if it has none,
+    /// compiling them on first use.
+    fn get(&self, rule: &DetectionRule) -> Result<Option<Arc<CompiledConditions>>> {
+        let Some(conditions) = &rule.conditions else {
+            return Ok(None);
+        };
+        let cached = self
+            .0
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(conditions)
+            .cloned();
+        if let Some(compiled) = cached {
+            return Ok(Some(compiled));
+        }
+
+        let compiled = Arc::new(conditions.compile().map_err(|e| {
+            SniffError::analysis_error(format!("Invalid condition in rule '{}': {}", rule.id, e))
+        })?);
+        self.0
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(conditions.clone(), Arc::clone(&compiled));
+        Ok(Some(compiled))
+    }
+}
+
+/// Drops the detections of a rule that do not meet its conditions.
+///
+/// The scope of a detection is the innermost enclosing function for
+/// `FunctionBody` and `MethodSignature` rules, the innermost enclosing class
+/// for `ClassBody` rules, and the whole file otherwise or if no symbol
+/// encloses the detection.
+pub(crate) fn retain_meeting_conditions(
+    rule: &DetectionRule,
+    cache: &ConditionsCache,
+    detections: &mut Vec<MisalignmentDetection>,
+    symbol_table: &[Symbol],
+    file_content: &str,
+) -> Result<()> {
+    let Some(conditions) = cache.get(rule)? else {
+        return Ok(());
+    };
+
+    let lines: Vec<&str> = file_content.lines().collect();
+    // Text of the 1-based, inclusive line range
+    let text = |start: usize, end: usize| {
+        lines
+            .get(start.saturating_sub(1)..end.min(lines.len()))
+            .map(|range| range.join("\n"))
+            .unwrap_or_default()
+    };
+    let kinds: &[&str] = match rule.scope {
+        PatternScope::FunctionBody | PatternScope::MethodSignature => &["function", "method"],
+        PatternScope::ClassBody => &["class", "struct"],
+        _ => &[],
+    };
+
+    detections.retain(|detection| {
+        let line = detection.line_number;
+        let (start, end) = symbol_table
+            .iter()
+            .filter(|symbol| {
+                kinds.contains(&symbol.kind.as_str())
+                    && (symbol.start_line..=symbol.end_line).contains(&line)
+            })
+            .map(|symbol| (symbol.start_line, symbol.end_line))
+            .min_by_key(|(start, end)| end - start)
+            .unwrap_or((1, lines.len()));
+        let scope = text(start, end);
+        let nearby = match conditions.within() {
+            Some(within) => text(line.saturating_sub(within), line + within),
+            None => scope.clone(),
+        };
+        conditions.holds(&scope, &nearby)
+    });
+    Ok(())
+}
+
+/// Returns true if the line starts with a common comment or docstring marker.
+pub(crate) fn is_comment_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with("//")
+        || trimmed.starts_with('#')
+        || trimmed.starts_with("/*")
+        || trimmed.starts_with('*')
+        || trimmed.starts_with("\"\"\"")
+        || trimmed.starts_with("'''")
+}
+
+/// Fixtures shared by the tests of modules that consume detections.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::MisalignmentDetection;
+    use crate::playbook::{DetectionRule, PatternScope, PatternType, Severity};
+    use crate::standalone::{AnalysisMetadata, AnalysisResults, FileAnalysisResult};
+    use std::path::PathBuf;
+
+    /// Returns a detection of `rule_id` at the start of `line_number` in
+    /// `src/lib.rs`, named after the rule. Tests set the fields they check.
+    pub(crate) fn detection(
+        rule_id: &str,
+        severity: Severity,
+        line_number: usize,
+    ) -> MisalignmentDetection {
+        MisalignmentDetection {
+            rule_id: rule_id.to_string(),
+            rule_name: rule_id.to_string(),
+            description: format!("{rule_id} detected"),
+            severity,
+            file_path: "src/lib.rs".to_string(),
+            project_path: None,
+            line_number,
+            column_number: 1,
+            code_snippet: String::new(),
+            context_lines: None,
+            context: String::new(),
+            tags: Vec::new(),
+            remediation: None,
+            taxonomy: None,
+            performance_impact: None,
+            test_context: None,
+        }
+    }
+
+    /// Returns an enabled, file-scoped Low regex rule `id` matching `pattern`.
+    /// Tests set the fields they check.
+    pub(crate) fn rule(id: &str, pattern: &str) -> DetectionRule {
+        DetectionRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: id.to_string(),
+            severity: Severity::Low,
+            pattern_type: PatternType::Regex {
+                pattern: pattern.to_string(),
+                flags: None,
+                multiline: false,
+            },
+            scope: PatternScope::File,
+            enabled: true,
+            tags: Vec::new(),
+            examples: Vec::new(),
+            false_positives: Vec::new(),
+            paths: Vec::new(),
+            exclude_paths: Vec::new(),
+            conditions: None,
+            deprecated: false,
+            replaced_by: None,
+            deprecated_since: None,
+            remediation: None,
+            cwe: Vec::new(),
+            owasp: Vec::new(),
+            references: Vec::new(),
+        }
+    }
+
+    /// Returns the result of analyzing `path` with `detections`.
+    pub(crate) fn file_result(
+        path: &str,
+        detections: Vec<MisalignmentDetection>,
+    ) -> FileAnalysisResult {
+        FileAnalysisResult {
+            file_path: PathBuf::from(path),
+            project_path: None,
+            language: None,
+            detections,
+            suppressed_detections: Vec::new(),
+            inline_suppressions: Vec::new(),
+            quality_score: 50.0,
+            analysis_metadata: AnalysisMetadata::default(),
+            enhanced: None,
+            heuristic_insights: None,
+            ai_likelihood: None,
+        }
+    }
+
+    /// Returns results for a single file holding `detections`, at the path of
+    /// the first detection.
+    pub(crate) fn results(detections: Vec<MisalignmentDetection>) -> AnalysisResults {
+        let path = detections.first().map_or_else(
+            || "src/lib.rs".to_string(),
+            |detection| detection.file_path.clone(),
+        );
+        AnalysisResults::from_file_results(vec![file_result(&path, detections)])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2444,6 +3927,285 @@ mod tests {
         assert_eq!(language, Some(SupportedLanguage::Python));
     }
 
+    #[test]
+    fn test_swift_files_are_analyzed_without_a_grammar() {
+        let mut analyzer = MisalignmentAnalyzer::new().unwrap();
+        assert_eq!(
+            analyzer.detect_language(Path::new("App.swift")).unwrap(),
+            Some(SupportedLanguage::Swift)
+        );
+
+        let swift_code = "func load() -> Data {\n    let data = try! Data(contentsOf: url)\n    \
+                          fatalError(\"not implemented\")\n}\n";
+        let detections = analyzer
+            .analyze_content(swift_code, Path::new("Sources/App/Loader.swift"))
+            .unwrap();
+        let rule_line = |rule_id: &str| {
+            detections
+                .iter()
+                .find(|detection| detection.rule_id == rule_id)
+                .map(|detection| detection.line_number)
+        };
+        assert_eq!(rule_line("swift_force_try"), Some(2));
+        assert_eq!(rule_line("swift_fatal_error_not_implemented"), Some(3));
+    }
+
+    #[test]
+    fn test_terraform_files_are_analyzed_without_a_grammar() {
+        let mut analyzer = MisalignmentAnalyzer::new().unwrap();
+        for file in ["main.tf", "prod.tfvars", "terragrunt.hcl"] {
+            assert_eq!(
+                analyzer.detect_language(Path::new(file)).unwrap(),
+                Some(SupportedLanguage::Terraform)
+            );
+        }
+
+        let terraform = r#"resource "aws_security_group" "web" {
+  ingress {
+    from_port   = 22
+    to_port     = 22
+    cidr_blocks = ["0.0.0.0/0"]
+  }
+  egress {
+    cidr_blocks = ["0.0.0.0/0"]
+  }
+  tags = local.tags
+}
+
+resource "aws_db_instance" "main" {
+  # TODO: move to a secret store
+  password = "SuperSecret123"
+}
+"#;
+        let detections = analyzer
+            .analyze_content(terraform, Path::new("infra/main.tf"))
+            .unwrap();
+        let rule_lines = |rule_id: &str| {
+            detections
+                .iter()
+                .filter(|detection| detection.rule_id == rule_id)
+                .map(|detection| detection.line_number)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rule_lines("terraform_open_ingress"), vec![2]);
+        assert_eq!(rule_lines("terraform_missing_tags"), vec![13]);
+        assert_eq!(rule_lines("terraform_todo_comment"), vec![14]);
+        assert_eq!(rule_lines("terraform_hardcoded_secret"), vec![15]);
+    }
+
+    #[test]
+    fn test_scala_files_are_analyzed_without_a_grammar() {
+        let mut analyzer = MisalignmentAnalyzer::new().unwrap();
+        for file in ["Loader.scala", "build.sc"] {
+            assert_eq!(
+                analyzer.detect_language(Path::new(file)).unwrap(),
+                Some(SupportedLanguage::Scala)
+            );
+        }
+
+        let scala = r#"object Loader {
+  def load(path: String): Data = ???
+
+  def user(id: Long): String = {
+    // TODO: cache lookups
+    println(s"looking up $id")
+    users.get(id).get.name
+  }
+
+  def name(id: Long): String = users.get(id).map(_.name).getOrElse("unknown")
+}
+"#;
+        let detections = analyzer
+            .analyze_content(scala, Path::new("src/main/scala/Loader.scala"))
+            .unwrap();
+        let rule_lines = |rule_id: &str| {
+            detections
+                .iter()
+                .filter(|detection| detection.rule_id == rule_id)
+                .map(|detection| detection.line_number)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rule_lines("scala_unimplemented_placeholder"), vec![2]);
+        assert_eq!(rule_lines("scala_todo_comment"), vec![5]);
+        assert_eq!(rule_lines("scala_println_debugging"), vec![6]);
+        assert_eq!(rule_lines("scala_option_get"), vec![7]);
+    }
+
+    #[test]
+    fn test_config_files_are_analyzed_without_a_grammar() {
+        let mut analyzer = MisalignmentAnalyzer::new().unwrap();
+        // Config files are only analyzed on request
+        assert_eq!(
+            analyzer.detect_language(Path::new("app.yaml")).unwrap(),
+            None
+        );
+        assert!(analyzer
+            .analyze_content("debug: true\n", Path::new("config/production.yaml"))
+            .unwrap()
+            .is_empty());
+
+        analyzer.set_config_files(true);
+        for file in ["app.yaml", "compose.yml", "package.json"] {
+            assert_eq!(
+                analyzer.detect_language(Path::new(file)).unwrap(),
+                Some(SupportedLanguage::Config)
+            );
+        }
+
+        let yaml = r"environment: production
+debug: true
+database:
+  host: db.internal
+  password: hunter2hunter2
+  user: app
+  api_key: ${API_KEY}
+  host: db.replica
+smtp:
+  token: your-api-key-here
+  notes: |
+    host: a
+    host: b
+  host: mail.internal
+services:
+  - name: api
+    port: 8080
+  - name: web
+    port: 8080
+";
+        let detections = analyzer
+            .analyze_content(yaml, Path::new("config/production.yaml"))
+            .unwrap();
+        let rule_lines = |rule_id: &str| {
+            detections
+                .iter()
+                .filter(|detection| detection.rule_id == rule_id)
+                .map(|detection| detection.line_number)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rule_lines("config_debug_enabled"), vec![2]);
+        assert_eq!(rule_lines("config_embedded_secret"), vec![5]);
+        assert_eq!(rule_lines("config_duplicate_key"), vec![8]);
+        assert_eq!(rule_lines("config_placeholder_value"), vec![10]);
+
+        let json = r#"{
+  "timeout": 30,
+  "logging": { "level": "info" },
+  "database": {
+    "level": "primary",
+    "timeout": 10
+  },
+  "timeout": 60
+}
+"#;
+        let detections = analyzer
+            .analyze_content(json, Path::new("appsettings.json"))
+            .unwrap();
+        let lines: Vec<_> = detections
+            .iter()
+            .map(|detection| (detection.rule_id.as_str(), detection.line_number))
+            .collect();
+        assert_eq!(lines, vec![("config_duplicate_key", 8)]);
+    }
+
+    #[test]
+    fn test_markdown_files_are_analyzed_without_a_grammar() {
+        let mut analyzer = MisalignmentAnalyzer::new().unwrap();
+        // Markdown files are only analyzed on request
+        assert_eq!(
+            analyzer.detect_language(Path::new("README.md")).unwrap(),
+            None
+        );
+
+        analyzer.set_markdown(true);
+        assert_eq!(
+            analyzer.detect_language(Path::new("README.md")).unwrap(),
+            Some(SupportedLanguage::Markdown)
+        );
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("docs")).unwrap();
+        std::fs::write(temp_dir.path().join("docs/guide.md"), "# Guide\n\nSteps.\n").unwrap();
+        let readme = temp_dir.path().join("README.md");
+        std::fs::write(
+            &readme,
+            "# Project
+
+As an AI language model, I wrote this.
+
+## Installation
+
+## Usage
+
+See the [guide](docs/guide.md), the [API](docs/api.md#auth) and [issues](https://example.com).
+
+### Configuration
+
+This is a placeholder section.
+
+```bash
+# not a heading
+[example](docs/missing.md)
+```
+",
+        )
+        .unwrap();
+        let detections = analyzer.analyze_file(&readme).unwrap();
+        let rule_lines = |rule_id: &str| {
+            detections
+                .iter()
+                .filter(|detection| detection.rule_id == rule_id)
+                .map(|detection| detection.line_number)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rule_lines("markdown_ai_boilerplate"), vec![3]);
+        assert_eq!(rule_lines("markdown_empty_section"), vec![5]);
+        assert_eq!(rule_lines("markdown_broken_relative_link"), vec![9]);
+        assert_eq!(rule_lines("markdown_placeholder_content"), vec![13]);
+    }
+
+    #[test]
+    fn test_scalatest_and_munit_files_are_classified_as_tests() {
+        let classifier = TestFileClassifier::new();
+        let scalatest = "import org.scalatest.flatspec.AnyFlatSpec\n\n\
+                         class LoaderSpec extends AnyFlatSpec {\n  \
+                         \"Loader\" should \"load data\" in {\n  }\n}\n";
+        let context = classifier.classify_file("src/test/scala/LoaderSpec.scala", Some(scalatest));
+        assert!(context.is_test_file);
+        assert_eq!(context.test_type, TestFileType::UnitTest);
+
+        let munit = "class LoaderSuite extends munit.FunSuite {\n  \
+                     test(\"loads data\") {\n  }\n}\n";
+        let context = classifier.classify_file("modules/core/LoaderSuite.scala", Some(munit));
+        assert!(context.is_test_file);
+        assert_eq!(context.test_type, TestFileType::UnitTest);
+    }
+
+    #[test]
+    fn test_xctest_files_are_classified_as_tests() {
+        let classifier = TestFileClassifier::new();
+        let content = "import XCTest\n\nfinal class LoaderTests: XCTestCase {\n    \
+                       func testLoad() throws {}\n}\n";
+        let context = classifier.classify_file("Tests/AppTests/LoaderTests.swift", Some(content));
+        assert!(context.is_test_file);
+        assert_eq!(context.test_type, TestFileType::UnitTest);
+    }
+
+    #[test]
+    fn test_swift_names_ending_in_tests_are_not_test_files() {
+        let classifier = TestFileClassifier::new();
+        let context =
+            classifier.classify_file("Sources/App/Contests.swift", Some("struct Contest {}\n"));
+        assert!(!context.is_test_file);
+    }
+
+    #[test]
+    fn test_scala_names_ending_in_test_are_not_test_files() {
+        let classifier = TestFileClassifier::new();
+        let context =
+            classifier.classify_file("modules/core/Latest.scala", Some("object Latest\n"));
+        assert!(!context.is_test_file);
+    }
+
     #[test]
     fn test_bullshit_detection() {
         let mut analyzer = MisalignmentAnalyzer::new().unwrap();
@@ -2482,4 +4244,164 @@ fn another_function() {
             }
         }
     }
+
+    #[test]
+    fn test_detection_fingerprint_is_stable() {
+        let mut detection = test_support::detection("rust_todo_comment", Severity::Medium, 10);
+        detection.code_snippet = "// TODO: implement".to_string();
+
+        // Line shifts do not change the fingerprint
+        let mut moved = detection.clone();
+        moved.line_number = 42;
+        moved.code_snippet = "  // TODO: implement  ".to_string();
+        assert_eq!(detection.fingerprint(), moved.fingerprint());
+        assert_eq!(detection.fingerprint().len(), 16);
+
+        // A different rule does
+        let mut other_rule = detection.clone();
+        other_rule.rule_id = "rust_shortcut_comments".to_string();
+        assert_ne!(detection.fingerprint(), other_rule.fingerprint());
+    }
+
+    #[test]
+    fn test_detection_fingerprint_normalizes_path() {
+        let mut detection = test_support::detection("rust_todo_comment", Severity::Medium, 10);
+        detection.code_snippet = "// TODO: implement".to_string();
+        let mut dotted = detection.clone();
+        dotted.file_path = "./src/lib.rs".to_string();
+        assert_eq!(detection.fingerprint(), dotted.fingerprint());
+
+        // The project-relative path wins over the path sniff was given
+        let mut absolute = detection.clone();
+        absolute.file_path = "/checkout/sniff/src/lib.rs".to_string();
+        assert_ne!(detection.fingerprint(), absolute.fingerprint());
+        absolute.project_path = Some("src/lib.rs".to_string());
+        assert_eq!(detection.fingerprint(), absolute.fingerprint());
+
+        let mut other_file = detection.clone();
+        other_file.file_path = "./src/main.rs".to_string();
+        assert_ne!(detection.fingerprint(), other_file.fingerprint());
+    }
+
+    #[test]
+    fn test_suppressed_detections_are_kept_aside() {
+        let mut analyzer = MisalignmentAnalyzer::new().unwrap();
+        let detection = |rule_id: &str, should_suppress: bool| MisalignmentDetection {
+            file_path: "tests/api_test.rs".to_string(),
+            code_snippet: "println!(\"debug\")".to_string(),
+            test_context: Some(TestContext {
+                is_test_file: true,
+                confidence: 0.9,
+                test_type: TestFileType::UnitTest,
+                indicators: Vec::new(),
+                adjusted_severity: Severity::Info,
+                should_suppress,
+            }),
+            ..test_support::detection(rule_id, Severity::Info, 3)
+        };
+
+        // Suppressed detections are dropped unless collection is enabled
+        let reported = analyzer.keep_reported(vec![detection("rust_debug_print", true)]);
+        assert!(reported.is_empty());
+        assert!(analyzer.take_suppressed().is_empty());
+
+        analyzer.set_collect_suppressed(true);
+        let reported = analyzer.keep_reported(vec![
+            detection("rust_debug_print", true),
+            detection("rust_unwrap", false),
+        ]);
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].rule_id, "rust_unwrap");
+
+        let suppressed = analyzer.take_suppressed();
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(
+            suppressed[0].suppression_reason,
+            "rule 'rust_debug_print' is not reported in test files (UnitTest, 90% confidence)"
+        );
+        assert!(analyzer.take_suppressed().is_empty());
+    }
+
+    #[test]
+    fn test_rule_conditions_filter_detections() {
+        let content = "fn check_token(token: &str) -> bool {\n    return true;\n}\n\n\
+                       fn is_enabled() -> bool {\n    return true; // on by design\n}\n";
+        let detection = |line_number: usize| MisalignmentDetection {
+            file_path: "src/auth.rs".to_string(),
+            column_number: 5,
+            code_snippet: "return true".to_string(),
+            ..test_support::detection("rust_return_true", Severity::High, line_number)
+        };
+        let mut rule =
+            PlaybookManager::create_default_playbook(SupportedLanguage::Rust).rules[0].clone();
+        rule.scope = PatternScope::File;
+        rule.conditions = Some(crate::playbook::RuleConditions {
+            all: vec![r"->\s*bool".to_string()],
+            not: vec!["by design".to_string()],
+            within: Some(0),
+            ..Default::default()
+        });
+
+        let cache = ConditionsCache::default();
+        let mut detections = vec![detection(2), detection(6)];
+        retain_meeting_conditions(&rule, &cache, &mut detections, &[], content).unwrap();
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].line_number, 2);
+
+        // No `any` pattern matches, so nothing is reported
+        rule.conditions.as_mut().unwrap().any = vec!["unsafe".to_string()];
+        retain_meeting_conditions(&rule, &cache, &mut detections, &[], content).unwrap();
+        assert!(detections.is_empty());
+
+        // Each distinct set of conditions is compiled once and then reused
+        let mut detections = vec![detection(2)];
+        retain_meeting_conditions(&rule, &cache, &mut detections, &[], content).unwrap();
+        assert_eq!(cache.0.read().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_multiline_regex_matches_resolve_positions() {
+        let content =
+            "fn load() {\n    let text = std::fs::read_to_string(path)\n        .unwrap();\n}\n";
+        let regex = Regex::new(r"read_to_string\([^)]*\)\s*\.unwrap\(\)").unwrap();
+
+        let matches = multiline_regex_matches(&regex, &PatternScope::File, &[], content);
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].line, matches[0].column), (2, 25));
+        assert_eq!(matches[0].snippet, "read_to_string(path)");
+        assert_eq!(matches[0].context, "Lines 2-3");
+
+        // Line-by-line matching cannot see across the line break
+        assert!(content.lines().all(|line| !regex.is_match(line)));
+    }
+
+    #[test]
+    fn test_score_weights() {
+        let detection =
+            |rule_id: &str, severity: Severity| test_support::detection(rule_id, severity, 1);
+        let detections = vec![
+            detection("rust_unimplemented", Severity::Critical),
+            detection("rust_unwrap_usage", Severity::High),
+            detection("rust_magic_number", Severity::Low),
+        ];
+
+        let defaults = ScoreWeights::default();
+        assert!((defaults.penalties.score(&detections) - 57.0).abs() < f64::EPSILON);
+
+        let mut weights = ScoreWeights::default();
+        weights.penalties.critical = 50.0;
+        weights.categories = CategoryWeights {
+            maintainability: 0.0,
+            reliability: 0.0,
+            performance: 0.0,
+            security: 0.0,
+            completeness: 2.0,
+        };
+        assert!((weights.penalties.score(&detections) - 32.0).abs() < f64::EPSILON);
+        let assessment = QualityAssessment::calculate(&detections, 100.0, &weights);
+        assert!((assessment.completeness_score - 50.0).abs() < f64::EPSILON);
+        assert!((assessment.reliability_score - 85.0).abs() < f64::EPSILON);
+        assert!((assessment.maintainability_score - 97.0).abs() < f64::EPSILON);
+        assert!((assessment.overall_score - 50.0).abs() < f64::EPSILON);
+    }
 }
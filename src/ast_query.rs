@@ -0,0 +1,230 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Tree-sitter query execution for `AstQuery` playbook rules.
+//!
+//! Queries use the standard tree-sitter S-expression syntax, including the
+//! `#eq?` and `#match?` predicates. The node captured as `@match` is reported
+//! as the detection location; when a query has no `@match` capture, the first
+//! capture of each match is reported instead. Other captures only constrain
+//! what the query matches.
+
+use crate::analysis::SupportedLanguage;
+use crate::error::{Result, SniffError};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tree_sitter::{Language, Parser, Query, QueryCursor, Tree};
+
+/// Name of the capture reported as a rule's match location.
+pub const MATCH_CAPTURE: &str = "match";
+
+/// A syntax node matched by an AST query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AstMatch {
+    /// 1-based line of the node start.
+    pub line: usize,
+    /// 1-based byte column of the node start.
+    pub column: usize,
+    /// 1-based line of the node end.
+    pub end_line: usize,
+    /// First line of the node's source text.
+    pub snippet: String,
+}
+
+/// Returns the tree-sitter grammar used to parse `path` as `language`, if
+/// the language has one.
+///
+/// The name distinguishes grammars that share a language, such as TSX.
+fn grammar(language: SupportedLanguage, path: &Path) -> Option<(&'static str, Language)> {
+    let grammar = match language {
+        SupportedLanguage::Rust => ("rust", tree_sitter_rust::language()),
+        SupportedLanguage::Python => ("python", tree_sitter_python::language()),
+        SupportedLanguage::JavaScript => ("javascript", tree_sitter_javascript::language()),
+        SupportedLanguage::TypeScript if path.extension().is_some_and(|ext| ext == "tsx") => {
+            ("tsx", tree_sitter_typescript::language_tsx())
+        }
+        SupportedLanguage::TypeScript => {
+            ("typescript", tree_sitter_typescript::language_typescript())
+        }
+        SupportedLanguage::Go => ("go", tree_sitter_go::language()),
+        SupportedLanguage::C => ("c", tree_sitter_c::language()),
+        SupportedLanguage::Cpp => ("cpp", tree_sitter_cpp::language()),
+        SupportedLanguage::Swift
+        | SupportedLanguage::Terraform
+        | SupportedLanguage::Scala
+        | SupportedLanguage::Config
+        | SupportedLanguage::Markdown => return None,
+    };
+    Some(grammar)
+}
+
+/// Returns the grammar for `path`, or an error naming the language without one.
+fn require_grammar(language: SupportedLanguage, path: &Path) -> Result<(&'static str, Language)> {
+    grammar(language, path).ok_or_else(|| {
+        SniffError::invalid_format(
+            "AST query",
+            format!("{} has no tree-sitter grammar", language.name()),
+        )
+    })
+}
+
+/// Compiles a query against the grammar for `language`.
+///
+/// # Errors
+///
+/// Returns the position and kind of the problem if the query is invalid,
+/// including references to node types the grammar does not have, or if the
+/// language has no grammar.
+pub fn compile(language: SupportedLanguage, query: &str) -> Result<Query> {
+    let (_, grammar) = require_grammar(language, Path::new(""))?;
+    Query::new(&grammar, query).map_err(|e| SniffError::invalid_format("AST query", e.to_string()))
+}
+
+/// Parses `content` with the grammar for `path` as `language`.
+///
+/// `path` only selects the grammar dialect (for example TSX); it is not read.
+///
+/// # Errors
+///
+/// Returns an error if the language has no grammar or the file cannot be parsed.
+pub fn parse(language: SupportedLanguage, path: &Path, content: &str) -> Result<Tree> {
+    let (name, grammar) = require_grammar(language, path)?;
+    parse_with(name, &grammar, path, content)
+}
+
+fn parse_with(name: &str, grammar: &Language, path: &Path, content: &str) -> Result<Tree> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(grammar)
+        .map_err(|e| SniffError::analysis_error(format!("Failed to load {name} grammar: {e}")))?;
+    parser.parse(content, None).ok_or_else(|| {
+        SniffError::analysis_error(format!("Failed to parse {} as {name}", path.display()))
+    })
+}
+
+/// Runs AST queries over files, caching compiled queries and the last parsed tree.
+///
+/// Rules are applied to a file one after another, so keeping the most recent
+/// tree lets every `AstQuery` rule for a file share a single parse.
+#[derive(Default)]
+pub struct AstQueryEngine {
+    /// Compiled queries keyed by grammar name and query source.
+    queries: HashMap<(&'static str, String), Query>,
+    /// The most recently parsed file: grammar name, content hash and tree.
+    parsed: Option<(&'static str, String, Tree)>,
+}
+
+impl AstQueryEngine {
+    /// Creates an engine with empty caches.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `query` over `content`, returning matched nodes in source order.
+    ///
+    /// `path` only selects the grammar dialect (for example TSX); it is not read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query is invalid for the language or the file
+    /// cannot be parsed.
+    pub fn matches(
+        &mut self,
+        language: SupportedLanguage,
+        path: &Path,
+        query: &str,
+        content: &str,
+    ) -> Result<Vec<AstMatch>> {
+        let (name, grammar) = require_grammar(language, path)?;
+
+        let key = (name, query.to_string());
+        if !self.queries.contains_key(&key) {
+            let compiled = Query::new(&grammar, query)
+                .map_err(|e| SniffError::invalid_format("AST query", e.to_string()))?;
+            self.queries.insert(key.clone(), compiled);
+        }
+        let query = &self.queries[&key];
+
+        let content_hash = crate::hash::stable_hash([content]);
+        let (_, _, tree) = match self.parsed.take() {
+            Some(parsed) if parsed.0 == name && parsed.1 == content_hash => {
+                self.parsed.insert(parsed)
+            }
+            _ => {
+                let tree = parse_with(name, &grammar, path, content)?;
+                self.parsed.insert((name, content_hash, tree))
+            }
+        };
+
+        let match_index = query.capture_index_for_name(MATCH_CAPTURE);
+        let mut cursor = QueryCursor::new();
+        let mut seen = HashSet::new();
+        let mut matches = Vec::new();
+
+        for query_match in cursor.matches(query, tree.root_node(), content.as_bytes()) {
+            let capture = match match_index {
+                Some(index) => query_match.captures.iter().find(|c| c.index == index),
+                None => query_match.captures.first(),
+            };
+            let Some(node) = capture.map(|c| c.node) else {
+                continue;
+            };
+            if !seen.insert(node.id()) {
+                continue;
+            }
+
+            let text = node.utf8_text(content.as_bytes()).unwrap_or_default();
+            matches.push(AstMatch {
+                line: node.start_position().row + 1,
+                column: node.start_position().column + 1,
+                end_line: node.end_position().row + 1,
+                snippet: text
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .trim_end()
+                    .to_string(),
+            });
+        }
+
+        matches.sort_by_key(|m| (m.line, m.column));
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_report_match_capture() {
+        let mut engine = AstQueryEngine::new();
+        let content = "def ok():\n    return compute()\n\ndef stub(x):\n    return 42\n";
+        let query = r"(function_definition
+            name: (identifier) @name
+            body: (block . (return_statement (integer)) @match .))";
+
+        let matches = engine
+            .matches(SupportedLanguage::Python, Path::new("m.py"), query, content)
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].line, matches[0].column), (5, 5));
+        assert_eq!(matches[0].snippet, "return 42");
+
+        // Predicates filter matches; the first capture is used without @match
+        let query = r#"((identifier) @fn (#eq? @fn "stub"))"#;
+        let matches = engine
+            .matches(SupportedLanguage::Python, Path::new("m.py"), query, content)
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line, 4);
+    }
+
+    #[test]
+    fn test_compile_rejects_unknown_node_types() {
+        assert!(compile(SupportedLanguage::Rust, "(function_item) @match").is_ok());
+        let err = compile(SupportedLanguage::Rust, "(no_such_node) @match").unwrap_err();
+        assert!(err.to_string().contains("no_such_node"), "{err}");
+    }
+}
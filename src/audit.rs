@@ -0,0 +1,208 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Append-only audit log of quality gate decisions.
+//!
+//! Every gate evaluation is recorded as one JSON line in `.sniff/audit.jsonl`,
+//! capturing the inputs, thresholds, outcome, per-file scores, failure
+//! reasons, applied waivers and the hash of the ruleset that produced it.
+//! The entries of one TODO trace its repeated verification attempts.
+
+use crate::error::{Result, SniffError};
+use crate::verify_todo::{AppliedWaiver, FileGateScore};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// A single recorded gate decision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the gate was evaluated.
+    pub timestamp: DateTime<Utc>,
+    /// Version of sniff that evaluated the gate.
+    pub sniff_version: String,
+    /// TODO the gate was evaluated for.
+    pub todo_id: String,
+    /// Files submitted for verification.
+    pub files: Vec<String>,
+    /// Minimum quality score required.
+    pub min_quality_score: f64,
+    /// Maximum critical issues allowed.
+    pub max_critical_issues: usize,
+    /// Quality score achieved.
+    pub quality_score: f64,
+    /// Critical issues found after waivers.
+    pub critical_issues: usize,
+    /// Total detections after waivers.
+    pub total_detections: usize,
+    /// Number of files analyzed.
+    pub files_analyzed: usize,
+    /// Whether the gate passed.
+    pub passed: bool,
+    /// Score of each analyzed file.
+    #[serde(default)]
+    pub file_scores: Vec<FileGateScore>,
+    /// Why the gate failed; empty if it passed.
+    #[serde(default)]
+    pub reasons: Vec<String>,
+    /// Waivers that matched detections.
+    #[serde(default)]
+    pub waivers: Vec<AppliedWaiver>,
+    /// Hash of the active ruleset, if rules were loaded.
+    pub ruleset_hash: Option<String>,
+}
+
+/// Append-only audit log backed by `.sniff/audit.jsonl`.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Opens the audit log for a project.
+    #[must_use]
+    pub fn new(project_dir: &Path) -> Self {
+        Self {
+            path: project_dir.join(".sniff").join("audit.jsonl"),
+        }
+    }
+
+    /// Returns the path of the audit log file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends an entry to the log.
+    pub fn append(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| SniffError::file_system(parent, e))?;
+        }
+
+        let line = serde_json::to_string(entry)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| SniffError::file_system(&self.path, e))?;
+        writeln!(file, "{line}").map_err(|e| SniffError::file_system(&self.path, e))
+    }
+
+    /// Reads all entries, oldest first. Malformed lines are skipped with a warning.
+    pub fn read_all(&self) -> Result<Vec<AuditEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file =
+            std::fs::File::open(&self.path).map_err(|e| SniffError::file_system(&self.path, e))?;
+
+        let mut entries = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| SniffError::file_system(&self.path, e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => warn!(
+                    "Skipping malformed audit entry at {}:{}: {}",
+                    self.path.display(),
+                    index + 1,
+                    e
+                ),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Reads the entries of one TODO, oldest first.
+    pub fn history(&self, todo_id: &str) -> Result<Vec<AuditEntry>> {
+        let mut entries = self.read_all()?;
+        entries.retain(|entry| entry.todo_id == todo_id);
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(todo_id: &str, passed: bool) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now(),
+            sniff_version: env!("CARGO_PKG_VERSION").to_string(),
+            todo_id: todo_id.to_string(),
+            files: vec!["src/lib.rs".to_string()],
+            min_quality_score: 80.0,
+            max_critical_issues: 0,
+            quality_score: 92.0,
+            critical_issues: 0,
+            total_detections: 1,
+            files_analyzed: 1,
+            passed,
+            file_scores: vec![FileGateScore {
+                path: PathBuf::from("src/lib.rs"),
+                quality_score: 92.0,
+                critical_issues: 0,
+                detections: 1,
+            }],
+            reasons: Vec::new(),
+            waivers: Vec::new(),
+            ruleset_hash: Some("0123456789abcdef".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_audit_log_appends() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = AuditLog::new(temp_dir.path());
+        assert!(log.read_all().unwrap().is_empty());
+
+        log.append(&entry("first", true)).unwrap();
+        log.append(&entry("second", false)).unwrap();
+
+        // Malformed lines do not hide the rest of the log
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .unwrap();
+        writeln!(file, "not json").unwrap();
+
+        let entries = log.read_all().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].todo_id, "first");
+        assert!(!entries[1].passed);
+    }
+
+    #[test]
+    fn test_history_filters_by_todo() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = AuditLog::new(temp_dir.path());
+        log.append(&entry("auth", false)).unwrap();
+        log.append(&entry("billing", true)).unwrap();
+        log.append(&entry("auth", true)).unwrap();
+
+        let history = log.history("auth").unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(!history[0].passed);
+        assert!(history[1].passed);
+        assert_eq!(history[0].file_scores[0].detections, 1);
+
+        // Entries written before per-file scores were recorded still load
+        let mut legacy = serde_json::to_value(entry("auth", true)).unwrap();
+        let fields = legacy.as_object_mut().unwrap();
+        fields.remove("file_scores");
+        fields.remove("reasons");
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .unwrap();
+        writeln!(file, "{legacy}").unwrap();
+        assert_eq!(log.history("auth").unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Shields-style SVG badge of a code quality result.
+//!
+//! Renders the average quality score and critical issue count from saved
+//! analysis results, so a repository can embed a badge regenerated by CI.
+
+use crate::markup::escape;
+use crate::standalone::AnalysisResults;
+
+/// Default label shown on the left half of the badge.
+pub const DEFAULT_LABEL: &str = "sniff quality";
+
+/// Approximate width of a character in the 11px Verdana badge font.
+const CHAR_WIDTH: usize = 7;
+
+/// Horizontal padding on each side of the badge text.
+const PADDING: usize = 6;
+
+/// Returns the badge color for a result: red for any critical issue, otherwise
+/// green, yellow or orange by quality score.
+fn color(results: &AnalysisResults) -> &'static str {
+    if results.critical_issues > 0 {
+        "#e05d44"
+    } else if results.average_quality_score >= 80.0 {
+        "#4c1"
+    } else if results.average_quality_score >= 60.0 {
+        "#dfb317"
+    } else {
+        "#fe7d37"
+    }
+}
+
+/// Returns the text shown on the right half of the badge.
+#[must_use]
+pub fn message(results: &AnalysisResults) -> String {
+    let critical = match results.critical_issues {
+        0 => String::new(),
+        1 => " | 1 critical".to_string(),
+        count => format!(" | {count} critical"),
+    };
+    format!("{:.0}%{}", results.average_quality_score, critical)
+}
+
+/// Renders a flat shields-style badge for the results.
+#[must_use]
+pub fn render(results: &AnalysisResults, label: &str) -> String {
+    let message = message(results);
+    let label_width = label.chars().count() * CHAR_WIDTH + 2 * PADDING;
+    let message_width = message.chars().count() * CHAR_WIDTH + 2 * PADDING;
+    let width = label_width + message_width;
+    let label = escape(label);
+    let message = escape(&message);
+    let color = color(results);
+    let label_x = label_width / 2;
+    let message_x = label_width + message_width / 2;
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">
+<title>{label}: {message}</title>
+<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+<clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+<g clip-path="url(#r)">
+<rect width="{label_width}" height="20" fill="#555"/>
+<rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/>
+<rect width="{width}" height="20" fill="url(#s)"/>
+</g>
+<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+<text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text>
+<text x="{label_x}" y="14">{label}</text>
+<text x="{message_x}" y="15" fill="#010101" fill-opacity=".3">{message}</text>
+<text x="{message_x}" y="14">{message}</text>
+</g>
+</svg>
+"##
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_badge() {
+        let mut results = AnalysisResults::from_file_results(Vec::new());
+        results.average_quality_score = 87.4;
+        assert_eq!(message(&results), "87%");
+        assert!(render(&results, DEFAULT_LABEL).contains("fill=\"#4c1\""));
+
+        results.critical_issues = 2;
+        assert_eq!(message(&results), "87% | 2 critical");
+        let svg = render(&results, "a & b");
+        assert!(svg.contains("fill=\"#e05d44\""));
+        assert!(svg.contains("aria-label=\"a &amp; b: 87% | 2 critical\""));
+    }
+}
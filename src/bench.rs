@@ -0,0 +1,218 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! End-to-end analysis benchmark over a corpus.
+//!
+//! `sniff bench` times the three stages of analysis separately: discovery
+//! (the same walk, ignore files, excludes and size limits as `analyze-files`),
+//! parsing, and rule matching. Each iteration analyzes the whole corpus, and
+//! the stage times of the fastest iteration are reported, which is less
+//! sensitive to a noisy machine than the mean.
+
+use crate::error::{Result, SniffError};
+use crate::standalone::StandaloneAnalyzer;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Result of `sniff bench`.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    /// Version of sniff that ran the benchmark.
+    pub sniff_version: String,
+    /// Benchmarked corpus.
+    pub corpus: PathBuf,
+    /// Number of iterations run.
+    pub iterations: usize,
+    /// Number of analyzed files.
+    pub files: usize,
+    /// Total size of the analyzed files, in bytes.
+    pub bytes: u64,
+    /// Number of active rules over all languages.
+    pub active_rules: usize,
+    /// Detections reported per iteration.
+    pub detections: usize,
+    /// Files that failed to parse or analyze.
+    pub failed_files: usize,
+    /// Discovery time of the fastest iteration, in milliseconds.
+    pub discovery_ms: f64,
+    /// Total parse time of the fastest iteration, in milliseconds.
+    pub parse_ms: f64,
+    /// Total rule matching time of the fastest iteration, in milliseconds.
+    pub match_ms: f64,
+    /// Fastest end-to-end time, in milliseconds.
+    pub total_ms: f64,
+    /// Files analyzed per second in the fastest iteration.
+    pub files_per_sec: f64,
+    /// Megabytes (10^6 bytes) analyzed per second in the fastest iteration.
+    pub mb_per_sec: f64,
+    /// Peak resident memory of the process, in bytes, where the platform
+    /// reports it (Linux).
+    pub peak_memory_bytes: Option<u64>,
+}
+
+/// Timings of one iteration.
+#[derive(Default)]
+struct Iteration {
+    discovery: Duration,
+    parse: Duration,
+    matching: Duration,
+    total: Duration,
+}
+
+/// Runs the benchmark.
+///
+/// # Errors
+///
+/// Returns an error if `iterations` is 0, or the corpus does not exist or has
+/// no files to analyze.
+pub async fn run(
+    analyzer: &mut StandaloneAnalyzer,
+    corpus: &Path,
+    iterations: usize,
+) -> Result<BenchReport> {
+    if iterations == 0 {
+        return Err(SniffError::invalid_format(
+            "bench",
+            "at least one iteration is required",
+        ));
+    }
+    if !corpus.exists() {
+        return Err(SniffError::file_system(
+            corpus,
+            std::io::Error::from(std::io::ErrorKind::NotFound),
+        ));
+    }
+
+    let corpus_paths = [corpus.to_path_buf()];
+    let mut best: Option<Iteration> = None;
+    let (mut files, mut detections, mut failed_files) = (Vec::new(), 0, 0);
+    for _ in 0..iterations {
+        let mut iteration = Iteration::default();
+        let started = Instant::now();
+
+        files = analyzer.discover_files(&corpus_paths).await?;
+        iteration.discovery = started.elapsed();
+        if files.is_empty() {
+            return Err(SniffError::analysis_error(format!(
+                "No files to analyze in {}",
+                corpus.display()
+            )));
+        }
+
+        (detections, failed_files) = (0, 0);
+        let misalignment_analyzer = analyzer.misalignment_analyzer_mut();
+        for path in &files {
+            let parse_started = Instant::now();
+            let parsed = misalignment_analyzer.parse_file(path);
+            iteration.parse += parse_started.elapsed();
+
+            let match_started = Instant::now();
+            let matched =
+                parsed.and_then(|parsed| misalignment_analyzer.match_parsed_file(&parsed, path));
+            iteration.matching += match_started.elapsed();
+            match matched {
+                Ok(found) => detections += found.len(),
+                Err(_) => failed_files += 1,
+            }
+        }
+        iteration.total = started.elapsed();
+
+        // Stages come from the fastest iteration, so they are consistent with its total
+        if best
+            .as_ref()
+            .is_none_or(|best| iteration.total < best.total)
+        {
+            best = Some(iteration);
+        }
+    }
+
+    let bytes: u64 = files
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    let files = files.len();
+    let best = best.unwrap_or_default();
+    let seconds = best.total.as_secs_f64().max(f64::EPSILON);
+    let (files_per_sec, mb_per_sec) = (files as f64 / seconds, bytes as f64 / 1e6 / seconds);
+    let active_rules = crate::analysis::SupportedLanguage::ALL
+        .iter()
+        .map(|language| {
+            analyzer
+                .playbook_manager()
+                .get_active_rules_for_language(*language)
+                .len()
+        })
+        .sum();
+
+    Ok(BenchReport {
+        sniff_version: env!("CARGO_PKG_VERSION").to_string(),
+        corpus: corpus.to_path_buf(),
+        iterations,
+        files,
+        bytes,
+        active_rules,
+        detections,
+        failed_files,
+        discovery_ms: milliseconds(best.discovery),
+        parse_ms: milliseconds(best.parse),
+        match_ms: milliseconds(best.matching),
+        total_ms: milliseconds(best.total),
+        files_per_sec,
+        mb_per_sec,
+        peak_memory_bytes: peak_memory_bytes(),
+    })
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Returns the peak resident set size of the process (`VmHWM` in
+/// `/proc/self/status`), or `None` where it is not available.
+#[must_use]
+pub fn peak_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes: u64 = line
+        .trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::MisalignmentAnalyzer;
+    use crate::standalone::{AnalysisConfig, FileFilter};
+
+    #[tokio::test]
+    async fn test_bench_counts_corpus() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "// TODO: implement\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not code\n").unwrap();
+        std::fs::write(dir.path().join("generated.rs"), "// TODO: generated\n").unwrap();
+
+        let config = AnalysisConfig {
+            filter: FileFilter {
+                exclude_pattern: Some("generated.rs".to_string()),
+                ..FileFilter::default()
+            },
+            ..AnalysisConfig::default()
+        };
+        let mut analyzer = StandaloneAnalyzer::new(MisalignmentAnalyzer::new().unwrap(), config);
+        let report = run(&mut analyzer, dir.path(), 2).await.unwrap();
+        assert_eq!(report.iterations, 2);
+        assert_eq!(report.files, 1);
+        assert_eq!(report.bytes, 19);
+        assert_eq!(report.detections, 1);
+        assert!(report.total_ms >= report.discovery_ms + report.parse_ms + report.match_ms);
+
+        assert!(run(&mut analyzer, dir.path(), 0).await.is_err());
+    }
+}
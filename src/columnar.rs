@@ -0,0 +1,188 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Columnar (Arrow IPC) output for large result sets.
+//!
+//! Detections are written one row per detection as an Arrow IPC file (Feather v2),
+//! which pandas, polars and duckdb read directly without parsing JSON.
+
+use crate::error::{Result, SniffError};
+use crate::standalone::AnalysisResults;
+use arrow_array::builder::{Float64Builder, ListBuilder, StringBuilder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Field, Schema};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Maximum number of rows per record batch.
+const BATCH_SIZE: usize = 64 * 1024;
+
+/// Returns the Arrow schema used for detection rows.
+fn detection_schema(results: &AnalysisResults) -> Schema {
+    let fields = vec![
+        Field::new("file_path", DataType::Utf8, false),
+        Field::new("language", DataType::Utf8, true),
+        Field::new("file_quality_score", DataType::Float64, false),
+        Field::new("rule_id", DataType::Utf8, false),
+        Field::new("rule_name", DataType::Utf8, false),
+        Field::new("severity", DataType::Utf8, false),
+        Field::new("line_number", DataType::UInt64, false),
+        Field::new("column_number", DataType::UInt64, false),
+        Field::new("code_snippet", DataType::Utf8, false),
+        Field::new("fingerprint", DataType::Utf8, false),
+        Field::new(
+            "tags",
+            DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+            false,
+        ),
+    ];
+
+    // Run-level values go into schema metadata rather than repeated columns
+    let mut metadata = HashMap::new();
+    if let Some(run) = &results.run {
+        metadata.insert("repository".to_string(), run.repository.clone());
+        metadata.insert("timestamp".to_string(), run.timestamp.to_rfc3339());
+        metadata.insert("sniff_version".to_string(), run.sniff_version.clone());
+        metadata.insert("ruleset_hash".to_string(), run.ruleset_hash.clone());
+        metadata.insert("network".to_string(), run.network.name().to_string());
+    }
+
+    Schema::new_with_metadata(fields, metadata)
+}
+
+/// Column builders for one record batch of detections.
+struct DetectionColumns {
+    file_path: StringBuilder,
+    language: StringBuilder,
+    file_quality_score: Float64Builder,
+    rule_id: StringBuilder,
+    rule_name: StringBuilder,
+    severity: StringBuilder,
+    line_number: UInt64Builder,
+    column_number: UInt64Builder,
+    code_snippet: StringBuilder,
+    fingerprint: StringBuilder,
+    tags: ListBuilder<StringBuilder>,
+    rows: usize,
+}
+
+impl DetectionColumns {
+    fn new() -> Self {
+        Self {
+            file_path: StringBuilder::new(),
+            language: StringBuilder::new(),
+            file_quality_score: Float64Builder::new(),
+            rule_id: StringBuilder::new(),
+            rule_name: StringBuilder::new(),
+            severity: StringBuilder::new(),
+            line_number: UInt64Builder::new(),
+            column_number: UInt64Builder::new(),
+            code_snippet: StringBuilder::new(),
+            fingerprint: StringBuilder::new(),
+            tags: ListBuilder::new(StringBuilder::new()),
+            rows: 0,
+        }
+    }
+
+    fn finish(&mut self, schema: &Arc<Schema>) -> Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.file_path.finish()),
+            Arc::new(self.language.finish()),
+            Arc::new(self.file_quality_score.finish()),
+            Arc::new(self.rule_id.finish()),
+            Arc::new(self.rule_name.finish()),
+            Arc::new(self.severity.finish()),
+            Arc::new(self.line_number.finish()),
+            Arc::new(self.column_number.finish()),
+            Arc::new(self.code_snippet.finish()),
+            Arc::new(self.fingerprint.finish()),
+            Arc::new(self.tags.finish()),
+        ];
+        self.rows = 0;
+
+        RecordBatch::try_new(Arc::clone(schema), columns).map_err(|e| arrow_error(&e))
+    }
+}
+
+/// Converts an Arrow error into a sniff error.
+fn arrow_error(error: &arrow_schema::ArrowError) -> SniffError {
+    SniffError::invalid_format("Arrow IPC output".to_string(), error.to_string())
+}
+
+/// Writes all detections as an Arrow IPC file.
+pub fn write_arrow_ipc<W: Write>(results: &AnalysisResults, writer: W) -> Result<()> {
+    let schema = Arc::new(detection_schema(results));
+    let mut writer = FileWriter::try_new(writer, &schema).map_err(|e| arrow_error(&e))?;
+    let mut columns = DetectionColumns::new();
+
+    for file_result in &results.file_results {
+        let file_path = file_result.file_path.to_string_lossy();
+        let language = file_result.language.map(|l| l.name());
+
+        for detection in &file_result.detections {
+            columns.file_path.append_value(&file_path);
+            columns.language.append_option(language);
+            columns
+                .file_quality_score
+                .append_value(file_result.quality_score);
+            columns.rule_id.append_value(&detection.rule_id);
+            columns.rule_name.append_value(&detection.rule_name);
+            columns.severity.append_value(detection.severity.name());
+            columns
+                .line_number
+                .append_value(detection.line_number as u64);
+            columns
+                .column_number
+                .append_value(detection.column_number as u64);
+            columns.code_snippet.append_value(&detection.code_snippet);
+            columns.fingerprint.append_value(detection.fingerprint());
+            for tag in &detection.tags {
+                columns.tags.values().append_value(tag);
+            }
+            columns.tags.append(true);
+            columns.rows += 1;
+
+            if columns.rows == BATCH_SIZE {
+                writer
+                    .write(&columns.finish(&schema)?)
+                    .map_err(|e| arrow_error(&e))?;
+            }
+        }
+    }
+
+    if columns.rows > 0 {
+        writer
+            .write(&columns.finish(&schema)?)
+            .map_err(|e| arrow_error(&e))?;
+    }
+
+    writer.finish().map_err(|e| arrow_error(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support::{detection, file_result};
+    use crate::playbook::Severity;
+    use crate::SupportedLanguage;
+    use arrow_ipc::reader::FileReader;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_arrow_ipc_roundtrip() {
+        let mut detection = detection("rust_todo_comment", Severity::Medium, 3);
+        detection.code_snippet = "// TODO: implement".to_string();
+        let mut file_result = file_result("src/lib.rs", vec![detection.clone(), detection]);
+        file_result.language = Some(SupportedLanguage::Rust);
+        let results = AnalysisResults::from_file_results(vec![file_result]);
+
+        let mut buffer = Vec::new();
+        write_arrow_ipc(&results, &mut buffer).unwrap();
+
+        let reader = FileReader::try_new(Cursor::new(buffer), None).unwrap();
+        let rows: usize = reader.map(|batch| batch.unwrap().num_rows()).sum();
+        assert_eq!(rows, 2);
+    }
+}
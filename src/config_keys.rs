@@ -0,0 +1,236 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Duplicate key detection for YAML and JSON config files.
+//!
+//! Files are deserialized with `serde_json` or `serde_yaml` through a visitor
+//! that tracks the keys of each mapping, so a key is reported only when it
+//! repeats in the same mapping, never for block scalars, comments or
+//! sibling list items. Deserialization cannot resume after the duplicate, so
+//! at most one is reported per JSON file or YAML document. Files that do not
+//! parse, such as templates, are not reported.
+
+use serde::de::{self, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess};
+use serde::de::{VariantAccess, Visitor};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A key repeated in the same mapping of a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey {
+    /// 1-based line of the repeated key.
+    pub line: usize,
+    /// The repeated key.
+    pub key: String,
+}
+
+/// Finds keys repeated in the same mapping of a YAML or JSON file.
+///
+/// YAML merge keys (`<<`) may repeat and are not reported.
+#[must_use]
+pub fn find_duplicate_keys(content: &str) -> Vec<DuplicateKey> {
+    if content.trim_start().starts_with(['{', '[']) {
+        let found = RefCell::new(None);
+        let mut deserializer = serde_json::Deserializer::from_str(content);
+        match (
+            AnyValue { found: &found }.deserialize(&mut deserializer),
+            found.take(),
+        ) {
+            (Ok(()), _) => return Vec::new(),
+            (Err(e), Some(key)) => {
+                return vec![DuplicateKey {
+                    line: e.line(),
+                    key,
+                }]
+            }
+            // Not JSON, but possibly a YAML flow mapping
+            (Err(_), None) => {}
+        }
+    }
+
+    let mut duplicates = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let found = RefCell::new(None);
+        if let Err(e) = (AnyValue { found: &found }).deserialize(document) {
+            match (found.take(), e.location()) {
+                (Some(key), Some(location)) => duplicates.push(DuplicateKey {
+                    line: location.line(),
+                    key,
+                }),
+                // A syntax error: later documents cannot be trusted
+                _ => break,
+            }
+        }
+    }
+    duplicates
+}
+
+/// Walks a value of any type, recording the first duplicate key in `found`.
+#[derive(Clone, Copy)]
+struct AnyValue<'a> {
+    found: &'a RefCell<Option<String>>,
+}
+
+impl<'de> DeserializeSeed<'de> for AnyValue<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for AnyValue<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a config value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_none<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while seq.next_element_seed(self)?.is_some() {}
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut keys = HashSet::new();
+        while map
+            .next_key_seed(Key {
+                keys: &mut keys,
+                found: self.found,
+            })?
+            .is_some()
+        {
+            map.next_value_seed(self)?;
+        }
+        Ok(())
+    }
+
+    /// Tagged YAML values such as `!Ref name`.
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<(), A::Error> {
+        let (de::IgnoredAny, variant) = data.variant()?;
+        variant.newtype_variant_seed(self)
+    }
+}
+
+/// A mapping key, failing if the mapping already has it.
+///
+/// The error is raised while the key itself is deserialized, so it carries
+/// the position of the key rather than of the mapping.
+struct Key<'a> {
+    keys: &'a mut HashSet<String>,
+    found: &'a RefCell<Option<String>>,
+}
+
+impl<'de> DeserializeSeed<'de> for Key<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl Visitor<'_> for Key<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a scalar key")
+    }
+
+    fn visit_str<E: de::Error>(self, key: &str) -> Result<(), E> {
+        if key == "<<" || self.keys.insert(key.to_string()) {
+            return Ok(());
+        }
+        *self.found.borrow_mut() = Some(key.to_string());
+        Err(E::custom(format!("duplicate key {key}")))
+    }
+
+    fn visit_bool<E: de::Error>(self, key: bool) -> Result<(), E> {
+        self.visit_str(&key.to_string())
+    }
+
+    fn visit_i64<E: de::Error>(self, key: i64) -> Result<(), E> {
+        self.visit_str(&key.to_string())
+    }
+
+    fn visit_u64<E: de::Error>(self, key: u64) -> Result<(), E> {
+        self.visit_str(&key.to_string())
+    }
+
+    fn visit_f64<E: de::Error>(self, key: f64) -> Result<(), E> {
+        self.visit_str(&key.to_string())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        self.visit_str("~")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(content: &str) -> Vec<(usize, String)> {
+        find_duplicate_keys(content)
+            .into_iter()
+            .map(|duplicate| (duplicate.line, duplicate.key))
+            .collect()
+    }
+
+    #[test]
+    fn test_yaml_duplicate_keys() {
+        let yaml = "database:\n  host: a\n  notes: |\n    host: b\n    host: c\n  host: d\n\
+                    services:\n  - name: api\n  - name: web\n\
+                    base: &base\n  port: 1\nmerged:\n  <<: *base\n  port: 2\n\
+                    ---\ntimeout: 1\nretries: 3\ntimeout: 2\n";
+        assert_eq!(
+            lines(yaml),
+            vec![(6, "host".to_string()), (18, "timeout".to_string())]
+        );
+
+        // Tagged values are walked, templates that do not parse are skipped
+        assert_eq!(lines("a: !Ref x\nb: 1\nb: 2\n"), vec![(3, "b".to_string())]);
+        assert!(lines("{{ if .x }}\nport: 1\n{{ else }}\nport: 2\n{{ end }}\n").is_empty());
+    }
+
+    #[test]
+    fn test_json_duplicate_keys() {
+        let json =
+            "{\n  \"logging\": { \"level\": \"info\" },\n  \"database\": { \"level\": 1 },\n\
+                    \"timeout\": 30,\n  \"timeout\": 60\n}\n";
+        assert_eq!(lines(json), vec![(5, "timeout".to_string())]);
+        assert!(lines("[{ \"id\": 1 }, { \"id\": 2 }]").is_empty());
+    }
+}
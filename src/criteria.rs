@@ -0,0 +1,513 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Acceptance criteria for TODO verification.
+//!
+//! A criteria file maps TODO ids to what must exist once the TODO is done:
+//!
+//! ```yaml
+//! retry-logic:
+//!   - symbol: retry_backoff
+//!     kind: function
+//!     file: src/net.rs
+//!   - test: retry_backoff
+//!   - file: docs/retries.md
+//! ```
+//!
+//! Symbols are looked up in the symbol table of the codebase analyzer, so a
+//! name in a comment or string does not satisfy a criterion.
+
+use crate::analysis::{SupportedLanguage, TestFileClassifier};
+use crate::ast_query;
+use crate::error::{Result, SniffError};
+use crate::project_config::project_files;
+use regex::Regex;
+use rust_tree_sitter::analyzer::{CodebaseAnalyzer, Symbol};
+use rust_tree_sitter::detect_language_from_path;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A requirement a TODO must meet to be verified.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Criterion {
+    /// Symbol that must be defined, in `file` if given, else in a verified file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Required kind of `symbol`, e.g. `function`, `method`, `struct` or `class`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// File containing `symbol`, or that must exist if no symbol is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    /// Name that a test must reference.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test: Option<String>,
+    /// Shown in reports instead of the generated description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Outcome of checking a single [`Criterion`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CriterionResult {
+    /// What was required.
+    pub description: String,
+    /// Whether the requirement is met.
+    pub met: bool,
+    /// Where it was met, or what is missing.
+    pub detail: String,
+}
+
+impl Criterion {
+    /// Returns the description shown in reports.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        if let Some(description) = &self.description {
+            return description.clone();
+        }
+
+        let mut parts = Vec::new();
+        if let Some(symbol) = &self.symbol {
+            let kind = self.kind.as_deref().unwrap_or("symbol");
+            match &self.file {
+                Some(file) => parts.push(format!("{kind} `{symbol}` exists in {}", file.display())),
+                None => parts.push(format!("{kind} `{symbol}` exists")),
+            }
+        } else if let Some(file) = &self.file {
+            parts.push(format!("{} exists", file.display()));
+        }
+        if let Some(test) = &self.test {
+            parts.push(format!("a test references `{test}`"));
+        }
+        parts.join(" and ")
+    }
+
+    fn validate(&self, todo_id: &str) -> Result<()> {
+        let problem = if self.symbol.is_none() && self.test.is_none() && self.file.is_none() {
+            "needs a symbol, test or file"
+        } else if self.kind.is_some() && self.symbol.is_none() {
+            "sets a kind without a symbol"
+        } else {
+            return Ok(());
+        };
+        Err(SniffError::invalid_format(
+            "acceptance criteria",
+            format!("A criterion of TODO '{todo_id}' {problem}"),
+        ))
+    }
+}
+
+/// Loads the acceptance criteria of a TODO from a criteria file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed, has no entry for
+/// the TODO, or contains an incomplete criterion.
+pub fn load(path: &Path, todo_id: &str) -> Result<Vec<Criterion>> {
+    let content = std::fs::read_to_string(path).map_err(|e| SniffError::file_system(path, e))?;
+    let mut todos: BTreeMap<String, Vec<Criterion>> =
+        serde_yaml::from_str(&content).map_err(|e| {
+            SniffError::invalid_format(
+                "acceptance criteria",
+                format!("Failed to parse {}: {e}", path.display()),
+            )
+        })?;
+
+    let criteria = todos.remove(todo_id).ok_or_else(|| {
+        SniffError::invalid_format(
+            "acceptance criteria",
+            format!("{} has no criteria for TODO '{todo_id}'", path.display()),
+        )
+    })?;
+    for criterion in &criteria {
+        criterion.validate(todo_id)?;
+    }
+    Ok(criteria)
+}
+
+/// Checks acceptance criteria against the verified files and the project.
+///
+/// Relative paths in criteria are resolved against `project_dir`. Tests are
+/// searched in every test file of the project and in test functions of the
+/// verified files.
+///
+/// # Errors
+///
+/// Returns an error if the codebase analyzer cannot be created.
+pub fn check(
+    criteria: &[Criterion],
+    files: &[PathBuf],
+    project_dir: &Path,
+) -> Result<Vec<CriterionResult>> {
+    if criteria.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut checker = Checker {
+        analyzer: CodebaseAnalyzer::new().map_err(|e| {
+            SniffError::analysis_error(format!("Failed to create codebase analyzer: {e}"))
+        })?,
+        classifier: TestFileClassifier::new(),
+        project_dir,
+        files,
+        test_files: None,
+    };
+
+    Ok(criteria
+        .iter()
+        .map(|criterion| {
+            let mut details = Vec::new();
+            let mut met = true;
+            if let Some(symbol) = &criterion.symbol {
+                let (found, detail) = checker.find_symbol(symbol, criterion);
+                met &= found;
+                details.push(detail);
+            } else if let Some(file) = &criterion.file {
+                let found = project_dir.join(file).exists();
+                met &= found;
+                details.push(if found {
+                    format!("{} exists", file.display())
+                } else {
+                    format!("{} does not exist", file.display())
+                });
+            }
+            if let Some(test) = &criterion.test {
+                let (found, detail) = checker.find_test(test);
+                met &= found;
+                details.push(detail);
+            }
+            CriterionResult {
+                description: criterion.describe(),
+                met,
+                detail: details.join("; "),
+            }
+        })
+        .collect())
+}
+
+struct Checker<'a> {
+    analyzer: CodebaseAnalyzer,
+    classifier: TestFileClassifier,
+    project_dir: &'a Path,
+    files: &'a [PathBuf],
+    /// Test files of the project, collected on first use.
+    test_files: Option<Vec<PathBuf>>,
+}
+
+impl Checker<'_> {
+    fn symbols(&mut self, path: &Path) -> Vec<Symbol> {
+        self.analyzer
+            .analyze_file(path)
+            .map(|result| {
+                result
+                    .files
+                    .into_iter()
+                    .flat_map(|file| file.symbols)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn find_symbol(&mut self, name: &str, criterion: &Criterion) -> (bool, String) {
+        let candidates: Vec<PathBuf> = match &criterion.file {
+            Some(file) => vec![self.project_dir.join(file)],
+            None => self
+                .files
+                .iter()
+                .map(|file| self.project_dir.join(file))
+                .collect(),
+        };
+
+        for path in &candidates {
+            let found = self.symbols(path).into_iter().find(|symbol| {
+                symbol.name == name
+                    && criterion
+                        .kind
+                        .as_ref()
+                        .is_none_or(|kind| symbol.kind.eq_ignore_ascii_case(kind))
+            });
+            if let Some(symbol) = found {
+                return (
+                    true,
+                    format!(
+                        "`{name}` defined at {}:{}",
+                        relative(self.project_dir, path),
+                        symbol.start_line
+                    ),
+                );
+            }
+        }
+
+        let place = match &criterion.file {
+            Some(file) => file.display().to_string(),
+            None => "the verified files".to_string(),
+        };
+        (false, format!("`{name}` is not defined in {place}"))
+    }
+
+    fn find_test(&mut self, name: &str) -> (bool, String) {
+        let Ok(pattern) = Regex::new(&format!(r"\b{}\b", regex::escape(name))) else {
+            return (false, format!("invalid name `{name}`"));
+        };
+
+        // Test files anywhere in the project
+        let test_files = self
+            .test_files
+            .get_or_insert_with(|| collect_test_files(self.project_dir, &self.classifier));
+        for path in test_files.iter() {
+            // Verified files with inline tests classify as test files too; only
+            // their test functions count, below
+            if self
+                .files
+                .iter()
+                .any(|file| self.project_dir.join(file) == *path)
+            {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            if let Some(line) = reference_lines(path, &content, name, &pattern).first() {
+                let location = format!("{}:{line}", relative(self.project_dir, path));
+                return (true, format!("referenced by a test at {location}"));
+            }
+        }
+
+        // Inline test functions in the verified files
+        for file in self.files {
+            let path = self.project_dir.join(file);
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let references = reference_lines(&path, &content, name, &pattern);
+            if references.is_empty() {
+                continue;
+            }
+            let lines: Vec<&str> = content.lines().collect();
+            for symbol in self.symbols(&path) {
+                let span = symbol.start_line..=symbol.end_line;
+                if is_test_function(&symbol, &lines)
+                    && references.iter().any(|line| span.contains(line))
+                {
+                    let location = format!(
+                        "{}:{}",
+                        relative(self.project_dir, &path),
+                        symbol.start_line
+                    );
+                    return (
+                        true,
+                        format!("referenced by test `{}` at {location}", symbol.name),
+                    );
+                }
+            }
+        }
+
+        (false, format!("no test references `{name}`"))
+    }
+}
+
+/// Returns the 1-based lines on which `name` is used as an identifier.
+///
+/// Files with a tree-sitter grammar are parsed, so a name in a comment or
+/// string literal is not a reference. Other files are matched line by line
+/// with `pattern`, skipping comment lines.
+fn reference_lines(path: &Path, content: &str, name: &str, pattern: &Regex) -> Vec<usize> {
+    let tree = detect_language_from_path(&path.to_string_lossy())
+        .map(SupportedLanguage::from_agent_language)
+        .and_then(|language| ast_query::parse(language, path, content).ok());
+    let Some(tree) = tree else {
+        return content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !crate::analysis::is_comment_line(line) && pattern.is_match(line))
+            .map(|(index, _)| index + 1)
+            .collect();
+    };
+
+    let mut lines = Vec::new();
+    let mut nodes = vec![tree.root_node()];
+    while let Some(node) = nodes.pop() {
+        if node.kind().ends_with("identifier") && node.utf8_text(content.as_bytes()) == Ok(name) {
+            lines.push(node.start_position().row + 1);
+        }
+        let mut cursor = node.walk();
+        nodes.extend(node.named_children(&mut cursor));
+    }
+    lines.sort_unstable();
+    lines.dedup();
+    lines
+}
+
+/// Returns whether a symbol is a test function.
+///
+/// A test carries a test attribute (`#[test]`, `#[tokio::test]`, `@Test`) or
+/// follows the `test`/`Test` naming convention of Python and Go.
+fn is_test_function(symbol: &Symbol, lines: &[&str]) -> bool {
+    if symbol.name.starts_with("test") || symbol.name.starts_with("Test") {
+        return true;
+    }
+
+    // Attributes precede the definition, possibly mixed with doc comments;
+    // some grammars include them in the symbol's span
+    let start = symbol.start_line.saturating_sub(1).min(lines.len());
+    let is_attribute_or_doc = |line: &&str| {
+        let line = line.trim();
+        line.starts_with("#[") || line.starts_with('@') || line.starts_with("///")
+    };
+    let above = lines[..start]
+        .iter()
+        .rev()
+        .copied()
+        .take_while(is_attribute_or_doc);
+    let within = lines[start..]
+        .iter()
+        .copied()
+        .take_while(is_attribute_or_doc);
+    above.chain(within).any(is_test_attribute)
+}
+
+/// Returns whether an attribute line marks a test, judging by the last
+/// segment of the attribute path: `#[test]`, `#[tokio::test]` and
+/// `@ParameterizedTest` do, `#[cfg(test)]` and `@pytest.fixture` do not.
+fn is_test_attribute(line: &str) -> bool {
+    let line = line.trim();
+    if !line.starts_with("#[") && !line.starts_with('@') {
+        return false;
+    }
+    line.trim_start_matches(['#', '[', '@'])
+        .split(['(', ']'])
+        .next()
+        .and_then(|path| path.rsplit([':', '.']).next())
+        .is_some_and(|segment| segment.trim().to_ascii_lowercase().ends_with("test"))
+}
+
+/// Shows a path relative to the project directory.
+fn relative(project_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(project_dir)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Collects the source files of the project that are classified as tests.
+fn collect_test_files(project_dir: &Path, classifier: &TestFileClassifier) -> Vec<PathBuf> {
+    project_files(project_dir)
+        .into_iter()
+        .filter(|path| detect_language_from_path(&path.to_string_lossy()).is_some())
+        .filter(|path| {
+            let content = std::fs::read_to_string(path).unwrap_or_default();
+            classifier
+                .classify_file(&path.to_string_lossy(), Some(&content))
+                .is_test_file
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_criteria() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("criteria.yaml");
+        std::fs::write(
+            &path,
+            "retry-logic:\n  - symbol: retry_backoff\n    file: src/net.rs\n  - test: retry_backoff\n\
+             broken:\n  - kind: function\n",
+        )
+        .unwrap();
+
+        let criteria = load(&path, "retry-logic").unwrap();
+        assert_eq!(criteria.len(), 2);
+        assert_eq!(
+            criteria[0].describe(),
+            "symbol `retry_backoff` exists in src/net.rs"
+        );
+        assert_eq!(criteria[1].describe(), "a test references `retry_backoff`");
+
+        assert!(load(&path, "broken").is_err());
+        assert!(load(&path, "unknown").is_err());
+    }
+
+    #[test]
+    fn test_check_files_and_test_references() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("tests")).unwrap();
+        std::fs::write(
+            dir.path().join("net.py"),
+            "def retry_backoff():\n    pass\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("tests/test_net.py"),
+            "from net import retry_backoff\n\ndef test_retry():\n    retry_backoff()\n    print(\"jitter\")  # jitter\n",
+        )
+        .unwrap();
+
+        let criterion = |file: Option<&str>, test: Option<&str>| Criterion {
+            symbol: None,
+            kind: None,
+            file: file.map(PathBuf::from),
+            test: test.map(str::to_string),
+            description: None,
+        };
+        let criteria = [
+            criterion(Some("net.py"), Some("retry_backoff")),
+            criterion(Some("missing.py"), None),
+            criterion(None, Some("jitter")),
+        ];
+        let results = check(&criteria, &[PathBuf::from("net.py")], dir.path()).unwrap();
+
+        assert!(results[0].met, "{}", results[0].detail);
+        assert!(
+            results[0].detail.contains("tests/test_net.py:1"),
+            "{}",
+            results[0].detail
+        );
+        assert!(!results[1].met);
+        assert!(!results[2].met);
+    }
+
+    #[test]
+    fn test_inline_tests_are_recognized_by_attribute() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("net.rs"),
+            "pub fn retry_backoff(attempt: u32) -> u32 {\n    attempt * 2\n}\n\n\
+             pub fn jitter() -> &'static str {\n    \"retry_backoff\"\n}\n\n\
+             #[cfg(test)]\nmod tests {\n    use super::*;\n\n    \
+             #[test]\n    fn retries_work() {\n        assert_eq!(retry_backoff(1), 2);\n    }\n}\n",
+        )
+        .unwrap();
+
+        let criterion = Criterion {
+            symbol: None,
+            kind: None,
+            file: None,
+            test: Some("retry_backoff".to_string()),
+            description: None,
+        };
+        let results = check(&[criterion], &[PathBuf::from("net.rs")], dir.path()).unwrap();
+        assert!(results[0].met, "{}", results[0].detail);
+        assert!(
+            results[0].detail.contains("`retries_work`"),
+            "{}",
+            results[0].detail
+        );
+    }
+
+    #[test]
+    fn test_is_test_attribute() {
+        assert!(is_test_attribute("#[test]"));
+        assert!(is_test_attribute(
+            "    #[tokio::test(flavor = \"multi_thread\")]"
+        ));
+        assert!(is_test_attribute("@ParameterizedTest"));
+        assert!(!is_test_attribute("#[cfg(test)]"));
+        assert!(!is_test_attribute("@pytest.fixture"));
+        assert!(!is_test_attribute("/// the retry test"));
+    }
+}
@@ -0,0 +1,580 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Local import graph of source files, for incremental re-analysis.
+//!
+//! Imports are read from the syntax tree of each file and resolved to files
+//! on disk; imports of external packages resolve to nothing and are dropped:
+//!
+//! - Rust: `mod name;` and `use crate::`, `use self::` and `use super::` paths,
+//!   resolved against the crate's `src` directory.
+//! - Python: `import a.b` and `from .a import b`, relative imports against the
+//!   file's package and absolute ones against its parent directories.
+//! - JavaScript and TypeScript: relative specifiers (`./`, `../`) of `import`,
+//!   `export ... from`, `import x = require()`, `require()` and dynamic `import()`.
+//! - C and C++: `#include "header"` relative to the including file.
+//!
+//! Go imports name packages by module path and are not resolved. Imports in
+//! comments and string literals are not imports and are never read.
+
+use crate::analysis::SupportedLanguage;
+use crate::ast_query;
+use crate::project_config::project_files;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use tracing::debug;
+use tree_sitter::Node;
+
+/// Extensions tried for extensionless JavaScript and TypeScript specifiers.
+const SCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// An import read from the syntax tree of a file, before it is resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Import {
+    /// `mod name;`, a child module in a file of its own.
+    RustModule(String),
+    /// The path tree of a `use` declaration, such as `crate::a::{b, c}`.
+    RustUse(String),
+    /// `import a.b`.
+    PythonModule(String),
+    /// `from module import names`; the module keeps its leading dots.
+    PythonFrom(String, Vec<String>),
+    /// The quoted specifier of a script import or a C include, unquoted.
+    Quoted(String),
+}
+
+/// Returns the language whose imports are read from files with `extension`.
+fn import_language(extension: &str) -> Option<SupportedLanguage> {
+    match extension {
+        "rs" => Some(SupportedLanguage::Rust),
+        "py" => Some(SupportedLanguage::Python),
+        "js" | "jsx" | "mjs" | "cjs" => Some(SupportedLanguage::JavaScript),
+        "ts" | "tsx" => Some(SupportedLanguage::TypeScript),
+        "c" | "h" => Some(SupportedLanguage::C),
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => Some(SupportedLanguage::Cpp),
+        _ => None,
+    }
+}
+
+/// Returns the existing local files imported by a source file, in path order.
+///
+/// The language is taken from the file extension; files of other languages
+/// and files that cannot be parsed import nothing.
+#[must_use]
+pub fn local_imports(file: &Path, content: &str) -> BTreeSet<PathBuf> {
+    let mut imports = BTreeSet::new();
+    let Some(language) = file
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(import_language)
+    else {
+        return imports;
+    };
+    let dir = file.parent().unwrap_or_else(|| Path::new(""));
+    // Directory holding the child modules of a Rust file's module
+    let module_dir = match file.file_name().and_then(|name| name.to_str()) {
+        Some("mod.rs" | "lib.rs" | "main.rs") | None => dir.to_path_buf(),
+        Some(_) => dir.join(file.file_stem().unwrap_or_default()),
+    };
+    let crate_dir = (language == SupportedLanguage::Rust)
+        .then(|| {
+            dir.ancestors()
+                .find(|ancestor| ancestor.join("Cargo.toml").is_file())
+        })
+        .flatten()
+        .map(|root| root.join("src"));
+
+    for import in parsed_imports(language, file, content) {
+        match import {
+            Import::RustModule(name) => {
+                imports.extend(resolve_rust_module(&module_dir, &[name.as_str()]));
+            }
+            Import::RustUse(tree) => {
+                rust_use_imports(&tree, &module_dir, crate_dir.as_deref(), &mut imports);
+            }
+            Import::PythonModule(dotted) => {
+                imports.extend(dir.ancestors().find_map(|base| {
+                    resolve_python(
+                        &dotted
+                            .split('.')
+                            .fold(base.to_path_buf(), |path, part| path.join(part)),
+                    )
+                }));
+            }
+            Import::PythonFrom(module, names) => {
+                python_from_imports(dir, &module, &names, &mut imports);
+            }
+            Import::Quoted(specifier) => match language {
+                SupportedLanguage::C | SupportedLanguage::Cpp => {
+                    imports.extend(existing(&dir.join(specifier)));
+                }
+                _ if specifier.starts_with("./") || specifier.starts_with("../") => {
+                    imports.extend(resolve_script(&dir.join(specifier)));
+                }
+                _ => {}
+            },
+        }
+    }
+    imports.remove(&normalize(file));
+    imports
+}
+
+/// Reads the imports of a file from its syntax tree, in no particular order.
+fn parsed_imports(language: SupportedLanguage, file: &Path, content: &str) -> Vec<Import> {
+    let tree = match ast_query::parse(language, file, content) {
+        Ok(tree) => tree,
+        Err(e) => {
+            debug!("Skipping imports of {}: {}", file.display(), e);
+            return Vec::new();
+        }
+    };
+    let text = |node: Node| {
+        node.utf8_text(content.as_bytes())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let unquote = |node: Node| text(node).trim_matches(['"', '\'', '`']).to_string();
+    // `import a.b as c` and `from a import b as c` import `a.b` and `b`
+    let python_name = |node: Node| {
+        if node.kind() == "aliased_import" {
+            text(node.child_by_field_name("name").unwrap_or(node))
+        } else {
+            text(node)
+        }
+    };
+
+    let mut imports = Vec::new();
+    let mut nodes = vec![tree.root_node()];
+    while let Some(node) = nodes.pop() {
+        let field = |name: &str| node.child_by_field_name(name);
+        let mut cursor = node.walk();
+        match (language, node.kind()) {
+            // Modules nested in inline modules are not resolved against this file
+            (SupportedLanguage::Rust, "mod_item")
+                if field("body").is_none()
+                    && node
+                        .parent()
+                        .is_some_and(|parent| parent.kind() == "source_file") =>
+            {
+                imports.extend(field("name").map(|name| Import::RustModule(text(name))));
+            }
+            (SupportedLanguage::Rust, "use_declaration") => {
+                imports.extend(field("argument").map(|tree| Import::RustUse(text(tree))));
+            }
+            (SupportedLanguage::Python, "import_statement") => imports.extend(
+                node.children_by_field_name("name", &mut cursor)
+                    .map(|name| Import::PythonModule(python_name(name))),
+            ),
+            (SupportedLanguage::Python, "import_from_statement") => {
+                let names: Vec<String> = node
+                    .children_by_field_name("name", &mut cursor)
+                    .map(python_name)
+                    .collect();
+                imports.extend(
+                    field("module_name").map(|module| Import::PythonFrom(text(module), names)),
+                );
+            }
+            (
+                SupportedLanguage::JavaScript | SupportedLanguage::TypeScript,
+                "import_statement" | "export_statement" | "import_require_clause",
+            ) => imports.extend(field("source").map(|source| Import::Quoted(unquote(source)))),
+            (SupportedLanguage::JavaScript | SupportedLanguage::TypeScript, "call_expression") => {
+                let dynamic = field("function")
+                    .is_some_and(|callee| callee.kind() == "import" || text(callee) == "require");
+                let specifier = field("arguments")
+                    .and_then(|arguments| arguments.named_child(0))
+                    .filter(|argument| argument.kind() == "string");
+                if let Some(specifier) = specifier.filter(|_| dynamic) {
+                    imports.push(Import::Quoted(unquote(specifier)));
+                }
+            }
+            (SupportedLanguage::C | SupportedLanguage::Cpp, "preproc_include") => imports.extend(
+                field("path")
+                    .filter(|path| path.kind() == "string_literal")
+                    .map(|path| Import::Quoted(unquote(path))),
+            ),
+            _ => {}
+        }
+        nodes.extend(node.named_children(&mut cursor));
+    }
+    imports
+}
+
+/// Resolves a `use` tree starting with `crate`, `self` or `super` to module files.
+fn rust_use_imports(
+    tree: &str,
+    module_dir: &Path,
+    crate_dir: Option<&Path>,
+    imports: &mut BTreeSet<PathBuf>,
+) {
+    for path in expand_use_tree(tree) {
+        let mut segments: Vec<&str> = path.split("::").map(str::trim).collect();
+        let mut base = match segments.first() {
+            Some(&"crate") => match crate_dir {
+                Some(crate_dir) => crate_dir.to_path_buf(),
+                None => continue,
+            },
+            Some(&("self" | "super")) => module_dir.to_path_buf(),
+            // External crates and the standard library
+            _ => continue,
+        };
+        if segments.first() == Some(&"crate") || segments.first() == Some(&"self") {
+            segments.remove(0);
+        }
+        while segments.first() == Some(&"super") {
+            segments.remove(0);
+            base.pop();
+        }
+        segments.retain(|segment| !segment.is_empty() && *segment != "*");
+        imports.extend(resolve_rust_module(&base, &segments));
+    }
+}
+
+/// Expands one level of braces of a `use` path (`a::{b, c::d}`) into paths.
+///
+/// Nested groups are cut at their brace, keeping the module they import from,
+/// and `as` renames are dropped.
+fn expand_use_tree(tree: &str) -> Vec<String> {
+    let paths = match tree.split_once('{') {
+        None => vec![tree.to_string()],
+        Some((prefix, rest)) => {
+            let items = rest.rsplit_once('}').map_or(rest, |(items, _)| items);
+            let mut paths = Vec::new();
+            let mut depth = 0usize;
+            let mut start = 0;
+            for (index, character) in items.char_indices() {
+                match character {
+                    '{' => depth += 1,
+                    '}' => depth = depth.saturating_sub(1),
+                    ',' if depth == 0 => {
+                        paths.push(format!("{prefix}{}", items[start..index].trim()));
+                        start = index + 1;
+                    }
+                    _ => {}
+                }
+            }
+            paths.push(format!("{prefix}{}", items[start..].trim()));
+            paths
+        }
+    };
+    paths
+        .into_iter()
+        .map(|path| {
+            let path = path.split('{').next().unwrap_or_default();
+            let path = path.split(" as ").next().unwrap_or_default();
+            path.trim().trim_end_matches("::").to_string()
+        })
+        .collect()
+}
+
+/// Resolves the longest prefix of a module path that names a file under `base`.
+fn resolve_rust_module(base: &Path, segments: &[&str]) -> Option<PathBuf> {
+    (1..=segments.len()).rev().find_map(|len| {
+        let module = segments[..len]
+            .iter()
+            .fold(base.to_path_buf(), |path, segment| path.join(segment));
+        existing(&module.with_extension("rs")).or_else(|| existing(&module.join("mod.rs")))
+    })
+}
+
+/// Resolves `from module import names`, trying the file's package for
+/// relative modules and its parent directories for absolute ones.
+fn python_from_imports(
+    dir: &Path,
+    module: &str,
+    names: &[String],
+    imports: &mut BTreeSet<PathBuf>,
+) {
+    let dots = module.chars().take_while(|&c| c == '.').count();
+    let dotted = &module[dots..];
+    let bases: Vec<PathBuf> = if dots > 0 {
+        let mut base = dir.to_path_buf();
+        for _ in 1..dots {
+            base.pop();
+        }
+        vec![base]
+    } else {
+        dir.ancestors().map(Path::to_path_buf).collect()
+    };
+    for base in bases {
+        let package = dotted
+            .split('.')
+            .filter(|part| !part.is_empty())
+            .fold(base, |path, part| path.join(part));
+        // Imported names may be submodules of the package
+        let mut resolved: Vec<PathBuf> = names
+            .iter()
+            .filter_map(|name| resolve_python(&package.join(name)))
+            .collect();
+        if !dotted.is_empty() {
+            resolved.extend(resolve_python(&package));
+        }
+        if !resolved.is_empty() {
+            imports.extend(resolved);
+            break;
+        }
+    }
+}
+
+/// Resolves a Python module path to its `.py` file or package `__init__.py`.
+fn resolve_python(module: &Path) -> Option<PathBuf> {
+    existing(&module.with_extension("py")).or_else(|| existing(&module.join("__init__.py")))
+}
+
+/// Resolves a relative JavaScript or TypeScript specifier to a file.
+fn resolve_script(specifier: &Path) -> Option<PathBuf> {
+    existing(specifier)
+        .or_else(|| {
+            SCRIPT_EXTENSIONS.iter().find_map(|extension| {
+                let mut file = specifier.as_os_str().to_os_string();
+                file.push(".");
+                file.push(extension);
+                existing(Path::new(&file))
+            })
+        })
+        .or_else(|| {
+            SCRIPT_EXTENSIONS
+                .iter()
+                .find_map(|extension| existing(&specifier.join(format!("index.{extension}"))))
+        })
+}
+
+/// Returns the normalized path if it is an existing file.
+fn existing(path: &Path) -> Option<PathBuf> {
+    path.is_file().then(|| normalize(path))
+}
+
+/// Removes `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Import graph of a set of files, with the reverse edges to look up the
+/// files affected by a change.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyMap {
+    /// Local files imported by each file.
+    imports: HashMap<PathBuf, BTreeSet<PathBuf>>,
+    /// Files importing each file.
+    dependents: HashMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl DependencyMap {
+    /// Creates an empty dependency map.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the imports of a file from its current content, replacing the
+    /// ones recorded before.
+    pub fn update(&mut self, file: &Path, content: &str) {
+        self.set_imports(file, local_imports(file, content));
+    }
+
+    /// Records the imports of every source file under `root`, honoring
+    /// ignore files like analysis does. Unreadable files are skipped.
+    pub fn add_directory(&mut self, root: &Path) {
+        for path in project_files(root) {
+            let source = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| import_language(ext).is_some());
+            if !source {
+                continue;
+            }
+            if let Ok(content) = std::fs::read_to_string(&path) {
+                self.update(&path, &content);
+            }
+        }
+    }
+
+    /// Records the local files imported by a file, replacing the ones
+    /// recorded before.
+    pub fn set_imports(&mut self, file: &Path, imports: BTreeSet<PathBuf>) {
+        let file = normalize(file);
+        self.remove(&file);
+        for import in &imports {
+            self.dependents
+                .entry(import.clone())
+                .or_default()
+                .insert(file.clone());
+        }
+        self.imports.insert(file, imports);
+    }
+
+    /// Forgets the imports of a file, e.g. after it was deleted.
+    pub fn remove(&mut self, file: &Path) {
+        let file = normalize(file);
+        for import in self.imports.remove(&file).unwrap_or_default() {
+            if let Some(dependents) = self.dependents.get_mut(&import) {
+                dependents.remove(&file);
+                if dependents.is_empty() {
+                    self.dependents.remove(&import);
+                }
+            }
+        }
+    }
+
+    /// Returns the files that import a file, in path order.
+    #[must_use]
+    pub fn dependents(&self, file: &Path) -> Vec<PathBuf> {
+        self.dependents
+            .get(&normalize(file))
+            .map(|dependents| dependents.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the local files imported by a file, in path order.
+    #[must_use]
+    pub fn imports(&self, file: &Path) -> Vec<PathBuf> {
+        self.imports
+            .get(&normalize(file))
+            .map(|imports| imports.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create(root: &Path, files: &[&str]) {
+        for file in files {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+    }
+
+    fn relative(root: &Path, imports: &BTreeSet<PathBuf>) -> Vec<String> {
+        imports
+            .iter()
+            .map(|path| {
+                path.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_imports() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        create(
+            root,
+            &[
+                "Cargo.toml",
+                "src/lib.rs",
+                "src/config.rs",
+                "src/net/mod.rs",
+                "src/net/tcp.rs",
+            ],
+        );
+
+        let lib = "pub mod net;\nmod config;\nuse std::fs;\n";
+        assert_eq!(
+            relative(root, &local_imports(&root.join("src/lib.rs"), lib)),
+            vec!["src/config.rs", "src/net/mod.rs"]
+        );
+
+        let tcp = "use super::super::config::Config;\nuse crate::{net, config::load as l};\n";
+        assert_eq!(
+            relative(root, &local_imports(&root.join("src/net/tcp.rs"), tcp)),
+            vec!["src/config.rs", "src/net/mod.rs"]
+        );
+    }
+
+    #[test]
+    fn test_python_and_script_imports() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        create(
+            root,
+            &[
+                "app/__init__.py",
+                "app/models.py",
+                "app/views.py",
+                "web/api.ts",
+                "web/legacy.ts",
+                "web/lazy.js",
+                "web/ui/index.tsx",
+            ],
+        );
+
+        let views = "import os\nfrom . import models\nfrom app.models import User\n";
+        assert_eq!(
+            relative(root, &local_imports(&root.join("app/views.py"), views)),
+            vec!["app/models.py"]
+        );
+
+        let main = "import { get } from './api';\nconst ui = require(\"./ui\");\n\
+                    import x from 'react';\nconst lazy = () => import('./lazy');\n\
+                    // import { old } from './legacy';\nconst hint = \"require('./legacy')\";\n";
+        assert_eq!(
+            relative(root, &local_imports(&root.join("web/main.ts"), main)),
+            vec!["web/api.ts", "web/lazy.js", "web/ui/index.tsx"]
+        );
+    }
+
+    #[test]
+    fn test_c_includes_and_project_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        create(root, &["src/util.h", "src/config.h"]);
+        std::fs::write(
+            root.join("src/main.c"),
+            "#include <stdio.h>\n#include \"util.h\"\n\
+             #ifdef CONFIG\n#include \"config.h\"\n#endif\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/other.c"), "/* #include \"util.h\" */\n").unwrap();
+
+        let mut map = DependencyMap::new();
+        map.add_directory(root);
+        assert_eq!(
+            relative(
+                root,
+                &map.imports(&root.join("src/main.c")).into_iter().collect()
+            ),
+            vec!["src/config.h", "src/util.h"]
+        );
+        assert_eq!(
+            map.dependents(&root.join("src/util.h")),
+            vec![root.join("src/main.c")]
+        );
+    }
+
+    #[test]
+    fn test_dependency_map_tracks_dependents() {
+        let mut map = DependencyMap::new();
+        let (a, b, c) = (Path::new("a.py"), Path::new("b.py"), Path::new("c.py"));
+        map.set_imports(a, BTreeSet::from([b.to_path_buf(), c.to_path_buf()]));
+        map.set_imports(b, BTreeSet::from([c.to_path_buf()]));
+        assert_eq!(map.dependents(c), vec![a.to_path_buf(), b.to_path_buf()]);
+
+        // Updating a file replaces its edges
+        map.set_imports(a, BTreeSet::new());
+        assert_eq!(map.dependents(c), vec![b.to_path_buf()]);
+        assert!(map.dependents(b).is_empty());
+
+        map.remove(b);
+        assert!(map.dependents(c).is_empty());
+        assert!(map.imports(b).is_empty());
+    }
+}
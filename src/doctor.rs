@@ -0,0 +1,442 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Rule and language coverage check of a project.
+//!
+//! `sniff doctor` compares what a project contains (languages by file
+//! extension, test frameworks by their manifests) with the loaded playbooks,
+//! and reports each gap with a suggested fix.
+
+use crate::analysis::{MisalignmentAnalyzer, SupportedLanguage, TestFileClassifier};
+use crate::playbook::RuleSource;
+use rust_tree_sitter::detect_language_from_path;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Extensions of common languages that sniff cannot analyze.
+const UNSUPPORTED_LANGUAGES: [(&str, &str); 5] = [
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("rb", "Ruby"),
+    ("php", "PHP"),
+    ("cs", "C#"),
+];
+
+/// Kind of coverage gap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapKind {
+    /// A language of the project only has the built-in rules.
+    MissingPlaybook,
+    /// The project contains a language sniff cannot analyze.
+    UnsupportedLanguage,
+    /// Test files of the project's framework are not classified as tests.
+    UnclassifiedTests,
+    /// An installed pack matches no file of the project.
+    UnusedPack,
+    /// A playbook or plugin module failed to load.
+    BrokenPlaybook,
+}
+
+impl GapKind {
+    /// Returns the name used in reports.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            GapKind::MissingPlaybook => "missing_playbook",
+            GapKind::UnsupportedLanguage => "unsupported_language",
+            GapKind::UnclassifiedTests => "unclassified_tests",
+            GapKind::UnusedPack => "unused_pack",
+            GapKind::BrokenPlaybook => "broken_playbook",
+        }
+    }
+}
+
+/// A coverage gap with a suggested fix.
+#[derive(Debug, Clone, Serialize)]
+pub struct Gap {
+    /// Kind of gap.
+    pub kind: GapKind,
+    /// What is missing.
+    pub message: String,
+    /// How to close the gap.
+    pub suggestion: String,
+}
+
+/// Rule coverage of a language present in the project.
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageCoverage {
+    /// Language name.
+    pub language: String,
+    /// Number of files in the language.
+    pub files: usize,
+    /// Number of active rules for the language.
+    pub active_rules: usize,
+    /// Installed packs and learned playbooks for the language.
+    pub playbooks: Vec<String>,
+}
+
+/// Result of `sniff doctor`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    /// Inspected project.
+    pub project_dir: PathBuf,
+    /// Languages present in the project.
+    pub languages: Vec<LanguageCoverage>,
+    /// Test frameworks detected from project manifests.
+    pub frameworks: Vec<String>,
+    /// Coverage gaps, most significant kinds first.
+    pub gaps: Vec<Gap>,
+}
+
+/// Naming conventions of a test framework's test files.
+struct TestFramework {
+    name: &'static str,
+    /// File name suffixes of test files.
+    suffixes: &'static [&'static str],
+    /// File name prefixes of test files, combined with `extensions`.
+    prefixes: &'static [&'static str],
+    /// Directories holding test files, combined with `extensions`.
+    dirs: &'static [&'static str],
+    extensions: &'static [&'static str],
+}
+
+const JS_EXTENSIONS: &[&str] = &["js", "jsx", "ts", "tsx", "mjs", "cjs"];
+const JS_TEST_SUFFIXES: &[&str] = &[
+    ".test.js",
+    ".test.jsx",
+    ".test.ts",
+    ".test.tsx",
+    ".spec.js",
+    ".spec.jsx",
+    ".spec.ts",
+    ".spec.tsx",
+];
+
+impl TestFramework {
+    /// Returns true if `path` follows this framework's test file conventions.
+    fn matches(&self, path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let has_extension = self.extensions.contains(&extension.as_str());
+
+        self.suffixes.iter().any(|suffix| name.ends_with(suffix))
+            || (has_extension && self.prefixes.iter().any(|prefix| name.starts_with(prefix)))
+            || (has_extension
+                && path.components().any(|component| {
+                    self.dirs
+                        .contains(&component.as_os_str().to_string_lossy().as_ref())
+                }))
+    }
+}
+
+/// Detects test frameworks from the manifests at the project root.
+fn detect_frameworks(project_dir: &Path) -> Vec<TestFramework> {
+    let read = |name: &str| std::fs::read_to_string(project_dir.join(name)).unwrap_or_default();
+    let mut frameworks = Vec::new();
+
+    if project_dir.join("Cargo.toml").is_file() {
+        frameworks.push(TestFramework {
+            name: "cargo test",
+            suffixes: &[],
+            prefixes: &[],
+            dirs: &["tests", "benches"],
+            extensions: &["rs"],
+        });
+    }
+    if project_dir.join("go.mod").is_file() {
+        frameworks.push(TestFramework {
+            name: "go test",
+            suffixes: &["_test.go"],
+            prefixes: &[],
+            dirs: &[],
+            extensions: &["go"],
+        });
+    }
+
+    let python_manifests = ["pyproject.toml", "setup.cfg", "tox.ini", "requirements.txt"];
+    if project_dir.join("pytest.ini").is_file()
+        || project_dir.join("conftest.py").is_file()
+        || python_manifests
+            .iter()
+            .any(|name| read(name).contains("pytest"))
+    {
+        frameworks.push(TestFramework {
+            name: "pytest",
+            suffixes: &["_test.py", "conftest.py"],
+            prefixes: &["test_"],
+            dirs: &[],
+            extensions: &["py"],
+        });
+    }
+
+    let package = read("package.json");
+    for (dependency, name) in [
+        ("\"jest\"", "Jest"),
+        ("\"vitest\"", "Vitest"),
+        ("\"mocha\"", "Mocha"),
+    ] {
+        if package.contains(dependency) {
+            frameworks.push(TestFramework {
+                name,
+                suffixes: JS_TEST_SUFFIXES,
+                prefixes: &[],
+                dirs: &["__tests__"],
+                extensions: JS_EXTENSIONS,
+            });
+        }
+    }
+    if package.contains("\"@playwright/test\"") {
+        frameworks.push(TestFramework {
+            name: "Playwright",
+            suffixes: &[".spec.js", ".spec.ts"],
+            prefixes: &[],
+            dirs: &["e2e"],
+            extensions: JS_EXTENSIONS,
+        });
+    }
+    if package.contains("\"cypress\"") {
+        frameworks.push(TestFramework {
+            name: "Cypress",
+            suffixes: &[".cy.js", ".cy.ts"],
+            prefixes: &[],
+            dirs: &["cypress"],
+            extensions: JS_EXTENSIONS,
+        });
+    }
+
+    frameworks
+}
+
+/// Reports test files that follow a framework's conventions but are not
+/// classified as tests.
+fn unclassified_test_gaps(
+    project_dir: &Path,
+    files: &[PathBuf],
+    frameworks: &[TestFramework],
+) -> Vec<Gap> {
+    let relative = |path: &Path| {
+        path.strip_prefix(project_dir)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    let mut gaps = Vec::new();
+    let classifier = TestFileClassifier::new();
+    for framework in frameworks {
+        let test_files: Vec<&PathBuf> = files
+            .iter()
+            .filter(|path| framework.matches(path))
+            .collect();
+        let unclassified: Vec<&PathBuf> = test_files
+            .iter()
+            .copied()
+            .filter(|path| {
+                let content = std::fs::read_to_string(path).unwrap_or_default();
+                !classifier
+                    .classify_file(&path.to_string_lossy(), Some(&content))
+                    .is_test_file
+            })
+            .collect();
+        if let Some(example) = unclassified.first() {
+            gaps.push(Gap {
+                kind: GapKind::UnclassifiedTests,
+                message: format!(
+                    "{} of {} {} test files are not recognized as tests, e.g. {}",
+                    unclassified.len(),
+                    test_files.len(),
+                    framework.name,
+                    relative(example)
+                ),
+                suggestion: "Their detections count toward quality scores and gates; \
+                             exclude them from analysis with --exclude"
+                    .to_string(),
+            });
+        }
+    }
+    gaps
+}
+
+/// Inspects a project and reports rule and language coverage gaps.
+///
+/// Files ignored by `.gitignore` and hidden files are not considered.
+#[must_use]
+pub fn diagnose(project_dir: &Path, analyzer: &MisalignmentAnalyzer) -> DoctorReport {
+    let files = crate::project_config::project_files(project_dir);
+
+    let mut language_files: BTreeMap<&'static str, (SupportedLanguage, usize)> = BTreeMap::new();
+    let mut unsupported_files: BTreeMap<&str, (&str, usize)> = BTreeMap::new();
+    for path in &files {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let language = detect_language_from_path(&path.to_string_lossy())
+            .map(SupportedLanguage::from_agent_language)
+            .or_else(|| SupportedLanguage::from_text_extension(&extension));
+        if let Some(language) = language {
+            language_files
+                .entry(language.name())
+                .or_insert((language, 0))
+                .1 += 1;
+            continue;
+        }
+        if let Some((extension, name)) = UNSUPPORTED_LANGUAGES
+            .iter()
+            .find(|(unsupported, _)| *unsupported == extension)
+        {
+            unsupported_files.entry(extension).or_insert((name, 0)).1 += 1;
+        }
+    }
+
+    let manager = analyzer.playbook_manager();
+    let mut gaps = Vec::new();
+    let mut languages = Vec::new();
+    for (language, files) in language_files.values() {
+        let playbooks: BTreeSet<String> = manager
+            .rule_origins(*language)
+            .into_iter()
+            .filter(|(_, origin, _)| origin.source != RuleSource::BuiltIn)
+            .map(|(_, origin, _)| origin.playbook)
+            .collect();
+        let active_rules = manager.get_active_rules_for_language(*language).len();
+        if playbooks.is_empty() {
+            gaps.push(Gap {
+                kind: GapKind::MissingPlaybook,
+                message: if active_rules == 0 {
+                    format!("{files} {} files have no detection rules", language.name())
+                } else {
+                    format!(
+                        "{files} {} files are only covered by the {active_rules} built-in rules",
+                        language.name()
+                    )
+                },
+                suggestion: format!(
+                    "Add a {} playbook to ~/.sniff/patterns, or learn patterns with \
+                     `sniff patterns create --language {}`",
+                    language.name(),
+                    language.name()
+                ),
+            });
+        }
+        languages.push(LanguageCoverage {
+            language: language.name().to_string(),
+            files: *files,
+            active_rules,
+            playbooks: playbooks.into_iter().collect(),
+        });
+    }
+
+    for (extension, (name, count)) in &unsupported_files {
+        gaps.push(Gap {
+            kind: GapKind::UnsupportedLanguage,
+            message: format!("{count} {name} files (.{extension}) are not analyzed"),
+            suggestion: format!(
+                "sniff has no {name} support; review these files by other means, since \
+                 analysis and verify-todo skip them"
+            ),
+        });
+    }
+
+    let frameworks = detect_frameworks(project_dir);
+    gaps.extend(unclassified_test_gaps(project_dir, &files, &frameworks));
+
+    for language in SupportedLanguage::ALL {
+        if language_files.contains_key(language.name()) {
+            continue;
+        }
+        let packs: BTreeSet<String> = manager
+            .rule_origins(language)
+            .into_iter()
+            .filter(|(_, origin, _)| origin.source == RuleSource::Pack)
+            .map(|(_, origin, _)| origin.playbook)
+            .collect();
+        for pack in packs {
+            gaps.push(Gap {
+                kind: GapKind::UnusedPack,
+                message: format!(
+                    "Pack '{pack}' ({}) matches no files in this project",
+                    language.name()
+                ),
+                suggestion: "Remove it from ~/.sniff/patterns if none of your projects use the \
+                             language"
+                    .to_string(),
+            });
+        }
+    }
+
+    let plugin_failures = analyzer.plugin_load_failures();
+    for (path, error) in analyzer
+        .playbook_load_failures()
+        .iter()
+        .chain(plugin_failures)
+    {
+        gaps.push(Gap {
+            kind: GapKind::BrokenPlaybook,
+            message: format!("{} failed to load: {error}", path.display()),
+            suggestion: "Fix or remove the file; its rules are not applied".to_string(),
+        });
+    }
+
+    DoctorReport {
+        project_dir: project_dir.to_path_buf(),
+        languages,
+        frameworks: frameworks
+            .iter()
+            .map(|framework| framework.name.to_string())
+            .collect(),
+        gaps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framework_conventions() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("go.mod"), "module example.com/app\n").unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"devDependencies": {"vitest": "^1.0.0"}}"#,
+        )
+        .unwrap();
+
+        let frameworks = detect_frameworks(dir.path());
+        let names: Vec<&str> = frameworks.iter().map(|framework| framework.name).collect();
+        assert_eq!(names, vec!["go test", "Vitest"]);
+
+        assert!(frameworks[0].matches(Path::new("pkg/store/store_test.go")));
+        assert!(!frameworks[0].matches(Path::new("pkg/store/store.go")));
+        assert!(frameworks[1].matches(Path::new("src/Button.test.tsx")));
+        assert!(frameworks[1].matches(Path::new("src/__tests__/button.ts")));
+        assert!(!frameworks[1].matches(Path::new("src/__tests__/README.md")));
+    }
+
+    #[test]
+    fn test_diagnose_reports_gaps() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.py"), "print('hi')\n").unwrap();
+        std::fs::write(dir.path().join("App.java"), "class App {}\n").unwrap();
+
+        let analyzer = MisalignmentAnalyzer::new().unwrap();
+        let report = diagnose(dir.path(), &analyzer);
+
+        assert_eq!(report.languages.len(), 1);
+        assert_eq!(report.languages[0].language, "python");
+        let kinds: Vec<GapKind> = report.gaps.iter().map(|gap| gap.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![GapKind::MissingPlaybook, GapKind::UnsupportedLanguage]
+        );
+        assert!(report.gaps[1].message.contains("Java"), "{:?}", report.gaps);
+    }
+}
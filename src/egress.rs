@@ -0,0 +1,186 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Network egress audit of agent sessions.
+//!
+//! Collects the external endpoints an agent touched: `WebFetch` URLs,
+//! `WebSearch` queries and URLs embedded in `Bash` commands (curl, wget,
+//! git clone, package installs from URLs). Events are summarized per domain
+//! for security review.
+
+use crate::session::Session;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::LazyLock;
+
+/// URLs with an explicit scheme or scp-style git remotes, capturing the host.
+static URL_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(?:(?:https?|wss?|ftp|ssh|git)://(?:[^\s/@'"`]+@)?|git@)([a-z0-9.-]+\.[a-z]{2,}|\[[0-9a-f:]+\]|\d{1,3}(?:\.\d{1,3}){3})(?::\d+)?[^\s'"`)<>]*"#)
+        .expect("URL pattern is valid")
+});
+
+/// Network access by an agent.
+#[derive(Debug, Clone, Serialize)]
+pub struct EgressEvent {
+    /// Session the access happened in.
+    pub session_id: String,
+    /// 1-based position of the tool use within the session.
+    pub step: usize,
+    /// When the tool use was issued.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Tool that made the access (`WebFetch`, `WebSearch`, `Bash`).
+    pub tool: String,
+    /// URL contacted, if known.
+    pub url: Option<String>,
+    /// Domain contacted, if known. Web searches have no domain.
+    pub domain: Option<String>,
+    /// Search query, for `WebSearch`.
+    pub query: Option<String>,
+}
+
+/// Egress totals for one domain.
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainSummary {
+    /// Domain contacted.
+    pub domain: String,
+    /// Number of accesses.
+    pub requests: usize,
+    /// Number of distinct sessions that contacted the domain.
+    pub sessions: usize,
+    /// Tools used to contact the domain.
+    pub tools: BTreeSet<String>,
+    /// First access.
+    pub first_seen: Option<DateTime<Utc>>,
+    /// Most recent access.
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// Extracts the network accesses of a session in transcript order.
+#[must_use]
+pub fn egress_events(session: &Session) -> Vec<EgressEvent> {
+    let mut events = Vec::new();
+    for tool_use in session.tool_uses() {
+        let event = |url: Option<&str>, query: Option<&str>| EgressEvent {
+            session_id: session.id.clone(),
+            step: tool_use.step,
+            timestamp: tool_use.timestamp,
+            tool: tool_use.name.to_string(),
+            url: url.map(String::from),
+            domain: url.and_then(domain_of),
+            query: query.map(String::from),
+        };
+        let input = |key: &str| tool_use.input.get(key).and_then(Value::as_str);
+
+        match tool_use.name {
+            "WebFetch" => events.push(event(input("url"), None)),
+            "WebSearch" => events.push(event(None, input("query"))),
+            "Bash" => {
+                if let Some(command) = input("command") {
+                    events.extend(
+                        URL_PATTERN
+                            .find_iter(command)
+                            .map(|url| event(Some(url.as_str()), None)),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+/// Returns the lowercase host of a URL.
+fn domain_of(url: &str) -> Option<String> {
+    URL_PATTERN
+        .captures(url)
+        .and_then(|captures| captures.get(1))
+        .map(|host| host.as_str().trim_end_matches('.').to_lowercase())
+}
+
+/// Summarizes egress events per domain, most contacted first.
+#[must_use]
+pub fn summarize_domains(events: &[EgressEvent]) -> Vec<DomainSummary> {
+    let mut by_domain: HashMap<&str, (DomainSummary, BTreeSet<&str>)> = HashMap::new();
+    for event in events {
+        let Some(domain) = event.domain.as_deref() else {
+            continue;
+        };
+        let (summary, sessions) = by_domain.entry(domain).or_insert_with(|| {
+            (
+                DomainSummary {
+                    domain: domain.to_string(),
+                    requests: 0,
+                    sessions: 0,
+                    tools: BTreeSet::new(),
+                    first_seen: None,
+                    last_seen: None,
+                },
+                BTreeSet::new(),
+            )
+        });
+        summary.requests += 1;
+        summary.tools.insert(event.tool.clone());
+        sessions.insert(&event.session_id);
+        if let Some(timestamp) = event.timestamp {
+            if summary.first_seen.is_none_or(|first| timestamp < first) {
+                summary.first_seen = Some(timestamp);
+            }
+            if summary.last_seen.is_none_or(|last| timestamp > last) {
+                summary.last_seen = Some(timestamp);
+            }
+        }
+    }
+
+    let mut summaries: Vec<DomainSummary> = by_domain
+        .into_values()
+        .map(|(mut summary, sessions)| {
+            summary.sessions = sessions.len();
+            summary
+        })
+        .collect();
+    summaries.sort_by(|a, b| {
+        b.requests
+            .cmp(&a.requests)
+            .then_with(|| a.domain.cmp(&b.domain))
+    });
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::test_support;
+
+    #[test]
+    fn test_egress_events_and_domains() {
+        let lines = [serde_json::json!({
+            "type": "assistant",
+            "timestamp": "2025-06-01T10:00:00Z",
+            "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t1", "name": "WebFetch",
+                 "input": {"url": "https://docs.rs/regex/latest/regex/", "prompt": "summarize"}},
+                {"type": "tool_use", "id": "t2", "name": "WebSearch", "input": {"query": "regex crate lookahead"}},
+                {"type": "tool_use", "id": "t3", "name": "Bash",
+                 "input": {"command": "curl -s https://Docs.rs/crate && git clone git@github.com:a/b.git && wget http://10.0.0.5:8080/x.sh"}}
+            ]}
+        })];
+        let session = test_support::session(&lines);
+
+        let events = egress_events(&session);
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[1].query.as_deref(), Some("regex crate lookahead"));
+
+        let domains = summarize_domains(&events);
+        assert_eq!(domains[0].domain, "docs.rs");
+        assert_eq!(domains[0].requests, 2);
+        assert_eq!(domains[0].sessions, 1);
+        assert_eq!(
+            domains[0].tools,
+            BTreeSet::from(["Bash".to_string(), "WebFetch".to_string()])
+        );
+        assert_eq!(domains[1].domain, "10.0.0.5");
+    }
+}
@@ -123,6 +123,38 @@ pub enum SniffError {
         /// The reason for the analysis failure.
         reason: String,
     },
+
+    /// An operation that writes to disk was requested in read-only mode.
+    #[error("{operation} is not available in read-only mode")]
+    ReadOnly {
+        /// The operation that was refused.
+        operation: String,
+    },
+
+    /// A network operation was requested in offline mode.
+    #[error("{operation} is not available in offline mode")]
+    Offline {
+        /// The operation that was refused.
+        operation: String,
+    },
+
+    /// A download or other network request failed.
+    #[error("Network error for '{url}': {reason}")]
+    Network {
+        /// The requested URL.
+        url: String,
+        /// The reason for the failure.
+        reason: String,
+    },
+
+    /// Another sniff process holds the lock on a shared resource.
+    #[error("Another sniff process is running and holds the lock on {resource} ('{path}'); try again when it finishes")]
+    Locked {
+        /// The locked resource.
+        resource: String,
+        /// The lock file.
+        path: PathBuf,
+    },
 }
 
 impl SniffError {
@@ -224,6 +256,36 @@ impl SniffError {
             reason: reason.into(),
         }
     }
+
+    /// Creates a new read-only mode error.
+    pub fn read_only(operation: impl Into<String>) -> Self {
+        Self::ReadOnly {
+            operation: operation.into(),
+        }
+    }
+
+    /// Creates a new offline mode error.
+    pub fn offline(operation: impl Into<String>) -> Self {
+        Self::Offline {
+            operation: operation.into(),
+        }
+    }
+
+    /// Creates a new network error.
+    pub fn network(url: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Network {
+            url: url.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Creates a new error for a resource locked by another sniff process.
+    pub fn locked(resource: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self::Locked {
+            resource: resource.into(),
+            path: path.into(),
+        }
+    }
 }
 
 // Automatic conversions from common error types
@@ -244,6 +306,14 @@ impl From<std::io::Error> for SniffError {
     }
 }
 
+impl From<rusqlite::Error> for SniffError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::StorageError {
+            reason: format!("SQLite operation failed: {error}"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,203 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! File-access audit trail of agent sessions.
+//!
+//! Lists every file an agent read, wrote, edited or searched, with paths
+//! normalized against the session's project root. Access outside the
+//! workspace and access to credential files (`~/.ssh`, `.env`, cloud
+//! credentials, private keys) is flagged.
+
+use crate::session::Session;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+
+/// Path fragments that identify credential and secret stores, with the reason they are flagged.
+const SENSITIVE_PATHS: &[(&str, &str)] = &[
+    ("/.ssh/", "SSH keys and config"),
+    ("/.aws/", "AWS credentials"),
+    ("/.gnupg/", "GPG keyring"),
+    ("/.kube/config", "Kubernetes credentials"),
+    ("/.docker/config.json", "Docker registry credentials"),
+    ("/.config/gcloud/", "Google Cloud credentials"),
+    ("/.azure/", "Azure credentials"),
+    ("/etc/shadow", "system password hashes"),
+    ("/etc/sudoers", "sudo configuration"),
+];
+
+/// File names that hold secrets, with the reason they are flagged.
+const SENSITIVE_FILE_NAMES: &[(&str, &str)] = &[
+    (".netrc", "stored login credentials"),
+    (".npmrc", "package registry token"),
+    (".pypirc", "package registry token"),
+    (".git-credentials", "stored git credentials"),
+    ("credentials.json", "service credentials"),
+    ("id_rsa", "private SSH key"),
+    ("id_ed25519", "private SSH key"),
+    ("id_ecdsa", "private SSH key"),
+];
+
+/// One file access by an agent.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileAccess {
+    /// Session the access happened in.
+    pub session_id: String,
+    /// 1-based position of the tool use within the session.
+    pub step: usize,
+    /// When the tool use was issued.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Tool that accessed the file.
+    pub tool: String,
+    /// Kind of access: `read`, `write`, `edit` or `search`.
+    pub operation: String,
+    /// Normalized absolute path, or the path as given when no project root is known.
+    pub path: String,
+    /// Path relative to the project root, when inside the workspace.
+    pub relative_path: Option<String>,
+    /// Whether the path lies outside the project root.
+    pub outside_workspace: bool,
+    /// Why the path is sensitive, if it is.
+    pub sensitive: Option<String>,
+}
+
+impl FileAccess {
+    /// Returns true if the access is outside the workspace or touches a sensitive path.
+    #[must_use]
+    pub fn is_flagged(&self) -> bool {
+        self.outside_workspace || self.sensitive.is_some()
+    }
+}
+
+/// Returns every file access of a session in transcript order.
+#[must_use]
+pub fn file_accesses(session: &Session) -> Vec<FileAccess> {
+    let project_dir = session.project_dir();
+    let home_dir = dirs::home_dir();
+
+    let mut accesses = Vec::new();
+    for tool_use in session.tool_uses() {
+        let (operation, key) = match tool_use.name {
+            "Read" => ("read", "file_path"),
+            "Write" => ("write", "file_path"),
+            "Edit" | "MultiEdit" => ("edit", "file_path"),
+            "NotebookEdit" => ("edit", "notebook_path"),
+            "Glob" | "Grep" | "LS" => ("search", "path"),
+            _ => continue,
+        };
+        let Some(raw_path) = tool_use.input.get(key).and_then(Value::as_str) else {
+            continue;
+        };
+
+        let path = normalize(raw_path, project_dir.as_deref(), home_dir.as_deref());
+        let relative_path = project_dir
+            .as_deref()
+            .and_then(|root| path.strip_prefix(root).ok())
+            .map(|relative| relative.to_string_lossy().to_string());
+        let outside_workspace =
+            project_dir.is_some() && path.is_absolute() && relative_path.is_none();
+
+        accesses.push(FileAccess {
+            session_id: session.id.clone(),
+            step: tool_use.step,
+            timestamp: tool_use.timestamp,
+            tool: tool_use.name.to_string(),
+            operation: operation.to_string(),
+            sensitive: sensitive_reason(&path).map(String::from),
+            path: path.to_string_lossy().to_string(),
+            relative_path,
+            outside_workspace,
+        });
+    }
+    accesses
+}
+
+/// Resolves `~` and relative paths against the project root and removes `.` and `..` components.
+fn normalize(raw_path: &str, project_dir: Option<&Path>, home_dir: Option<&Path>) -> PathBuf {
+    let expanded = match (raw_path.strip_prefix("~/"), home_dir) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ if raw_path == "~" => home_dir.map_or_else(|| PathBuf::from(raw_path), Path::to_path_buf),
+        _ => PathBuf::from(raw_path),
+    };
+    let absolute = match project_dir {
+        Some(root) if expanded.is_relative() => root.join(expanded),
+        _ => expanded,
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Returns why a path is sensitive, if it matches a known secret store.
+fn sensitive_reason(path: &Path) -> Option<&'static str> {
+    let path_str = path.to_string_lossy().replace('\\', "/");
+    if let Some((_, reason)) = SENSITIVE_PATHS
+        .iter()
+        .find(|(fragment, _)| path_str.contains(fragment))
+    {
+        return Some(reason);
+    }
+
+    let file_name = path.file_name()?.to_string_lossy();
+    if file_name == ".env" || (file_name.starts_with(".env.") && !file_name.ends_with(".example")) {
+        return Some("environment secrets");
+    }
+    if file_name.ends_with(".pem") || file_name.ends_with(".p12") || file_name.ends_with(".key") {
+        return Some("private key or certificate");
+    }
+    SENSITIVE_FILE_NAMES
+        .iter()
+        .find(|(name, _)| file_name == *name)
+        .map(|(_, reason)| *reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_and_flag_paths() {
+        let root = Path::new("/work/project");
+        let home = Path::new("/home/dev");
+
+        assert_eq!(
+            normalize("src/../lib.rs", Some(root), Some(home)),
+            PathBuf::from("/work/project/lib.rs")
+        );
+        assert_eq!(
+            normalize("~/.ssh/id_rsa", Some(root), Some(home)),
+            PathBuf::from("/home/dev/.ssh/id_rsa")
+        );
+        assert_eq!(
+            normalize("/work/project/./a/../../other/x", Some(root), Some(home)),
+            PathBuf::from("/work/other/x")
+        );
+
+        assert_eq!(
+            sensitive_reason(Path::new("/home/dev/.ssh/config")),
+            Some("SSH keys and config")
+        );
+        assert_eq!(
+            sensitive_reason(Path::new("/work/project/.env")),
+            Some("environment secrets")
+        );
+        assert_eq!(
+            sensitive_reason(Path::new("/work/project/.env.example")),
+            None
+        );
+        assert_eq!(
+            sensitive_reason(Path::new("/work/project/src/env.rs")),
+            None
+        );
+    }
+}
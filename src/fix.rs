@@ -0,0 +1,339 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Textual fixes for detections of rules with a `replacement`.
+//!
+//! `analyze-files --fix` replaces the text each detection matched with its
+//! rule's `remediation.replacement`, expanding capture groups of the rule's
+//! regex. A fix is only applied if the rule's regex still matches at the
+//! detection's position, so files edited since the analysis are left alone
+//! where they changed. A replacement that reproduces the matched text is not a
+//! fix. Overlapping fixes on a line keep the rightmost one, and a line left
+//! blank by a fix is removed.
+
+use crate::analysis::MisalignmentDetection;
+use crate::error::{Result, SniffError};
+use crate::playbook::{DetectionRule, PatternType, PlaybookManager};
+use crate::standalone::AnalysisResults;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A fix applied to one line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LineFix {
+    /// 1-based line number before fixing.
+    pub line: usize,
+    /// Rules whose detections were fixed on the line.
+    pub rule_ids: Vec<String>,
+    /// The line before fixing.
+    pub before: String,
+    /// The line after fixing; `None` if the line was removed.
+    pub after: Option<String>,
+}
+
+/// Fixes applied to a file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileFixes {
+    /// Fixed file.
+    pub path: PathBuf,
+    /// Fixed lines, in line order.
+    pub lines: Vec<LineFix>,
+    /// Number of detections fixed.
+    pub fixed: usize,
+    /// Number of fixable detections that no longer matched or overlapped
+    /// another fix.
+    pub skipped: usize,
+}
+
+/// A single replacement within a line.
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+    rule_id: String,
+}
+
+/// Returns the regex and replacement of a rule that supports fixes.
+fn fix_of(rule: &DetectionRule) -> Option<(&str, &str)> {
+    let replacement = rule.remediation.as_ref()?.replacement.as_deref()?;
+    match &rule.pattern_type {
+        PatternType::Regex {
+            pattern,
+            multiline: false,
+            ..
+        } => Some((pattern, replacement)),
+        _ => None,
+    }
+}
+
+/// Computes the fixes of a file's detections, returning the fixed content and
+/// the fixes made. `rule` looks up the rule of a detection.
+///
+/// # Errors
+///
+/// Returns an error if a rule's regex does not compile.
+pub fn fix_content<'a>(
+    path: &Path,
+    content: &str,
+    detections: &[MisalignmentDetection],
+    rule: impl Fn(&str) -> Option<&'a DetectionRule>,
+) -> Result<(String, FileFixes)> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut edits: BTreeMap<usize, Vec<Edit>> = BTreeMap::new();
+    let mut skipped = 0;
+
+    for detection in detections {
+        let Some((pattern, replacement)) = rule(&detection.rule_id).and_then(fix_of) else {
+            continue;
+        };
+        let regex = Regex::new(pattern).map_err(|e| {
+            SniffError::analysis_error(format!(
+                "Invalid regex in rule '{}': {}",
+                detection.rule_id, e
+            ))
+        })?;
+
+        let index = detection.line_number.saturating_sub(1);
+        let text = lines.get(index).map_or("", |line| strip_newline(line));
+        let start = detection.column_number.saturating_sub(1);
+        let captures = text
+            .is_char_boundary(start)
+            .then(|| regex.captures_at(text, start))
+            .flatten();
+        let Some((end, captures)) = captures.and_then(|captures| {
+            let found = captures.get(0)?;
+            (found.start() == start).then_some((found.end(), captures))
+        }) else {
+            skipped += 1;
+            continue;
+        };
+
+        let mut replaced = String::new();
+        captures.expand(replacement, &mut replaced);
+        // Rules leave forms they cannot fix safely by replacing them with themselves
+        if replaced == text[start..end] {
+            continue;
+        }
+        // The same rule can be loaded from several playbooks
+        let line_edits = edits.entry(index).or_default();
+        if line_edits
+            .iter()
+            .any(|edit| (edit.start, edit.end, &edit.text) == (start, end, &replaced))
+        {
+            continue;
+        }
+        line_edits.push(Edit {
+            start,
+            end,
+            text: replaced,
+            rule_id: detection.rule_id.clone(),
+        });
+    }
+
+    let mut fixed_lines = Vec::new();
+    let mut applied = 0;
+    let mut output = String::with_capacity(content.len());
+    for (index, line) in lines.iter().enumerate() {
+        let Some(mut line_edits) = edits.remove(&index) else {
+            output.push_str(line);
+            continue;
+        };
+
+        // Apply right to left so earlier offsets stay valid
+        line_edits.sort_by(|a, b| b.start.cmp(&a.start).then(b.end.cmp(&a.end)));
+        let before = strip_newline(line);
+        let mut after = before.to_string();
+        let mut rule_ids = Vec::new();
+        let mut applied_from = usize::MAX;
+        for edit in line_edits {
+            if edit.end > applied_from {
+                skipped += 1;
+                continue;
+            }
+            after.replace_range(edit.start..edit.end, &edit.text);
+            applied_from = edit.start;
+            applied += 1;
+            if !rule_ids.contains(&edit.rule_id) {
+                rule_ids.push(edit.rule_id);
+            }
+        }
+        rule_ids.reverse();
+
+        let removed = after.trim().is_empty() && !before.trim().is_empty();
+        if !removed {
+            output.push_str(&after);
+            output.push_str(&line[before.len()..]);
+        }
+        fixed_lines.push(LineFix {
+            line: index + 1,
+            rule_ids,
+            before: before.to_string(),
+            after: (!removed).then_some(after),
+        });
+    }
+
+    let file_fixes = FileFixes {
+        path: path.to_path_buf(),
+        lines: fixed_lines,
+        fixed: applied,
+        skipped,
+    };
+    Ok((output, file_fixes))
+}
+
+fn strip_newline(line: &str) -> &str {
+    line.strip_suffix('\n')
+        .map_or(line, |line| line.strip_suffix('\r').unwrap_or(line))
+}
+
+/// Fixes the detections in analysis results, writing the fixed files unless
+/// `dry_run` is set. Returns the files with fixes.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be read or written, or a rule's regex
+/// does not compile.
+pub fn fix_results(
+    results: &AnalysisResults,
+    playbooks: &PlaybookManager,
+    dry_run: bool,
+) -> Result<Vec<FileFixes>> {
+    let mut fixes = Vec::new();
+    for file in &results.file_results {
+        let rules: Vec<&DetectionRule> = match file.language {
+            Some(language) => playbooks.get_active_rules_for_language(language),
+            None => Vec::new(),
+        };
+        let lookup = |rule_id: &str| {
+            rules
+                .iter()
+                .copied()
+                .find(|rule| rule.id == rule_id)
+                .or_else(|| playbooks.get_rule(rule_id))
+        };
+        if !file
+            .detections
+            .iter()
+            .any(|detection| lookup(&detection.rule_id).and_then(fix_of).is_some())
+        {
+            continue;
+        }
+
+        let path: &Path = &file.file_path;
+        let content =
+            std::fs::read_to_string(path).map_err(|e| SniffError::file_system(path, e))?;
+        let (fixed_content, file_fixes) = fix_content(path, &content, &file.detections, lookup)?;
+        if file_fixes.fixed > 0 && !dry_run {
+            std::fs::write(path, fixed_content).map_err(|e| SniffError::file_system(path, e))?;
+        }
+        fixes.push(file_fixes);
+    }
+    Ok(fixes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support;
+    use crate::playbook::{Remediation, Severity};
+
+    fn rule(id: &str, pattern: &str, replacement: &str) -> DetectionRule {
+        DetectionRule {
+            remediation: Some(Remediation {
+                explanation: String::new(),
+                example: None,
+                replacement: Some(replacement.to_string()),
+            }),
+            ..test_support::rule(id, pattern)
+        }
+    }
+
+    /// Returns a detection of `rule_id` matching at `column_number`.
+    fn detection(rule_id: &str, line_number: usize, column_number: usize) -> MisalignmentDetection {
+        MisalignmentDetection {
+            column_number,
+            ..test_support::detection(rule_id, Severity::Low, line_number)
+        }
+    }
+
+    #[test]
+    fn test_fix_content() {
+        let rules = [
+            rule("unwrap", r"\.unwrap\(\)", r#".expect("value")"#),
+            rule("dbg", r"^\s*dbg!\((\w+)\);$", ""),
+        ];
+        let lookup = |id: &str| rules.iter().find(|rule| rule.id == id);
+        let content = "let a = b.unwrap();\r\n    dbg!(a);\nlet c = d.unwrap();\n";
+
+        let detections = [
+            detection("unwrap", 1, 10),
+            detection("dbg", 2, 1),
+            // The line changed since the analysis
+            detection("unwrap", 3, 4),
+        ];
+        let (output, file_fixes) =
+            fix_content(Path::new("src/lib.rs"), content, &detections, lookup).unwrap();
+        assert_eq!(
+            output,
+            "let a = b.expect(\"value\");\r\nlet c = d.unwrap();\n"
+        );
+        assert_eq!((file_fixes.fixed, file_fixes.skipped), (2, 1));
+        let lines = &file_fixes.lines;
+        assert_eq!(
+            lines[0].after.as_deref(),
+            Some("let a = b.expect(\"value\");")
+        );
+        assert_eq!(lines[1].before, "    dbg!(a);");
+        assert_eq!(lines[1].after, None);
+    }
+
+    #[tokio::test]
+    async fn test_shipped_rust_fixes() {
+        use crate::analysis::MisalignmentAnalyzer;
+        use crate::standalone::{AnalysisConfig, StandaloneAnalyzer};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let playbooks = temp_dir.path().join("playbooks");
+        std::fs::create_dir(&playbooks).unwrap();
+        std::fs::write(
+            playbooks.join("rust-patterns.yaml"),
+            include_str!("../playbooks/rust-patterns.yaml"),
+        )
+        .unwrap();
+        let source = temp_dir.path().join("loader.rs");
+        std::fs::write(
+            &source,
+            "fn load(path: &str) -> usize {\n\
+             \x20   let text = std::fs::read_to_string(path).unwrap();\n\
+             \x20   dbg!(&text);\n\
+             \x20   let size = dbg!(text.len());\n\
+             \x20   dbg!(size + 1) * 2\n\
+             }\n",
+        )
+        .unwrap();
+
+        let mut analyzer = MisalignmentAnalyzer::new().unwrap();
+        analyzer.load_playbooks(&playbooks).unwrap();
+        let mut analyzer = StandaloneAnalyzer::new(analyzer, AnalysisConfig::default());
+        let results = analyzer
+            .analyze_files(std::slice::from_ref(&source))
+            .await
+            .unwrap();
+        let fixes = fix_results(&results, analyzer.playbook_manager(), false).unwrap();
+
+        // dbg! of an operator expression is left alone, as removing it could
+        // change precedence
+        assert_eq!(
+            std::fs::read_to_string(&source).unwrap(),
+            "fn load(path: &str) -> usize {\n\
+             \x20   let text = std::fs::read_to_string(path)\
+             .expect(\"explain why this cannot fail\");\n\
+             \x20   let size = text.len();\n\
+             \x20   dbg!(size + 1) * 2\n\
+             }\n"
+        );
+        assert_eq!((fixes[0].fixed, fixes[0].skipped), (3, 0));
+    }
+}
@@ -0,0 +1,609 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Function-level quality.
+//!
+//! Reviewers think in functions rather than files, so detections are also
+//! scored per function: each detection counts toward the innermost function
+//! or method containing its line, and the function's quality score is
+//! computed from them like a file's. Ranking functions by detection density
+//! and complexity gives a refactoring queue.
+//!
+//! Complexity is cyclomatic: one plus the decision points read from the
+//! file's syntax tree. Decision points are conditionals, loops, `match` arms
+//! after the first, `switch` cases other than `default`, exception handlers,
+//! Rust's `?` and the short-circuit operators `&&`, `||` and `??`. Comments
+//! and string literals never count.
+//!
+//! Comparing against a checkpoint narrows this to the changed functions. A
+//! checkpoint records a hash of every line of its text files, so the lines
+//! changed since then can be found without storing the code. Checkpoints
+//! created by `analyze-files --checkpoint` also record the function scores,
+//! which gives each changed function a quality delta. Comparing against a
+//! git revision takes the changed lines from `git diff` instead, and the
+//! deltas from analyzing the files as of the revision.
+
+use crate::analysis::{MisalignmentDetection, SeverityPenalties};
+use crate::ast_query;
+use crate::hash::stable_hash_u64;
+use crate::standalone::FileAnalysisResult;
+use rust_tree_sitter::analyzer::Symbol;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Largest diff table, in cells, before a changed region counts as changed
+/// as a whole.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Quality of one function or method.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionScore {
+    /// Function name.
+    pub name: String,
+    /// Symbol kind (`function` or `method`).
+    pub kind: String,
+    /// First line of the function (1-based).
+    pub start_line: usize,
+    /// Last line of the function (1-based, inclusive).
+    pub end_line: usize,
+    /// Number of detections in the function.
+    pub detections: usize,
+    /// Cyclomatic complexity: one plus the decision points in the function.
+    #[serde(default)]
+    pub complexity: usize,
+    /// Quality score of the function (0-100).
+    pub quality_score: f64,
+}
+
+impl FunctionScore {
+    /// Number of lines in the function.
+    #[must_use]
+    pub fn lines(&self) -> usize {
+        self.end_line.saturating_sub(self.start_line) + 1
+    }
+
+    /// Detections per 100 lines of the function.
+    #[must_use]
+    pub fn detection_density(&self) -> f64 {
+        self.detections as f64 * 100.0 / self.lines() as f64
+    }
+
+    /// Orders functions worst first: by detection density, then complexity,
+    /// then quality score.
+    #[must_use]
+    pub fn worst_first(&self, other: &Self) -> Ordering {
+        other
+            .detection_density()
+            .total_cmp(&self.detection_density())
+            .then(other.complexity.cmp(&self.complexity))
+            .then(self.quality_score.total_cmp(&other.quality_score))
+    }
+}
+
+/// A function and the file containing it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileFunction {
+    /// File containing the function.
+    pub file_path: PathBuf,
+    /// Quality of the function.
+    #[serde(flatten)]
+    pub function: FunctionScore,
+}
+
+/// Returns the `limit` worst functions, worst first, skipping functions
+/// without detections or branches.
+#[must_use]
+pub fn worst_functions(mut functions: Vec<FileFunction>, limit: usize) -> Vec<FileFunction> {
+    functions.retain(|entry| entry.function.detections > 0 || entry.function.complexity > 1);
+    functions.sort_by(|a, b| {
+        a.function
+            .worst_first(&b.function)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then(a.function.start_line.cmp(&b.function.start_line))
+    });
+    functions.truncate(limit);
+    functions
+}
+
+/// A function whose lines changed since a checkpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangedFunction {
+    /// File containing the function.
+    pub file_path: PathBuf,
+    /// Current quality of the function.
+    #[serde(flatten)]
+    pub function: FunctionScore,
+    /// Number of added or modified lines in the function.
+    pub changed_lines: usize,
+    /// Quality score at the checkpoint; absent for new functions and for
+    /// checkpoints that did not record function scores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_quality_score: Option<f64>,
+    /// Change of the quality score since the checkpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality_delta: Option<f64>,
+}
+
+/// Scores one function or method of an analyzed file from the detections
+/// within the function's lines. `content` is the file's content, which the
+/// complexity is read from.
+///
+/// Detections in nested functions count toward this function as well; use
+/// [`score_functions`] to attribute them to the innermost function.
+#[must_use]
+pub fn score_function(
+    file: &FileAnalysisResult,
+    symbol: &Symbol,
+    content: &str,
+    penalties: &SeverityPenalties,
+) -> FunctionScore {
+    let detections: Vec<MisalignmentDetection> = file
+        .detections
+        .iter()
+        .filter(|detection| (symbol.start_line..=symbol.end_line).contains(&detection.line_number))
+        .cloned()
+        .collect();
+    let decisions = decision_lines(file, content);
+    function_score(symbol, &decisions, &detections, penalties)
+}
+
+/// Scores the functions and methods of an analyzed file, whose content is
+/// `content`. Each detection counts toward the innermost function containing it.
+#[must_use]
+pub fn score_functions(
+    file: &FileAnalysisResult,
+    symbols: &[Symbol],
+    content: &str,
+    penalties: &SeverityPenalties,
+) -> Vec<FunctionScore> {
+    let functions: Vec<&Symbol> = symbols
+        .iter()
+        .filter(|symbol| symbol.kind == "function" || symbol.kind == "method")
+        .filter(|symbol| symbol.start_line <= symbol.end_line)
+        .collect();
+    if functions.is_empty() {
+        return Vec::new();
+    }
+    let decisions = decision_lines(file, content);
+    let mut grouped: Vec<Vec<MisalignmentDetection>> = vec![Vec::new(); functions.len()];
+    for detection in &file.detections {
+        let innermost = functions
+            .iter()
+            .enumerate()
+            .filter(|(_, function)| {
+                (function.start_line..=function.end_line).contains(&detection.line_number)
+            })
+            .min_by_key(|(_, function)| function.end_line - function.start_line);
+        if let Some((index, _)) = innermost {
+            grouped[index].push(detection.clone());
+        }
+    }
+
+    functions
+        .into_iter()
+        .zip(grouped)
+        .map(|(function, detections)| function_score(function, &decisions, &detections, penalties))
+        .collect()
+}
+
+/// Scores a function from the file's sorted decision lines and the
+/// detections attributed to it.
+fn function_score(
+    symbol: &Symbol,
+    decisions: &[usize],
+    detections: &[MisalignmentDetection],
+    penalties: &SeverityPenalties,
+) -> FunctionScore {
+    let start = decisions.partition_point(|&line| line < symbol.start_line);
+    let end = decisions.partition_point(|&line| line <= symbol.end_line);
+    FunctionScore {
+        name: symbol.name.clone(),
+        kind: symbol.kind.clone(),
+        start_line: symbol.start_line,
+        end_line: symbol.end_line,
+        detections: detections.len(),
+        complexity: 1 + end.saturating_sub(start),
+        quality_score: penalties.score(detections),
+    }
+}
+
+/// Syntax nodes that are always decision points, across the grammars of the
+/// languages with functions.
+const DECISION_NODES: &[&str] = &[
+    // Conditionals
+    "if_expression",
+    "if_statement",
+    "elif_clause",
+    "if_clause",
+    "conditional_expression",
+    "ternary_expression",
+    // Loops
+    "while_expression",
+    "while_statement",
+    "do_statement",
+    "for_expression",
+    "for_statement",
+    "for_in_statement",
+    "for_in_clause",
+    "for_range_loop",
+    // Cases
+    "switch_case",
+    "case_clause",
+    "expression_case",
+    "type_case",
+    "communication_case",
+    // Error handling
+    "catch_clause",
+    "except_clause",
+    "try_expression",
+    // Python's `and` and `or`
+    "boolean_operator",
+];
+
+/// Returns the 1-based line of each decision point in an analyzed file,
+/// sorted, or none if the file cannot be parsed.
+fn decision_lines(file: &FileAnalysisResult, content: &str) -> Vec<usize> {
+    let Some(language) = file.language else {
+        return Vec::new();
+    };
+    let tree = match ast_query::parse(language, &file.file_path, content) {
+        Ok(tree) => tree,
+        Err(e) => {
+            debug!("Skipping complexity of {}: {}", file.file_path.display(), e);
+            return Vec::new();
+        }
+    };
+
+    let mut lines = Vec::new();
+    let mut nodes = vec![tree.root_node()];
+    while let Some(node) = nodes.pop() {
+        let mut cursor = node.walk();
+        let decision = match node.kind() {
+            kind if DECISION_NODES.contains(&kind) => true,
+            // `default:` is a case without a value
+            "case_statement" => node.child_by_field_name("value").is_some(),
+            "binary_expression" => node
+                .child_by_field_name("operator")
+                .is_some_and(|operator| matches!(operator.kind(), "&&" | "||" | "??")),
+            _ => false,
+        };
+        if decision {
+            lines.push(node.start_position().row + 1);
+        }
+        // Each arm after the first adds a path through a Rust `match`
+        if node.kind() == "match_block" {
+            lines.extend(
+                node.named_children(&mut cursor)
+                    .filter(|arm| arm.kind() == "match_arm")
+                    .skip(1)
+                    .map(|arm| arm.start_position().row + 1),
+            );
+        }
+        nodes.extend(node.named_children(&mut cursor));
+    }
+    lines.sort_unstable();
+    lines
+}
+
+/// Hashes each line of `content`, ignoring trailing whitespace.
+#[must_use]
+pub fn line_hashes(content: &str) -> Vec<u64> {
+    content
+        .lines()
+        .map(|line| stable_hash_u64([line.trim_end()]))
+        .collect()
+}
+
+/// Returns the 1-based lines of `content` added or modified since the lines
+/// hashed in `previous`. Where lines were only deleted, the line following
+/// the deletion counts as changed.
+#[must_use]
+pub fn changed_lines(previous: &[u64], content: &str) -> BTreeSet<usize> {
+    let current = line_hashes(content);
+    let prefix = previous
+        .iter()
+        .zip(&current)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old = &previous[prefix..previous.len() - suffix];
+    let new = &current[prefix..current.len() - suffix];
+
+    let mut changed = BTreeSet::new();
+    // Marks the line following a deletion before new[index], or the last line
+    // if the deletion is at the end of the file
+    let mark_deletion = |changed: &mut BTreeSet<usize>, index: usize| {
+        let line = (prefix + index + 1).min(current.len());
+        if line > 0 {
+            changed.insert(line);
+        }
+    };
+
+    if old.is_empty() || new.is_empty() || old.len() * new.len() > MAX_DIFF_CELLS {
+        changed.extend(prefix + 1..=prefix + new.len());
+        if new.is_empty() && !old.is_empty() {
+            mark_deletion(&mut changed, 0);
+        }
+        return changed;
+    }
+
+    // Longest common subsequence of the differing regions
+    let width = new.len() + 1;
+    let mut table = vec![0_u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            mark_deletion(&mut changed, j);
+            i += 1;
+        } else {
+            changed.insert(prefix + j + 1);
+            j += 1;
+        }
+    }
+    changed.extend(prefix + j + 1..=prefix + new.len());
+    if i < old.len() {
+        mark_deletion(&mut changed, new.len());
+    }
+    changed
+}
+
+/// Returns the lines added or modified in each file of a unified diff without
+/// context lines (`git diff --unified=0`), by the file's path after the change.
+/// Where lines were only deleted, the line following the deletion counts as
+/// changed, as in [`changed_lines`].
+#[must_use]
+pub fn diff_changed_lines(diff: &str) -> BTreeMap<PathBuf, BTreeSet<usize>> {
+    let mut files: BTreeMap<PathBuf, BTreeSet<usize>> = BTreeMap::new();
+    let mut file: Option<PathBuf> = None;
+    let mut previous = "";
+    for line in diff.lines() {
+        // A `+++` line is only a file header after a `---` line; elsewhere it is
+        // an added line starting with `++`
+        if let Some(path) = line
+            .strip_prefix("+++ ")
+            .filter(|_| previous.starts_with("--- "))
+        {
+            // Deleted files are diffed against /dev/null
+            file = path.strip_prefix("b/").map(PathBuf::from);
+            if let Some(file) = &file {
+                files.entry(file.clone()).or_default();
+            }
+        } else if let (Some(file), Some(hunk)) = (&file, line.strip_prefix("@@ ")) {
+            // `@@ -old_start,old_count +new_start,new_count @@`, counts default to 1
+            let Some(added) = hunk.split(' ').find_map(|range| range.strip_prefix('+')) else {
+                continue;
+            };
+            let (start, count) = added.split_once(',').unwrap_or((added, "1"));
+            let (Ok(start), Ok(count)) = (start.parse::<usize>(), count.parse::<usize>()) else {
+                continue;
+            };
+            let lines = files.entry(file.clone()).or_default();
+            if count == 0 {
+                lines.insert(start + 1);
+            } else {
+                lines.extend(start..start + count);
+            }
+        }
+        previous = line;
+    }
+    files
+}
+
+/// Selects the functions containing changed lines and compares them with the
+/// scores recorded at the checkpoint, matching functions by name.
+#[must_use]
+pub fn changed_functions(
+    file_path: &Path,
+    functions: Vec<FunctionScore>,
+    changed: &BTreeSet<usize>,
+    previous: &[FunctionScore],
+) -> Vec<ChangedFunction> {
+    functions
+        .into_iter()
+        .filter(|function| function.start_line <= function.end_line)
+        .filter_map(|function| {
+            let changed_lines = changed
+                .range(function.start_line..=function.end_line)
+                .count();
+            if changed_lines == 0 {
+                return None;
+            }
+            let previous_quality_score = previous
+                .iter()
+                .find(|old| old.name == function.name && old.kind == function.kind)
+                .map(|old| old.quality_score);
+            Some(ChangedFunction {
+                file_path: file_path.to_path_buf(),
+                quality_delta: previous_quality_score.map(|old| function.quality_score - old),
+                previous_quality_score,
+                changed_lines,
+                function,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support::file_result;
+    use crate::analysis::SupportedLanguage;
+
+    fn score(name: &str, start_line: usize, end_line: usize, quality_score: f64) -> FunctionScore {
+        FunctionScore {
+            name: name.to_string(),
+            kind: "function".to_string(),
+            start_line,
+            end_line,
+            detections: 0,
+            complexity: 1,
+            quality_score,
+        }
+    }
+
+    #[test]
+    fn test_decision_lines() {
+        let content = r#"fn f(x: Option<i32>) -> Result<i32, E> {
+    // if this is a comment
+    let s = "if while for && ||";
+    let y = parse(s)?;
+    if x.is_some() && y > 0 {
+        return Ok(1);
+    } else if y < 0 {
+        return Ok(2);
+    }
+    match x {
+        Some(1) => Ok(1),
+        Some(_) => Ok(2),
+        None => Ok(3),
+    }
+}
+"#;
+        let file = FileAnalysisResult {
+            language: Some(SupportedLanguage::Rust),
+            ..file_result("src/lib.rs", Vec::new())
+        };
+        // `?`, `if` and `&&`, `else if`, and the arms after the first
+        assert_eq!(decision_lines(&file, content), vec![4, 5, 5, 7, 12, 13]);
+
+        let content = "def g(a):\n    # if for\n    return [x for x in a if x] or None\n";
+        let file = FileAnalysisResult {
+            language: Some(SupportedLanguage::Python),
+            ..file_result("g.py", Vec::new())
+        };
+        assert_eq!(decision_lines(&file, content), vec![3, 3, 3]);
+
+        // Files without a grammar have no decision points
+        let file = FileAnalysisResult {
+            language: Some(SupportedLanguage::Terraform),
+            ..file_result("main.tf", Vec::new())
+        };
+        assert!(decision_lines(&file, "if x {}").is_empty());
+    }
+
+    #[test]
+    fn test_worst_functions() {
+        let dense = FunctionScore {
+            detections: 2,
+            ..score("dense", 1, 4, 50.0)
+        };
+        let sparse = FunctionScore {
+            detections: 2,
+            ..score("sparse", 10, 49, 50.0)
+        };
+        let complex = FunctionScore {
+            complexity: 12,
+            ..score("complex", 50, 80, 100.0)
+        };
+        let clean = score("clean", 90, 95, 100.0);
+        let functions = [dense, sparse, complex, clean]
+            .into_iter()
+            .map(|function| FileFunction {
+                file_path: PathBuf::from("src/lib.rs"),
+                function,
+            })
+            .collect();
+
+        let names: Vec<_> = worst_functions(functions, 10)
+            .into_iter()
+            .map(|entry| entry.function.name)
+            .collect();
+        assert_eq!(names, vec!["dense", "sparse", "complex"]);
+    }
+
+    #[test]
+    fn test_changed_lines() {
+        let before = "fn a() {\n    1\n}\n\nfn b() {\n    2\n}\n";
+        let previous = line_hashes(before);
+        assert!(changed_lines(&previous, before).is_empty());
+
+        // Modified line in b, trailing whitespace ignored
+        let after = "fn a() {\n    1   \n}\n\nfn b() {\n    3\n}\n";
+        assert_eq!(changed_lines(&previous, after), BTreeSet::from([6]));
+
+        // Inserted lines in a
+        let after = "fn a() {\n    0\n    1\n}\n\nfn b() {\n    2\n}\n";
+        assert_eq!(changed_lines(&previous, after), BTreeSet::from([2]));
+
+        // Deleted line in a: the line after the deletion counts as changed
+        let after = "fn a() {\n}\n\nfn b() {\n    2\n}\n";
+        assert_eq!(changed_lines(&previous, after), BTreeSet::from([2]));
+
+        // No previous lines: everything is new
+        assert_eq!(changed_lines(&[], "x\ny\n"), BTreeSet::from([1, 2]));
+    }
+
+    #[test]
+    fn test_diff_changed_lines() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -2 +2 @@ fn a() {
+-    1
++    3
+@@ -10,2 +10,0 @@ fn b() {
+-    x
+-    y
+@@ -20,0 +19,3 @@ fn c() {
++++counter;
++    a
++    b
+diff --git a/new.py b/new.py
+new file mode 100644
+--- /dev/null
++++ b/new.py
+@@ -0,0 +1 @@
++pass
+";
+        let changed = diff_changed_lines(diff);
+        assert_eq!(
+            changed[Path::new("src/lib.rs")],
+            BTreeSet::from([2, 11, 19, 20, 21])
+        );
+        assert_eq!(changed[Path::new("new.py")], BTreeSet::from([1]));
+        assert_eq!(changed.len(), 2);
+    }
+
+    #[test]
+    fn test_changed_functions_with_quality_delta() {
+        let functions = vec![
+            score("a", 1, 3, 100.0),
+            score("b", 5, 7, 75.0),
+            score("c", 9, 11, 92.0),
+        ];
+        let previous = vec![score("a", 1, 3, 100.0), score("b", 5, 7, 100.0)];
+        let changed = BTreeSet::from([6, 10]);
+
+        let result = changed_functions(Path::new("src/lib.rs"), functions, &changed, &previous);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].function.name, "b");
+        assert_eq!(result[0].changed_lines, 1);
+        assert_eq!(result[0].quality_delta, Some(-25.0));
+        // New function: no previous score
+        assert_eq!(result[1].function.name, "c");
+        assert_eq!(result[1].previous_quality_score, None);
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Stable hashing for identifiers that are persisted or compared across runs.
+
+/// Hashes the given parts with 64-bit FNV-1a and returns a 16-digit hex string.
+///
+/// Unlike `DefaultHasher`, the output is stable across Rust versions and platforms.
+/// Parts are separated by a NUL byte so `["ab", "c"]` and `["a", "bc"]` differ.
+pub(crate) fn stable_hash<'a>(parts: impl IntoIterator<Item = &'a str>) -> String {
+    format!("{:016x}", stable_hash_u64(parts))
+}
+
+/// Like [`stable_hash`], but returns the hash as a number, for hashes stored in bulk.
+pub(crate) fn stable_hash_u64<'a>(parts: impl IntoIterator<Item = &'a str>) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_hash() {
+        // Known FNV-1a value guards against accidental algorithm changes
+        assert_eq!(stable_hash([""]), "af63bd4c8601b7df");
+        assert_ne!(stable_hash(["ab", "c"]), stable_hash(["a", "bc"]));
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! History of analysis results in a `SQLite` database.
+//!
+//! Each exported run is appended to normalized `runs`, `files`, `detections`
+//! and `rules` tables, so result history can be queried with plain SQL or
+//! connected to BI tools without custom parsers.
+//!
+//! [`ResultsDatabase::query`] runs ad-hoc read-only SQL over the history.
+
+use crate::error::{Result, SniffError};
+use crate::standalone::AnalysisResults;
+use chrono::Utc;
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection, OpenFlags};
+use serde::Serialize;
+use std::path::Path;
+
+/// Default location of the history database within a project.
+pub const DEFAULT_DATABASE_PATH: &str = ".sniff/results.db";
+
+/// Schema of the history database. Statements are idempotent.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    repository TEXT,
+    timestamp TEXT NOT NULL,
+    sniff_version TEXT,
+    ruleset_hash TEXT,
+    total_files INTEGER NOT NULL,
+    total_detections INTEGER NOT NULL,
+    critical_issues INTEGER NOT NULL,
+    average_quality_score REAL NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS rules (
+    rule_id TEXT PRIMARY KEY,
+    rule_name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    severity TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS files (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    language TEXT,
+    quality_score REAL NOT NULL,
+    detections INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS detections (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_id INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+    file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    rule_id TEXT NOT NULL REFERENCES rules(rule_id),
+    severity TEXT NOT NULL,
+    line_number INTEGER NOT NULL,
+    column_number INTEGER NOT NULL,
+    code_snippet TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    tags TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_files_run ON files(run_id);
+CREATE INDEX IF NOT EXISTS idx_detections_run ON detections(run_id);
+CREATE INDEX IF NOT EXISTS idx_detections_rule ON detections(rule_id);
+CREATE INDEX IF NOT EXISTS idx_detections_fingerprint ON detections(fingerprint);
+";
+
+/// Result of an ad-hoc query.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryResult {
+    /// Column names, in select order.
+    pub columns: Vec<String>,
+    /// Row values, one entry per column.
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+/// `SQLite` database holding the history of analysis runs.
+#[derive(Debug)]
+pub struct ResultsDatabase {
+    conn: Connection,
+}
+
+impl ResultsDatabase {
+    /// Opens (creating if needed) a history database.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| SniffError::file_system(parent, e))?;
+        }
+
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+
+        Ok(Self { conn })
+    }
+
+    /// Opens an existing history database without write access.
+    pub fn open_read_only(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(SniffError::storage_error(format!(
+                "No results history at {} (create one with analyze-files --export-sqlite)",
+                path.display()
+            )));
+        }
+
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// Runs a read-only SQL statement and returns its rows.
+    ///
+    /// Statements that would modify the database are rejected before execution.
+    pub fn query(&self, sql: &str) -> Result<QueryResult> {
+        let mut statement = self.conn.prepare(sql)?;
+        if !statement.readonly() {
+            return Err(SniffError::invalid_format(
+                "query".to_string(),
+                "only read-only statements (e.g. SELECT) are allowed".to_string(),
+            ));
+        }
+
+        let columns: Vec<String> = statement
+            .column_names()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        let column_count = columns.len();
+
+        let mut rows = Vec::new();
+        let mut cursor = statement.query([])?;
+        while let Some(row) = cursor.next()? {
+            let mut values = Vec::with_capacity(column_count);
+            for index in 0..column_count {
+                values.push(match row.get_ref(index)? {
+                    ValueRef::Null => serde_json::Value::Null,
+                    ValueRef::Integer(value) => value.into(),
+                    ValueRef::Real(value) => value.into(),
+                    ValueRef::Text(text) => String::from_utf8_lossy(text).into(),
+                    ValueRef::Blob(blob) => format!("<{} bytes>", blob.len()).into(),
+                });
+            }
+            rows.push(values);
+        }
+
+        Ok(QueryResult { columns, rows })
+    }
+
+    /// Records a run and all its files and detections, returning the run id.
+    pub fn record_run(&mut self, results: &AnalysisResults) -> Result<i64> {
+        let tx = self.conn.transaction()?;
+
+        let run = results.run.as_ref();
+        tx.execute(
+            "INSERT INTO runs (repository, timestamp, sniff_version, ruleset_hash,
+                               total_files, total_detections, critical_issues,
+                               average_quality_score)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                run.map(|r| r.repository.as_str()),
+                run.map_or_else(Utc::now, |r| r.timestamp).to_rfc3339(),
+                run.map(|r| r.sniff_version.as_str()),
+                run.map(|r| r.ruleset_hash.as_str()),
+                results.total_files,
+                results.total_detections,
+                results.critical_issues,
+                results.average_quality_score,
+            ],
+        )?;
+        let run_id = tx.last_insert_rowid();
+
+        {
+            let mut insert_rule = tx.prepare(
+                "INSERT INTO rules (rule_id, rule_name, description, severity)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(rule_id) DO UPDATE SET
+                     rule_name = excluded.rule_name,
+                     description = excluded.description,
+                     severity = excluded.severity",
+            )?;
+            let mut insert_file = tx.prepare(
+                "INSERT INTO files (run_id, path, language, quality_score, detections)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            let mut insert_detection = tx.prepare(
+                "INSERT INTO detections (run_id, file_id, rule_id, severity, line_number,
+                                         column_number, code_snippet, fingerprint, tags)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+
+            for file_result in &results.file_results {
+                insert_file.execute(params![
+                    run_id,
+                    file_result.file_path.to_string_lossy(),
+                    file_result.language.map(|l| l.name()),
+                    file_result.quality_score,
+                    file_result.detections.len(),
+                ])?;
+                let file_id = tx.last_insert_rowid();
+
+                for detection in &file_result.detections {
+                    insert_rule.execute(params![
+                        detection.rule_id,
+                        detection.rule_name,
+                        detection.description,
+                        detection.severity.name(),
+                    ])?;
+                    insert_detection.execute(params![
+                        run_id,
+                        file_id,
+                        detection.rule_id,
+                        detection.severity.name(),
+                        detection.line_number,
+                        detection.column_number,
+                        detection.code_snippet,
+                        detection.fingerprint(),
+                        detection.tags.join(","),
+                    ])?;
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(run_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support::{self, detection};
+    use crate::playbook::Severity;
+    use tempfile::TempDir;
+
+    fn results() -> AnalysisResults {
+        let mut detection = detection("python_bare_except", Severity::High, 10);
+        detection.file_path = "app.py".to_string();
+        test_support::results(vec![detection])
+    }
+
+    #[test]
+    fn test_record_runs_appends_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("results.db");
+
+        let mut db = ResultsDatabase::open(&path).unwrap();
+        let first = db.record_run(&results()).unwrap();
+        let second = db.record_run(&results()).unwrap();
+        assert_ne!(first, second);
+
+        let count = |table: &str| -> i64 {
+            db.conn
+                .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                    row.get(0)
+                })
+                .unwrap()
+        };
+        assert_eq!(count("runs"), 2);
+        assert_eq!(count("files"), 2);
+        assert_eq!(count("detections"), 2);
+        // Rules are shared across runs
+        assert_eq!(count("rules"), 1);
+    }
+
+    #[test]
+    fn test_query_is_read_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("results.db");
+        ResultsDatabase::open(&path)
+            .unwrap()
+            .record_run(&results())
+            .unwrap();
+
+        let db = ResultsDatabase::open_read_only(&path).unwrap();
+        let result = db
+            .query("SELECT rule_id, COUNT(*) AS hits FROM detections GROUP BY rule_id")
+            .unwrap();
+        assert_eq!(result.columns, vec!["rule_id", "hits"]);
+        assert_eq!(
+            result.rows,
+            vec![vec![
+                serde_json::json!("python_bare_except"),
+                serde_json::json!(1)
+            ]]
+        );
+
+        assert!(db.query("DELETE FROM runs").is_err());
+    }
+}
@@ -0,0 +1,343 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Standalone HTML report of analysis results.
+//!
+//! The report is a single self-contained file (inline CSS and script, no
+//! external assets) with summary cards, a collapsible section per file,
+//! severity filters and the flagged code in context, so results can be shared
+//! with reviewers who would rather not read JSON.
+
+#![allow(clippy::format_push_string)]
+
+use crate::analysis::MisalignmentDetection;
+use crate::markup::escape;
+use crate::network::NetworkStatus;
+use crate::playbook::Severity;
+use crate::standalone::{AnalysisResults, FileAnalysisResult};
+
+/// Severities in the order they are shown in filters.
+const SEVERITIES: [Severity; 5] = [
+    Severity::Critical,
+    Severity::High,
+    Severity::Medium,
+    Severity::Low,
+    Severity::Info,
+];
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; margin: 0; background: #f6f8fa; color: #1f2328; }
+header { background: #24292f; color: #fff; padding: 20px 32px; }
+header h1 { margin: 0 0 4px; font-size: 22px; }
+header .meta { color: #afb8c1; font-size: 13px; }
+main { padding: 24px 32px; max-width: 1200px; }
+.cards { display: flex; gap: 16px; flex-wrap: wrap; margin-bottom: 24px; }
+.card { background: #fff; border: 1px solid #d0d7de; border-radius: 6px; padding: 16px 20px; min-width: 160px; }
+.card .value { font-size: 28px; font-weight: 600; }
+.card .label { color: #656d76; font-size: 13px; }
+.filters { margin-bottom: 16px; }
+.filters label { margin-right: 14px; font-size: 14px; cursor: pointer; }
+details.file { background: #fff; border: 1px solid #d0d7de; border-radius: 6px; margin-bottom: 12px; }
+details.file > summary { padding: 12px 16px; cursor: pointer; display: flex; gap: 12px; align-items: center; }
+details.file > summary .path { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-weight: 600; flex: 1; }
+.score { font-size: 13px; padding: 2px 8px; border-radius: 12px; background: #eaeef2; }
+.score.good { background: #dafbe1; } .score.fair { background: #fff8c5; } .score.poor { background: #ffebe9; }
+.detection { border-top: 1px solid #d8dee4; padding: 12px 16px; }
+.detection h3 { margin: 0 0 6px; font-size: 15px; }
+.detection .location { color: #656d76; font-size: 13px; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; }
+.badge { display: inline-block; font-size: 12px; font-weight: 600; padding: 1px 8px; border-radius: 12px; color: #fff; margin-right: 6px; }
+.badge.critical { background: #a40e26; } .badge.high { background: #cf222e; } .badge.medium { background: #bf8700; }
+.badge.low { background: #1a7f37; } .badge.info { background: #0969da; }
+pre { background: #f6f8fa; border: 1px solid #d8dee4; border-radius: 6px; padding: 8px 0; overflow-x: auto; font-size: 13px; }
+pre .line { display: block; padding: 0 12px; } pre .line.target { background: #fff8c5; }
+pre .ln { color: #8c959f; display: inline-block; width: 4em; user-select: none; }
+.remediation { background: #ddf4ff; border-radius: 6px; padding: 8px 12px; font-size: 14px; }
+.tags { font-size: 12px; color: #656d76; }
+.empty { color: #656d76; }
+.insights h2 { font-size: 18px; margin: 24px 0 8px; }
+.insights .attribution { color: #656d76; font-size: 13px; font-style: italic; }
+details.insight { background: #fff; border: 1px solid #d0d7de; border-radius: 6px; margin-bottom: 12px; }
+details.insight > summary { padding: 12px 16px; cursor: pointer; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-weight: 600; }
+details.insight pre { margin: 0 16px 12px; padding: 8px 12px; }
+"#;
+
+const SCRIPT: &str = r"
+function applyFilters() {
+  const enabled = new Set();
+  document.querySelectorAll('.filters input:checked').forEach(function (box) { enabled.add(box.value); });
+  document.querySelectorAll('.detection').forEach(function (el) {
+    el.style.display = enabled.has(el.dataset.severity) ? '' : 'none';
+  });
+  document.querySelectorAll('details.file').forEach(function (file) {
+    const visible = Array.from(file.querySelectorAll('.detection')).some(function (el) { return el.style.display !== 'none'; });
+    file.style.display = visible ? '' : 'none';
+  });
+}
+document.querySelectorAll('.filters input').forEach(function (box) { box.addEventListener('change', applyFilters); });
+";
+
+/// Returns the CSS class for a severity.
+fn severity_class(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "critical",
+        Severity::High => "high",
+        Severity::Medium => "medium",
+        Severity::Low => "low",
+        Severity::Info => "info",
+    }
+}
+
+/// Returns the CSS class for a quality score.
+fn score_class(score: f64) -> &'static str {
+    if score >= 80.0 {
+        "good"
+    } else if score >= 50.0 {
+        "fair"
+    } else {
+        "poor"
+    }
+}
+
+/// Renders the flagged code, with surrounding lines when available.
+fn render_code(detection: &MisalignmentDetection) -> String {
+    let line = |number: usize, text: &str, target: bool| {
+        format!(
+            "<span class=\"line{}\"><span class=\"ln\">{}</span>{}</span>",
+            if target { " target" } else { "" },
+            number,
+            escape(text)
+        )
+    };
+
+    let lines: Vec<String> = match &detection.context_lines {
+        Some(context) => {
+            let mut number = context.start_line;
+            let mut lines = Vec::new();
+            for text in &context.before {
+                lines.push(line(number, text, false));
+                number += 1;
+            }
+            lines.push(line(number, &context.target, true));
+            for text in &context.after {
+                number += 1;
+                lines.push(line(number, text, false));
+            }
+            lines
+        }
+        None => detection
+            .code_snippet
+            .lines()
+            .enumerate()
+            .map(|(offset, text)| line(detection.line_number + offset, text, offset == 0))
+            .collect(),
+    };
+    format!("<pre><code>{}</code></pre>", lines.join(""))
+}
+
+/// Renders one detection.
+fn render_detection(detection: &MisalignmentDetection) -> String {
+    let severity = severity_class(detection.severity);
+    let mut html = format!(
+        "<div class=\"detection\" data-severity=\"{severity}\">\n\
+         <h3><span class=\"badge {severity}\">{}</span>{}</h3>\n\
+         <div class=\"location\">line {}, column {} &middot; {}</div>\n\
+         <p>{}</p>\n{}\n",
+        detection.severity.name(),
+        escape(&detection.rule_name),
+        detection.line_number,
+        detection.column_number,
+        escape(&detection.rule_id),
+        escape(&detection.description),
+        render_code(detection)
+    );
+    if let Some(remediation) = &detection.remediation {
+        html.push_str(&format!(
+            "<div class=\"remediation\"><strong>How to fix:</strong> {}",
+            escape(&remediation.explanation)
+        ));
+        if let Some(example) = &remediation.example {
+            html.push_str(&format!("<pre><code>{}</code></pre>", escape(example)));
+        }
+        html.push_str("</div>\n");
+    }
+    if !detection.tags.is_empty() {
+        html.push_str(&format!(
+            "<div class=\"tags\">{}</div>\n",
+            escape(&detection.tags.join(", "))
+        ));
+    }
+    html.push_str("</div>\n");
+    html
+}
+
+/// Renders the section for one file.
+fn render_file(file_result: &FileAnalysisResult) -> String {
+    let critical = file_result
+        .detections
+        .iter()
+        .filter(|d| d.severity == Severity::Critical)
+        .count();
+    let mut html = format!(
+        "<details class=\"file\"{}>\n<summary><span class=\"path\">{}</span>\
+         <span>{}</span><span>{} issues{}</span>\
+         <span class=\"score {}\">{:.1}%</span></summary>\n",
+        if critical > 0 { " open" } else { "" },
+        escape(&file_result.file_path.to_string_lossy()),
+        file_result.language.map_or("unknown", |l| l.name()),
+        file_result.detections.len(),
+        if critical > 0 {
+            format!(", {critical} critical")
+        } else {
+            String::new()
+        },
+        score_class(file_result.quality_score),
+        file_result.quality_score
+    );
+    let mut detections: Vec<&MisalignmentDetection> = file_result.detections.iter().collect();
+    detections.sort_by_key(|d| (std::cmp::Reverse(d.severity.score()), d.line_number));
+    for detection in detections {
+        html.push_str(&render_detection(detection));
+    }
+    html.push_str("</details>\n");
+    html
+}
+
+/// Renders the heuristic insights of the files that have them, kept apart from
+/// the detections since they are not playbook findings.
+fn render_insights(results: &AnalysisResults) -> String {
+    let files: Vec<_> = results
+        .file_results
+        .iter()
+        .filter_map(|file_result| {
+            let insights = file_result.heuristic_insights.as_ref()?;
+            Some((file_result.file_path.to_string_lossy(), insights))
+        })
+        .collect();
+    let Some((_, first)) = files.first() else {
+        return String::new();
+    };
+
+    let mut html = format!(
+        "<section class=\"insights\">\n<h2>Heuristic Insights</h2>\n\
+         <p class=\"attribution\">{}</p>\n",
+        escape(&first.attribution)
+    );
+    for (path, insights) in &files {
+        let body = serde_json::to_string_pretty(&insights.insights)
+            .unwrap_or_else(|_| insights.insights.to_string());
+        html.push_str(&format!(
+            "<details class=\"insight\"><summary>{}</summary>\
+             <pre><code>{}</code></pre></details>\n",
+            escape(path),
+            escape(&body)
+        ));
+    }
+    html.push_str("</section>\n");
+    html
+}
+
+/// Renders analysis results as a standalone HTML report.
+#[must_use]
+pub fn render(results: &AnalysisResults) -> String {
+    let meta = results.run.as_ref().map_or_else(String::new, |run| {
+        let offline = if run.network == NetworkStatus::Disabled {
+            " &middot; network disabled"
+        } else {
+            ""
+        };
+        format!(
+            "{} &middot; {} &middot; sniff {} &middot; ruleset {}{offline}",
+            escape(&run.repository),
+            run.timestamp.format("%Y-%m-%d %H:%M UTC"),
+            escape(&run.sniff_version),
+            escape(&run.ruleset_hash)
+        )
+    });
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>Sniff Report</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+         <header><h1>Sniff Analysis Report</h1><div class=\"meta\">{meta}</div></header>\n<main>\n"
+    );
+
+    html.push_str("<section class=\"cards\">\n");
+    for (value, label) in [
+        (results.total_files.to_string(), "Files analyzed"),
+        (results.total_detections.to_string(), "Detections"),
+        (results.critical_issues.to_string(), "Critical issues"),
+        (
+            format!("{:.1}%", results.average_quality_score),
+            "Average quality",
+        ),
+    ] {
+        html.push_str(&format!(
+            "<div class=\"card\"><div class=\"value\">{value}</div><div class=\"label\">{label}</div></div>\n"
+        ));
+    }
+    html.push_str("</section>\n");
+
+    let flagged: Vec<&FileAnalysisResult> = results
+        .file_results
+        .iter()
+        .filter(|file_result| !file_result.detections.is_empty())
+        .collect();
+    if flagged.is_empty() {
+        html.push_str("<p class=\"empty\">No issues detected.</p>\n");
+    } else {
+        html.push_str("<section class=\"filters\"><strong>Severity:</strong> ");
+        for severity in SEVERITIES {
+            let count = flagged
+                .iter()
+                .flat_map(|file_result| &file_result.detections)
+                .filter(|d| d.severity == severity)
+                .count();
+            html.push_str(&format!(
+                "<label><input type=\"checkbox\" value=\"{}\" checked> {} ({count})</label>",
+                severity_class(severity),
+                severity.name()
+            ));
+        }
+        html.push_str("</section>\n");
+
+        for file_result in flagged {
+            html.push_str(&render_file(file_result));
+        }
+    }
+    html.push_str(&render_insights(results));
+
+    html.push_str(&format!(
+        "</main>\n<script>{SCRIPT}</script>\n</body>\n</html>\n"
+    ));
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support::{detection, file_result};
+    use crate::standalone::HeuristicInsights;
+
+    #[test]
+    fn test_html_report() {
+        let mut detection = detection("python_bare_except", Severity::Critical, 10);
+        detection.description = "Bare except swallows <all> errors".to_string();
+        detection.code_snippet = "except:".to_string();
+        let mut file_result = file_result("app.py", vec![detection]);
+        file_result.heuristic_insights = Some(HeuristicInsights::new(serde_json::json!({
+            "patterns": ["repeated <try> blocks"],
+        })));
+        let results = AnalysisResults::from_file_results(vec![file_result]);
+
+        let html = render(&results);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("swallows &lt;all&gt; errors"));
+        assert!(html.contains("data-severity=\"critical\""));
+        assert!(html.contains("<details class=\"file\" open>"));
+        assert!(html.contains("Critical (1)"));
+        assert!(html.contains("<h2>Heuristic Insights</h2>"));
+        assert!(html.contains("not playbook detections and may be inaccurate"));
+        assert!(html.contains("<details class=\"insight\"><summary>app.py</summary>"));
+        assert!(html.contains("repeated &lt;try&gt; blocks"));
+    }
+}
@@ -0,0 +1,408 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Export of detections to issue trackers.
+//!
+//! Converts detections at or above a severity into issue-creation payloads
+//! for Jira (REST API v2) or Linear (GraphQL `issueCreate`), and optionally
+//! creates the issues. Every filed detection is recorded by fingerprint in
+//! `.sniff/filed-issues.jsonl`, so later runs only export detections that
+//! have not been filed yet.
+//!
+//! Jira credentials are read from `JIRA_URL` (the site, e.g.
+//! `https://example.atlassian.net`), `JIRA_EMAIL` and `JIRA_API_TOKEN`;
+//! Linear's from `LINEAR_API_KEY`.
+
+#![allow(clippy::format_push_string)]
+
+use crate::analysis::MisalignmentDetection;
+use crate::error::{Result, SniffError};
+use crate::network;
+use crate::playbook::Severity;
+use crate::standalone::AnalysisResults;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Linear GraphQL endpoint.
+const LINEAR_API_URL: &str = "https://api.linear.app/graphql";
+
+/// Linear mutation creating one issue.
+const LINEAR_ISSUE_CREATE: &str = "mutation IssueCreate($input: IssueCreateInput!) { \
+    issueCreate(input: $input) { success issue { identifier url } } }";
+
+/// Issue tracker that detections are exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tracker {
+    /// Atlassian Jira.
+    Jira,
+    /// Linear.
+    Linear,
+}
+
+impl Tracker {
+    /// Returns the tracker name.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Jira => "jira",
+            Self::Linear => "linear",
+        }
+    }
+}
+
+/// An issue to file for one detection.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IssueDraft {
+    /// Fingerprint of the detection, see [`MisalignmentDetection::fingerprint`].
+    pub fingerprint: String,
+    /// Rule that produced the detection.
+    pub rule_id: String,
+    /// Severity of the detection.
+    pub severity: Severity,
+    /// File containing the detection.
+    pub file_path: String,
+    /// Line of the detection (1-based).
+    pub line_number: usize,
+    /// Issue title.
+    pub summary: String,
+    /// What the rule detects.
+    pub description: String,
+    /// Flagged code.
+    pub code_snippet: String,
+    /// How to fix it, if the rule says.
+    pub remediation: Option<String>,
+}
+
+impl IssueDraft {
+    /// Drafts the issue for a detection.
+    #[must_use]
+    pub fn of(detection: &MisalignmentDetection) -> Self {
+        Self {
+            fingerprint: detection.fingerprint(),
+            rule_id: detection.rule_id.clone(),
+            severity: detection.severity,
+            file_path: detection.file_path.clone(),
+            line_number: detection.line_number,
+            summary: format!(
+                "[sniff] {} in {}:{}",
+                detection.rule_name, detection.file_path, detection.line_number
+            ),
+            description: detection.description.clone(),
+            code_snippet: detection.code_snippet.trim_end().to_string(),
+            remediation: detection
+                .remediation
+                .as_ref()
+                .map(|remediation| remediation.explanation.clone()),
+        }
+    }
+
+    /// Returns the issue body in the tracker's markup: Jira wiki markup or
+    /// Linear Markdown.
+    #[must_use]
+    pub fn body(&self, tracker: Tracker) -> String {
+        let (strong, code, code_end, block_start, block_end) = match tracker {
+            Tracker::Jira => ("*", "{{", "}}", "{code}", "{code}"),
+            Tracker::Linear => ("**", "`", "`", "```", "```"),
+        };
+        let mut body = format!(
+            "{strong}{}{strong} at {code}{}:{}{code_end}\n\n{}\n\n{block_start}\n{}\n{block_end}\n",
+            self.rule_id, self.file_path, self.line_number, self.description, self.code_snippet
+        );
+        if let Some(remediation) = &self.remediation {
+            body.push_str(&format!("\n{strong}Remediation:{strong} {remediation}\n"));
+        }
+        body.push_str(&format!(
+            "\nsniff fingerprint: {code}{}{code_end}\n",
+            self.fingerprint
+        ));
+        body
+    }
+
+    /// Returns the issue-creation payload for a tracker. `project` is the
+    /// Jira project key or the Linear team id.
+    #[must_use]
+    pub fn payload(&self, tracker: Tracker, project: &str) -> Value {
+        match tracker {
+            Tracker::Jira => json!({
+                "fields": {
+                    "project": { "key": project },
+                    "issuetype": { "name": "Bug" },
+                    "summary": self.summary,
+                    "description": self.body(tracker),
+                    "labels": ["sniff", format!("sniff-{}", self.fingerprint)],
+                    "priority": { "name": jira_priority(self.severity) },
+                }
+            }),
+            Tracker::Linear => json!({
+                "query": LINEAR_ISSUE_CREATE,
+                "variables": {
+                    "input": {
+                        "teamId": project,
+                        "title": self.summary,
+                        "description": self.body(tracker),
+                        "priority": linear_priority(self.severity),
+                    }
+                }
+            }),
+        }
+    }
+}
+
+/// Maps a severity to a default Jira priority name.
+fn jira_priority(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "Highest",
+        Severity::High => "High",
+        Severity::Medium => "Medium",
+        Severity::Low => "Low",
+        Severity::Info => "Lowest",
+    }
+}
+
+/// Maps a severity to a Linear priority (1 urgent to 4 low).
+fn linear_priority(severity: Severity) -> u8 {
+    match severity {
+        Severity::Critical => 1,
+        Severity::High => 2,
+        Severity::Medium => 3,
+        Severity::Low | Severity::Info => 4,
+    }
+}
+
+/// Drafts issues for the detections at or above `min_severity` whose
+/// fingerprints are not in `filed`. Detections sharing a fingerprint are
+/// drafted once.
+#[must_use]
+pub fn drafts(
+    results: &AnalysisResults,
+    min_severity: Severity,
+    filed: &HashSet<String>,
+) -> Vec<IssueDraft> {
+    let mut seen = HashSet::new();
+    results
+        .file_results
+        .iter()
+        .flat_map(|file_result| &file_result.detections)
+        .filter(|detection| detection.severity >= min_severity)
+        .filter(|detection| {
+            let fingerprint = detection.fingerprint();
+            !filed.contains(&fingerprint) && seen.insert(fingerprint)
+        })
+        .map(IssueDraft::of)
+        .collect()
+}
+
+/// A detection filed as an issue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FiledIssue {
+    /// When the issue was filed.
+    pub timestamp: DateTime<Utc>,
+    /// Tracker the issue was filed in.
+    pub tracker: Tracker,
+    /// Fingerprint of the detection.
+    pub fingerprint: String,
+    /// Issue key or identifier returned by the tracker; absent when only the
+    /// payload was exported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<String>,
+}
+
+/// Append-only record of filed detections backed by `.sniff/filed-issues.jsonl`.
+#[derive(Debug)]
+pub struct FiledIssues {
+    path: PathBuf,
+}
+
+impl FiledIssues {
+    /// Opens the record of filed issues for a project.
+    #[must_use]
+    pub fn new(project_dir: &Path) -> Self {
+        Self {
+            path: project_dir.join(".sniff").join("filed-issues.jsonl"),
+        }
+    }
+
+    /// Returns the path of the record file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends a filed issue to the record.
+    pub fn append(&self, issue: &FiledIssue) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| SniffError::file_system(parent, e))?;
+        }
+
+        let line = serde_json::to_string(issue)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| SniffError::file_system(&self.path, e))?;
+        writeln!(file, "{line}").map_err(|e| SniffError::file_system(&self.path, e))
+    }
+
+    /// Returns the fingerprints filed in a tracker. Malformed lines are
+    /// skipped with a warning.
+    pub fn fingerprints(&self, tracker: Tracker) -> Result<HashSet<String>> {
+        if !self.path.exists() {
+            return Ok(HashSet::new());
+        }
+
+        let file =
+            std::fs::File::open(&self.path).map_err(|e| SniffError::file_system(&self.path, e))?;
+
+        let mut fingerprints = HashSet::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| SniffError::file_system(&self.path, e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<FiledIssue>(&line) {
+                Ok(issue) if issue.tracker == tracker => {
+                    fingerprints.insert(issue.fingerprint);
+                }
+                Ok(_) => {}
+                Err(e) => warn!(
+                    "Skipping malformed filed issue at {}:{}: {}",
+                    self.path.display(),
+                    index + 1,
+                    e
+                ),
+            }
+        }
+
+        Ok(fingerprints)
+    }
+}
+
+/// Creates an issue through the tracker's API, returning its key or identifier.
+///
+/// # Errors
+///
+/// Returns an error if network access is disabled, the credentials are not
+/// set, or the tracker rejects the request.
+pub fn create(tracker: Tracker, payload: &Value) -> Result<String> {
+    network::check(&format!("Creating {} issues", tracker.name()))?;
+    let var = |name: &str| {
+        std::env::var(name)
+            .map_err(|_| SniffError::missing_field(name, "issue tracker credentials"))
+    };
+
+    let (url, request) = match tracker {
+        Tracker::Jira => {
+            let site = var("JIRA_URL")?;
+            let credentials = format!("{}:{}", var("JIRA_EMAIL")?, var("JIRA_API_TOKEN")?);
+            let url = format!("{}/rest/api/2/issue", site.trim_end_matches('/'));
+            let request = ureq::post(&url).set(
+                "authorization",
+                &format!("Basic {}", BASE64.encode(credentials)),
+            );
+            (url, request)
+        }
+        Tracker::Linear => {
+            let request = ureq::post(LINEAR_API_URL).set("authorization", &var("LINEAR_API_KEY")?);
+            (LINEAR_API_URL.to_string(), request)
+        }
+    };
+
+    let request = request.set("content-type", "application/json");
+    let response: Value = match request.send_string(&payload.to_string()) {
+        Ok(response) => {
+            let body = response
+                .into_string()
+                .map_err(|e| SniffError::network(&url, e.to_string()))?;
+            serde_json::from_str(&body).map_err(|e| SniffError::network(&url, e.to_string()))?
+        }
+        Err(ureq::Error::Status(status, response)) => {
+            let detail = response.into_string().unwrap_or_default();
+            return Err(SniffError::network(url, format!("HTTP {status}: {detail}")));
+        }
+        Err(e) => return Err(SniffError::network(url, e.to_string())),
+    };
+
+    let issue = match tracker {
+        Tracker::Jira => response.pointer("/key"),
+        Tracker::Linear => response.pointer("/data/issueCreate/issue/identifier"),
+    };
+    issue
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| SniffError::network(url, format!("unexpected response: {response}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support::{detection, results};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_drafts_skip_filed_and_minor_detections() {
+        let mut detections = vec![
+            detection("rust_unimplemented", Severity::Critical, 3),
+            detection("rust_unimplemented", Severity::Critical, 3),
+            detection("rust_hardcoded_secret", Severity::Critical, 3),
+            detection("rust_todo_comment", Severity::Low, 3),
+        ];
+        let snippets = [
+            "unimplemented!()",
+            "unimplemented!()",
+            "let key = \"abc\";",
+            "// TODO",
+        ];
+        for (detection, snippet) in detections.iter_mut().zip(snippets) {
+            detection.code_snippet = snippet.to_string();
+        }
+        let results = results(detections);
+        let all = drafts(&results, Severity::Critical, &HashSet::new());
+        assert_eq!(all.len(), 2);
+
+        let filed = HashSet::from([all[0].fingerprint.clone()]);
+        let new = drafts(&results, Severity::Critical, &filed);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].rule_id, "rust_hardcoded_secret");
+
+        let payload = new[0].payload(Tracker::Jira, "OPS");
+        assert_eq!(payload["fields"]["project"]["key"], "OPS");
+        assert!(payload["fields"]["description"]
+            .as_str()
+            .unwrap()
+            .contains("{code}\nlet key = \"abc\";\n{code}"));
+        assert_eq!(payload["fields"]["priority"]["name"], "Highest");
+        let payload = new[0].payload(Tracker::Linear, "team-id");
+        assert_eq!(payload["variables"]["input"]["teamId"], "team-id");
+        assert_eq!(payload["variables"]["input"]["priority"], 1);
+    }
+
+    #[test]
+    fn test_filed_issues_are_recorded_per_tracker() {
+        let temp_dir = TempDir::new().unwrap();
+        let filed = FiledIssues::new(temp_dir.path());
+        assert!(filed.fingerprints(Tracker::Jira).unwrap().is_empty());
+
+        for (tracker, fingerprint) in [(Tracker::Jira, "a1"), (Tracker::Linear, "b2")] {
+            filed
+                .append(&FiledIssue {
+                    timestamp: Utc::now(),
+                    tracker,
+                    fingerprint: fingerprint.to_string(),
+                    issue: None,
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            filed.fingerprints(Tracker::Jira).unwrap(),
+            HashSet::from(["a1".to_string()])
+        );
+    }
+}
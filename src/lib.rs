@@ -14,19 +14,61 @@
 #![allow(clippy::missing_errors_doc)] // Error docs would be repetitive for Result<T>
 #![allow(clippy::cast_precision_loss)] // Necessary for quality score calculations
 
+pub mod ai_likelihood;
 pub mod analysis;
+pub mod ast_query;
+pub mod audit;
+pub mod badge;
+pub mod bash_risk;
+pub mod bench;
+pub mod columnar;
+pub mod config_keys;
+pub mod criteria;
+pub mod dependencies;
 pub mod display;
+pub mod doctor;
+pub mod egress;
 pub mod error;
+pub mod file_access;
+pub mod fix;
+pub mod function_quality;
+mod hash;
+pub mod history;
+pub mod html_report;
+pub mod injection;
+pub mod issue_export;
+mod markup;
+pub mod monitor;
+pub mod network;
+pub mod org_report;
+pub mod pack_registry;
+pub mod pack_signing;
 pub mod pattern_learning;
+pub mod pattern_testing;
 pub mod playbook;
+pub mod plugin;
+pub mod prefilter;
+pub mod project_config;
+pub mod remote_store;
+pub mod report;
+pub mod scorecard;
+pub mod script;
+pub mod session;
+pub mod session_policy;
 pub mod standalone;
+pub mod structural;
+pub mod suppression;
+pub mod template;
+pub mod text_check;
 
 pub mod verify_todo;
+pub mod waiver;
 
 // Re-export commonly used types
 pub use analysis::{
-    MisalignmentAnalyzer, MisalignmentDetection, ContextLines, EnhancedMisalignmentAnalysis, PerformanceImpact,
-    QualityAssessment, SemanticContextResult, SupportedLanguage,
+    ContextLines, EnhancedMisalignmentAnalysis, MisalignmentAnalyzer, MisalignmentDetection,
+    PerformanceImpact, PerformanceReport, QualityAssessment, ScoreWeights, SemanticContextResult,
+    SupportedLanguage,
 };
 pub use display::MisalignmentDisplayFormatter;
 pub use error::{Result, SniffError};
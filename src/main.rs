@@ -7,8 +7,8 @@
 
 use clap::{Parser, Subcommand, ValueEnum};
 use sniff::{Result, SniffError};
-use std::path::PathBuf;
 use std::fs;
+use std::path::{Path, PathBuf};
 use tracing::{info, warn, Level};
 use tracing_subscriber::fmt;
 
@@ -25,13 +25,27 @@ struct Cli {
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
 
+    /// Never write to disk (no .sniff creation, playbook installation, checkpoints or logs)
+    #[arg(long, global = true)]
+    read_only: bool,
+
+    /// Never use the network (no pack downloads, remote stores or git fetches);
+    /// analysis results record `network: disabled`
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Make analysis results byte-for-byte reproducible: run timestamp fixed to
+    /// SOURCE_DATE_EPOCH (or the Unix epoch) and all lists in a stable order
+    #[arg(long, global = true)]
+    reproducible: bool,
+
     /// Subcommand to execute
     #[command(subcommand)]
     command: Commands,
 }
 
 /// Output format for commands
-#[derive(ValueEnum, Clone, Debug, PartialEq)]
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
 enum OutputFormat {
     /// Human-readable table format
     Table,
@@ -41,6 +55,24 @@ enum OutputFormat {
     Markdown,
     /// Compact one-line format
     Compact,
+    /// Arrow IPC (Feather v2) columnar format, one row per detection.
+    /// Only produced by commands that report detections.
+    Arrow,
+    /// SARIF 2.1.0, for GitHub Code Scanning and other SARIF consumers.
+    /// Only produced by commands that report detections.
+    Sarif,
+    /// `JUnit` XML, one test case per analyzed file, Critical/High detections as failures.
+    /// Only produced by commands that report detections.
+    Junit,
+    /// Code Climate issue JSON, for GitLab merge request code quality reports.
+    /// Only produced by commands that report detections.
+    Codeclimate,
+    /// Reviewdog Diagnostic Format, a single JSON result with all diagnostics.
+    /// Only produced by commands that report detections.
+    Rdjson,
+    /// Reviewdog Diagnostic Format, one JSON diagnostic per line.
+    /// Only produced by commands that report detections.
+    Rdjsonl,
 }
 
 /// Available CLI commands.
@@ -60,6 +92,12 @@ enum Commands {
         /// Include hidden files and directories
         #[arg(long)]
         include_hidden: bool,
+        /// Also analyze paths ignored by .gitignore and .sniffignore files
+        #[arg(long)]
+        no_ignore: bool,
+        /// Only analyze files tracked by git (from git ls-files) in directories
+        #[arg(long)]
+        tracked_only: bool,
         /// File extensions to include (e.g., rs,py,ts)
         #[arg(long)]
         extensions: Option<String>,
@@ -72,25 +110,146 @@ enum Commands {
         /// Language to force for all files (overrides detection)
         #[arg(long)]
         force_language: Option<String>,
-        /// Save analysis results to file
+        /// Save analysis results to file (a .html path writes a standalone HTML report)
         #[arg(long)]
         output_file: Option<PathBuf>,
+        /// Render results with a Jinja template instead of --format
+        #[arg(long, value_name = "FILE")]
+        template: Option<PathBuf>,
         /// Create checkpoint for tracking changes
         #[arg(long)]
         checkpoint: Option<String>,
         /// Compare against previous checkpoint
         #[arg(long)]
         diff_checkpoint: Option<String>,
+        /// Only analyze the files changed since a git revision, reporting the
+        /// functions touched by the change and their quality delta
+        #[arg(long, value_name = "REV", conflicts_with_all = ["checkpoint", "diff_checkpoint"])]
+        diff: Option<String>,
+        /// Directory holding checkpoints (defaults to .sniff/checkpoints in the project root)
+        #[arg(long)]
+        checkpoint_dir: Option<PathBuf>,
+        /// Do not compare against the team baseline configured in .sniff.toml
+        #[arg(long, conflicts_with = "diff_checkpoint")]
+        no_baseline: bool,
         /// Include test files in analysis (by default test files are excluded)
         #[arg(long)]
         include_tests: bool,
         /// Confidence threshold for test file detection (0.0-1.0)
         #[arg(long, default_value = "0.3")]
         test_confidence: f64,
+        /// Report detections suppressed by test context, with the reason
+        #[arg(long)]
+        show_suppressed: bool,
+        /// Include heuristic AI insights in detailed output
+        #[arg(long, requires = "detailed")]
+        insights: bool,
+        /// Run enhanced analysis with performance impact and quality assessment
+        #[arg(long)]
+        enhanced: bool,
+        /// Estimate how likely each file is to be AI-generated (reported, never gated)
+        #[arg(long)]
+        ai_likelihood: bool,
+        /// Directory of session transcripts cross-referenced by --ai-likelihood
+        /// (defaults to ~/.claude/projects)
+        #[arg(long, requires = "ai_likelihood")]
+        projects_dir: Option<PathBuf>,
+        /// Also analyze YAML and JSON configuration files (secrets, debug flags,
+        /// placeholder values, duplicate keys)
+        #[arg(long)]
+        config_files: bool,
+        /// Also analyze Markdown documentation (assistant boilerplate, placeholder
+        /// text, broken relative links, empty sections)
+        #[arg(long)]
+        markdown: bool,
+        /// List the N worst functions by detection density and complexity (default 10)
+        #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "10")]
+        per_function: Option<usize>,
+        /// Only analyze one deterministic slice of the files (e.g. 2/8)
+        #[arg(long, value_name = "INDEX/TOTAL")]
+        shard: Option<sniff::standalone::Shard>,
+        /// Append results to a SQLite database (runs, files, detections, rules)
+        #[arg(long, value_name = "PATH")]
+        export_sqlite: Option<PathBuf>,
+        /// Fail if more than this percentage of the selected files could not be analyzed
+        /// (too large, unsupported language, unreadable, failed)
+        #[arg(long, value_name = "PCT")]
+        fail_if_skipped_over: Option<f64>,
+        /// Apply the replacements of rules that define one to the flagged code
+        #[arg(long)]
+        fix: bool,
+        /// Show the fixes --fix would apply without changing any file
+        #[arg(long, conflicts_with = "fix")]
+        fix_dry_run: bool,
+    },
+
+    /// Merge JSON results from sharded analyze-files runs
+    MergeResults {
+        /// JSON result files produced by `analyze-files --format json`
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+        /// Show detailed pattern analysis
+        #[arg(short, long)]
+        detailed: bool,
+        /// Fail if the merged average quality score is below this value (0-100)
+        #[arg(long)]
+        min_quality_score: Option<f64>,
+        /// Fail if the merged results contain more critical issues than this
+        #[arg(long)]
+        max_critical_issues: Option<usize>,
+    },
+
+    /// Aggregate JSON results from many repositories into an org-wide report
+    OrgReport {
+        /// JSON result files produced by `analyze-files --format json`
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+        /// Number of top failing rules to include
+        #[arg(long, default_value = "10")]
+        top_rules: usize,
+    },
+
+    /// Render an SVG quality badge from saved analysis results
+    Badge {
+        /// JSON result file produced by `analyze-files --format json --output-file`
+        results: PathBuf,
+        /// Write the badge to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Text on the left half of the badge
+        #[arg(long, default_value = sniff::badge::DEFAULT_LABEL)]
+        label: String,
+    },
+
+    /// Export saved analysis results to other tools
+    Report {
+        #[command(subcommand)]
+        command: ReportCommands,
+    },
+
+    /// Run read-only SQL over the results history written by --export-sqlite
+    Query {
+        /// SQL statement to run (e.g. "SELECT rule_id, COUNT(*) FROM detections GROUP BY 1")
+        sql: String,
+        /// History database to query
+        #[arg(long, default_value = sniff::history::DEFAULT_DATABASE_PATH)]
+        db: PathBuf,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
     },
 
     /// Manage analysis checkpoints for tracking changes over time
     Checkpoint {
+        /// Directory holding checkpoints (defaults to .sniff/checkpoints in the project root)
+        #[arg(long, global = true)]
+        checkpoint_dir: Option<PathBuf>,
         #[command(subcommand)]
         command: CheckpointCommands,
     },
@@ -101,11 +260,150 @@ enum Commands {
         command: PatternCommands,
     },
 
+    /// Report performance hotspots, nested loops and parallelization opportunities
+    Perf {
+        /// Files or directories to analyze
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+        /// Include hidden files and directories
+        #[arg(long)]
+        include_hidden: bool,
+        /// File extensions to include (e.g., rs,py,ts)
+        #[arg(long)]
+        extensions: Option<String>,
+        /// Pattern to exclude files (glob pattern)
+        #[arg(long)]
+        exclude: Option<String>,
+        /// Include test files in analysis (by default test files are excluded)
+        #[arg(long)]
+        include_tests: bool,
+    },
+
+    /// Show symbol tables, taint flows and validation points for files
+    Semantics {
+        /// Files to analyze
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Waive a detection for quality gates until a given date
+    Waive {
+        /// Fingerprint of the detection to waive (shown in detailed output)
+        #[arg(required_unless_present_any = ["list", "remove"])]
+        fingerprint: Option<String>,
+        /// Last day the waiver is honored (YYYY-MM-DD)
+        #[arg(long, required_unless_present_any = ["list", "remove"])]
+        until: Option<String>,
+        /// Why the detection is waived
+        #[arg(long, required_unless_present_any = ["list", "remove"])]
+        reason: Option<String>,
+        /// List existing waivers and their status
+        #[arg(long, conflicts_with_all = ["fingerprint", "until", "reason", "remove"])]
+        list: bool,
+        /// Remove the waiver for a fingerprint
+        #[arg(long, conflicts_with_all = ["fingerprint", "until", "reason"])]
+        remove: Option<String>,
+    },
+
+    /// Share the team baseline (the `baseline` checkpoint) through remote storage
+    Baseline {
+        /// Directory holding checkpoints (defaults to .sniff/checkpoints in the project root)
+        #[arg(long, global = true)]
+        checkpoint_dir: Option<PathBuf>,
+        #[command(subcommand)]
+        command: BaselineCommands,
+    },
+
+    /// Share checkpoints and results history through S3-compatible object storage
+    Remote {
+        /// Directory holding checkpoints (defaults to .sniff/checkpoints in the project root)
+        #[arg(long, global = true)]
+        checkpoint_dir: Option<PathBuf>,
+        #[command(subcommand)]
+        command: RemoteCommands,
+    },
+
+    /// Inspect the audit log of quality gate decisions
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommands,
+    },
+
+    /// Inspect and validate .sniff.toml configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Analyze agent session transcripts
+    Sessions {
+        /// Directory holding session transcripts (defaults to ~/.claude/projects)
+        #[arg(long, global = true)]
+        projects_dir: Option<PathBuf>,
+        #[command(subcommand)]
+        command: SessionCommands,
+    },
+
+    /// Gate a commit message or PR description read from stdin against text rules
+    /// (unverified claims, vague summaries, missing issue references)
+    CheckText {
+        /// Kind of text on stdin
+        #[arg(long)]
+        kind: TextKindArg,
+        /// Text playbook to apply instead of the built-in rules
+        #[arg(long, value_name = "FILE")]
+        rules: Option<PathBuf>,
+        /// Lowest severity that fails the check (info, low, medium, high, critical)
+        #[arg(long, default_value = "medium")]
+        fail_on: String,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Report rule and language coverage gaps of the current project
+    Doctor {
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+
+    /// Measure analysis throughput, per-stage timing and peak memory on a corpus
+    Bench {
+        /// Directory of source files to analyze
+        #[arg(long, default_value = ".")]
+        corpus: PathBuf,
+        /// Number of runs over the corpus; the stage times of the fastest run are reported
+        #[arg(long, default_value = "3")]
+        iterations: usize,
+        /// Only analyze files tracked by git (from git ls-files) in directories
+        #[arg(long)]
+        tracked_only: bool,
+        /// Pattern to exclude files (glob pattern)
+        #[arg(long)]
+        exclude: Option<String>,
+        /// Maximum file size to analyze (in MB)
+        #[arg(long, default_value = "10")]
+        max_file_size_mb: f64,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+
     /// Verify TODO completion with sniff analysis
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     VerifyTodo {
+        #[command(subcommand)]
+        command: Option<VerifyTodoCommands>,
         /// TODO ID to verify
-        #[arg(short, long)]
-        todo_id: String,
+        #[arg(short, long, required = true)]
+        todo_id: Option<String>,
         /// Files to analyze for this TODO
         #[arg(short, long)]
         files: Vec<PathBuf>,
@@ -121,6 +419,250 @@ enum Commands {
         /// Use Git to discover changed files (prevents agent deception)
         #[arg(long)]
         git_discovery: bool,
+        /// YAML file mapping TODO ids to required symbols, files and tests
+        #[arg(long, value_name = "FILE")]
+        criteria: Option<PathBuf>,
+        /// Print nothing but the report (no notes or log messages); exit codes are
+        /// 0 when the gate passes, 1 when it fails and 2 on errors
+        #[arg(short, long)]
+        quiet: bool,
+    },
+}
+
+/// Report commands
+#[derive(Subcommand)]
+enum ReportCommands {
+    /// Draft or file tracker issues for detections not filed before
+    Issues {
+        /// JSON result file produced by `analyze-files --format json --output-file`
+        results: PathBuf,
+        /// Issue tracker
+        #[arg(long)]
+        tracker: TrackerArg,
+        /// Jira project key, or Linear team id
+        #[arg(long)]
+        project: String,
+        /// Lowest severity to file (info, low, medium, high or critical)
+        #[arg(long, default_value = "critical")]
+        min_severity: String,
+        /// Create the issues through the tracker's API instead of printing payloads
+        #[arg(long)]
+        create: bool,
+        /// Record the printed payloads as filed, for a pipeline that posts them
+        #[arg(long, conflicts_with = "create")]
+        mark_filed: bool,
+        /// Write the payloads to this file instead of stdout
+        #[arg(short, long, conflicts_with = "create")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Issue tracker of `report issues`
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum TrackerArg {
+    /// Atlassian Jira (REST API v2)
+    Jira,
+    /// Linear (GraphQL API)
+    Linear,
+}
+
+impl From<TrackerArg> for sniff::issue_export::Tracker {
+    fn from(tracker: TrackerArg) -> Self {
+        match tracker {
+            TrackerArg::Jira => Self::Jira,
+            TrackerArg::Linear => Self::Linear,
+        }
+    }
+}
+
+/// Kind of text checked by `check-text`
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum TextKindArg {
+    /// Git commit message
+    CommitMessage,
+    /// Pull request description
+    PrDescription,
+}
+
+impl From<TextKindArg> for sniff::text_check::TextKind {
+    fn from(kind: TextKindArg) -> Self {
+        match kind {
+            TextKindArg::CommitMessage => Self::CommitMessage,
+            TextKindArg::PrDescription => Self::PrDescription,
+        }
+    }
+}
+
+/// TODO verification commands
+#[derive(Subcommand)]
+enum VerifyTodoCommands {
+    /// Show past verification attempts at a TODO, oldest first
+    History {
+        /// TODO ID to show attempts for
+        todo_id: String,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+}
+
+/// Audit log commands
+#[derive(Subcommand)]
+enum AuditCommands {
+    /// Show recorded gate decisions, most recent last
+    Show {
+        /// Only show decisions for this TODO
+        #[arg(long)]
+        todo_id: Option<String>,
+        /// Maximum number of entries to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+}
+
+/// Team baseline commands
+#[derive(Subcommand)]
+enum BaselineCommands {
+    /// Upload the local `baseline` checkpoint as the team baseline
+    Push {
+        /// Remote store (defaults to [baseline] remote in .sniff.toml)
+        #[arg(long)]
+        remote: Option<String>,
+    },
+    /// Replace the local `baseline` checkpoint with the team baseline
+    Pull {
+        /// Remote store (defaults to [baseline] remote in .sniff.toml)
+        #[arg(long)]
+        remote: Option<String>,
+    },
+}
+
+/// Remote storage commands
+#[derive(Subcommand)]
+enum RemoteCommands {
+    /// Upload the project's checkpoints and results history
+    Push {
+        /// Remote store: s3://bucket/prefix, gs://bucket/prefix or file:///path
+        url: String,
+    },
+    /// Download checkpoints and results history into the project
+    Pull {
+        /// Remote store: s3://bucket/prefix, gs://bucket/prefix or file:///path
+        url: String,
+    },
+}
+
+/// Configuration commands
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print the JSON Schema of the .sniff.toml format
+    Schema,
+    /// Check a .sniff.toml for syntax errors, unknown keys and invalid values
+    Validate {
+        /// Config file to check (defaults to ./.sniff.toml)
+        path: Option<PathBuf>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+}
+
+/// Session analysis commands
+#[derive(Subcommand)]
+enum SessionCommands {
+    /// Score an agent session: quality of touched files, deception indicators, gates, tokens and time
+    Scorecard {
+        /// Session id or path to a session transcript (.jsonl)
+        session: String,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Flag intent-level red flags in assistant thinking (all sessions if none given)
+    Thinking {
+        /// Session id or path to a session transcript (.jsonl)
+        session: Option<String>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Flag prompt-injection markers in tool results and fetched content (all sessions if none given)
+    Injections {
+        /// Session id or path to a session transcript (.jsonl)
+        session: Option<String>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Audit Bash commands run in sessions for risky executions (all sessions if none given)
+    RiskyCommands {
+        /// Session id or path to a session transcript (.jsonl)
+        session: Option<String>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Report external endpoints contacted by agents: domains, frequency, first seen (all sessions if none given)
+    Egress {
+        /// Session id or path to a session transcript (.jsonl)
+        session: Option<String>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// List files read, written or edited in sessions, flagging access outside the workspace
+    /// and to credential files (all sessions if none given)
+    Files {
+        /// Session id or path to a session transcript (.jsonl)
+        session: Option<String>,
+        /// Only show flagged accesses
+        #[arg(long)]
+        flagged: bool,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Evaluate a session against a session policy (banned commands, banned paths, required
+    /// test runs); fails if the policy is violated
+    Enforce {
+        /// Session id or path to a session transcript (.jsonl)
+        session: String,
+        /// Policy file
+        #[arg(long, default_value = sniff::session_policy::DEFAULT_POLICY_PATH)]
+        policy: PathBuf,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
+    /// Live terminal view of a session while the agent works: tool calls, files touched,
+    /// detection counts and alerts (follows the most recent session if none given)
+    Monitor {
+        /// Session id or path to a session transcript (.jsonl)
+        session: Option<String>,
+        /// Session policy to raise alerts for (defaults to .sniff/session-policy.yaml if present)
+        #[arg(long)]
+        policy: Option<PathBuf>,
+        /// Quiet time after the last transcript change before re-reading it (milliseconds)
+        #[arg(long, default_value = "500")]
+        debounce_ms: u64,
+        /// Largest number of changed files analyzed per batch
+        #[arg(long, default_value = "20")]
+        max_batch: usize,
+    },
+    /// Compare quality and deception rates across models over all sessions
+    CompareModels {
+        /// Only include sessions started on or after this date (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// Only include the most recent N sessions
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
     },
 }
 
@@ -137,6 +679,12 @@ enum CheckpointCommands {
         /// Description of the checkpoint
         #[arg(short, long)]
         description: Option<String>,
+        /// Only capture files matching this glob (repeatable); stored for later diffs
+        #[arg(long)]
+        include: Vec<String>,
+        /// Leave out files matching this glob (repeatable); stored for later diffs
+        #[arg(long)]
+        exclude: Vec<String>,
     },
     /// List available checkpoints
     List {
@@ -156,11 +704,18 @@ enum CheckpointCommands {
     Diff {
         /// Checkpoint name to compare against
         checkpoint: String,
-        /// Paths to compare (optional, uses checkpoint paths if not provided)
+        /// Paths to compare (defaults to the paths the checkpoint was created with)
         paths: Option<Vec<PathBuf>>,
         /// Output format
         #[arg(short, long, default_value = "table")]
         format: OutputFormat,
+        /// Do not count changes to indentation, trailing whitespace or blank lines
+        #[arg(long)]
+        ignore_whitespace: bool,
+        /// Do not count formatting changes (whitespace, line breaks, trailing commas),
+        /// e.g. from a `cargo fmt` pass
+        #[arg(long)]
+        ignore_format: bool,
     },
     /// Delete a checkpoint
     Delete {
@@ -211,7 +766,12 @@ enum PatternCommands {
         #[arg(long)]
         false_positives: Vec<String>,
     },
-    /// List learned patterns
+    /// List loaded patterns by namespaced id (`namespace/rule_id`)
+    ///
+    /// When several playbooks of a language define the same rule id, learned
+    /// patterns take precedence over pattern packs, and packs over the built-in
+    /// rules; among equals the first loaded wins. The other definitions are
+    /// shadowed and not applied.
     List {
         /// Filter by programming language
         #[arg(short, long)]
@@ -222,6 +782,9 @@ enum PatternCommands {
         /// Show only active patterns
         #[arg(short, long)]
         active_only: bool,
+        /// Show only rule ids defined by more than one playbook, and which definition wins
+        #[arg(long)]
+        conflicts: bool,
     },
     /// Show pattern statistics
     Stats {
@@ -252,12 +815,83 @@ enum PatternCommands {
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
-    /// Validate existing learned patterns
+    /// Create learned patterns in bulk from a CSV or JSON file
+    ///
+    /// Columns (CSV header) or keys (JSON objects): name, regex, language, and
+    /// optionally severity, description, scope and examples.
+    Import {
+        /// CSV file with a header row, or JSON file holding an array of objects
+        file: PathBuf,
+    },
+    /// Validate loaded playbooks and run every rule against its test cases
     Validate {
         /// Fix invalid patterns automatically
         #[arg(long)]
         fix: bool,
     },
+    /// Run rules against their examples (which must be flagged) and false
+    /// positives (which must not)
+    ///
+    /// With --golden, analyze every source file in a directory instead and
+    /// compare its detections with the `<file>.expected.yaml` next to it.
+    Test {
+        /// Filter by programming language
+        #[arg(short, long)]
+        language: Option<String>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+        /// Directory of source files with golden files of expected detections
+        #[arg(long, value_name = "DIR")]
+        golden: Option<PathBuf>,
+        /// Write the current detections to golden files that are missing or differ
+        #[arg(long, requires = "golden")]
+        update: bool,
+        /// Use only the rules shipped with sniff, ignoring installed playbooks,
+        /// learned patterns, plugins and .sniff.toml, so golden results do not
+        /// depend on the machine
+        #[arg(long, requires = "golden")]
+        bundled: bool,
+    },
+    /// Install a versioned pattern pack into ~/.sniff/patterns
+    ///
+    /// Packs are named in a registry index (`name` or `name@version`), or
+    /// fetched from an https:// URL of a playbook YAML file or a git repository
+    /// (`git+<url>[@ref]`). Installs are recorded with their checksum in
+    /// ~/.sniff/patterns/packs.lock.
+    Install {
+        /// Pack name, name@version, https:// URL or git+ URL
+        #[arg(required_unless_present = "locked")]
+        source: Option<String>,
+        /// Expected SHA-256 checksum of the pack
+        #[arg(long)]
+        sha256: Option<String>,
+        /// Registry index URL or path (defaults to $SNIFF_PACK_REGISTRY)
+        #[arg(long)]
+        registry: Option<String>,
+        /// Reinstall every pack in packs.lock at its locked version
+        #[arg(long, conflicts_with = "source")]
+        locked: bool,
+        /// Minisign public key trusted to sign packs, in addition to those listed in
+        /// ~/.sniff/patterns/trusted-keys (repeatable)
+        #[arg(long = "trusted-key", value_name = "KEY")]
+        trusted_keys: Vec<String>,
+        /// Install packs without a signature from a trusted key
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+    /// Run the ruleset over code written during an agent session
+    Replay {
+        /// Session id or path to a session transcript (.jsonl)
+        #[arg(long)]
+        session: String,
+        /// Directory holding session transcripts (defaults to ~/.claude/projects)
+        #[arg(long)]
+        projects_dir: Option<PathBuf>,
+        /// Output format
+        #[arg(short, long, default_value = "table")]
+        format: OutputFormat,
+    },
 }
 
 #[tokio::main]
@@ -265,15 +899,26 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     // Initialize logging based on verbosity level
+    let quiet = matches!(cli.command, Commands::VerifyTodo { quiet: true, .. });
     let log_level = match cli.verbose {
+        _ if quiet => Level::ERROR,
         0 => Level::WARN,
         1 => Level::INFO,
         2 => Level::DEBUG,
         _ => Level::TRACE,
     };
-    fmt().with_max_level(log_level).with_target(false).init();
+    // Log to stderr so JSON output on stdout can be redirected to a file
+    fmt()
+        .with_max_level(log_level)
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
 
     info!("Starting Sniff CLI v{}", env!("CARGO_PKG_VERSION"));
+    if cli.offline {
+        sniff::network::disable();
+        info!("Offline mode: network features are disabled");
+    }
 
     // Execute the selected command
     match cli.command {
@@ -282,75 +927,329 @@ async fn main() -> Result<()> {
             format,
             detailed,
             include_hidden,
+            no_ignore,
+            tracked_only,
             extensions,
             exclude,
             max_file_size_mb,
             force_language,
             output_file,
+            template,
             checkpoint,
             diff_checkpoint,
+            diff,
+            checkpoint_dir,
+            no_baseline,
             include_tests,
             test_confidence,
+            show_suppressed,
+            insights,
+            enhanced,
+            ai_likelihood,
+            projects_dir,
+            config_files,
+            markdown,
+            per_function,
+            shard,
+            export_sqlite,
+            fail_if_skipped_over,
+            fix,
+            fix_dry_run,
         } => {
             handle_analyze_files_command(AnalyzeFilesArgs {
                 paths,
                 format,
                 detailed,
                 include_hidden,
+                no_ignore,
+                tracked_only,
                 extensions,
                 exclude,
                 max_file_size_mb,
                 force_language,
                 output_file,
+                template,
                 checkpoint,
                 diff_checkpoint,
+                diff,
+                checkpoint_dir,
+                no_baseline,
                 include_tests,
                 test_confidence,
+                show_suppressed,
+                insights,
+                enhanced,
+                ai_likelihood,
+                projects_dir,
+                config_files,
+                markdown,
+                per_function,
+                shard,
+                export_sqlite,
+                fail_if_skipped_over,
+                fix,
+                fix_dry_run,
+                read_only: cli.read_only,
+                reproducible: cli.reproducible,
             })
             .await
         }
 
-        Commands::Checkpoint { command } => handle_checkpoint_command(command).await,
-
-        Commands::Patterns { command } => handle_patterns_command(command).await,
-
-        Commands::VerifyTodo {
-            todo_id,
+        Commands::MergeResults {
             files,
+            format,
+            detailed,
             min_quality_score,
             max_critical_issues,
+        } => handle_merge_results_command(
+            &files,
             format,
-            git_discovery,
-        } => {
-            handle_verify_todo_command(todo_id, files, min_quality_score, max_critical_issues, format, git_discovery)
-                .await
-        }
-    }
-}
+            detailed,
+            min_quality_score,
+            max_critical_issues,
+        ),
 
-// Keep only the modern command handlers from the original main.rs
-// These will be copied from the original file...
+        Commands::OrgReport {
+            files,
+            format,
+            top_rules,
+        } => handle_org_report_command(&files, format, top_rules),
 
-/// Handles the analyze-files command - analyzes arbitrary files for misalignment patterns.
+        Commands::Badge {
+            results,
+            output,
+            label,
+        } => handle_badge_command(&results, output.as_deref(), &label, cli.read_only),
+
+        Commands::Report { command } => match command {
+            ReportCommands::Issues {
+                results,
+                tracker,
+                project,
+                min_severity,
+                create,
+                mark_filed,
+                output,
+            } => {
+                let record = create || mark_filed;
+                if cli.read_only && record {
+                    report_read_only("filed issues not recorded");
+                }
+                handle_report_issues_command(
+                    &results,
+                    tracker.into(),
+                    &project,
+                    &min_severity,
+                    create,
+                    output.as_deref(),
+                    record && !cli.read_only,
+                )
+            }
+        },
+
+        Commands::Query { sql, db, format } => handle_query_command(&sql, &db, format),
+
+        Commands::Checkpoint {
+            checkpoint_dir,
+            command,
+        } => handle_checkpoint_command(command, checkpoint_dir.as_deref(), cli.read_only).await,
+
+        Commands::Patterns { command } => handle_patterns_command(command, cli.read_only).await,
+
+        Commands::Perf {
+            paths,
+            format,
+            include_hidden,
+            extensions,
+            exclude,
+            include_tests,
+        } => {
+            handle_perf_command(
+                paths,
+                format,
+                include_hidden,
+                extensions,
+                exclude,
+                include_tests,
+                cli.read_only,
+            )
+            .await
+        }
+
+        Commands::Semantics { files, format } => handle_semantics_command(files, format),
+
+        Commands::Waive {
+            fingerprint,
+            until,
+            reason,
+            list,
+            remove,
+        } => handle_waive_command(fingerprint, until, reason, list, remove, cli.read_only),
+
+        Commands::Baseline {
+            checkpoint_dir,
+            command,
+        } => handle_baseline_command(command, checkpoint_dir.as_deref(), cli.read_only).await,
+
+        Commands::Remote {
+            checkpoint_dir,
+            command,
+        } => handle_remote_command(command, checkpoint_dir.as_deref(), cli.read_only).await,
+
+        Commands::Audit { command } => handle_audit_command(command),
+
+        Commands::Config { command } => handle_config_command(command),
+
+        Commands::Sessions {
+            projects_dir,
+            command,
+        } => handle_sessions_command(command, projects_dir, cli.read_only),
+
+        Commands::CheckText {
+            kind,
+            rules,
+            fail_on,
+            format,
+        } => handle_check_text_command(kind.into(), rules.as_deref(), &fail_on, format),
+
+        Commands::Doctor { format } => handle_doctor_command(format, cli.read_only),
+
+        Commands::Bench {
+            corpus,
+            iterations,
+            tracked_only,
+            exclude,
+            max_file_size_mb,
+            format,
+        } => {
+            let filter = sniff::standalone::FileFilter {
+                tracked_only,
+                exclude_pattern: exclude,
+                max_file_size_bytes: (max_file_size_mb * 1024.0 * 1024.0) as u64,
+                ..sniff::standalone::FileFilter::default()
+            };
+            handle_bench_command(&corpus, iterations, filter, format, cli.read_only).await
+        }
+
+        Commands::VerifyTodo {
+            command: Some(VerifyTodoCommands::History { todo_id, format }),
+            ..
+        } => handle_verify_history_command(&todo_id, format),
+
+        Commands::VerifyTodo {
+            command: None,
+            todo_id,
+            files,
+            min_quality_score,
+            max_critical_issues,
+            format,
+            git_discovery,
+            criteria,
+            quiet,
+        } => {
+            use sniff::verify_todo::{
+                VerificationConfig, EXIT_GATE_FAILED, EXIT_TOOL_ERROR, GATE_REPORT_SCHEMA_VERSION,
+            };
+
+            // Required by clap unless a subcommand is given
+            let todo_id = todo_id.unwrap_or_default();
+            // An unsupported format surfaces as a tool error from the handler below
+            let json = is_json_format(format).unwrap_or(false);
+            let criteria = criteria
+                .map(|path| sniff::criteria::load(&path, &todo_id))
+                .transpose();
+            let outcome = match criteria {
+                Ok(criteria) => {
+                    let config = VerificationConfig {
+                        min_quality_score,
+                        max_critical_issues,
+                        // Exclude test files by default for quality verification
+                        include_test_files: false,
+                        read_only: cli.read_only,
+                        reproducible: cli.reproducible,
+                        criteria: criteria.unwrap_or_default(),
+                    };
+                    handle_verify_todo_command(
+                        todo_id.clone(),
+                        files,
+                        config,
+                        format,
+                        git_discovery,
+                        quiet,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+
+            // Orchestrators branch on the exit code: gate failures and tool errors differ
+            match outcome {
+                Ok(true) => Ok(()),
+                Ok(false) => std::process::exit(EXIT_GATE_FAILED),
+                Err(e) => {
+                    if json {
+                        let report = serde_json::json!({
+                            "schema_version": GATE_REPORT_SCHEMA_VERSION,
+                            "todo_id": todo_id,
+                            "pass": false,
+                            "error": e.to_string(),
+                        });
+                        println!("{}", serde_json::to_string_pretty(&report)?);
+                    } else {
+                        eprintln!("Error: {e}");
+                    }
+                    std::process::exit(EXIT_TOOL_ERROR)
+                }
+            }
+        }
+    }
+}
+
+// Keep only the modern command handlers from the original main.rs
+// These will be copied from the original file...
+
+/// Handles the analyze-files command - analyzes arbitrary files for misalignment patterns.
 struct AnalyzeFilesArgs {
     paths: Vec<PathBuf>,
     format: OutputFormat,
     detailed: bool,
     include_hidden: bool,
+    no_ignore: bool,
+    tracked_only: bool,
     extensions: Option<String>,
     exclude: Option<String>,
     max_file_size_mb: f64,
     force_language: Option<String>,
     output_file: Option<PathBuf>,
+    template: Option<PathBuf>,
     checkpoint: Option<String>,
     diff_checkpoint: Option<String>,
+    diff: Option<String>,
+    checkpoint_dir: Option<PathBuf>,
+    no_baseline: bool,
     include_tests: bool,
     test_confidence: f64,
+    show_suppressed: bool,
+    insights: bool,
+    enhanced: bool,
+    ai_likelihood: bool,
+    projects_dir: Option<PathBuf>,
+    config_files: bool,
+    markdown: bool,
+    per_function: Option<usize>,
+    shard: Option<sniff::standalone::Shard>,
+    export_sqlite: Option<PathBuf>,
+    fail_if_skipped_over: Option<f64>,
+    fix: bool,
+    fix_dry_run: bool,
+    read_only: bool,
+    reproducible: bool,
 }
 
 async fn handle_analyze_files_command(args: AnalyzeFilesArgs) -> Result<()> {
-    use sniff::analysis::MisalignmentAnalyzer;
-    use sniff::standalone::{AnalysisConfig, CheckpointManager, FileFilter, StandaloneAnalyzer};
+    use sniff::standalone::{
+        AnalysisConfig, CheckpointScope, ComparisonMode, FileFilter, StandaloneAnalyzer,
+    };
 
     info!(">> Starting standalone file analysis");
 
@@ -363,6 +1262,8 @@ async fn handle_analyze_files_command(args: AnalyzeFilesArgs) -> Result<()> {
 
     let filter = FileFilter {
         include_hidden: args.include_hidden,
+        respect_ignore_files: !args.no_ignore,
+        tracked_only: args.tracked_only,
         allowed_extensions,
         exclude_pattern: args.exclude,
         max_file_size_bytes: (args.max_file_size_mb * 1024.0 * 1024.0) as u64,
@@ -370,53 +1271,102 @@ async fn handle_analyze_files_command(args: AnalyzeFilesArgs) -> Result<()> {
         test_confidence_threshold: args.test_confidence,
     };
 
+    // Compare against the team baseline when .sniff.toml configures one
+    let diff_checkpoint = match args.diff_checkpoint {
+        None if args.checkpoint.is_none() && args.diff.is_none() && !args.no_baseline => {
+            team_baseline(
+                args.paths.first(),
+                args.checkpoint_dir.as_deref(),
+                args.read_only,
+            )
+            .await?
+        }
+        diff_checkpoint => diff_checkpoint,
+    };
+
     // Create analysis config
     let config = AnalysisConfig {
         filter,
-        force_language: args.force_language.and_then(|lang| match lang.to_lowercase().as_str() {
-            "rust" => Some(sniff::SupportedLanguage::Rust),
-            "python" => Some(sniff::SupportedLanguage::Python),
-            "typescript" => Some(sniff::SupportedLanguage::TypeScript),
-            "javascript" => Some(sniff::SupportedLanguage::JavaScript),
-            "go" => Some(sniff::SupportedLanguage::Go),
-            "c" => Some(sniff::SupportedLanguage::C),
-            "cpp" => Some(sniff::SupportedLanguage::Cpp),
-            _ => {
-                warn!("Unknown language '{}', will auto-detect", lang);
-                None
-            }
-        }),
+        force_language: args
+            .force_language
+            .and_then(|lang| match lang.to_lowercase().as_str() {
+                "rust" => Some(sniff::SupportedLanguage::Rust),
+                "python" => Some(sniff::SupportedLanguage::Python),
+                "typescript" => Some(sniff::SupportedLanguage::TypeScript),
+                "javascript" => Some(sniff::SupportedLanguage::JavaScript),
+                "go" => Some(sniff::SupportedLanguage::Go),
+                "c" => Some(sniff::SupportedLanguage::C),
+                "cpp" => Some(sniff::SupportedLanguage::Cpp),
+                "swift" => Some(sniff::SupportedLanguage::Swift),
+                "terraform" | "hcl" => Some(sniff::SupportedLanguage::Terraform),
+                "scala" => Some(sniff::SupportedLanguage::Scala),
+                "config" | "yaml" | "json" => Some(sniff::SupportedLanguage::Config),
+                "markdown" | "md" => Some(sniff::SupportedLanguage::Markdown),
+                _ => {
+                    warn!("Unknown language '{}', will auto-detect", lang);
+                    None
+                }
+            }),
         detailed_analysis: args.detailed,
+        include_insights: args.insights,
+        enhanced_analysis: args.enhanced,
+        shard: args.shard,
+        show_suppressed: args.show_suppressed,
+        reproducible: args.reproducible,
+        ai_likelihood: args.ai_likelihood,
+        config_files: args.config_files,
+        markdown: args.markdown,
+        function_scores: args.per_function.is_some()
+            || args.checkpoint.is_some()
+            || diff_checkpoint.is_some()
+            || args.diff.is_some(),
     };
 
-    // Initialize analyzer with default patterns
-    let mut misalignment_analyzer = MisalignmentAnalyzer::new()?;
-    
-    // Install and load enhanced playbooks from .sniff/patterns/
-    let sniff_dir = ensure_sniff_directory()?;
-    let patterns_dir = sniff_dir.join("patterns");
-    
-    // Install playbooks if they don't exist
-    if !patterns_dir.exists() {
-        install_default_playbooks(&patterns_dir)?;
-    }
-    
-    // Always load patterns from .sniff/patterns/
-    if let Err(e) = misalignment_analyzer.load_playbooks(&patterns_dir) {
-        warn!("Failed to load playbooks from {}: {}", patterns_dir.display(), e);
-    } else {
-        info!("Loaded enhanced playbooks from {}", patterns_dir.display());
-    }
+    // Initialize analyzer with default patterns and installed playbooks
+    let misalignment_analyzer = create_misalignment_analyzer(args.read_only)?;
     let mut analyzer = StandaloneAnalyzer::new(misalignment_analyzer, config);
+    if args.ai_likelihood {
+        analyzer = analyzer.with_session_writes(load_session_writes(args.projects_dir));
+    }
+
+    // Arrow output written to a file is not also streamed to stdout
+    // A template replaces the --format output
+    let writes_to_file = (matches!(args.format, OutputFormat::Arrow) && args.output_file.is_some())
+        || args.template.is_some();
+
+    // Handle git revision or checkpoint comparison if requested
+    let results = if let Some(revision) = &args.diff {
+        let changed = sniff::standalone::git_changed_lines(revision, &args.paths).await?;
+        if changed.is_empty() {
+            println!(">> No changes since {}", revision);
+            return Ok(());
+        }
+
+        let changed_files: Vec<PathBuf> = changed.keys().cloned().collect();
+        println!(
+            "[ANALYSIS] Analyzing {} changed files since {}",
+            changed_files.len(),
+            revision
+        );
 
-    // Handle checkpoint comparison if requested
-    if let Some(checkpoint_name) = args.diff_checkpoint {
-        let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
-        let checkpoint_manager = CheckpointManager::new(&current_dir)?;
+        let mut results = analyzer.analyze_files(&changed_files).await?;
+        results.changed_functions = analyzer
+            .changed_functions_since(&results, revision, &changed)
+            .await;
+        if let Some(limit) = args.per_function {
+            results.worst_functions = analyzer.worst_functions(&results, limit);
+        }
+        if !writes_to_file {
+            display_standalone_results(&results, args.format, args.detailed, None)?;
+        }
+        results
+    } else if let Some(checkpoint_name) = diff_checkpoint {
+        let checkpoint_manager =
+            open_checkpoint_manager(args.checkpoint_dir.as_deref(), args.paths.first())?;
 
         info!("[INFO] Comparing against checkpoint: {}", checkpoint_name);
         let comparison = checkpoint_manager
-            .compare_files(&checkpoint_name, &args.paths)
+            .compare_files(&checkpoint_name, Some(&args.paths), ComparisonMode::Exact)
             .await?;
 
         // Analyze only changed files
@@ -441,440 +1391,4044 @@ async fn handle_analyze_files_command(args: AnalyzeFilesArgs) -> Result<()> {
             checkpoint_name
         );
 
-        let results = analyzer.analyze_files(&changed_files).await?;
-        display_standalone_results(&results, args.format, args.detailed, Some(&comparison))?;
+        let mut results = analyzer.analyze_files(&changed_files).await?;
+        let recorded = checkpoint_manager.recorded_files(&checkpoint_name).await?;
+        results.changed_functions = analyzer.changed_functions(&results, &recorded);
+        if let Some(limit) = args.per_function {
+            results.worst_functions = analyzer.worst_functions(&results, limit);
+        }
+        if !writes_to_file {
+            display_standalone_results(&results, args.format, args.detailed, Some(&comparison))?;
+        }
+        results
     } else {
         // Analyze specified files/directories
-        let results = analyzer.analyze_files(&args.paths).await?;
+        let mut results = analyzer.analyze_files(&args.paths).await?;
+        if let Some(limit) = args.per_function {
+            results.worst_functions = analyzer.worst_functions(&results, limit);
+        }
 
         // Create checkpoint if requested
         if let Some(checkpoint_name) = args.checkpoint {
-            let current_dir =
-                std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
-            let checkpoint_manager = CheckpointManager::new(&current_dir)?;
+            if args.read_only {
+                report_read_only(&format!("checkpoint '{checkpoint_name}' not created"));
+            } else {
+                let checkpoint_manager =
+                    open_checkpoint_manager(args.checkpoint_dir.as_deref(), args.paths.first())?;
 
-            info!(">> Creating checkpoint: {}", checkpoint_name);
-            checkpoint_manager
-                .create_checkpoint(&checkpoint_name, &args.paths, None)
-                .await?;
-            println!(">> Checkpoint '{}' created", checkpoint_name);
+                info!(">> Creating checkpoint: {}", checkpoint_name);
+                checkpoint_manager
+                    .create_checkpoint(
+                        &checkpoint_name,
+                        &CheckpointScope::new(args.paths.clone()),
+                        None,
+                    )
+                    .await?;
+                checkpoint_manager
+                    .record_function_scores(
+                        &checkpoint_name,
+                        analyzer.all_function_scores(&results),
+                    )
+                    .await?;
+                println!(">> Checkpoint '{}' created", checkpoint_name);
+            }
         }
 
-        display_standalone_results(&results, args.format, args.detailed, None)?;
-    }
+        if !writes_to_file {
+            display_standalone_results(&results, args.format, args.detailed, None)?;
+        }
+        results
+    };
 
-    // Save results to file if requested
-    if let Some(output_path) = args.output_file {
-        // Implement result serialization
+    // Render the template to the output file or stdout, or save results to file if requested
+    if let Some(template_path) = &args.template {
+        let rendered = sniff::template::render_file(template_path, &results)?;
+        if let Some(output_path) = &args.output_file {
+            fs::write(output_path, rendered)
+                .map_err(|e| SniffError::file_system(output_path, e))?;
+            eprintln!(">> Report rendered to {}", output_path.display());
+        } else {
+            println!("{rendered}");
+        }
+    } else if let Some(output_path) = args.output_file {
         info!("[SAVE] Saving results to: {}", output_path.display());
-        // This would serialize the results in the requested format
-        println!(">> Result saving not yet implemented");
+        save_results(&results, args.format, &output_path)?;
+        eprintln!(">> Results saved to {}", output_path.display());
+    }
+
+    // Append results to the SQLite history if requested
+    if let Some(db_path) = args.export_sqlite {
+        if args.read_only {
+            report_read_only(&format!("results not exported to {}", db_path.display()));
+        } else {
+            let mut database = sniff::history::ResultsDatabase::open(&db_path)?;
+            let run_id = database.record_run(&results)?;
+            eprintln!(">> Run {} exported to {}", run_id, db_path.display());
+        }
+    }
+
+    if args.fix || args.fix_dry_run {
+        let dry_run = args.fix_dry_run || args.read_only;
+        if args.fix && args.read_only {
+            report_read_only("fixes not applied");
+        }
+        let fixes = sniff::fix::fix_results(&results, analyzer.playbook_manager(), dry_run)?;
+        report_fixes(&fixes, dry_run);
+    }
+
+    if let (Some(max_skipped), Some(coverage)) = (args.fail_if_skipped_over, &results.coverage) {
+        let skipped = coverage.skipped_percentage();
+        if skipped > max_skipped {
+            return Err(SniffError::analysis_error(format!(
+                "{:.1}% of the selected files were not analyzed (limit {:.1}%)",
+                skipped, max_skipped
+            )));
+        }
+    }
+
+    let gate_failures = analyzer.project_gate_failures(&results)?;
+    if !gate_failures.is_empty() {
+        return Err(SniffError::analysis_error(format!(
+            "{} quality gates from .sniff.toml failed:\n  {}",
+            gate_failures.len(),
+            gate_failures.join("\n  ")
+        )));
     }
 
     Ok(())
 }
 
-// Additional modern command handlers would go here...
-// These need to be copied from the original main.rs file
+/// Prints what `--fix` changed (or would change) to stderr, so it does not mix
+/// with the report on stdout.
+fn report_fixes(fixes: &[sniff::fix::FileFixes], dry_run: bool) {
+    let fixed: usize = fixes.iter().map(|file| file.fixed).sum();
+    let skipped: usize = fixes.iter().map(|file| file.skipped).sum();
+    let files = fixes.iter().filter(|file| file.fixed > 0).count();
+    let verb = if dry_run { "Would fix" } else { "Fixed" };
+    eprintln!(">> {} {} detections in {} files", verb, fixed, files);
 
-/// Displays standalone analysis results.
-fn display_standalone_results(
+    for file in fixes {
+        for line in &file.lines {
+            eprintln!(
+                "{}:{} [{}]",
+                file.path.display(),
+                line.line,
+                line.rule_ids.join(", ")
+            );
+            eprintln!("  - {}", line.before);
+            if let Some(after) = &line.after {
+                eprintln!("  + {}", after);
+            }
+        }
+    }
+    if skipped > 0 {
+        eprintln!(
+            ">> Skipped {} detections whose code changed or that overlap another fix",
+            skipped
+        );
+    }
+}
+
+/// Saves results to a file in the requested report format (Arrow IPC, SARIF, JUnit XML,
+/// Code Climate, Reviewdog); JSON otherwise. A `.html` or `.htm` path always produces the
+/// standalone HTML report.
+fn save_results(
     results: &sniff::standalone::AnalysisResults,
     format: OutputFormat,
-    detailed: bool,
-    comparison: Option<&sniff::standalone::FileComparison>,
+    output_path: &PathBuf,
 ) -> Result<()> {
-    match format {
-        OutputFormat::Table => {
-            println!(":: Standalone File Analysis Results");
-            println!("═══════════════════════════════════════");
-            println!();
-
-            if let Some(comp) = comparison {
-                println!(">> Change Summary:");
-                println!("   New files: {}", comp.new_files.len());
-                println!("   Modified files: {}", comp.changed_files.len());
-                println!("   Deleted files: {}", comp.deleted_files.len());
-                println!();
-            }
-
-            println!(">> Analysis Summary:");
-            println!("   Files analyzed: {}", results.total_files);
-            println!("   Total patterns: {}", results.total_detections);
-            println!("   Critical issues: {}", results.critical_issues);
-            println!("   Average quality: {:.1}%", results.average_quality_score);
-            println!();
+    let file =
+        fs::File::create(output_path).map_err(|e| SniffError::file_system(output_path, e))?;
+    let writer = std::io::BufWriter::new(file);
 
-            if !results.file_results.is_empty() {
-                println!(">> File Analysis:");
-                for file_result in &results.file_results {
-                    if !file_result.detections.is_empty() {
-                        println!(
-                            "   {} ({})",
-                            file_result.file_path.display(),
-                            file_result.language.map(|l| l.name()).unwrap_or("unknown")
-                        );
-                        println!(
-                            "      Issues: {} | Quality: {:.1}%",
-                            file_result.detections.len(),
-                            file_result.quality_score
-                        );
+    let is_html = output_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+    if is_html {
+        use std::io::Write;
 
-                        if detailed {
-                            for detection in &file_result.detections {
-                                println!(
-                                    "         {} {} ({}:{}): {}",
-                                    detection.severity.emoji(),
-                                    detection.rule_name,
-                                    detection.file_path,
-                                    detection.line_number,
-                                    detection.code_snippet.trim()
-                                );
-                            }
-                        }
-                        println!();
-                    }
-                }
-            }
+        let mut writer = writer;
+        return writer
+            .write_all(sniff::html_report::render(results).as_bytes())
+            .map_err(|e| SniffError::file_system(output_path, e));
+    }
 
-            if results.critical_issues > 0 {
-                println!(
-                    "!! {} critical issues detected that require immediate attention",
-                    results.critical_issues
-                );
-            } else if results.total_detections == 0 {
-                println!(">> No issues detected! Code quality looks excellent.");
-            }
+    match format {
+        OutputFormat::Arrow => sniff::columnar::write_arrow_ipc(results, writer),
+        OutputFormat::Sarif => {
+            serde_json::to_writer_pretty(writer, &sniff::report::sarif(results))?;
+            Ok(())
         }
+        OutputFormat::Junit => {
+            use std::io::Write;
 
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(results)?);
+            let mut writer = writer;
+            writer
+                .write_all(sniff::report::junit(results).as_bytes())
+                .map_err(|e| SniffError::file_system(output_path, e))
         }
-
-        OutputFormat::Markdown => {
-            println!("# Standalone File Analysis Results");
-            println!();
-            println!("## Summary");
-            println!();
-            println!("| Metric | Value |");
-            println!("| ------ | ----- |");
-            println!("| Files analyzed | {} |", results.total_files);
-            println!("| Total patterns | {} |", results.total_detections);
-            println!("| Critical issues | {} |", results.critical_issues);
-            println!(
-                "| Average quality | {:.1}% |",
-                results.average_quality_score
-            );
-            println!();
-
-            if !results.file_results.is_empty() {
-                println!("## File Analysis");
-                println!();
-                for file_result in &results.file_results {
-                    if !file_result.detections.is_empty() {
-                        println!("### `{}`", file_result.file_path.display());
-                        println!();
-                        println!(
-                            "- **Language**: {}",
-                            file_result.language.map(|l| l.name()).unwrap_or("unknown")
-                        );
-                        println!("- **Issues**: {}", file_result.detections.len());
-                        println!("- **Quality**: {:.1}%", file_result.quality_score);
-                        println!();
-
-                        if detailed {
-                            println!("#### Issues");
-                            println!();
-                            for detection in &file_result.detections {
-                                println!(
-                                    "- {} **{}** (line {}): `{}`",
-                                    detection.severity.emoji(),
-                                    detection.rule_name,
-                                    detection.line_number,
-                                    detection.code_snippet.trim()
-                                );
-                            }
-                            println!();
-                        }
-                    }
-                }
-            }
+        OutputFormat::Codeclimate => {
+            serde_json::to_writer_pretty(writer, &sniff::report::codeclimate(results))?;
+            Ok(())
+        }
+        OutputFormat::Rdjson => {
+            serde_json::to_writer_pretty(writer, &sniff::report::rdjson(results))?;
+            Ok(())
         }
+        OutputFormat::Rdjsonl => {
+            use std::io::Write;
 
-        OutputFormat::Compact => {
-            for file_result in &results.file_results {
-                if !file_result.detections.is_empty() {
-                    println!(
-                        "{}: {} issues, {:.1}% quality",
-                        file_result.file_path.display(),
-                        file_result.detections.len(),
-                        file_result.quality_score
-                    );
-                }
-            }
+            let mut writer = writer;
+            writer
+                .write_all(sniff::report::rdjsonl(results).as_bytes())
+                .map_err(|e| SniffError::file_system(output_path, e))
+        }
+        _ => {
+            serde_json::to_writer_pretty(writer, results)?;
+            Ok(())
         }
     }
-
-    Ok(())
 }
 
-// Modern command handlers (copied from legacy main.rs)
+/// Handles the merge-results command - combines sharded results and re-checks gates.
+fn handle_merge_results_command(
+    files: &[PathBuf],
+    format: OutputFormat,
+    detailed: bool,
+    min_quality_score: Option<f64>,
+    max_critical_issues: Option<usize>,
+) -> Result<()> {
+    use sniff::standalone::AnalysisResults;
 
-/// Handles checkpoint management commands.
-async fn handle_checkpoint_command(command: CheckpointCommands) -> Result<()> {
+    let mut parts = Vec::new();
+    for file in files {
+        parts.push(read_results_file(file)?);
+    }
+
+    let results = AnalysisResults::merge(parts);
+    display_standalone_results(&results, format, detailed, None)?;
+
+    let mut failures = Vec::new();
+    if let Some(min_quality_score) = min_quality_score {
+        if results.average_quality_score < min_quality_score {
+            failures.push(format!(
+                "quality {:.1}% < {:.1}%",
+                results.average_quality_score, min_quality_score
+            ));
+        }
+    }
+    if let Some(max_critical_issues) = max_critical_issues {
+        if results.critical_issues > max_critical_issues {
+            failures.push(format!(
+                "critical issues {} > {}",
+                results.critical_issues, max_critical_issues
+            ));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(SniffError::analysis_error(format!(
+            "Merged results failed quality gate: {}",
+            failures.join(", ")
+        )))
+    }
+}
+
+/// Reads analysis results written by `analyze-files --format json`.
+fn read_results_file(file: &PathBuf) -> Result<sniff::standalone::AnalysisResults> {
+    let content = fs::read_to_string(file).map_err(|e| SniffError::file_system(file, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| SniffError::invalid_format(file.display().to_string(), e.to_string()))
+}
+
+/// Handles the badge command - renders an SVG quality badge from saved results.
+fn handle_badge_command(
+    results_file: &PathBuf,
+    output: Option<&std::path::Path>,
+    label: &str,
+    read_only: bool,
+) -> Result<()> {
+    let results = read_results_file(results_file)?;
+    let svg = sniff::badge::render(&results, label);
+
+    match output {
+        Some(path) if read_only => {
+            report_read_only(&format!("badge not written to {}", path.display()));
+        }
+        Some(path) => {
+            fs::write(path, svg).map_err(|e| SniffError::file_system(path, e))?;
+            eprintln!(
+                ">> Badge ({}) written to {}",
+                sniff::badge::message(&results),
+                path.display()
+            );
+        }
+        None => print!("{svg}"),
+    }
+    Ok(())
+}
+
+/// Handles `report issues` - drafts tracker issues for detections that were not
+/// filed before, and creates them if asked. With `record`, each created issue,
+/// or each printed payload without `create`, is recorded as filed.
+fn handle_report_issues_command(
+    results_file: &PathBuf,
+    tracker: sniff::issue_export::Tracker,
+    project: &str,
+    min_severity: &str,
+    create: bool,
+    output: Option<&std::path::Path>,
+    record: bool,
+) -> Result<()> {
+    use sniff::issue_export::{self, FiledIssue, FiledIssues};
+    use sniff::playbook::Severity;
+
+    let min_severity = Severity::from_name(min_severity).ok_or_else(|| {
+        SniffError::invalid_format(
+            "severity".to_string(),
+            format!(
+                "Unknown severity '{min_severity}' (expected info, low, medium, high or critical)"
+            ),
+        )
+    })?;
+    let results = read_results_file(results_file)?;
+    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+    let filed = FiledIssues::new(&current_dir);
+    let drafts = issue_export::drafts(&results, min_severity, &filed.fingerprints(tracker)?);
+    if drafts.is_empty() {
+        eprintln!(">> No new detections to file in {}", tracker.name());
+        return Ok(());
+    }
+
+    let mut payloads = Vec::new();
+    for draft in &drafts {
+        let payload = draft.payload(tracker, project);
+        let issue = if create {
+            let issue = issue_export::create(tracker, &payload)?;
+            println!("{}  {}", issue, draft.summary);
+            Some(issue)
+        } else {
+            payloads.push(payload);
+            None
+        };
+        if record {
+            filed.append(&FiledIssue {
+                timestamp: chrono::Utc::now(),
+                tracker,
+                fingerprint: draft.fingerprint.clone(),
+                issue,
+            })?;
+        }
+    }
+
+    if !create {
+        let json = serde_json::to_string_pretty(&payloads)?;
+        match output {
+            Some(path) => {
+                fs::write(path, json).map_err(|e| SniffError::file_system(path, e))?;
+                eprintln!(
+                    ">> {} {} issue payloads written to {}",
+                    payloads.len(),
+                    tracker.name(),
+                    path.display()
+                );
+            }
+            None => println!("{json}"),
+        }
+    }
+    if record {
+        eprintln!(
+            ">> {} detections recorded as filed in {}",
+            drafts.len(),
+            filed.path().display()
+        );
+    }
+    Ok(())
+}
+
+/// Handles the query command - runs read-only SQL over the results history.
+fn handle_query_command(sql: &str, db: &std::path::Path, format: OutputFormat) -> Result<()> {
+    use sniff::history::ResultsDatabase;
+
+    let database = ResultsDatabase::open_read_only(db)?;
+    let result = database.query(sql)?;
+
+    let cell = |value: &serde_json::Value| match value {
+        serde_json::Value::Null => "NULL".to_string(),
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            let mut widths: Vec<usize> = result.columns.iter().map(String::len).collect();
+            for row in &result.rows {
+                for (width, value) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell(value).chars().count());
+                }
+            }
+
+            let line = |values: Vec<String>| {
+                values
+                    .iter()
+                    .zip(&widths)
+                    .map(|(value, width)| format!("{value:<width$}"))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            };
+            println!("{}", line(result.columns.clone()));
+            println!(
+                "{}",
+                line(widths.iter().map(|width| "-".repeat(*width)).collect())
+            );
+            for row in &result.rows {
+                println!("{}", line(row.iter().map(cell).collect()));
+            }
+            println!();
+            println!("({} rows)", result.rows.len());
+        }
+        SummaryFormat::Json => {
+            let rows: Vec<serde_json::Map<String, serde_json::Value>> = result
+                .rows
+                .iter()
+                .map(|row| {
+                    result
+                        .columns
+                        .iter()
+                        .cloned()
+                        .zip(row.iter().cloned())
+                        .collect()
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("| {} |", result.columns.join(" | "));
+            println!("|{}|", vec!["---"; result.columns.len()].join("|"));
+            for row in &result.rows {
+                let cells: Vec<String> = row.iter().map(cell).collect();
+                println!("| {} |", cells.join(" | "));
+            }
+        }
+        SummaryFormat::Compact => {
+            for row in &result.rows {
+                let cells: Vec<String> = row.iter().map(cell).collect();
+                println!("{}", cells.join("\t"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the org-report command - aggregates results across repositories.
+fn handle_org_report_command(
+    files: &[PathBuf],
+    format: OutputFormat,
+    top_rules: usize,
+) -> Result<()> {
+    use sniff::org_report::OrgReport;
+
+    let mut inputs = Vec::new();
+    for file in files {
+        let fallback_name = file.file_stem().map_or_else(
+            || file.display().to_string(),
+            |stem| stem.to_string_lossy().to_string(),
+        );
+        inputs.push((fallback_name, read_results_file(file)?));
+    }
+
+    let report = OrgReport::build(inputs, top_rules);
+    let trend = |trend: Option<f64>| trend.map_or_else(|| "-".to_string(), |t| format!("{t:+.1}"));
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Organization Report");
+            println!("═══════════════════════════════════════");
+            println!();
+            println!(">> Summary:");
+            println!("   Repositories: {}", report.total_repositories);
+            println!("   Average quality: {:.1}%", report.average_quality_score);
+            println!("   Critical issues: {}", report.critical_issues);
+            println!();
+
+            println!(">> Repositories (worst first):");
+            for repo in &report.repositories {
+                println!(
+                    "   {} - quality {:.1}% ({}), {} issues, {} critical, {} runs",
+                    repo.repository,
+                    repo.quality_score,
+                    trend(repo.quality_trend),
+                    repo.total_detections,
+                    repo.critical_issues,
+                    repo.runs
+                );
+            }
+            println!();
+
+            if !report.top_failing_rules.is_empty() {
+                println!(">> Top failing rules:");
+                for rule in &report.top_failing_rules {
+                    println!(
+                        "   {} ({}) - {} detections in {} repositories",
+                        rule.rule_name, rule.rule_id, rule.detections, rule.repositories
+                    );
+                }
+            }
+        }
+
+        SummaryFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+
+        SummaryFormat::Markdown => {
+            println!("# Organization Report");
+            println!();
+            println!("| Repository | Quality | Trend | Issues | Critical | Runs |");
+            println!("| ---------- | ------- | ----- | ------ | -------- | ---- |");
+            for repo in &report.repositories {
+                println!(
+                    "| {} | {:.1}% | {} | {} | {} | {} |",
+                    repo.repository,
+                    repo.quality_score,
+                    trend(repo.quality_trend),
+                    repo.total_detections,
+                    repo.critical_issues,
+                    repo.runs
+                );
+            }
+            println!();
+            println!("## Top Failing Rules");
+            println!();
+            println!("| Rule | Detections | Repositories |");
+            println!("| ---- | ---------- | ------------ |");
+            for rule in &report.top_failing_rules {
+                println!(
+                    "| {} (`{}`) | {} | {} |",
+                    rule.rule_name, rule.rule_id, rule.detections, rule.repositories
+                );
+            }
+        }
+
+        SummaryFormat::Compact => {
+            for repo in &report.repositories {
+                println!(
+                    "{}: {:.1}% quality ({}), {} critical",
+                    repo.repository,
+                    repo.quality_score,
+                    trend(repo.quality_trend),
+                    repo.critical_issues
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates an analyzer with only the rules shipped in this binary: the built-in
+/// rules and the bundled playbooks. Installed playbooks, learned patterns,
+/// project plugins and `.sniff.toml` overrides are ignored.
+fn create_bundled_misalignment_analyzer() -> Result<sniff::analysis::MisalignmentAnalyzer> {
+    let mut misalignment_analyzer = sniff::analysis::MisalignmentAnalyzer::new()?;
+    let bundled_dir =
+        tempfile::tempdir().map_err(|e| SniffError::file_system(std::env::temp_dir(), e))?;
+    install_default_playbooks(&bundled_dir.path().to_path_buf())?;
+    misalignment_analyzer.load_playbooks(bundled_dir.path())?;
+    if let Some((path, error)) = misalignment_analyzer.playbook_load_failures().first() {
+        return Err(SniffError::invalid_format(
+            "bundled playbook",
+            format!("{} failed to load: {error}", path.display()),
+        ));
+    }
+    Ok(misalignment_analyzer)
+}
+
+/// Creates a misalignment analyzer with the playbooks installed in ~/.sniff/patterns.
+///
+/// In read-only mode nothing is created or installed; playbooks are loaded only
+/// if they were installed previously.
+fn create_misalignment_analyzer(read_only: bool) -> Result<sniff::analysis::MisalignmentAnalyzer> {
+    let mut misalignment_analyzer = sniff::analysis::MisalignmentAnalyzer::new()?;
+
+    // Rule overrides from the project's .sniff.toml also apply to playbooks loaded below
+    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+    let project_config =
+        sniff::project_config::ConfigResolver::new().resolve_directory(&current_dir)?;
+    misalignment_analyzer.set_rule_overrides(
+        project_config
+            .rule_overrides
+            .iter()
+            .map(|(rule_id, rule_override)| (rule_id.clone(), *rule_override))
+            .collect(),
+    );
+
+    // Experimental WASM rule plugins of the project
+    let plugins_dir = current_dir.join(".sniff").join("plugins");
+    if let Err(e) = misalignment_analyzer.load_plugins(&plugins_dir) {
+        warn!(
+            "Failed to load plugins from {}: {}",
+            plugins_dir.display(),
+            e
+        );
+    }
+
+    // Install and load enhanced playbooks from .sniff/patterns/
+    let patterns_dir = if read_only {
+        let home_dir = dirs::home_dir()
+            .ok_or_else(|| SniffError::analysis_error("Cannot determine home directory"))?;
+        let patterns_dir = home_dir.join(".sniff").join("patterns");
+        if !patterns_dir.exists() {
+            report_read_only("playbook installation skipped; using built-in rules");
+            return Ok(misalignment_analyzer);
+        }
+        patterns_dir
+    } else {
+        let patterns_dir = ensure_sniff_directory()?.join("patterns");
+
+        // Install playbooks if they don't exist
+        if !patterns_dir.exists() {
+            install_default_playbooks(&patterns_dir)?;
+        }
+        patterns_dir
+    };
+
+    // Always load patterns from .sniff/patterns/
+    if let Err(e) = misalignment_analyzer.load_playbooks(&patterns_dir) {
+        warn!(
+            "Failed to load playbooks from {}: {}",
+            patterns_dir.display(),
+            e
+        );
+    } else {
+        info!("Loaded enhanced playbooks from {}", patterns_dir.display());
+    }
+
+    // Learned and imported patterns live in per-language folders below it
+    if let Some(home_dir) = dirs::home_dir() {
+        if let Err(e) = misalignment_analyzer.load_learned_patterns(&home_dir, read_only) {
+            warn!("Failed to load learned patterns: {}", e);
+        }
+    }
+
+    Ok(misalignment_analyzer)
+}
+
+/// Handles the perf command - reports performance hotspots for files.
+async fn handle_perf_command(
+    paths: Vec<PathBuf>,
+    format: OutputFormat,
+    include_hidden: bool,
+    extensions: Option<String>,
+    exclude: Option<String>,
+    include_tests: bool,
+    read_only: bool,
+) -> Result<()> {
+    use sniff::standalone::{AnalysisConfig, FileFilter, StandaloneAnalyzer};
+
+    info!(">> Starting performance analysis");
+
+    let filter = FileFilter {
+        include_hidden,
+        allowed_extensions: extensions.map(|ext| {
+            ext.split(',')
+                .map(|e| e.trim().to_string())
+                .collect::<Vec<_>>()
+        }),
+        exclude_pattern: exclude,
+        include_test_files: include_tests,
+        ..FileFilter::default()
+    };
+
+    let config = AnalysisConfig {
+        filter,
+        ..AnalysisConfig::default()
+    };
+
+    let misalignment_analyzer = create_misalignment_analyzer(read_only)?;
+    let mut analyzer = StandaloneAnalyzer::new(misalignment_analyzer, config);
+    let reports = analyzer.analyze_performance(&paths).await?;
+
+    display_performance_reports(&reports, format)
+}
+
+/// Displays performance hotspot reports.
+fn display_performance_reports(
+    reports: &[sniff::PerformanceReport],
+    format: OutputFormat,
+) -> Result<()> {
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Performance Analysis Results");
+            println!("═══════════════════════════════════════");
+            println!();
+
+            let total_hotspots: usize = reports.iter().map(|r| r.hotspots.len()).sum();
+            println!(">> Summary:");
+            println!("   Files analyzed: {}", reports.len());
+            println!("   Total hotspots: {}", total_hotspots);
+            println!();
+
+            for report in reports {
+                println!(
+                    "   {} (score: {}/100, avg complexity: {:.1})",
+                    report.file_path.display(),
+                    report.performance_score,
+                    report.average_complexity
+                );
+
+                for hotspot in &report.hotspots {
+                    println!(
+                        "      [{}] {} -> {} (~{:.0}% gain)",
+                        hotspot.severity,
+                        hotspot.title,
+                        hotspot.optimization,
+                        hotspot.expected_gain
+                    );
+                }
+                for nested_loop in &report.nested_loops {
+                    println!(
+                        "      Nested loop (depth {}) at line {}",
+                        nested_loop.depth, nested_loop.line
+                    );
+                }
+                if report.allocation_hotspots > 0 {
+                    println!(
+                        "      {} memory allocation hotspots",
+                        report.allocation_hotspots
+                    );
+                }
+                for opportunity in &report.parallelization_opportunities {
+                    println!(
+                        "      Parallelization: {} (~{:.1}x speedup)",
+                        opportunity.approach, opportunity.expected_speedup
+                    );
+                }
+                println!();
+            }
+
+            if total_hotspots == 0 {
+                println!(">> No performance hotspots detected.");
+            }
+        }
+
+        SummaryFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(reports)?);
+        }
+
+        SummaryFormat::Markdown => {
+            println!("# Performance Analysis Results");
+            println!();
+            println!("| File | Score | Avg Complexity | Hotspots | Nested Loops |");
+            println!("| ---- | ----- | -------------- | -------- | ------------ |");
+            for report in reports {
+                println!(
+                    "| `{}` | {} | {:.1} | {} | {} |",
+                    report.file_path.display(),
+                    report.performance_score,
+                    report.average_complexity,
+                    report.hotspots.len(),
+                    report.nested_loops.len()
+                );
+            }
+        }
+
+        SummaryFormat::Compact => {
+            for report in reports {
+                println!(
+                    "{}: score {}, {} hotspots, {} nested loops",
+                    report.file_path.display(),
+                    report.performance_score,
+                    report.hotspots.len(),
+                    report.nested_loops.len()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the semantics command - prints a structural map of files.
+fn handle_semantics_command(files: Vec<PathBuf>, format: OutputFormat) -> Result<()> {
+    let mut analyzer = sniff::analysis::MisalignmentAnalyzer::new()?;
+
+    let mut results = Vec::new();
+    for file in &files {
+        match analyzer.analyze_semantic_context(file) {
+            Ok(result) => results.push(result),
+            Err(e) => warn!("Failed to analyze {}: {}", file.display(), e),
+        }
+    }
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Semantic Context");
+            println!("═══════════════════════════════════════");
+            println!();
+
+            for result in &results {
+                println!(
+                    ">> {} ({})",
+                    result.file_path.display(),
+                    result.language.name()
+                );
+                println!("   Symbols: {}", result.symbol_count);
+                print_semantic_section("Functions", &result.function_definitions);
+                print_semantic_section("Variables", &result.variable_definitions);
+                print_semantic_section("Taint flows", &result.data_flow_warnings);
+                print_semantic_section("Validation points", &result.security_warnings);
+                println!();
+            }
+        }
+
+        SummaryFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&results)?);
+        }
+
+        SummaryFormat::Markdown => {
+            println!("# Semantic Context");
+            println!();
+            for result in &results {
+                println!("## `{}`", result.file_path.display());
+                println!();
+                println!("- **Language**: {}", result.language.name());
+                println!("- **Symbols**: {}", result.symbol_count);
+                println!(
+                    "- **Functions**: {}",
+                    result.function_definitions.join(", ")
+                );
+                println!(
+                    "- **Variables**: {}",
+                    result.variable_definitions.join(", ")
+                );
+                println!("- **Taint flows**: {}", result.data_flow_warnings.len());
+                println!(
+                    "- **Validation points**: {}",
+                    result.security_warnings.len()
+                );
+                println!();
+            }
+        }
+
+        SummaryFormat::Compact => {
+            for result in &results {
+                println!(
+                    "{}: {} symbols, {} functions, {} taint flows, {} validation points",
+                    result.file_path.display(),
+                    result.symbol_count,
+                    result.function_definitions.len(),
+                    result.data_flow_warnings.len(),
+                    result.security_warnings.len()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints a titled list of semantic entries, or nothing if empty.
+fn print_semantic_section(title: &str, entries: &[String]) {
+    if entries.is_empty() {
+        return;
+    }
+    println!("   {} ({}):", title, entries.len());
+    for entry in entries {
+        println!("      {}", entry);
+    }
+}
+
+// Additional modern command handlers would go here...
+// These need to be copied from the original main.rs file
+
+/// Displays standalone analysis results.
+fn display_standalone_results(
+    results: &sniff::standalone::AnalysisResults,
+    format: OutputFormat,
+    detailed: bool,
+    comparison: Option<&sniff::standalone::FileComparison>,
+) -> Result<()> {
+    match format {
+        OutputFormat::Table => {
+            println!(":: Standalone File Analysis Results");
+            println!("═══════════════════════════════════════");
+            println!();
+
+            if let Some(comp) = comparison {
+                println!(">> Change Summary:");
+                println!("   New files: {}", comp.new_files.len());
+                println!("   Modified files: {}", comp.changed_files.len());
+                println!("   Deleted files: {}", comp.deleted_files.len());
+                println!();
+            }
+
+            println!(">> Analysis Summary:");
+            println!("   Files analyzed: {}", results.total_files);
+            if let Some(coverage) = &results.coverage {
+                let skipped: Vec<String> = coverage
+                    .skipped
+                    .iter()
+                    .map(|(reason, count)| format!("{} {}", count, reason.name()))
+                    .collect();
+                println!(
+                    "   Files discovered: {} (rules ran on {}, skipped {}{})",
+                    coverage.discovered,
+                    coverage.analyzed,
+                    coverage.total_skipped(),
+                    if skipped.is_empty() {
+                        String::new()
+                    } else {
+                        format!(": {}", skipped.join(", "))
+                    }
+                );
+            }
+            println!("   Total patterns: {}", results.total_detections);
+            println!("   Critical issues: {}", results.critical_issues);
+            println!("   Average quality: {:.1}%", results.average_quality_score);
+            println!();
+
+            if !results.warnings.is_empty() {
+                let mut counts: std::collections::BTreeMap<&str, usize> =
+                    std::collections::BTreeMap::new();
+                for warning in &results.warnings {
+                    *counts.entry(warning.kind.name()).or_default() += 1;
+                }
+                println!(
+                    "!! {} warnings, results may be incomplete:",
+                    results.warnings.len()
+                );
+                for (kind, count) in counts {
+                    println!("   {}: {}", kind, count);
+                }
+                if detailed {
+                    for warning in &results.warnings {
+                        println!("      {}", warning.message);
+                    }
+                }
+                println!();
+            }
+
+            if !results.file_results.is_empty() {
+                println!(">> File Analysis:");
+                for file_result in &results.file_results {
+                    if !file_result.detections.is_empty() {
+                        println!(
+                            "   {} ({})",
+                            file_result.file_path.display(),
+                            file_result.language.map(|l| l.name()).unwrap_or("unknown")
+                        );
+                        println!(
+                            "      Issues: {} | Quality: {:.1}%",
+                            file_result.detections.len(),
+                            file_result.quality_score
+                        );
+
+                        if let Some(enhanced) = &file_result.enhanced {
+                            let qa = &enhanced.quality_assessment;
+                            println!(
+                                "      Performance: {}/100 | Maintainability: {:.1} | Reliability: {:.1} | Security: {:.1} | Completeness: {:.1}",
+                                enhanced.performance_score,
+                                qa.maintainability_score,
+                                qa.reliability_score,
+                                qa.security_score,
+                                qa.completeness_score
+                            );
+                        }
+
+                        if detailed {
+                            for detection in &file_result.detections {
+                                println!(
+                                    "         {} {} ({}:{}) [{}]: {}",
+                                    detection.severity.emoji(),
+                                    detection.rule_name,
+                                    detection.file_path,
+                                    detection.line_number,
+                                    detection.fingerprint(),
+                                    detection.code_snippet.trim()
+                                );
+                                if let Some(impact) = &detection.performance_impact {
+                                    println!(
+                                        "            Performance impact: {} - {}",
+                                        impact.severity, impact.description
+                                    );
+                                }
+                                if let Some(remediation) = &detection.remediation {
+                                    println!("            Fix: {}", remediation.explanation);
+                                    if let Some(example) = &remediation.example {
+                                        for line in example.lines() {
+                                            println!("               {}", line);
+                                        }
+                                    }
+                                }
+                            }
+                            if let Some(enhanced) = &file_result.enhanced {
+                                for recommendation in &enhanced.performance_recommendations {
+                                    println!("      -> {}", recommendation);
+                                }
+                            }
+                            if file_result.heuristic_insights.is_some() {
+                                println!("      [HEURISTIC] Insights available in JSON output");
+                            }
+                        }
+                        println!();
+                    }
+                }
+            }
+
+            if !results.worst_functions.is_empty() {
+                println!(">> Worst Functions:");
+                for entry in &results.worst_functions {
+                    println!(
+                        "   {}:{} {} - {:.1} detections per 100 lines, complexity {}, quality {:.1}%",
+                        entry.file_path.display(),
+                        entry.function.start_line,
+                        entry.function.name,
+                        entry.function.detection_density(),
+                        entry.function.complexity,
+                        entry.function.quality_score
+                    );
+                }
+                println!();
+            }
+
+            if !results.changed_functions.is_empty() {
+                println!(">> Changed Functions:");
+                for changed in &results.changed_functions {
+                    let delta = changed
+                        .quality_delta
+                        .map(|delta| format!(" ({:+.1})", delta))
+                        .unwrap_or_default();
+                    println!(
+                        "   {}:{} {} - quality {:.1}%{}, {} changed lines, {} detections",
+                        changed.file_path.display(),
+                        changed.function.start_line,
+                        changed.function.name,
+                        changed.function.quality_score,
+                        delta,
+                        changed.changed_lines,
+                        changed.function.detections
+                    );
+                }
+                println!();
+            }
+
+            let mut likely_generated: Vec<_> = results
+                .file_results
+                .iter()
+                .filter_map(|file_result| {
+                    file_result
+                        .ai_likelihood
+                        .as_ref()
+                        .filter(|likelihood| {
+                            likelihood.level != sniff::ai_likelihood::LikelihoodLevel::Low
+                        })
+                        .map(|likelihood| (file_result, likelihood))
+                })
+                .collect();
+            if !likely_generated.is_empty() {
+                likely_generated.sort_by(|a, b| b.1.score.total_cmp(&a.1.score));
+                println!(">> Likely AI-generated (review first, not gated):");
+                for (file_result, likelihood) in likely_generated {
+                    println!(
+                        "   {} {:.2} ({})",
+                        file_result.file_path.display(),
+                        likelihood.score,
+                        likelihood.level.name()
+                    );
+                    for signal in &likelihood.signals {
+                        println!("      - {}", signal.evidence);
+                    }
+                    if detailed {
+                        for hunk in &likelihood.hunks {
+                            println!(
+                                "      lines {}-{} written in session {}",
+                                hunk.start_line, hunk.end_line, hunk.session_id
+                            );
+                        }
+                    }
+                }
+                println!();
+            }
+
+            let inline = sniff::suppression::SuppressionSummary::of(
+                results
+                    .file_results
+                    .iter()
+                    .flat_map(|file_result| &file_result.inline_suppressions),
+            );
+            if inline.comments > 0 {
+                println!(
+                    ">> Inline suppressions: {} comments hid {} detections ({} without a reason, {} unused)",
+                    inline.comments, inline.suppressed, inline.without_reason, inline.unused
+                );
+                if detailed {
+                    for file_result in &results.file_results {
+                        for suppression in &file_result.inline_suppressions {
+                            println!(
+                                "      {}:{} [{}] hid {}: {}",
+                                file_result.file_path.display(),
+                                suppression.line_number,
+                                if suppression.rule_ids.is_empty() {
+                                    "all rules".to_string()
+                                } else {
+                                    suppression.rule_ids.join(", ")
+                                },
+                                suppression.suppressed,
+                                suppression.reason.as_deref().unwrap_or("no reason given")
+                            );
+                        }
+                    }
+                }
+                println!();
+            }
+
+            let suppressed: Vec<_> = results
+                .file_results
+                .iter()
+                .flat_map(|file_result| &file_result.suppressed_detections)
+                .collect();
+            if !suppressed.is_empty() {
+                println!(">> Suppressed detections ({}):", suppressed.len());
+                for item in suppressed {
+                    println!(
+                        "   {} ({}:{}): {}",
+                        item.detection.rule_name,
+                        item.detection.file_path,
+                        item.detection.line_number,
+                        item.suppression_reason
+                    );
+                }
+                println!();
+            }
+
+            if results.critical_issues > 0 {
+                println!(
+                    "!! {} critical issues detected that require immediate attention",
+                    results.critical_issues
+                );
+            } else if results.total_detections == 0 {
+                println!(">> No issues detected! Code quality looks excellent.");
+            }
+        }
+
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(results)?);
+        }
+
+        OutputFormat::Markdown => {
+            println!("# Standalone File Analysis Results");
+            println!();
+            println!("## Summary");
+            println!();
+            println!("| Metric | Value |");
+            println!("| ------ | ----- |");
+            println!("| Files analyzed | {} |", results.total_files);
+            if let Some(coverage) = &results.coverage {
+                println!("| Files discovered | {} |", coverage.discovered);
+                println!("| Files skipped | {} |", coverage.total_skipped());
+            }
+            println!("| Total patterns | {} |", results.total_detections);
+            println!("| Critical issues | {} |", results.critical_issues);
+            println!(
+                "| Average quality | {:.1}% |",
+                results.average_quality_score
+            );
+            let inline = sniff::suppression::SuppressionSummary::of(
+                results
+                    .file_results
+                    .iter()
+                    .flat_map(|file_result| &file_result.inline_suppressions),
+            );
+            if inline.comments > 0 {
+                println!(
+                    "| Inline suppressions | {} (hid {}, {} without a reason) |",
+                    inline.comments, inline.suppressed, inline.without_reason
+                );
+            }
+            println!();
+
+            if !results.warnings.is_empty() {
+                println!("## Warnings");
+                println!();
+                for warning in &results.warnings {
+                    println!("- `{}`: {}", warning.kind.name(), warning.message);
+                }
+                println!();
+            }
+
+            if !results.compliance.is_empty() {
+                println!("## Compliance Summary");
+                println!();
+                println!("| CWE | OWASP | Detections | Files | Highest severity | Rules |");
+                println!("| --- | ----- | ---------- | ----- | ---------------- | ----- |");
+                for group in &results.compliance {
+                    println!(
+                        "| {} | {} | {} | {} | {} | {} |",
+                        group.cwe,
+                        if group.owasp.is_empty() {
+                            "-".to_string()
+                        } else {
+                            group.owasp.join(", ")
+                        },
+                        group.detections,
+                        group.files,
+                        group.highest_severity.name(),
+                        group
+                            .rules
+                            .iter()
+                            .map(|rule| format!("`{rule}`"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+                println!();
+            }
+
+            if !results.file_results.is_empty() {
+                println!("## File Analysis");
+                println!();
+                for file_result in &results.file_results {
+                    if !file_result.detections.is_empty() {
+                        println!("### `{}`", file_result.file_path.display());
+                        println!();
+                        println!(
+                            "- **Language**: {}",
+                            file_result.language.map(|l| l.name()).unwrap_or("unknown")
+                        );
+                        println!("- **Issues**: {}", file_result.detections.len());
+                        println!("- **Quality**: {:.1}%", file_result.quality_score);
+                        if let Some(enhanced) = &file_result.enhanced {
+                            let qa = &enhanced.quality_assessment;
+                            println!("- **Performance**: {}/100", enhanced.performance_score);
+                            println!("- **Maintainability**: {:.1}", qa.maintainability_score);
+                            println!("- **Reliability**: {:.1}", qa.reliability_score);
+                            println!("- **Security**: {:.1}", qa.security_score);
+                            println!("- **Completeness**: {:.1}", qa.completeness_score);
+                        }
+                        println!();
+
+                        if detailed {
+                            println!("#### Issues");
+                            println!();
+                            for detection in &file_result.detections {
+                                println!(
+                                    "- {} **{}** (line {}, `{}`): `{}`",
+                                    detection.severity.emoji(),
+                                    detection.rule_name,
+                                    detection.line_number,
+                                    detection.fingerprint(),
+                                    detection.code_snippet.trim()
+                                );
+                                if let Some(impact) = &detection.performance_impact {
+                                    println!(
+                                        "  - Performance impact: {} - {}",
+                                        impact.severity, impact.description
+                                    );
+                                }
+                                if let Some(remediation) = &detection.remediation {
+                                    println!("  - Fix: {}", remediation.explanation);
+                                    if let Some(example) = &remediation.example {
+                                        println!();
+                                        println!("    ```");
+                                        for line in example.lines() {
+                                            println!("    {}", line);
+                                        }
+                                        println!("    ```");
+                                        println!();
+                                    }
+                                }
+                                if let Some(taxonomy) = &detection.taxonomy {
+                                    let ids: Vec<&str> = taxonomy
+                                        .cwe
+                                        .iter()
+                                        .chain(&taxonomy.owasp)
+                                        .map(String::as_str)
+                                        .collect();
+                                    if !ids.is_empty() {
+                                        println!("  - Taxonomy: {}", ids.join(", "));
+                                    }
+                                    for reference in &taxonomy.references {
+                                        println!("  - See: <{}>", reference);
+                                    }
+                                }
+                            }
+                            println!();
+                        }
+                    }
+                }
+            }
+        }
+
+        OutputFormat::Compact => {
+            for file_result in &results.file_results {
+                if !file_result.detections.is_empty() {
+                    if let Some(enhanced) = &file_result.enhanced {
+                        println!(
+                            "{}: {} issues, {:.1}% quality, {}/100 performance",
+                            file_result.file_path.display(),
+                            file_result.detections.len(),
+                            file_result.quality_score,
+                            enhanced.performance_score
+                        );
+                    } else {
+                        println!(
+                            "{}: {} issues, {:.1}% quality",
+                            file_result.file_path.display(),
+                            file_result.detections.len(),
+                            file_result.quality_score
+                        );
+                    }
+                }
+            }
+        }
+        OutputFormat::Arrow => {
+            let stdout = std::io::stdout();
+            sniff::columnar::write_arrow_ipc(results, std::io::BufWriter::new(stdout.lock()))?;
+        }
+        OutputFormat::Sarif => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&sniff::report::sarif(results))?
+            );
+        }
+        OutputFormat::Junit => {
+            print!("{}", sniff::report::junit(results));
+        }
+        OutputFormat::Codeclimate => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&sniff::report::codeclimate(results))?
+            );
+        }
+        OutputFormat::Rdjson => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&sniff::report::rdjson(results))?
+            );
+        }
+        OutputFormat::Rdjsonl => {
+            print!("{}", sniff::report::rdjsonl(results));
+        }
+    }
+
+    Ok(())
+}
+
+// Modern command handlers (copied from legacy main.rs)
+
+/// Opens the checkpoint store: `checkpoint_dir` if given, else `.sniff/checkpoints`
+/// in the root of the project containing `path` (or the current directory).
+fn open_checkpoint_manager(
+    checkpoint_dir: Option<&Path>,
+    path: Option<&PathBuf>,
+) -> Result<sniff::standalone::CheckpointManager> {
     use sniff::standalone::CheckpointManager;
 
     let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
-    let checkpoint_manager = CheckpointManager::new(&current_dir)?;
+    let project_dir = sniff::project_config::ConfigResolver::new()
+        .project_root(path.unwrap_or(&current_dir))?
+        .unwrap_or(current_dir);
+    match checkpoint_dir {
+        Some(checkpoint_dir) => Ok(CheckpointManager::with_checkpoint_dir(
+            &project_dir,
+            checkpoint_dir,
+        )),
+        None => CheckpointManager::new(&project_dir),
+    }
+}
+
+/// Handles checkpoint management commands.
+async fn handle_checkpoint_command(
+    command: CheckpointCommands,
+    checkpoint_dir: Option<&Path>,
+    read_only: bool,
+) -> Result<()> {
+    use sniff::standalone::{CheckpointScope, ComparisonMode};
+
+    if read_only
+        && matches!(
+            command,
+            CheckpointCommands::Create { .. } | CheckpointCommands::Delete { .. }
+        )
+    {
+        return Err(SniffError::read_only("Creating or deleting checkpoints"));
+    }
+
+    // Keep checkpoints with the project of the checkpointed paths
+    let anchor = match &command {
+        CheckpointCommands::Create { paths, .. } => paths.first(),
+        CheckpointCommands::Diff { paths, .. } => paths.as_ref().and_then(|paths| paths.first()),
+        _ => None,
+    };
+    let checkpoint_manager = open_checkpoint_manager(checkpoint_dir, anchor)?;
+
+    match command {
+        CheckpointCommands::Create {
+            name,
+            paths,
+            description,
+            include,
+            exclude,
+        } => {
+            info!(">> Creating checkpoint: {}", name);
+            let scope = CheckpointScope {
+                paths,
+                include,
+                exclude,
+            };
+            checkpoint_manager
+                .create_checkpoint(&name, &scope, description)
+                .await?;
+            let checkpoint = checkpoint_manager.get_checkpoint(&name).await?;
+            println!(
+                ">> Checkpoint '{}' created with {} files",
+                name,
+                checkpoint.map_or(0, |checkpoint| checkpoint.file_count)
+            );
+        }
+
+        CheckpointCommands::List { format } => {
+            let checkpoints = checkpoint_manager.list_checkpoints().await?;
+
+            if checkpoints.is_empty() {
+                println!("[INFO] No checkpoints found");
+                return Ok(());
+            }
+
+            match summary_format(format)? {
+                SummaryFormat::Table => {
+                    println!(":: Available Checkpoints");
+                    println!("════════════════════════");
+                    println!();
+
+                    for checkpoint in checkpoints {
+                        println!("   {}", checkpoint.name);
+                        println!(
+                            "   Created: {}",
+                            checkpoint.timestamp.format("%Y-%m-%d %H:%M:%S")
+                        );
+                        println!("   Files: {}", checkpoint.file_count);
+                        if let Some(desc) = checkpoint.description {
+                            println!("   Description: {}", desc);
+                        }
+                        println!();
+                    }
+                }
+                SummaryFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&checkpoints)?);
+                }
+                _ => {
+                    for checkpoint in checkpoints {
+                        println!(
+                            "{}: {} files ({})",
+                            checkpoint.name,
+                            checkpoint.file_count,
+                            checkpoint.timestamp.format("%Y-%m-%d %H:%M")
+                        );
+                    }
+                }
+            }
+        }
+
+        CheckpointCommands::Show { name, format: _ } => {
+            if let Some(checkpoint) = checkpoint_manager.get_checkpoint(&name).await? {
+                println!(":: Checkpoint: {}", checkpoint.name);
+                println!(
+                    "Created: {}",
+                    checkpoint.timestamp.format("%Y-%m-%d %H:%M:%S")
+                );
+                println!("Files: {}", checkpoint.file_count);
+                if let Some(desc) = checkpoint.description {
+                    println!("Description: {}", desc);
+                }
+                if let Some(scope) = &checkpoint.scope {
+                    let paths: Vec<_> = scope
+                        .paths
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect();
+                    println!("Paths: {}", paths.join(", "));
+                    if !scope.include.is_empty() {
+                        println!("Include: {}", scope.include.join(", "));
+                    }
+                    if !scope.exclude.is_empty() {
+                        println!("Exclude: {}", scope.exclude.join(", "));
+                    }
+                }
+                // Show file list
+                let details = checkpoint_manager.get_checkpoint_files(&name).await?;
+                println!("\nFiles in checkpoint:");
+                for file_info in details {
+                    println!("  {} ({})", file_info.path.display(), file_info.file_size);
+                }
+            } else {
+                println!("❌ Checkpoint '{}' not found", name);
+            }
+        }
+
+        CheckpointCommands::Diff {
+            checkpoint,
+            paths,
+            format,
+            ignore_whitespace,
+            ignore_format,
+        } => {
+            let mode = if ignore_format {
+                ComparisonMode::IgnoreFormat
+            } else if ignore_whitespace {
+                ComparisonMode::IgnoreWhitespace
+            } else {
+                ComparisonMode::Exact
+            };
+
+            let comparison = checkpoint_manager
+                .compare_files(&checkpoint, paths.as_deref(), mode)
+                .await?;
+
+            match summary_format(format)? {
+                SummaryFormat::Table => {
+                    println!("[DIFF] Changes since checkpoint '{}'", checkpoint);
+                    println!("═══════════════════════════════════");
+                    println!();
+
+                    if !comparison.new_files.is_empty() {
+                        println!("[NEW] New files ({}): ", comparison.new_files.len());
+                        for file in &comparison.new_files {
+                            println!("  + {}", file.display());
+                        }
+                        println!();
+                    }
+
+                    if !comparison.changed_files.is_empty() {
+                        println!(
+                            "[MOD] Modified files ({}): ",
+                            comparison.changed_files.len()
+                        );
+                        for file in &comparison.changed_files {
+                            println!("  ~ {}", file.display());
+                        }
+                        println!();
+                    }
+
+                    if !comparison.deleted_files.is_empty() {
+                        println!("[DEL] Deleted files ({}): ", comparison.deleted_files.len());
+                        for file in &comparison.deleted_files {
+                            println!("  - {}", file.display());
+                        }
+                        println!();
+                    }
+
+                    if !comparison.formatting_only_files.is_empty() {
+                        println!(
+                            ">> {} files changed only in formatting (not counted)",
+                            comparison.formatting_only_files.len()
+                        );
+                        println!();
+                    }
+
+                    if comparison.new_files.is_empty()
+                        && comparison.changed_files.is_empty()
+                        && comparison.deleted_files.is_empty()
+                    {
+                        println!(">> No changes detected since checkpoint");
+                    }
+                }
+                SummaryFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&comparison)?);
+                }
+                _ => {
+                    println!(
+                        "Changes: +{} ~{} -{}",
+                        comparison.new_files.len(),
+                        comparison.changed_files.len(),
+                        comparison.deleted_files.len()
+                    );
+                }
+            }
+        }
+
+        CheckpointCommands::Delete { name, confirm } => {
+            if !confirm {
+                println!("❌ Checkpoint deletion requires --confirm flag for safety");
+                return Ok(());
+            }
+
+            checkpoint_manager.delete_checkpoint(&name).await?;
+            println!(">> Checkpoint '{}' deleted", name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles pattern management commands.
+async fn handle_patterns_command(command: PatternCommands, read_only: bool) -> Result<()> {
+    // Simplified implementation - pattern management functionality is available
+    // but the full implementation needs API updates
+
+    match command {
+        PatternCommands::Replay {
+            session,
+            projects_dir,
+            format,
+        } => return handle_patterns_replay_command(&session, projects_dir, format, read_only),
+        PatternCommands::Import { file } => {
+            return handle_patterns_import_command(&file, read_only)
+        }
+        PatternCommands::Install {
+            source,
+            sha256,
+            registry,
+            locked,
+            trusted_keys,
+            allow_unsigned,
+        } => {
+            return handle_patterns_install_command(
+                source.as_deref(),
+                sha256.as_deref(),
+                registry,
+                locked,
+                &trusted_keys,
+                allow_unsigned,
+                read_only,
+            )
+        }
+        PatternCommands::List {
+            language,
+            format,
+            active_only,
+            conflicts,
+        } => {
+            return handle_patterns_list_command(
+                language.as_deref(),
+                format,
+                active_only,
+                conflicts,
+                read_only,
+            )
+        }
+        PatternCommands::Test {
+            language,
+            format,
+            golden: Some(dir),
+            update,
+            bundled,
+        } => {
+            return handle_patterns_golden_command(
+                &dir,
+                language.as_deref(),
+                format,
+                update,
+                bundled,
+                read_only,
+            )
+        }
+        PatternCommands::Test {
+            language, format, ..
+        } => return handle_patterns_test_command(language.as_deref(), format, false, read_only),
+        PatternCommands::Validate { fix } => {
+            if fix {
+                println!("[INFO] Playbooks cannot be fixed automatically; see the failures below");
+            }
+            return handle_patterns_test_command(None, OutputFormat::Table, true, read_only);
+        }
+        PatternCommands::Init { force: _ } => {
+            println!(">> Enhanced patterns are installed in ~/.sniff/patterns/");
+            println!(">> Add custom patterns by placing YAML files in that directory");
+            println!(">> Available patterns are loaded automatically during analysis");
+        }
+        _ => {
+            println!("[INFO] Pattern management commands simplified in streamlined version");
+            println!("[TIP] Enhanced patterns are installed in ~/.sniff/patterns/");
+            println!("[TIP] Add custom patterns by placing YAML files in that directory");
+            println!("[TIP] Available patterns are loaded automatically during analysis");
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the named language, or every supported language if none is given.
+fn language_filter(language: Option<&str>) -> Result<Vec<sniff::analysis::SupportedLanguage>> {
+    use sniff::analysis::SupportedLanguage;
+
+    match language {
+        Some(name) => Ok(vec![SupportedLanguage::from_name(name).ok_or_else(
+            || {
+                SniffError::invalid_format(
+                    "language".to_string(),
+                    format!("Unsupported language: {name}"),
+                )
+            },
+        )?]),
+        None => Ok(SupportedLanguage::ALL.to_vec()),
+    }
+}
+
+/// Handles `patterns test` - runs rules against their examples and false
+/// positives. `patterns validate` also reports playbooks and plugins that failed to load.
+fn handle_patterns_test_command(
+    language: Option<&str>,
+    format: OutputFormat,
+    validate: bool,
+    read_only: bool,
+) -> Result<()> {
+    let json = is_json_format(format)?;
+    let languages = language_filter(language)?;
+    let mut analyzer = create_misalignment_analyzer(read_only)?;
+    let load_failures: Vec<(PathBuf, String)> = if validate {
+        let plugin_failures = analyzer.plugin_load_failures();
+        analyzer
+            .playbook_load_failures()
+            .iter()
+            .chain(plugin_failures)
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let results = sniff::pattern_testing::test_rules(&mut analyzer, &languages)?;
+    let failed: Vec<_> = results.iter().filter(|result| !result.passed()).collect();
+
+    if json {
+        let load_failures: Vec<_> = load_failures
+            .iter()
+            .map(|(path, error)| serde_json::json!({ "path": path, "error": error }))
+            .collect();
+        let report = serde_json::json!({
+            "rules_tested": results.len(),
+            "rules_failed": failed.len(),
+            "load_failures": load_failures,
+            "results": results,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if format == OutputFormat::Markdown {
+        println!("# Pattern Tests");
+        println!();
+        println!(
+            "{} of {} rules failed their test cases.",
+            failed.len(),
+            results.len()
+        );
+        println!();
+        println!("| Rule | Language | Case | Reason | Code |");
+        println!("|------|----------|------|--------|------|");
+        for result in &failed {
+            for failure in &result.failures {
+                println!(
+                    "| `{}` | {} | {:?} | {} | `{}` |",
+                    result.qualified_id,
+                    result.language,
+                    failure.kind,
+                    failure.reason,
+                    failure.code.replace('|', "\\|")
+                );
+            }
+        }
+    } else if format == OutputFormat::Compact {
+        for result in &failed {
+            for failure in &result.failures {
+                println!(
+                    "{}:{}:{:?}: {}",
+                    result.language, result.qualified_id, failure.kind, failure.reason
+                );
+            }
+        }
+    } else {
+        println!(":: Pattern Tests");
+        println!("═══════════════════════════════════════");
+        for (path, error) in &load_failures {
+            println!("[ERROR] {} failed to load: {}", path.display(), error);
+        }
+        println!(
+            ">> {} rules tested, {} passed, {} failed",
+            results.len(),
+            results.len() - failed.len(),
+            failed.len()
+        );
+        for result in &failed {
+            println!();
+            println!("[FAIL] {} ({})", result.qualified_id, result.language);
+            for failure in &result.failures {
+                let kind = match failure.kind {
+                    sniff::pattern_testing::CaseKind::Example => "example",
+                    sniff::pattern_testing::CaseKind::FalsePositive => "false positive",
+                };
+                println!("   {} {}: {}", kind, failure.reason, failure.code.trim());
+            }
+        }
+    }
+
+    if !load_failures.is_empty() {
+        return Err(SniffError::analysis_error(format!(
+            "{} playbooks or plugins failed to load, {} rules failed their test cases",
+            load_failures.len(),
+            failed.len()
+        )));
+    }
+    if !failed.is_empty() {
+        return Err(SniffError::analysis_error(format!(
+            "{} rules failed their test cases",
+            failed.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Handles `patterns test --golden` - compares the detections of every source
+/// file in a directory with its golden file.
+fn handle_patterns_golden_command(
+    dir: &Path,
+    language: Option<&str>,
+    format: OutputFormat,
+    update: bool,
+    bundled: bool,
+    read_only: bool,
+) -> Result<()> {
+    let json = is_json_format(format)?;
+    if update && read_only {
+        return Err(SniffError::read_only("Updating golden files"));
+    }
+
+    let languages = language_filter(language)?;
+    let mut analyzer = if bundled {
+        create_bundled_misalignment_analyzer()?
+    } else {
+        create_misalignment_analyzer(read_only)?
+    };
+    // Config and Markdown inputs are golden-tested only when their rules are asked for
+    analyzer.set_config_files(languages == [sniff::analysis::SupportedLanguage::Config]);
+    analyzer.set_markdown(languages == [sniff::analysis::SupportedLanguage::Markdown]);
+    let results = sniff::pattern_testing::run_golden(&mut analyzer, dir, &languages, update)?;
+    let failed: Vec<_> = results.iter().filter(|result| !result.passed()).collect();
+    let updated = results.iter().filter(|result| result.updated).count();
+
+    if json {
+        let report = serde_json::json!({
+            "files_tested": results.len(),
+            "files_failed": failed.len(),
+            "files_updated": updated,
+            "results": results,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if format == OutputFormat::Compact {
+        for result in &failed {
+            let input = result.input.display();
+            if let Some(error) = &result.error {
+                println!("{}: {}", input, error);
+            }
+            for detection in &result.missing {
+                println!("{}:{}: missing {}", input, detection.line, detection.rule);
+            }
+            for detection in &result.unexpected {
+                println!(
+                    "{}:{}: unexpected {}",
+                    input, detection.line, detection.rule
+                );
+            }
+        }
+    } else {
+        println!(":: Golden Pattern Tests");
+        println!("═══════════════════════════════════════");
+        println!(
+            ">> {} files tested, {} passed, {} failed, {} golden files updated",
+            results.len(),
+            results.len() - failed.len(),
+            failed.len(),
+            updated
+        );
+        for result in &failed {
+            println!();
+            println!("[FAIL] {}", result.input.display());
+            if let Some(error) = &result.error {
+                println!("   {}", error);
+            }
+            for detection in &result.missing {
+                println!(
+                    "   - line {} {} ({})",
+                    detection.line,
+                    detection.rule,
+                    detection.severity.name()
+                );
+            }
+            for detection in &result.unexpected {
+                println!(
+                    "   + line {} {} ({})",
+                    detection.line,
+                    detection.rule,
+                    detection.severity.name()
+                );
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(SniffError::analysis_error(format!(
+            "{} files differ from their golden files",
+            failed.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Handles `patterns list` - shows the loaded rules by namespaced id, or the
+/// rule ids that several playbooks define.
+fn handle_patterns_list_command(
+    language: Option<&str>,
+    format: OutputFormat,
+    active_only: bool,
+    conflicts: bool,
+    read_only: bool,
+) -> Result<()> {
+    let analyzer = create_misalignment_analyzer(read_only)?;
+    let manager = analyzer.playbook_manager();
+    let languages = language_filter(language)?;
+    let language_names: Vec<&str> = languages.iter().map(|language| language.name()).collect();
+
+    if conflicts {
+        let conflicts: Vec<_> = manager
+            .conflicts()
+            .into_iter()
+            .filter(|conflict| {
+                language.is_none() || language_names.contains(&conflict.language.as_str())
+            })
+            .collect();
+        return display_rule_conflicts(&conflicts, format);
+    }
+
+    let mut rules: Vec<_> = languages
+        .iter()
+        .flat_map(|language| {
+            manager
+                .rule_origins(*language)
+                .into_iter()
+                .map(move |(rule, origin, active)| (language.name(), rule, origin, active))
+        })
+        .filter(|(_, rule, _, active)| !active_only || (*active && rule.enabled))
+        .collect();
+    rules.sort_by(|a, b| (a.0, &a.2.qualified_id).cmp(&(b.0, &b.2.qualified_id)));
+
+    let status = |rule: &sniff::playbook::DetectionRule, active: bool| {
+        if !active {
+            "shadowed"
+        } else if rule.enabled {
+            "active"
+        } else {
+            "disabled"
+        }
+    };
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Loaded Patterns ({})", rules.len());
+            println!("═══════════════════════════════════════");
+            for (language, rule, origin, active) in &rules {
+                println!(
+                    "{} {:<8} {:<10} {:<8} {} {}",
+                    rule.severity.emoji(),
+                    status(rule, *active),
+                    language,
+                    origin.source.name(),
+                    origin.qualified_id,
+                    rule.name
+                );
+            }
+        }
+        SummaryFormat::Json => {
+            let rules: Vec<_> = rules
+                .iter()
+                .map(|(language, rule, origin, active)| {
+                    serde_json::json!({
+                        "qualified_id": origin.qualified_id,
+                        "rule_id": rule.id,
+                        "language": language,
+                        "playbook": origin.playbook,
+                        "source": origin.source,
+                        "status": status(rule, *active),
+                        "severity": rule.severity,
+                        "name": rule.name,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&rules)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# Loaded Patterns");
+            println!();
+            println!("| Rule | Language | Source | Status | Severity | Name |");
+            println!("|------|----------|--------|--------|----------|------|");
+            for (language, rule, origin, active) in &rules {
+                println!(
+                    "| `{}` | {} | {} | {} | {:?} | {} |",
+                    origin.qualified_id,
+                    language,
+                    origin.source.name(),
+                    status(rule, *active),
+                    rule.severity,
+                    rule.name
+                );
+            }
+        }
+        SummaryFormat::Compact => {
+            for (language, rule, origin, active) in &rules {
+                println!(
+                    "{}:{}:{}:{}",
+                    language,
+                    origin.qualified_id,
+                    origin.source.name(),
+                    status(rule, *active)
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Displays rule ids defined by more than one playbook.
+fn display_rule_conflicts(
+    conflicts: &[sniff::playbook::RuleConflict],
+    format: OutputFormat,
+) -> Result<()> {
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Rule Id Conflicts ({})", conflicts.len());
+            println!("═══════════════════════════════════════");
+            println!(">> Precedence: learned > pack > built-in; first loaded wins among equals");
+            for conflict in conflicts {
+                println!();
+                println!("{} ({})", conflict.rule_id, conflict.language);
+                println!(
+                    "   active:   {} [{}]",
+                    conflict.active.qualified_id,
+                    conflict.active.source.name()
+                );
+                for origin in &conflict.shadowed {
+                    println!(
+                        "   shadowed: {} [{}]",
+                        origin.qualified_id,
+                        origin.source.name()
+                    );
+                }
+            }
+        }
+        SummaryFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(conflicts)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# Rule Id Conflicts");
+            println!();
+            println!("Precedence: learned > pack > built-in; first loaded wins among equals.");
+            println!();
+            println!("| Rule | Language | Active | Shadowed |");
+            println!("|------|----------|--------|----------|");
+            for conflict in conflicts {
+                let shadowed: Vec<_> = conflict
+                    .shadowed
+                    .iter()
+                    .map(|origin| format!("`{}`", origin.qualified_id))
+                    .collect();
+                println!(
+                    "| {} | {} | `{}` | {} |",
+                    conflict.rule_id,
+                    conflict.language,
+                    conflict.active.qualified_id,
+                    shadowed.join(", ")
+                );
+            }
+        }
+        SummaryFormat::Compact => {
+            for conflict in conflicts {
+                let shadowed: Vec<_> = conflict
+                    .shadowed
+                    .iter()
+                    .map(|origin| origin.qualified_id.as_str())
+                    .collect();
+                println!(
+                    "{}:{}:{}>{}",
+                    conflict.language,
+                    conflict.rule_id,
+                    conflict.active.qualified_id,
+                    shadowed.join(",")
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the patterns import command - bulk-creates learned patterns.
+fn handle_patterns_import_command(file: &Path, read_only: bool) -> Result<()> {
+    if read_only {
+        report_read_only(&format!("patterns from {} not imported", file.display()));
+        return Ok(());
+    }
+
+    let home_dir = dirs::home_dir()
+        .ok_or_else(|| SniffError::analysis_error("Cannot determine home directory"))?;
+    let mut manager = sniff::PatternLearningManager::new(&home_dir)?;
+    let report = manager.import_patterns(file)?;
+
+    println!(
+        ">> Imported {} patterns from {}",
+        report.created.len(),
+        file.display()
+    );
+    for (row, warning) in &report.warnings {
+        println!("   row {}: {}", row, warning);
+    }
+    if report.failures.is_empty() {
+        return Ok(());
+    }
+
+    println!("!! {} rows failed:", report.failures.len());
+    for failure in &report.failures {
+        match &failure.name {
+            Some(name) => println!("   row {} ({}): {}", failure.row, name, failure.error),
+            None => println!("   row {}: {}", failure.row, failure.error),
+        }
+    }
+    Err(SniffError::invalid_format(
+        file.display().to_string(),
+        format!(
+            "{} of {} rows failed to import",
+            report.failures.len(),
+            report.failures.len() + report.created.len()
+        ),
+    ))
+}
+
+/// Installs a pattern pack, or every pack in the lockfile.
+fn handle_patterns_install_command(
+    source: Option<&str>,
+    sha256: Option<&str>,
+    registry: Option<String>,
+    locked: bool,
+    trusted_keys: &[String],
+    allow_unsigned: bool,
+    read_only: bool,
+) -> Result<()> {
+    if read_only {
+        return Err(SniffError::read_only("Installing pattern packs"));
+    }
+
+    let patterns_dir = ensure_sniff_directory()?.join("patterns");
+    let registry = registry.or_else(|| std::env::var("SNIFF_PACK_REGISTRY").ok());
+    let mut keys = sniff::pack_signing::load_trusted_keys(&patterns_dir)?;
+    for key in trusted_keys {
+        keys.push(sniff::pack_signing::PublicKey::parse(key)?);
+    }
+    let installer = sniff::pack_registry::PackInstaller::new(&patterns_dir, registry)
+        .with_trusted_keys(keys)
+        .allow_unsigned(allow_unsigned);
+    let installed = if locked {
+        installer.install_locked()?
+    } else {
+        let spec = source.ok_or_else(|| SniffError::analysis_error("No pack to install"))?;
+        let source = sniff::pack_registry::PackSource::parse(spec)?;
+        vec![installer.install(&source, sha256)?]
+    };
+
+    for pack in &installed {
+        println!(
+            ">> Installed {}@{} ({} files, sha256 {}, {})",
+            pack.name,
+            pack.version,
+            pack.files.len(),
+            pack.sha256,
+            pack.signed_by.as_ref().map_or_else(
+                || "UNSIGNED".to_string(),
+                |keys| format!("signed by {keys}")
+            )
+        );
+    }
+    if locked && installed.is_empty() {
+        println!(
+            ">> No packs in {}",
+            patterns_dir.join("packs.lock").display()
+        );
+    }
+    Ok(())
+}
+
+/// Resolves the directory holding session transcripts.
+fn sessions_projects_dir(projects_dir: Option<PathBuf>) -> Result<PathBuf> {
+    projects_dir
+        .or_else(sniff::session::Session::default_projects_dir)
+        .ok_or_else(|| SniffError::analysis_error("Cannot determine home directory"))
+}
+
+/// Loads the code written by the session transcripts in a projects directory,
+/// or none if there are no transcripts to cross-reference.
+fn load_session_writes(projects_dir: Option<PathBuf>) -> sniff::ai_likelihood::SessionWrites {
+    let writes = sessions_projects_dir(projects_dir)
+        .and_then(|dir| sniff::ai_likelihood::SessionWrites::load(&dir));
+    match writes {
+        Ok(writes) => {
+            if writes.is_empty() {
+                info!("[INFO] No session transcripts to cross-reference; AI likelihood is heuristic only");
+            }
+            writes
+        }
+        Err(e) => {
+            warn!("Failed to load session transcripts: {}", e);
+            sniff::ai_likelihood::SessionWrites::default()
+        }
+    }
+}
+
+/// Loads a session by id or transcript path.
+fn load_session(session: &str, projects_dir: Option<PathBuf>) -> Result<sniff::session::Session> {
+    use sniff::session::Session;
+
+    let projects_dir = sessions_projects_dir(projects_dir)?;
+    Session::load(&Session::locate(session, &projects_dir)?)
+}
+
+/// Handles `patterns replay` - shows what the ruleset would have caught during a session.
+fn handle_patterns_replay_command(
+    session: &str,
+    projects_dir: Option<PathBuf>,
+    format: OutputFormat,
+    read_only: bool,
+) -> Result<()> {
+    let session = load_session(session, projects_dir)?;
+    let mut analyzer = create_misalignment_analyzer(read_only)?;
+    let steps = session.replay(&mut analyzer)?;
+
+    let flagged = steps
+        .iter()
+        .filter(|step| !step.detections.is_empty())
+        .count();
+    let total_detections: usize = steps.iter().map(|step| step.detections.len()).sum();
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Ruleset Replay: {}", session.id);
+            println!("═══════════════════════════════════════");
+            println!();
+            println!(">> Code writes analyzed: {}", steps.len());
+            println!(">> Writes with detections: {}", flagged);
+            println!(">> Total detections: {}", total_detections);
+
+            for step in steps.iter().filter(|step| !step.detections.is_empty()) {
+                let timestamp = step.timestamp.map_or_else(String::new, |t| {
+                    format!(" at {}", t.format("%Y-%m-%d %H:%M:%S"))
+                });
+                println!();
+                println!(
+                    "Step {} - {} {}{}",
+                    step.step, step.tool, step.file_path, timestamp
+                );
+                for detection in &step.detections {
+                    println!(
+                        "  {} {} (line {}): {}",
+                        detection.severity.emoji(),
+                        detection.rule_name,
+                        detection.line_number,
+                        detection.code_snippet.trim()
+                    );
+                }
+            }
+        }
+        SummaryFormat::Json => {
+            let report = serde_json::json!({
+                "session_id": session.id,
+                "writes_analyzed": steps.len(),
+                "writes_with_detections": flagged,
+                "total_detections": total_detections,
+                "steps": steps,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# Ruleset Replay: {}", session.id);
+            println!();
+            println!(
+                "{} of {} code writes would have been flagged ({} detections).",
+                flagged,
+                steps.len(),
+                total_detections
+            );
+            println!();
+            println!("| Step | Tool | File | Line | Rule | Severity |");
+            println!("|------|------|------|------|------|----------|");
+            for step in &steps {
+                for detection in &step.detections {
+                    println!(
+                        "| {} | {} | `{}` | {} | {} | {} |",
+                        step.step,
+                        step.tool,
+                        step.file_path,
+                        detection.line_number,
+                        detection.rule_name,
+                        detection.severity.name()
+                    );
+                }
+            }
+        }
+        SummaryFormat::Compact => {
+            for step in &steps {
+                for detection in &step.detections {
+                    println!(
+                        "step {}: {}:{} {} ({})",
+                        step.step,
+                        step.file_path,
+                        detection.line_number,
+                        detection.rule_id,
+                        detection.severity.name()
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles session analysis commands.
+fn handle_sessions_command(
+    command: SessionCommands,
+    projects_dir: Option<PathBuf>,
+    read_only: bool,
+) -> Result<()> {
+    match command {
+        SessionCommands::Scorecard { session, format } => {
+            let session = load_session(&session, projects_dir)?;
+            let mut analyzer = create_misalignment_analyzer(read_only)?;
+            let scorecard = sniff::scorecard::SessionScorecard::build(&session, &mut analyzer)?;
+            display_scorecard(&scorecard, format)
+        }
+        SessionCommands::Thinking { session, format } => {
+            handle_thinking_command(session.as_deref(), projects_dir, format, read_only)
+        }
+        SessionCommands::Injections { session, format } => {
+            handle_injections_command(session.as_deref(), projects_dir, format)
+        }
+        SessionCommands::RiskyCommands { session, format } => {
+            handle_risky_commands_command(session.as_deref(), projects_dir, format)
+        }
+        SessionCommands::Egress { session, format } => {
+            handle_egress_command(session.as_deref(), projects_dir, format)
+        }
+        SessionCommands::Files {
+            session,
+            flagged,
+            format,
+        } => handle_file_access_command(session.as_deref(), projects_dir, flagged, format),
+        SessionCommands::Enforce {
+            session,
+            policy,
+            format,
+        } => handle_enforce_command(&session, projects_dir, &policy, format),
+        SessionCommands::Monitor {
+            session,
+            policy,
+            debounce_ms,
+            max_batch,
+        } => handle_monitor_command(
+            session.as_deref(),
+            projects_dir,
+            policy,
+            sniff::monitor::WatchBatching {
+                debounce: std::time::Duration::from_millis(debounce_ms),
+                max_batch,
+            },
+            read_only,
+        ),
+        SessionCommands::CompareModels {
+            since,
+            limit,
+            format,
+        } => handle_compare_models_command(projects_dir, since, limit, format, read_only),
+    }
+}
+
+/// Loads one session, or every session under the projects directory.
+fn load_sessions(
+    session: Option<&str>,
+    projects_dir: Option<PathBuf>,
+) -> Result<Vec<sniff::session::Session>> {
+    use sniff::session::Session;
+
+    match session {
+        Some(session) => Ok(vec![load_session(session, projects_dir)?]),
+        None => Session::discover(&sessions_projects_dir(projects_dir)?)?
+            .iter()
+            .map(|path| Session::load(path))
+            .collect(),
+    }
+}
+
+/// Handles `sessions thinking` - applies thinking rules to assistant thinking blocks.
+fn handle_thinking_command(
+    session: Option<&str>,
+    projects_dir: Option<PathBuf>,
+    format: OutputFormat,
+    read_only: bool,
+) -> Result<()> {
+    let sessions = load_sessions(session, projects_dir)?;
+    let rules = create_misalignment_analyzer(read_only)?.thinking_rules();
+
+    let flagged: Vec<(&str, Vec<sniff::session::ThinkingFlag>)> = sessions
+        .iter()
+        .map(|session| (session.id.as_str(), session.thinking_flags(&rules)))
+        .filter(|(_, flags)| !flags.is_empty())
+        .collect();
+    let total_flags: usize = flagged.iter().map(|(_, flags)| flags.len()).sum();
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Thinking Red Flags");
+            println!("═══════════════════════════════════════");
+            println!();
+            println!(
+                ">> {} flags in {} of {} sessions",
+                total_flags,
+                flagged.len(),
+                sessions.len()
+            );
+            for (session_id, flags) in &flagged {
+                println!();
+                println!("Session {}:", session_id);
+                for flag in flags {
+                    let timestamp = flag.timestamp.map_or_else(String::new, |t| {
+                        format!("{} ", t.format("%Y-%m-%d %H:%M:%S"))
+                    });
+                    println!(
+                        "  {} {}{}",
+                        flag.severity.emoji(),
+                        timestamp,
+                        flag.rule_name
+                    );
+                    println!("      \"{}\"", flag.excerpt);
+                }
+            }
+        }
+        SummaryFormat::Json => {
+            let report: Vec<serde_json::Value> = flagged
+                .iter()
+                .map(|(session_id, flags)| serde_json::json!({"session_id": session_id, "flags": flags}))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# Thinking Red Flags");
+            println!();
+            println!("| Session | Time | Rule | Severity | Excerpt |");
+            println!("|---------|------|------|----------|---------|");
+            for (session_id, flags) in &flagged {
+                for flag in flags {
+                    println!(
+                        "| {} | {} | {} | {} | {} |",
+                        session_id,
+                        flag.timestamp.map_or_else(String::new, |t| t.to_rfc3339()),
+                        flag.rule_name,
+                        flag.severity.name(),
+                        flag.excerpt.replace('|', "\\|")
+                    );
+                }
+            }
+        }
+        SummaryFormat::Compact => {
+            for (session_id, flags) in &flagged {
+                for flag in flags {
+                    println!("{}: {} \"{}\"", session_id, flag.rule_id, flag.excerpt);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles `sessions injections` - scans tool results for prompt-injection markers.
+fn handle_injections_command(
+    session: Option<&str>,
+    projects_dir: Option<PathBuf>,
+    format: OutputFormat,
+) -> Result<()> {
+    use sniff::injection::{scan_session, InjectionFinding};
+
+    let sessions = load_sessions(session, projects_dir)?;
+    let flagged: Vec<(&str, Vec<InjectionFinding>)> = sessions
+        .iter()
+        .map(|session| (session.id.as_str(), scan_session(session)))
+        .filter(|(_, findings)| !findings.is_empty())
+        .collect();
+    let total_findings: usize = flagged.iter().map(|(_, findings)| findings.len()).sum();
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Prompt Injection Markers");
+            println!("═══════════════════════════════════════");
+            println!();
+            println!(
+                ">> {} markers in {} of {} sessions",
+                total_findings,
+                flagged.len(),
+                sessions.len()
+            );
+            for (session_id, findings) in &flagged {
+                println!();
+                println!("Session {}:", session_id);
+                for finding in findings {
+                    let timestamp = finding.timestamp.map_or_else(String::new, |t| {
+                        format!("{} ", t.format("%Y-%m-%d %H:%M:%S"))
+                    });
+                    println!(
+                        "  {} {}{} in {} result",
+                        finding.severity.emoji(),
+                        timestamp,
+                        finding.kind,
+                        finding.tool_name.as_deref().unwrap_or("tool")
+                    );
+                    if let Some(uuid) = &finding.message_uuid {
+                        println!("      message: {}", uuid);
+                    }
+                    println!("      \"{}\"", finding.excerpt);
+                }
+            }
+        }
+        SummaryFormat::Json => {
+            let report: Vec<serde_json::Value> = flagged
+                .iter()
+                .map(|(session_id, findings)| {
+                    serde_json::json!({"session_id": session_id, "findings": findings})
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# Prompt Injection Markers");
+            println!();
+            println!("| Session | Message | Tool | Marker | Severity | Excerpt |");
+            println!("|---------|---------|------|--------|----------|---------|");
+            for (session_id, findings) in &flagged {
+                for finding in findings {
+                    println!(
+                        "| {} | {} | {} | {} | {} | {} |",
+                        session_id,
+                        finding.message_uuid.as_deref().unwrap_or(""),
+                        finding.tool_name.as_deref().unwrap_or(""),
+                        finding.kind,
+                        finding.severity.name(),
+                        finding.excerpt.replace('|', "\\|")
+                    );
+                }
+            }
+        }
+        SummaryFormat::Compact => {
+            for (session_id, findings) in &flagged {
+                for finding in findings {
+                    println!("{}: {} \"{}\"", session_id, finding.kind, finding.excerpt);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles `sessions risky-commands` - classifies Bash tool uses by risk.
+fn handle_risky_commands_command(
+    session: Option<&str>,
+    projects_dir: Option<PathBuf>,
+    format: OutputFormat,
+) -> Result<()> {
+    use sniff::bash_risk::{audit_session, RiskyCommand};
+
+    let sessions = load_sessions(session, projects_dir)?;
+    let flagged: Vec<(&str, Vec<RiskyCommand>)> = sessions
+        .iter()
+        .map(|session| (session.id.as_str(), audit_session(session)))
+        .filter(|(_, commands)| !commands.is_empty())
+        .collect();
+    let total_commands: usize = flagged.iter().map(|(_, commands)| commands.len()).sum();
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Risky Commands");
+            println!("═══════════════════════════════════════");
+            println!();
+            println!(
+                ">> {} risky commands in {} of {} sessions",
+                total_commands,
+                flagged.len(),
+                sessions.len()
+            );
+            for (session_id, commands) in &flagged {
+                println!();
+                println!("Session {}:", session_id);
+                for risky in commands {
+                    let timestamp = risky.timestamp.map_or_else(String::new, |t| {
+                        format!("{} ", t.format("%Y-%m-%d %H:%M:%S"))
+                    });
+                    println!(
+                        "  {} {}step {} {} - {}",
+                        risky.severity.emoji(),
+                        timestamp,
+                        risky.step,
+                        risky.category,
+                        risky.description
+                    );
+                    println!("      $ {}", risky.command);
+                    if let Some(purpose) = &risky.purpose {
+                        println!("      purpose: {}", purpose);
+                    }
+                    if let Some(output) = &risky.output {
+                        println!("      output: {}", output);
+                    }
+                }
+            }
+        }
+        SummaryFormat::Json => {
+            let report: Vec<serde_json::Value> = flagged
+                .iter()
+                .map(|(session_id, commands)| {
+                    serde_json::json!({"session_id": session_id, "commands": commands})
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# Risky Commands");
+            println!();
+            println!("| Session | Step | Time | Category | Severity | Command |");
+            println!("|---------|------|------|----------|----------|---------|");
+            for (session_id, commands) in &flagged {
+                for risky in commands {
+                    println!(
+                        "| {} | {} | {} | {} | {} | `{}` |",
+                        session_id,
+                        risky.step,
+                        risky.timestamp.map_or_else(String::new, |t| t.to_rfc3339()),
+                        risky.category,
+                        risky.severity.name(),
+                        risky.command.replace('|', "\\|").replace('\n', " ")
+                    );
+                }
+            }
+        }
+        SummaryFormat::Compact => {
+            for (session_id, commands) in &flagged {
+                for risky in commands {
+                    println!(
+                        "{}:{}: {} {}",
+                        session_id, risky.step, risky.category, risky.command
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles `sessions egress` - summarizes network access per domain.
+fn handle_egress_command(
+    session: Option<&str>,
+    projects_dir: Option<PathBuf>,
+    format: OutputFormat,
+) -> Result<()> {
+    use sniff::egress::{egress_events, summarize_domains};
+
+    let sessions = load_sessions(session, projects_dir)?;
+    let events: Vec<_> = sessions.iter().flat_map(egress_events).collect();
+    let domains = summarize_domains(&events);
+    let searches: Vec<&str> = events
+        .iter()
+        .filter_map(|event| event.query.as_deref())
+        .collect();
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Network Egress");
+            println!("═══════════════════════════════════════");
+            println!();
+            println!(
+                ">> {} domains, {} requests, {} web searches across {} sessions",
+                domains.len(),
+                events.len() - searches.len(),
+                searches.len(),
+                sessions.len()
+            );
+            if !domains.is_empty() {
+                println!();
+                println!(
+                    "{:<40} {:>8} {:>8}  {:<20} Tools",
+                    "Domain", "Requests", "Sessions", "First Seen"
+                );
+                println!("{}", "-".repeat(100));
+                for domain in &domains {
+                    println!(
+                        "{:<40} {:>8} {:>8}  {:<20} {}",
+                        domain.domain,
+                        domain.requests,
+                        domain.sessions,
+                        domain
+                            .first_seen
+                            .map_or_else(String::new, |t| t.format("%Y-%m-%d %H:%M").to_string()),
+                        domain.tools.iter().cloned().collect::<Vec<_>>().join(", ")
+                    );
+                }
+            }
+        }
+        SummaryFormat::Json => {
+            let report = serde_json::json!({
+                "sessions": sessions.len(),
+                "domains": domains,
+                "searches": searches,
+                "events": events,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# Network Egress");
+            println!();
+            println!("| Domain | Requests | Sessions | First Seen | Last Seen | Tools |");
+            println!("|--------|----------|----------|------------|-----------|-------|");
+            for domain in &domains {
+                println!(
+                    "| {} | {} | {} | {} | {} | {} |",
+                    domain.domain,
+                    domain.requests,
+                    domain.sessions,
+                    domain
+                        .first_seen
+                        .map_or_else(String::new, |t| t.to_rfc3339()),
+                    domain
+                        .last_seen
+                        .map_or_else(String::new, |t| t.to_rfc3339()),
+                    domain.tools.iter().cloned().collect::<Vec<_>>().join(", ")
+                );
+            }
+            if !searches.is_empty() {
+                println!();
+                println!("## Web Searches");
+                println!();
+                for query in &searches {
+                    println!("- {}", query);
+                }
+            }
+        }
+        SummaryFormat::Compact => {
+            for domain in &domains {
+                println!("{} {}", domain.domain, domain.requests);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles `sessions files` - the file-access audit trail of sessions.
+fn handle_file_access_command(
+    session: Option<&str>,
+    projects_dir: Option<PathBuf>,
+    flagged_only: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    use sniff::file_access::{file_accesses, FileAccess};
+
+    let sessions = load_sessions(session, projects_dir)?;
+    let trails: Vec<(&str, Vec<FileAccess>)> = sessions
+        .iter()
+        .map(|session| {
+            let mut accesses = file_accesses(session);
+            if flagged_only {
+                accesses.retain(FileAccess::is_flagged);
+            }
+            (session.id.as_str(), accesses)
+        })
+        .filter(|(_, accesses)| !accesses.is_empty())
+        .collect();
+    let flag_label = |access: &FileAccess| {
+        let mut flags = Vec::new();
+        if access.outside_workspace {
+            flags.push("outside workspace".to_string());
+        }
+        if let Some(reason) = &access.sensitive {
+            flags.push(format!("sensitive: {}", reason));
+        }
+        flags.join(", ")
+    };
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: File Access Audit");
+            println!("═══════════════════════════════════════");
+            for (session_id, accesses) in &trails {
+                let flagged = accesses.iter().filter(|a| a.is_flagged()).count();
+                println!();
+                println!(
+                    ">> Session {}: {} accesses, {} flagged",
+                    session_id,
+                    accesses.len(),
+                    flagged
+                );
+                for access in accesses {
+                    let marker = if access.is_flagged() { "⚠" } else { " " };
+                    println!(
+                        "  {} {:>4} {:<6} {}",
+                        marker,
+                        access.step,
+                        access.operation,
+                        access.relative_path.as_deref().unwrap_or(&access.path)
+                    );
+                    if access.is_flagged() {
+                        println!("              {}", flag_label(access));
+                    }
+                }
+            }
+            if trails.is_empty() {
+                println!();
+                println!(">> No file accesses found");
+            }
+        }
+        SummaryFormat::Json => {
+            let report: Vec<serde_json::Value> = trails
+                .iter()
+                .map(|(session_id, accesses)| {
+                    serde_json::json!({"session_id": session_id, "accesses": accesses})
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# File Access Audit");
+            println!();
+            println!("| Session | Step | Time | Operation | Path | Flags |");
+            println!("|---------|------|------|-----------|------|-------|");
+            for (session_id, accesses) in &trails {
+                for access in accesses {
+                    println!(
+                        "| {} | {} | {} | {} | {} | {} |",
+                        session_id,
+                        access.step,
+                        access
+                            .timestamp
+                            .map_or_else(String::new, |t| t.to_rfc3339()),
+                        access.operation,
+                        access.relative_path.as_deref().unwrap_or(&access.path),
+                        flag_label(access)
+                    );
+                }
+            }
+        }
+        SummaryFormat::Compact => {
+            for (session_id, accesses) in &trails {
+                for access in accesses {
+                    println!(
+                        "{}:{}: {} {}",
+                        session_id, access.step, access.operation, access.path
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles `sessions enforce` - evaluates a session against a session policy.
+fn handle_enforce_command(
+    session: &str,
+    projects_dir: Option<PathBuf>,
+    policy_path: &std::path::Path,
+    format: OutputFormat,
+) -> Result<()> {
+    use sniff::session_policy::SessionPolicy;
+
+    let policy = SessionPolicy::load(policy_path)?;
+    let session = load_session(session, projects_dir)?;
+    let violations = policy.evaluate(&session);
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Session Policy: {}", session.id);
+            println!("═══════════════════════════════════════");
+            println!();
+            println!(
+                ">> Policy: {} ({} violations)",
+                policy_path.display(),
+                violations.len()
+            );
+            for violation in &violations {
+                let step = violation
+                    .step
+                    .map_or_else(String::new, |step| format!("step {} ", step));
+                println!();
+                println!(
+                    "  {} {}[{}] {}",
+                    violation.severity.emoji(),
+                    step,
+                    violation.rule_id,
+                    violation.message
+                );
+                if let Some(evidence) = &violation.evidence {
+                    println!("      {}", evidence);
+                }
+            }
+        }
+        SummaryFormat::Json => {
+            let report = serde_json::json!({
+                "session_id": session.id,
+                "policy": policy_path,
+                "passed": violations.is_empty(),
+                "violations": violations,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# Session Policy: {}", session.id);
+            println!();
+            println!("| Step | Rule | Kind | Severity | Message | Evidence |");
+            println!("|------|------|------|----------|---------|----------|");
+            for violation in &violations {
+                println!(
+                    "| {} | {} | {} | {} | {} | {} |",
+                    violation
+                        .step
+                        .map_or_else(String::new, |step| step.to_string()),
+                    violation.rule_id,
+                    violation.kind,
+                    violation.severity.name(),
+                    violation.message.replace('|', "\\|"),
+                    violation
+                        .evidence
+                        .as_deref()
+                        .unwrap_or("")
+                        .replace('|', "\\|")
+                );
+            }
+        }
+        SummaryFormat::Compact => {
+            for violation in &violations {
+                println!(
+                    "{}:{}: {} {}",
+                    session.id,
+                    violation
+                        .step
+                        .map_or_else(String::new, |step| step.to_string()),
+                    violation.rule_id,
+                    violation.message
+                );
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(SniffError::analysis_error(format!(
+            "Session {} violated the session policy ({} violations)",
+            session.id,
+            violations.len()
+        )))
+    }
+}
+
+/// Handles `sessions monitor` - live terminal view of an agent session.
+fn handle_monitor_command(
+    session: Option<&str>,
+    projects_dir: Option<PathBuf>,
+    policy: Option<PathBuf>,
+    batching: sniff::monitor::WatchBatching,
+    read_only: bool,
+) -> Result<()> {
+    use sniff::session::Session;
+    use sniff::session_policy::{SessionPolicy, DEFAULT_POLICY_PATH};
+
+    let projects_dir = sessions_projects_dir(projects_dir)?;
+    let (session_path, follow) = match session {
+        Some(session) => (Session::locate(session, &projects_dir)?, false),
+        None => (sniff::monitor::active_session(&projects_dir)?, true),
+    };
+
+    let policy = match policy {
+        Some(path) => Some(SessionPolicy::load(&path)?),
+        None => {
+            let default_path = PathBuf::from(DEFAULT_POLICY_PATH);
+            default_path
+                .exists()
+                .then(|| SessionPolicy::load(&default_path))
+                .transpose()?
+        }
+    };
+
+    let mut analyzer = create_misalignment_analyzer(read_only)?;
+    sniff::monitor::run(
+        session_path,
+        follow,
+        &mut analyzer,
+        policy.as_ref(),
+        batching,
+    )
+}
+
+/// Handles `sessions compare-models` - aggregates scorecards by model.
+fn handle_compare_models_command(
+    projects_dir: Option<PathBuf>,
+    since: Option<chrono::NaiveDate>,
+    limit: Option<usize>,
+    format: OutputFormat,
+    read_only: bool,
+) -> Result<()> {
+    use sniff::scorecard::{compare_models, SessionScorecard};
+    use sniff::session::Session;
+
+    let projects_dir = sessions_projects_dir(projects_dir)?;
+    let mut sessions = Vec::new();
+    let mut unreadable = 0;
+    for path in Session::discover(&projects_dir)? {
+        // One corrupt or half-written transcript should not abort the whole comparison
+        let session = match Session::load(&path) {
+            Ok(session) => session,
+            Err(e) => {
+                warn!("Skipping unreadable transcript {}: {}", path.display(), e);
+                unreadable += 1;
+                continue;
+            }
+        };
+        let started = session.time_range().map(|(start, _)| start);
+        if since.is_some_and(|since| started.is_none_or(|start| start.date_naive() < since)) {
+            continue;
+        }
+        sessions.push((started, session));
+    }
+
+    // Most recent first, so --limit keeps the latest sessions
+    sessions.sort_by_key(|(started, _)| std::cmp::Reverse(*started));
+    if let Some(limit) = limit {
+        sessions.truncate(limit);
+    }
+
+    info!("Scoring {} sessions", sessions.len());
+    let mut analyzer = create_misalignment_analyzer(read_only)?;
+    let mut scorecards = Vec::new();
+    for (_, session) in &sessions {
+        scorecards.push(SessionScorecard::build(session, &mut analyzer)?);
+    }
+
+    if unreadable > 0 {
+        eprintln!(">> Skipped {} unreadable transcripts", unreadable);
+    }
+
+    let summaries = compare_models(&scorecards);
+    let rate =
+        |rate: Option<f64>| rate.map_or_else(|| "-".to_string(), |r| format!("{:.0}%", r * 100.0));
+    let delta = |delta: Option<f64>| delta.map_or_else(|| "-".to_string(), |d| format!("{d:+.1}"));
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Model Comparison ({} sessions)", scorecards.len());
+            println!("═══════════════════════════════════════");
+            for summary in &summaries {
+                println!();
+                println!("{} ({} sessions)", summary.model, summary.sessions);
+                println!("  Quality delta: {}", delta(summary.average_quality_delta));
+                println!(
+                    "  Detections per session: {:.1}",
+                    summary.detections_per_session
+                );
+                println!("  Deception rate: {}", rate(Some(summary.deception_rate)));
+                println!("  Gate pass rate: {}", rate(summary.gate_pass_rate));
+                println!("  Tokens per session: {:.0}", summary.average_tokens);
+            }
+        }
+        SummaryFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&summaries)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# Model Comparison");
+            println!();
+            println!("| Model | Sessions | Quality delta | Detections/session | Deception rate | Gate pass rate | Tokens/session |");
+            println!("|-------|----------|---------------|--------------------|----------------|----------------|----------------|");
+            for summary in &summaries {
+                println!(
+                    "| {} | {} | {} | {:.1} | {} | {} | {:.0} |",
+                    summary.model,
+                    summary.sessions,
+                    delta(summary.average_quality_delta),
+                    summary.detections_per_session,
+                    rate(Some(summary.deception_rate)),
+                    rate(summary.gate_pass_rate),
+                    summary.average_tokens
+                );
+            }
+        }
+        SummaryFormat::Compact => {
+            for summary in &summaries {
+                println!(
+                    "{}: {} sessions, quality delta {}, deception {}, gates {}",
+                    summary.model,
+                    summary.sessions,
+                    delta(summary.average_quality_delta),
+                    rate(Some(summary.deception_rate)),
+                    rate(summary.gate_pass_rate)
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Displays a session scorecard.
+fn display_scorecard(
+    scorecard: &sniff::scorecard::SessionScorecard,
+    format: OutputFormat,
+) -> Result<()> {
+    let score = |score: Option<f64>| score.map_or_else(|| "-".to_string(), |s| format!("{s:.1}"));
+    let delta = |delta: Option<f64>| delta.map_or_else(|| "-".to_string(), |d| format!("{d:+.1}"));
+    let duration = scorecard.duration_seconds.map_or_else(
+        || "-".to_string(),
+        |seconds| format!("{}m {}s", seconds / 60, seconds % 60),
+    );
+
+    match summary_format(format)? {
+        SummaryFormat::Table => {
+            println!(":: Session Scorecard: {}", scorecard.session_id);
+            println!("═══════════════════════════════════════");
+            println!();
+            println!(">> Models: {}", scorecard.models.join(", "));
+            println!(">> Duration: {}", duration);
+            println!(">> Tool uses: {}", scorecard.tool_uses);
+            println!(
+                ">> Tokens: {} input, {} output, {} cached",
+                scorecard.tokens.input_tokens,
+                scorecard.tokens.output_tokens,
+                scorecard.tokens.cache_creation_input_tokens
+                    + scorecard.tokens.cache_read_input_tokens
+            );
+            println!(
+                ">> Average quality delta: {}",
+                delta(scorecard.average_quality_delta)
+            );
+            println!(
+                ">> Detections introduced: {}",
+                scorecard.detections_introduced
+            );
+
+            if !scorecard.files.is_empty() {
+                println!();
+                println!("Files Touched:");
+                for file in &scorecard.files {
+                    println!(
+                        "  {}{}: {} -> {} ({}), {} new detections",
+                        file.file_path,
+                        if file.created { " (new)" } else { "" },
+                        score(file.quality_before),
+                        score(file.quality_after),
+                        delta(file.quality_delta),
+                        file.detections_introduced
+                    );
+                }
+            }
+
+            if !scorecard.todo_verifications.is_empty() {
+                println!();
+                println!("TODO Verifications:");
+                for outcome in &scorecard.todo_verifications {
+                    println!(
+                        "  {} {}: {} ({:.1}% quality, {} critical)",
+                        outcome.timestamp.format("%H:%M:%S"),
+                        outcome.todo_id,
+                        if outcome.passed { "PASSED" } else { "FAILED" },
+                        outcome.quality_score,
+                        outcome.critical_issues
+                    );
+                }
+            }
+
+            println!();
+            if scorecard.deception_indicators.is_empty() {
+                println!(">> No deception indicators");
+            } else {
+                println!("Deception Indicators:");
+                for indicator in &scorecard.deception_indicators {
+                    println!("  [{}] {}", indicator.kind, indicator.description);
+                }
+            }
+        }
+        SummaryFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(scorecard)?);
+        }
+        SummaryFormat::Markdown => {
+            println!("# Session Scorecard: {}", scorecard.session_id);
+            println!();
+            println!("| Metric | Value |");
+            println!("|--------|-------|");
+            println!("| Models | {} |", scorecard.models.join(", "));
+            println!("| Duration | {} |", duration);
+            println!("| Tool uses | {} |", scorecard.tool_uses);
+            println!("| Total tokens | {} |", scorecard.tokens.total());
+            println!(
+                "| Average quality delta | {} |",
+                delta(scorecard.average_quality_delta)
+            );
+            println!(
+                "| Detections introduced | {} |",
+                scorecard.detections_introduced
+            );
+            println!(
+                "| Deception indicators | {} |",
+                scorecard.deception_indicators.len()
+            );
+            println!();
+            println!("## Files Touched");
+            println!();
+            println!("| File | Before | After | Delta | New detections |");
+            println!("|------|--------|-------|-------|----------------|");
+            for file in &scorecard.files {
+                println!(
+                    "| `{}` | {} | {} | {} | {} |",
+                    file.file_path,
+                    score(file.quality_before),
+                    score(file.quality_after),
+                    delta(file.quality_delta),
+                    file.detections_introduced
+                );
+            }
+            if !scorecard.todo_verifications.is_empty() {
+                println!();
+                println!("## TODO Verifications");
+                println!();
+                for outcome in &scorecard.todo_verifications {
+                    println!(
+                        "- {} `{}`: {} ({:.1}% quality)",
+                        if outcome.passed { "✅" } else { "❌" },
+                        outcome.todo_id,
+                        if outcome.passed { "passed" } else { "failed" },
+                        outcome.quality_score
+                    );
+                }
+            }
+            if !scorecard.deception_indicators.is_empty() {
+                println!();
+                println!("## Deception Indicators");
+                println!();
+                for indicator in &scorecard.deception_indicators {
+                    println!("- **{}**: {}", indicator.kind, indicator.description);
+                }
+            }
+        }
+        SummaryFormat::Compact => {
+            println!(
+                "{}: {} files, quality delta {}, {} new detections, {} indicators, {} tokens, {}",
+                scorecard.session_id,
+                scorecard.files.len(),
+                delta(scorecard.average_quality_delta),
+                scorecard.detections_introduced,
+                scorecard.deception_indicators.len(),
+                scorecard.tokens.total(),
+                duration
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles the verify-todo command - verifies TODO completion with sniff analysis.
+///
+/// Returns whether the quality gate passed.
+async fn handle_verify_todo_command(
+    todo_id: String,
+    files: Vec<PathBuf>,
+    config: sniff::verify_todo::VerificationConfig,
+    format: OutputFormat,
+    git_discovery: bool,
+    quiet: bool,
+) -> Result<bool> {
+    use sniff::verify_todo::{display_verification_result, verify_todo, GateReport};
+
+    let json = is_json_format(format)?;
+
+    // A missing file is an input error, not a passing gate over zero files
+    if !git_discovery {
+        if let Some(missing) = files.iter().find(|file| !file.exists()) {
+            return Err(SniffError::file_system(
+                missing,
+                std::io::Error::new(std::io::ErrorKind::NotFound, "reported file does not exist"),
+            ));
+        }
+    }
+
+    // Use git discovery if requested, otherwise use provided files
+    let mut unreported_files = Vec::new();
+    let actual_files = if git_discovery {
+        match sniff::verify_todo::discover_git_changes() {
+            Ok(git_files) => {
+                if git_files != files && !quiet {
+                    eprintln!(
+                        "Git discovery found {} files vs {} reported",
+                        git_files.len(),
+                        files.len()
+                    );
+                    eprintln!("Using git-discovered files for verification");
+                }
+                unreported_files = git_files
+                    .iter()
+                    .filter(|file| !files.contains(file))
+                    .cloned()
+                    .collect();
+                git_files
+            }
+            Err(e) => {
+                if !quiet {
+                    eprintln!("Git discovery failed: {}, using reported files", e);
+                }
+                files
+            }
+        }
+    } else {
+        files
+    };
+
+    let result = verify_todo(&todo_id, &actual_files, config.clone()).await?;
+    let report = GateReport::new(&todo_id, &result, &config, &unreported_files);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        display_verification_result(&todo_id, &result, &config);
+        if !report.pass && !quiet {
+            eprintln!(
+                "TODO '{}' failed verification: {}",
+                todo_id,
+                report.reasons.join(", ")
+            );
+        }
+    }
+
+    Ok(report.pass)
+}
+
+/// Output format of commands that do not report detections.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SummaryFormat {
+    Table,
+    Json,
+    Markdown,
+    Compact,
+}
+
+/// Narrows the output format of a command that does not report detections.
+///
+/// The detection formats (Arrow, SARIF, `JUnit`, Code Climate and rdjson) are
+/// rejected rather than silently replaced by JSON.
+fn summary_format(format: OutputFormat) -> Result<SummaryFormat> {
+    match format {
+        OutputFormat::Table => Ok(SummaryFormat::Table),
+        OutputFormat::Json => Ok(SummaryFormat::Json),
+        OutputFormat::Markdown => Ok(SummaryFormat::Markdown),
+        OutputFormat::Compact => Ok(SummaryFormat::Compact),
+        OutputFormat::Arrow
+        | OutputFormat::Sarif
+        | OutputFormat::Junit
+        | OutputFormat::Codeclimate
+        | OutputFormat::Rdjson
+        | OutputFormat::Rdjsonl => {
+            let name = format
+                .to_possible_value()
+                .map_or_else(String::new, |value| value.get_name().to_string());
+            Err(SniffError::invalid_format(
+                "output format",
+                format!(
+                    "'{name}' is only produced by commands that report detections; \
+                     use table, compact, markdown or json"
+                ),
+            ))
+        }
+    }
+}
+
+/// Returns true if a command that does not report detections should print JSON.
+fn is_json_format(format: OutputFormat) -> Result<bool> {
+    Ok(summary_format(format)? == SummaryFormat::Json)
+}
+
+/// Handles the waive command - manages time-boxed waivers in .sniff/waivers.yaml.
+fn handle_waive_command(
+    fingerprint: Option<String>,
+    until: Option<String>,
+    reason: Option<String>,
+    list: bool,
+    remove: Option<String>,
+    read_only: bool,
+) -> Result<()> {
+    use sniff::waiver::{Waiver, WaiverStore};
+
+    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+    let mut store = WaiverStore::load(&current_dir)?;
+
+    if list {
+        if store.waivers().is_empty() {
+            println!(">> No waivers found");
+            return Ok(());
+        }
+
+        let today = chrono::Utc::now().date_naive();
+        println!(":: Waivers");
+        for waiver in store.waivers() {
+            println!(
+                "   {} until {} [{}] - {}",
+                waiver.fingerprint,
+                waiver.until,
+                waiver.status_on(today).name(),
+                waiver.reason
+            );
+        }
+        return Ok(());
+    }
+
+    if read_only {
+        return Err(SniffError::read_only("Changing waivers"));
+    }
+
+    if let Some(fingerprint) = remove {
+        if !store.remove(&fingerprint) {
+            return Err(SniffError::invalid_format(
+                "waiver".to_string(),
+                format!("No waiver found for fingerprint '{fingerprint}'"),
+            ));
+        }
+        store.save()?;
+        println!(">> Waiver for '{}' removed", fingerprint);
+        return Ok(());
+    }
+
+    // clap guarantees these are present when neither --list nor --remove is given
+    let (Some(fingerprint), Some(until), Some(reason)) = (fingerprint, until, reason) else {
+        return Err(SniffError::invalid_format(
+            "waiver".to_string(),
+            "fingerprint, --until and --reason are required".to_string(),
+        ));
+    };
+
+    let until = chrono::NaiveDate::parse_from_str(&until, "%Y-%m-%d").map_err(|e| {
+        SniffError::invalid_format("waiver date".to_string(), format!("'{until}': {e}"))
+    })?;
+    if until < chrono::Utc::now().date_naive() {
+        return Err(SniffError::invalid_format(
+            "waiver date".to_string(),
+            format!("{until} is in the past"),
+        ));
+    }
+
+    store.add(Waiver {
+        fingerprint: fingerprint.clone(),
+        until,
+        reason,
+        created_at: chrono::Utc::now(),
+    });
+    store.save()?;
+
+    println!(">> Waived '{}' until {}", fingerprint, until);
+    Ok(())
+}
+
+/// Handles `check-text` - applies text rules to a commit message or PR description on stdin.
+fn handle_check_text_command(
+    kind: sniff::text_check::TextKind,
+    rules: Option<&Path>,
+    fail_on: &str,
+    format: OutputFormat,
+) -> Result<()> {
+    use sniff::playbook::Severity;
+    use sniff::text_check;
+    use std::io::Read;
+
+    let json = is_json_format(format)?;
+    let fail_on = Severity::from_name(fail_on).ok_or_else(|| {
+        SniffError::invalid_format(
+            "severity".to_string(),
+            format!("Unknown severity '{fail_on}' (expected info, low, medium, high or critical)"),
+        )
+    })?;
+    let playbook = match rules {
+        Some(path) => text_check::load_playbook(path)?,
+        None => text_check::default_playbook(kind),
+    };
+
+    let mut text = String::new();
+    std::io::stdin()
+        .read_to_string(&mut text)
+        .map_err(|e| SniffError::file_system("<stdin>", e))?;
+    let detections = text_check::check(&playbook, kind, &text)?;
+    let failing = detections
+        .iter()
+        .filter(|detection| detection.severity.score() >= fail_on.score())
+        .count();
+
+    if json {
+        let report = serde_json::json!({
+            "kind": kind.name(),
+            "passed": failing == 0,
+            "fail_on": fail_on.name(),
+            "detections": detections,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if format == OutputFormat::Markdown {
+        println!("# Text Check: {}", kind.name());
+        println!();
+        println!("| Line | Rule | Severity | Text |");
+        println!("|------|------|----------|------|");
+        for detection in &detections {
+            println!(
+                "| {} | {} | {} | {} |",
+                detection.line_number,
+                detection.rule_name,
+                detection.severity.name(),
+                detection.code_snippet.replace('|', "\\|")
+            );
+        }
+    } else if format == OutputFormat::Compact {
+        for detection in &detections {
+            println!(
+                "{}:{}: {} {}",
+                kind.name(),
+                detection.line_number,
+                detection.rule_id,
+                detection.rule_name
+            );
+        }
+    } else {
+        println!(":: Text Check: {}", kind.name());
+        println!();
+        println!(
+            ">> {} findings, {} at or above {}",
+            detections.len(),
+            failing,
+            fail_on.name()
+        );
+        for detection in &detections {
+            println!(
+                "  {} line {} [{}] {}: {}",
+                detection.severity.emoji(),
+                detection.line_number,
+                detection.rule_id,
+                detection.description,
+                detection.code_snippet
+            );
+        }
+    }
+
+    if failing == 0 {
+        Ok(())
+    } else {
+        Err(SniffError::analysis_error(format!(
+            "{} failed the text check ({} findings at or above {})",
+            kind.name(),
+            failing,
+            fail_on.name()
+        )))
+    }
+}
+
+/// Handles `doctor` - reports coverage gaps between the project and the loaded rules.
+/// Handles the bench command - times analysis of a corpus.
+async fn handle_bench_command(
+    corpus: &Path,
+    iterations: usize,
+    filter: sniff::standalone::FileFilter,
+    format: OutputFormat,
+    read_only: bool,
+) -> Result<()> {
+    use sniff::standalone::{AnalysisConfig, StandaloneAnalyzer};
+
+    let json = is_json_format(format)?;
+    let config = AnalysisConfig {
+        filter,
+        ..AnalysisConfig::default()
+    };
+    let mut analyzer = StandaloneAnalyzer::new(create_misalignment_analyzer(read_only)?, config);
+    let report = sniff::bench::run(&mut analyzer, corpus, iterations).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    if format == OutputFormat::Compact {
+        println!(
+            "files={} bytes={} discovery_ms={:.1} parse_ms={:.1} match_ms={:.1} total_ms={:.1} \
+             files_per_sec={:.1} mb_per_sec={:.2} peak_memory_bytes={}",
+            report.files,
+            report.bytes,
+            report.discovery_ms,
+            report.parse_ms,
+            report.match_ms,
+            report.total_ms,
+            report.files_per_sec,
+            report.mb_per_sec,
+            report
+                .peak_memory_bytes
+                .map_or_else(|| "n/a".to_string(), |bytes| bytes.to_string())
+        );
+        return Ok(());
+    }
+
+    println!(":: Benchmark");
+    println!("═══════════════════════════════════════");
+    println!(">> Corpus: {}", report.corpus.display());
+    println!(
+        ">> {} files, {:.2} MB, {} active rules, best of {} iterations",
+        report.files,
+        report.bytes as f64 / 1e6,
+        report.active_rules,
+        report.iterations
+    );
+    println!();
+    println!("   discovery  {:>10.1} ms", report.discovery_ms);
+    println!("   parse      {:>10.1} ms", report.parse_ms);
+    println!("   match      {:>10.1} ms", report.match_ms);
+    println!("   total      {:>10.1} ms", report.total_ms);
+    println!();
+    println!(
+        ">> {:.1} files/sec, {:.2} MB/sec",
+        report.files_per_sec, report.mb_per_sec
+    );
+    match report.peak_memory_bytes {
+        Some(bytes) => println!(">> Peak memory: {:.1} MB", bytes as f64 / 1e6),
+        None => println!(">> Peak memory: not available on this platform"),
+    }
+    if report.failed_files > 0 {
+        println!("!! {} files failed to analyze", report.failed_files);
+    }
+    Ok(())
+}
+
+fn handle_doctor_command(format: OutputFormat, read_only: bool) -> Result<()> {
+    let json = is_json_format(format)?;
+    let analyzer = create_misalignment_analyzer(read_only)?;
+    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+    let report = sniff::doctor::diagnose(&current_dir, &analyzer);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if format == OutputFormat::Markdown {
+        println!("# Sniff Doctor");
+        println!();
+        println!("| Language | Files | Active rules | Playbooks |");
+        println!("|----------|-------|--------------|-----------|");
+        for coverage in &report.languages {
+            println!(
+                "| {} | {} | {} | {} |",
+                coverage.language,
+                coverage.files,
+                coverage.active_rules,
+                coverage.playbooks.join(", ")
+            );
+        }
+        println!();
+        println!("Test frameworks: {}", report.frameworks.join(", "));
+        println!();
+        for gap in &report.gaps {
+            println!("- **{}**: {}", gap.message, gap.suggestion);
+        }
+    } else if format == OutputFormat::Compact {
+        for gap in &report.gaps {
+            println!("{}: {}", gap.kind.name(), gap.message);
+        }
+    } else {
+        println!(":: Sniff Doctor");
+        println!();
+        println!(">> Languages");
+        for coverage in &report.languages {
+            let playbooks = if coverage.playbooks.is_empty() {
+                "built-in only".to_string()
+            } else {
+                coverage.playbooks.join(", ")
+            };
+            println!(
+                "   {}: {} files, {} active rules ({})",
+                coverage.language, coverage.files, coverage.active_rules, playbooks
+            );
+        }
+        if !report.frameworks.is_empty() {
+            println!(">> Test frameworks: {}", report.frameworks.join(", "));
+        }
+        println!();
+        if report.gaps.is_empty() {
+            println!(">> No coverage gaps found");
+        } else {
+            println!(">> {} coverage gaps", report.gaps.len());
+            for gap in &report.gaps {
+                println!("   - {}", gap.message);
+                println!("     Suggestion: {}", gap.suggestion);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles `verify-todo history` - lists recorded attempts at a TODO.
+fn handle_verify_history_command(todo_id: &str, format: OutputFormat) -> Result<()> {
+    use sniff::audit::AuditLog;
+
+    let json = is_json_format(format)?;
+    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+    let records = AuditLog::new(&current_dir).history(todo_id)?;
 
-    match command {
-        CheckpointCommands::Create {
-            name,
-            paths,
-            description,
-        } => {
-            info!(">> Creating checkpoint: {}", name);
-            checkpoint_manager
-                .create_checkpoint(&name, &paths, description)
-                .await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+        return Ok(());
+    }
+
+    if format == OutputFormat::Markdown {
+        println!("# Verification History: {todo_id}");
+        println!();
+        println!("| Attempt | Time | Result | Quality | Critical | Files | Ruleset |");
+        println!("| ------- | ---- | ------ | ------- | -------- | ----- | ------- |");
+        for (attempt, record) in records.iter().enumerate() {
             println!(
-                ">> Checkpoint '{}' created with {} files",
-                name,
-                paths.len()
+                "| {} | {} | {} | {:.1}% | {} | {} | `{}` |",
+                attempt + 1,
+                record.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                if record.passed { "PASSED" } else { "FAILED" },
+                record.quality_score,
+                record.critical_issues,
+                record.files.join(", "),
+                record.ruleset_hash.as_deref().unwrap_or("-")
             );
         }
+        return Ok(());
+    }
 
-        CheckpointCommands::List { format } => {
-            let checkpoints = checkpoint_manager.list_checkpoints().await?;
+    if records.is_empty() {
+        println!(">> No verifications recorded for TODO '{todo_id}'");
+        return Ok(());
+    }
 
-            if checkpoints.is_empty() {
-                println!("[INFO] No checkpoints found");
-                return Ok(());
-            }
+    println!(":: Verification History: {todo_id}");
+    for (attempt, record) in records.iter().enumerate() {
+        println!(
+            "#{} {} {} quality {:.1}%, critical {}, ruleset {}",
+            attempt + 1,
+            record.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            if record.passed { "PASSED" } else { "FAILED" },
+            record.quality_score,
+            record.critical_issues,
+            record.ruleset_hash.as_deref().unwrap_or("-")
+        );
+        for file in &record.file_scores {
+            println!(
+                "   {} {:.1}% ({} critical, {} detections)",
+                file.path.display(),
+                file.quality_score,
+                file.critical_issues,
+                file.detections
+            );
+        }
+        for reason in &record.reasons {
+            println!("   ⚠ {reason}");
+        }
+    }
+    Ok(())
+}
 
-            match format {
-                OutputFormat::Table => {
-                    println!(":: Available Checkpoints");
-                    println!("════════════════════════");
-                    println!();
+/// Handles audit log commands.
+fn handle_audit_command(command: AuditCommands) -> Result<()> {
+    use sniff::audit::AuditLog;
 
-                    for checkpoint in checkpoints {
-                        println!("   {}", checkpoint.name);
+    match command {
+        AuditCommands::Show {
+            todo_id,
+            limit,
+            format,
+        } => {
+            let current_dir =
+                std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+            let log = AuditLog::new(&current_dir);
+            let entries = match &todo_id {
+                Some(todo_id) => log.history(todo_id)?,
+                None => log.read_all()?,
+            };
+            let skip = entries.len().saturating_sub(limit);
+            let entries = &entries[skip..];
+
+            match summary_format(format)? {
+                SummaryFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(entries)?);
+                }
+                SummaryFormat::Markdown => {
+                    println!("# Gate Decisions");
+                    println!();
+                    println!("| Time | TODO | Result | Quality | Critical | Waivers | Ruleset |");
+                    println!("| ---- | ---- | ------ | ------- | -------- | ------- | ------- |");
+                    for entry in entries {
                         println!(
-                            "   Created: {}",
-                            checkpoint.timestamp.format("%Y-%m-%d %H:%M:%S")
+                            "| {} | {} | {} | {:.1}% / {:.1}% | {} / {} | {} | `{}` |",
+                            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                            entry.todo_id,
+                            if entry.passed { "PASSED" } else { "FAILED" },
+                            entry.quality_score,
+                            entry.min_quality_score,
+                            entry.critical_issues,
+                            entry.max_critical_issues,
+                            entry.waivers.len(),
+                            entry.ruleset_hash.as_deref().unwrap_or("-")
                         );
-                        println!("   Files: {}", checkpoint.file_count);
-                        if let Some(desc) = checkpoint.description {
-                            println!("   Description: {}", desc);
-                        }
-                        println!();
                     }
                 }
-                OutputFormat::Json => {
-                    println!("{}", serde_json::to_string_pretty(&checkpoints)?);
-                }
-                _ => {
-                    for checkpoint in checkpoints {
+                SummaryFormat::Table | SummaryFormat::Compact => {
+                    if entries.is_empty() {
+                        println!(">> No gate decisions recorded");
+                        return Ok(());
+                    }
+                    for entry in entries {
                         println!(
-                            "{}: {} files ({})",
-                            checkpoint.name,
-                            checkpoint.file_count,
-                            checkpoint.timestamp.format("%Y-%m-%d %H:%M")
+                            "{} {} {} quality {:.1}% (min {:.1}%), critical {} (max {}), {} files, {} waivers, ruleset {}",
+                            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                            if entry.passed { "PASSED" } else { "FAILED" },
+                            entry.todo_id,
+                            entry.quality_score,
+                            entry.min_quality_score,
+                            entry.critical_issues,
+                            entry.max_critical_issues,
+                            entry.files_analyzed,
+                            entry.waivers.len(),
+                            entry.ruleset_hash.as_deref().unwrap_or("-")
                         );
                     }
                 }
             }
         }
+    }
 
-        CheckpointCommands::Show { name, format: _ } => {
-            if let Some(checkpoint) = checkpoint_manager.get_checkpoint(&name).await? {
-                println!(":: Checkpoint: {}", checkpoint.name);
-                println!(
-                    "Created: {}",
-                    checkpoint.timestamp.format("%Y-%m-%d %H:%M:%S")
-                );
-                println!("Files: {}", checkpoint.file_count);
-                if let Some(desc) = checkpoint.description {
-                    println!("Description: {}", desc);
-                }
-                // Show file list
-                let details = checkpoint_manager.get_checkpoint_files(&name).await?;
-                println!("\nFiles in checkpoint:");
-                for file_info in details {
-                    println!("  {} ({})", file_info.path.display(), file_info.file_size);
-                }
-            } else {
-                println!("❌ Checkpoint '{}' not found", name);
-            }
-        }
-
-        CheckpointCommands::Diff {
-            checkpoint,
-            paths,
-            format,
-        } => {
-            let comparison_paths = paths.unwrap_or_else(|| {
-                // Get paths from checkpoint if not provided
-                vec![std::env::current_dir().unwrap()]
-            });
-
-            let comparison = checkpoint_manager
-                .compare_files(&checkpoint, &comparison_paths)
-                .await?;
-
-            match format {
-                OutputFormat::Table => {
-                    println!("[DIFF] Changes since checkpoint '{}'", checkpoint);
-                    println!("═══════════════════════════════════");
-                    println!();
-
-                    if !comparison.new_files.is_empty() {
-                        println!("[NEW] New files ({}): ", comparison.new_files.len());
-                        for file in &comparison.new_files {
-                            println!("  + {}", file.display());
-                        }
-                        println!();
-                    }
+    Ok(())
+}
 
-                    if !comparison.changed_files.is_empty() {
-                        println!("[MOD] Modified files ({}): ", comparison.changed_files.len());
-                        for file in &comparison.changed_files {
-                            println!("  ~ {}", file.display());
-                        }
-                        println!();
-                    }
+/// Handles team baseline commands.
+async fn handle_baseline_command(
+    command: BaselineCommands,
+    checkpoint_dir: Option<&Path>,
+    read_only: bool,
+) -> Result<()> {
+    use sniff::remote_store;
 
-                    if !comparison.deleted_files.is_empty() {
-                        println!("[DEL] Deleted files ({}): ", comparison.deleted_files.len());
-                        for file in &comparison.deleted_files {
-                            println!("  - {}", file.display());
-                        }
-                        println!();
-                    }
+    if read_only && matches!(command, BaselineCommands::Pull { .. }) {
+        return Err(SniffError::read_only("Pulling the team baseline"));
+    }
 
-                    if comparison.new_files.is_empty()
-                        && comparison.changed_files.is_empty()
-                        && comparison.deleted_files.is_empty()
-                    {
-                        println!(">> No changes detected since checkpoint");
-                    }
-                }
-                OutputFormat::Json => {
-                    println!("{}", serde_json::to_string_pretty(&comparison)?);
-                }
-                _ => {
-                    println!(
-                        "Changes: +{} ~{} -{}",
-                        comparison.new_files.len(),
-                        comparison.changed_files.len(),
-                        comparison.deleted_files.len()
-                    );
-                }
-            }
+    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+    let mut resolver = sniff::project_config::ConfigResolver::new();
+    let project_dir = resolver.project_root(&current_dir)?.unwrap_or(current_dir);
+    let (BaselineCommands::Push { remote } | BaselineCommands::Pull { remote }) = &command;
+    let remote = match remote {
+        Some(remote) => remote.clone(),
+        None => {
+            let remote = resolver
+                .resolve_directory(&project_dir)?
+                .baseline_remote
+                .clone()
+                .ok_or_else(|| {
+                    SniffError::invalid_format(
+                        "baseline sync",
+                        "no remote store: pass --remote or set [baseline] remote in .sniff.toml",
+                    )
+                })?;
+            remote_store::check_config_url(&remote)?;
+            remote
         }
+    };
 
-        CheckpointCommands::Delete { name, confirm } => {
-            if !confirm {
-                println!("❌ Checkpoint deletion requires --confirm flag for safety");
-                return Ok(());
+    let store = remote_store::open(&remote)?;
+    let checkpoint_manager = open_checkpoint_manager(checkpoint_dir, Some(&project_dir))?;
+    match command {
+        BaselineCommands::Push { .. } => {
+            remote_store::push_baseline(store.as_ref(), checkpoint_manager.checkpoint_dir())?;
+            println!(">> Team baseline pushed to {remote}");
+        }
+        BaselineCommands::Pull { .. } => {
+            if remote_store::pull_baseline(store.as_ref(), &checkpoint_manager).await? {
+                println!(">> Team baseline pulled from {remote}");
+            } else {
+                println!(">> No team baseline in {remote}");
             }
-
-            checkpoint_manager.delete_checkpoint(&name).await?;
-            println!(">> Checkpoint '{}' deleted", name);
         }
     }
-
     Ok(())
 }
 
-/// Handles pattern management commands.
-async fn handle_patterns_command(command: PatternCommands) -> Result<()> {
-    // Simplified implementation - pattern management functionality is available
-    // but the full implementation needs API updates
+/// Refreshes the team baseline configured in `.sniff.toml` for the project of
+/// `path` and returns the name of its checkpoint, or `None` if the project has
+/// no team baseline. If the remote cannot be reached, the last pulled copy is used.
+async fn team_baseline(
+    path: Option<&PathBuf>,
+    checkpoint_dir: Option<&Path>,
+    read_only: bool,
+) -> Result<Option<String>> {
+    use sniff::remote_store::{self, BASELINE_CHECKPOINT};
 
-    match command {
-        PatternCommands::Init { force: _ } => {
-            println!(">> Enhanced patterns are installed in ~/.sniff/patterns/");
-            println!(">> Add custom patterns by placing YAML files in that directory");
-            println!(">> Available patterns are loaded automatically during analysis");
-        }
-        _ => {
-            println!("[INFO] Pattern management commands simplified in streamlined version");
-            println!("[TIP] Enhanced patterns are installed in ~/.sniff/patterns/");
-            println!("[TIP] Add custom patterns by placing YAML files in that directory");
-            println!("[TIP] Available patterns are loaded automatically during analysis");
+    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+    let mut resolver = sniff::project_config::ConfigResolver::new();
+    let project_dir = resolver
+        .project_root(path.unwrap_or(&current_dir))?
+        .unwrap_or(current_dir);
+    let Some(remote) = resolver
+        .resolve_directory(&project_dir)?
+        .baseline_remote
+        .clone()
+    else {
+        return Ok(None);
+    };
+    // Resolved like every other checkpoint command, so the baseline lands where
+    // the comparison reads it
+    let checkpoint_manager = open_checkpoint_manager(checkpoint_dir, Some(&project_dir))?;
+
+    if read_only {
+        report_read_only("team baseline not refreshed");
+    } else {
+        // The remote comes from the checkout's own config
+        let pulled = match remote_store::check_config_url(&remote)
+            .and_then(|()| remote_store::open(&remote))
+        {
+            Ok(store) => remote_store::pull_baseline(store.as_ref(), &checkpoint_manager).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = pulled {
+            warn!("Failed to pull the team baseline from {}: {}", remote, e);
         }
     }
 
-    Ok(())
+    if !remote_store::baseline_path(checkpoint_manager.checkpoint_dir()).is_file() {
+        warn!(
+            "No team baseline in {}; create one with `sniff baseline push`",
+            remote
+        );
+        return Ok(None);
+    }
+    info!("[INFO] Comparing against the team baseline from {}", remote);
+    Ok(Some(BASELINE_CHECKPOINT.to_string()))
 }
 
-/// Handles the verify-todo command - verifies TODO completion with sniff analysis.
-async fn handle_verify_todo_command(
-    todo_id: String,
-    files: Vec<PathBuf>,
-    min_quality_score: f64,
-    max_critical_issues: usize,
-    format: OutputFormat,
-    git_discovery: bool,
+/// Handles remote storage commands.
+async fn handle_remote_command(
+    command: RemoteCommands,
+    checkpoint_dir: Option<&Path>,
+    read_only: bool,
 ) -> Result<()> {
-    use sniff::verify_todo::{verify_todo, display_verification_result, VerificationConfig};
+    if read_only && matches!(command, RemoteCommands::Pull { .. }) {
+        return Err(SniffError::read_only("Pulling remote state"));
+    }
 
-    let config = VerificationConfig {
-        min_quality_score,
-        max_critical_issues,
-        include_test_files: false, // Exclude test files by default for quality verification
-    };
+    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+    let project_dir = sniff::project_config::ConfigResolver::new()
+        .project_root(&current_dir)?
+        .unwrap_or(current_dir);
+    let checkpoint_manager = open_checkpoint_manager(checkpoint_dir, None)?;
 
-    // Use git discovery if requested, otherwise use provided files
-    let actual_files = if git_discovery {
-        match sniff::verify_todo::discover_git_changes() {
-            Ok(git_files) => {
-                if git_files != files {
-                    println!("Git discovery found {} files vs {} reported", git_files.len(), files.len());
-                    println!("Using git-discovered files for verification");
-                }
-                git_files
-            }
-            Err(e) => {
-                eprintln!("Git discovery failed: {}, using reported files", e);
-                files
-            }
+    let (keys, summary) = match command {
+        RemoteCommands::Push { url } => {
+            let store = sniff::remote_store::open(&url)?;
+            let keys = sniff::remote_store::push(
+                store.as_ref(),
+                &project_dir,
+                checkpoint_manager.checkpoint_dir(),
+            )?;
+            let summary = format!("Pushed {} object(s) to {url}", keys.len());
+            (keys, summary)
+        }
+        RemoteCommands::Pull { url } => {
+            let store = sniff::remote_store::open(&url)?;
+            let keys = sniff::remote_store::pull(store.as_ref(), &project_dir, &checkpoint_manager)
+                .await?;
+            let summary = format!("Pulled {} object(s) from {url}", keys.len());
+            (keys, summary)
         }
-    } else {
-        files
     };
 
-    let result = verify_todo(&todo_id, &actual_files, config.clone()).await?;
+    println!("{summary}");
+    for key in keys {
+        println!("  {key}");
+    }
+    Ok(())
+}
 
-    match format {
-        OutputFormat::Json => {
-            let verification_result = serde_json::json!({
-                "todo_id": todo_id,
-                "verification_passed": result.passed,
-                "quality_score": result.quality_score,
-                "min_quality_required": config.min_quality_score,
-                "critical_issues": result.critical_issues,
-                "max_critical_allowed": config.max_critical_issues,
-                "analysis_results": result.analysis_results
-            });
-            println!("{}", serde_json::to_string_pretty(&verification_result)?);
+/// Handles configuration commands.
+fn handle_config_command(command: ConfigCommands) -> Result<()> {
+    use sniff::project_config::{self, CONFIG_FILE_NAME};
+
+    match command {
+        ConfigCommands::Schema => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&project_config::json_schema())?
+            );
         }
-        _ => {
-            display_verification_result(&todo_id, &result, &config);
+        ConfigCommands::Validate { path, format } => {
+            let path = path.unwrap_or_else(|| PathBuf::from(CONFIG_FILE_NAME));
+            let content =
+                fs::read_to_string(&path).map_err(|e| SniffError::file_system(&path, e))?;
+            let issues = project_config::validate(&content);
+
+            match summary_format(format)? {
+                SummaryFormat::Json => {
+                    println!("{}", serde_json::to_string_pretty(&issues)?);
+                }
+                SummaryFormat::Markdown => {
+                    println!("# Config Validation: `{}`", path.display());
+                    println!();
+                    if issues.is_empty() {
+                        println!("No issues found.");
+                    } else {
+                        println!("| Line | Column | Key | Issue |");
+                        println!("| ---- | ------ | --- | ----- |");
+                        for issue in &issues {
+                            println!(
+                                "| {} | {} | `{}` | {} |",
+                                issue.line, issue.column, issue.key, issue.message
+                            );
+                        }
+                    }
+                }
+                SummaryFormat::Table | SummaryFormat::Compact => {
+                    println!(":: Config Validation");
+                    println!("═══════════════════");
+                    if issues.is_empty() {
+                        println!(">> {} is valid", path.display());
+                    }
+                    for issue in &issues {
+                        let key = if issue.key.is_empty() {
+                            String::new()
+                        } else {
+                            format!("{}: ", issue.key)
+                        };
+                        println!(
+                            "!! {}:{}:{} {}{}",
+                            path.display(),
+                            issue.line,
+                            issue.column,
+                            key,
+                            issue.message
+                        );
+                    }
+                }
+            }
+
+            if !issues.is_empty() {
+                return Err(SniffError::invalid_format(
+                    path.display().to_string(),
+                    format!("{} configuration issues found", issues.len()),
+                ));
+            }
         }
     }
 
-    if result.passed {
-        Ok(())
-    } else {
-        Err(SniffError::analysis_error(format!(
-            "TODO '{}' failed verification: quality {:.1}% < {:.1}%, critical issues {} > {}",
-            todo_id, result.quality_score, config.min_quality_score, 
-            result.critical_issues, config.max_critical_issues
-        )))
-    }
+    Ok(())
+}
+
+/// Reports a feature that was disabled by read-only mode.
+fn report_read_only(message: &str) {
+    eprintln!("[READ-ONLY] {message}");
 }
 
 /// Ensures the .sniff directory exists and returns its path.
@@ -899,21 +5453,12 @@ fn install_default_playbooks(patterns_dir: &PathBuf) -> Result<()> {
     fs::create_dir_all(patterns_dir)
         .map_err(|e| SniffError::file_system(patterns_dir, e))?;
     
-    // Get the embedded playbooks from the binary
-    let rust_patterns = include_str!("../playbooks/rust-patterns.yaml");
-    let python_patterns = include_str!("../playbooks/python-patterns.yaml");
-    let typescript_patterns = include_str!("../playbooks/typescript-patterns.yaml");
-    
-    // Write playbooks to .sniff/patterns/
-    fs::write(patterns_dir.join("rust-patterns.yaml"), rust_patterns)
-        .map_err(|e| SniffError::file_system(patterns_dir, e))?;
-    
-    fs::write(patterns_dir.join("python-patterns.yaml"), python_patterns)
-        .map_err(|e| SniffError::file_system(patterns_dir, e))?;
-    
-    fs::write(patterns_dir.join("typescript-patterns.yaml"), typescript_patterns)
-        .map_err(|e| SniffError::file_system(patterns_dir, e))?;
-    
+    // Write the playbooks embedded in the binary to .sniff/patterns/
+    for (language, playbook) in sniff::playbook::BUNDLED_PLAYBOOKS {
+        let path = patterns_dir.join(format!("{}-patterns.yaml", language.name()));
+        fs::write(&path, playbook).map_err(|e| SniffError::file_system(&path, e))?;
+    }
+
     info!("Installed default playbooks to {}", patterns_dir.display());
     
     Ok(())
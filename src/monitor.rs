@@ -0,0 +1,799 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Live terminal monitor for an agent session.
+//!
+//! Tails a session transcript with a file watcher and shows, while the agent
+//! works, its tool calls, the files it touched with running detection counts,
+//! and alerts for policy violations, risky commands and prompt-injection
+//! markers. When a written file changes, the written files importing it are
+//! re-analyzed with it.
+//!
+//! Agents often rewrite many files in a burst, so watcher events are debounced
+//! and changed files are analyzed in batches of bounded size, each summarized
+//! as a single delta.
+
+use crate::analysis::MisalignmentAnalyzer;
+use crate::bash_risk::audit_session;
+use crate::dependencies::DependencyMap;
+use crate::error::{Result, SniffError};
+use crate::file_access::file_accesses;
+use crate::injection::scan_session;
+use crate::playbook::Severity;
+use crate::session::{Session, SessionTail};
+use crate::session_policy::SessionPolicy;
+use chrono::{DateTime, Utc};
+use notify::{RecursiveMode, Watcher};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, Paragraph, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Maximum number of tool calls kept in the live view.
+const MAX_TOOL_CALLS: usize = 200;
+
+/// How long to wait for key presses between watcher checks.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Multiple of the debounce delay after which a steady stream of events is
+/// processed anyway.
+const MAX_DEBOUNCE_FACTOR: u32 = 10;
+
+/// How watcher events are coalesced into analysis batches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchBatching {
+    /// Quiet time after the last event before the session is re-read.
+    pub debounce: Duration,
+    /// Largest number of files analyzed per batch; the rest wait for the next one.
+    pub max_batch: usize,
+}
+
+impl Default for WatchBatching {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(500),
+            max_batch: 20,
+        }
+    }
+}
+
+/// Consolidated outcome of one analysis batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchDelta {
+    /// Files analyzed in the batch.
+    pub files: usize,
+    /// Detections in those files before the batch.
+    pub detections_before: usize,
+    /// Detections in those files after the batch.
+    pub detections_after: usize,
+    /// Changed files left for the next batches.
+    pub pending: usize,
+}
+
+impl BatchDelta {
+    /// Signed change in detections, e.g. `+3`, `-1` or `0`.
+    #[must_use]
+    pub fn change(&self) -> String {
+        match self.detections_after.cmp(&self.detections_before) {
+            std::cmp::Ordering::Greater => {
+                format!("+{}", self.detections_after - self.detections_before)
+            }
+            std::cmp::Ordering::Less => {
+                format!("-{}", self.detections_before - self.detections_after)
+            }
+            std::cmp::Ordering::Equal => "0".to_string(),
+        }
+    }
+}
+
+/// A tool call as shown in the monitor.
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    /// 1-based position of the tool use within the session.
+    pub step: usize,
+    /// When the tool use was issued.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Tool name.
+    pub tool: String,
+    /// One-line summary of the input (command, path, URL or query).
+    pub summary: String,
+}
+
+/// Activity on one file.
+#[derive(Debug, Clone, Default)]
+pub struct FileActivity {
+    /// Number of reads.
+    pub reads: usize,
+    /// Number of writes and edits.
+    pub writes: usize,
+    /// Step of the most recent write or edit.
+    pub last_write_step: Option<usize>,
+    /// Detections in the file as of the last analysis.
+    pub detections: Option<usize>,
+    /// Critical detections in the file as of the last analysis.
+    pub critical: usize,
+}
+
+/// An alert raised while monitoring.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    /// When the offending tool use was issued.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Severity of the alert.
+    pub severity: Severity,
+    /// What raised the alert: `policy`, `command` or `injection`.
+    pub source: String,
+    /// Alert text.
+    pub message: String,
+}
+
+/// Live state of a monitored session.
+#[derive(Debug, Default)]
+pub struct MonitorState {
+    /// Session being monitored.
+    pub session_id: String,
+    /// Models seen in the session.
+    pub models: Vec<String>,
+    /// Most recent tool calls, oldest first.
+    pub tool_calls: Vec<ToolCall>,
+    /// Total number of tool calls in the session.
+    pub total_tool_calls: usize,
+    /// Files touched, keyed by path relative to the project root where possible.
+    pub files: BTreeMap<String, FileActivity>,
+    /// Alerts raised so far, oldest first.
+    pub alerts: Vec<Alert>,
+    /// Outcome of the most recent analysis batch.
+    pub last_batch: Option<BatchDelta>,
+    /// Malformed transcript lines skipped so far, oldest first. They are
+    /// shown in the view, as logging them would corrupt the screen.
+    pub warnings: Vec<String>,
+    seen_alerts: HashSet<String>,
+    absolute_paths: HashMap<String, PathBuf>,
+    analyzed_steps: HashMap<String, usize>,
+    /// Working directory of the session, whose source files are indexed for
+    /// the dependents of a change.
+    project_dir: Option<PathBuf>,
+    /// Imports between the project's source files, to re-analyze the
+    /// dependents of a change.
+    dependencies: DependencyMap,
+    /// Whether `dependencies` covers the whole project yet.
+    indexed: bool,
+    /// Files waiting for analysis, in order.
+    pending: Vec<String>,
+}
+
+impl MonitorState {
+    /// Updates tool calls, file activity and alerts from the current transcript.
+    pub fn refresh(&mut self, session: &Session, policy: Option<&SessionPolicy>) {
+        if self.session_id != session.id {
+            *self = Self {
+                session_id: session.id.clone(),
+                ..Self::default()
+            };
+        }
+        self.models = session.models();
+        self.project_dir = session.project_dir();
+
+        let tool_uses = session.tool_uses();
+        self.total_tool_calls = tool_uses.len();
+        self.tool_calls = tool_uses
+            .iter()
+            .skip(tool_uses.len().saturating_sub(MAX_TOOL_CALLS))
+            .map(|tool_use| ToolCall {
+                step: tool_use.step,
+                timestamp: tool_use.timestamp,
+                tool: tool_use.name.to_string(),
+                summary: summarize_input(tool_use.input),
+            })
+            .collect();
+
+        for activity in self.files.values_mut() {
+            activity.reads = 0;
+            activity.writes = 0;
+        }
+        for access in file_accesses(session) {
+            let key = access
+                .relative_path
+                .clone()
+                .unwrap_or_else(|| access.path.clone());
+            let activity = self.files.entry(key.clone()).or_default();
+            match access.operation.as_str() {
+                "read" => activity.reads += 1,
+                "write" | "edit" => {
+                    activity.writes += 1;
+                    activity.last_write_step = Some(access.step);
+                    self.absolute_paths.insert(key, PathBuf::from(&access.path));
+                }
+                _ => {}
+            }
+        }
+
+        let mut alerts = Vec::new();
+        if let Some(policy) = policy {
+            alerts.extend(policy.evaluate(session).into_iter().map(|violation| {
+                (
+                    format!("policy:{}:{:?}", violation.rule_id, violation.step),
+                    Alert {
+                        timestamp: violation.timestamp,
+                        severity: violation.severity,
+                        source: "policy".to_string(),
+                        message: format!("[{}] {}", violation.rule_id, violation.message),
+                    },
+                )
+            }));
+        }
+        alerts.extend(audit_session(session).into_iter().map(|risky| {
+            (
+                format!("command:{}:{}", risky.category, risky.step),
+                Alert {
+                    timestamp: risky.timestamp,
+                    severity: risky.severity,
+                    source: "command".to_string(),
+                    message: format!("{}: {}", risky.category, risky.command),
+                },
+            )
+        }));
+        alerts.extend(scan_session(session).into_iter().map(|finding| {
+            (
+                format!("injection:{}:{}", finding.kind, finding.tool_use_id),
+                Alert {
+                    timestamp: finding.timestamp,
+                    severity: finding.severity,
+                    source: "injection".to_string(),
+                    message: format!(
+                        "{} in {} result: {}",
+                        finding.kind,
+                        finding.tool_name.as_deref().unwrap_or("tool"),
+                        finding.excerpt
+                    ),
+                },
+            )
+        }));
+
+        for (key, alert) in alerts {
+            if self.seen_alerts.insert(key) {
+                self.alerts.push(alert);
+            }
+        }
+    }
+
+    /// Re-analyzes up to `max_batch` of the files written since their last
+    /// analysis and the project files importing them, and records the batch
+    /// delta. Returns false if there was nothing to analyze.
+    pub fn analyze_changed_files(
+        &mut self,
+        analyzer: &mut MisalignmentAnalyzer,
+        max_batch: usize,
+    ) -> bool {
+        let batch = self.next_batch(max_batch);
+        if batch.is_empty() {
+            return false;
+        }
+
+        let mut delta = BatchDelta {
+            files: batch.len(),
+            pending: self.pending.len(),
+            ..BatchDelta::default()
+        };
+        for key in batch {
+            let Some(activity) = self.files.get_mut(&key) else {
+                continue;
+            };
+            delta.detections_before += activity.detections.unwrap_or(0);
+            if let Some(path) = self.absolute_paths.get(&key).filter(|path| path.is_file()) {
+                match analyzer.analyze_file(path) {
+                    Ok(detections) => {
+                        activity.detections = Some(detections.len());
+                        activity.critical = detections
+                            .iter()
+                            .filter(|d| d.severity == Severity::Critical)
+                            .count();
+                    }
+                    Err(e) => debug!("Skipping analysis of {}: {}", path.display(), e),
+                }
+            }
+            delta.detections_after += activity.detections.unwrap_or(0);
+        }
+        self.last_batch = Some(delta);
+        true
+    }
+
+    /// Queues the files changed since the last call and returns up to
+    /// `max_batch` of the queued files.
+    fn next_batch(&mut self, max_batch: usize) -> Vec<String> {
+        for key in self.files_to_analyze() {
+            if !self.pending.contains(&key) {
+                self.pending.push(key);
+            }
+        }
+        let size = self.pending.len().min(max_batch.max(1));
+        self.pending.drain(..size).collect()
+    }
+
+    /// Returns the files written since their last analysis, followed by the
+    /// project files importing them, and records the imports of the former.
+    ///
+    /// The imports of the whole project are indexed the first time, so files
+    /// the agent never touched are found as dependents too.
+    fn files_to_analyze(&mut self) -> Vec<String> {
+        if !self.indexed {
+            if let Some(project_dir) = &self.project_dir {
+                self.dependencies.add_directory(project_dir);
+                self.indexed = true;
+            }
+        }
+
+        let mut changed = Vec::new();
+        for (key, activity) in &self.files {
+            let Some(step) = activity.last_write_step else {
+                continue;
+            };
+            if self.analyzed_steps.get(key) != Some(&step) {
+                self.analyzed_steps.insert(key.clone(), step);
+                changed.push(key.clone());
+            }
+        }
+
+        for key in &changed {
+            let Some(path) = self.absolute_paths.get(key) else {
+                continue;
+            };
+            match std::fs::read_to_string(path) {
+                Ok(content) => self.dependencies.update(path, &content),
+                Err(_) => self.dependencies.remove(path),
+            }
+        }
+
+        let keys_by_path: HashMap<&PathBuf, &String> = self
+            .absolute_paths
+            .iter()
+            .map(|(key, path)| (path, key))
+            .collect();
+        let mut dependents = BTreeSet::new();
+        for key in &changed {
+            let Some(path) = self.absolute_paths.get(key) else {
+                continue;
+            };
+            for dependent in self.dependencies.dependents(path) {
+                // Keyed like file accesses: relative to the project where possible
+                let dependent_key = keys_by_path.get(&dependent).map_or_else(
+                    || {
+                        self.project_dir
+                            .as_deref()
+                            .and_then(|root| dependent.strip_prefix(root).ok())
+                            .unwrap_or(&dependent)
+                            .to_string_lossy()
+                            .to_string()
+                    },
+                    |&key| key.clone(),
+                );
+                if !changed.contains(&dependent_key) {
+                    dependents.insert((dependent_key, dependent));
+                }
+            }
+        }
+        for (key, path) in dependents {
+            self.files.entry(key.clone()).or_default();
+            self.absolute_paths.entry(key.clone()).or_insert(path);
+            changed.push(key);
+        }
+        changed
+    }
+
+    /// Returns the total detections across analyzed files.
+    #[must_use]
+    pub fn total_detections(&self) -> usize {
+        self.files.values().filter_map(|f| f.detections).sum()
+    }
+}
+
+/// Returns a one-line summary of a tool input.
+fn summarize_input(input: &Value) -> String {
+    let summary = [
+        "command",
+        "file_path",
+        "notebook_path",
+        "url",
+        "query",
+        "pattern",
+        "path",
+    ]
+    .iter()
+    .find_map(|key| input.get(key).and_then(Value::as_str))
+    .unwrap_or_default();
+    summary.lines().next().unwrap_or_default().to_string()
+}
+
+/// Returns the most recently modified session under the projects directory.
+pub fn active_session(projects_dir: &Path) -> Result<PathBuf> {
+    Session::discover(projects_dir)?
+        .into_iter()
+        .max_by_key(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .ok_or_else(|| {
+            SniffError::invalid_session(format!(
+                "No sessions found under {}",
+                projects_dir.display()
+            ))
+        })
+}
+
+/// Runs the monitor until the user quits.
+///
+/// When `follow` is set, the monitor switches to any newer session started in
+/// the same project directory.
+pub fn run(
+    session_path: PathBuf,
+    follow: bool,
+    analyzer: &mut MisalignmentAnalyzer,
+    policy: Option<&SessionPolicy>,
+    batching: WatchBatching,
+) -> Result<()> {
+    let watch_dir = session_path
+        .parent()
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(SniffError::file_watcher)?;
+    watcher
+        .watch(&watch_dir, RecursiveMode::NonRecursive)
+        .map_err(SniffError::file_watcher)?;
+
+    let mut terminal = ratatui::init();
+    let result = monitor_loop(
+        &mut terminal,
+        session_path,
+        follow,
+        &rx,
+        analyzer,
+        policy,
+        batching,
+    );
+    ratatui::restore();
+    result
+}
+
+fn monitor_loop(
+    terminal: &mut DefaultTerminal,
+    mut session_path: PathBuf,
+    follow: bool,
+    events: &mpsc::Receiver<notify::Result<notify::Event>>,
+    analyzer: &mut MisalignmentAnalyzer,
+    policy: Option<&SessionPolicy>,
+    batching: WatchBatching,
+) -> Result<()> {
+    let mut state = MonitorState::default();
+    let mut tail = SessionTail::new(&session_path);
+    // First and last event since the session was last read; the session is
+    // read right away at startup
+    let start = Instant::now()
+        .checked_sub(batching.debounce)
+        .unwrap_or_else(Instant::now);
+    let mut dirty: Option<(Instant, Instant)> = Some((start, start));
+    let poll_interval = POLL_INTERVAL.min(batching.debounce.max(Duration::from_millis(10)));
+    // Saturates, as any `--debounce-ms` is accepted
+    let max_wait = batching.debounce.saturating_mul(MAX_DEBOUNCE_FACTOR);
+
+    loop {
+        if let Some((first, last)) = dirty {
+            if last.elapsed() >= batching.debounce || first.elapsed() >= max_wait {
+                if tail.session().path != session_path {
+                    tail = SessionTail::new(&session_path);
+                }
+                let warnings = tail.read_appended()?;
+                // A new session resets the state, so its warnings go in after
+                state.refresh(tail.session(), policy);
+                state.warnings.extend(warnings);
+                dirty = None;
+            }
+        }
+        // Files left over from a large batch are analyzed between events
+        state.analyze_changed_files(analyzer, batching.max_batch);
+        terminal.draw(|frame| draw(frame, &state))?;
+
+        if event::poll(poll_interval)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press
+                    && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+                {
+                    return Ok(());
+                }
+            }
+        }
+
+        for event in events.try_iter().flatten() {
+            for path in &event.paths {
+                let changed = if path == &session_path {
+                    true
+                } else if follow
+                    && event.kind.is_create()
+                    && path.extension().is_some_and(|ext| ext == "jsonl")
+                {
+                    session_path.clone_from(path);
+                    true
+                } else {
+                    false
+                };
+                if changed {
+                    let now = Instant::now();
+                    dirty = Some((dirty.map_or(now, |(first, _)| first), now));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the display color for a severity.
+fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Critical => Color::LightRed,
+        Severity::High => Color::Red,
+        Severity::Medium => Color::Yellow,
+        Severity::Low => Color::Green,
+        Severity::Info => Color::Blue,
+    }
+}
+
+fn draw(frame: &mut Frame, state: &MonitorState) {
+    let [header, body, alerts, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(8),
+        Constraint::Length(10),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [tool_calls, files] =
+        Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(body);
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let summary = Line::from(vec![
+        Span::styled("Session ", bold),
+        Span::raw(format!("{}  ", state.session_id)),
+        Span::styled("Model ", bold),
+        Span::raw(format!("{}  ", state.models.join(", "))),
+        Span::styled("Tool calls ", bold),
+        Span::raw(format!("{}  ", state.total_tool_calls)),
+        Span::styled("Files ", bold),
+        Span::raw(format!("{}  ", state.files.len())),
+        Span::styled("Detections ", bold),
+        Span::raw(format!("{}  ", state.total_detections())),
+        Span::styled("Alerts ", bold),
+        Span::styled(
+            state.alerts.len().to_string(),
+            if state.alerts.is_empty() {
+                Style::default()
+            } else {
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+            },
+        ),
+    ]);
+    frame.render_widget(
+        Paragraph::new(summary).block(Block::bordered().title(":: Sniff Session Monitor")),
+        header,
+    );
+
+    let visible = usize::from(tool_calls.height.saturating_sub(2));
+    let calls: Vec<ListItem> = state
+        .tool_calls
+        .iter()
+        .skip(state.tool_calls.len().saturating_sub(visible))
+        .map(|call| {
+            let time = call
+                .timestamp
+                .map_or_else(String::new, |t| t.format("%H:%M:%S ").to_string());
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:>4} ", call.step),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(time),
+                Span::styled(
+                    format!("{:<10} ", call.tool),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw(call.summary.clone()),
+            ]))
+        })
+        .collect();
+    frame.render_widget(
+        List::new(calls).block(Block::bordered().title("Tool Calls")),
+        tool_calls,
+    );
+
+    let rows: Vec<Row> = state
+        .files
+        .iter()
+        .map(|(path, activity)| {
+            let detections = activity
+                .detections
+                .map_or_else(|| "-".to_string(), |count| count.to_string());
+            let style = if activity.critical > 0 {
+                Style::default().fg(Color::LightRed)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                path.clone(),
+                activity.reads.to_string(),
+                activity.writes.to_string(),
+                detections,
+                activity.critical.to_string(),
+            ])
+            .style(style)
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(4),
+            Constraint::Length(4),
+            Constraint::Length(6),
+            Constraint::Length(5),
+        ],
+    )
+    .header(Row::new(vec!["File", "R", "W", "Issues", "Crit"]).style(bold))
+    .block(Block::bordered().title("Files Touched"));
+    frame.render_widget(table, files);
+
+    let visible = usize::from(alerts.height.saturating_sub(2));
+    let items: Vec<ListItem> = state
+        .alerts
+        .iter()
+        .skip(state.alerts.len().saturating_sub(visible))
+        .map(|alert| {
+            let time = alert
+                .timestamp
+                .map_or_else(String::new, |t| t.format("%H:%M:%S ").to_string());
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<8} ", alert.severity.name()),
+                    Style::default().fg(severity_color(alert.severity)),
+                ),
+                Span::raw(time),
+                Span::styled(
+                    format!("{:<9} ", alert.source),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(alert.message.clone()),
+            ]))
+        })
+        .collect();
+    frame.render_widget(
+        List::new(items).block(Block::bordered().title("Alerts")),
+        alerts,
+    );
+
+    let footer_text = match state.last_batch {
+        Some(batch) => format!(
+            "Last batch: {} file(s), issues {} -> {} ({}), {} pending  |  q/Esc quit",
+            batch.files,
+            batch.detections_before,
+            batch.detections_after,
+            batch.change(),
+            batch.pending
+        ),
+        None => "q/Esc quit".to_string(),
+    };
+    let mut footer_line = Line::styled(footer_text, Style::default().fg(Color::DarkGray));
+    if let Some(warning) = state.warnings.last() {
+        footer_line.push_span(Span::styled(
+            format!("  |  {} warning(s), last: {warning}", state.warnings.len()),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    frame.render_widget(Paragraph::new(footer_line), footer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::test_support;
+
+    #[test]
+    fn test_monitor_state_refresh() {
+        let line = serde_json::json!({
+            "type": "assistant",
+            "cwd": "/work/project",
+            "message": {"role": "assistant", "model": "claude-sonnet-4", "content": [
+                {"type": "tool_use", "id": "t1", "name": "Read", "input": {"file_path": "src/lib.rs"}},
+                {"type": "tool_use", "id": "t2", "name": "Edit",
+                 "input": {"file_path": "/work/project/src/lib.rs", "old_string": "a", "new_string": "b"}},
+                {"type": "tool_use", "id": "t3", "name": "Bash", "input": {"command": "chmod 777 run.sh"}}
+            ]}
+        });
+        let session = test_support::session(&[line]);
+
+        let mut state = MonitorState::default();
+        state.refresh(&session, None);
+        state.refresh(&session, None);
+
+        assert_eq!(state.total_tool_calls, 3);
+        assert_eq!(state.tool_calls[2].summary, "chmod 777 run.sh");
+        let activity = &state.files["src/lib.rs"];
+        assert_eq!((activity.reads, activity.writes), (1, 1));
+        // Alerts are raised once, not on every refresh
+        assert_eq!(state.alerts.len(), 1);
+        assert_eq!(state.alerts[0].source, "command");
+    }
+
+    #[test]
+    fn test_dependents_of_changed_files_are_reanalyzed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let models = temp_dir.path().join("models.py");
+        let views = temp_dir.path().join("views.py");
+        std::fs::write(&models, "class User:\n    pass\n").unwrap();
+        std::fs::write(&views, "from models import User\n").unwrap();
+        // Imports models.py but is never touched by the agent
+        let admin = temp_dir.path().join("admin.py");
+        std::fs::write(&admin, "import models\n").unwrap();
+
+        let mut state = MonitorState {
+            project_dir: Some(temp_dir.path().to_path_buf()),
+            ..MonitorState::default()
+        };
+        for (key, path, step) in [("models.py", &models, 1), ("views.py", &views, 2)] {
+            state.files.insert(
+                key.to_string(),
+                FileActivity {
+                    writes: 1,
+                    last_write_step: Some(step),
+                    ..FileActivity::default()
+                },
+            );
+            state.absolute_paths.insert(key.to_string(), path.clone());
+        }
+        assert_eq!(
+            state.files_to_analyze(),
+            vec!["models.py", "views.py", "admin.py"]
+        );
+        assert_eq!(state.absolute_paths["admin.py"], admin);
+        assert!(state.files_to_analyze().is_empty());
+
+        // A new write to models.py brings its dependents along
+        state.files.get_mut("models.py").unwrap().last_write_step = Some(3);
+        assert_eq!(
+            state.files_to_analyze(),
+            vec!["models.py", "admin.py", "views.py"]
+        );
+
+        // views.py does not depend on anything written since
+        state.files.get_mut("views.py").unwrap().last_write_step = Some(4);
+        assert_eq!(state.files_to_analyze(), vec!["views.py"]);
+    }
+
+    #[test]
+    fn test_changed_files_are_analyzed_in_batches() {
+        let mut state = MonitorState::default();
+        for (step, key) in ["a.rs", "b.rs", "c.rs"].into_iter().enumerate() {
+            state.files.insert(
+                key.to_string(),
+                FileActivity {
+                    writes: 1,
+                    last_write_step: Some(step),
+                    ..FileActivity::default()
+                },
+            );
+        }
+        assert_eq!(state.next_batch(2), vec!["a.rs", "b.rs"]);
+
+        // A rewrite of a queued file does not queue it twice
+        state.files.get_mut("c.rs").unwrap().last_write_step = Some(7);
+        state.files.get_mut("a.rs").unwrap().last_write_step = Some(8);
+        assert_eq!(state.next_batch(2), vec!["c.rs", "a.rs"]);
+        assert!(state.next_batch(2).is_empty());
+
+        let delta = BatchDelta {
+            detections_before: 5,
+            detections_after: 3,
+            ..BatchDelta::default()
+        };
+        assert_eq!(delta.change(), "-2");
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Process-wide switch for network access.
+//!
+//! Air-gapped environments need analysis runs that provably make no network
+//! requests. After [`disable`] (the `--offline` flag), every code path that
+//! reaches the network calls [`check`] first and fails instead: pattern pack
+//! downloads and git clones, and remote stores other than `file://`. Local
+//! registries and stores keep working, and the run metadata of the results
+//! records `network: disabled`.
+
+use crate::error::{Result, SniffError};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether a run had network access.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkStatus {
+    /// Network features were available.
+    #[default]
+    Enabled,
+    /// Network features were disabled with `--offline`.
+    Disabled,
+}
+
+impl NetworkStatus {
+    /// Gets the string representation of the status.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Enabled => "enabled",
+            Self::Disabled => "disabled",
+        }
+    }
+}
+
+/// Disables network access for the rest of the process. There is no way to
+/// enable it again.
+pub fn disable() {
+    DISABLED.store(true, Ordering::SeqCst);
+}
+
+/// Returns the current network status.
+#[must_use]
+pub fn status() -> NetworkStatus {
+    if DISABLED.load(Ordering::SeqCst) {
+        NetworkStatus::Disabled
+    } else {
+        NetworkStatus::Enabled
+    }
+}
+
+/// Refuses `operation` if network access is disabled.
+///
+/// # Errors
+///
+/// Returns an offline error naming the operation if network access is disabled.
+pub fn check(operation: &str) -> Result<()> {
+    match status() {
+        NetworkStatus::Enabled => Ok(()),
+        NetworkStatus::Disabled => Err(SniffError::offline(operation)),
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Organization-wide aggregation of analysis results.
+//!
+//! Combines result sets from many repositories (and many runs of each) into a
+//! single dataset with per-repository scores, trends and the most frequently
+//! failing rules, suitable for feeding a fleet-wide dashboard.
+
+use crate::standalone::AnalysisResults;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Aggregated report across repositories.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrgReport {
+    /// When the report was generated.
+    pub generated_at: DateTime<Utc>,
+    /// Number of repositories in the report.
+    pub total_repositories: usize,
+    /// Average of the latest quality score of each repository.
+    pub average_quality_score: f64,
+    /// Critical issues across the latest run of each repository.
+    pub critical_issues: usize,
+    /// Per-repository summaries, sorted by quality score (worst first).
+    pub repositories: Vec<RepositorySummary>,
+    /// Rules with the most detections across the latest runs.
+    pub top_failing_rules: Vec<RuleSummary>,
+}
+
+/// Summary of the latest run of a single repository.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositorySummary {
+    /// Repository name.
+    pub repository: String,
+    /// When the latest run happened, if known.
+    pub last_run: Option<DateTime<Utc>>,
+    /// Number of runs seen for this repository.
+    pub runs: usize,
+    /// Files analyzed in the latest run.
+    pub files_analyzed: usize,
+    /// Detections in the latest run.
+    pub total_detections: usize,
+    /// Critical issues in the latest run.
+    pub critical_issues: usize,
+    /// Quality score of the latest run.
+    pub quality_score: f64,
+    /// Change in quality score since the previous run, if there is one.
+    pub quality_trend: Option<f64>,
+}
+
+/// Detection counts for a single rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSummary {
+    /// Rule identifier.
+    pub rule_id: String,
+    /// Human-readable rule name.
+    pub rule_name: String,
+    /// Number of detections.
+    pub detections: usize,
+    /// Number of repositories with at least one detection.
+    pub repositories: usize,
+}
+
+impl OrgReport {
+    /// Builds a report from result sets.
+    ///
+    /// Each input is paired with a fallback repository name, used when the
+    /// results carry no run metadata (e.g. results from older versions).
+    #[must_use]
+    pub fn build(inputs: Vec<(String, AnalysisResults)>, top_rules: usize) -> Self {
+        // Group runs by repository, oldest first
+        let mut runs_by_repo: BTreeMap<String, Vec<AnalysisResults>> = BTreeMap::new();
+        for (fallback_name, results) in inputs {
+            let repository = results
+                .run
+                .as_ref()
+                .map_or(fallback_name, |run| run.repository.clone());
+            runs_by_repo.entry(repository).or_default().push(results);
+        }
+        for runs in runs_by_repo.values_mut() {
+            runs.sort_by_key(|results| results.run.as_ref().map(|run| run.timestamp));
+        }
+
+        let mut repositories = Vec::new();
+        let mut rule_counts: HashMap<String, (String, usize, HashSet<String>)> = HashMap::new();
+
+        for (repository, runs) in &runs_by_repo {
+            let Some(latest) = runs.last() else {
+                continue;
+            };
+            let previous = runs.len().checked_sub(2).map(|index| &runs[index]);
+
+            for detection in latest.file_results.iter().flat_map(|f| &f.detections) {
+                let entry = rule_counts
+                    .entry(detection.rule_id.clone())
+                    .or_insert_with(|| (detection.rule_name.clone(), 0, HashSet::new()));
+                entry.1 += 1;
+                entry.2.insert(repository.clone());
+            }
+
+            repositories.push(RepositorySummary {
+                repository: repository.clone(),
+                last_run: latest.run.as_ref().map(|run| run.timestamp),
+                runs: runs.len(),
+                files_analyzed: latest.total_files,
+                total_detections: latest.total_detections,
+                critical_issues: latest.critical_issues,
+                quality_score: latest.average_quality_score,
+                quality_trend: previous
+                    .map(|previous| latest.average_quality_score - previous.average_quality_score),
+            });
+        }
+
+        repositories.sort_by(|a, b| {
+            a.quality_score
+                .total_cmp(&b.quality_score)
+                .then_with(|| a.repository.cmp(&b.repository))
+        });
+
+        let mut top_failing_rules: Vec<RuleSummary> = rule_counts
+            .into_iter()
+            .map(|(rule_id, (rule_name, detections, repos))| RuleSummary {
+                rule_id,
+                rule_name,
+                detections,
+                repositories: repos.len(),
+            })
+            .collect();
+        top_failing_rules.sort_by(|a, b| {
+            b.detections
+                .cmp(&a.detections)
+                .then_with(|| a.rule_id.cmp(&b.rule_id))
+        });
+        top_failing_rules.truncate(top_rules);
+
+        let average_quality_score = if repositories.is_empty() {
+            100.0
+        } else {
+            repositories.iter().map(|r| r.quality_score).sum::<f64>() / repositories.len() as f64
+        };
+
+        Self {
+            generated_at: Utc::now(),
+            total_repositories: repositories.len(),
+            average_quality_score,
+            critical_issues: repositories.iter().map(|r| r.critical_issues).sum(),
+            repositories,
+            top_failing_rules,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::standalone::RunMetadata;
+    use std::path::Path;
+
+    fn results(repository: &str, quality_score: f64, minutes_ago: i64) -> AnalysisResults {
+        let mut results = AnalysisResults::from_file_results(Vec::new());
+        results.average_quality_score = quality_score;
+        let mut run = RunMetadata::capture(Path::new(repository), "hash".to_string());
+        run.timestamp = Utc::now() - chrono::Duration::minutes(minutes_ago);
+        results.run = Some(run);
+        results
+    }
+
+    #[test]
+    fn test_org_report_uses_latest_run_and_trend() {
+        let report = OrgReport::build(
+            vec![
+                ("a.json".to_string(), results("api", 90.0, 0)),
+                ("b.json".to_string(), results("api", 80.0, 60)),
+                ("c.json".to_string(), results("web", 70.0, 0)),
+            ],
+            10,
+        );
+
+        assert_eq!(report.total_repositories, 2);
+        // Worst repository first
+        assert_eq!(report.repositories[0].repository, "web");
+        assert_eq!(report.repositories[0].quality_trend, None);
+
+        let api = &report.repositories[1];
+        assert_eq!(api.runs, 2);
+        assert!((api.quality_score - 90.0).abs() < f64::EPSILON);
+        assert!((api.quality_trend.unwrap() - 10.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_org_report_falls_back_to_input_name() {
+        let mut legacy = AnalysisResults::from_file_results(Vec::new());
+        legacy.run = None;
+
+        let report = OrgReport::build(vec![("legacy-repo".to_string(), legacy)], 10);
+        assert_eq!(report.repositories[0].repository, "legacy-repo");
+    }
+}
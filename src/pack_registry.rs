@@ -0,0 +1,942 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Installation of versioned playbook packs into `~/.sniff/patterns/`.
+//!
+//! A pack is a playbook YAML file, or a git repository of them. Packs are
+//! installed by name from a registry index, from an HTTPS URL, or from a git
+//! repository. Every install is recorded in `packs.lock` in the patterns
+//! directory with the pack's version, source and SHA-256 checksum, and
+//! installing a locked version again verifies the checksum. Sharing the lockfile
+//! gives a team the same rules with `sniff patterns install --locked`.
+//!
+//! A single-file pack's checksum is the SHA-256 of the file. For packs of
+//! several files it is the SHA-256 of their `sha256sum` listing: one
+//! `<sha256>  <file name>` line per file, sorted by file name.
+//!
+//! Every file of a pack must also be signed by a trusted minisign key (see
+//! [`crate::pack_signing`]), unless the installer allows unsigned packs.
+
+use crate::error::{Result, SniffError};
+use crate::network;
+use crate::pack_signing::{self, PublicKey, SIGNATURE_EXTENSION};
+use crate::playbook::{Playbook, PlaybookManager};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// File name of the lockfile in the patterns directory.
+pub const LOCKFILE_NAME: &str = "packs.lock";
+
+/// Largest file accepted from a download, in bytes.
+const MAX_DOWNLOAD_BYTES: u64 = 10 << 20;
+
+/// Where a pack comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackSource {
+    /// A pack listed in a registry index, optionally at a specific version.
+    Registry {
+        /// Pack name.
+        name: String,
+        /// Requested version; the highest listed version if `None`.
+        version: Option<String>,
+    },
+    /// A playbook YAML file served over HTTPS.
+    Url(String),
+    /// A git repository holding playbook YAML files.
+    Git {
+        /// Repository URL.
+        url: String,
+        /// Branch, tag or commit; the default branch if `None`.
+        reference: Option<String>,
+    },
+}
+
+impl PackSource {
+    /// Parses a pack spec: `name`, `name@version`, an `https://` URL of a YAML
+    /// file, or a git repository as `git+<url>[@ref]` or `https://...git[@ref]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for other URL schemes and invalid pack names.
+    pub fn parse(spec: &str) -> Result<Self> {
+        if let Some(repository) = spec.strip_prefix("git+") {
+            let (url, reference) = split_reference(repository);
+            check_git_argument(&url, reference.as_deref())?;
+            return Ok(Self::Git { url, reference });
+        }
+        if spec.starts_with("https://") {
+            let (url, reference) = split_reference(spec);
+            if Path::new(&url)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("git"))
+            {
+                check_git_argument(&url, reference.as_deref())?;
+                return Ok(Self::Git { url, reference });
+            }
+            return Ok(Self::Url(spec.to_string()));
+        }
+        if spec.contains("://") {
+            return Err(SniffError::invalid_format(
+                "pack source",
+                format!(
+                    "Unsupported pack source '{spec}': use a registry name, an https:// URL \
+                     or a git+ URL"
+                ),
+            ));
+        }
+
+        let (name, version) = match spec.split_once('@') {
+            Some((name, version)) => (name, Some(version.to_string())),
+            None => (spec, None),
+        };
+        validate_pack_name(name)?;
+        Ok(Self::Registry {
+            name: name.to_string(),
+            version,
+        })
+    }
+}
+
+/// Rejects a repository URL or reference that git would read as an option,
+/// such as `--upload-pack=<command>`. Sources come from `packs.lock`, which is
+/// shared through the repository.
+fn check_git_argument(url: &str, reference: Option<&str>) -> Result<()> {
+    for value in std::iter::once(url).chain(reference) {
+        if value.is_empty() || value.starts_with('-') {
+            return Err(SniffError::invalid_format(
+                "pack source",
+                format!("'{value}' is not a valid git repository URL or reference"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Rejects a pack file name that is not a plain file name, so lock entries
+/// such as `../../.bashrc` cannot reach outside the patterns directory.
+fn check_file_name(name: &str) -> Result<()> {
+    if Path::new(name).file_name() == Some(std::ffi::OsStr::new(name)) {
+        Ok(())
+    } else {
+        Err(SniffError::invalid_format(
+            "pack file",
+            format!("'{name}' is not a plain file name"),
+        ))
+    }
+}
+
+/// Splits a trailing `@ref` off a repository URL. An `@` followed by a path
+/// belongs to the URL (as in `ssh://git@host/repo`).
+fn split_reference(repository: &str) -> (String, Option<String>) {
+    match repository.rsplit_once('@') {
+        Some((url, reference)) if !reference.contains('/') && url.contains('/') => {
+            (url.to_string(), Some(reference.to_string()))
+        }
+        _ => (repository.to_string(), None),
+    }
+}
+
+fn validate_pack_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(SniffError::invalid_format(
+            "pack name",
+            format!("'{name}' is not a valid pack name (letters, digits, '-' and '_')"),
+        ))
+    }
+}
+
+/// A pack version listed in a registry index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Pack name.
+    pub name: String,
+    /// Pack version.
+    pub version: String,
+    /// HTTPS URL of the playbook YAML file.
+    pub url: String,
+    /// SHA-256 checksum of the file.
+    pub sha256: String,
+    /// HTTPS URL of the file's minisign signature (defaults to `<url>.minisig`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// A registry index: a YAML file listing pack versions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegistryIndex {
+    /// Every listed pack version.
+    #[serde(default)]
+    pub packs: Vec<IndexEntry>,
+}
+
+impl RegistryIndex {
+    /// Returns the requested version of a pack, or its highest version.
+    #[must_use]
+    pub fn resolve(&self, name: &str, version: Option<&str>) -> Option<&IndexEntry> {
+        let versions = self.packs.iter().filter(|entry| entry.name == name);
+        match version {
+            Some(version) => versions.into_iter().find(|entry| entry.version == version),
+            None => versions.max_by(|a, b| compare_versions(&a.version, &b.version)),
+        }
+    }
+}
+
+/// Compares dotted versions numerically where components are numbers.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let components = |version: &str| -> Vec<(u64, String)> {
+        version
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| (part.parse().unwrap_or(0), part.to_string()))
+            .collect()
+    };
+    components(a).cmp(&components(b))
+}
+
+/// An installed pack as recorded in the lockfile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPack {
+    /// Pack name.
+    pub name: String,
+    /// Installed version: the registry version, the playbook version of a URL
+    /// pack, or the git reference (commit if none was given).
+    pub version: String,
+    /// Where the files were fetched from, as an `https://` or `git+` spec.
+    pub source: String,
+    /// SHA-256 checksum of the pack.
+    pub sha256: String,
+    /// Installed file names in the patterns directory.
+    pub files: Vec<String>,
+    /// Ids of the trusted keys that signed the pack; `None` if it was
+    /// installed unsigned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_by: Option<String>,
+}
+
+/// On-disk layout of `packs.lock`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LockFile {
+    #[serde(default)]
+    packs: Vec<LockedPack>,
+}
+
+/// The lockfile of a patterns directory.
+#[derive(Debug)]
+pub struct PackLock {
+    path: PathBuf,
+    packs: Vec<LockedPack>,
+}
+
+impl PackLock {
+    /// Loads the lockfile of a patterns directory; empty if there is none.
+    pub fn load(patterns_dir: &Path) -> Result<Self> {
+        let path = patterns_dir.join(LOCKFILE_NAME);
+        let packs = if path.exists() {
+            let content =
+                std::fs::read_to_string(&path).map_err(|e| SniffError::file_system(&path, e))?;
+            serde_yaml::from_str::<LockFile>(&content)
+                .map_err(|e| {
+                    SniffError::invalid_format("pack lockfile", format!("{}: {e}", path.display()))
+                })?
+                .packs
+        } else {
+            Vec::new()
+        };
+        Ok(Self { path, packs })
+    }
+
+    /// Returns the locked packs.
+    #[must_use]
+    pub fn packs(&self) -> &[LockedPack] {
+        &self.packs
+    }
+
+    /// Returns the locked pack with the given name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&LockedPack> {
+        self.packs.iter().find(|pack| pack.name == name)
+    }
+
+    /// Records a pack, replacing any entry with the same name.
+    pub fn upsert(&mut self, pack: LockedPack) {
+        self.packs.retain(|locked| locked.name != pack.name);
+        self.packs.push(pack);
+        self.packs.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    /// Writes the lockfile.
+    pub fn save(&self) -> Result<()> {
+        let content = serde_yaml::to_string(&LockFile {
+            packs: self.packs.clone(),
+        })
+        .map_err(|e| SniffError::invalid_format("pack lockfile", e.to_string()))?;
+        std::fs::write(&self.path, content).map_err(|e| SniffError::file_system(&self.path, e))
+    }
+}
+
+/// Pack files by name, with their content.
+type PackFiles = Vec<(String, Vec<u8>)>;
+
+/// Returns the SHA-256 of `bytes` as lowercase hex.
+#[must_use]
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Returns the checksum of a pack's files (see the module docs).
+#[must_use]
+pub fn pack_checksum(files: &[(String, Vec<u8>)]) -> String {
+    if let [(_, content)] = files {
+        return sha256_hex(content);
+    }
+    let mut listing: Vec<String> = files
+        .iter()
+        .map(|(name, content)| format!("{}  {name}\n", sha256_hex(content)))
+        .collect();
+    listing.sort_by(|a, b| a[66..].cmp(&b[66..]));
+    sha256_hex(listing.concat().as_bytes())
+}
+
+/// Fetches a URL over HTTPS, or reads a local path (for mirrored registries).
+fn fetch(location: &str) -> Result<Vec<u8>> {
+    if !location.contains("://") {
+        return std::fs::read(location).map_err(|e| SniffError::file_system(location, e));
+    }
+    fetch_optional(location)?.ok_or_else(|| SniffError::network(location, "HTTP 404"))
+}
+
+/// Fetches a URL like [`fetch`], returning `None` if there is nothing there.
+fn fetch_optional(location: &str) -> Result<Option<Vec<u8>>> {
+    if !location.contains("://") {
+        return match std::fs::read(location) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(SniffError::file_system(location, e)),
+        };
+    }
+    if !location.starts_with("https://") {
+        return Err(SniffError::network(
+            location,
+            "only https:// URLs are supported",
+        ));
+    }
+    network::check(&format!("Downloading {location}"))?;
+
+    let response = match ureq::get(location).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => return Ok(None),
+        Err(e) => return Err(SniffError::network(location, e.to_string())),
+    };
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_DOWNLOAD_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| SniffError::network(location, e.to_string()))?;
+    if bytes.len() as u64 > MAX_DOWNLOAD_BYTES {
+        return Err(SniffError::network(
+            location,
+            format!("response exceeds {MAX_DOWNLOAD_BYTES} bytes"),
+        ));
+    }
+    Ok(Some(bytes))
+}
+
+/// Runs git, returning its trimmed standard output.
+fn git(args: &[&str], dir: Option<&Path>) -> Result<String> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    let output = command
+        .args(args)
+        .output()
+        .map_err(|e| SniffError::analysis_error(format!("Failed to run git: {e}")))?;
+    if !output.status.success() {
+        return Err(SniffError::analysis_error(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Clones a repository and returns the commit, the playbook files at its root
+/// and in `playbooks/`, and the signatures found next to them.
+fn fetch_git(
+    url: &str,
+    reference: Option<&str>,
+) -> Result<(String, PackFiles, HashMap<String, Vec<u8>>)> {
+    check_git_argument(url, reference)?;
+    network::check(&format!("Cloning {url}"))?;
+    let checkout =
+        tempfile::TempDir::new().map_err(|e| SniffError::file_system(std::env::temp_dir(), e))?;
+    let dir = checkout.path().to_string_lossy().to_string();
+    git(&["clone", "--quiet", "--", url, &dir], None)?;
+    if let Some(reference) = reference {
+        // The trailing `--` makes git read the reference as a revision only
+        git(
+            &["checkout", "--quiet", reference, "--"],
+            Some(checkout.path()),
+        )?;
+    }
+    let commit = git(&["rev-parse", "HEAD"], Some(checkout.path()))?;
+
+    let mut files = Vec::new();
+    let mut signatures = HashMap::new();
+    for dir in [
+        checkout.path().to_path_buf(),
+        checkout.path().join("playbooks"),
+    ] {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry.map_err(|e| SniffError::file_system(&dir, e))?.path();
+            let is_yaml = path
+                .extension()
+                .is_some_and(|ext| ext == "yaml" || ext == "yml");
+            if is_yaml && path.is_file() {
+                let content =
+                    std::fs::read(&path).map_err(|e| SniffError::file_system(&path, e))?;
+                let name = path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                let signature_path = dir.join(format!("{name}.{SIGNATURE_EXTENSION}"));
+                if signature_path.is_file() {
+                    let signature = std::fs::read(&signature_path)
+                        .map_err(|e| SniffError::file_system(&signature_path, e))?;
+                    signatures.insert(name.clone(), signature);
+                }
+                files.push((name, content));
+            }
+        }
+    }
+    files.sort();
+    Ok((commit, files, signatures))
+}
+
+/// Parses and validates a pack file as a playbook.
+fn validate_playbook(name: &str, content: &[u8]) -> Result<Playbook> {
+    let staging =
+        tempfile::TempDir::new().map_err(|e| SniffError::file_system(std::env::temp_dir(), e))?;
+    let path = staging.path().join(name);
+    std::fs::write(&path, content).map_err(|e| SniffError::file_system(&path, e))?;
+    PlaybookManager::new().load_playbook(&path)?;
+
+    let content = String::from_utf8_lossy(content);
+    serde_yaml::from_str(&content)
+        .map_err(|e| SniffError::invalid_format("playbook parsing", e.to_string()))
+}
+
+/// Installs packs into a patterns directory and maintains its lockfile.
+#[derive(Debug)]
+pub struct PackInstaller {
+    patterns_dir: PathBuf,
+    registry: Option<String>,
+    trusted_keys: Vec<PublicKey>,
+    allow_unsigned: bool,
+}
+
+impl PackInstaller {
+    /// Creates an installer. `registry` is the URL or path of the registry
+    /// index used to resolve pack names.
+    #[must_use]
+    pub fn new(patterns_dir: &Path, registry: Option<String>) -> Self {
+        Self {
+            patterns_dir: patterns_dir.to_path_buf(),
+            registry,
+            trusted_keys: Vec::new(),
+            allow_unsigned: false,
+        }
+    }
+
+    /// Sets the keys that pack signatures are verified against.
+    #[must_use]
+    pub fn with_trusted_keys(mut self, trusted_keys: Vec<PublicKey>) -> Self {
+        self.trusted_keys = trusted_keys;
+        self
+    }
+
+    /// Installs packs that are unsigned or cannot be verified for lack of
+    /// trusted keys, instead of refusing them. Signatures that do not match
+    /// are rejected regardless.
+    #[must_use]
+    pub fn allow_unsigned(mut self, allow_unsigned: bool) -> Self {
+        self.allow_unsigned = allow_unsigned;
+        self
+    }
+
+    /// Installs a pack and records it in the lockfile.
+    ///
+    /// The checksum must match `expected_sha256` if given, the registry's
+    /// checksum for registry packs, and the lockfile's if the same version from
+    /// the same source is locked. Every file must be signed by a trusted key
+    /// unless unsigned packs are allowed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pack cannot be fetched, a checksum or signature
+    /// does not match, the pack is not signed by a trusted key, or a file is not
+    /// a valid playbook. Nothing is installed then.
+    pub fn install(
+        &self,
+        source: &PackSource,
+        expected_sha256: Option<&str>,
+    ) -> Result<LockedPack> {
+        let mut expected: Vec<String> =
+            expected_sha256.map(str::to_lowercase).into_iter().collect();
+
+        let mut signatures = HashMap::new();
+        let (name, version, fetched_from, files) = match source {
+            PackSource::Registry { name, version } => {
+                let registry = self.registry.as_deref().ok_or_else(|| {
+                    SniffError::invalid_format(
+                        "pack source",
+                        format!(
+                            "No pack registry configured to resolve '{name}'; pass --registry \
+                             or set SNIFF_PACK_REGISTRY"
+                        ),
+                    )
+                })?;
+                let index: RegistryIndex =
+                    serde_yaml::from_slice(&fetch(registry)?).map_err(|e| {
+                        SniffError::invalid_format("registry index", format!("{registry}: {e}"))
+                    })?;
+                let entry = index.resolve(name, version.as_deref()).ok_or_else(|| {
+                    SniffError::invalid_format(
+                        "pack source",
+                        match version {
+                            Some(version) => format!("{name}@{version} is not in {registry}"),
+                            None => format!("Pack '{name}' is not in {registry}"),
+                        },
+                    )
+                })?;
+                if !entry.url.starts_with("https://") {
+                    return Err(SniffError::network(
+                        &entry.url,
+                        "only https:// URLs are supported",
+                    ));
+                }
+                expected.push(entry.sha256.to_lowercase());
+                let signature_url = entry
+                    .signature
+                    .clone()
+                    .unwrap_or_else(|| format!("{}.{SIGNATURE_EXTENSION}", entry.url));
+                if !signature_url.starts_with("https://") {
+                    return Err(SniffError::network(
+                        &signature_url,
+                        "only https:// URLs are supported",
+                    ));
+                }
+                if let Some(signature) = fetch_optional(&signature_url)? {
+                    signatures.insert(format!("{name}.yaml"), signature);
+                }
+                let file = (format!("{name}.yaml"), fetch(&entry.url)?);
+                (
+                    name.clone(),
+                    entry.version.clone(),
+                    entry.url.clone(),
+                    vec![file],
+                )
+            }
+            PackSource::Url(url) => {
+                let content = fetch(url)?;
+                let file_name = url.rsplit('/').next().unwrap_or_default();
+                let stem = file_name
+                    .trim_end_matches(".yaml")
+                    .trim_end_matches(".yml")
+                    .to_string();
+                validate_pack_name(&stem)?;
+                let version = validate_playbook(file_name, &content)?.version;
+                let signature_url = format!("{url}.{SIGNATURE_EXTENSION}");
+                if let Some(signature) = fetch_optional(&signature_url)? {
+                    signatures.insert(format!("{stem}.yaml"), signature);
+                }
+                let file = (format!("{stem}.yaml"), content);
+                (stem, version, url.clone(), vec![file])
+            }
+            PackSource::Git { url, reference } => {
+                let (commit, files, mut git_signatures) = fetch_git(url, reference.as_deref())?;
+                if files.is_empty() {
+                    return Err(SniffError::invalid_format(
+                        "pack source",
+                        format!("{url} has no playbook YAML files at its root or in playbooks/"),
+                    ));
+                }
+                let name = url
+                    .trim_end_matches('/')
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or_default()
+                    .trim_end_matches(".git")
+                    .to_string();
+                validate_pack_name(&name)?;
+                let version = reference.clone().unwrap_or(commit);
+                let files: PackFiles = if let [(file, content)] = files.as_slice() {
+                    let installed = format!("{name}.yaml");
+                    if let Some(signature) = git_signatures.remove(file) {
+                        signatures.insert(installed.clone(), signature);
+                    }
+                    vec![(installed, content.clone())]
+                } else {
+                    files
+                        .into_iter()
+                        .map(|(file, content)| {
+                            let installed = format!("{name}-{file}");
+                            if let Some(signature) = git_signatures.remove(&file) {
+                                signatures.insert(installed.clone(), signature);
+                            }
+                            (installed, content)
+                        })
+                        .collect()
+                };
+                (name, version, format!("git+{url}"), files)
+            }
+        };
+
+        let mut lock = PackLock::load(&self.patterns_dir)?;
+        if let Some(locked) = lock.get(&name) {
+            if locked.version == version && locked.source == fetched_from {
+                expected.push(locked.sha256.clone());
+            }
+        }
+
+        let sha256 = pack_checksum(&files);
+        if let Some(mismatch) = expected.iter().find(|expected| **expected != sha256) {
+            return Err(SniffError::invalid_format(
+                "pack checksum",
+                format!("Checksum mismatch for pack '{name}': expected {mismatch}, got {sha256}"),
+            ));
+        }
+        let signed_by = self.verify_signatures(&name, &files, &signatures)?;
+        for (file, content) in &files {
+            validate_playbook(file, content)?;
+        }
+
+        let previous_files = lock.get(&name).map(|previous| previous.files.iter());
+        for file in previous_files
+            .into_iter()
+            .flatten()
+            .chain(files.iter().map(|(file, _)| file))
+        {
+            check_file_name(file)?;
+        }
+
+        std::fs::create_dir_all(&self.patterns_dir)
+            .map_err(|e| SniffError::file_system(&self.patterns_dir, e))?;
+        if let Some(previous) = lock.get(&name) {
+            for file in &previous.files {
+                let path = self.patterns_dir.join(file);
+                if path.exists() {
+                    std::fs::remove_file(&path).map_err(|e| SniffError::file_system(&path, e))?;
+                }
+            }
+        }
+        for (file, content) in &files {
+            let path = self.patterns_dir.join(file);
+            std::fs::write(&path, content).map_err(|e| SniffError::file_system(&path, e))?;
+        }
+
+        let pack = LockedPack {
+            name,
+            version,
+            source: fetched_from,
+            sha256,
+            files: files.into_iter().map(|(file, _)| file).collect(),
+            signed_by,
+        };
+        lock.upsert(pack.clone());
+        lock.save()?;
+        Ok(pack)
+    }
+
+    /// Verifies the signature of every file of a pack and returns the ids of
+    /// the keys that signed them, or `None` for an allowed unsigned pack.
+    fn verify_signatures(
+        &self,
+        name: &str,
+        files: &PackFiles,
+        signatures: &HashMap<String, Vec<u8>>,
+    ) -> Result<Option<String>> {
+        let mut key_ids = BTreeSet::new();
+        let mut unverified = None;
+        for (file, content) in files {
+            let Some(signature) = signatures.get(file) else {
+                unverified.get_or_insert_with(|| format!("{file} has no signature"));
+                continue;
+            };
+            if self.trusted_keys.is_empty() {
+                unverified.get_or_insert_with(|| "no trusted keys are configured".to_string());
+                continue;
+            }
+            let key = pack_signing::verify(
+                content,
+                &String::from_utf8_lossy(signature),
+                &self.trusted_keys,
+            )
+            .map_err(|e| {
+                SniffError::invalid_format(
+                    "pack signature",
+                    format!("Refusing pack '{name}', {file}: {e}"),
+                )
+            })?;
+            key_ids.insert(key.key_id());
+        }
+
+        match unverified {
+            None => Ok(Some(key_ids.into_iter().collect::<Vec<_>>().join(","))),
+            Some(_) if self.allow_unsigned => Ok(None),
+            Some(reason) => Err(SniffError::invalid_format(
+                "pack signature",
+                format!(
+                    "Refusing unverified pack '{name}': {reason}; pass --allow-unsigned to \
+                     install it anyway"
+                ),
+            )),
+        }
+    }
+
+    /// Reinstalls every pack in the lockfile at its locked version, verifying
+    /// the locked checksums.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on the first pack that fails to install.
+    pub fn install_locked(&self) -> Result<Vec<LockedPack>> {
+        let lock = PackLock::load(&self.patterns_dir)?;
+        let mut installed = Vec::new();
+        for locked in lock.packs() {
+            let source = match PackSource::parse(&locked.source)? {
+                PackSource::Git { url, .. } => PackSource::Git {
+                    url,
+                    reference: Some(locked.version.clone()),
+                },
+                source => source,
+            };
+            installed.push(self.install(&source, Some(&locked.sha256))?);
+        }
+        Ok(installed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYBOOK: &str = r#"
+name: "Team Rules"
+version: "2.1.0"
+language: "rust"
+author: "Team"
+description: "Shared rules"
+metadata: {}
+rules:
+  - id: "team_dbg"
+    name: "Debug Macro"
+    description: "dbg! left in code"
+    severity: "Low"
+    pattern_type: !Regex
+      pattern: "dbg!\\("
+    scope: "File"
+    enabled: true
+    tags: []
+    examples: []
+    false_positives: []
+"#;
+
+    #[test]
+    fn test_parse_pack_source() {
+        assert_eq!(
+            PackSource::parse("django@1.2.0").unwrap(),
+            PackSource::Registry {
+                name: "django".to_string(),
+                version: Some("1.2.0".to_string())
+            }
+        );
+        assert_eq!(
+            PackSource::parse("git+ssh://git@example.com/team/rules.git@v2").unwrap(),
+            PackSource::Git {
+                url: "ssh://git@example.com/team/rules.git".to_string(),
+                reference: Some("v2".to_string())
+            }
+        );
+        assert_eq!(
+            PackSource::parse("https://example.com/team/rules.git").unwrap(),
+            PackSource::Git {
+                url: "https://example.com/team/rules.git".to_string(),
+                reference: None
+            }
+        );
+        assert!(matches!(
+            PackSource::parse("https://example.com/rules.yaml").unwrap(),
+            PackSource::Url(_)
+        ));
+        assert!(PackSource::parse("http://example.com/rules.yaml").is_err());
+        assert!(PackSource::parse("../rules").is_err());
+        // Sources that git would read as options
+        assert!(PackSource::parse("git+--upload-pack=touch pwned").is_err());
+        assert!(PackSource::parse("git+https://example.com/team/rules.git@-b").is_err());
+    }
+
+    #[test]
+    fn test_registry_resolves_highest_version() {
+        let entry = |version: &str| IndexEntry {
+            name: "django".to_string(),
+            version: version.to_string(),
+            url: format!("https://example.com/django-{version}.yaml"),
+            sha256: String::new(),
+            signature: None,
+        };
+        let index = RegistryIndex {
+            packs: vec![entry("1.9.0"), entry("1.10.0"), entry("1.2.0")],
+        };
+        assert_eq!(index.resolve("django", None).unwrap().version, "1.10.0");
+        assert_eq!(
+            index.resolve("django", Some("1.2.0")).unwrap().version,
+            "1.2.0"
+        );
+        assert!(index.resolve("flask", None).is_none());
+    }
+
+    /// Creates a git repository `team-rules` holding `files`, tagged `v2`.
+    fn create_pack_repository(root: &Path, files: &[(&str, &str)]) -> PathBuf {
+        let run = |dir: &Path, args: &[&str]| {
+            let status = Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {args:?}");
+        };
+        let repo_dir = root.join("team-rules");
+        std::fs::create_dir(&repo_dir).unwrap();
+        for (name, content) in files {
+            std::fs::write(repo_dir.join(name), content).unwrap();
+        }
+        run(&repo_dir, &["init", "--quiet"]);
+        run(&repo_dir, &["add", "."]);
+        run(
+            &repo_dir,
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                "rules",
+            ],
+        );
+        run(&repo_dir, &["tag", "v2"]);
+        repo_dir
+    }
+
+    #[test]
+    fn test_install_from_git_is_locked() {
+        let repo = tempfile::TempDir::new().unwrap();
+        let repo_dir = create_pack_repository(repo.path(), &[("rules.yaml", PLAYBOOK)]);
+
+        let patterns = tempfile::TempDir::new().unwrap();
+        let source = PackSource::Git {
+            url: format!("file://{}", repo_dir.display()),
+            reference: Some("v2".to_string()),
+        };
+
+        // Unsigned packs are refused unless allowed
+        let strict = PackInstaller::new(patterns.path(), None);
+        let err = strict.install(&source, None).unwrap_err();
+        assert!(err.to_string().contains("--allow-unsigned"), "{err}");
+
+        let installer = PackInstaller::new(patterns.path(), None).allow_unsigned(true);
+        let pack = installer.install(&source, None).unwrap();
+        assert_eq!(pack.name, "team-rules");
+        assert_eq!(pack.version, "v2");
+        assert_eq!(pack.files, vec!["team-rules.yaml"]);
+        assert_eq!(pack.sha256, sha256_hex(PLAYBOOK.as_bytes()));
+        assert_eq!(pack.signed_by, None);
+        assert!(patterns.path().join("team-rules.yaml").exists());
+        assert_eq!(
+            PackLock::load(patterns.path()).unwrap().packs(),
+            std::slice::from_ref(&pack)
+        );
+
+        // Reinstalling from the lockfile verifies the checksum
+        assert_eq!(installer.install_locked().unwrap(), vec![pack.clone()]);
+        assert!(installer.install(&source, Some("00")).is_err());
+
+        // A crafted lockfile cannot remove files outside the patterns directory
+        let victim = repo.path().join("victim.yaml");
+        std::fs::write(&victim, "keep").unwrap();
+        let mut lock = PackLock::load(patterns.path()).unwrap();
+        lock.upsert(LockedPack {
+            files: vec![
+                victim.to_string_lossy().to_string(),
+                "../victim.yaml".to_string(),
+            ],
+            ..pack
+        });
+        lock.save().unwrap();
+        assert!(installer.install_locked().is_err());
+        assert!(victim.exists());
+    }
+
+    #[test]
+    fn test_install_verifies_signatures() {
+        const SIGNATURE: &str = "untrusted comment: signature from minisign secret key\n\
+            RUQqXRAzkXwE6EEZw6YHHWX3fir1orDrhK0PK+8oaueDidAHVqqRlBfRqVoYurIAarAPeyjth9xxUP9X2nYLla3BnDLlqfTUCAg=\n\
+            trusted comment: timestamp:1760000000\tfile:rules.yaml\thashed\n\
+            RCgv0prcGl2Rjr0B3gfxS1csD+EVskjJNI7JEaFXe7Mn3VERrTAZVoAiBs9NaL7DUG3wVokQsT32uYTge3QUDw==\n";
+        let trusted_key =
+            PublicKey::parse("RWQqXRAzkXwE6AOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4").unwrap();
+
+        let repo = tempfile::TempDir::new().unwrap();
+        let repo_dir = create_pack_repository(
+            repo.path(),
+            &[("rules.yaml", PLAYBOOK), ("rules.yaml.minisig", SIGNATURE)],
+        );
+        let source = PackSource::Git {
+            url: format!("file://{}", repo_dir.display()),
+            reference: None,
+        };
+
+        let patterns = tempfile::TempDir::new().unwrap();
+        let installer =
+            PackInstaller::new(patterns.path(), None).with_trusted_keys(vec![trusted_key]);
+        let pack = installer.install(&source, None).unwrap();
+        assert_eq!(pack.signed_by.as_deref(), Some("E8047C9133105D2A"));
+
+        // A tampered pack is refused even when unsigned packs are allowed
+        let tampered = tempfile::TempDir::new().unwrap();
+        let tampered_dir = create_pack_repository(
+            tampered.path(),
+            &[
+                ("rules.yaml", &PLAYBOOK.replace("Low", "Info")),
+                ("rules.yaml.minisig", SIGNATURE),
+            ],
+        );
+        let source = PackSource::Git {
+            url: format!("file://{}", tampered_dir.display()),
+            reference: None,
+        };
+        let err = installer
+            .allow_unsigned(true)
+            .install(&source, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Minisign signatures of pattern packs.
+//!
+//! Rules decide whether CI gates pass, so a pack fetched over the network is
+//! only installed if every file carries a minisign signature (`<file>.minisig`)
+//! from a trusted key. Trusted public keys are listed one per line in
+//! `trusted-keys` in the patterns directory, in the base64 form printed by
+//! `minisign -G`; a whole `.pub` file can be pasted as is, and lines starting
+//! with `#` are ignored.
+//!
+//! Both the default prehashed (`ED`, BLAKE2b-512) and the legacy (`Ed`)
+//! signature algorithms are accepted. The trusted comment is verified too.
+
+use crate::error::{Result, SniffError};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use blake2::{Blake2b512, Digest};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use std::path::Path;
+
+/// File name of the trusted keys list in the patterns directory.
+pub const TRUSTED_KEYS_FILE_NAME: &str = "trusted-keys";
+
+/// Extension appended to a file's name for its signature.
+pub const SIGNATURE_EXTENSION: &str = "minisig";
+
+/// A minisign public key.
+#[derive(Debug, Clone)]
+pub struct PublicKey {
+    key_id: [u8; 8],
+    key: VerifyingKey,
+}
+
+impl PublicKey {
+    /// Parses a public key from its base64 line, or from the content of a
+    /// minisign `.pub` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text holds no valid Ed25519 minisign key.
+    pub fn parse(text: &str) -> Result<Self> {
+        let encoded = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+            .unwrap_or_default();
+        let bytes = decode(encoded, "public key")?;
+        if bytes.len() != 42 || &bytes[..2] != b"Ed" {
+            return Err(invalid("public key", "not an Ed25519 minisign key"));
+        }
+        let mut key_id = [0; 8];
+        key_id.copy_from_slice(&bytes[2..10]);
+        let mut key = [0; 32];
+        key.copy_from_slice(&bytes[10..]);
+        let key =
+            VerifyingKey::from_bytes(&key).map_err(|e| invalid("public key", e.to_string()))?;
+        Ok(Self { key_id, key })
+    }
+
+    /// Returns the key id as minisign prints it (16 uppercase hex digits).
+    #[must_use]
+    pub fn key_id(&self) -> String {
+        key_id_hex(&self.key_id)
+    }
+}
+
+/// Loads the trusted keys listed in a patterns directory; empty if there is no
+/// list.
+///
+/// # Errors
+///
+/// Returns an error if the list cannot be read or holds an invalid key.
+pub fn load_trusted_keys(patterns_dir: &Path) -> Result<Vec<PublicKey>> {
+    let path = patterns_dir.join(TRUSTED_KEYS_FILE_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| SniffError::file_system(&path, e))?;
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty() && !line.starts_with('#') && !line.starts_with("untrusted comment:")
+        })
+        .map(PublicKey::parse)
+        .collect()
+}
+
+/// Verifies a minisign signature of `content` against the trusted keys and
+/// returns the key that made it.
+///
+/// # Errors
+///
+/// Returns an error if the signature is malformed, was made by an untrusted
+/// key, or does not match the content or its trusted comment.
+pub fn verify<'a>(
+    content: &[u8],
+    signature: &str,
+    trusted_keys: &'a [PublicKey],
+) -> Result<&'a PublicKey> {
+    let mut lines = signature.lines().map(str::trim_end);
+    let (Some(untrusted), Some(encoded), Some(trusted), Some(global)) =
+        (lines.next(), lines.next(), lines.next(), lines.next())
+    else {
+        return Err(invalid("signature", "expected four lines"));
+    };
+    let Some(trusted_comment) = trusted.strip_prefix("trusted comment: ") else {
+        return Err(invalid("signature", "missing trusted comment"));
+    };
+    if !untrusted.starts_with("untrusted comment:") {
+        return Err(invalid("signature", "missing untrusted comment"));
+    }
+
+    let bytes = decode(encoded, "signature")?;
+    if bytes.len() != 74 {
+        return Err(invalid("signature", "wrong signature length"));
+    }
+    let prehashed = match &bytes[..2] {
+        b"ED" => true,
+        b"Ed" => false,
+        _ => return Err(invalid("signature", "unsupported signature algorithm")),
+    };
+    let key_id = &bytes[2..10];
+    let signature_bytes = &bytes[10..];
+    let signature =
+        Signature::from_slice(signature_bytes).map_err(|e| invalid("signature", e.to_string()))?;
+    let global_signature = Signature::from_slice(&decode(global, "trusted comment signature")?)
+        .map_err(|e| invalid("trusted comment signature", e.to_string()))?;
+
+    let key = trusted_keys
+        .iter()
+        .find(|key| key.key_id == key_id)
+        .ok_or_else(|| {
+            invalid(
+                "signature",
+                format!("signed by untrusted key {}", key_id_hex(key_id)),
+            )
+        })?;
+
+    let verified = if prehashed {
+        key.key.verify(&Blake2b512::digest(content), &signature)
+    } else {
+        key.key.verify(content, &signature)
+    };
+    verified.map_err(|_| invalid("signature", "content does not match its signature"))?;
+
+    let mut signed_comment = signature_bytes.to_vec();
+    signed_comment.extend_from_slice(trusted_comment.as_bytes());
+    key.key
+        .verify(&signed_comment, &global_signature)
+        .map_err(|_| invalid("signature", "trusted comment does not match its signature"))?;
+    Ok(key)
+}
+
+fn decode(encoded: &str, context: &str) -> Result<Vec<u8>> {
+    BASE64
+        .decode(encoded.trim())
+        .map_err(|e| invalid(context, e.to_string()))
+}
+
+fn key_id_hex(key_id: &[u8]) -> String {
+    // minisign prints the little-endian key id as a number
+    key_id
+        .iter()
+        .rev()
+        .map(|byte| format!("{byte:02X}"))
+        .collect()
+}
+
+fn invalid(context: &str, reason: impl Into<String>) -> SniffError {
+    SniffError::invalid_format(format!("minisign {context}"), reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBLIC_KEY: &str = "untrusted comment: minisign public key E8047C9133105D2A\n\
+                              RWQqXRAzkXwE6AOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4\n";
+    const CONTENT: &[u8] = b"rules: []\n";
+    const PREHASHED_SIGNATURE: &str = "untrusted comment: signature from minisign secret key\n\
+        RUQqXRAzkXwE6LO6vZBUMLJjON6fgJ19M1eSmBhrttgm4ptXA5v6KPA1D7TT2x5Q74RyPXBqadD3ox9YzfwvqWJaLOwT9zBJ4wc=\n\
+        trusted comment: timestamp:1760000000\tfile:team-rules.yaml\thashed\n\
+        W/z49udH6V4ZbIBaa/HOEFUogmENwCGu4KHMzrWFEj9y7vYczlKXXj2H3iWbOEspKBiXha5sZG15CCMhuWMvDQ==\n";
+    const LEGACY_SIGNATURE: &str = "untrusted comment: signature from minisign secret key\n\
+        RWQqXRAzkXwE6LGBu8NPX64p7/QHzswV77/rnTX5PnroxWzvZgBBvn/JzgqO8T3Z853CsbXJjPVaWlYU45ga9P30zcInFGMolQQ=\n\
+        trusted comment: timestamp:1760000000\tfile:team-rules.yaml\thashed\n\
+        uPSMqfj32NczXngDYvBEo6L+jbzIiEPQMOIIoeVxbfF0ry5zO4gCIEeKUAIbYSBrmTAQqRTZq8cN7k+Xk8hmBA==\n";
+
+    #[test]
+    fn test_verify_minisign_signatures() {
+        let keys = vec![PublicKey::parse(PUBLIC_KEY).unwrap()];
+        assert_eq!(keys[0].key_id(), "E8047C9133105D2A");
+
+        for signature in [PREHASHED_SIGNATURE, LEGACY_SIGNATURE] {
+            let key = verify(CONTENT, signature, &keys).unwrap();
+            assert_eq!(key.key_id(), "E8047C9133105D2A");
+
+            // Tampered content or trusted comment
+            assert!(verify(b"rules: [evil]\n", signature, &keys).is_err());
+            let edited = signature.replace("hashed", "edited");
+            assert!(verify(CONTENT, &edited, &keys).is_err());
+        }
+
+        // No trusted key made the signature
+        assert!(verify(CONTENT, PREHASHED_SIGNATURE, &[]).is_err());
+    }
+
+    #[test]
+    fn test_load_trusted_keys() {
+        let patterns = tempfile::TempDir::new().unwrap();
+        assert!(load_trusted_keys(patterns.path()).unwrap().is_empty());
+
+        std::fs::write(
+            patterns.path().join(TRUSTED_KEYS_FILE_NAME),
+            format!("# Team rules\n{PUBLIC_KEY}"),
+        )
+        .unwrap();
+        let keys = load_trusted_keys(patterns.path()).unwrap();
+        assert_eq!(keys.len(), 1);
+
+        std::fs::write(patterns.path().join(TRUSTED_KEYS_FILE_NAME), "not a key\n").unwrap();
+        assert!(load_trusted_keys(patterns.path()).is_err());
+    }
+}
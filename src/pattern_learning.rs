@@ -11,6 +11,9 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+/// Namespace of the playbooks built from learned patterns.
+pub const LEARNED_NAMESPACE: &str = "learned";
+
 /// Configuration for pattern learning system.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LearningConfig {
@@ -139,6 +142,176 @@ pub struct PatternStatistics {
     pub most_active_patterns: Vec<(String, u64)>,
 }
 
+/// One pattern to import, as read from a CSV or JSON file.
+///
+/// CSV files need a header row naming the columns; several examples can be
+/// given in one CSV cell on separate lines.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatternImportRecord {
+    /// Pattern name.
+    pub name: String,
+    /// Regex to match.
+    #[serde(alias = "pattern")]
+    pub regex: String,
+    /// Language the pattern applies to.
+    pub language: String,
+    /// Severity name, `medium` if omitted.
+    #[serde(default)]
+    pub severity: Option<String>,
+    /// What the pattern detects; defaults to the name.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Scope name, `file` if omitted.
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// Example code that should trigger this pattern.
+    #[serde(default)]
+    pub examples: Vec<String>,
+}
+
+/// A row of an import file that could not be imported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternImportFailure {
+    /// Row number in the file (1-based, not counting a CSV header; entry index for JSON).
+    pub row: usize,
+    /// Pattern name, if the row had one.
+    pub name: Option<String>,
+    /// Why the row was rejected.
+    pub error: String,
+}
+
+/// Outcome of a batch import.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatternImportReport {
+    /// Ids of the created patterns.
+    pub created: Vec<String>,
+    /// Rows that were rejected.
+    pub failures: Vec<PatternImportFailure>,
+    /// Validation warnings of created patterns, by row.
+    pub warnings: Vec<(usize, String)>,
+}
+
+/// Reads the records of a CSV or JSON (array of objects) import file, chosen
+/// by extension. Rows that cannot be read are returned as errors.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, or is not a CSV file with a
+/// header row or a JSON array.
+pub fn read_import_file(
+    path: &Path,
+) -> Result<Vec<(usize, std::result::Result<PatternImportRecord, String>)>> {
+    let content = std::fs::read_to_string(path).map_err(|e| SniffError::file_system(path, e))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&content).map_err(|e| {
+            SniffError::invalid_format(
+                path.display().to_string(),
+                format!("expected a JSON array: {e}"),
+            )
+        })?;
+        return Ok(entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                (
+                    index + 1,
+                    serde_json::from_value(entry).map_err(|e| e.to_string()),
+                )
+            })
+            .collect());
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| SniffError::invalid_format(path.display().to_string(), e.to_string()))?
+        .iter()
+        .map(str::to_lowercase)
+        .collect();
+
+    Ok(reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|e| e.to_string())?;
+            let field = |names: &[&str]| {
+                headers
+                    .iter()
+                    .position(|header| names.contains(&header.as_str()))
+                    .and_then(|column| record.get(column))
+                    .filter(|value| !value.is_empty())
+                    .map(str::to_string)
+            };
+            Ok(PatternImportRecord {
+                name: field(&["name"]).unwrap_or_default(),
+                regex: field(&["regex", "pattern"]).unwrap_or_default(),
+                language: field(&["language"]).unwrap_or_default(),
+                severity: field(&["severity"]),
+                description: field(&["description"]),
+                scope: field(&["scope"]),
+                examples: field(&["examples", "example"])
+                    .map(|examples| examples.lines().map(str::to_string).collect())
+                    .unwrap_or_default(),
+            })
+        })
+        .enumerate()
+        .map(|(index, record)| (index + 1, record))
+        .collect())
+}
+
+/// Builds a creation request from an import record.
+fn import_request(
+    record: PatternImportRecord,
+    source_file: &Path,
+) -> std::result::Result<PatternCreationRequest, String> {
+    if record.name.trim().is_empty() {
+        return Err("missing name".to_string());
+    }
+    if record.regex.is_empty() {
+        return Err("missing regex".to_string());
+    }
+    let language = SupportedLanguage::from_name(record.language.trim())
+        .ok_or_else(|| format!("unknown language '{}'", record.language))?;
+    let severity = match &record.severity {
+        Some(name) => {
+            Severity::from_name(name.trim()).ok_or_else(|| format!("unknown severity '{name}'"))?
+        }
+        None => Severity::Medium,
+    };
+    let scope = match &record.scope {
+        Some(name) => match PatternScope::from_name(name.trim()) {
+            Some(PatternScope::Thinking) | None => {
+                return Err(format!("unsupported scope '{name}'"));
+            }
+            Some(scope) => scope,
+        },
+        None => PatternScope::File,
+    };
+
+    Ok(PatternCreationRequest {
+        description: record.description.unwrap_or_else(|| record.name.clone()),
+        name: record.name,
+        severity,
+        pattern: record.regex,
+        flags: None,
+        scope,
+        language,
+        tags: vec!["imported".to_string()],
+        examples: record.examples,
+        false_positives: Vec::new(),
+        // Imported patterns were chosen by people, not inferred
+        confidence: 1.0,
+        source: "import".to_string(),
+        metadata: HashMap::from([("import_file".to_string(), source_file.display().to_string())]),
+    })
+}
+
 /// Manages pattern learning and storage in the .sniff folder.
 pub struct PatternLearningManager {
     /// Base path to .sniff folder
@@ -172,6 +345,34 @@ impl PatternLearningManager {
         Ok(manager)
     }
 
+    /// Opens the learned patterns below `base_path` for reading only.
+    ///
+    /// Unlike [`Self::new`], this neither creates the `.sniff` folder nor
+    /// writes a default config; a missing folder simply has no patterns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an existing config or pattern file cannot be read or parsed.
+    pub fn open_read_only<P: AsRef<Path>>(base_path: P) -> Result<Self> {
+        let mut manager = Self {
+            sniff_path: base_path.as_ref().join(".sniff"),
+            config: LearningConfig::default(),
+            learned_patterns: HashMap::new(),
+        };
+
+        // load_config writes the default config when it is missing
+        let config_path = manager
+            .sniff_path
+            .join("config")
+            .join("learning-config.yaml");
+        if config_path.exists() {
+            manager.load_config()?;
+        }
+        manager.load_learned_patterns()?;
+
+        Ok(manager)
+    }
+
     /// Initializes the .sniff folder structure.
     fn initialize_folder_structure(&self) -> Result<()> {
         let folders = [
@@ -252,6 +453,11 @@ impl PatternLearningManager {
             SupportedLanguage::Go,
             SupportedLanguage::C,
             SupportedLanguage::Cpp,
+            SupportedLanguage::Swift,
+            SupportedLanguage::Terraform,
+            SupportedLanguage::Scala,
+            SupportedLanguage::Config,
+            SupportedLanguage::Markdown,
         ];
 
         for language in &languages {
@@ -346,12 +552,23 @@ impl PatternLearningManager {
             pattern_type: PatternType::Regex {
                 pattern: request.pattern,
                 flags: request.flags,
+                multiline: false,
             },
             scope: request.scope,
             enabled: true,
             tags: request.tags.clone(),
             examples: request.examples,
             false_positives: request.false_positives,
+            paths: Vec::new(),
+            exclude_paths: Vec::new(),
+            conditions: None,
+            deprecated: false,
+            replaced_by: None,
+            deprecated_since: None,
+            remediation: None,
+            cwe: Vec::new(),
+            owasp: Vec::new(),
+            references: Vec::new(),
         };
 
         // Create metadata
@@ -391,6 +608,46 @@ impl PatternLearningManager {
         })
     }
 
+    /// Creates learned patterns from a CSV or JSON file, validating each row.
+    ///
+    /// Valid rows are imported even if other rows fail; the report lists both.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or the patterns cannot be saved.
+    pub fn import_patterns(&mut self, path: &Path) -> Result<PatternImportReport> {
+        let mut report = PatternImportReport::default();
+        for (row, record) in read_import_file(path)? {
+            let name = record.as_ref().ok().map(|record| record.name.clone());
+            let fail = |error: String| PatternImportFailure {
+                row,
+                name: name.clone().filter(|name| !name.is_empty()),
+                error,
+            };
+
+            let request = match record.and_then(|record| import_request(record, path)) {
+                Ok(request) => request,
+                Err(error) => {
+                    report.failures.push(fail(error));
+                    continue;
+                }
+            };
+            match self.create_pattern(request) {
+                Ok(response) if response.success => {
+                    report.created.extend(response.pattern_id);
+                    report
+                        .warnings
+                        .extend(response.warnings.into_iter().map(|warning| (row, warning)));
+                }
+                Ok(response) => report.failures.push(fail(
+                    response.error.unwrap_or_else(|| "rejected".to_string()),
+                )),
+                Err(e) => report.failures.push(fail(e.to_string())),
+            }
+        }
+        Ok(report)
+    }
+
     /// Validates a pattern creation request.
     fn validate_pattern_request(&self, request: &PatternCreationRequest) -> Result<Vec<String>> {
         let mut warnings = Vec::new();
@@ -533,6 +790,7 @@ impl PatternLearningManager {
 
         Some(Playbook {
             name: format!("Learned {} Patterns", language.name()),
+            namespace: Some(LEARNED_NAMESPACE.to_string()),
             version: "1.0.0".to_string(),
             language: language.name().to_string(),
             author: "Sniff Learning System".to_string(),
@@ -548,3 +806,72 @@ impl PatternLearningManager {
         &self.sniff_path
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_import_patterns() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = PatternLearningManager::new(temp_dir.path()).unwrap();
+
+        let csv_path = temp_dir.path().join("findings.csv");
+        std::fs::write(
+            &csv_path,
+            "name,regex,language,severity,examples\n\
+             Debug print,println!\\(,rust,low,\"println!(\"\"x\"\")\"\n\
+             Broken,([a-z,python,high,\n\
+             Elixir TODO,TODO,elixir,low,\n",
+        )
+        .unwrap();
+        let report = manager.import_patterns(&csv_path).unwrap();
+        assert_eq!(report.created.len(), 1);
+        assert_eq!(report.failures.len(), 2);
+        assert_eq!(report.failures[0].row, 2);
+        assert_eq!(report.failures[0].name.as_deref(), Some("Broken"));
+        assert!(report.failures[1].error.contains("unknown language"));
+
+        let rust_patterns = manager.get_patterns_for_language(SupportedLanguage::Rust);
+        assert_eq!(rust_patterns.len(), 1);
+        assert_eq!(rust_patterns[0].rule.severity, Severity::Low);
+        assert_eq!(rust_patterns[0].rule.examples, vec!["println!(\"x\")"]);
+
+        let json_path = temp_dir.path().join("findings.json");
+        std::fs::write(
+            &json_path,
+            r#"[{"name": "Bare except", "pattern": "except:", "language": "python", "scope": "function_body"},
+                {"name": "No regex", "language": "python"}]"#,
+        )
+        .unwrap();
+        let report = manager.import_patterns(&json_path).unwrap();
+        assert_eq!(report.created.len(), 1);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].row, 2);
+        let python_patterns = manager.get_patterns_for_language(SupportedLanguage::Python);
+        assert_eq!(python_patterns[0].rule.scope, PatternScope::FunctionBody);
+    }
+
+    #[test]
+    fn test_open_read_only() {
+        let empty = TempDir::new().unwrap();
+        let manager = PatternLearningManager::open_read_only(empty.path()).unwrap();
+        assert!(manager.to_playbook(SupportedLanguage::Rust).is_none());
+        assert!(!empty.path().join(".sniff").exists());
+
+        let temp_dir = TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("findings.csv");
+        std::fs::write(
+            &csv_path,
+            "name,regex,language,severity,examples\nDebug print,println!\\(,rust,low,\n",
+        )
+        .unwrap();
+        let mut manager = PatternLearningManager::new(temp_dir.path()).unwrap();
+        manager.import_patterns(&csv_path).unwrap();
+
+        let manager = PatternLearningManager::open_read_only(temp_dir.path()).unwrap();
+        let rust_patterns = manager.get_patterns_for_language(SupportedLanguage::Rust);
+        assert_eq!(rust_patterns.len(), 1);
+    }
+}
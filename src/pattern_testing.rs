@@ -0,0 +1,392 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Runs playbook rules against their own test cases.
+//!
+//! Every rule may list `examples` it must flag and `false_positives` it must
+//! not. `sniff patterns test` (and `sniff patterns validate`) applies each rule
+//! to each case as a snippet of the rule's language and reports the cases the
+//! rule gets wrong. Snippets need not be complete functions: a function body
+//! rule applies to a bare statement as if it were the body.
+//!
+//! Golden tests (`sniff patterns test --golden <dir>`) pin down the behavior of
+//! the whole ruleset instead: every source file in the directory is analyzed
+//! and its detections compared with the `<file>.expected.yaml` next to it.
+
+use crate::analysis::{MisalignmentAnalyzer, MisalignmentDetection, SupportedLanguage};
+use crate::error::{Result, SniffError};
+use crate::playbook::{DetectionRule, PatternScope, Severity};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Suffix of the golden file holding a source file's expected detections.
+pub const GOLDEN_SUFFIX: &str = ".expected.yaml";
+
+/// Kind of rule test case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaseKind {
+    /// Code the rule must flag.
+    Example,
+    /// Code the rule must not flag.
+    FalsePositive,
+}
+
+/// A test case a rule got wrong.
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseFailure {
+    /// Kind of test case.
+    pub kind: CaseKind,
+    /// Code of the test case.
+    pub code: String,
+    /// Why the case failed.
+    pub reason: String,
+}
+
+/// Outcome of running a rule against its test cases.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleTestResult {
+    /// Namespaced rule id (`namespace/rule_id`).
+    pub qualified_id: String,
+    /// Language of the rule's playbook.
+    pub language: String,
+    /// Number of test cases run.
+    pub cases: usize,
+    /// Test cases the rule got wrong.
+    pub failures: Vec<CaseFailure>,
+}
+
+impl RuleTestResult {
+    /// Returns true if the rule passed all its test cases.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Runs a rule against its examples and false positives.
+///
+/// # Errors
+///
+/// Returns an error if the analyzer fails to set up a snippet.
+pub fn test_rule(
+    analyzer: &mut MisalignmentAnalyzer,
+    rule: &DetectionRule,
+    language: SupportedLanguage,
+) -> Result<Vec<CaseFailure>> {
+    let cases = rule
+        .examples
+        .iter()
+        .map(|code| (CaseKind::Example, code))
+        .chain(
+            rule.false_positives
+                .iter()
+                .map(|code| (CaseKind::FalsePositive, code)),
+        );
+
+    let mut failures = Vec::new();
+    for (kind, code) in cases {
+        let reason = match analyzer.apply_rule_to_snippet(rule, language, code) {
+            Ok(detections) if kind == CaseKind::Example && detections.is_empty() => {
+                "not flagged".to_string()
+            }
+            Ok(detections) if kind == CaseKind::FalsePositive && !detections.is_empty() => {
+                format!("flagged at line {}", detections[0].line_number)
+            }
+            Ok(_) => continue,
+            Err(e) => e.to_string(),
+        };
+        failures.push(CaseFailure {
+            kind,
+            code: code.clone(),
+            reason,
+        });
+    }
+
+    Ok(failures)
+}
+
+/// Runs every loaded rule of the given languages that has test cases.
+///
+/// Shadowed and disabled rules are tested too, since they can be re-enabled.
+/// Thinking rules apply to session transcripts rather than code and are
+/// skipped.
+///
+/// # Errors
+///
+/// Returns an error if the analyzer fails to set up a snippet.
+pub fn test_rules(
+    analyzer: &mut MisalignmentAnalyzer,
+    languages: &[SupportedLanguage],
+) -> Result<Vec<RuleTestResult>> {
+    let mut results = Vec::new();
+    for language in languages {
+        let rules: Vec<(DetectionRule, String)> = analyzer
+            .playbook_manager()
+            .rule_origins(*language)
+            .into_iter()
+            .filter(|(rule, _, _)| {
+                rule.scope != PatternScope::Thinking
+                    && !(rule.examples.is_empty() && rule.false_positives.is_empty())
+            })
+            .map(|(rule, origin, _)| (rule.clone(), origin.qualified_id))
+            .collect();
+
+        for (rule, qualified_id) in rules {
+            let failures = test_rule(analyzer, &rule, *language)?;
+            results.push(RuleTestResult {
+                qualified_id,
+                language: language.name().to_string(),
+                cases: rule.examples.len() + rule.false_positives.len(),
+                failures,
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+/// A detection as recorded in a golden file.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ExpectedDetection {
+    /// 1-based line of the detection.
+    pub line: usize,
+    /// Id of the rule that fired.
+    pub rule: String,
+    /// Reported severity.
+    pub severity: Severity,
+}
+
+impl From<&MisalignmentDetection> for ExpectedDetection {
+    fn from(detection: &MisalignmentDetection) -> Self {
+        Self {
+            line: detection.line_number,
+            rule: detection.rule_id.clone(),
+            severity: detection.severity,
+        }
+    }
+}
+
+/// Outcome of comparing a source file's detections with its golden file.
+#[derive(Debug, Clone, Serialize)]
+pub struct GoldenResult {
+    /// Source file, relative to the golden directory.
+    pub input: PathBuf,
+    /// Expected detections that were not reported.
+    pub missing: Vec<ExpectedDetection>,
+    /// Reported detections that were not expected.
+    pub unexpected: Vec<ExpectedDetection>,
+    /// Why the file could not be compared (no golden file, analysis failed).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Whether the golden file was written with the current detections.
+    pub updated: bool,
+}
+
+impl GoldenResult {
+    /// Returns true if the detections matched the golden file.
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty() && self.error.is_none()
+    }
+}
+
+/// Returns the path of a source file's golden file.
+#[must_use]
+pub fn golden_path(input: &Path) -> PathBuf {
+    let mut name = input.file_name().unwrap_or_default().to_os_string();
+    name.push(GOLDEN_SUFFIX);
+    input.with_file_name(name)
+}
+
+/// Analyzes every source file below `dir` in one of `languages` and compares
+/// its detections with its golden file. With `update`, golden files that are
+/// missing or differ are rewritten with the current detections instead.
+///
+/// Files are analyzed by their path relative to `dir`, so rule path filters
+/// see the layout of the golden directory. Detections suppressed in test
+/// context are not compared.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read or a golden file cannot
+/// be written.
+pub fn run_golden(
+    analyzer: &mut MisalignmentAnalyzer,
+    dir: &Path,
+    languages: &[SupportedLanguage],
+    update: bool,
+) -> Result<Vec<GoldenResult>> {
+    let mut inputs = Vec::new();
+    for entry in walkdir::WalkDir::new(dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| {
+            SniffError::analysis_error(format!("Failed to read {}: {e}", dir.display()))
+        })?;
+        let path = entry.path();
+        let is_golden = path.to_string_lossy().ends_with(GOLDEN_SUFFIX);
+        if !entry.file_type().is_file() || is_golden {
+            continue;
+        }
+        if analyzer
+            .detect_language(path)?
+            .is_some_and(|language| languages.contains(&language))
+        {
+            inputs.push(path.to_path_buf());
+        }
+    }
+
+    let mut results = Vec::new();
+    for path in inputs {
+        let input = path.strip_prefix(dir).unwrap_or(&path).to_path_buf();
+        let mut result = GoldenResult {
+            input: input.clone(),
+            missing: Vec::new(),
+            unexpected: Vec::new(),
+            error: None,
+            updated: false,
+        };
+
+        let detections = std::fs::read_to_string(&path)
+            .map_err(|e| SniffError::file_system(&path, e))
+            .and_then(|content| analyzer.analyze_content(&content, &input));
+        let mut actual: Vec<ExpectedDetection> = match detections {
+            Ok(detections) => detections.iter().map(ExpectedDetection::from).collect(),
+            Err(e) => {
+                result.error = Some(e.to_string());
+                results.push(result);
+                continue;
+            }
+        };
+        actual.sort();
+
+        let golden = golden_path(&path);
+        let expected = if golden.exists() {
+            let content = std::fs::read_to_string(&golden)
+                .map_err(|e| SniffError::file_system(&golden, e))?;
+            match serde_yaml::from_str::<Option<Vec<ExpectedDetection>>>(&content) {
+                Ok(expected) => Some(expected.unwrap_or_default()),
+                Err(e) => {
+                    result.error = Some(format!("Invalid golden file: {e}"));
+                    None
+                }
+            }
+        } else {
+            result.error = Some(format!(
+                "No golden file {}; run with --update to create it",
+                golden.display()
+            ));
+            None
+        };
+
+        if let Some(mut expected) = expected {
+            expected.sort();
+            let (missing, unexpected) = multiset_difference(&expected, &actual);
+            result.missing = missing;
+            result.unexpected = unexpected;
+        }
+
+        if update && !result.passed() {
+            let content = serde_yaml::to_string(&actual)
+                .map_err(|e| SniffError::invalid_format("golden file", e.to_string()))?;
+            std::fs::write(&golden, content).map_err(|e| SniffError::file_system(&golden, e))?;
+            result.missing.clear();
+            result.unexpected.clear();
+            result.error = None;
+            result.updated = true;
+        }
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+/// Returns the entries only in `expected` and only in `actual`, counting
+/// repeated entries. Both must be sorted.
+fn multiset_difference(
+    expected: &[ExpectedDetection],
+    actual: &[ExpectedDetection],
+) -> (Vec<ExpectedDetection>, Vec<ExpectedDetection>) {
+    let (mut missing, mut unexpected) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        match (expected.get(i), actual.get(j)) {
+            (Some(want), Some(got)) if want == got => {
+                i += 1;
+                j += 1;
+            }
+            (Some(want), Some(got)) if want < got => {
+                missing.push(want.clone());
+                i += 1;
+            }
+            (Some(want), None) => {
+                missing.push(want.clone());
+                i += 1;
+            }
+            (_, Some(got)) => {
+                unexpected.push(got.clone());
+                j += 1;
+            }
+            (None, None) => break,
+        }
+    }
+    (missing, unexpected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support;
+    use crate::playbook::Severity;
+
+    fn unwrap_rule(false_positives: &[&str]) -> DetectionRule {
+        DetectionRule {
+            severity: Severity::Medium,
+            examples: vec![
+                "let value = input.parse::<u32>().unwrap();".to_string(),
+                "let value = input.expect(\"value\");".to_string(),
+            ],
+            false_positives: false_positives.iter().map(ToString::to_string).collect(),
+            paths: vec!["src/never/**".to_string()],
+            ..test_support::rule("unwrap_call", r"\.unwrap\(\)")
+        }
+    }
+
+    #[test]
+    fn test_rule_reports_failing_cases() {
+        let mut analyzer = MisalignmentAnalyzer::new().unwrap();
+        let rule = unwrap_rule(&["let value = input?;", "// avoid .unwrap() here"]);
+
+        let failures = test_rule(&mut analyzer, &rule, SupportedLanguage::Rust).unwrap();
+        assert_eq!(failures.len(), 2, "{failures:?}");
+        assert_eq!(failures[0].kind, CaseKind::Example);
+        assert!(failures[0].code.contains("expect"));
+        assert_eq!(failures[0].reason, "not flagged");
+        assert_eq!(failures[1].kind, CaseKind::FalsePositive);
+        assert_eq!(failures[1].reason, "flagged at line 1");
+    }
+
+    #[test]
+    fn test_golden_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("notes.rs");
+        std::fs::write(&input, "// TODO: parse the header\nconst A: u8 = 1;\n").unwrap();
+        let mut analyzer = MisalignmentAnalyzer::new().unwrap();
+        let languages = [SupportedLanguage::Rust];
+
+        let results = run_golden(&mut analyzer, dir.path(), &languages, false).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].error.as_ref().unwrap().contains("--update"));
+
+        let results = run_golden(&mut analyzer, dir.path(), &languages, true).unwrap();
+        assert!(results[0].updated);
+        let golden = std::fs::read_to_string(golden_path(&input)).unwrap();
+        assert!(golden.contains("rule: rust_todo_comment"), "{golden}");
+
+        // A ruleset change shows up as a difference
+        std::fs::write(&input, "const A: u8 = 1;\n// TODO: parse the header\n").unwrap();
+        let results = run_golden(&mut analyzer, dir.path(), &languages, false).unwrap();
+        assert_eq!(results[0].missing.len(), 1);
+        assert_eq!(results[0].missing[0].line, 1);
+        assert_eq!(results[0].unexpected[0].line, 2);
+    }
+}
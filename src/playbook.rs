@@ -5,13 +5,17 @@
 
 use crate::analysis::SupportedLanguage;
 use crate::error::{Result, SniffError};
+use crate::prefilter::RegexPrefilter;
+use crate::structural::StructuralAnalysis;
+use chrono::NaiveDate;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Severity level for detected bullshit patterns.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Severity {
     /// Informational severity - minor notes that don't require action
     Info,
@@ -61,6 +65,61 @@ impl Severity {
             Severity::Critical => "Critical",
         }
     }
+
+    /// Parses a severity name (`info`, `low`, `medium`, `high`, `critical`), ignoring case.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "info" => Some(Severity::Info),
+            "low" => Some(Severity::Low),
+            "medium" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+}
+
+/// A team's override of a rule's default severity, or `off` to disable it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum RuleOverride {
+    /// The rule does not run.
+    Off,
+    /// The rule runs with this severity.
+    Severity(Severity),
+}
+
+impl RuleOverride {
+    /// Parses `off` or a severity name, ignoring case.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("off") {
+            Some(Self::Off)
+        } else {
+            Severity::from_name(name).map(Self::Severity)
+        }
+    }
+
+    /// Applies the override to a rule.
+    fn apply(self, rule: &mut DetectionRule) {
+        match self {
+            Self::Off => rule.enabled = false,
+            Self::Severity(severity) => rule.severity = severity,
+        }
+    }
+}
+
+impl TryFrom<String> for RuleOverride {
+    type Error = String;
+
+    fn try_from(name: String) -> std::result::Result<Self, Self::Error> {
+        Self::from_name(&name).ok_or_else(|| {
+            format!(
+                "invalid rule override '{name}', expected off, info, low, medium, high or critical"
+            )
+        })
+    }
 }
 
 /// Scope where a pattern should be applied.
@@ -72,10 +131,40 @@ pub enum PatternScope {
     FunctionBody,
     /// Apply only within class bodies.
     ClassBody,
+    /// Apply only to top-level module or namespace code, outside any function or method.
+    ModuleBody,
     /// Apply only within comments.
     Comments,
     /// Apply only within method signatures.
     MethodSignature,
+    /// Apply only to assistant thinking blocks in session transcripts, never to code.
+    Thinking,
+}
+
+impl PatternScope {
+    /// Parses a scope name in `snake_case` (`function_body`) or as written in
+    /// playbooks (`FunctionBody`).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.replace('_', "").to_lowercase().as_str() {
+            "file" => Some(Self::File),
+            "functionbody" => Some(Self::FunctionBody),
+            "classbody" => Some(Self::ClassBody),
+            "modulebody" => Some(Self::ModuleBody),
+            "comments" => Some(Self::Comments),
+            "methodsignature" => Some(Self::MethodSignature),
+            "thinking" => Some(Self::Thinking),
+            _ => None,
+        }
+    }
+
+    /// Returns true if multiline regexes can be matched in this scope. Only
+    /// files, functions and classes are contiguous spans of text; the other
+    /// scopes are matched line by line.
+    #[must_use]
+    pub fn supports_multiline(&self) -> bool {
+        matches!(self, Self::File | Self::FunctionBody | Self::ClassBody)
+    }
 }
 
 /// Type of pattern matching to perform.
@@ -87,6 +176,11 @@ pub enum PatternType {
         pattern: String,
         /// Optional regex flags (i, m, s, etc.)
         flags: Option<String>,
+        /// Match against the whole text in scope instead of line by line, so
+        /// patterns can span lines. Only `File`, `FunctionBody` and `ClassBody`
+        /// scopes support this.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        multiline: bool,
     },
     /// `TreeSitter` AST query pattern.
     AstQuery {
@@ -100,6 +194,18 @@ pub enum PatternType {
         /// Additional parameters for the analysis
         parameters: HashMap<String, String>,
     },
+    /// Experimental WASM plugin that receives the file content and symbols and
+    /// reports matches (see [`crate::plugin`]).
+    Wasm {
+        /// File name of the module in `.sniff/plugins/`
+        module: String,
+    },
+    /// Rhai script run once per file, flagging lines through a sandboxed API
+    /// over the lines, symbols and regex helpers (see [`crate::script`]).
+    Script {
+        /// Script source
+        script: String,
+    },
 }
 
 /// A single detection rule within a playbook.
@@ -125,6 +231,206 @@ pub struct DetectionRule {
     pub examples: Vec<String>,
     /// False positive examples that should NOT trigger this rule.
     pub false_positives: Vec<String>,
+    /// Glob patterns of the files this rule applies to (e.g. `src/api/**`); all files if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Glob patterns of files this rule never applies to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_paths: Vec<String>,
+    /// Further patterns a match must satisfy to be reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conditions: Option<RuleConditions>,
+    /// Whether this rule is deprecated. Deprecated rules still run, but stop
+    /// counting toward quality gates once their grace period ends.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deprecated: bool,
+    /// Id of the rule that supersedes this one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
+    /// Date the rule was deprecated, from which the grace period runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated_since: Option<NaiveDate>,
+    /// How to fix code that triggers this rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<Remediation>,
+    /// CWE identifiers of the weakness this rule detects (e.g. `CWE-798`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cwe: Vec<String>,
+    /// OWASP Top 10 categories this rule maps to (e.g. `A07:2021`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owasp: Vec<String>,
+    /// Links to further reading about the weakness.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+}
+
+/// Days a deprecated rule keeps counting toward quality gates, unless configured.
+pub const DEFAULT_DEPRECATION_GRACE_DAYS: u32 = 90;
+
+impl DetectionRule {
+    /// Returns an error if the rule is a multiline regex in a scope that does
+    /// not support multiline matching.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the rule and its scope.
+    pub fn check_multiline_scope(&self) -> Result<()> {
+        match &self.pattern_type {
+            PatternType::Regex {
+                multiline: true, ..
+            } if !self.scope.supports_multiline() => Err(SniffError::invalid_format(
+                "playbook validation".to_string(),
+                format!(
+                    "Multiline regex rule '{}' uses the {:?} scope, but multiline matching only \
+                     supports File, FunctionBody and ClassBody; drop `multiline` to match \
+                     {:?} line by line",
+                    self.id, self.scope, self.scope
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the last day a deprecated rule counts toward quality gates, or
+    /// `None` if it does not count at all (no `deprecated_since` date).
+    #[must_use]
+    pub fn grace_period_end(&self, grace_days: u32) -> Option<NaiveDate> {
+        self.deprecated_since
+            .and_then(|since| since.checked_add_days(chrono::Days::new(u64::from(grace_days))))
+    }
+
+    /// Returns true if detections of this rule count toward quality gates on `today`.
+    #[must_use]
+    pub fn gates_on(&self, today: NaiveDate, grace_days: u32) -> bool {
+        !self.deprecated
+            || self
+                .grace_period_end(grace_days)
+                .is_some_and(|end| today <= end)
+    }
+
+    /// Returns the rule's security taxonomy, or `None` if it has no CWE,
+    /// OWASP or reference metadata.
+    #[must_use]
+    pub fn taxonomy(&self) -> Option<SecurityTaxonomy> {
+        if self.cwe.is_empty() && self.owasp.is_empty() && self.references.is_empty() {
+            return None;
+        }
+        Some(SecurityTaxonomy {
+            cwe: self.cwe.clone(),
+            owasp: self.owasp.clone(),
+            references: self.references.clone(),
+        })
+    }
+}
+
+/// Returns true if `id` has the form `CWE-<number>`.
+fn is_cwe_id(id: &str) -> bool {
+    id.strip_prefix("CWE-")
+        .is_some_and(|number| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Security taxonomy of a rule, carried by its detections for compliance
+/// reporting.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityTaxonomy {
+    /// CWE identifiers (e.g. `CWE-798`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cwe: Vec<String>,
+    /// OWASP Top 10 categories (e.g. `A07:2021`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owasp: Vec<String>,
+    /// Links to further reading.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+}
+
+/// Remediation guidance for a detection rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Remediation {
+    /// Why the flagged code is a problem and how to fix it.
+    pub explanation: String,
+    /// Corrected code example.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<String>,
+    /// Replacement for the matched text, applied by `analyze-files --fix`.
+    /// `$1` or `${name}` insert capture groups of the rule's regex. Only
+    /// single-line regex rules support replacements.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+/// Conditions combining a rule's pattern with further regexes.
+///
+/// A match is only reported if every `all` pattern and at least one `any`
+/// pattern also match in the same scope: the enclosing function for
+/// `FunctionBody` and `MethodSignature` rules, the enclosing class for
+/// `ClassBody` rules and the whole file otherwise. A match is suppressed if a
+/// `not` pattern matches within `within` lines of it, or anywhere in the
+/// scope if `within` is not set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConditions {
+    /// Patterns that must all match in the same scope.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub all: Vec<String>,
+    /// Patterns of which at least one must match in the same scope.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub any: Vec<String>,
+    /// Patterns that suppress the match when present nearby.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub not: Vec<String>,
+    /// Lines before and after the match searched for `not` patterns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub within: Option<usize>,
+}
+
+/// [`RuleConditions`] with compiled patterns.
+#[derive(Debug, Clone)]
+pub struct CompiledConditions {
+    all: Vec<Regex>,
+    any: Vec<Regex>,
+    not: Vec<Regex>,
+    within: Option<usize>,
+}
+
+impl RuleConditions {
+    /// Compiles the condition patterns.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first pattern that is not a valid regex.
+    pub fn compile(&self) -> std::result::Result<CompiledConditions, regex::Error> {
+        let compile = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern))
+                .collect::<std::result::Result<Vec<_>, _>>()
+        };
+        Ok(CompiledConditions {
+            all: compile(&self.all)?,
+            any: compile(&self.any)?,
+            not: compile(&self.not)?,
+            within: self.within,
+        })
+    }
+}
+
+impl CompiledConditions {
+    /// Lines before and after a match searched for `not` patterns, or `None`
+    /// to search the whole scope.
+    #[must_use]
+    pub fn within(&self) -> Option<usize> {
+        self.within
+    }
+
+    /// Returns true if a match whose scope is `scope` and whose surroundings
+    /// are `nearby` meets the conditions.
+    #[must_use]
+    pub fn holds(&self, scope: &str, nearby: &str) -> bool {
+        self.all.iter().all(|regex| regex.is_match(scope))
+            && (self.any.is_empty() || self.any.iter().any(|regex| regex.is_match(scope)))
+            && !self.not.iter().any(|regex| regex.is_match(nearby))
+    }
 }
 
 /// A collection of detection rules for a specific language.
@@ -132,6 +438,10 @@ pub struct DetectionRule {
 pub struct Playbook {
     /// Name of this playbook.
     pub name: String,
+    /// Namespace that qualifies rule ids as `namespace/rule_id`; defaults to a
+    /// slug of the name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
     /// Version of this playbook.
     pub version: String,
     /// Programming language this playbook targets.
@@ -146,12 +456,249 @@ pub struct Playbook {
     pub metadata: HashMap<String, String>,
 }
 
+/// Language name of playbooks that hold session (thinking) rules rather than code rules.
+pub const SESSION_PLAYBOOK_LANGUAGE: &str = "session";
+
+/// Namespace of the playbooks compiled into sniff.
+pub const BUILTIN_NAMESPACE: &str = "builtin";
+
+/// Playbooks shipped with sniff, installed by `sniff init` as `<language>-patterns.yaml`.
+///
+/// The Swift, Terraform, Scala, Config and Markdown playbooks are also the
+/// built-in rules of those languages, which have no hand-written defaults.
+pub const BUNDLED_PLAYBOOKS: &[(SupportedLanguage, &str)] = &[
+    (
+        SupportedLanguage::Rust,
+        include_str!("../playbooks/rust-patterns.yaml"),
+    ),
+    (
+        SupportedLanguage::Python,
+        include_str!("../playbooks/python-patterns.yaml"),
+    ),
+    (
+        SupportedLanguage::TypeScript,
+        include_str!("../playbooks/typescript-patterns.yaml"),
+    ),
+    (
+        SupportedLanguage::Swift,
+        include_str!("../playbooks/swift-patterns.yaml"),
+    ),
+    (
+        SupportedLanguage::Terraform,
+        include_str!("../playbooks/terraform-patterns.yaml"),
+    ),
+    (
+        SupportedLanguage::Scala,
+        include_str!("../playbooks/scala-patterns.yaml"),
+    ),
+    (
+        SupportedLanguage::Config,
+        include_str!("../playbooks/config-patterns.yaml"),
+    ),
+    (
+        SupportedLanguage::Markdown,
+        include_str!("../playbooks/markdown-patterns.yaml"),
+    ),
+];
+
+impl Playbook {
+    /// Returns the namespace of this playbook: the explicit one, or a slug of the name.
+    #[must_use]
+    pub fn namespace(&self) -> String {
+        if let Some(namespace) = &self.namespace {
+            return namespace.clone();
+        }
+        let slug: String = self
+            .name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        slug.split('-')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Returns the namespaced id (`namespace/rule_id`) of one of this playbook's rules.
+    #[must_use]
+    pub fn qualified_id(&self, rule_id: &str) -> String {
+        format!("{}/{rule_id}", self.namespace())
+    }
+}
+
+/// Where a playbook came from, in increasing order of precedence.
+///
+/// When playbooks of the same language define the same rule id, the rule from
+/// the highest source stays active and the others are shadowed (disabled). Among
+/// playbooks of the same source, the first one loaded wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleSource {
+    /// Playbooks compiled into sniff.
+    BuiltIn,
+    /// Playbook files loaded from a patterns directory.
+    Pack,
+    /// Patterns learned or imported into `.sniff/`.
+    Learned,
+}
+
+impl RuleSource {
+    /// Returns the display name of this source.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::BuiltIn => "built-in",
+            Self::Pack => "pack",
+            Self::Learned => "learned",
+        }
+    }
+}
+
+/// One definition of a rule id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleOrigin {
+    /// Namespaced rule id (`namespace/rule_id`).
+    pub qualified_id: String,
+    /// Name of the playbook that defines the rule.
+    pub playbook: String,
+    /// Where the playbook came from.
+    pub source: RuleSource,
+}
+
+/// A rule id defined by more than one playbook of a language.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleConflict {
+    /// The colliding rule id.
+    pub rule_id: String,
+    /// Language of the playbooks (or `session`).
+    pub language: String,
+    /// Definition that is applied.
+    pub active: RuleOrigin,
+    /// Definitions that are shadowed, in load order.
+    pub shadowed: Vec<RuleOrigin>,
+}
+
+/// Every definition of a rule id within one language, and which one is active.
+#[derive(Debug, Clone)]
+struct RuleSlot {
+    /// Definitions in load order, with the index of their playbook.
+    definitions: Vec<(RuleOrigin, usize)>,
+    /// Index into `definitions` of the active definition.
+    active: usize,
+}
+
+/// Compiled include and exclude path globs, such as the `paths` and
+/// `exclude_paths` of a rule.
+#[derive(Debug, Clone)]
+pub(crate) struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl PathFilter {
+    /// Compiles path globs, or returns `None` if there are none and every file
+    /// matches. `owner` names where the globs come from in errors.
+    pub(crate) fn compile(
+        include: &[String],
+        exclude: &[String],
+        owner: &str,
+    ) -> Result<Option<Self>> {
+        if include.is_empty() && exclude.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            include: Self::glob_set(include, owner)?,
+            exclude: Self::glob_set(exclude, owner)?,
+        }))
+    }
+
+    /// Compiles the path globs of a rule.
+    fn for_rule(rule: &DetectionRule) -> Result<Option<Self>> {
+        Self::compile(
+            &rule.paths,
+            &rule.exclude_paths,
+            &format!("rule '{}'", rule.id),
+        )
+    }
+
+    fn glob_set(patterns: &[String], owner: &str) -> Result<Option<GlobSet>> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+        let invalid = |pattern: &str, e: globset::Error| {
+            SniffError::invalid_format(
+                "path glob".to_string(),
+                format!("Invalid path glob '{pattern}' in {owner}: {e}"),
+            )
+        };
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            // `*` stays within one directory; `**` crosses directories
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| invalid(pattern, e))?;
+            builder.add(glob);
+        }
+        builder
+            .build()
+            .map(Some)
+            .map_err(|e| invalid(&patterns.join(", "), e))
+    }
+
+    /// Returns true if a path, as returned by [`glob_match_path`], passes the globs.
+    pub(crate) fn matches(&self, path: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|globs| globs.is_match(path))
+            && !self
+                .exclude
+                .as_ref()
+                .is_some_and(|globs| globs.is_match(path))
+    }
+}
+
+/// Returns the path that path globs are matched against: relative to the
+/// current directory when possible, without a leading `./`.
+pub(crate) fn glob_match_path(path: &Path) -> PathBuf {
+    let relative = if path.is_absolute() {
+        std::env::current_dir()
+            .ok()
+            .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+            .unwrap_or_else(|| path.to_path_buf())
+    } else {
+        path.to_path_buf()
+    };
+    relative
+        .components()
+        .filter(|component| !matches!(component, std::path::Component::CurDir))
+        .collect()
+}
+
 /// Manages loading and organizing playbooks.
 pub struct PlaybookManager {
     /// Loaded playbooks organized by language.
     playbooks: HashMap<SupportedLanguage, Vec<Playbook>>,
+    /// Loaded session playbooks, whose rules apply to session transcripts.
+    session_playbooks: Vec<Playbook>,
     /// Compiled regex patterns for performance.
     compiled_patterns: HashMap<String, Regex>,
+    /// Compiled path globs of active rules that declare `paths` or `exclude_paths`.
+    path_filters: HashMap<(SupportedLanguage, String), PathFilter>,
+    /// Playbook files that failed to load from a directory, with the error.
+    load_failures: Vec<(PathBuf, String)>,
+    /// Per-rule severity overrides, applied to every loaded playbook.
+    rule_overrides: HashMap<String, RuleOverride>,
+    /// Enabled state and severity of each rule before overrides, keyed by
+    /// language name, playbook index and rule index, so replaced overrides
+    /// do not leave earlier ones behind.
+    rule_defaults: HashMap<(String, usize, usize), (bool, Severity)>,
+    /// Definitions of each rule id, keyed by language name and rule id.
+    rule_slots: HashMap<(String, String), RuleSlot>,
+    /// Prefilters over the active regex rules of each language, built on first
+    /// use and dropped whenever the rules change.
+    regex_prefilters: HashMap<SupportedLanguage, RegexPrefilter>,
 }
 
 impl PlaybookManager {
@@ -160,11 +707,18 @@ impl PlaybookManager {
     pub fn new() -> Self {
         Self {
             playbooks: HashMap::new(),
+            session_playbooks: Vec::new(),
             compiled_patterns: HashMap::new(),
+            path_filters: HashMap::new(),
+            load_failures: Vec::new(),
+            rule_overrides: HashMap::new(),
+            rule_defaults: HashMap::new(),
+            rule_slots: HashMap::new(),
+            regex_prefilters: HashMap::new(),
         }
     }
 
-    /// Loads a playbook from a YAML file.
+    /// Loads a playbook from a YAML file as a [`RuleSource::Pack`].
     pub fn load_playbook(&mut self, path: &Path) -> Result<()> {
         let content =
             std::fs::read_to_string(path).map_err(|e| SniffError::file_system(path, e))?;
@@ -194,6 +748,11 @@ impl PlaybookManager {
             }
         }
 
+        if playbook.language == SESSION_PLAYBOOK_LANGUAGE {
+            self.insert_playbook(None, playbook, RuleSource::Pack);
+            return Ok(());
+        }
+
         // Convert language name to SupportedLanguage
         let supported_language = match playbook.language.as_str() {
             "rust" => SupportedLanguage::Rust,
@@ -203,6 +762,11 @@ impl PlaybookManager {
             "go" => SupportedLanguage::Go,
             "c" => SupportedLanguage::C,
             "cpp" => SupportedLanguage::Cpp,
+            "swift" => SupportedLanguage::Swift,
+            "terraform" => SupportedLanguage::Terraform,
+            "scala" => SupportedLanguage::Scala,
+            "config" => SupportedLanguage::Config,
+            "markdown" => SupportedLanguage::Markdown,
             _ => {
                 return Err(SniffError::invalid_format(
                     "unsupported language".to_string(),
@@ -211,11 +775,7 @@ impl PlaybookManager {
             }
         };
 
-        // Add to playbooks
-        self.playbooks
-            .entry(supported_language)
-            .or_default()
-            .push(playbook);
+        self.insert_playbook(Some(supported_language), playbook, RuleSource::Pack);
 
         Ok(())
     }
@@ -226,17 +786,21 @@ impl PlaybookManager {
             return Ok(());
         }
 
-        let entries = std::fs::read_dir(dir).map_err(|e| SniffError::file_system(dir, e))?;
-
-        for entry in entries {
-            let entry = entry.map_err(|e| SniffError::file_system(dir, e))?;
+        let mut paths = std::fs::read_dir(dir)
+            .map_err(|e| SniffError::file_system(dir, e))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()
+            .map_err(|e| SniffError::file_system(dir, e))?;
+        // Load in name order, so ties between packs resolve the same way everywhere
+        paths.sort();
 
-            let path = entry.path();
+        for path in paths {
             if path.extension().and_then(|s| s.to_str()) == Some("yaml")
                 || path.extension().and_then(|s| s.to_str()) == Some("yml")
             {
                 if let Err(e) = self.load_playbook(&path) {
-                    eprintln!("Warning: Failed to load playbook {}: {}", path.display(), e);
+                    tracing::warn!("Failed to load playbook {}: {}", path.display(), e);
+                    self.load_failures.push((path, e.to_string()));
                 }
             }
         }
@@ -244,6 +808,13 @@ impl PlaybookManager {
         Ok(())
     }
 
+    /// Returns the playbook files that were skipped by [`Self::load_playbooks_from_dir`]
+    /// because they failed to load (e.g. an invalid regex), with the error.
+    #[must_use]
+    pub fn load_failures(&self) -> &[(PathBuf, String)] {
+        &self.load_failures
+    }
+
     /// Gets all playbooks for a specific language.
     #[must_use]
     pub fn get_playbooks_for_language(&self, language: SupportedLanguage) -> Vec<&Playbook> {
@@ -263,7 +834,7 @@ impl PlaybookManager {
             .collect()
     }
 
-    /// Gets all active rules for a specific language.
+    /// Gets all active rules for a specific language, with rule overrides applied.
     #[must_use]
     pub fn get_active_rules_for_language(
         &self,
@@ -276,15 +847,334 @@ impl PlaybookManager {
             .collect()
     }
 
+    /// Gets the active rules for a language whose `paths` and `exclude_paths`
+    /// globs select the file.
+    #[must_use]
+    pub fn get_active_rules_for_path(
+        &self,
+        language: SupportedLanguage,
+        path: &Path,
+    ) -> Vec<&DetectionRule> {
+        let match_path = glob_match_path(path);
+        self.get_active_rules_for_language(language)
+            .into_iter()
+            .filter(|rule| {
+                self.path_filters
+                    .get(&(language, rule.id.clone()))
+                    .is_none_or(|filter| filter.matches(&match_path))
+            })
+            .collect()
+    }
+
+    /// Returns the prefilter over the active regex rules of a language.
+    pub fn regex_prefilter(&mut self, language: SupportedLanguage) -> &RegexPrefilter {
+        if !self.regex_prefilters.contains_key(&language) {
+            let prefilter = RegexPrefilter::new(self.get_active_rules_for_language(language));
+            self.regex_prefilters.insert(language, prefilter);
+        }
+        &self.regex_prefilters[&language]
+    }
+
+    /// Gets a loaded code rule by id.
+    ///
+    /// A namespaced id (`namespace/rule_id`) selects that playbook's definition;
+    /// a plain id selects the active definition.
+    #[must_use]
+    pub fn get_rule(&self, rule_id: &str) -> Option<&DetectionRule> {
+        let qualified = rule_id.split_once('/').and_then(|(namespace, id)| {
+            self.playbooks
+                .values()
+                .flatten()
+                .filter(|playbook| playbook.namespace() == namespace)
+                .flat_map(|playbook| playbook.rules.iter())
+                .find(|rule| rule.id == id)
+        });
+        qualified.or_else(|| {
+            self.playbooks.iter().find_map(|(language, playbooks)| {
+                let slot = self
+                    .rule_slots
+                    .get(&(language.name().to_string(), rule_id.to_string()))?;
+                let (_, index) = &slot.definitions[slot.active];
+                playbooks[*index]
+                    .rules
+                    .iter()
+                    .find(|rule| rule.id == rule_id)
+            })
+        })
+    }
+
+    /// Returns the rule ids defined by more than one playbook of a language,
+    /// sorted by language and rule id.
+    #[must_use]
+    pub fn conflicts(&self) -> Vec<RuleConflict> {
+        let mut conflicts: Vec<RuleConflict> = self
+            .rule_slots
+            .iter()
+            .filter(|(_, slot)| slot.definitions.len() > 1)
+            .map(|((language, rule_id), slot)| RuleConflict {
+                rule_id: rule_id.clone(),
+                language: language.clone(),
+                active: slot.definitions[slot.active].0.clone(),
+                shadowed: slot
+                    .definitions
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| *index != slot.active)
+                    .map(|(_, (origin, _))| origin.clone())
+                    .collect(),
+            })
+            .collect();
+        conflicts.sort_by(|a, b| (&a.language, &a.rule_id).cmp(&(&b.language, &b.rule_id)));
+        conflicts
+    }
+
+    /// Returns the namespaced id and source of every loaded rule of a language,
+    /// with whether it is the active definition of its id.
+    #[must_use]
+    pub fn rule_origins(
+        &self,
+        language: SupportedLanguage,
+    ) -> Vec<(&DetectionRule, RuleOrigin, bool)> {
+        let Some(playbooks) = self.playbooks.get(&language) else {
+            return Vec::new();
+        };
+        self.rule_slots
+            .iter()
+            .filter(|((slot_language, _), _)| slot_language == language.name())
+            .flat_map(|((_, rule_id), slot)| {
+                slot.definitions.iter().enumerate().filter_map(
+                    move |(position, (origin, index))| {
+                        let rule = playbooks[*index]
+                            .rules
+                            .iter()
+                            .find(|rule| &rule.id == rule_id)?;
+                        Some((rule, origin.clone(), position == slot.active))
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Gets all active rules that apply to assistant thinking blocks.
+    ///
+    /// Thinking rules may live in session playbooks or in any language playbook.
+    #[must_use]
+    pub fn get_active_thinking_rules(&self) -> Vec<&DetectionRule> {
+        self.session_playbooks
+            .iter()
+            .chain(self.playbooks.values().flatten())
+            .flat_map(|playbook| playbook.rules.iter())
+            .filter(|rule| rule.enabled && rule.scope == PatternScope::Thinking)
+            .collect()
+    }
+
+    /// Returns a stable hash of all active rules across languages.
+    ///
+    /// Runs with the same hash applied the same rules, which makes gate decisions
+    /// comparable over time.
+    #[must_use]
+    pub fn ruleset_hash(&self) -> String {
+        let mut rules: Vec<(&'static str, String)> = self
+            .playbooks
+            .iter()
+            .flat_map(|(language, playbooks)| {
+                playbooks
+                    .iter()
+                    .flat_map(|playbook| playbook.rules.iter())
+                    .filter(|rule| rule.enabled)
+                    .map(move |rule| {
+                        // Go through `Value` so map keys serialize in sorted order
+                        let rule = serde_json::to_value(rule)
+                            .map(|value| value.to_string())
+                            .unwrap_or_default();
+                        (language.name(), rule)
+                    })
+            })
+            .collect();
+        rules.extend(
+            self.session_playbooks
+                .iter()
+                .flat_map(|playbook| playbook.rules.iter())
+                .filter(|rule| rule.enabled)
+                .map(|rule| {
+                    let rule = serde_json::to_value(rule)
+                        .map(|value| value.to_string())
+                        .unwrap_or_default();
+                    (SESSION_PLAYBOOK_LANGUAGE, rule)
+                }),
+        );
+        rules.sort();
+
+        crate::hash::stable_hash(
+            rules
+                .iter()
+                .flat_map(|(language, rule)| [*language, rule.as_str()]),
+        )
+    }
+
     /// Gets a compiled regex pattern for a rule.
     #[must_use]
     pub fn get_compiled_pattern(&self, rule_id: &str) -> Option<&Regex> {
         self.compiled_patterns.get(rule_id)
     }
 
-    /// Adds a playbook directly to the manager.
+    /// Adds a built-in playbook directly to the manager.
+    ///
+    /// Rules with invalid path globs are applied to every file.
     pub fn add_playbook(&mut self, language: SupportedLanguage, playbook: Playbook) {
-        self.playbooks.entry(language).or_default().push(playbook);
+        self.add_playbook_from(language, playbook, RuleSource::BuiltIn);
+    }
+
+    /// Adds a playbook from the given source directly to the manager.
+    ///
+    /// Rules with invalid path globs are applied to every file.
+    pub fn add_playbook_from(
+        &mut self,
+        language: SupportedLanguage,
+        playbook: Playbook,
+        source: RuleSource,
+    ) {
+        self.insert_playbook(Some(language), playbook, source);
+    }
+
+    /// Adds a built-in session playbook directly to the manager.
+    pub fn add_session_playbook(&mut self, playbook: Playbook) {
+        self.insert_playbook(None, playbook, RuleSource::BuiltIn);
+    }
+
+    /// Applies rule overrides to a playbook, resolves its rule ids against the
+    /// playbooks already loaded for the language (`None` for session playbooks)
+    /// and stores it.
+    fn insert_playbook(
+        &mut self,
+        language: Option<SupportedLanguage>,
+        mut playbook: Playbook,
+        source: RuleSource,
+    ) {
+        self.regex_prefilters.clear();
+
+        let language_name = language.map_or(SESSION_PLAYBOOK_LANGUAGE, |language| language.name());
+        let playbooks = match language {
+            Some(language) => self.playbooks.entry(language).or_default(),
+            None => &mut self.session_playbooks,
+        };
+        let index = playbooks.len();
+        let namespace = playbook.namespace();
+
+        for rule in &mut playbook.rules {
+            let origin = RuleOrigin {
+                qualified_id: format!("{namespace}/{}", rule.id),
+                playbook: playbook.name.clone(),
+                source,
+            };
+            let key = (language_name.to_string(), rule.id.clone());
+            let slot = self.rule_slots.entry(key).or_insert_with(|| RuleSlot {
+                definitions: Vec::new(),
+                active: 0,
+            });
+            slot.definitions.push((origin, index));
+            let position = slot.definitions.len() - 1;
+
+            if position > 0 {
+                let (active, active_index) = &slot.definitions[slot.active];
+                if source <= active.source {
+                    rule.enabled = false;
+                    continue;
+                }
+                // The new definition takes precedence over the active one
+                if let Some((shadowed_index, shadowed)) = playbooks[*active_index]
+                    .rules
+                    .iter_mut()
+                    .enumerate()
+                    .find(|(_, shadowed)| shadowed.id == rule.id)
+                {
+                    shadowed.enabled = false;
+                    let key = (language_name.to_string(), *active_index, shadowed_index);
+                    if let Some((enabled, _)) = self.rule_defaults.get_mut(&key) {
+                        *enabled = false;
+                    }
+                }
+                slot.active = position;
+            }
+
+            // Playbooks added directly skip validation; never fall back to
+            // matching a multiline rule line by line
+            if let Err(e) = rule.check_multiline_scope() {
+                tracing::warn!("{}", e);
+                rule.enabled = false;
+            }
+
+            if let Some(language) = language {
+                let key = (language, rule.id.clone());
+                match PathFilter::for_rule(rule) {
+                    Ok(Some(filter)) => {
+                        self.path_filters.insert(key, filter);
+                    }
+                    Ok(None) => {
+                        self.path_filters.remove(&key);
+                    }
+                    Err(e) => {
+                        tracing::warn!("{}", e);
+                        self.path_filters.remove(&key);
+                    }
+                }
+            }
+        }
+
+        for (rule_index, rule) in playbook.rules.iter().enumerate() {
+            self.rule_defaults.insert(
+                (language_name.to_string(), index, rule_index),
+                (rule.enabled, rule.severity),
+            );
+        }
+        Self::apply_rule_overrides(&self.rule_overrides, &mut playbook);
+        playbooks.push(playbook);
+    }
+
+    /// Sets per-rule overrides (`off` or a severity) and applies them to the
+    /// loaded playbooks and to playbooks loaded later. Keys are plain rule ids,
+    /// or namespaced ids (`namespace/rule_id`) that target a single playbook.
+    ///
+    /// Overridden rules are what [`Self::get_active_rules_for_language`] returns
+    /// and what [`Self::ruleset_hash`] covers, so teams can tune the default
+    /// playbooks without editing their YAML files.
+    pub fn set_rule_overrides(&mut self, overrides: HashMap<String, RuleOverride>) {
+        self.rule_overrides = overrides;
+        self.regex_prefilters.clear();
+        let playbooks = self
+            .playbooks
+            .iter_mut()
+            .map(|(language, playbooks)| (language.name(), playbooks))
+            .chain(std::iter::once((
+                SESSION_PLAYBOOK_LANGUAGE,
+                &mut self.session_playbooks,
+            )));
+        for (language_name, playbooks) in playbooks {
+            for (index, playbook) in playbooks.iter_mut().enumerate() {
+                // Start from the rules as loaded, so earlier overrides are undone
+                for (rule_index, rule) in playbook.rules.iter_mut().enumerate() {
+                    let key = (language_name.to_string(), index, rule_index);
+                    if let Some(&(enabled, severity)) = self.rule_defaults.get(&key) {
+                        rule.enabled = enabled;
+                        rule.severity = severity;
+                    }
+                }
+                Self::apply_rule_overrides(&self.rule_overrides, playbook);
+            }
+        }
+    }
+
+    /// Applies per-rule overrides to a playbook, preferring namespaced keys.
+    fn apply_rule_overrides(overrides: &HashMap<String, RuleOverride>, playbook: &mut Playbook) {
+        let namespace = playbook.namespace();
+        for rule in &mut playbook.rules {
+            let rule_override = overrides
+                .get(&format!("{namespace}/{}", rule.id))
+                .or_else(|| overrides.get(&rule.id));
+            if let Some(rule_override) = rule_override {
+                rule_override.apply(rule);
+            }
+        }
     }
 
     /// Validates a playbook for correctness.
@@ -303,16 +1193,124 @@ impl PlaybookManager {
             }
         }
 
-        // Validate regex patterns
+        // Session playbooks may only contain rules for session content
+        if playbook.language == SESSION_PLAYBOOK_LANGUAGE {
+            if let Some(rule) = playbook
+                .rules
+                .iter()
+                .find(|rule| rule.scope != PatternScope::Thinking)
+            {
+                return Err(SniffError::invalid_format(
+                    "playbook validation".to_string(),
+                    format!(
+                        "Rule '{}' in session playbook '{}' must use the Thinking scope",
+                        rule.id, playbook.name
+                    ),
+                ));
+            }
+        }
+
+        // Validate regex patterns, AST queries and path globs
+        let language = SupportedLanguage::from_name(&playbook.language);
         for rule in &playbook.rules {
-            if let PatternType::Regex { pattern, .. } = &rule.pattern_type {
-                Regex::new(pattern).map_err(|e| {
+            match &rule.pattern_type {
+                PatternType::Regex { pattern, .. } => {
+                    Regex::new(pattern).map_err(|e| {
+                        SniffError::invalid_format(
+                            "regex validation".to_string(),
+                            format!("Invalid regex in rule '{}': {}", rule.id, e),
+                        )
+                    })?;
+                    rule.check_multiline_scope()?;
+                }
+                PatternType::AstQuery { query } => {
+                    let Some(language) = language else {
+                        return Err(SniffError::invalid_format(
+                            "playbook validation".to_string(),
+                            format!(
+                                "AST query rule '{}' needs a playbook language with a grammar",
+                                rule.id
+                            ),
+                        ));
+                    };
+                    crate::ast_query::compile(language, query).map_err(|e| {
+                        SniffError::invalid_format(
+                            "AST query validation".to_string(),
+                            format!("Invalid AST query in rule '{}': {}", rule.id, e),
+                        )
+                    })?;
+                }
+                PatternType::Structural {
+                    analysis_type,
+                    parameters,
+                } => {
+                    StructuralAnalysis::parse(analysis_type, parameters).map_err(|e| {
+                        SniffError::invalid_format(
+                            "structural rule validation".to_string(),
+                            format!("Invalid structural rule '{}': {}", rule.id, e),
+                        )
+                    })?;
+                }
+                PatternType::Wasm { module } => {
+                    let path = Path::new(module);
+                    let is_file_name = path.file_name() == Some(module.as_ref());
+                    if !is_file_name || path.extension().is_none_or(|ext| ext != "wasm") {
+                        return Err(SniffError::invalid_format(
+                            "playbook validation".to_string(),
+                            format!(
+                                "WASM rule '{}' must name a .wasm file in .sniff/plugins, not '{}'",
+                                rule.id, module
+                            ),
+                        ));
+                    }
+                }
+                PatternType::Script { script } => {
+                    crate::script::compile(script).map_err(|e| {
+                        SniffError::invalid_format(
+                            "script validation".to_string(),
+                            format!("Invalid script in rule '{}': {}", rule.id, e),
+                        )
+                    })?;
+                }
+            }
+            let has_replacement = rule
+                .remediation
+                .as_ref()
+                .is_some_and(|remediation| remediation.replacement.is_some());
+            let single_line_regex = matches!(
+                rule.pattern_type,
+                PatternType::Regex {
+                    multiline: false,
+                    ..
+                }
+            );
+            if has_replacement && !single_line_regex {
+                return Err(SniffError::invalid_format(
+                    "playbook validation".to_string(),
+                    format!(
+                        "Rule '{}' has a replacement, which only single-line regex rules support",
+                        rule.id
+                    ),
+                ));
+            }
+            if let Some(cwe) = rule.cwe.iter().find(|cwe| !is_cwe_id(cwe)) {
+                return Err(SniffError::invalid_format(
+                    "playbook validation".to_string(),
+                    format!(
+                        "Rule '{}' has an invalid CWE identifier '{}', expected e.g. CWE-798",
+                        rule.id, cwe
+                    ),
+                ));
+            }
+            if let Some(conditions) = &rule.conditions {
+                conditions.compile().map_err(|e| {
                     SniffError::invalid_format(
                         "regex validation".to_string(),
-                        format!("Invalid regex in rule '{}': {}", rule.id, e),
+                        format!("Invalid condition in rule '{}': {}", rule.id, e),
                     )
                 })?;
             }
+            PathFilter::for_rule(rule)?;
         }
 
         Ok(())
@@ -329,10 +1327,16 @@ impl PlaybookManager {
             SupportedLanguage::Go => Self::create_go_default_rules(),
             SupportedLanguage::C => Self::create_c_default_rules(),
             SupportedLanguage::Cpp => Self::create_cpp_default_rules(),
+            SupportedLanguage::Swift => Self::bundled_rules(language),
+            SupportedLanguage::Terraform => Self::bundled_rules(language),
+            SupportedLanguage::Scala => Self::bundled_rules(language),
+            SupportedLanguage::Config => Self::bundled_rules(language),
+            SupportedLanguage::Markdown => Self::bundled_rules(language),
         };
 
         Playbook {
             name: format!("{} Default Patterns", language.name()),
+            namespace: Some(BUILTIN_NAMESPACE.to_string()),
             version: "1.0.0".to_string(),
             language: language.name().to_string(),
             author: "Sniff Core Team".to_string(),
@@ -345,6 +1349,100 @@ impl PlaybookManager {
         }
     }
 
+    /// Creates the default session playbook with rules for assistant thinking.
+    #[must_use]
+    pub fn create_session_default_playbook() -> Playbook {
+        let thinking_rule = |id: &str,
+                             name: &str,
+                             description: &str,
+                             severity,
+                             pattern: &str,
+                             tags: &[&str],
+                             example: &str| DetectionRule {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            severity,
+            pattern_type: PatternType::Regex {
+                pattern: pattern.to_string(),
+                flags: Some("i".to_string()),
+                multiline: false,
+            },
+            scope: PatternScope::Thinking,
+            enabled: true,
+            tags: tags.iter().map(ToString::to_string).collect(),
+            examples: vec![example.to_string()],
+            false_positives: vec![],
+            paths: Vec::new(),
+            exclude_paths: Vec::new(),
+            conditions: None,
+            deprecated: false,
+            replaced_by: None,
+            deprecated_since: None,
+            remediation: None,
+            cwe: Vec::new(),
+            owasp: Vec::new(),
+            references: Vec::new(),
+        };
+
+        Playbook {
+            name: "Session Default Patterns".to_string(),
+            namespace: Some(BUILTIN_NAMESPACE.to_string()),
+            version: "1.0.0".to_string(),
+            language: SESSION_PLAYBOOK_LANGUAGE.to_string(),
+            author: "Sniff Core Team".to_string(),
+            description: "Intent-level red flags in assistant thinking".to_string(),
+            rules: vec![
+                thinking_rule(
+                    "thinking_hardcode_intent",
+                    "Intent to Hardcode",
+                    "Assistant plans to hardcode values instead of implementing logic",
+                    Severity::High,
+                    r"(?i)\b(just|simply)\s+hard[- ]?code\b|hard[- ]?code\s+(it|this|that|the\s+\w+)\s+for\s+now",
+                    &["intent", "hardcoded"],
+                    "I'll just hardcode this for now",
+                ),
+                thinking_rule(
+                    "thinking_skip_tests_intent",
+                    "Intent to Skip Tests",
+                    "Assistant plans to skip, disable or weaken tests",
+                    Severity::High,
+                    r"(?i)\b(skip|disable|comment\s+out|remove|delete)\s+(the\s+|these\s+|those\s+)?(failing\s+)?tests?\b|\bmark\s+(it|them|the\s+tests?)\s+as\s+ignored\b",
+                    &["intent", "testing"],
+                    "Let me skip the tests since they keep failing",
+                ),
+                thinking_rule(
+                    "thinking_fake_success_intent",
+                    "Intent to Fake Success",
+                    "Assistant plans to make checks pass without fixing the underlying problem",
+                    Severity::Critical,
+                    r"(?i)\b(make|get)\s+(it|the\s+tests?|the\s+check)\s+(look\s+like\s+it\s+)?pass(es)?\s+(anyway|regardless)\b|\bpretend\s+(it|this|that)\s+(works|passed|succeeded)\b|\bfake\s+(the\s+)?(result|response|output|data)\b",
+                    &["intent", "deception"],
+                    "I'll pretend it works and move on",
+                ),
+                thinking_rule(
+                    "thinking_placeholder_intent",
+                    "Intent to Leave Placeholder",
+                    "Assistant plans to leave a stub or placeholder in place of a real implementation",
+                    Severity::Medium,
+                    r"(?i)\b(stub|placeholder|mock)\s+(it|this|that)\s+out\s+for\s+now\b|\bleave\s+(it|this)\s+as\s+a\s+(stub|placeholder|todo)\b|\bfor\s+now\s*,?\s*(i'?ll|let'?s)\s+just\b",
+                    &["intent", "placeholder"],
+                    "For now, I'll just return an empty list",
+                ),
+                thinking_rule(
+                    "thinking_bypass_check_intent",
+                    "Intent to Bypass Checks",
+                    "Assistant plans to bypass linting, type checking or verification",
+                    Severity::High,
+                    r"(?i)\b(bypass|suppress|silence|work\s+around)\s+(the\s+)?(linter|lint|type\s+check(er)?|verification|quality\s+gate|hook)\b|--no-verify\b",
+                    &["intent", "bypass"],
+                    "I can bypass the quality gate with --no-verify",
+                ),
+            ],
+            metadata: HashMap::new(),
+        }
+    }
+
     /// Creates default Rust detection rules.
     fn create_rust_default_rules() -> Vec<DetectionRule> {
         vec![
@@ -356,6 +1454,7 @@ impl PlaybookManager {
                 pattern_type: PatternType::Regex {
                     pattern: r"unimplemented!\(\)".to_string(),
                     flags: None,
+                    multiline: false,
                 },
                 scope: PatternScope::FunctionBody,
                 enabled: true,
@@ -364,6 +1463,16 @@ impl PlaybookManager {
                     "fn do_something() { unimplemented!() }".to_string(),
                 ],
                 false_positives: vec![],
+                paths: Vec::new(),
+                exclude_paths: Vec::new(),
+                conditions: None,
+                deprecated: false,
+                replaced_by: None,
+                deprecated_since: None,
+                remediation: None,
+                cwe: Vec::new(),
+                owasp: Vec::new(),
+                references: Vec::new(),
             },
             DetectionRule {
                 id: "rust_todo_comment".to_string(),
@@ -373,6 +1482,7 @@ impl PlaybookManager {
                 pattern_type: PatternType::Regex {
                     pattern: r"(?i)//\s*(TODO|FIXME|XXX|HACK):".to_string(),
                     flags: Some("i".to_string()),
+                    multiline: false,
                 },
                 scope: PatternScope::Comments,
                 enabled: true,
@@ -382,6 +1492,16 @@ impl PlaybookManager {
                     "// FIXME: handle errors".to_string(),
                 ],
                 false_positives: vec![],
+                paths: Vec::new(),
+                exclude_paths: Vec::new(),
+                conditions: None,
+                deprecated: false,
+                replaced_by: None,
+                deprecated_since: None,
+                remediation: None,
+                cwe: Vec::new(),
+                owasp: Vec::new(),
+                references: Vec::new(),
             },
             DetectionRule {
                 id: "rust_panic_with_todo".to_string(),
@@ -391,6 +1511,7 @@ impl PlaybookManager {
                 pattern_type: PatternType::Regex {
                     pattern: r#"panic!\s*\(\s*"[^"]*(?:TODO|FIXME|XXX|placeholder|not implemented)[^"]*"\s*\)"#.to_string(),
                     flags: Some("i".to_string()),
+                    multiline: false,
                 },
                 scope: PatternScope::FunctionBody,
                 enabled: true,
@@ -399,6 +1520,16 @@ impl PlaybookManager {
                     r#"panic!("TODO: implement this")"#.to_string(),
                 ],
                 false_positives: vec![],
+                paths: Vec::new(),
+                exclude_paths: Vec::new(),
+                conditions: None,
+                deprecated: false,
+                replaced_by: None,
+                deprecated_since: None,
+                remediation: None,
+                cwe: Vec::new(),
+                owasp: Vec::new(),
+                references: Vec::new(),
             },
             DetectionRule {
                 id: "rust_unwrap_without_context".to_string(),
@@ -408,6 +1539,7 @@ impl PlaybookManager {
                 pattern_type: PatternType::Regex {
                     pattern: r"\.unwrap\(\)".to_string(),
                     flags: None,
+                    multiline: false,
                 },
                 scope: PatternScope::FunctionBody,
                 enabled: true,
@@ -418,6 +1550,20 @@ impl PlaybookManager {
                 false_positives: vec![
                     "let value = result.unwrap(); // Safe: checked above".to_string(),
                 ],
+                paths: Vec::new(),
+                exclude_paths: Vec::new(),
+                conditions: None,
+                deprecated: false,
+                replaced_by: None,
+                deprecated_since: None,
+                remediation: Some(Remediation {
+                    explanation: "Propagate the error with `?` or add context so failures are diagnosable".to_string(),
+                    example: Some(r#"let value = result.context("failed to load value")?;"#.to_string()),
+                    replacement: Some(r#".expect("explain why this cannot fail")"#.to_string()),
+                }),
+                cwe: Vec::new(),
+                owasp: Vec::new(),
+                references: Vec::new(),
             },
         ]
     }
@@ -433,12 +1579,23 @@ impl PlaybookManager {
                 pattern_type: PatternType::Regex {
                     pattern: r"def\s+\w+\([^)]*\):\s*pass".to_string(),
                     flags: Some("m".to_string()),
+                    multiline: true,
                 },
                 scope: PatternScope::FunctionBody,
                 enabled: true,
                 tags: vec!["placeholder".to_string(), "incomplete".to_string()],
                 examples: vec!["def do_something():\n    pass".to_string()],
                 false_positives: vec![],
+                paths: Vec::new(),
+                exclude_paths: Vec::new(),
+                conditions: None,
+                deprecated: false,
+                replaced_by: None,
+                deprecated_since: None,
+                remediation: None,
+                cwe: Vec::new(),
+                owasp: Vec::new(),
+                references: Vec::new(),
             },
             DetectionRule {
                 id: "python_not_implemented_error".to_string(),
@@ -448,12 +1605,23 @@ impl PlaybookManager {
                 pattern_type: PatternType::Regex {
                     pattern: r"raise\s+NotImplementedError".to_string(),
                     flags: None,
+                    multiline: false,
                 },
                 scope: PatternScope::FunctionBody,
                 enabled: true,
                 tags: vec!["placeholder".to_string(), "incomplete".to_string()],
                 examples: vec!["raise NotImplementedError()".to_string()],
                 false_positives: vec![],
+                paths: Vec::new(),
+                exclude_paths: Vec::new(),
+                conditions: None,
+                deprecated: false,
+                replaced_by: None,
+                deprecated_since: None,
+                remediation: None,
+                cwe: Vec::new(),
+                owasp: Vec::new(),
+                references: Vec::new(),
             },
             DetectionRule {
                 id: "python_todo_comment".to_string(),
@@ -463,6 +1631,7 @@ impl PlaybookManager {
                 pattern_type: PatternType::Regex {
                     pattern: r"(?i)#\s*(TODO|FIXME|XXX|HACK):".to_string(),
                     flags: Some("i".to_string()),
+                    multiline: false,
                 },
                 scope: PatternScope::Comments,
                 enabled: true,
@@ -472,6 +1641,16 @@ impl PlaybookManager {
                     "# FIXME: handle errors".to_string(),
                 ],
                 false_positives: vec![],
+                paths: Vec::new(),
+                exclude_paths: Vec::new(),
+                conditions: None,
+                deprecated: false,
+                replaced_by: None,
+                deprecated_since: None,
+                remediation: None,
+                cwe: Vec::new(),
+                owasp: Vec::new(),
+                references: Vec::new(),
             },
         ]
     }
@@ -486,12 +1665,23 @@ impl PlaybookManager {
             pattern_type: PatternType::Regex {
                 pattern: r"function\s+\w+\s*\([^)]*\)\s*\{\s*\}".to_string(),
                 flags: None,
+                multiline: false,
             },
             scope: PatternScope::FunctionBody,
             enabled: true,
             tags: vec!["placeholder".to_string(), "incomplete".to_string()],
             examples: vec!["function doSomething() {}".to_string()],
             false_positives: vec![],
+            paths: Vec::new(),
+            exclude_paths: Vec::new(),
+            conditions: None,
+            deprecated: false,
+            replaced_by: None,
+            deprecated_since: None,
+            remediation: None,
+            cwe: Vec::new(),
+            owasp: Vec::new(),
+            references: Vec::new(),
         }]
     }
 
@@ -507,6 +1697,20 @@ impl PlaybookManager {
     fn create_cpp_default_rules() -> Vec<DetectionRule> {
         vec![]
     }
+
+    /// Rules of a language's bundled playbook, for languages without hand-written defaults.
+    ///
+    /// The playbooks are compiled in, so an invalid one is a bug in sniff itself
+    /// and panics instead of leaving the language without rules.
+    fn bundled_rules(language: SupportedLanguage) -> Vec<DetectionRule> {
+        let (_, yaml) = BUNDLED_PLAYBOOKS
+            .iter()
+            .find(|(bundled, _)| *bundled == language)
+            .unwrap_or_else(|| panic!("no bundled {} playbook", language.name()));
+        serde_yaml::from_str::<Playbook>(yaml)
+            .unwrap_or_else(|e| panic!("bundled {} playbook is invalid: {e}", language.name()))
+            .rules
+    }
 }
 
 impl Default for PlaybookManager {
@@ -546,4 +1750,371 @@ mod tests {
         let rules = manager.get_active_rules_for_language(SupportedLanguage::Rust);
         assert!(!rules.is_empty());
     }
+
+    #[test]
+    fn test_rule_overrides() {
+        let mut manager = PlaybookManager::new();
+        let playbook = PlaybookManager::create_default_playbook(SupportedLanguage::Rust);
+        let disabled = playbook.rules[0].id.clone();
+        let raised = playbook.rules[1].id.clone();
+        let default_severity = playbook.rules[1].severity;
+        manager.add_playbook(SupportedLanguage::Rust, playbook.clone());
+        manager.set_rule_overrides(HashMap::from([
+            (disabled.clone(), RuleOverride::Off),
+            (raised.clone(), RuleOverride::Severity(Severity::Critical)),
+        ]));
+        // Playbooks added after the overrides are set get them too
+        let mut learned = playbook;
+        learned.namespace = Some("team".to_string());
+        manager.add_playbook_from(SupportedLanguage::Rust, learned, RuleSource::Learned);
+
+        let rules = manager.get_active_rules_for_language(SupportedLanguage::Rust);
+        assert!(rules.iter().all(|rule| rule.id != disabled));
+        assert_eq!(rules.iter().filter(|rule| rule.id == raised).count(), 1);
+        for rule_id in [
+            raised.clone(),
+            format!("builtin/{raised}"),
+            format!("team/{raised}"),
+        ] {
+            assert_eq!(
+                manager.get_rule(&rule_id).unwrap().severity,
+                Severity::Critical
+            );
+        }
+
+        // A namespaced override targets one playbook only, and replacing the
+        // overrides undoes the earlier ones
+        manager.set_rule_overrides(HashMap::from([(
+            format!("team/{raised}"),
+            RuleOverride::Severity(Severity::Low),
+        )]));
+        assert_eq!(manager.get_rule(&raised).unwrap().severity, Severity::Low);
+        assert_eq!(
+            manager
+                .get_rule(&format!("builtin/{raised}"))
+                .unwrap()
+                .severity,
+            default_severity
+        );
+        let rules = manager.get_active_rules_for_language(SupportedLanguage::Rust);
+        assert_eq!(rules.iter().filter(|rule| rule.id == disabled).count(), 1);
+
+        // A rule turned off comes back with a severity override
+        manager.set_rule_overrides(HashMap::from([(disabled.clone(), RuleOverride::Off)]));
+        manager.set_rule_overrides(HashMap::from([(
+            disabled.clone(),
+            RuleOverride::Severity(Severity::High),
+        )]));
+        let rules = manager.get_active_rules_for_language(SupportedLanguage::Rust);
+        let rule = rules.iter().find(|rule| rule.id == disabled).unwrap();
+        assert_eq!(rule.severity, Severity::High);
+    }
+
+    #[test]
+    fn test_rule_conflicts() {
+        let mut manager = PlaybookManager::new();
+        let builtin = PlaybookManager::create_default_playbook(SupportedLanguage::Python);
+        let rule_id = builtin.rules[0].id.clone();
+        let mut pack = builtin.clone();
+        pack.name = "Team Python Pack".to_string();
+        pack.namespace = None;
+        pack.rules.truncate(1);
+        pack.rules[0].severity = Severity::Critical;
+
+        // The pack wins even though it is loaded after the built-in playbook
+        manager.add_playbook(SupportedLanguage::Python, builtin);
+        manager.add_playbook_from(SupportedLanguage::Python, pack.clone(), RuleSource::Pack);
+        // Among packs, the first one loaded wins
+        pack.name = "Other Pack".to_string();
+        manager.add_playbook_from(SupportedLanguage::Python, pack, RuleSource::Pack);
+
+        let active: Vec<_> = manager
+            .get_active_rules_for_language(SupportedLanguage::Python)
+            .into_iter()
+            .filter(|rule| rule.id == rule_id)
+            .collect();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].severity, Severity::Critical);
+
+        let conflicts = manager.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].rule_id, rule_id);
+        assert_eq!(
+            conflicts[0].active.qualified_id,
+            format!("team-python-pack/{rule_id}")
+        );
+        let shadowed: Vec<_> = conflicts[0]
+            .shadowed
+            .iter()
+            .map(|origin| (origin.qualified_id.as_str(), origin.source))
+            .collect();
+        assert_eq!(
+            shadowed,
+            vec![
+                (format!("builtin/{rule_id}").as_str(), RuleSource::BuiltIn),
+                (format!("other-pack/{rule_id}").as_str(), RuleSource::Pack),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deprecated_rule_grace_period() {
+        let mut rule =
+            PlaybookManager::create_default_playbook(SupportedLanguage::Rust).rules[0].clone();
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        assert!(rule.gates_on(day("2030-01-01"), 30));
+
+        rule.deprecated = true;
+        assert!(!rule.gates_on(day("2030-01-01"), 30));
+
+        rule.deprecated_since = Some(day("2026-01-01"));
+        assert_eq!(rule.grace_period_end(30), Some(day("2026-01-31")));
+        assert!(rule.gates_on(day("2026-01-31"), 30));
+        assert!(!rule.gates_on(day("2026-02-01"), 30));
+    }
+
+    #[test]
+    fn test_rule_path_globs() {
+        let mut manager = PlaybookManager::new();
+        let mut playbook = PlaybookManager::create_default_playbook(SupportedLanguage::Rust);
+        playbook.rules[0].paths = vec!["src/api/**".to_string()];
+        playbook.rules[0].exclude_paths = vec!["**/generated/*.rs".to_string()];
+        let scoped_id = playbook.rules[0].id.clone();
+        manager.add_playbook(SupportedLanguage::Rust, playbook);
+
+        let selects = |path: &str| {
+            manager
+                .get_active_rules_for_path(SupportedLanguage::Rust, Path::new(path))
+                .iter()
+                .any(|rule| rule.id == scoped_id)
+        };
+        assert!(selects("src/api/handlers/user.rs"));
+        assert!(selects("./src/api/mod.rs"));
+        assert!(!selects("src/lib.rs"));
+        assert!(!selects("src/api/generated/schema.rs"));
+
+        // Other rules still apply everywhere
+        assert!(
+            manager
+                .get_active_rules_for_path(SupportedLanguage::Rust, Path::new("src/lib.rs"))
+                .len()
+                > 1
+        );
+
+        let mut invalid = PlaybookManager::create_default_playbook(SupportedLanguage::Rust);
+        invalid.rules[0].paths = vec!["src/[api".to_string()];
+        assert!(manager.validate_playbook(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_ruleset_hash_tracks_active_rules() {
+        let mut manager = PlaybookManager::new();
+        let empty_hash = manager.ruleset_hash();
+
+        let mut playbook = PlaybookManager::create_default_playbook(SupportedLanguage::Rust);
+        manager
+            .playbooks
+            .insert(SupportedLanguage::Rust, vec![playbook.clone()]);
+        let hash = manager.ruleset_hash();
+        assert_ne!(hash, empty_hash);
+        assert_eq!(hash, manager.ruleset_hash());
+
+        // Disabling a rule changes the active ruleset
+        playbook.rules[0].enabled = false;
+        manager
+            .playbooks
+            .insert(SupportedLanguage::Rust, vec![playbook]);
+        assert_ne!(manager.ruleset_hash(), hash);
+    }
+
+    #[test]
+    fn test_session_playbook_thinking_rules() {
+        let mut manager = PlaybookManager::new();
+        manager.add_session_playbook(PlaybookManager::create_session_default_playbook());
+
+        let rules = manager.get_active_thinking_rules();
+        assert!(!rules.is_empty());
+        for rule in rules {
+            let PatternType::Regex { pattern, .. } = &rule.pattern_type else {
+                panic!("thinking rules are regex rules");
+            };
+            let regex = Regex::new(pattern).unwrap();
+            assert!(
+                rule.examples.iter().all(|example| regex.is_match(example)),
+                "rule {} misses its examples",
+                rule.id
+            );
+        }
+
+        // Session playbooks cannot carry code rules
+        let mut playbook = PlaybookManager::create_session_default_playbook();
+        playbook.rules[0].scope = PatternScope::File;
+        assert!(manager.validate_playbook(&playbook).is_err());
+    }
+
+    #[test]
+    fn test_bundled_playbook_remediation() {
+        let playbook: Playbook =
+            serde_yaml::from_str(include_str!("../playbooks/rust-patterns.yaml")).unwrap();
+
+        let unwrap_rule = playbook
+            .rules
+            .iter()
+            .find(|rule| rule.id == "rust_unwrap_without_context")
+            .unwrap();
+        let remediation = unwrap_rule.remediation.as_ref().unwrap();
+        assert!(!remediation.explanation.is_empty());
+        assert!(remediation.example.is_some());
+
+        // Rules without a remediation block still parse
+        let todo_rule = playbook
+            .rules
+            .iter()
+            .find(|rule| rule.id == "rust_todo_comment")
+            .unwrap();
+        assert!(todo_rule.remediation.is_none());
+    }
+
+    #[test]
+    fn test_ast_query_rule_validation() {
+        let manager = PlaybookManager::new();
+        let mut playbook: Playbook =
+            serde_yaml::from_str(include_str!("../playbooks/typescript-patterns.yaml")).unwrap();
+        assert!(manager.validate_playbook(&playbook).is_ok());
+
+        let rule = playbook
+            .rules
+            .iter_mut()
+            .find(|rule| rule.id == "ts_empty_catch_block")
+            .unwrap();
+        rule.pattern_type = PatternType::AstQuery {
+            query: "(catch_block) @match".to_string(),
+        };
+        let err = manager.validate_playbook(&playbook).unwrap_err();
+        assert!(err.to_string().contains("ts_empty_catch_block"), "{err}");
+    }
+
+    #[test]
+    fn test_structural_rule_validation() {
+        let manager = PlaybookManager::new();
+        let mut playbook: Playbook =
+            serde_yaml::from_str(include_str!("../playbooks/rust-patterns.yaml")).unwrap();
+        assert!(manager.validate_playbook(&playbook).is_ok());
+
+        let rule = playbook
+            .rules
+            .iter_mut()
+            .find(|rule| rule.id == "rust_empty_ok_function")
+            .unwrap();
+        let PatternType::Structural { parameters, .. } = &mut rule.pattern_type else {
+            panic!("rust_empty_ok_function is a structural rule");
+        };
+        parameters.insert("max_statements".to_string(), "one".to_string());
+        let err = manager.validate_playbook(&playbook).unwrap_err();
+        assert!(err.to_string().contains("max_statements"), "{err}");
+    }
+
+    #[test]
+    fn test_multiline_regex_rule_validation() {
+        let manager = PlaybookManager::new();
+        let mut playbook: Playbook =
+            serde_yaml::from_str(include_str!("../playbooks/python-patterns.yaml")).unwrap();
+        assert!(manager.validate_playbook(&playbook).is_ok());
+
+        let rule = playbook
+            .rules
+            .iter_mut()
+            .find(|rule| rule.id == "python_pass_only_function")
+            .unwrap();
+        assert!(matches!(
+            rule.pattern_type,
+            PatternType::Regex {
+                multiline: true,
+                ..
+            }
+        ));
+        rule.scope = PatternScope::Comments;
+        let err = manager.validate_playbook(&playbook).unwrap_err();
+        assert!(
+            err.to_string().contains("python_pass_only_function"),
+            "{err}"
+        );
+        assert!(err.to_string().contains("Comments scope"), "{err}");
+
+        // Playbooks added without validation disable the rule instead
+        let mut manager = PlaybookManager::new();
+        manager.add_playbook(SupportedLanguage::Python, playbook);
+        assert!(manager
+            .get_active_rules_for_language(SupportedLanguage::Python)
+            .iter()
+            .all(|rule| rule.id != "python_pass_only_function"));
+    }
+
+    #[test]
+    fn test_bundled_playbooks_are_valid() {
+        let manager = PlaybookManager::new();
+        for (language, yaml) in BUNDLED_PLAYBOOKS {
+            let playbook: Playbook = serde_yaml::from_str(yaml)
+                .unwrap_or_else(|e| panic!("{} playbook: {e}", language.name()));
+            assert_eq!(playbook.language, language.name());
+            assert!(
+                !playbook.rules.is_empty(),
+                "{} playbook has no rules",
+                language.name()
+            );
+            manager.validate_playbook(&playbook).unwrap();
+        }
+
+        for (language, rules) in [
+            (SupportedLanguage::Swift, 5),
+            (SupportedLanguage::Terraform, 4),
+            (SupportedLanguage::Scala, 4),
+            (SupportedLanguage::Config, 4),
+            (SupportedLanguage::Markdown, 4),
+        ] {
+            let playbook = PlaybookManager::create_default_playbook(language);
+            assert_eq!(
+                playbook.rules.len(),
+                rules,
+                "{} built-in rules",
+                language.name()
+            );
+        }
+
+        // Swift and Terraform have no grammar, so rules cannot depend on parsed symbols
+        for language in [SupportedLanguage::Swift, SupportedLanguage::Terraform] {
+            let playbook = PlaybookManager::create_default_playbook(language);
+            assert!(playbook
+                .rules
+                .iter()
+                .all(|rule| matches!(rule.scope, PatternScope::File | PatternScope::Comments)));
+        }
+    }
+
+    #[test]
+    fn test_security_taxonomy() {
+        let manager = PlaybookManager::new();
+        let mut playbook: Playbook =
+            serde_yaml::from_str(include_str!("../playbooks/rust-patterns.yaml")).unwrap();
+
+        let rule = playbook
+            .rules
+            .iter_mut()
+            .find(|rule| rule.id == "rust_fake_authentication")
+            .unwrap();
+        let taxonomy = rule.taxonomy().unwrap();
+        assert_eq!(taxonomy.cwe, vec!["CWE-287"]);
+        assert_eq!(taxonomy.owasp, vec!["A07:2021"]);
+
+        rule.cwe = vec!["287".to_string()];
+        let err = manager.validate_playbook(&playbook).unwrap_err();
+        assert!(err.to_string().contains("CWE-798"), "{err}");
+
+        let todo_rule = playbook
+            .rules
+            .iter()
+            .find(|rule| rule.id == "rust_todo_comment")
+            .unwrap();
+        assert!(todo_rule.taxonomy().is_none());
+    }
 }
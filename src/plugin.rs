@@ -0,0 +1,373 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Experimental WASM rule plugins.
+//!
+//! A rule with `pattern_type: !Wasm { module: "name.wasm" }` is implemented by
+//! a WebAssembly module in the project's `.sniff/plugins/` directory, next to
+//! the playbook declaring the rule. Modules run sandboxed: no host functions
+//! are provided, so a module cannot reach the file system, network or clock,
+//! and each check runs with a fuel and memory budget.
+//!
+//! # Module interface
+//!
+//! A module exports its `memory` and two functions:
+//!
+//! - `sniff_alloc(len: i32) -> i32` returns the address of `len` writable bytes.
+//! - `sniff_check(ptr: i32, len: i32) -> i64` receives a JSON [`PluginInput`]
+//!   at `ptr` and returns the address (high 32 bits) and length (low 32 bits)
+//!   of a JSON array of [`PluginMatch`]es.
+//!
+//! Every check instantiates the module afresh, so no state carries over
+//! between files.
+//!
+//! The WASM runtime is only built with the `wasm-plugins` cargo feature.
+//! Without it, modules fail to load and their rules report an error.
+
+use crate::analysis::SupportedLanguage;
+use crate::error::{Result, SniffError};
+use rust_tree_sitter::analyzer::Symbol;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "wasm-plugins")]
+use wasmi::{Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Fuel available to a single check, roughly one unit per instruction.
+#[cfg(feature = "wasm-plugins")]
+const FUEL_PER_CHECK: u64 = 100_000_000;
+
+/// Largest linear memory a module may use, in bytes.
+#[cfg(feature = "wasm-plugins")]
+const MEMORY_LIMIT: usize = 256 << 20;
+
+/// A symbol passed to a plugin.
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginSymbol<'a> {
+    /// Symbol name.
+    pub name: &'a str,
+    /// Symbol kind, e.g. `function`, `method`, `class` or `struct`.
+    pub kind: &'a str,
+    /// 1-based first line of the symbol.
+    pub start_line: usize,
+    /// 1-based last line of the symbol.
+    pub end_line: usize,
+}
+
+/// What a plugin receives for each file.
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInput<'a> {
+    /// Path of the analyzed file.
+    pub path: &'a str,
+    /// Language name, as in playbooks.
+    pub language: &'a str,
+    /// File content.
+    pub content: &'a str,
+    /// Symbols found in the file.
+    pub symbols: Vec<PluginSymbol<'a>>,
+}
+
+impl<'a> PluginInput<'a> {
+    /// Builds the input for a file.
+    #[must_use]
+    pub fn new(
+        path: &'a str,
+        language: SupportedLanguage,
+        content: &'a str,
+        symbols: &'a [Symbol],
+    ) -> Self {
+        Self {
+            path,
+            language: language.name(),
+            content,
+            symbols: symbols
+                .iter()
+                .map(|symbol| PluginSymbol {
+                    name: &symbol.name,
+                    kind: &symbol.kind,
+                    start_line: symbol.start_line,
+                    end_line: symbol.end_line,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A match reported by a plugin.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PluginMatch {
+    /// 1-based line of the match.
+    pub line: usize,
+    /// 1-based column of the match.
+    #[serde(default = "first_column")]
+    pub column: usize,
+    /// What the plugin found; used as the detection context.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+fn first_column() -> usize {
+    1
+}
+
+/// A compiled WASM rule module.
+#[derive(Debug)]
+pub struct WasmPlugin {
+    name: String,
+    #[cfg(feature = "wasm-plugins")]
+    engine: Engine,
+    #[cfg(feature = "wasm-plugins")]
+    module: Module,
+}
+
+impl WasmPlugin {
+    /// Compiles a module from WASM bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes are not a valid WASM module.
+    #[cfg(feature = "wasm-plugins")]
+    pub fn from_bytes(name: &str, wasm: &[u8]) -> Result<Self> {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm)
+            .map_err(|e| SniffError::invalid_format("WASM plugin", format!("{name}: {e}")))?;
+        Ok(Self {
+            name: name.to_string(),
+            engine,
+            module,
+        })
+    }
+
+    /// Fails, as this build has no WASM runtime.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error naming the `wasm-plugins` feature.
+    #[cfg(not(feature = "wasm-plugins"))]
+    pub fn from_bytes(name: &str, _wasm: &[u8]) -> Result<Self> {
+        Err(SniffError::invalid_format(
+            "WASM plugin",
+            format!("{name}: sniff was built without the `wasm-plugins` feature"),
+        ))
+    }
+
+    /// Compiles a module from a `.wasm` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid module.
+    pub fn load(path: &Path) -> Result<Self> {
+        let wasm = std::fs::read(path).map_err(|e| SniffError::file_system(path, e))?;
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        );
+        Self::from_bytes(&name, &wasm)
+    }
+
+    /// Runs the module's check on a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the module does not follow the plugin interface,
+    /// traps, runs out of fuel or memory, or returns invalid JSON.
+    #[cfg(feature = "wasm-plugins")]
+    pub fn check(&self, input: &PluginInput<'_>) -> Result<Vec<PluginMatch>> {
+        let wasm_error = |e: wasmi::Error| {
+            SniffError::analysis_error(format!("WASM plugin {} failed: {e}", self.name))
+        };
+        let interface_error = |problem: &str| {
+            SniffError::invalid_format("WASM plugin", format!("{}: {problem}", self.name))
+        };
+
+        let limits = StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store
+            .set_fuel(FUEL_PER_CHECK)
+            .map_err(|e| wasm_error(e.into()))?;
+
+        // No host functions are linked, so modules with imports fail here
+        let instance = Linker::<StoreLimits>::new(&self.engine)
+            .instantiate(&mut store, &self.module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(wasm_error)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| interface_error("does not export `memory`"))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "sniff_alloc")
+            .map_err(wasm_error)?;
+        let check = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "sniff_check")
+            .map_err(wasm_error)?;
+
+        let input = serde_json::to_vec(input)?;
+        let input_len =
+            i32::try_from(input.len()).map_err(|_| interface_error("input is too large"))?;
+        let input_ptr = alloc.call(&mut store, input_len).map_err(wasm_error)?;
+        let input_offset = usize::try_from(input_ptr)
+            .map_err(|_| interface_error("`sniff_alloc` returned a negative address"))?;
+        memory
+            .write(&mut store, input_offset, &input)
+            .map_err(|e| wasm_error(e.into()))?;
+
+        let packed = check
+            .call(&mut store, (input_ptr, input_len))
+            .map_err(wasm_error)?
+            .cast_unsigned();
+        let output_offset = usize::try_from(packed >> 32)
+            .map_err(|_| interface_error("output address is out of range"))?;
+        let output_len = usize::try_from(packed & 0xffff_ffff)
+            .map_err(|_| interface_error("output length is out of range"))?;
+        let mut output = vec![0; output_len];
+        memory
+            .read(&store, output_offset, &mut output)
+            .map_err(|e| wasm_error(e.into()))?;
+
+        serde_json::from_slice(&output)
+            .map_err(|e| interface_error(&format!("invalid output from `sniff_check`: {e}")))
+    }
+
+    /// Fails, as this build has no WASM runtime.
+    ///
+    /// # Errors
+    ///
+    /// Always returns an error naming the `wasm-plugins` feature.
+    #[cfg(not(feature = "wasm-plugins"))]
+    pub fn check(&self, _input: &PluginInput<'_>) -> Result<Vec<PluginMatch>> {
+        Err(SniffError::analysis_error(format!(
+            "WASM plugin {} needs the `wasm-plugins` feature",
+            self.name
+        )))
+    }
+}
+
+/// WASM modules loaded from a plugins directory, by file name.
+#[derive(Debug, Default)]
+pub struct PluginRegistry {
+    modules: HashMap<String, WasmPlugin>,
+    load_failures: Vec<(PathBuf, String)>,
+}
+
+impl PluginRegistry {
+    /// Compiles every `.wasm` file in `dir`. Modules that fail to compile are
+    /// skipped and recorded in [`Self::load_failures`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory exists but cannot be read.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<()> {
+        if !dir.exists() {
+            return Ok(());
+        }
+
+        let entries = std::fs::read_dir(dir).map_err(|e| SniffError::file_system(dir, e))?;
+        for entry in entries {
+            let path = entry.map_err(|e| SniffError::file_system(dir, e))?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("wasm") {
+                continue;
+            }
+            match WasmPlugin::load(&path) {
+                Ok(plugin) => {
+                    self.modules.insert(plugin.name.clone(), plugin);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to load WASM plugin {}: {}", path.display(), e);
+                    self.load_failures.push((path, e.to_string()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the module with the given file name.
+    #[must_use]
+    pub fn get(&self, module: &str) -> Option<&WasmPlugin> {
+        self.modules.get(module)
+    }
+
+    /// Returns the modules that failed to compile, with the error.
+    #[must_use]
+    pub fn load_failures(&self) -> &[(PathBuf, String)] {
+        &self.load_failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reports line 2 of every file, from a JSON array at address 0.
+    #[cfg(feature = "wasm-plugins")]
+    const LINE_TWO_PLUGIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (data (i32.const 0) "[{\"line\":2,\"message\":\"flagged by plugin\"}]")
+          (func (export "sniff_alloc") (param i32) (result i32) (i32.const 1024))
+          (func (export "sniff_check") (param i32 i32) (result i64)
+            (i64.const 42)))
+    "#;
+
+    #[cfg(feature = "wasm-plugins")]
+    fn input(content: &str) -> PluginInput<'_> {
+        PluginInput::new("src/lib.rs", SupportedLanguage::Rust, content, &[])
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[test]
+    fn test_plugin_check_reads_matches() {
+        let wasm = wat::parse_str(LINE_TWO_PLUGIN).unwrap();
+        let plugin = WasmPlugin::from_bytes("line_two.wasm", &wasm).unwrap();
+
+        let matches = plugin.check(&input("fn a() {}\nfn b() {}\n")).unwrap();
+        assert_eq!(
+            matches,
+            vec![PluginMatch {
+                line: 2,
+                column: 1,
+                message: Some("flagged by plugin".to_string()),
+            }]
+        );
+    }
+
+    #[cfg(feature = "wasm-plugins")]
+    #[test]
+    fn test_plugin_sandbox_limits() {
+        // Host imports are not provided
+        let wasm = wat::parse_str(
+            r#"(module (import "env" "read_file" (func (param i32))) (memory (export "memory") 1))"#,
+        )
+        .unwrap();
+        let plugin = WasmPlugin::from_bytes("imports.wasm", &wasm).unwrap();
+        assert!(plugin.check(&input("")).is_err());
+
+        // Endless loops run out of fuel
+        let wasm = wat::parse_str(
+            r#"
+            (module
+              (memory (export "memory") 1)
+              (func (export "sniff_alloc") (param i32) (result i32) (i32.const 0))
+              (func (export "sniff_check") (param i32 i32) (result i64)
+                (loop $forever (br $forever))
+                (i64.const 0)))
+            "#,
+        )
+        .unwrap();
+        let plugin = WasmPlugin::from_bytes("loop.wasm", &wasm).unwrap();
+        let error = plugin.check(&input("")).unwrap_err().to_string();
+        assert!(error.contains("loop.wasm"), "{error}");
+    }
+
+    #[cfg(not(feature = "wasm-plugins"))]
+    #[test]
+    fn test_plugins_need_the_feature() {
+        let error = WasmPlugin::from_bytes("rule.wasm", &[])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("wasm-plugins"), "{error}");
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! `RegexSet` prefilter for regex rules.
+//!
+//! Rules are matched one at a time, and each regex rule scans every line of
+//! its scope. Most rules match nowhere in most files, so the prefilter scans a
+//! file once with a [`RegexSet`] of all the single-line regex rules of its
+//! language, and only the rules whose pattern matched somewhere in the file
+//! are run.
+//!
+//! The set runs on the whole file with `^` and `$` matching at line breaks, so
+//! a pattern that matches a line also matches the file. Patterns the set cannot
+//! treat that way (multiline rules, and patterns that anchor at the start or
+//! end of the text: `\A`, `\z`, or `^` and `$` under an inline flag turning
+//! multi-line mode off) are left out of the set and always run. Anchors are
+//! found by parsing the pattern, so flag groups such as `(?i-m)` count too.
+
+use crate::playbook::{DetectionRule, PatternType};
+use regex::{RegexSet, RegexSetBuilder};
+use regex_syntax::ParserBuilder;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Prefilter over the regex rules of one language.
+#[derive(Debug, Clone, Default)]
+pub struct RegexPrefilter {
+    /// Set of the prefiltered patterns; `None` if no pattern is prefiltered.
+    set: Option<RegexSet>,
+    /// Index in the set of each prefiltered pattern.
+    patterns: HashMap<String, usize>,
+}
+
+impl RegexPrefilter {
+    /// Builds a prefilter over the single-line regex rules among `rules`.
+    ///
+    /// If the set does not compile (e.g. it exceeds the regex size limit),
+    /// the prefilter keeps every rule.
+    pub fn new<'a>(rules: impl IntoIterator<Item = &'a DetectionRule>) -> Self {
+        let mut patterns: HashMap<String, usize> = HashMap::new();
+        let mut ordered = Vec::new();
+        for rule in rules {
+            if let Some(pattern) = prefilter_pattern(rule) {
+                if !patterns.contains_key(pattern) {
+                    patterns.insert(pattern.to_string(), ordered.len());
+                    ordered.push(pattern);
+                }
+            }
+        }
+        if ordered.is_empty() {
+            return Self::default();
+        }
+
+        match RegexSetBuilder::new(&ordered)
+            .multi_line(true)
+            .crlf(true)
+            .build()
+        {
+            Ok(set) => Self {
+                set: Some(set),
+                patterns,
+            },
+            Err(e) => {
+                warn!(
+                    "Cannot build the regex rule prefilter, running every rule: {}",
+                    e
+                );
+                Self::default()
+            }
+        }
+    }
+
+    /// Number of prefiltered patterns.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Returns true if no pattern is prefiltered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Removes the rules that cannot match anywhere in `content`.
+    pub fn retain_candidates(&self, rules: &mut Vec<DetectionRule>, content: &str) {
+        let Some(set) = &self.set else {
+            return;
+        };
+        let matched = set.matches(content);
+        rules.retain(|rule| {
+            prefilter_pattern(rule)
+                .and_then(|pattern| self.patterns.get(pattern))
+                .is_none_or(|&index| matched.matched(index))
+        });
+    }
+}
+
+/// Returns the pattern of a rule the set can prefilter.
+fn prefilter_pattern(rule: &DetectionRule) -> Option<&str> {
+    match &rule.pattern_type {
+        PatternType::Regex {
+            pattern,
+            multiline: false,
+            ..
+        } if !anchors_to_text(pattern) => Some(pattern),
+        _ => None,
+    }
+}
+
+/// Whether a pattern, parsed as the set parses it, anchors at the start or end
+/// of the text rather than of a line. Invalid patterns count as anchored, so
+/// they are left to fail when their rule runs.
+fn anchors_to_text(pattern: &str) -> bool {
+    let Ok(hir) = ParserBuilder::new()
+        .multi_line(true)
+        .crlf(true)
+        .build()
+        .parse(pattern)
+    else {
+        return true;
+    };
+    hir.properties().look_set().contains_anchor_haystack()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support::rule;
+
+    #[test]
+    fn test_prefilter_keeps_matching_rules() {
+        let rules = vec![
+            rule("todo", "TODO"),
+            rule("anchored", r"^\s*pass$"),
+            rule("absent", r"unimplemented!\(\)"),
+            rule("start_of_text", r"\Aabsent"),
+            rule("flag_group", r"(?i-m)^absent"),
+            rule("scoped_flags", r"(?i:absent)|^\s*pass$"),
+        ];
+        let prefilter = RegexPrefilter::new(&rules);
+        assert_eq!(prefilter.len(), 4);
+
+        let mut candidates = rules.clone();
+        prefilter.retain_candidates(&mut candidates, "def f():\r\n    pass\r\n# TODO\n");
+        let ids: Vec<&str> = candidates.iter().map(|rule| rule.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "todo",
+                "anchored",
+                "start_of_text",
+                "flag_group",
+                "scoped_flags"
+            ]
+        );
+    }
+}
@@ -0,0 +1,816 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Hierarchical per-directory project configuration (`.sniff.toml`).
+//!
+//! A file is governed by every `.sniff.toml` between it and the filesystem
+//! root, or the nearest config marked `root = true`. Configs closer to the
+//! file override the ones above them, so a monorepo can relax rules in
+//! `examples/` while keeping strict gates in `src/`:
+//!
+//! ```toml
+//! # examples/.sniff.toml
+//! [analysis]
+//! min_quality_score = 40
+//!
+//! [rules]
+//! disable = ["rust_unwrap_usage", "rust_debug_print"]
+//! ```
+
+use crate::analysis::ScoreWeights;
+use crate::error::{Result, SniffError};
+use crate::playbook::{RuleOverride, Severity};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use toml::de::{DeTable, DeValue};
+use toml::Spanned;
+
+/// File name of a per-directory config.
+pub const CONFIG_FILE_NAME: &str = ".sniff.toml";
+
+/// One `.sniff.toml` file as written.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    /// Stop looking for configs in parent directories.
+    #[serde(default)]
+    root: bool,
+    #[serde(default)]
+    analysis: AnalysisSection,
+    #[serde(default)]
+    rules: RulesSection,
+    #[serde(default)]
+    scoring: ScoringSection,
+    #[serde(default)]
+    baseline: BaselineSection,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnalysisSection {
+    min_quality_score: Option<f64>,
+    max_critical_issues: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesSection {
+    /// Rule ids not reported below this directory.
+    #[serde(default)]
+    disable: Vec<String>,
+    /// Rule ids disabled by a parent config that are reported again below this directory.
+    #[serde(default)]
+    enable: Vec<String>,
+    /// Days deprecated rules keep counting toward quality gates.
+    deprecation_grace_days: Option<u32>,
+    /// Rule ids mapped to `off` or a severity.
+    #[serde(default)]
+    overrides: BTreeMap<String, RuleOverride>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScoringSection {
+    /// Points deducted per detection of each severity.
+    #[serde(default)]
+    penalties: PenaltiesSection,
+    /// Relative weights of the assessment categories.
+    #[serde(default)]
+    weights: WeightsSection,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PenaltiesSection {
+    critical: Option<f64>,
+    high: Option<f64>,
+    medium: Option<f64>,
+    low: Option<f64>,
+    info: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WeightsSection {
+    maintainability: Option<f64>,
+    reliability: Option<f64>,
+    performance: Option<f64>,
+    security: Option<f64>,
+    completeness: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BaselineSection {
+    /// Remote store holding the team baseline.
+    remote: Option<String>,
+}
+
+/// The effective configuration for a file, after merging its config hierarchy.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectConfig {
+    /// Minimum quality score each file must reach.
+    pub min_quality_score: Option<f64>,
+    /// Maximum number of critical issues allowed per file.
+    pub max_critical_issues: Option<usize>,
+    /// Rule ids that are not reported.
+    pub disabled_rules: BTreeSet<String>,
+    /// Days deprecated rules keep counting toward quality gates.
+    pub deprecation_grace_days: Option<u32>,
+    /// Rule ids mapped to `off` or a severity.
+    pub rule_overrides: BTreeMap<String, RuleOverride>,
+    /// Weights of the quality score formula.
+    pub score_weights: ScoreWeights,
+    /// Remote store holding the team baseline that analysis compares against.
+    pub baseline_remote: Option<String>,
+    /// Config files that contributed, outermost first.
+    pub sources: Vec<PathBuf>,
+}
+
+impl ProjectConfig {
+    /// Returns true if the rule is enabled by this config.
+    #[must_use]
+    pub fn is_rule_enabled(&self, rule_id: &str) -> bool {
+        !self.disabled_rules.contains(rule_id)
+            && self.rule_overrides.get(rule_id) != Some(&RuleOverride::Off)
+    }
+
+    /// Returns the severity this config gives a rule, if it overrides it.
+    #[must_use]
+    pub fn rule_severity(&self, rule_id: &str) -> Option<Severity> {
+        match self.rule_overrides.get(rule_id) {
+            Some(RuleOverride::Severity(severity)) => Some(*severity),
+            _ => None,
+        }
+    }
+
+    /// Applies a child config on top of this one.
+    fn apply(&mut self, file: &ConfigFile, source: &Path) {
+        if let Some(min_quality_score) = file.analysis.min_quality_score {
+            self.min_quality_score = Some(min_quality_score);
+        }
+        if let Some(max_critical_issues) = file.analysis.max_critical_issues {
+            self.max_critical_issues = Some(max_critical_issues);
+        }
+        if let Some(remote) = &file.baseline.remote {
+            self.baseline_remote = Some(remote.clone());
+        }
+        if let Some(grace_days) = file.rules.deprecation_grace_days {
+            self.deprecation_grace_days = Some(grace_days);
+        }
+        for rule_id in &file.rules.enable {
+            self.disabled_rules.remove(rule_id);
+        }
+        self.disabled_rules
+            .extend(file.rules.disable.iter().cloned());
+        self.rule_overrides.extend(
+            file.rules
+                .overrides
+                .iter()
+                .map(|(rule_id, rule_override)| (rule_id.clone(), *rule_override)),
+        );
+
+        let penalties = &file.scoring.penalties;
+        let weights = &file.scoring.weights;
+        for (value, target) in [
+            (
+                penalties.critical,
+                &mut self.score_weights.penalties.critical,
+            ),
+            (penalties.high, &mut self.score_weights.penalties.high),
+            (penalties.medium, &mut self.score_weights.penalties.medium),
+            (penalties.low, &mut self.score_weights.penalties.low),
+            (penalties.info, &mut self.score_weights.penalties.info),
+            (
+                weights.maintainability,
+                &mut self.score_weights.categories.maintainability,
+            ),
+            (
+                weights.reliability,
+                &mut self.score_weights.categories.reliability,
+            ),
+            (
+                weights.performance,
+                &mut self.score_weights.categories.performance,
+            ),
+            (
+                weights.security,
+                &mut self.score_weights.categories.security,
+            ),
+            (
+                weights.completeness,
+                &mut self.score_weights.categories.completeness,
+            ),
+        ] {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+        self.sources.push(source.to_path_buf());
+    }
+}
+
+/// Parses a `.sniff.toml`, naming `source` in errors.
+fn parse(content: &str, source: &Path) -> Result<ConfigFile> {
+    toml::from_str(content)
+        .map_err(|e| SniffError::invalid_format(source.display().to_string(), e.to_string()))
+}
+
+/// Returns the JSON Schema of `.sniff.toml`.
+///
+/// [`validate`] checks configs against this schema, so it must cover every key
+/// that [`ConfigResolver`] accepts.
+#[must_use]
+pub fn json_schema() -> Value {
+    let rule_ids = json!({ "type": "array", "items": { "type": "string" } });
+    let weights = |keys: &[&str], description: &str| {
+        let weight = json!({ "type": "number", "minimum": 0, "description": description });
+        json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": keys
+                .iter()
+                .map(|key| ((*key).to_string(), weight.clone()))
+                .collect::<serde_json::Map<_, _>>()
+        })
+    };
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": CONFIG_FILE_NAME,
+        "description": "Per-directory sniff configuration; nested configs override parent ones",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "root": {
+                "type": "boolean",
+                "description": "Stop looking for configs in parent directories"
+            },
+            "analysis": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "min_quality_score": {
+                        "type": "number",
+                        "minimum": 0,
+                        "maximum": 100,
+                        "description": "Minimum quality score each file must reach"
+                    },
+                    "max_critical_issues": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Maximum number of critical issues allowed per file"
+                    }
+                }
+            },
+            "rules": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "disable": rule_ids.clone(),
+                    "enable": rule_ids,
+                    "deprecation_grace_days": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "maximum": u32::MAX,
+                        "description": "Days deprecated rules keep counting toward quality gates"
+                    },
+                    "overrides": {
+                        "type": "object",
+                        "description": "Rule ids mapped to off or a severity (case-insensitive)",
+                        "additionalProperties": {
+                            "type": "string",
+                            "enum": ["off", "info", "low", "medium", "high", "critical"]
+                        }
+                    }
+                }
+            },
+            "scoring": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "penalties": weights(
+                        &["critical", "high", "medium", "low", "info"],
+                        "Points deducted from a file's score per detection of this severity"
+                    ),
+                    "weights": weights(
+                        &[
+                            "maintainability",
+                            "reliability",
+                            "performance",
+                            "security",
+                            "completeness"
+                        ],
+                        "Relative weight of this category in the overall assessment score"
+                    )
+                }
+            },
+            "baseline": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "remote": {
+                        "type": "string",
+                        "description": "Remote store holding the team baseline \
+                                        (s3://, gs://, file:// or git+<remote>)"
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// A problem found in a `.sniff.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigIssue {
+    /// Line of the problem (1-based).
+    pub line: usize,
+    /// Column of the problem (1-based).
+    pub column: usize,
+    /// Dotted path of the key, empty for syntax errors.
+    pub key: String,
+    /// What is wrong.
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn new(content: &str, offset: usize, key: &str, message: impl Into<String>) -> Self {
+        let before = &content[..offset.min(content.len())];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            key: key.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Checks the content of a `.sniff.toml` against [`json_schema`] and returns
+/// every problem found: syntax errors, unknown keys and invalid values.
+#[must_use]
+pub fn validate(content: &str) -> Vec<ConfigIssue> {
+    let table = match DeTable::parse(content) {
+        Ok(table) => table,
+        Err(e) => {
+            let offset = e.span().map_or(0, |span| span.start);
+            return vec![ConfigIssue::new(content, offset, "", e.message())];
+        }
+    };
+
+    let mut issues = Vec::new();
+    check_table(content, &json_schema(), table.get_ref(), "", &mut issues);
+    issues.sort_by_key(|issue| (issue.line, issue.column));
+
+    // The loader has the final say, in case the schema misses a constraint
+    if issues.is_empty() {
+        if let Err(e) = toml::from_str::<ConfigFile>(content) {
+            let offset = e.span().map_or(0, |span| span.start);
+            issues.push(ConfigIssue::new(content, offset, "", e.message()));
+        }
+    }
+    issues
+}
+
+/// Checks the keys of a table against an object schema.
+fn check_table(
+    content: &str,
+    schema: &Value,
+    table: &DeTable<'_>,
+    path: &str,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    for (key, value) in table {
+        let key_path = if path.is_empty() {
+            key.get_ref().to_string()
+        } else {
+            format!("{path}.{}", key.get_ref())
+        };
+        let property = schema["properties"]
+            .get(key.get_ref().as_ref())
+            .or_else(|| Some(&schema["additionalProperties"]).filter(|s| s.is_object()));
+        if let Some(property) = property {
+            check_value(content, property, value, &key_path, issues);
+        } else {
+            let known: Vec<_> = schema["properties"]
+                .as_object()
+                .map(|properties| properties.keys().map(String::as_str).collect())
+                .unwrap_or_default();
+            issues.push(ConfigIssue::new(
+                content,
+                key.span().start,
+                &key_path,
+                format!("unknown key, expected one of: {}", known.join(", ")),
+            ));
+        }
+    }
+}
+
+/// Checks a value against a schema.
+fn check_value(
+    content: &str,
+    schema: &Value,
+    value: &Spanned<DeValue<'_>>,
+    path: &str,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    let offset = value.span().start;
+    let mut issue = |message: String| issues.push(ConfigIssue::new(content, offset, path, message));
+
+    let (found, number) = match value.get_ref() {
+        DeValue::String(_) => ("string", None),
+        DeValue::Integer(integer) => (
+            "integer",
+            i128::from_str_radix(integer.as_str(), integer.radix())
+                .ok()
+                .map(|integer| integer as f64),
+        ),
+        DeValue::Float(float) => ("number", float.as_str().parse::<f64>().ok()),
+        DeValue::Boolean(_) => ("boolean", None),
+        DeValue::Datetime(_) => ("datetime", None),
+        DeValue::Array(_) => ("array", None),
+        DeValue::Table(_) => ("object", None),
+    };
+    let expected = schema["type"].as_str().unwrap_or_default();
+    if found != expected && !(expected == "number" && found == "integer") {
+        issue(format!("expected {expected}, found {found}"));
+        return;
+    }
+
+    if let Some(number) = number {
+        if let Some(minimum) = schema["minimum"]
+            .as_f64()
+            .filter(|minimum| number < *minimum)
+        {
+            issue(format!("must be at least {minimum}"));
+        }
+        if let Some(maximum) = schema["maximum"]
+            .as_f64()
+            .filter(|maximum| number > *maximum)
+        {
+            issue(format!("must be at most {maximum}"));
+        }
+    }
+
+    match value.get_ref() {
+        DeValue::String(string) => {
+            if let Some(allowed) = schema["enum"].as_array() {
+                let allowed: Vec<_> = allowed.iter().filter_map(Value::as_str).collect();
+                if !allowed
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(string))
+                {
+                    issue(format!(
+                        "invalid value '{string}', expected one of: {}",
+                        allowed.join(", ")
+                    ));
+                }
+            }
+        }
+        DeValue::Array(items) => {
+            for (index, item) in items.into_iter().enumerate() {
+                check_value(
+                    content,
+                    &schema["items"],
+                    item,
+                    &format!("{path}[{index}]"),
+                    issues,
+                );
+            }
+        }
+        DeValue::Table(table) => check_table(content, schema, table, path, issues),
+        _ => {}
+    }
+}
+
+/// Resolves the effective config of files, caching parsed configs per directory.
+#[derive(Debug, Default)]
+pub struct ConfigResolver {
+    /// Parsed config of each visited directory (`None` if it has no config file).
+    directories: HashMap<PathBuf, Option<Arc<ConfigFile>>>,
+    /// Effective config of each visited directory.
+    effective: HashMap<PathBuf, Arc<ProjectConfig>>,
+}
+
+impl ConfigResolver {
+    /// Creates an empty resolver.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the effective config for a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a config file in the hierarchy cannot be read or parsed.
+    pub fn resolve(&mut self, file_path: &Path) -> Result<Arc<ProjectConfig>> {
+        let absolute =
+            std::path::absolute(file_path).map_err(|e| SniffError::file_system(file_path, e))?;
+        let directory = absolute.parent().unwrap_or(&absolute).to_path_buf();
+        self.resolve_directory(&directory)
+    }
+
+    /// Returns the effective config for a directory, which must be absolute.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a config file in the hierarchy cannot be read or parsed.
+    pub fn resolve_directory(&mut self, directory: &Path) -> Result<Arc<ProjectConfig>> {
+        if let Some(config) = self.effective.get(directory) {
+            return Ok(Arc::clone(config));
+        }
+
+        // Collect configs from the directory upwards until a root config
+        let mut chain = Vec::new();
+        let mut current = Some(directory);
+        while let Some(dir) = current {
+            if let Some(file) = self.load(dir)? {
+                let is_root = file.root;
+                chain.push((dir.join(CONFIG_FILE_NAME), file));
+                if is_root {
+                    break;
+                }
+            }
+            current = dir.parent();
+        }
+
+        let mut config = ProjectConfig::default();
+        for (source, file) in chain.iter().rev() {
+            config.apply(file, source);
+        }
+        let config = Arc::new(config);
+        self.effective
+            .insert(directory.to_path_buf(), Arc::clone(&config));
+        Ok(config)
+    }
+
+    /// Returns the root of the project containing a path: the nearest directory
+    /// holding `.git` or a root config, else the topmost directory holding a
+    /// config. Returns `None` if the path is in no project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a config file on the way cannot be read or parsed.
+    pub fn project_root(&mut self, path: &Path) -> Result<Option<PathBuf>> {
+        let absolute = std::path::absolute(path).map_err(|e| SniffError::file_system(path, e))?;
+        let mut current = if absolute.is_dir() {
+            Some(absolute.as_path())
+        } else {
+            absolute.parent()
+        };
+
+        let mut topmost_config = None;
+        while let Some(dir) = current {
+            if dir.join(".git").exists() {
+                return Ok(Some(dir.to_path_buf()));
+            }
+            if let Some(file) = self.load(dir)? {
+                if file.root {
+                    return Ok(Some(dir.to_path_buf()));
+                }
+                topmost_config = Some(dir.to_path_buf());
+            }
+            current = dir.parent();
+        }
+        Ok(topmost_config)
+    }
+
+    /// Returns the path of a file relative to the root of its project, with
+    /// `/` separators. Returns `None` if the file is in no project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a config file on the way cannot be read or parsed.
+    pub fn project_path(&mut self, path: &Path) -> Result<Option<String>> {
+        let Some(root) = self.project_root(path)? else {
+            return Ok(None);
+        };
+        let absolute = std::path::absolute(path).map_err(|e| SniffError::file_system(path, e))?;
+        Ok(absolute
+            .strip_prefix(&root)
+            .ok()
+            .map(|relative| relative.to_string_lossy().replace('\\', "/")))
+    }
+
+    /// Loads the config file of a single directory, if it has one.
+    fn load(&mut self, directory: &Path) -> Result<Option<Arc<ConfigFile>>> {
+        if let Some(file) = self.directories.get(directory) {
+            return Ok(file.clone());
+        }
+        let path = directory.join(CONFIG_FILE_NAME);
+        let file = if path.is_file() {
+            let content =
+                std::fs::read_to_string(&path).map_err(|e| SniffError::file_system(&path, e))?;
+            Some(Arc::new(parse(&content, &path)?))
+        } else {
+            None
+        };
+        self.directories
+            .insert(directory.to_path_buf(), file.clone());
+        Ok(file)
+    }
+}
+
+/// Returns the files under `root` in path order, skipping hidden files,
+/// files ignored by `.gitignore` and unreadable entries.
+#[must_use]
+pub fn project_files(root: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = ignore::WalkBuilder::new(root)
+        .build()
+        .filter_map(std::result::Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+        .map(ignore::DirEntry::into_path)
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_nested_configs_override_parents() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("examples/basic")).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join(CONFIG_FILE_NAME),
+            "root = true\n[analysis]\nmin_quality_score = 90\nmax_critical_issues = 0\n\
+             [rules]\ndisable = [\"rust_todo_comment\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("examples").join(CONFIG_FILE_NAME),
+            "[analysis]\nmin_quality_score = 40\n\
+             [rules]\ndisable = [\"rust_unwrap_usage\"]\nenable = [\"rust_todo_comment\"]\n",
+        )
+        .unwrap();
+
+        let mut resolver = ConfigResolver::new();
+
+        let src = resolver.resolve(&root.join("src/lib.rs")).unwrap();
+        assert_eq!(src.min_quality_score, Some(90.0));
+        assert!(!src.is_rule_enabled("rust_todo_comment"));
+        assert!(src.is_rule_enabled("rust_unwrap_usage"));
+
+        let example = resolver
+            .resolve(&root.join("examples/basic/main.rs"))
+            .unwrap();
+        assert_eq!(example.min_quality_score, Some(40.0));
+        assert_eq!(example.max_critical_issues, Some(0));
+        assert!(example.is_rule_enabled("rust_todo_comment"));
+        assert!(!example.is_rule_enabled("rust_unwrap_usage"));
+        assert_eq!(example.sources.len(), 2);
+    }
+
+    #[test]
+    fn test_rule_overrides() {
+        let file = parse(
+            "[rules.overrides]\nrust_unwrap_usage = \"off\"\npython_print_debug = \"Critical\"\n",
+            Path::new(CONFIG_FILE_NAME),
+        )
+        .unwrap();
+        let mut config = ProjectConfig::default();
+        config.apply(&file, Path::new(CONFIG_FILE_NAME));
+        assert!(!config.is_rule_enabled("rust_unwrap_usage"));
+        assert_eq!(
+            config.rule_overrides.get("python_print_debug"),
+            Some(&RuleOverride::Severity(Severity::Critical))
+        );
+        assert_eq!(
+            config.rule_severity("python_print_debug"),
+            Some(Severity::Critical)
+        );
+        assert_eq!(config.rule_severity("rust_unwrap_usage"), None);
+
+        let invalid = parse(
+            "[rules.overrides]\nrust_unwrap_usage = \"severe\"\n",
+            Path::new(CONFIG_FILE_NAME),
+        );
+        assert!(invalid
+            .unwrap_err()
+            .to_string()
+            .contains("expected off, info"));
+    }
+
+    #[test]
+    fn test_baseline_remote() {
+        let content = "[baseline]\nremote = \"git+origin\"\n";
+        let file = parse(content, Path::new(CONFIG_FILE_NAME)).unwrap();
+        let mut config = ProjectConfig::default();
+        config.apply(&file, Path::new(CONFIG_FILE_NAME));
+        assert_eq!(config.baseline_remote.as_deref(), Some("git+origin"));
+        assert!(validate(content).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        assert!(parse("[analysis]\nmin_quality = 3\n", Path::new(CONFIG_FILE_NAME)).is_err());
+    }
+
+    #[test]
+    fn test_nested_score_weights() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("examples")).unwrap();
+        std::fs::write(
+            root.join(CONFIG_FILE_NAME),
+            "[scoring.penalties]\ncritical = 40\n[scoring.weights]\nsecurity = 0.5\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("examples").join(CONFIG_FILE_NAME),
+            "[scoring.penalties]\ninfo = 0\n",
+        )
+        .unwrap();
+
+        let config = ConfigResolver::new()
+            .resolve(&root.join("examples/main.rs"))
+            .unwrap();
+        let weights = config.score_weights;
+        assert!((weights.penalties.critical - 40.0).abs() < f64::EPSILON);
+        assert!(weights.penalties.info.abs() < f64::EPSILON);
+        assert!((weights.penalties.high - 15.0).abs() < f64::EPSILON);
+        assert!((weights.categories.security - 0.5).abs() < f64::EPSILON);
+
+        let issues = validate("[scoring.weights]\nsecurity = -1\nstyle = 1\n");
+        assert_eq!(issues.len(), 2);
+        assert!(issues[1].message.contains("maintainability, performance"));
+    }
+
+    #[test]
+    fn test_project_root_detection() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("monorepo");
+        std::fs::create_dir_all(root.join("services/api/src")).unwrap();
+        std::fs::write(root.join(CONFIG_FILE_NAME), "").unwrap();
+        std::fs::write(root.join("services").join(CONFIG_FILE_NAME), "").unwrap();
+
+        let mut resolver = ConfigResolver::new();
+        let file = root.join("services/api/src/main.rs");
+        assert_eq!(resolver.project_root(&file).unwrap(), Some(root.clone()));
+
+        std::fs::write(
+            root.join("services").join(CONFIG_FILE_NAME),
+            "root = true\n",
+        )
+        .unwrap();
+        let mut resolver = ConfigResolver::new();
+        assert_eq!(
+            resolver.project_root(&file).unwrap(),
+            Some(root.join("services"))
+        );
+
+        assert_eq!(
+            resolver.project_path(&file).unwrap().as_deref(),
+            Some("api/src/main.rs")
+        );
+
+        std::fs::create_dir(root.join("services/api/.git")).unwrap();
+        assert_eq!(
+            resolver.project_root(&file).unwrap(),
+            Some(root.join("services/api"))
+        );
+        assert_eq!(
+            resolver.project_path(&file).unwrap().as_deref(),
+            Some("src/main.rs")
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_issues_with_lines() {
+        let valid = "root = true\n[analysis]\nmin_quality_score = 80.5\nmax_critical_issues = 0\n\
+                     [rules]\ndisable = [\"a\"]\nenable = []\ndeprecation_grace_days = 30\n\
+                     [rules.overrides]\n\"core/a\" = \"High\"\n";
+        assert_eq!(validate(valid), Vec::new());
+
+        let issues = validate(
+            "[analysis]\nmin_quality = 3\nmax_critical_issues = -1\n\
+             [rules]\ndisable = [1]\n[rules.overrides]\na = \"severe\"\n",
+        );
+        let found: Vec<_> = issues
+            .iter()
+            .map(|issue| (issue.line, issue.key.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, "analysis.min_quality"),
+                (3, "analysis.max_critical_issues"),
+                (5, "rules.disable[0]"),
+                (7, "rules.overrides.a"),
+            ]
+        );
+        assert!(issues[0].message.contains("min_quality_score"));
+        assert!(issues[1].message.contains("at least 0"));
+
+        let syntax = validate("[analysis\n");
+        assert_eq!((syntax.len(), syntax[0].line), (1, 1));
+    }
+}
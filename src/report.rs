@@ -0,0 +1,549 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Report formats for CI and code-review integrations.
+//!
+//! Renders [`AnalysisResults`] into the interchange formats other tools
+//! consume: SARIF 2.1.0 for GitHub Code Scanning, `JUnit` XML for CI test
+//! report UIs, Code Climate JSON for GitLab merge request code quality
+//! widgets and the Reviewdog Diagnostic Format for PR review comments.
+
+#![allow(clippy::format_push_string)]
+
+use crate::analysis::MisalignmentDetection;
+use crate::markup::escape;
+use crate::playbook::Severity;
+use crate::standalone::{AnalysisResults, FileAnalysisResult};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// SARIF schema the output conforms to.
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Project home reported as the SARIF tool information URI.
+const INFORMATION_URI: &str = "https://github.com/conikeec/sniff";
+
+/// Maps a playbook severity to a SARIF result level.
+#[must_use]
+pub fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info => "note",
+    }
+}
+
+/// Maps a playbook severity to the GitHub `problem.severity` rule property.
+fn problem_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info => "recommendation",
+    }
+}
+
+/// Returns the path of an analyzed file as reported to code-scanning services:
+/// relative to the project root, which is what GitHub and GitLab resolve it
+/// against, with forward slashes. Files outside any project keep their path.
+fn artifact_uri(file_result: &FileAnalysisResult) -> String {
+    if let Some(project_path) = &file_result.project_path {
+        return project_path.clone();
+    }
+    let path = file_result.file_path.to_string_lossy().replace('\\', "/");
+    path.strip_prefix("./")
+        .map_or_else(|| path.clone(), str::to_string)
+}
+
+/// Builds the SARIF rule descriptor for the rule behind a detection.
+fn sarif_rule(detection: &MisalignmentDetection) -> Value {
+    // GitHub Code Scanning shows CWE tags in this form
+    let mut tags = detection.tags.clone();
+    if let Some(taxonomy) = &detection.taxonomy {
+        tags.extend(
+            taxonomy
+                .cwe
+                .iter()
+                .map(|cwe| format!("external/cwe/{}", cwe.to_ascii_lowercase())),
+        );
+    }
+    let mut rule = json!({
+        "id": detection.rule_id,
+        "name": detection.rule_name,
+        "shortDescription": { "text": detection.rule_name },
+        "fullDescription": { "text": detection.description },
+        "defaultConfiguration": { "level": sarif_level(detection.severity) },
+        "properties": {
+            "tags": tags,
+            "problem.severity": problem_severity(detection.severity),
+            "severity": detection.severity.name(),
+        },
+    });
+    if let Some(remediation) = &detection.remediation {
+        let mut markdown = remediation.explanation.clone();
+        if let Some(example) = &remediation.example {
+            markdown.push_str("\n\n```\n");
+            markdown.push_str(example);
+            markdown.push_str("\n```");
+        }
+        rule["help"] = json!({ "text": remediation.explanation, "markdown": markdown });
+    }
+    if let Some(reference) = detection
+        .taxonomy
+        .as_ref()
+        .and_then(|taxonomy| taxonomy.references.first())
+    {
+        rule["helpUri"] = json!(reference);
+    }
+    rule
+}
+
+/// Renders analysis results as a SARIF 2.1.0 log.
+///
+/// Each distinct rule that produced a detection is emitted once in the tool
+/// driver's rule table; results reference it by index. Detection fingerprints
+/// are emitted as partial fingerprints so consumers can track alerts across runs.
+#[must_use]
+pub fn sarif(results: &AnalysisResults) -> Value {
+    let mut rule_indices: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut rules = Vec::new();
+    let mut sarif_results = Vec::new();
+
+    for file_result in &results.file_results {
+        for detection in &file_result.detections {
+            let rule_index = *rule_indices
+                .entry(detection.rule_id.as_str())
+                .or_insert_with(|| {
+                    rules.push(sarif_rule(detection));
+                    rules.len() - 1
+                });
+
+            sarif_results.push(json!({
+                "ruleId": detection.rule_id,
+                "ruleIndex": rule_index,
+                "level": sarif_level(detection.severity),
+                "message": { "text": detection.description },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": artifact_uri(file_result),
+                        },
+                        "region": {
+                            "startLine": detection.line_number.max(1),
+                            "startColumn": detection.column_number.max(1),
+                            "snippet": { "text": detection.code_snippet },
+                        },
+                    },
+                }],
+                "partialFingerprints": { "sniffFingerprint/v1": detection.fingerprint() },
+            }));
+        }
+    }
+
+    let mut run = json!({
+        "tool": {
+            "driver": {
+                "name": "sniff",
+                "version": env!("CARGO_PKG_VERSION"),
+                "informationUri": INFORMATION_URI,
+                "rules": rules,
+            },
+        },
+        "results": sarif_results,
+    });
+    if let Some(metadata) = &results.run {
+        run["properties"] = json!({
+            "repository": metadata.repository,
+            "timestamp": metadata.timestamp,
+            "rulesetHash": metadata.ruleset_hash,
+        });
+    }
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [run],
+    })
+}
+
+/// Renders analysis results as a `JUnit` XML report.
+///
+/// Each analyzed file is a test case. Every Critical or High detection in a
+/// file is reported as a failure of that test case; lower severities are
+/// listed in the test case's `system-out`.
+#[must_use]
+pub fn junit(results: &AnalysisResults) -> String {
+    let is_failure = |detection: &&MisalignmentDetection| {
+        matches!(detection.severity, Severity::Critical | Severity::High)
+    };
+    let failed_files = results
+        .file_results
+        .iter()
+        .filter(|file_result| file_result.detections.iter().any(|d| is_failure(&d)))
+        .count();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"sniff\" tests=\"{}\" failures=\"{}\">\n",
+        results.total_files, failed_files
+    ));
+    let timestamp = results
+        .run
+        .as_ref()
+        .map(|run| {
+            format!(
+                " timestamp=\"{}\"",
+                run.timestamp.format("%Y-%m-%dT%H:%M:%S")
+            )
+        })
+        .unwrap_or_default();
+    xml.push_str(&format!(
+        "  <testsuite name=\"sniff\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"0\"{}>\n",
+        results.total_files, failed_files, timestamp
+    ));
+
+    for file_result in &results.file_results {
+        let file_path = escape(&file_result.file_path.to_string_lossy());
+        let classname = file_result
+            .language
+            .map_or("sniff", |language| language.name());
+        xml.push_str(&format!(
+            "    <testcase classname=\"{}\" name=\"{}\" file=\"{}\">\n",
+            escape(classname),
+            file_path,
+            file_path
+        ));
+
+        for detection in file_result.detections.iter().filter(is_failure) {
+            xml.push_str(&format!(
+                "      <failure type=\"{}\" message=\"{}\">{}:{}:{} [{}] {}\n{}</failure>\n",
+                escape(&detection.rule_id),
+                escape(&format!(
+                    "{}: {}",
+                    detection.rule_name, detection.description
+                )),
+                file_path,
+                detection.line_number,
+                detection.column_number,
+                detection.severity.name(),
+                escape(&detection.description),
+                escape(detection.code_snippet.trim())
+            ));
+        }
+
+        let notes: Vec<String> = file_result
+            .detections
+            .iter()
+            .filter(|detection| !is_failure(detection))
+            .map(|detection| {
+                format!(
+                    "{}:{} [{}] {}: {}",
+                    file_path,
+                    detection.line_number,
+                    detection.severity.name(),
+                    escape(&detection.rule_name),
+                    escape(&detection.description)
+                )
+            })
+            .collect();
+        if !notes.is_empty() {
+            xml.push_str(&format!(
+                "      <system-out>{}</system-out>\n",
+                notes.join("\n")
+            ));
+        }
+        xml.push_str("    </testcase>\n");
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Maps a playbook severity to a Code Climate issue severity.
+#[must_use]
+pub fn codeclimate_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "blocker",
+        Severity::High => "critical",
+        Severity::Medium => "major",
+        Severity::Low => "minor",
+        Severity::Info => "info",
+    }
+}
+
+/// Picks the Code Climate category for a detection from its tags.
+fn codeclimate_category(detection: &MisalignmentDetection) -> &'static str {
+    let has_tag = |name: &str| {
+        detection
+            .tags
+            .iter()
+            .any(|tag| tag.eq_ignore_ascii_case(name))
+    };
+    if has_tag("security") {
+        "Security"
+    } else if has_tag("performance") {
+        "Performance"
+    } else if has_tag("style") {
+        "Style"
+    } else {
+        "Bug Risk"
+    }
+}
+
+/// Renders analysis results as a Code Climate issue list.
+///
+/// This is the format GitLab reads from a `codequality` report artifact to show
+/// issues inline in merge request diffs. Fingerprints must be unique within a
+/// report, so repeated detections of the same snippet get an occurrence suffix.
+#[must_use]
+pub fn codeclimate(results: &AnalysisResults) -> Value {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut issues = Vec::new();
+
+    for file_result in &results.file_results {
+        for detection in &file_result.detections {
+            let base = detection.fingerprint();
+            let occurrence = occurrences.entry(base.clone()).or_insert(0);
+            let fingerprint = if *occurrence == 0 {
+                base
+            } else {
+                crate::hash::stable_hash([base.as_str(), occurrence.to_string().as_str()])
+            };
+            *occurrence += 1;
+
+            issues.push(json!({
+                "type": "issue",
+                "check_name": detection.rule_id,
+                "description": format!("{}: {}", detection.rule_name, detection.description),
+                "categories": [codeclimate_category(detection)],
+                "severity": codeclimate_severity(detection.severity),
+                "fingerprint": fingerprint,
+                "location": {
+                    "path": artifact_uri(file_result),
+                    "lines": { "begin": detection.line_number.max(1) },
+                },
+            }));
+        }
+    }
+
+    Value::Array(issues)
+}
+
+/// Maps a playbook severity to a Reviewdog diagnostic severity.
+#[must_use]
+pub fn rdjson_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "ERROR",
+        Severity::Medium => "WARNING",
+        Severity::Low | Severity::Info => "INFO",
+    }
+}
+
+/// Reviewdog `source` object identifying sniff as the producer.
+fn rdjson_source() -> Value {
+    json!({ "name": "sniff", "url": INFORMATION_URI })
+}
+
+/// Reviewdog suggestion replacing a detection's matched text with its rule's
+/// `remediation.replacement`.
+///
+/// Only single-line matches get a suggestion. Replacements with capture group
+/// references need the rule's regex to expand, so they are left to `--fix`.
+fn rdjson_suggestion(detection: &MisalignmentDetection) -> Option<Value> {
+    let replacement = detection.remediation.as_ref()?.replacement.as_deref()?;
+    if replacement.contains('$') || detection.code_snippet.contains('\n') {
+        return None;
+    }
+    let line = detection.line_number.max(1);
+    let column = detection.column_number.max(1);
+    Some(json!({
+        "range": {
+            "start": { "line": line, "column": column },
+            "end": { "line": line, "column": column + detection.code_snippet.len() },
+        },
+        "text": replacement,
+    }))
+}
+
+/// Builds one Reviewdog diagnostic per detection.
+fn rdjson_diagnostics(results: &AnalysisResults) -> Vec<Value> {
+    let mut diagnostics = Vec::new();
+    for file_result in &results.file_results {
+        for detection in &file_result.detections {
+            let mut message = format!("{}: {}", detection.rule_name, detection.description);
+            if let Some(remediation) = &detection.remediation {
+                message.push_str("\n\n");
+                message.push_str(&remediation.explanation);
+            }
+            let mut diagnostic = json!({
+                "message": message,
+                "location": {
+                    "path": artifact_uri(file_result),
+                    "range": {
+                        "start": {
+                            "line": detection.line_number.max(1),
+                            "column": detection.column_number.max(1),
+                        },
+                    },
+                },
+                "severity": rdjson_severity(detection.severity),
+                "code": { "value": detection.rule_id },
+                "original_output": detection.code_snippet,
+            });
+            if let Some(suggestion) = rdjson_suggestion(detection) {
+                diagnostic["suggestions"] = json!([suggestion]);
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+/// Renders analysis results as a Reviewdog Diagnostic Format result (`rdjson`).
+#[must_use]
+pub fn rdjson(results: &AnalysisResults) -> Value {
+    json!({
+        "source": rdjson_source(),
+        "diagnostics": rdjson_diagnostics(results),
+    })
+}
+
+/// Renders analysis results as Reviewdog diagnostics, one JSON object per line (`rdjsonl`).
+#[must_use]
+pub fn rdjsonl(results: &AnalysisResults) -> String {
+    let mut output = String::new();
+    for mut diagnostic in rdjson_diagnostics(results) {
+        diagnostic["source"] = rdjson_source();
+        output.push_str(&diagnostic.to_string());
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support::detection;
+    use crate::playbook::Remediation;
+
+    fn results() -> AnalysisResults {
+        let mut detections = vec![
+            detection("rust_unimplemented", Severity::Critical, 3),
+            detection("rust_todo_comment", Severity::Low, 7),
+            detection("rust_unimplemented", Severity::Critical, 12),
+        ];
+        for detection in &mut detections {
+            detection.code_snippet = "unimplemented!()".to_string();
+        }
+        crate::analysis::test_support::results(detections)
+    }
+
+    #[test]
+    fn test_sarif_output() {
+        let log = sarif(&results());
+        assert_eq!(log["version"], "2.1.0");
+
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(results[1]["level"], "note");
+        assert_eq!(results[2]["ruleIndex"], 0);
+        let region = &results[0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 3);
+        assert_eq!(region["startColumn"], 1);
+    }
+
+    #[test]
+    fn test_artifact_uri_is_project_relative() {
+        let mut file_result =
+            crate::analysis::test_support::file_result("./src\\lib.rs", Vec::new());
+        assert_eq!(artifact_uri(&file_result), "src/lib.rs");
+
+        file_result.file_path = "/work/project/crates/core/src/lib.rs".into();
+        file_result.project_path = Some("crates/core/src/lib.rs".to_string());
+        assert_eq!(artifact_uri(&file_result), "crates/core/src/lib.rs");
+    }
+
+    #[test]
+    fn test_junit_output() {
+        let mut results = results();
+        results.file_results[0].detections[0].description =
+            "uses <unimplemented> & panics".to_string();
+        let xml = junit(&results);
+
+        assert!(xml.contains(r#"<testsuites name="sniff" tests="1" failures="1">"#));
+        assert_eq!(xml.matches("<failure ").count(), 2);
+        assert!(xml.contains("uses &lt;unimplemented&gt; &amp; panics"));
+        assert!(xml.contains("<system-out>src/lib.rs:7 [Low]"));
+    }
+
+    #[test]
+    fn test_codeclimate_output() {
+        let mut results = results();
+        results.file_results[0].detections[2].line_number = 3;
+        let report = codeclimate(&results);
+        let issues = report.as_array().unwrap();
+
+        assert_eq!(issues.len(), 3);
+        assert_eq!(issues[0]["severity"], "blocker");
+        assert_eq!(issues[1]["severity"], "minor");
+        assert_eq!(issues[0]["location"]["path"], "src/lib.rs");
+        assert_eq!(issues[0]["location"]["lines"]["begin"], 3);
+        // Identical detections still get distinct fingerprints
+        assert_ne!(issues[0]["fingerprint"], issues[2]["fingerprint"]);
+    }
+
+    #[test]
+    fn test_rdjson_output() {
+        let report = rdjson(&results());
+        assert_eq!(report["source"]["name"], "sniff");
+        let diagnostics = report["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0]["severity"], "ERROR");
+        assert_eq!(diagnostics[1]["severity"], "INFO");
+        assert_eq!(diagnostics[0]["code"]["value"], "rust_unimplemented");
+        assert_eq!(diagnostics[0]["location"]["range"]["start"]["line"], 3);
+
+        let lines: Vec<Value> = rdjsonl(&results())
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2]["source"]["name"], "sniff");
+    }
+
+    #[test]
+    fn test_rdjson_suggestions() {
+        let mut results = results();
+        let detections = &mut results.file_results[0].detections;
+        detections[0].column_number = 5;
+        detections[0].remediation = Some(Remediation {
+            explanation: "Return an error".to_string(),
+            example: None,
+            replacement: Some("todo!()".to_string()),
+        });
+        detections[1].remediation = Some(Remediation {
+            explanation: "Keep the name".to_string(),
+            example: None,
+            replacement: Some("$1".to_string()),
+        });
+        let report = rdjson(&results);
+        let diagnostics = report["diagnostics"].as_array().unwrap();
+
+        let suggestions = diagnostics[0]["suggestions"].as_array().unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0]["text"], "todo!()");
+        assert_eq!(suggestions[0]["range"]["start"]["line"], 3);
+        assert_eq!(suggestions[0]["range"]["start"]["column"], 5);
+        assert_eq!(suggestions[0]["range"]["end"]["line"], 3);
+        assert_eq!(suggestions[0]["range"]["end"]["column"], 21);
+        // Capture group references cannot be expanded without the rule's regex
+        assert!(diagnostics[1].get("suggestions").is_none());
+        assert!(diagnostics[2].get("suggestions").is_none());
+
+        let line: Value = serde_json::from_str(rdjsonl(&results).lines().next().unwrap()).unwrap();
+        assert_eq!(line["suggestions"][0]["text"], "todo!()");
+    }
+}
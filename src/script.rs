@@ -0,0 +1,343 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Rhai scripts for `Script` detection rules.
+//!
+//! A rule with `pattern_type: !Script { script: "..." }` runs a
+//! [Rhai](https://rhai.rs) script once per file. The script sees the file as
+//! variables and reports matches by calling `flag`:
+//!
+//! - `path`, `language` and `content` (strings), `lines` (array of strings) and
+//!   `symbols` (array of maps with `name`, `kind`, `start_line`, `end_line`).
+//! - `line(n)` returns the 1-based line `n`, or `""` past the end of the file.
+//! - `regex_match(text, pattern)` returns true if the regex matches `text`.
+//! - `regex_find(text, pattern)` returns the 1-based column of the first
+//!   match, or 0 if there is none.
+//! - `file_exists(path)` returns true if `path`, relative to the directory of
+//!   the file, exists. Absolute paths and paths leading outside the project
+//!   of the file (or its directory, outside a project) never exist.
+//! - `flag(line)` and `flag(line, message)` report a match on a 1-based line.
+//!
+//! Scripts run sandboxed: they cannot import modules, `eval` code or read and
+//! write files (`file_exists` only checks for a path), and each run has an
+//! operation budget.
+
+use crate::error::{Result, SniffError};
+use crate::plugin::{PluginInput, PluginMatch};
+use crate::project_config::ConfigResolver;
+use regex::Regex;
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+/// Operations available to a single run, roughly one per evaluated node.
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+thread_local! {
+    /// Compiled scripts keyed by source, so a rule's script is compiled once
+    /// per thread instead of once per file. Rhai ASTs are not `Send`, so the
+    /// cache cannot be shared between threads like the rule conditions.
+    static COMPILED: RefCell<HashMap<String, Rc<AST>>> = RefCell::default();
+}
+
+/// Creates an engine with the sandbox limits and without the helpers that
+/// depend on a file.
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(64)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(16 << 20)
+        .set_max_array_size(1 << 20)
+        .set_max_map_size(1 << 16)
+        .set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new())
+        .disable_symbol("eval")
+        .on_print(|text| tracing::debug!("script: {}", text))
+        .on_debug(|text, _, _| tracing::debug!("script: {}", text));
+    engine
+}
+
+/// Compiles a script, checking its syntax.
+///
+/// # Errors
+///
+/// Returns an error describing the first syntax error.
+pub fn compile(script: &str) -> Result<AST> {
+    sandboxed_engine()
+        .compile(script)
+        .map_err(|e| SniffError::invalid_format("rule script", e.to_string()))
+}
+
+/// Returns the compiled script, compiling it on first use on this thread.
+fn compiled(script: &str) -> Result<Rc<AST>> {
+    COMPILED.with(|cache| {
+        if let Some(ast) = cache.borrow().get(script) {
+            return Ok(Rc::clone(ast));
+        }
+        let ast = Rc::new(compile(script)?);
+        cache
+            .borrow_mut()
+            .insert(script.to_string(), Rc::clone(&ast));
+        Ok(ast)
+    })
+}
+
+/// Runs a script on a file and returns the matches it flagged. The script is
+/// compiled on its first run only.
+///
+/// # Errors
+///
+/// Returns an error if the script does not compile, fails, passes an invalid
+/// regex to a helper, or exceeds its operation budget.
+pub fn run(script: &str, input: &PluginInput<'_>) -> Result<Vec<PluginMatch>> {
+    let ast = compiled(script)?;
+    let mut engine = sandboxed_engine();
+
+    let lines: Rc<Vec<String>> = Rc::new(input.content.lines().map(str::to_string).collect());
+    let flagged: Rc<RefCell<Vec<PluginMatch>>> = Rc::default();
+    let regexes: Rc<RefCell<HashMap<String, Regex>>> = Rc::default();
+
+    let file_lines = Rc::clone(&lines);
+    engine.register_fn("line", move |n: i64| -> String {
+        usize::try_from(n)
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|index| file_lines.get(index))
+            .cloned()
+            .unwrap_or_default()
+    });
+
+    let cache = Rc::clone(&regexes);
+    engine.register_fn(
+        "regex_match",
+        move |text: &str, pattern: &str| -> std::result::Result<bool, Box<rhai::EvalAltResult>> {
+            with_regex(&cache, pattern, |regex| regex.is_match(text))
+        },
+    );
+    let cache = Rc::clone(&regexes);
+    engine.register_fn(
+        "regex_find",
+        move |text: &str, pattern: &str| -> std::result::Result<i64, Box<rhai::EvalAltResult>> {
+            with_regex(&cache, pattern, |regex| {
+                regex.find(text).map_or(0, |found| {
+                    i64::try_from(text[..found.start()].chars().count() + 1).unwrap_or(i64::MAX)
+                })
+            })
+        },
+    );
+
+    // Paths outside the file's project, or its directory outside a project, do not exist
+    let directory = Path::new(input.path)
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let directory =
+        std::path::absolute(directory).map_err(|e| SniffError::file_system(directory, e))?;
+    let root = ConfigResolver::new()
+        .project_root(&directory)?
+        .unwrap_or_else(|| directory.clone());
+    engine.register_fn("file_exists", move |path: &str| {
+        resolve_in_project(&root, &directory, path).is_some_and(|path| path.exists())
+    });
+
+    let matches = Rc::clone(&flagged);
+    engine.register_fn("flag", move |line: i64| {
+        matches.borrow_mut().push(script_match(line, None));
+    });
+    let matches = Rc::clone(&flagged);
+    engine.register_fn("flag", move |line: i64, message: &str| {
+        matches
+            .borrow_mut()
+            .push(script_match(line, Some(message.to_string())));
+    });
+
+    let symbols: Array = input
+        .symbols
+        .iter()
+        .map(|symbol| {
+            let mut map = Map::new();
+            map.insert("name".into(), symbol.name.into());
+            map.insert("kind".into(), symbol.kind.into());
+            map.insert("start_line".into(), line_number(symbol.start_line).into());
+            map.insert("end_line".into(), line_number(symbol.end_line).into());
+            Dynamic::from_map(map)
+        })
+        .collect();
+    let mut scope = Scope::new();
+    scope.push_constant("path", input.path.to_string());
+    scope.push_constant("language", input.language.to_string());
+    scope.push_constant("content", input.content.to_string());
+    scope.push_constant(
+        "lines",
+        lines.iter().cloned().map(Dynamic::from).collect::<Array>(),
+    );
+    scope.push_constant("symbols", symbols);
+
+    engine
+        .run_ast_with_scope(&mut scope, &ast)
+        .map_err(|e| SniffError::analysis_error(format!("Rule script failed: {e}")))?;
+
+    let matches = flagged.borrow().clone();
+    Ok(matches)
+}
+
+/// Resolves a relative `path` against `directory`, or returns `None` if the
+/// path is absolute or leads outside `root`.
+fn resolve_in_project(root: &Path, directory: &Path, path: &str) -> Option<PathBuf> {
+    let mut resolved = directory.to_path_buf();
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(part) => resolved.push(part),
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    resolved.starts_with(root).then_some(resolved)
+}
+
+/// Applies a cached regex, compiling it on first use.
+fn with_regex<T>(
+    cache: &RefCell<HashMap<String, Regex>>,
+    pattern: &str,
+    apply: impl FnOnce(&Regex) -> T,
+) -> std::result::Result<T, Box<rhai::EvalAltResult>> {
+    let mut cache = cache.borrow_mut();
+    if !cache.contains_key(pattern) {
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid regex '{pattern}': {e}"))?;
+        cache.insert(pattern.to_string(), regex);
+    }
+    Ok(apply(&cache[pattern]))
+}
+
+fn line_number(line: usize) -> i64 {
+    i64::try_from(line).unwrap_or(i64::MAX)
+}
+
+fn script_match(line: i64, message: Option<String>) -> PluginMatch {
+    PluginMatch {
+        line: usize::try_from(line).unwrap_or(1).max(1),
+        column: 1,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::SupportedLanguage;
+
+    const SOURCE: &str = "fn parse() -> Result<u32> {\n    Ok(input.parse().unwrap())\n}\n\n\
+                          fn main() {\n    run().unwrap();\n}\n";
+
+    /// Flags unwrap only in functions returning Result.
+    const UNWRAP_IN_RESULT_FN: &str = r#"
+        for n in 1..=lines.len() {
+            if regex_match(line(n), "^fn .*->\\s*Result") {
+                let end = n;
+                while end < lines.len() && line(end) != "}" { end += 1; }
+                for body in n..=end {
+                    let column = regex_find(line(body), "\\.unwrap\\(\\)");
+                    if column > 0 { flag(body, `unwrap at column ${column}`); }
+                }
+            }
+        }
+    "#;
+
+    fn input(content: &str) -> PluginInput<'_> {
+        PluginInput::new("src/lib.rs", SupportedLanguage::Rust, content, &[])
+    }
+
+    #[test]
+    fn test_script_flags_lines() {
+        let matches = run(UNWRAP_IN_RESULT_FN, &input(SOURCE)).unwrap();
+        assert_eq!(
+            matches,
+            vec![PluginMatch {
+                line: 2,
+                column: 1,
+                message: Some("unwrap at column 21".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_script_is_compiled_once() {
+        let script = "flag(lines.len());";
+        assert_eq!(run(script, &input("a\nb\n")).unwrap()[0].line, 2);
+        let first = compiled(script).unwrap();
+        assert_eq!(run(script, &input("a\n")).unwrap()[0].line, 1);
+        assert!(Rc::ptr_eq(&first, &compiled(script).unwrap()));
+    }
+
+    #[test]
+    fn test_script_sandbox() {
+        assert!(compile("let x = ;").is_err());
+        assert!(run(r#"import "other" as other;"#, &input("")).is_err());
+        assert!(run(r#"eval("flag(1)")"#, &input("")).is_err());
+        assert!(run(r#"regex_match("x", "(")"#, &input("")).is_err());
+
+        let error = run("loop {}", &input("")).unwrap_err().to_string();
+        assert!(error.contains("operations"), "{error}");
+    }
+
+    #[test]
+    fn test_file_exists_is_relative_to_the_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("docs")).unwrap();
+        std::fs::write(temp_dir.path().join("docs/guide.md"), "# Guide").unwrap();
+        let path = temp_dir
+            .path()
+            .join("README.md")
+            .to_string_lossy()
+            .to_string();
+        let input = PluginInput::new(&path, SupportedLanguage::Rust, "", &[]);
+
+        let script = r#"
+            if file_exists("docs/guide.md") { flag(1); }
+            if file_exists("./docs") { flag(2); }
+            if file_exists("docs/missing.md") { flag(3); }
+            if file_exists("docs/../docs/guide.md") { flag(4); }
+        "#;
+        let lines: Vec<_> = run(script, &input)
+            .unwrap()
+            .into_iter()
+            .map(|found| found.line)
+            .collect();
+        assert_eq!(lines, vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn test_file_exists_stays_in_the_project() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir_all(project.join(".git")).unwrap();
+        std::fs::create_dir_all(project.join("docs")).unwrap();
+        std::fs::write(project.join("README.md"), "# Project").unwrap();
+        std::fs::write(temp_dir.path().join("secret.txt"), "").unwrap();
+        let path = project.join("docs/guide.md").to_string_lossy().to_string();
+        let input = PluginInput::new(&path, SupportedLanguage::Markdown, "", &[]);
+
+        let outside = temp_dir.path().join("secret.txt");
+        let script = format!(
+            r#"
+            if file_exists("../README.md") {{ flag(1); }}
+            if file_exists("../../secret.txt") {{ flag(2); }}
+            if file_exists("{}") {{ flag(3); }}
+            if file_exists("/") {{ flag(4); }}
+            "#,
+            outside.to_string_lossy().replace('\\', "/")
+        );
+        let lines: Vec<_> = run(&script, &input)
+            .unwrap()
+            .into_iter()
+            .map(|found| found.line)
+            .collect();
+        assert_eq!(lines, vec![1]);
+    }
+}
@@ -0,0 +1,834 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Agent session transcripts.
+//!
+//! Sessions are JSONL files with one entry per line, as written by Claude Code
+//! under `~/.claude/projects/<project>/<session-id>.jsonl`. Only the fields
+//! sniff needs are modeled; everything else is ignored so newer transcript
+//! versions keep loading.
+
+use crate::analysis::{MisalignmentAnalyzer, MisalignmentDetection};
+use crate::error::{Result, SniffError};
+use crate::playbook::{DetectionRule, PatternType, Severity};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// A single line of a session transcript.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEntry {
+    /// Entry type (`user`, `assistant`, `summary`, ...).
+    #[serde(rename = "type", default)]
+    pub entry_type: String,
+    /// Unique id of the entry.
+    #[serde(default)]
+    pub uuid: Option<String>,
+    /// Session the entry belongs to.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// When the entry was written.
+    #[serde(default)]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Working directory of the agent.
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// The message carried by `user` and `assistant` entries.
+    #[serde(default)]
+    pub message: Option<SessionMessage>,
+}
+
+/// A user or assistant message.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SessionMessage {
+    /// API message id. Transcripts split one message across several entries.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Message role (`user` or `assistant`).
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Model that produced an assistant message.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Message content.
+    #[serde(default)]
+    pub content: MessageContent,
+    /// Token usage reported for an assistant message.
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+}
+
+/// Message content, either plain text or a list of content blocks.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    /// Plain text content.
+    Text(String),
+    /// Structured content blocks.
+    Blocks(Vec<ContentBlock>),
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        Self::Blocks(Vec::new())
+    }
+}
+
+/// A structured content block.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    /// Text shown to the user.
+    Text {
+        /// The text.
+        text: String,
+    },
+    /// Extended thinking of the assistant.
+    Thinking {
+        /// The thinking text.
+        thinking: String,
+    },
+    /// A tool invocation by the assistant.
+    ToolUse {
+        /// Tool use id, referenced by the matching result.
+        id: String,
+        /// Tool name (`Write`, `Edit`, `Bash`, ...).
+        name: String,
+        /// Tool input.
+        #[serde(default)]
+        input: Value,
+    },
+    /// The result of a tool invocation.
+    ToolResult {
+        /// Id of the tool use this result answers.
+        tool_use_id: String,
+        /// Result content, a string or a list of blocks.
+        #[serde(default)]
+        content: Value,
+    },
+    /// Any other block type.
+    #[serde(other)]
+    Other,
+}
+
+/// Token usage of an assistant message.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Input tokens.
+    #[serde(default)]
+    pub input_tokens: u64,
+    /// Output tokens.
+    #[serde(default)]
+    pub output_tokens: u64,
+    /// Input tokens written to the prompt cache.
+    #[serde(default)]
+    pub cache_creation_input_tokens: u64,
+    /// Input tokens read from the prompt cache.
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Returns the total number of tokens, including cached input.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+}
+
+/// A tool invocation together with where it happened in the session.
+#[derive(Debug, Clone)]
+pub struct ToolUse<'a> {
+    /// 1-based position of the tool use within the session.
+    pub step: usize,
+    /// Tool use id.
+    pub id: &'a str,
+    /// Tool name.
+    pub name: &'a str,
+    /// Tool input.
+    pub input: &'a Value,
+    /// When the invoking message was written.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Uuid of the invoking message.
+    pub message_uuid: Option<&'a str>,
+}
+
+/// The result of a tool invocation, matched to the tool that produced it.
+#[derive(Debug, Clone)]
+pub struct ToolResult<'a> {
+    /// Id of the tool use this result answers.
+    pub tool_use_id: &'a str,
+    /// Name of the tool, if its invocation is in the transcript.
+    pub tool_name: Option<&'a str>,
+    /// Text of the result.
+    pub text: String,
+    /// When the result was written.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Uuid of the message carrying the result.
+    pub message_uuid: Option<&'a str>,
+}
+
+/// Code written to a file by a `Write`, `Edit` or `MultiEdit` tool use.
+#[derive(Debug, Clone)]
+pub struct CodeWrite<'a> {
+    /// 1-based position of the tool use within the session.
+    pub step: usize,
+    /// Tool that wrote the code.
+    pub tool: &'a str,
+    /// Target file.
+    pub file_path: &'a str,
+    /// Code that was written.
+    pub content: &'a str,
+    /// When the code was written.
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// What the ruleset detects in one code write of a replayed session.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStep {
+    /// 1-based position of the tool use within the session.
+    pub step: usize,
+    /// Tool that wrote the code.
+    pub tool: String,
+    /// Target file.
+    pub file_path: String,
+    /// When the code was written.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Detections in the written code.
+    pub detections: Vec<MisalignmentDetection>,
+}
+
+/// A thinking rule match in an assistant message.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThinkingFlag {
+    /// Rule that matched.
+    pub rule_id: String,
+    /// Human-readable rule name.
+    pub rule_name: String,
+    /// Rule severity.
+    pub severity: Severity,
+    /// Uuid of the message containing the thinking block.
+    pub message_uuid: Option<String>,
+    /// When the message was written.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Sentence of the thinking block containing the match.
+    pub excerpt: String,
+}
+
+/// Maximum length of a thinking excerpt, in characters.
+const EXCERPT_MAX_CHARS: usize = 200;
+
+/// A parsed session transcript.
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// Session id (the transcript file stem).
+    pub id: String,
+    /// Path of the transcript.
+    pub path: PathBuf,
+    /// Entries in transcript order.
+    pub entries: Vec<SessionEntry>,
+}
+
+impl Session {
+    /// Returns the default directory holding session transcripts.
+    #[must_use]
+    pub fn default_projects_dir() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".claude").join("projects"))
+    }
+
+    /// Resolves a session id or transcript path to a transcript file.
+    ///
+    /// Ids are looked up as `<projects_dir>/*/<id>.jsonl`.
+    pub fn locate(id_or_path: &str, projects_dir: &Path) -> Result<PathBuf> {
+        let path = Path::new(id_or_path);
+        if path.is_file() {
+            return Ok(path.to_path_buf());
+        }
+
+        let file_name = format!("{id_or_path}.jsonl");
+        Self::discover(projects_dir)?
+            .into_iter()
+            .find(|candidate| {
+                candidate
+                    .file_name()
+                    .is_some_and(|name| name == file_name.as_str())
+            })
+            .ok_or_else(|| {
+                SniffError::invalid_session(format!(
+                    "No session '{id_or_path}' under {}",
+                    projects_dir.display()
+                ))
+            })
+    }
+
+    /// Lists all transcripts under a projects directory, sorted by path.
+    pub fn discover(projects_dir: &Path) -> Result<Vec<PathBuf>> {
+        if !projects_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut sessions: Vec<PathBuf> = walkdir::WalkDir::new(projects_dir)
+            .max_depth(2)
+            .into_iter()
+            .filter_map(std::result::Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .map(walkdir::DirEntry::into_path)
+            .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect();
+        sessions.sort();
+
+        Ok(sessions)
+    }
+
+    /// Loads a transcript. Malformed lines are skipped with a warning.
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = std::fs::File::open(path).map_err(|e| SniffError::file_system(path, e))?;
+
+        let mut session = Self::empty(path);
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| SniffError::file_system(path, e))?;
+            if let Err(warning) = session.push_line(&line, index + 1) {
+                warn!("{warning}");
+            }
+        }
+
+        Ok(session)
+    }
+
+    /// Returns a session with no entries for the transcript at `path`.
+    fn empty(path: &Path) -> Self {
+        let id = path
+            .file_stem()
+            .map_or_else(String::new, |stem| stem.to_string_lossy().to_string());
+
+        Self {
+            id,
+            path: path.to_path_buf(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Parses one transcript line, skipping blank lines. Returns a warning
+    /// for a malformed line.
+    fn push_line(&mut self, line: &str, line_number: usize) -> std::result::Result<(), String> {
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        match serde_json::from_str(line) {
+            Ok(entry) => {
+                self.entries.push(entry);
+                Ok(())
+            }
+            Err(e) => Err(format!(
+                "Skipping malformed session entry at {}:{}: {}",
+                self.path.display(),
+                line_number,
+                e
+            )),
+        }
+    }
+
+    /// Returns the first and last timestamps of the session.
+    #[must_use]
+    pub fn time_range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let mut timestamps = self.entries.iter().filter_map(|entry| entry.timestamp);
+        let first = timestamps.next()?;
+        let (start, end) =
+            timestamps.fold((first, first), |(start, end), t| (start.min(t), end.max(t)));
+        Some((start, end))
+    }
+
+    /// Returns the working directory the session started in.
+    #[must_use]
+    pub fn project_dir(&self) -> Option<PathBuf> {
+        self.entries
+            .iter()
+            .find_map(|entry| entry.cwd.as_ref())
+            .map(PathBuf::from)
+    }
+
+    /// Returns the models that produced assistant messages, in order of first use.
+    #[must_use]
+    pub fn models(&self) -> Vec<String> {
+        let mut models: Vec<String> = Vec::new();
+        for model in self
+            .entries
+            .iter()
+            .filter_map(|entry| entry.message.as_ref()?.model.as_ref())
+        {
+            if !models.contains(model) {
+                models.push(model.clone());
+            }
+        }
+        models
+    }
+
+    /// Returns the token usage summed over all assistant messages.
+    ///
+    /// Entries that repeat the usage of an already counted message are skipped.
+    #[must_use]
+    pub fn token_usage(&self) -> TokenUsage {
+        let mut seen = std::collections::HashSet::new();
+        let mut total = TokenUsage::default();
+        for message in self
+            .entries
+            .iter()
+            .filter_map(|entry| entry.message.as_ref())
+        {
+            let Some(usage) = &message.usage else {
+                continue;
+            };
+            if let Some(id) = &message.id {
+                if !seen.insert(id.as_str()) {
+                    continue;
+                }
+            }
+            total.input_tokens += usage.input_tokens;
+            total.output_tokens += usage.output_tokens;
+            total.cache_creation_input_tokens += usage.cache_creation_input_tokens;
+            total.cache_read_input_tokens += usage.cache_read_input_tokens;
+        }
+        total
+    }
+
+    /// Returns the text of the last assistant message that contains text.
+    #[must_use]
+    pub fn final_assistant_text(&self) -> Option<String> {
+        self.entries.iter().rev().find_map(|entry| {
+            let message = entry.message.as_ref()?;
+            if message.role.as_deref() != Some("assistant") {
+                return None;
+            }
+            let text = match &message.content {
+                MessageContent::Text(text) => text.clone(),
+                MessageContent::Blocks(blocks) => blocks
+                    .iter()
+                    .filter_map(|block| match block {
+                        ContentBlock::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            };
+            (!text.trim().is_empty()).then_some(text)
+        })
+    }
+
+    /// Returns all tool uses in transcript order.
+    #[must_use]
+    pub fn tool_uses(&self) -> Vec<ToolUse<'_>> {
+        let mut tool_uses = Vec::new();
+        for entry in &self.entries {
+            let Some(MessageContent::Blocks(blocks)) = entry.message.as_ref().map(|m| &m.content)
+            else {
+                continue;
+            };
+            for block in blocks {
+                if let ContentBlock::ToolUse { id, name, input } = block {
+                    tool_uses.push(ToolUse {
+                        step: tool_uses.len() + 1,
+                        id,
+                        name,
+                        input,
+                        timestamp: entry.timestamp,
+                        message_uuid: entry.uuid.as_deref(),
+                    });
+                }
+            }
+        }
+        tool_uses
+    }
+
+    /// Returns all tool results in transcript order.
+    #[must_use]
+    pub fn tool_results(&self) -> Vec<ToolResult<'_>> {
+        let tool_names: HashMap<&str, &str> = self
+            .tool_uses()
+            .into_iter()
+            .map(|tool_use| (tool_use.id, tool_use.name))
+            .collect();
+        let mut results = Vec::new();
+        for entry in &self.entries {
+            let Some(MessageContent::Blocks(blocks)) = entry.message.as_ref().map(|m| &m.content)
+            else {
+                continue;
+            };
+            for block in blocks {
+                if let ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                } = block
+                {
+                    results.push(ToolResult {
+                        tool_use_id,
+                        tool_name: tool_names.get(tool_use_id.as_str()).copied(),
+                        text: result_text(content),
+                        timestamp: entry.timestamp,
+                        message_uuid: entry.uuid.as_deref(),
+                    });
+                }
+            }
+        }
+        results
+    }
+
+    /// Returns the code written by file-editing tool uses, in transcript order.
+    #[must_use]
+    pub fn code_writes(&self) -> Vec<CodeWrite<'_>> {
+        let mut writes = Vec::new();
+        for tool_use in self.tool_uses() {
+            let Some(file_path) = tool_use.input.get("file_path").and_then(Value::as_str) else {
+                continue;
+            };
+            let contents: Vec<&Value> = match tool_use.name {
+                "Write" => tool_use.input.get("content").into_iter().collect(),
+                "Edit" => tool_use.input.get("new_string").into_iter().collect(),
+                "MultiEdit" => tool_use
+                    .input
+                    .get("edits")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|edit| edit.get("new_string"))
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            for content in contents.into_iter().filter_map(Value::as_str) {
+                writes.push(CodeWrite {
+                    step: tool_use.step,
+                    tool: tool_use.name,
+                    file_path,
+                    content,
+                    timestamp: tool_use.timestamp,
+                });
+            }
+        }
+        writes
+    }
+
+    /// Applies thinking rules to the assistant's thinking blocks.
+    ///
+    /// Rules with invalid or non-regex patterns are skipped.
+    #[must_use]
+    pub fn thinking_flags(&self, rules: &[DetectionRule]) -> Vec<ThinkingFlag> {
+        let compiled: Vec<(&DetectionRule, regex::Regex)> = rules
+            .iter()
+            .filter_map(|rule| match &rule.pattern_type {
+                PatternType::Regex { pattern, .. } => match regex::Regex::new(pattern) {
+                    Ok(regex) => Some((rule, regex)),
+                    Err(e) => {
+                        warn!("Skipping thinking rule '{}': {}", rule.id, e);
+                        None
+                    }
+                },
+                _ => None,
+            })
+            .collect();
+
+        let mut flags = Vec::new();
+        for entry in &self.entries {
+            let Some(MessageContent::Blocks(blocks)) = entry.message.as_ref().map(|m| &m.content)
+            else {
+                continue;
+            };
+            for block in blocks {
+                let ContentBlock::Thinking { thinking } = block else {
+                    continue;
+                };
+                for (rule, regex) in &compiled {
+                    if let Some(found) = regex.find(thinking) {
+                        flags.push(ThinkingFlag {
+                            rule_id: rule.id.clone(),
+                            rule_name: rule.name.clone(),
+                            severity: rule.severity,
+                            message_uuid: entry.uuid.clone(),
+                            timestamp: entry.timestamp,
+                            excerpt: sentence_around(thinking, found.start(), found.end()),
+                        });
+                    }
+                }
+            }
+        }
+        flags
+    }
+
+    /// Runs the ruleset over every code write in the session.
+    ///
+    /// Writes to files in unsupported languages are skipped. Each returned step
+    /// shows what sniff would have caught at that point of the session.
+    pub fn replay(&self, analyzer: &mut MisalignmentAnalyzer) -> Result<Vec<ReplayStep>> {
+        let mut steps = Vec::new();
+        for write in self.code_writes() {
+            let file_path = Path::new(write.file_path);
+            if analyzer.detect_language(file_path)?.is_none() {
+                continue;
+            }
+
+            let detections = match analyzer.analyze_content(write.content, file_path) {
+                Ok(detections) => detections,
+                Err(e) => {
+                    warn!("Skipping step {} ({}): {}", write.step, write.file_path, e);
+                    continue;
+                }
+            };
+
+            steps.push(ReplayStep {
+                step: write.step,
+                tool: write.tool.to_string(),
+                file_path: write.file_path.to_string(),
+                timestamp: write.timestamp,
+                detections,
+            });
+        }
+        Ok(steps)
+    }
+}
+
+/// Flattens tool result content, a string or a list of text blocks, into text.
+fn result_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Returns the sentence of `text` containing the byte range `start..end`.
+pub(crate) fn sentence_around(text: &str, start: usize, end: usize) -> String {
+    let is_boundary = |c: char| matches!(c, '.' | '!' | '?' | '\n');
+    let sentence_start = text[..start]
+        .rfind(is_boundary)
+        .map_or(0, |index| index + 1);
+    let sentence_end = text[end..]
+        .find(is_boundary)
+        .map_or(text.len(), |index| end + index + 1);
+
+    let sentence = text[sentence_start..sentence_end].trim();
+    if sentence.chars().count() > EXCERPT_MAX_CHARS {
+        let truncated: String = sentence.chars().take(EXCERPT_MAX_CHARS).collect();
+        format!("{truncated}...")
+    } else {
+        sentence.to_string()
+    }
+}
+
+/// A transcript read incrementally while it grows.
+///
+/// Each read parses only the complete lines appended since the previous one,
+/// so a live transcript is not re-read from the start on every change.
+#[derive(Debug)]
+pub struct SessionTail {
+    session: Session,
+    /// Byte offset just past the last complete line read.
+    offset: u64,
+    /// Number of complete lines read.
+    lines: usize,
+}
+
+impl SessionTail {
+    /// Starts tailing the transcript at `path` from its beginning.
+    #[must_use]
+    pub fn new(path: &Path) -> Self {
+        Self {
+            session: Session::empty(path),
+            offset: 0,
+            lines: 0,
+        }
+    }
+
+    /// Returns the entries read so far.
+    #[must_use]
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Reads the complete lines appended since the last read and returns a
+    /// warning for each malformed one, instead of logging it. A line still
+    /// being written is left for the next read, and a transcript that shrank
+    /// is read again from the start.
+    pub fn read_appended(&mut self) -> Result<Vec<String>> {
+        let path = self.session.path.clone();
+        let mut file = std::fs::File::open(&path).map_err(|e| SniffError::file_system(&path, e))?;
+        let len = file
+            .metadata()
+            .map_err(|e| SniffError::file_system(&path, e))?
+            .len();
+        if len < self.offset {
+            self.offset = 0;
+            self.lines = 0;
+            self.session.entries.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))
+            .map_err(|e| SniffError::file_system(&path, e))?;
+
+        let mut warnings = Vec::new();
+        let mut reader = BufReader::new(file);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader
+                .read_until(b'\n', &mut line)
+                .map_err(|e| SniffError::file_system(&path, e))?;
+            if read == 0 || line.last() != Some(&b'\n') {
+                break;
+            }
+            self.offset += read as u64;
+            self.lines += 1;
+            if let Err(warning) = self
+                .session
+                .push_line(&String::from_utf8_lossy(&line), self.lines)
+            {
+                warnings.push(warning);
+            }
+        }
+
+        Ok(warnings)
+    }
+}
+
+/// Fixtures shared by the tests of modules that consume sessions.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::Session;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    /// Loads a session from transcript entries, written one per JSONL line.
+    pub(crate) fn session(entries: &[serde_json::Value]) -> Session {
+        let mut file = NamedTempFile::new().unwrap();
+        for entry in entries {
+            writeln!(file, "{entry}").unwrap();
+        }
+        Session::load(file.path()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write_session(dir: &Path, id: &str, lines: &[Value]) -> PathBuf {
+        let project_dir = dir.join("-work-project");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        let path = project_dir.join(format!("{id}.jsonl"));
+        let mut file = std::fs::File::create(&path).unwrap();
+        for line in lines {
+            writeln!(file, "{line}").unwrap();
+        }
+        writeln!(file, "{{truncated").unwrap();
+        path
+    }
+
+    #[test]
+    fn test_session_extracts_code_writes() {
+        let temp_dir = TempDir::new().unwrap();
+        write_session(
+            temp_dir.path(),
+            "abc-123",
+            &[
+                serde_json::json!({"type": "user", "message": {"role": "user", "content": "add a parser"}}),
+                serde_json::json!({
+                    "type": "assistant",
+                    "uuid": "m1",
+                    "timestamp": "2025-06-01T10:00:00Z",
+                    "message": {"role": "assistant", "model": "claude-sonnet-4", "content": [
+                        {"type": "thinking", "thinking": "plan"},
+                        {"type": "tool_use", "id": "t1", "name": "Write",
+                         "input": {"file_path": "src/parser.rs", "content": "fn parse() {}"}},
+                        {"type": "tool_use", "id": "t2", "name": "MultiEdit",
+                         "input": {"file_path": "src/lib.rs", "edits": [
+                             {"old_string": "a", "new_string": "b"},
+                             {"old_string": "c", "new_string": "d"}]}},
+                        {"type": "tool_use", "id": "t3", "name": "Bash", "input": {"command": "ls"}}
+                    ]}
+                }),
+            ],
+        );
+
+        let path = Session::locate("abc-123", temp_dir.path()).unwrap();
+        let session = Session::load(&path).unwrap();
+        assert_eq!(session.id, "abc-123");
+        assert_eq!(session.entries.len(), 2);
+        assert_eq!(session.tool_uses().len(), 3);
+
+        let writes = session.code_writes();
+        assert_eq!(writes.len(), 3);
+        assert_eq!(writes[0].file_path, "src/parser.rs");
+        assert_eq!(writes[2].content, "d");
+        assert_eq!(writes[2].step, 2);
+
+        assert!(Session::locate("missing", temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_thinking_flags() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = write_session(
+            temp_dir.path(),
+            "thinking",
+            &[serde_json::json!({
+                "type": "assistant",
+                "uuid": "m1",
+                "message": {"role": "assistant", "content": [
+                    {"type": "thinking", "thinking": "The API is flaky. I'll just hardcode this for now. Then continue."},
+                    {"type": "text", "text": "I'll just hardcode this for now"}
+                ]}
+            })],
+        );
+        let session = Session::load(&path).unwrap();
+
+        let playbook = crate::playbook::PlaybookManager::create_session_default_playbook();
+        let flags = session.thinking_flags(&playbook.rules);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].rule_id, "thinking_hardcode_intent");
+        assert_eq!(flags[0].excerpt, "I'll just hardcode this for now.");
+        assert_eq!(flags[0].message_uuid.as_deref(), Some("m1"));
+    }
+
+    #[test]
+    fn test_session_tail_reads_appended_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = write_session(
+            temp_dir.path(),
+            "live",
+            &[serde_json::json!({"type": "user", "message": {"role": "user", "content": "a"}})],
+        );
+        let mut tail = SessionTail::new(&path);
+        let warnings = tail.read_appended().unwrap();
+        assert_eq!(tail.session().entries.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("live.jsonl:2:"));
+
+        // Only the new lines are parsed, and a partial line waits for the rest
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(file, "{{\"type\": \"user\"}}\n{{\"type\": \"user\"").unwrap();
+        assert!(tail.read_appended().unwrap().is_empty());
+        assert_eq!(tail.session().entries.len(), 2);
+        writeln!(file, "}}").unwrap();
+        assert!(tail.read_appended().unwrap().is_empty());
+        assert_eq!(tail.session().entries.len(), 3);
+
+        // A rewritten transcript is read again
+        std::fs::write(&path, "{\"type\": \"user\"}\n").unwrap();
+        tail.read_appended().unwrap();
+        assert_eq!(tail.session().entries.len(), 1);
+    }
+}
@@ -0,0 +1,356 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Enforceable policies for agent session behavior.
+//!
+//! A policy is a YAML file (by default `.sniff/session-policy.yaml`) listing
+//! banned commands, banned paths and the test commands that must run after
+//! the last code change before the agent may claim the work is complete:
+//!
+//! ```yaml
+//! banned_commands:
+//!   - id: no-force-push
+//!     pattern: 'git\s+push\s+.*--force'
+//!     severity: Critical
+//! banned_paths:
+//!   - id: no-secrets
+//!     pattern: '(^|/)\.env$'
+//!     operations: [read, write, edit]
+//! require_tests:
+//!   commands: ['cargo test', 'npm test']
+//! ```
+
+use crate::error::{Result, SniffError};
+use crate::file_access::file_accesses;
+use crate::playbook::Severity;
+use crate::scorecard::claims_completion;
+use crate::session::Session;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Default location of the session policy within a project.
+pub const DEFAULT_POLICY_PATH: &str = ".sniff/session-policy.yaml";
+
+fn default_severity() -> Severity {
+    Severity::High
+}
+
+/// On-disk layout of a session policy.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    banned_commands: Vec<RuleSpec>,
+    #[serde(default)]
+    banned_paths: Vec<RuleSpec>,
+    #[serde(default)]
+    require_tests: Option<TestSpec>,
+}
+
+/// A banned command or path as written in the policy file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSpec {
+    id: String,
+    pattern: String,
+    #[serde(default = "default_severity")]
+    severity: Severity,
+    #[serde(default)]
+    message: Option<String>,
+    /// File operations the rule applies to (`read`, `write`, `edit`, `search`).
+    /// Empty means all. Only used by banned paths.
+    #[serde(default)]
+    operations: Vec<String>,
+}
+
+/// The test-run requirement as written in the policy file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TestSpec {
+    commands: Vec<String>,
+    #[serde(default = "default_severity")]
+    severity: Severity,
+}
+
+/// A compiled banned command or path.
+#[derive(Debug)]
+struct PolicyRule {
+    id: String,
+    regex: Regex,
+    severity: Severity,
+    message: Option<String>,
+    operations: Vec<String>,
+}
+
+/// A compiled test-run requirement.
+#[derive(Debug)]
+struct TestRequirement {
+    commands: Vec<Regex>,
+    severity: Severity,
+}
+
+/// A session behavior policy.
+#[derive(Debug)]
+pub struct SessionPolicy {
+    banned_commands: Vec<PolicyRule>,
+    banned_paths: Vec<PolicyRule>,
+    require_tests: Option<TestRequirement>,
+}
+
+/// A policy rule broken by a session.
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyViolation {
+    /// Session that broke the rule.
+    pub session_id: String,
+    /// Id of the broken rule (`require_tests` for the test-run requirement).
+    pub rule_id: String,
+    /// Kind of violation: `banned_command`, `banned_path` or `missing_test_run`.
+    pub kind: String,
+    /// Severity of the violation.
+    pub severity: Severity,
+    /// What went wrong.
+    pub message: String,
+    /// 1-based position of the offending tool use, if any.
+    pub step: Option<usize>,
+    /// When the offending tool use was issued.
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The offending command or path.
+    pub evidence: Option<String>,
+}
+
+impl SessionPolicy {
+    /// Loads and compiles a policy file.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(SniffError::invalid_format(
+                "session policy".to_string(),
+                format!("No policy at {}", path.display()),
+            ));
+        }
+        let content =
+            std::fs::read_to_string(path).map_err(|e| SniffError::file_system(path, e))?;
+        Self::parse(&content, &path.display().to_string())
+    }
+
+    /// Parses and compiles a policy from YAML.
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        Self::parse(content, "session policy")
+    }
+
+    /// Parses and compiles a policy, naming `source` in errors.
+    fn parse(content: &str, source: &str) -> Result<Self> {
+        let file: PolicyFile = serde_yaml::from_str(content)
+            .map_err(|e| SniffError::invalid_format(source.to_string(), e.to_string()))?;
+
+        let compile = |pattern: &str| {
+            Regex::new(pattern).map_err(|e| {
+                SniffError::invalid_format(
+                    source.to_string(),
+                    format!("invalid pattern '{pattern}': {e}"),
+                )
+            })
+        };
+        let compile_rules = |specs: Vec<RuleSpec>| -> Result<Vec<PolicyRule>> {
+            specs
+                .into_iter()
+                .map(|spec| {
+                    Ok(PolicyRule {
+                        regex: compile(&spec.pattern)?,
+                        id: spec.id,
+                        severity: spec.severity,
+                        message: spec.message,
+                        operations: spec.operations,
+                    })
+                })
+                .collect()
+        };
+
+        Ok(Self {
+            banned_commands: compile_rules(file.banned_commands)?,
+            banned_paths: compile_rules(file.banned_paths)?,
+            require_tests: file
+                .require_tests
+                .map(|spec| -> Result<TestRequirement> {
+                    Ok(TestRequirement {
+                        commands: spec
+                            .commands
+                            .iter()
+                            .map(|command| compile(command))
+                            .collect::<Result<_>>()?,
+                        severity: spec.severity,
+                    })
+                })
+                .transpose()?,
+        })
+    }
+
+    /// Evaluates a session against the policy, returning violations in transcript order.
+    #[must_use]
+    pub fn evaluate(&self, session: &Session) -> Vec<PolicyViolation> {
+        let violation = |rule_id: &str, kind: &str, severity, message: String| PolicyViolation {
+            session_id: session.id.clone(),
+            rule_id: rule_id.to_string(),
+            kind: kind.to_string(),
+            severity,
+            message,
+            step: None,
+            timestamp: None,
+            evidence: None,
+        };
+        let mut violations = Vec::new();
+
+        let commands: Vec<_> = session
+            .tool_uses()
+            .into_iter()
+            .filter(|tool_use| tool_use.name == "Bash")
+            .filter_map(|tool_use| {
+                let command = tool_use.input.get("command").and_then(Value::as_str)?;
+                Some((tool_use, command))
+            })
+            .collect();
+
+        for (tool_use, command) in &commands {
+            for rule in self
+                .banned_commands
+                .iter()
+                .filter(|rule| rule.regex.is_match(command))
+            {
+                violations.push(PolicyViolation {
+                    step: Some(tool_use.step),
+                    timestamp: tool_use.timestamp,
+                    evidence: Some((*command).to_string()),
+                    ..violation(
+                        &rule.id,
+                        "banned_command",
+                        rule.severity,
+                        rule.message
+                            .clone()
+                            .unwrap_or_else(|| format!("ran banned command '{}'", rule.id)),
+                    )
+                });
+            }
+        }
+
+        for access in file_accesses(session) {
+            let relative = access.relative_path.as_deref();
+            for rule in self.banned_paths.iter().filter(|rule| {
+                (rule.operations.is_empty() || rule.operations.contains(&access.operation))
+                    && (rule.regex.is_match(&access.path)
+                        || relative.is_some_and(|path| rule.regex.is_match(path)))
+            }) {
+                violations.push(PolicyViolation {
+                    step: Some(access.step),
+                    timestamp: access.timestamp,
+                    evidence: Some(access.path.clone()),
+                    ..violation(
+                        &rule.id,
+                        "banned_path",
+                        rule.severity,
+                        rule.message.clone().unwrap_or_else(|| {
+                            format!("{} of banned path '{}'", access.operation, rule.id)
+                        }),
+                    )
+                });
+            }
+        }
+
+        if let Some(requirement) = &self.require_tests {
+            let claims_done = session
+                .final_assistant_text()
+                .is_some_and(|text| claims_completion(&text));
+            if let Some(last_write) = session.code_writes().last().filter(|_| claims_done) {
+                let tested = commands.iter().any(|(tool_use, command)| {
+                    tool_use.step > last_write.step
+                        && requirement
+                            .commands
+                            .iter()
+                            .any(|regex| regex.is_match(command))
+                });
+                if !tested {
+                    violations.push(PolicyViolation {
+                        step: Some(last_write.step),
+                        timestamp: last_write.timestamp,
+                        evidence: Some(last_write.file_path.to_string()),
+                        ..violation(
+                            "require_tests",
+                            "missing_test_run",
+                            requirement.severity,
+                            "claimed completion without running tests after the last code change"
+                                .to_string(),
+                        )
+                    });
+                }
+            }
+        }
+
+        violations.sort_by_key(|violation| violation.step);
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::test_support;
+
+    const POLICY: &str = r"
+banned_commands:
+  - id: no-force-push
+    pattern: 'git\s+push\s+.*--force'
+    severity: Critical
+banned_paths:
+  - id: no-env
+    pattern: '(^|/)\.env$'
+require_tests:
+  commands: ['cargo test']
+";
+
+    fn session(commands: &[&str]) -> Session {
+        let mut blocks = vec![
+            serde_json::json!({"type": "tool_use", "id": "r1", "name": "Read", "input": {"file_path": ".env"}}),
+            serde_json::json!({"type": "tool_use", "id": "w1", "name": "Write",
+                               "input": {"file_path": "src/lib.rs", "content": "fn a() {}"}}),
+        ];
+        for (index, command) in commands.iter().enumerate() {
+            blocks.push(
+                serde_json::json!({"type": "tool_use", "id": format!("b{index}"),
+                                           "name": "Bash", "input": {"command": command}}),
+            );
+        }
+        let lines = [
+            serde_json::json!({"type": "assistant", "cwd": "/work/project",
+                               "message": {"role": "assistant", "content": blocks}}),
+            serde_json::json!({"type": "assistant", "message": {"role": "assistant",
+                               "content": "Done, the feature is fully implemented."}}),
+        ];
+        test_support::session(&lines)
+    }
+
+    #[test]
+    fn test_policy_violations() {
+        let policy = SessionPolicy::from_yaml(POLICY).unwrap();
+
+        let violations = policy.evaluate(&session(&["git push origin main --force"]));
+        let kinds: Vec<&str> = violations.iter().map(|v| v.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            vec!["banned_path", "missing_test_run", "banned_command"]
+        );
+        assert_eq!(violations[2].severity, Severity::Critical);
+
+        let violations = policy.evaluate(&session(&["cargo test --workspace"]));
+        let kinds: Vec<&str> = violations.iter().map(|v| v.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["banned_path"]);
+    }
+
+    #[test]
+    fn test_invalid_policy_is_rejected() {
+        assert!(
+            SessionPolicy::from_yaml("banned_commands:\n  - id: x\n    pattern: '('\n").is_err()
+        );
+        assert!(SessionPolicy::from_yaml("unknown_key: true\n").is_err());
+    }
+}
@@ -7,18 +7,34 @@
 //! create checkpoints for change tracking, and integrate with editors like Cursor,
 //! Windsurf, and VS Code.
 
-use crate::analysis::{MisalignmentAnalyzer, MisalignmentDetection, TestFileClassifier};
+use crate::ai_likelihood::{self, AiLikelihood, SessionWrites};
+use crate::analysis::{
+    EnhancedMisalignmentAnalysis, MisalignmentAnalyzer, MisalignmentDetection, PerformanceReport,
+    QualityAssessment, SuppressedDetection, TestFileClassifier,
+};
 use crate::error::{Result, SniffError};
+use crate::function_quality::{self, ChangedFunction, FileFunction, FunctionScore};
+use crate::network::{self, NetworkStatus};
+use crate::playbook::{glob_match_path, PathFilter, Severity, DEFAULT_DEPRECATION_GRACE_DAYS};
+use crate::project_config::ConfigResolver;
+use crate::suppression::{self, InlineSuppression};
 use crate::SupportedLanguage;
 use chrono::{DateTime, Utc};
+use rust_tree_sitter::analyzer::Symbol;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tokio::fs;
 use tracing::{debug, info, warn};
 
+/// File name of analysis-specific ignore files, in `.gitignore` syntax.
+pub const SNIFF_IGNORE_FILE_NAME: &str = ".sniffignore";
+
 /// Configuration for standalone file analysis.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)] // Independent CLI toggles, not a state machine
 pub struct AnalysisConfig {
     /// File filtering configuration.
     pub filter: FileFilter,
@@ -26,6 +42,85 @@ pub struct AnalysisConfig {
     pub force_language: Option<SupportedLanguage>,
     /// Enable detailed analysis with additional context.
     pub detailed_analysis: bool,
+    /// Include heuristic AI insights in detailed analysis.
+    pub include_insights: bool,
+    /// Run enhanced analysis with performance impact and quality assessment.
+    pub enhanced_analysis: bool,
+    /// Only analyze the files belonging to this shard.
+    pub shard: Option<Shard>,
+    /// Keep detections suppressed by test context, with the reason, in the results.
+    pub show_suppressed: bool,
+    /// Make results byte-for-byte identical for the same inputs: a fixed run
+    /// timestamp and every list in a stable order.
+    pub reproducible: bool,
+    /// Estimate how likely each file is to be AI-generated.
+    pub ai_likelihood: bool,
+    /// Also analyze YAML and JSON configuration files.
+    pub config_files: bool,
+    /// Also analyze Markdown documentation.
+    pub markdown: bool,
+    /// Keep the functions of analyzed files, so function scores reuse the parse.
+    pub function_scores: bool,
+}
+
+/// A deterministic slice of the discovered files, e.g. shard `2/8`.
+///
+/// Files are assigned to shards by a stable hash of their path, so every worker
+/// given the same paths computes the same partition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// 1-based index of this shard.
+    pub index: usize,
+    /// Total number of shards.
+    pub total: usize,
+}
+
+impl Shard {
+    /// Returns whether a file belongs to this shard.
+    ///
+    /// The path is hashed with `/` separators and without `.` components, so
+    /// `./src/a.rs` and `src/a.rs` land in the same shard. Discovery passes
+    /// paths relative to the project root, so workers agree on the shards
+    /// whatever directory they run in.
+    #[must_use]
+    pub fn contains(&self, path: &Path) -> bool {
+        let path: PathBuf = path
+            .components()
+            .filter(|component| !matches!(component, std::path::Component::CurDir))
+            .collect();
+        let path = path.to_string_lossy().replace('\\', "/");
+        let hash = crate::hash::stable_hash([path.as_str()]);
+        let bucket = u64::from_str_radix(&hash, 16).unwrap_or(0) % self.total as u64;
+        bucket == (self.index - 1) as u64
+    }
+}
+
+impl std::str::FromStr for Shard {
+    type Err = SniffError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            SniffError::invalid_format(
+                "shard".to_string(),
+                format!("'{s}' must be INDEX/TOTAL with 1 <= INDEX <= TOTAL, e.g. 2/8"),
+            )
+        };
+
+        let (index, total) = s.split_once('/').ok_or_else(invalid)?;
+        let index: usize = index.trim().parse().map_err(|_| invalid())?;
+        let total: usize = total.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > total {
+            return Err(invalid());
+        }
+
+        Ok(Self { index, total })
+    }
+}
+
+impl std::fmt::Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.total)
+    }
 }
 
 /// File filtering configuration.
@@ -33,6 +128,11 @@ pub struct AnalysisConfig {
 pub struct FileFilter {
     /// Include hidden files and directories.
     pub include_hidden: bool,
+    /// Skip paths ignored by `.gitignore` and `.sniffignore` files when
+    /// discovering files in directories.
+    pub respect_ignore_files: bool,
+    /// Only discover files tracked by git (`git ls-files`) in directories.
+    pub tracked_only: bool,
     /// Allowed file extensions (e.g., `["rs", "py", "ts"]`).
     pub allowed_extensions: Option<Vec<String>>,
     /// Pattern to exclude files (glob pattern).
@@ -49,6 +149,8 @@ impl Default for FileFilter {
     fn default() -> Self {
         Self {
             include_hidden: false,
+            respect_ignore_files: true,
+            tracked_only: false,
             allowed_extensions: None,
             exclude_pattern: None,
             max_file_size_bytes: 10 * 1024 * 1024, // 10MB
@@ -58,90 +160,659 @@ impl Default for FileFilter {
     }
 }
 
+/// Returns the files tracked by git under a directory, and their parent
+/// directories, relative to the directory.
+async fn tracked_paths(dir_path: &Path) -> Result<HashSet<PathBuf>> {
+    let output = tokio::process::Command::new("git")
+        .args(["ls-files", "-z", "--cached"])
+        .current_dir(dir_path)
+        .output()
+        .await
+        .map_err(|e| {
+            SniffError::project_discovery(dir_path, format!("failed to run git ls-files: {e}"))
+        })?;
+    if !output.status.success() {
+        return Err(SniffError::project_discovery(
+            dir_path,
+            format!(
+                "--tracked-only needs a git checkout: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ));
+    }
+
+    let mut paths = HashSet::new();
+    // The walk root itself is the empty path
+    paths.insert(PathBuf::new());
+    for file in output.stdout.split(|&byte| byte == 0) {
+        if file.is_empty() {
+            continue;
+        }
+        let file = PathBuf::from(String::from_utf8_lossy(file).as_ref());
+        paths.extend(file.ancestors().skip(1).map(Path::to_path_buf));
+        paths.insert(file);
+    }
+    Ok(paths)
+}
+
+/// Returns the lines added or modified since a git revision in the tracked
+/// files under `paths`, by path relative to the current directory. Deleted
+/// files are left out.
+pub async fn git_changed_lines(
+    revision: &str,
+    paths: &[PathBuf],
+) -> Result<BTreeMap<PathBuf, BTreeSet<usize>>> {
+    let output = tokio::process::Command::new("git")
+        .args(["-c", "core.quotePath=false", "diff", "--relative"])
+        .args(["--unified=0", "--no-color", "--no-ext-diff"])
+        .args(["--src-prefix=a/", "--dst-prefix=b/", "--diff-filter=d"])
+        .args([revision, "--"])
+        .args(paths)
+        .output()
+        .await
+        .map_err(|e| SniffError::analysis_error(format!("failed to run git diff: {e}")))?;
+    if !output.status.success() {
+        return Err(SniffError::analysis_error(format!(
+            "git diff against '{revision}' failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(function_quality::diff_changed_lines(
+        &String::from_utf8_lossy(&output.stdout),
+    ))
+}
+
+/// Returns the content of a file, by path relative to the current directory,
+/// as of a git revision, or `None` if the file did not exist then.
+async fn git_show(revision: &str, file_path: &Path) -> Result<Option<String>> {
+    let path = file_path.to_string_lossy().replace('\\', "/");
+    let output = tokio::process::Command::new("git")
+        .arg("show")
+        .arg(format!("{revision}:./{path}"))
+        .output()
+        .await
+        .map_err(|e| SniffError::analysis_error(format!("failed to run git show: {e}")))?;
+    Ok(output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+/// Keeps the functions and methods of a file's symbols.
+fn functions(symbols: impl IntoIterator<Item = Symbol>) -> Vec<Symbol> {
+    symbols
+        .into_iter()
+        .filter(|symbol| symbol.kind == "function" || symbol.kind == "method")
+        .collect()
+}
+
 /// Standalone analyzer for arbitrary files.
 pub struct StandaloneAnalyzer {
     misalignment_analyzer: MisalignmentAnalyzer,
     config: AnalysisConfig,
     language_detector: LanguageDetector,
     test_classifier: TestFileClassifier,
+    /// Warnings raised during the current run, drained into its results.
+    warnings: Mutex<Vec<AnalysisWarning>>,
+    /// Discovered, analyzed and skipped file counts of the current run.
+    coverage: Mutex<FileCoverage>,
+    /// Per-directory `.sniff.toml` configs of the analyzed files.
+    project_configs: ConfigResolver,
+    /// Deprecated rules already announced, so each notice is shown once.
+    deprecation_notices: HashSet<String>,
+    /// Code written by agent sessions, evidence for the AI likelihood estimate.
+    session_writes: SessionWrites,
+    /// Functions and methods of the analyzed files, kept with `function_scores`.
+    function_symbols: HashMap<PathBuf, Vec<Symbol>>,
 }
 
 impl StandaloneAnalyzer {
     /// Creates a new standalone analyzer.
     #[must_use]
-    pub fn new(misalignment_analyzer: MisalignmentAnalyzer, config: AnalysisConfig) -> Self {
+    pub fn new(mut misalignment_analyzer: MisalignmentAnalyzer, config: AnalysisConfig) -> Self {
+        // A forced language is analyzed even if it is opt-in
+        let forced = config.force_language;
+        let config_files = config.config_files || forced == Some(SupportedLanguage::Config);
+        let markdown = config.markdown || forced == Some(SupportedLanguage::Markdown);
+        misalignment_analyzer.set_collect_suppressed(config.show_suppressed);
+        misalignment_analyzer.set_config_files(config_files);
+        misalignment_analyzer.set_markdown(markdown);
+        misalignment_analyzer.set_collect_symbols(config.function_scores);
         Self {
             misalignment_analyzer,
+            language_detector: LanguageDetector::new(config_files, markdown),
             config,
-            language_detector: LanguageDetector::new(),
             test_classifier: TestFileClassifier::new(),
+            warnings: Mutex::new(Vec::new()),
+            coverage: Mutex::new(FileCoverage::default()),
+            project_configs: ConfigResolver::new(),
+            deprecation_notices: HashSet::new(),
+            session_writes: SessionWrites::default(),
+            function_symbols: HashMap::new(),
         }
     }
 
-    /// Analyzes the specified files and directories.
-    pub async fn analyze_files(&mut self, paths: &[PathBuf]) -> Result<AnalysisResults> {
+    /// Uses the code written by agent sessions as evidence when estimating how
+    /// likely files are to be AI-generated.
+    #[must_use]
+    pub fn with_session_writes(mut self, session_writes: SessionWrites) -> Self {
+        self.session_writes = session_writes;
+        self
+    }
+
+    /// Counts a file seen by discovery, and why it was skipped if it was.
+    fn count_discovered(&self, skip_reason: Option<SkipReason>) {
+        if let Ok(mut coverage) = self.coverage.lock() {
+            coverage.discovered += 1;
+            if let Some(reason) = skip_reason {
+                coverage.count_skipped(reason);
+            }
+        }
+    }
+
+    /// Records a warning for the current run.
+    fn record_warning(&self, kind: AnalysisWarningKind, path: Option<&Path>, message: String) {
+        warn!("{}", message);
+        if let Ok(mut warnings) = self.warnings.lock() {
+            warnings.push(AnalysisWarning {
+                kind,
+                path: path.map(Path::to_path_buf),
+                message,
+            });
+        }
+    }
+
+    /// Returns the warnings recorded so far, including playbooks that failed to load.
+    fn take_warnings(&self) -> Vec<AnalysisWarning> {
+        let mut warnings: Vec<AnalysisWarning> = self
+            .misalignment_analyzer
+            .playbook_load_failures()
+            .iter()
+            .map(|(path, error)| AnalysisWarning {
+                kind: AnalysisWarningKind::InvalidPlaybook,
+                path: Some(path.clone()),
+                message: format!("Playbook {} was not loaded: {}", path.display(), error),
+            })
+            .collect();
+        if let Ok(mut recorded) = self.warnings.lock() {
+            warnings.append(&mut recorded);
+        }
+        warnings
+    }
+
+    /// Returns the file counts recorded so far and starts counting afresh.
+    fn reset_coverage(&self) -> FileCoverage {
+        self.coverage
+            .lock()
+            .map(|mut coverage| std::mem::take(&mut *coverage))
+            .unwrap_or_default()
+    }
+
+    /// Returns the underlying analyzer, to run analysis stages separately.
+    pub(crate) fn misalignment_analyzer_mut(&mut self) -> &mut MisalignmentAnalyzer {
+        &mut self.misalignment_analyzer
+    }
+
+    /// Returns the loaded playbooks.
+    #[must_use]
+    pub fn playbook_manager(&self) -> &crate::playbook::PlaybookManager {
+        self.misalignment_analyzer.playbook_manager()
+    }
+
+    /// Returns a stable hash of the active detection rules.
+    #[must_use]
+    pub fn ruleset_hash(&self) -> String {
+        self.misalignment_analyzer.ruleset_hash()
+    }
+
+    /// Discovers the files to analyze from the specified files and directories.
+    pub async fn discover_files(&self, paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
         let mut discovered_files = Vec::new();
+        // Locates project roots, to assign files to shards by project path
+        let mut roots = ConfigResolver::new();
 
-        // Discover all files to analyze
         for path in paths {
             if path.is_file() {
-                if self.should_analyze_file(path).await? {
-                    discovered_files.push(path.clone());
-                }
+                self.consider_file(path, &mut roots, &mut discovered_files)
+                    .await?;
             } else if path.is_dir() {
-                let dir_files = self.discover_files_in_directory(path).await?;
+                let dir_files = self.discover_files_in_directory(path, &mut roots).await?;
                 discovered_files.extend(dir_files);
             } else {
-                warn!(
-                    "Path does not exist or is not accessible: {}",
-                    path.display()
+                self.record_warning(
+                    AnalysisWarningKind::MissingPath,
+                    Some(path),
+                    format!(
+                        "Path does not exist or is not accessible: {}",
+                        path.display()
+                    ),
                 );
             }
         }
 
+        if let Some(shard) = self.config.shard {
+            info!("Shard {} selected {} files", shard, discovered_files.len());
+        }
+
+        Ok(discovered_files)
+    }
+
+    /// Adds a file to `files` if it belongs to this shard and passes the filter.
+    ///
+    /// Files of other shards are not counted as discovered, so coverage of
+    /// sharded runs adds up when their results are merged.
+    async fn consider_file(
+        &self,
+        path: &Path,
+        roots: &mut ConfigResolver,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        if let Some(shard) = self.config.shard {
+            let project_path = roots
+                .project_path(path)?
+                .map_or_else(|| path.to_path_buf(), PathBuf::from);
+            if !shard.contains(&project_path) {
+                return Ok(());
+            }
+        }
+        let skip_reason = self.skip_reason(path).await?;
+        self.count_discovered(skip_reason);
+        if skip_reason.is_none() {
+            files.push(path.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// Analyzes the specified files and directories.
+    pub async fn analyze_files(&mut self, paths: &[PathBuf]) -> Result<AnalysisResults> {
+        self.reset_coverage();
+
+        // Discover all files to analyze
+        let discovered_files = self.discover_files(paths).await?;
+
         if discovered_files.is_empty() {
-            return Ok(AnalysisResults::empty());
+            let mut results = self.with_run_metadata(AnalysisResults::empty());
+            results.warnings = self.take_warnings();
+            results.coverage = Some(self.reset_coverage());
+            if self.config.reproducible {
+                results.sort_for_reproducibility();
+            }
+            return Ok(results);
         }
 
         info!("Analyzing {} files", discovered_files.len());
 
         // Analyze each file
         let mut file_results = Vec::new();
-        let mut total_detections = 0;
-        let mut critical_issues = 0;
-        let mut quality_scores = Vec::new();
 
         for file_path in discovered_files {
             match self.analyze_single_file(&file_path).await {
                 Ok(result) => {
-                    total_detections += result.detections.len();
-                    critical_issues += result
-                        .detections
-                        .iter()
-                        .filter(|d| matches!(d.severity, crate::playbook::Severity::Critical))
-                        .count();
-                    quality_scores.push(result.quality_score);
+                    if let Ok(coverage) = self.coverage.get_mut() {
+                        match result.language {
+                            Some(_) => coverage.analyzed += 1,
+                            None => coverage.count_skipped(SkipReason::UnsupportedLanguage),
+                        }
+                    }
                     file_results.push(result);
                 }
                 Err(e) => {
-                    warn!("Failed to analyze {}: {}", file_path.display(), e);
+                    let (kind, reason) = if matches!(e, SniffError::FileSystem { .. }) {
+                        (AnalysisWarningKind::UnreadableFile, SkipReason::Unreadable)
+                    } else {
+                        (
+                            AnalysisWarningKind::AnalysisFailed,
+                            SkipReason::AnalysisFailed,
+                        )
+                    };
+                    if let Ok(coverage) = self.coverage.get_mut() {
+                        coverage.count_skipped(reason);
+                    }
+                    self.record_warning(
+                        kind,
+                        Some(&file_path),
+                        format!("Failed to analyze {}: {}", file_path.display(), e),
+                    );
                 }
             }
         }
 
-        let average_quality_score = if quality_scores.is_empty() {
-            100.0
-        } else {
-            quality_scores.iter().sum::<f64>() / quality_scores.len() as f64
+        let mut results = self.with_run_metadata(AnalysisResults::from_file_results(file_results));
+        results.warnings = self.take_warnings();
+        results.coverage = Some(self.reset_coverage());
+        if self.config.reproducible {
+            results.sort_for_reproducibility();
+        }
+        Ok(results)
+    }
+
+    /// Checks each analyzed file against the quality gates of its `.sniff.toml`
+    /// hierarchy, returning a message per failing file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a config file cannot be read or parsed.
+    pub fn project_gate_failures(&mut self, results: &AnalysisResults) -> Result<Vec<String>> {
+        let mut failures = Vec::new();
+        for file_result in results.file_results.iter().filter(|r| r.language.is_some()) {
+            let config = self.project_configs.resolve(&file_result.file_path)?;
+            let Some(source) = config.sources.last() else {
+                continue;
+            };
+            let path = file_result.file_path.display();
+            if let Some(min_score) = config.min_quality_score {
+                if file_result.quality_score < min_score {
+                    failures.push(format!(
+                        "{path}: quality score {:.1}% is below {min_score:.1}% ({})",
+                        file_result.quality_score,
+                        source.display()
+                    ));
+                }
+            }
+            if let Some(max_critical) = config.max_critical_issues {
+                let critical = file_result
+                    .detections
+                    .iter()
+                    .filter(|d| d.severity == crate::playbook::Severity::Critical)
+                    .count();
+                if critical > max_critical {
+                    failures.push(format!(
+                        "{path}: {critical} critical issues exceed the limit of {max_critical} ({})",
+                        source.display()
+                    ));
+                }
+            }
+        }
+        Ok(failures)
+    }
+
+    /// Returns the functions and methods of an analyzed file from its symbol
+    /// table. Files in languages without a grammar have no symbol table, and
+    /// so no functions. Files analyzed with `function_scores` are not parsed again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be parsed.
+    pub fn function_symbols(&mut self, file_result: &FileAnalysisResult) -> Result<Vec<Symbol>> {
+        if !file_result
+            .language
+            .is_some_and(|language| language.has_grammar())
+        {
+            return Ok(Vec::new());
+        }
+        if let Some(symbols) = self.function_symbols.get(&file_result.file_path) {
+            return Ok(symbols.clone());
+        }
+        let parsed = self
+            .misalignment_analyzer
+            .parse_file(&file_result.file_path)?;
+        Ok(functions(
+            parsed.files.into_iter().flat_map(|file| file.symbols),
+        ))
+    }
+
+    /// Scores one function of an analyzed file with the weights of the file's
+    /// `.sniff.toml` hierarchy. See [`function_quality::score_function`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or its `.sniff.toml`
+    /// hierarchy cannot be read.
+    pub fn score_function(
+        &mut self,
+        file_result: &FileAnalysisResult,
+        symbol: &Symbol,
+    ) -> Result<FunctionScore> {
+        let content = std::fs::read_to_string(&file_result.file_path)
+            .map_err(|e| SniffError::file_system(&file_result.file_path, e))?;
+        let config = self.project_configs.resolve(&file_result.file_path)?;
+        Ok(function_quality::score_function(
+            file_result,
+            symbol,
+            &content,
+            &config.score_weights.penalties,
+        ))
+    }
+
+    /// Scores the functions of an analyzed file, attributing each detection to
+    /// the innermost function containing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or its
+    /// `.sniff.toml` hierarchy cannot be read.
+    pub fn function_scores(
+        &mut self,
+        file_result: &FileAnalysisResult,
+    ) -> Result<Vec<FunctionScore>> {
+        let symbols = self.function_symbols(file_result)?;
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&file_result.file_path)
+            .map_err(|e| SniffError::file_system(&file_result.file_path, e))?;
+        let config = self.project_configs.resolve(&file_result.file_path)?;
+        Ok(function_quality::score_functions(
+            file_result,
+            &symbols,
+            &content,
+            &config.score_weights.penalties,
+        ))
+    }
+
+    /// Returns the `limit` worst functions of the analyzed files by detection
+    /// density and complexity. Files that cannot be scored are skipped with a
+    /// warning.
+    pub fn worst_functions(
+        &mut self,
+        results: &AnalysisResults,
+        limit: usize,
+    ) -> Vec<FileFunction> {
+        let functions = self
+            .all_function_scores(results)
+            .into_iter()
+            .flat_map(|(file_path, functions)| {
+                functions.into_iter().map(move |function| FileFunction {
+                    file_path: file_path.clone(),
+                    function,
+                })
+            })
+            .collect();
+        function_quality::worst_functions(functions, limit)
+    }
+
+    /// Scores the functions of every analyzed file, for recording in a checkpoint.
+    /// Files that cannot be scored are skipped with a warning.
+    pub fn all_function_scores(
+        &mut self,
+        results: &AnalysisResults,
+    ) -> HashMap<PathBuf, Vec<FunctionScore>> {
+        let mut scores = HashMap::new();
+        for file_result in &results.file_results {
+            if let Some(functions) = self.scored_functions(file_result) {
+                scores.insert(file_result.file_path.clone(), functions);
+            }
+        }
+        scores
+    }
+
+    /// Scores the functions of an analyzed file, or returns `None` if it has
+    /// none or cannot be scored, with a warning.
+    fn scored_functions(&mut self, file_result: &FileAnalysisResult) -> Option<Vec<FunctionScore>> {
+        match self.function_scores(file_result) {
+            Ok(functions) if !functions.is_empty() => Some(functions),
+            Ok(_) => None,
+            Err(e) => {
+                warn!(
+                    "Cannot score functions of {}: {}",
+                    file_result.file_path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Maps the lines changed since a checkpoint to the functions containing
+    /// them, with quality deltas where the checkpoint recorded function scores.
+    ///
+    /// Every line of a file the checkpoint did not hash counts as changed.
+    /// Files that cannot be read or scored are skipped with a warning.
+    pub fn changed_functions(
+        &mut self,
+        results: &AnalysisResults,
+        recorded: &HashMap<PathBuf, RecordedFile>,
+    ) -> Vec<ChangedFunction> {
+        let mut changed_functions = Vec::new();
+        for file_result in &results.file_results {
+            let Some(functions) = self.scored_functions(file_result) else {
+                continue;
+            };
+            let content = match std::fs::read_to_string(&file_result.file_path) {
+                Ok(content) => content,
+                Err(e) => {
+                    warn!("Cannot read {}: {}", file_result.file_path.display(), e);
+                    continue;
+                }
+            };
+            let recorded_file = recorded.get(&file_result.file_path);
+            let previous_lines = recorded_file
+                .and_then(|file| file.line_hashes.as_deref())
+                .unwrap_or_default();
+            let previous_functions = recorded_file.map_or(&[][..], |file| &file.functions[..]);
+            let changed = function_quality::changed_lines(previous_lines, &content);
+            changed_functions.extend(function_quality::changed_functions(
+                &file_result.file_path,
+                functions,
+                &changed,
+                previous_functions,
+            ));
+        }
+        changed_functions
+    }
+
+    /// Maps the lines changed since a git revision, from [`git_changed_lines`],
+    /// to the functions containing them. Quality deltas come from analyzing
+    /// each file as of the revision; files added since then have none.
+    ///
+    /// Files that cannot be scored are skipped with a warning.
+    pub async fn changed_functions_since(
+        &mut self,
+        results: &AnalysisResults,
+        revision: &str,
+        changed: &BTreeMap<PathBuf, BTreeSet<usize>>,
+    ) -> Vec<ChangedFunction> {
+        let mut changed_functions = Vec::new();
+        for file_result in &results.file_results {
+            let Some(changed) = changed.get(&file_result.file_path) else {
+                continue;
+            };
+            let Some(functions) = self.scored_functions(file_result) else {
+                continue;
+            };
+            let previous = self
+                .revision_function_scores(revision, &file_result.file_path)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!(
+                        "Cannot score functions of {} as of {}: {}",
+                        file_result.file_path.display(),
+                        revision,
+                        e
+                    );
+                    Vec::new()
+                });
+            changed_functions.extend(function_quality::changed_functions(
+                &file_result.file_path,
+                functions,
+                changed,
+                &previous,
+            ));
+        }
+        changed_functions
+    }
+
+    /// Scores the functions of a file as it was at a git revision, or returns
+    /// no scores if the file did not exist then.
+    async fn revision_function_scores(
+        &mut self,
+        revision: &str,
+        file_path: &Path,
+    ) -> Result<Vec<FunctionScore>> {
+        let Some(content) = git_show(revision, file_path).await? else {
+            return Ok(Vec::new());
         };
 
-        Ok(AnalysisResults {
-            total_files: file_results.len(),
-            total_detections,
-            critical_issues,
-            average_quality_score,
-            file_results,
-        })
+        self.misalignment_analyzer.set_collect_symbols(true);
+        let file_result = self.analyze_file_content(file_path, &content);
+        let symbols = functions(self.misalignment_analyzer.take_symbols());
+        self.misalignment_analyzer
+            .set_collect_symbols(self.config.function_scores);
+
+        let file_result = file_result?;
+        let config = self.project_configs.resolve(file_path)?;
+        Ok(function_quality::score_functions(
+            &file_result,
+            &symbols,
+            &content,
+            &config.score_weights.penalties,
+        ))
+    }
+
+    /// Attaches metadata about the current run to the results.
+    fn with_run_metadata(&self, mut results: AnalysisResults) -> AnalysisResults {
+        match std::env::current_dir() {
+            Ok(project_dir) => {
+                let mut run = RunMetadata::capture(&project_dir, self.ruleset_hash());
+                if self.config.reproducible {
+                    run.timestamp = reproducible_timestamp();
+                }
+                results.run = Some(run);
+            }
+            Err(e) => warn!("Cannot determine project directory for run metadata: {}", e),
+        }
+        results
+    }
+
+    /// Builds performance hotspot reports for the specified files and directories.
+    ///
+    /// Files with an unknown language are skipped; files that fail analysis are
+    /// logged and skipped.
+    pub async fn analyze_performance(
+        &mut self,
+        paths: &[PathBuf],
+    ) -> Result<Vec<PerformanceReport>> {
+        let discovered_files = self.discover_files(paths).await?;
+        info!("Analyzing performance of {} files", discovered_files.len());
+
+        let mut reports = Vec::new();
+        for file_path in discovered_files {
+            if self.config.force_language.is_none()
+                && self
+                    .language_detector
+                    .detect_from_path(&file_path)
+                    .is_none()
+            {
+                debug!(
+                    "Unknown language for file: {}, skipping",
+                    file_path.display()
+                );
+                continue;
+            }
+
+            match self.misalignment_analyzer.analyze_performance(&file_path) {
+                Ok(report) => reports.push(report),
+                Err(e) => {
+                    warn!(
+                        "Failed to analyze performance of {}: {}",
+                        file_path.display(),
+                        e
+                    );
+                }
+            }
+        }
+
+        Ok(reports)
     }
 
     /// Analyzes a single file.
@@ -153,6 +824,21 @@ impl StandaloneAnalyzer {
             .await
             .map_err(|e| SniffError::file_system(file_path, e))?;
 
+        let file_result = self.analyze_file_content(file_path, &content)?;
+        if self.config.function_scores {
+            let symbols = functions(self.misalignment_analyzer.take_symbols());
+            self.function_symbols
+                .insert(file_path.to_path_buf(), symbols);
+        }
+        Ok(file_result)
+    }
+
+    /// Analyzes `content` as the file at `file_path`.
+    fn analyze_file_content(
+        &mut self,
+        file_path: &Path,
+        content: &str,
+    ) -> Result<FileAnalysisResult> {
         // Detect or use forced language
         let language = if let Some(forced) = self.config.force_language {
             Some(forced)
@@ -161,16 +847,26 @@ impl StandaloneAnalyzer {
         };
 
         if language.is_none() {
-            debug!(
-                "Unknown language for file: {}, skipping",
-                file_path.display()
+            self.record_warning(
+                AnalysisWarningKind::UnsupportedLanguage,
+                Some(file_path),
+                format!(
+                    "Unknown language for file: {}, skipping",
+                    file_path.display()
+                ),
             );
             return Ok(FileAnalysisResult {
                 file_path: file_path.to_path_buf(),
+                project_path: None,
                 language: None,
                 detections: Vec::new(),
+                suppressed_detections: Vec::new(),
+                inline_suppressions: Vec::new(),
                 quality_score: 100.0,
                 analysis_metadata: AnalysisMetadata::default(),
+                enhanced: None,
+                heuristic_insights: None,
+                ai_likelihood: None,
             });
         }
 
@@ -187,20 +883,80 @@ impl StandaloneAnalyzer {
             .tempfile()
             .map_err(|e| SniffError::file_system(file_path, e))?;
 
-        std::fs::write(temp_file.path(), &content)
+        std::fs::write(temp_file.path(), content)
             .map_err(|e| SniffError::file_system(file_path, e))?;
 
         // Analyze content for bullshit patterns
-        let mut detections = self.misalignment_analyzer.analyze_file(temp_file.path())?;
+        let (mut detections, enhanced) = if self.config.enhanced_analysis {
+            let mut analysis = self
+                .misalignment_analyzer
+                .analyze_file_enhanced_as(temp_file.path(), file_path)?;
+            // The detections are filtered below and kept on the file result
+            (std::mem::take(&mut analysis.detections), Some(analysis))
+        } else {
+            (
+                self.misalignment_analyzer
+                    .analyze_file_as(temp_file.path(), file_path)?,
+                None,
+            )
+        };
 
-        // Fix detection file paths to use original file path instead of temp file path
+        // Collected only with `show_suppressed`
+        let mut suppressed_detections = self.misalignment_analyzer.take_suppressed();
+
+        // Drop rules disabled by the file's .sniff.toml hierarchy, and apply its
+        // severity overrides: the analyzer only has those of the working directory
+        let project_config = self.project_configs.resolve(file_path)?;
+        detections.retain(|d| project_config.is_rule_enabled(&d.rule_id));
+        suppressed_detections.retain(|s| project_config.is_rule_enabled(&s.detection.rule_id));
+        for detection in detections
+            .iter_mut()
+            .chain(suppressed_detections.iter_mut().map(|s| &mut s.detection))
+        {
+            if let Some(severity) = project_config.rule_severity(&detection.rule_id) {
+                detection.severity = severity;
+            }
+        }
+
+        // Honor inline sniff-ignore comments
+        let mut inline_suppressions = suppression::parse(content);
+        let (detections, inline_hidden) = suppression::apply(&mut inline_suppressions, detections);
+        if self.config.show_suppressed {
+            suppressed_detections.extend(inline_hidden);
+        }
+
+        // Deprecated rules still run, but stop gating once their grace period ends
+        let grace_days = project_config
+            .deprecation_grace_days
+            .unwrap_or(DEFAULT_DEPRECATION_GRACE_DAYS);
+        let (mut detections, retired) = self.withhold_retired_rules(detections, grace_days);
+        if self.config.show_suppressed {
+            suppressed_detections.extend(retired);
+        }
+
+        // Fix detection file paths to use original file path instead of temp file path,
+        // and record the project-relative path fingerprints are derived from
         let original_path_str = file_path.to_string_lossy().to_string();
-        for detection in &mut detections {
+        let project_path = self.project_configs.project_path(file_path)?;
+        for detection in detections
+            .iter_mut()
+            .chain(suppressed_detections.iter_mut().map(|s| &mut s.detection))
+        {
             detection.file_path = original_path_str.clone();
+            detection.project_path.clone_from(&project_path);
         }
 
-        // Calculate quality score
-        let quality_score = self.calculate_quality_score(&detections);
+        // Score the file with the weights of its .sniff.toml hierarchy
+        let weights = &project_config.score_weights;
+        let quality_score = weights.penalties.score(&detections);
+        let enhanced = enhanced.map(|analysis| EnhancedMisalignmentAnalysis {
+            quality_assessment: QualityAssessment::calculate(
+                &detections,
+                analysis.quality_assessment.performance_score,
+                weights,
+            ),
+            ..analysis
+        });
 
         // Gather analysis metadata
         let metadata = if self.config.detailed_analysis {
@@ -208,52 +964,165 @@ impl StandaloneAnalyzer {
                 line_count: content.lines().count(),
                 char_count: content.chars().count(),
                 file_size_bytes: content.len(),
-                complexity_metrics: self.calculate_complexity_metrics(&content, lang),
+                complexity_metrics: self.calculate_complexity_metrics(content, lang),
             }
         } else {
             AnalysisMetadata::default()
         };
 
+        // Gather heuristic insights if requested
+        let heuristic_insights = if self.config.detailed_analysis && self.config.include_insights {
+            match self.misalignment_analyzer.analyze_ai_insights(file_path) {
+                Ok(insights) => Some(HeuristicInsights::new(serde_json::to_value(insights)?)),
+                Err(e) => {
+                    warn!(
+                        "Failed to gather insights for {}: {}",
+                        file_path.display(),
+                        e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let ai_likelihood = self
+            .config
+            .ai_likelihood
+            .then(|| ai_likelihood::estimate(content, self.session_writes.for_file(file_path)));
+
         Ok(FileAnalysisResult {
             file_path: file_path.to_path_buf(),
+            project_path,
             language: Some(lang),
             detections,
+            suppressed_detections,
+            inline_suppressions,
             quality_score,
             analysis_metadata: metadata,
+            enhanced,
+            heuristic_insights,
+            ai_likelihood,
         })
     }
 
-    /// Discovers files in a directory recursively.
-    async fn discover_files_in_directory(&self, dir_path: &Path) -> Result<Vec<PathBuf>> {
-        let mut files = Vec::new();
-        let mut stack = vec![dir_path.to_path_buf()];
+    /// Announces each deprecated rule that fired once, and separates the
+    /// detections of deprecated rules whose grace period has ended.
+    fn withhold_retired_rules(
+        &mut self,
+        detections: Vec<MisalignmentDetection>,
+        grace_days: u32,
+    ) -> (Vec<MisalignmentDetection>, Vec<SuppressedDetection>) {
+        let today = Utc::now().date_naive();
+        let mut reported = Vec::new();
+        let mut retired = Vec::new();
+        for detection in detections {
+            let Some(rule) = self
+                .misalignment_analyzer
+                .get_rule(&detection.rule_id)
+                .filter(|rule| rule.deprecated)
+            else {
+                reported.push(detection);
+                continue;
+            };
 
-        while let Some(current_dir) = stack.pop() {
-            let mut entries = fs::read_dir(&current_dir)
-                .await
-                .map_err(|e| SniffError::file_system(&current_dir, e))?;
+            let replacement = rule
+                .replaced_by
+                .as_ref()
+                .map_or_else(String::new, |id| format!(", use '{id}' instead"));
+            let grace_end = rule.grace_period_end(grace_days);
+            if self.deprecation_notices.insert(rule.id.clone()) {
+                match grace_end {
+                    Some(end) if today <= end => warn!(
+                        "Rule '{}' is deprecated{}; it stops counting toward quality gates after {}",
+                        rule.id, replacement, end
+                    ),
+                    _ => warn!(
+                        "Rule '{}' is deprecated{}; it no longer counts toward quality gates",
+                        rule.id, replacement
+                    ),
+                }
+            }
 
-            while let Some(entry) = entries
-                .next_entry()
-                .await
-                .map_err(|e| SniffError::file_system(&current_dir, e))?
-            {
-                let path = entry.path();
+            if rule.gates_on(today, grace_days) {
+                reported.push(detection);
+            } else {
+                let suppression_reason = format!(
+                    "rule '{}' is deprecated{}{}",
+                    rule.id,
+                    replacement,
+                    grace_end
+                        .map_or_else(String::new, |end| format!(" (grace period ended {end})"))
+                );
+                retired.push(SuppressedDetection {
+                    detection,
+                    suppression_reason,
+                });
+            }
+        }
+        (reported, retired)
+    }
 
-                // Skip hidden files/directories unless configured to include them
-                if !self.config.filter.include_hidden {
-                    if let Some(file_name) = path.file_name() {
-                        if file_name.to_string_lossy().starts_with('.') {
-                            continue;
-                        }
-                    }
-                }
+    /// Discovers files in a directory recursively, in file name order.
+    ///
+    /// Unless configured otherwise, paths ignored by `.gitignore` files (in the
+    /// directory, its parents, `.git/info/exclude` and the global git config)
+    /// and by `.sniffignore` files are skipped. `.sniffignore` uses the
+    /// `.gitignore` syntax and holds exclusions that only apply to analysis.
+    ///
+    /// With `tracked_only`, only files listed by `git ls-files` are discovered,
+    /// and directories holding no tracked file are not walked at all.
+    async fn discover_files_in_directory(
+        &self,
+        dir_path: &Path,
+        roots: &mut ConfigResolver,
+    ) -> Result<Vec<PathBuf>> {
+        let respect_ignore_files = self.config.filter.respect_ignore_files;
+        let mut walker = ignore::WalkBuilder::new(dir_path);
+        if self.config.filter.tracked_only {
+            let tracked = tracked_paths(dir_path).await?;
+            let root = dir_path.to_path_buf();
+            walker.filter_entry(move |entry| {
+                entry
+                    .path()
+                    .strip_prefix(&root)
+                    .is_ok_and(|path| tracked.contains(path))
+            });
+        }
+        walker
+            .hidden(!self.config.filter.include_hidden)
+            .follow_links(true)
+            .sort_by_file_name(std::cmp::Ord::cmp)
+            .ignore(false)
+            .parents(respect_ignore_files)
+            .git_ignore(respect_ignore_files)
+            .git_global(respect_ignore_files)
+            .git_exclude(respect_ignore_files)
+            // Honor .gitignore files outside git checkouts too
+            .require_git(false);
+        if respect_ignore_files {
+            walker.add_custom_ignore_filename(SNIFF_IGNORE_FILE_NAME);
+        }
 
-                if path.is_dir() {
-                    stack.push(path);
-                } else if self.should_analyze_file(&path).await? {
-                    files.push(path);
+        let mut files = Vec::new();
+        for entry in walker.build() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.record_warning(
+                        AnalysisWarningKind::UnreadableFile,
+                        Some(dir_path),
+                        format!("Skipping unreadable path in {}: {e}", dir_path.display()),
+                    );
+                    continue;
                 }
+            };
+            if entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+            {
+                self.consider_file(entry.path(), roots, &mut files).await?;
             }
         }
 
@@ -261,16 +1130,26 @@ impl StandaloneAnalyzer {
     }
 
     /// Checks if a file should be analyzed based on the filter configuration.
+    #[cfg(test)]
     async fn should_analyze_file(&self, file_path: &Path) -> Result<bool> {
+        Ok(self.skip_reason(file_path).await?.is_none())
+    }
+
+    /// Returns why the filter configuration skips a file, or `None` if it is analyzed.
+    async fn skip_reason(&self, file_path: &Path) -> Result<Option<SkipReason>> {
         // Check file size
         if let Ok(metadata) = fs::metadata(file_path).await {
             if metadata.len() > self.config.filter.max_file_size_bytes {
-                debug!(
-                    "Skipping large file: {} ({} bytes)",
-                    file_path.display(),
-                    metadata.len()
+                self.record_warning(
+                    AnalysisWarningKind::FileTooLarge,
+                    Some(file_path),
+                    format!(
+                        "Skipping large file: {} ({} bytes)",
+                        file_path.display(),
+                        metadata.len()
+                    ),
                 );
-                return Ok(false);
+                return Ok(Some(SkipReason::FileTooLarge));
             }
         }
 
@@ -282,11 +1161,11 @@ impl StandaloneAnalyzer {
                     .iter()
                     .any(|allowed| allowed.to_lowercase() == ext_str)
                 {
-                    return Ok(false);
+                    return Ok(Some(SkipReason::ExtensionFilter));
                 }
             } else {
                 // No extension, skip if we have extension filters
-                return Ok(false);
+                return Ok(Some(SkipReason::ExtensionFilter));
             }
         }
 
@@ -299,7 +1178,7 @@ impl StandaloneAnalyzer {
                     exclude_pattern,
                     file_path.display()
                 );
-                return Ok(false);
+                return Ok(Some(SkipReason::Excluded));
             }
         }
 
@@ -309,7 +1188,7 @@ impl StandaloneAnalyzer {
             let content = if let Ok(content) = fs::read_to_string(file_path).await { content } else {
                 // If we can't read the file, skip test file detection
                 debug!("Unable to read file for test classification: {}", file_path.display());
-                return Ok(true);
+                return Ok(None);
             };
 
             let test_classification = self.test_classifier.classify_file(
@@ -323,31 +1202,11 @@ impl StandaloneAnalyzer {
                     file_path.display(),
                     test_classification.confidence
                 );
-                return Ok(false);
+                return Ok(Some(SkipReason::TestFile));
             }
         }
 
-        Ok(true)
-    }
-
-    /// Calculates a quality score based on detected patterns.
-    fn calculate_quality_score(&self, detections: &[MisalignmentDetection]) -> f64 {
-        if detections.is_empty() {
-            return 100.0;
-        }
-
-        let mut penalty = 0.0;
-        for detection in detections {
-            penalty += match detection.severity {
-                crate::playbook::Severity::Critical => 25.0,
-                crate::playbook::Severity::High => 15.0,
-                crate::playbook::Severity::Medium => 8.0,
-                crate::playbook::Severity::Low => 3.0,
-                crate::playbook::Severity::Info => 1.0,
-            };
-        }
-
-        (100.0_f64 - penalty).max(0.0)
+        Ok(None)
     }
 
     /// Calculates basic complexity metrics for a file.
@@ -420,7 +1279,10 @@ struct LanguageDetector {
 }
 
 impl LanguageDetector {
-    fn new() -> Self {
+    /// Maps source file extensions, plus YAML and JSON when `config_files` is set
+    /// and Markdown when `markdown` is: those are data and documentation, so they
+    /// are only analyzed on request.
+    fn new(config_files: bool, markdown: bool) -> Self {
         let mut extension_map = HashMap::new();
 
         extension_map.insert("rs".to_string(), SupportedLanguage::Rust);
@@ -437,6 +1299,23 @@ impl LanguageDetector {
         extension_map.insert("cxx".to_string(), SupportedLanguage::Cpp);
         extension_map.insert("cc".to_string(), SupportedLanguage::Cpp);
         extension_map.insert("hpp".to_string(), SupportedLanguage::Cpp);
+        extension_map.insert("swift".to_string(), SupportedLanguage::Swift);
+        for extension in ["tf", "tfvars", "hcl"] {
+            extension_map.insert(extension.to_string(), SupportedLanguage::Terraform);
+        }
+        for extension in ["scala", "sc"] {
+            extension_map.insert(extension.to_string(), SupportedLanguage::Scala);
+        }
+        if markdown {
+            for extension in ["md", "markdown"] {
+                extension_map.insert(extension.to_string(), SupportedLanguage::Markdown);
+            }
+        }
+        if config_files {
+            for extension in ["yaml", "yml", "json"] {
+                extension_map.insert(extension.to_string(), SupportedLanguage::Config);
+            }
+        }
 
         Self { extension_map }
     }
@@ -450,46 +1329,511 @@ impl LanguageDetector {
     }
 }
 
-/// Results of analyzing multiple files.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AnalysisResults {
-    /// Total number of files analyzed.
-    pub total_files: usize,
-    /// Total number of pattern detections across all files.
-    pub total_detections: usize,
-    /// Number of critical issues found.
-    pub critical_issues: usize,
-    /// Average quality score across all files.
-    pub average_quality_score: f64,
-    /// Individual file analysis results.
-    pub file_results: Vec<FileAnalysisResult>,
-}
+/// Results of analyzing multiple files.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AnalysisResults {
+    /// Total number of files analyzed.
+    pub total_files: usize,
+    /// Total number of pattern detections across all files.
+    pub total_detections: usize,
+    /// Number of critical issues found.
+    pub critical_issues: usize,
+    /// Average quality score across all files.
+    pub average_quality_score: f64,
+    /// Individual file analysis results.
+    pub file_results: Vec<FileAnalysisResult>,
+    /// Information about the run that produced these results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<RunMetadata>,
+    /// Problems that degraded the run without failing it (skipped or unreadable files, ...).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<AnalysisWarning>,
+    /// How many files were discovered, analyzed and skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<FileCoverage>,
+    /// Detections of rules with a CWE, grouped by CWE.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compliance: Vec<ComplianceGroup>,
+    /// Functions changed since the compared checkpoint, with their quality.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_functions: Vec<ChangedFunction>,
+    /// Worst functions by detection density and complexity, when requested.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub worst_functions: Vec<FileFunction>,
+}
+
+/// Kind of problem behind an [`AnalysisWarning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisWarningKind {
+    /// A path given to analyze does not exist or is not accessible.
+    MissingPath,
+    /// A file exceeded the maximum file size and was skipped.
+    FileTooLarge,
+    /// A file could not be read.
+    UnreadableFile,
+    /// A file's language is not supported, so no rules ran on it.
+    UnsupportedLanguage,
+    /// Analysis of a file failed.
+    AnalysisFailed,
+    /// A playbook failed to load (e.g. an invalid regex), so its rules did not run.
+    InvalidPlaybook,
+}
+
+impl AnalysisWarningKind {
+    /// Returns the kind as it appears in JSON output.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MissingPath => "missing_path",
+            Self::FileTooLarge => "file_too_large",
+            Self::UnreadableFile => "unreadable_file",
+            Self::UnsupportedLanguage => "unsupported_language",
+            Self::AnalysisFailed => "analysis_failed",
+            Self::InvalidPlaybook => "invalid_playbook",
+        }
+    }
+}
+
+/// Why a discovered file was not analyzed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Not one of the `--extensions` requested.
+    ExtensionFilter,
+    /// Matched the `--exclude` pattern.
+    Excluded,
+    /// Classified as a test file while test files are excluded.
+    TestFile,
+    /// Larger than the maximum file size.
+    FileTooLarge,
+    /// Language not supported, so no rules ran on it.
+    UnsupportedLanguage,
+    /// Could not be read.
+    Unreadable,
+    /// Analysis failed.
+    AnalysisFailed,
+}
+
+impl SkipReason {
+    /// Returns the reason as it appears in JSON output.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ExtensionFilter => "extension_filter",
+            Self::Excluded => "excluded",
+            Self::TestFile => "test_file",
+            Self::FileTooLarge => "file_too_large",
+            Self::UnsupportedLanguage => "unsupported_language",
+            Self::Unreadable => "unreadable",
+            Self::AnalysisFailed => "analysis_failed",
+        }
+    }
+
+    /// Returns true if the file was skipped because the user's filters asked for it.
+    #[must_use]
+    pub fn is_deliberate(&self) -> bool {
+        matches!(
+            self,
+            Self::ExtensionFilter | Self::Excluded | Self::TestFile
+        )
+    }
+}
+
+/// How many files a run discovered, analyzed and skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileCoverage {
+    /// Files found under the given paths (within this shard, if sharded).
+    pub discovered: usize,
+    /// Files the rules ran on.
+    pub analyzed: usize,
+    /// Files not analyzed, by reason.
+    pub skipped: BTreeMap<SkipReason, usize>,
+}
+
+impl FileCoverage {
+    /// Counts one skipped file.
+    pub fn count_skipped(&mut self, reason: SkipReason) {
+        *self.skipped.entry(reason).or_default() += 1;
+    }
+
+    /// Total number of skipped files.
+    #[must_use]
+    pub fn total_skipped(&self) -> usize {
+        self.skipped.values().sum()
+    }
+
+    /// Percentage of the files selected for analysis that could not be analyzed.
+    ///
+    /// Files skipped deliberately (extension filter, exclude pattern, test files)
+    /// are not selected, so they count neither as skipped nor toward the total.
+    #[must_use]
+    pub fn skipped_percentage(&self) -> f64 {
+        let (deliberate, unintended) =
+            self.skipped
+                .iter()
+                .fold((0, 0), |(deliberate, unintended), (reason, count)| {
+                    if reason.is_deliberate() {
+                        (deliberate + count, unintended)
+                    } else {
+                        (deliberate, unintended + count)
+                    }
+                });
+        let selected = self.discovered.saturating_sub(deliberate);
+        if selected == 0 {
+            0.0
+        } else {
+            unintended as f64 * 100.0 / selected as f64
+        }
+    }
+
+    /// Adds the counts of another run, e.g. another shard.
+    pub fn add(&mut self, other: &FileCoverage) {
+        self.discovered += other.discovered;
+        self.analyzed += other.analyzed;
+        for (reason, count) in &other.skipped {
+            *self.skipped.entry(*reason).or_default() += count;
+        }
+    }
+}
+
+/// Detections of one CWE, for the compliance summary of a result set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComplianceGroup {
+    /// CWE identifier (e.g. `CWE-798`).
+    pub cwe: String,
+    /// OWASP Top 10 categories of the rules that reported the CWE.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owasp: Vec<String>,
+    /// Rules that reported the CWE.
+    pub rules: Vec<String>,
+    /// Number of detections.
+    pub detections: usize,
+    /// Number of files with detections.
+    pub files: usize,
+    /// Highest severity among the detections.
+    pub highest_severity: Severity,
+}
+
+impl ComplianceGroup {
+    /// Groups the detections of rules with a CWE by CWE. A detection whose
+    /// rule names several CWEs counts toward each. Groups are ordered by
+    /// highest severity, then number of detections.
+    #[must_use]
+    pub fn summarize(file_results: &[FileAnalysisResult]) -> Vec<Self> {
+        let mut groups: BTreeMap<&str, (Self, BTreeSet<&Path>)> = BTreeMap::new();
+        for file_result in file_results {
+            for detection in &file_result.detections {
+                let Some(taxonomy) = &detection.taxonomy else {
+                    continue;
+                };
+                for cwe in &taxonomy.cwe {
+                    let (group, files) = groups.entry(cwe).or_insert_with(|| {
+                        let group = Self {
+                            cwe: cwe.clone(),
+                            owasp: Vec::new(),
+                            rules: Vec::new(),
+                            detections: 0,
+                            files: 0,
+                            highest_severity: detection.severity,
+                        };
+                        (group, BTreeSet::new())
+                    });
+                    for category in &taxonomy.owasp {
+                        if !group.owasp.contains(category) {
+                            group.owasp.push(category.clone());
+                        }
+                    }
+                    if !group.rules.contains(&detection.rule_id) {
+                        group.rules.push(detection.rule_id.clone());
+                    }
+                    group.detections += 1;
+                    group.highest_severity = group.highest_severity.max(detection.severity);
+                    files.insert(&file_result.file_path);
+                }
+            }
+        }
+
+        let mut summary: Vec<Self> = groups
+            .into_values()
+            .map(|(mut group, files)| {
+                group.files = files.len();
+                group.owasp.sort();
+                group.rules.sort();
+                group
+            })
+            .collect();
+        summary.sort_by(|a, b| {
+            b.highest_severity
+                .cmp(&a.highest_severity)
+                .then(b.detections.cmp(&a.detections))
+        });
+        summary
+    }
+}
+
+/// A problem that degraded an analysis run without failing it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisWarning {
+    /// Kind of problem.
+    pub kind: AnalysisWarningKind,
+    /// File or path the warning concerns, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// What went wrong.
+    pub message: String,
+}
+
+/// Returns the timestamp recorded by reproducible runs: `SOURCE_DATE_EPOCH`
+/// (seconds since the Unix epoch) if set, as in reproducible builds, and the
+/// Unix epoch otherwise.
+#[must_use]
+pub fn reproducible_timestamp() -> DateTime<Utc> {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|seconds| seconds.trim().parse::<i64>().ok())
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .unwrap_or(DateTime::UNIX_EPOCH)
+}
+
+/// Information about the analysis run that produced a result set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunMetadata {
+    /// Repository (project directory) name the analysis ran in.
+    pub repository: String,
+    /// When the analysis ran.
+    pub timestamp: DateTime<Utc>,
+    /// Version of sniff that produced the results.
+    pub sniff_version: String,
+    /// Hash of the active ruleset.
+    pub ruleset_hash: String,
+    /// Whether network features were available (`disabled` with `--offline`).
+    #[serde(default)]
+    pub network: NetworkStatus,
+}
+
+impl RunMetadata {
+    /// Captures metadata for a run in the given project directory.
+    #[must_use]
+    pub fn capture(project_dir: &Path, ruleset_hash: String) -> Self {
+        Self {
+            repository: project_dir.file_name().map_or_else(
+                || "unknown".to_string(),
+                |name| name.to_string_lossy().to_string(),
+            ),
+            timestamp: Utc::now(),
+            sniff_version: env!("CARGO_PKG_VERSION").to_string(),
+            ruleset_hash,
+            network: network::status(),
+        }
+    }
+}
+
+impl AnalysisResults {
+    fn empty() -> Self {
+        Self {
+            total_files: 0,
+            total_detections: 0,
+            critical_issues: 0,
+            average_quality_score: 100.0,
+            file_results: Vec::new(),
+            run: None,
+            warnings: Vec::new(),
+            coverage: None,
+            compliance: Vec::new(),
+            changed_functions: Vec::new(),
+            worst_functions: Vec::new(),
+        }
+    }
+
+    /// Merges results produced by separate runs, e.g. shards of one analysis.
+    ///
+    /// If a file appears in more than one part, the result from the later part
+    /// wins, along with its changed and worst functions. The worst functions
+    /// are ranked again across parts, keeping as many as the longest part.
+    #[must_use]
+    pub fn merge(parts: Vec<AnalysisResults>) -> Self {
+        let mut file_results: Vec<FileAnalysisResult> = Vec::new();
+        let mut seen: HashMap<PathBuf, usize> = HashMap::new();
+        let run = parts.iter().find_map(|part| part.run.clone());
+        let warnings = parts
+            .iter()
+            .flat_map(|part| part.warnings.iter().cloned())
+            .collect();
+        let coverage = parts.iter().filter_map(|part| part.coverage.as_ref()).fold(
+            None,
+            |total: Option<FileCoverage>, coverage| {
+                let mut total = total.unwrap_or_default();
+                total.add(coverage);
+                Some(total)
+            },
+        );
+
+        let worst_limit = parts
+            .iter()
+            .map(|part| part.worst_functions.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut changed_functions: Vec<ChangedFunction> = Vec::new();
+        let mut worst_functions: Vec<FileFunction> = Vec::new();
+        for part in parts {
+            let covered: HashSet<&PathBuf> =
+                part.file_results.iter().map(|r| &r.file_path).collect();
+            changed_functions.retain(|changed| !covered.contains(&changed.file_path));
+            worst_functions.retain(|worst| !covered.contains(&worst.file_path));
+            changed_functions.extend(part.changed_functions);
+            worst_functions.extend(part.worst_functions);
+
+            for file_result in part.file_results {
+                if let Some(&index) = seen.get(&file_result.file_path) {
+                    warn!(
+                        "{} appears in more than one result set, keeping the last",
+                        file_result.file_path.display()
+                    );
+                    file_results[index] = file_result;
+                } else {
+                    seen.insert(file_result.file_path.clone(), file_results.len());
+                    file_results.push(file_result);
+                }
+            }
+        }
+
+        file_results.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        changed_functions.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.function.start_line.cmp(&b.function.start_line))
+        });
+        let mut merged = Self::from_file_results(file_results);
+        merged.run = run;
+        merged.warnings = warnings;
+        merged.coverage = coverage;
+        merged.changed_functions = changed_functions;
+        merged.worst_functions = function_quality::worst_functions(worst_functions, worst_limit);
+        merged
+    }
+
+    /// Puts every list in the results in a stable order, so the same inputs
+    /// serialize identically whatever order files were analyzed and rules ran in.
+    pub fn sort_for_reproducibility(&mut self) {
+        self.file_results
+            .sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        for file_result in &mut self.file_results {
+            file_result.detections.sort_by(detection_order);
+            file_result
+                .suppressed_detections
+                .sort_by(|a, b| detection_order(&a.detection, &b.detection));
+            file_result
+                .inline_suppressions
+                .sort_by_key(|suppression| suppression.line_number);
+        }
+        self.warnings.sort_by(|a, b| {
+            (a.kind.name(), &a.path, &a.message).cmp(&(b.kind.name(), &b.path, &b.message))
+        });
+    }
+
+    /// Builds results from per-file results, computing the summary totals.
+    #[must_use]
+    pub fn from_file_results(file_results: Vec<FileAnalysisResult>) -> Self {
+        if file_results.is_empty() {
+            return Self::empty();
+        }
+
+        let total_detections = file_results.iter().map(|r| r.detections.len()).sum();
+        let critical_issues = file_results
+            .iter()
+            .flat_map(|r| &r.detections)
+            .filter(|d| matches!(d.severity, crate::playbook::Severity::Critical))
+            .count();
+        let average_quality_score =
+            file_results.iter().map(|r| r.quality_score).sum::<f64>() / file_results.len() as f64;
 
-impl AnalysisResults {
-    fn empty() -> Self {
         Self {
-            total_files: 0,
-            total_detections: 0,
-            critical_issues: 0,
-            average_quality_score: 100.0,
-            file_results: Vec::new(),
+            total_files: file_results.len(),
+            total_detections,
+            critical_issues,
+            average_quality_score,
+            compliance: ComplianceGroup::summarize(&file_results),
+            changed_functions: Vec::new(),
+            worst_functions: Vec::new(),
+            file_results,
+            run: None,
+            warnings: Vec::new(),
+            coverage: None,
         }
     }
 }
 
+/// Orders detections by position, then rule.
+fn detection_order(a: &MisalignmentDetection, b: &MisalignmentDetection) -> std::cmp::Ordering {
+    (a.line_number, a.column_number, &a.rule_id, &a.code_snippet).cmp(&(
+        b.line_number,
+        b.column_number,
+        &b.rule_id,
+        &b.code_snippet,
+    ))
+}
+
 /// Results of analyzing a single file.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileAnalysisResult {
     /// Path to the analyzed file.
     pub file_path: PathBuf,
+    /// Path of the file relative to its project root, with `/` separators,
+    /// if it is in a project.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
     /// Detected language (if any).
     pub language: Option<SupportedLanguage>,
     /// Bullshit patterns detected in the file.
     pub detections: Vec<MisalignmentDetection>,
+    /// Detections withheld because of test context or inline suppression comments,
+    /// present only with `show_suppressed`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressed_detections: Vec<SuppressedDetection>,
+    /// Inline `sniff-ignore` comments in the file and how many detections each hid.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inline_suppressions: Vec<InlineSuppression>,
     /// Overall quality score for the file (0-100).
     pub quality_score: f64,
     /// Additional analysis metadata.
     pub analysis_metadata: AnalysisMetadata,
+    /// Performance and quality assessment, present only in enhanced mode.
+    /// Its detections are empty; they are the file's `detections`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enhanced: Option<EnhancedMisalignmentAnalysis>,
+    /// Heuristic AI insights, present only when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heuristic_insights: Option<HeuristicInsights>,
+    /// Estimated likelihood that the file is AI-generated, present only when
+    /// requested. Informational: it does not affect the quality score.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_likelihood: Option<AiLikelihood>,
+}
+
+/// Heuristic insights (architectural observations and pattern recognitions).
+///
+/// These come from heuristic analysis rather than playbook rules and should not
+/// be treated as detections.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeuristicInsights {
+    /// Attribution note shown alongside the insights.
+    pub attribution: String,
+    /// Raw insights as produced by the AI analyzer.
+    pub insights: serde_json::Value,
+}
+
+impl HeuristicInsights {
+    /// Wraps raw insights with a heuristic attribution note.
+    #[must_use]
+    pub fn new(insights: serde_json::Value) -> Self {
+        Self {
+            attribution: "Heuristic insights; not playbook detections and may be inaccurate"
+                .to_string(),
+            insights,
+        }
+    }
 }
 
 /// Additional metadata about the analysis.
@@ -518,9 +1862,19 @@ pub struct ComplexityMetrics {
     pub comment_ratio: f64,
 }
 
+/// Lock file that serializes checkpoint writes across sniff processes.
+const CHECKPOINT_LOCK_FILE_NAME: &str = ".lock";
+
+/// How long checkpoint writes wait for another sniff process to release the lock.
+const CHECKPOINT_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Exclusive lock on the checkpoint directory, released when dropped.
+struct CheckpointLock {
+    _file: std::fs::File,
+}
+
 /// Checkpoint management for tracking file changes over time.
 pub struct CheckpointManager {
-    #[allow(dead_code)]
     project_dir: PathBuf,
     checkpoint_dir: PathBuf,
 }
@@ -536,13 +1890,27 @@ impl CheckpointManager {
         })
     }
 
-    /// Creates a new checkpoint with the current state of specified files.
+    /// Creates a checkpoint manager that keeps checkpoints in the given directory.
+    #[must_use]
+    pub fn with_checkpoint_dir(project_dir: &Path, checkpoint_dir: &Path) -> Self {
+        Self {
+            project_dir: project_dir.to_path_buf(),
+            checkpoint_dir: checkpoint_dir.to_path_buf(),
+        }
+    }
+
+    /// Creates a new checkpoint with the current state of the files in scope.
+    ///
+    /// The scope is stored in the checkpoint, so later comparisons capture the
+    /// same paths with the same filters.
     pub async fn create_checkpoint(
         &self,
         name: &str,
-        paths: &[PathBuf],
+        scope: &CheckpointScope,
         description: Option<String>,
     ) -> Result<()> {
+        let filter = scope.filter(name)?;
+
         // Ensure checkpoint directory exists
         fs::create_dir_all(&self.checkpoint_dir)
             .await
@@ -554,15 +1922,15 @@ impl CheckpointManager {
             timestamp: Utc::now(),
             file_count: 0, // Will be updated below
             files: HashMap::new(),
+            scope: Some(scope.clone()),
         };
 
-        let checkpoint_file = self.checkpoint_dir.join(format!("{name}.json"));
         let mut file_snapshots = HashMap::new();
         let mut total_files = 0;
 
         // Capture file states
-        for path in paths {
-            let snapshots = self.capture_file_states(path).await?;
+        for path in &scope.paths {
+            let snapshots = self.capture_file_states(path, filter.as_ref()).await?;
             total_files += snapshots.len();
             file_snapshots.extend(snapshots);
         }
@@ -573,18 +1941,87 @@ impl CheckpointManager {
             ..checkpoint
         };
 
-        // Save checkpoint to file
-        let checkpoint_json = serde_json::to_string_pretty(&final_checkpoint)
-            .map_err(|e| SniffError::invalid_format("checkpoint".to_string(), e.to_string()))?;
+        self.save_checkpoint(&final_checkpoint).await?;
+
+        info!("Created checkpoint '{}' with {} files", name, total_files);
+        Ok(())
+    }
 
-        fs::write(&checkpoint_file, checkpoint_json)
+    /// Records the function scores of analyzed files in a checkpoint, so later
+    /// comparisons report quality deltas of the changed functions.
+    pub async fn record_function_scores(
+        &self,
+        name: &str,
+        scores: HashMap<PathBuf, Vec<FunctionScore>>,
+    ) -> Result<()> {
+        // Hold the lock from load to save, so concurrent writes are not lost
+        let _lock = self.lock(CHECKPOINT_LOCK_TIMEOUT).await?;
+        let mut checkpoint = self.load_checkpoint(name).await?;
+        for (path, functions) in scores {
+            if let Some(snapshot) = checkpoint.files.get_mut(path.to_string_lossy().as_ref()) {
+                snapshot.functions = functions;
+            }
+        }
+        self.replace_checkpoint(name, checkpoint_json(&checkpoint)?.as_bytes())
             .await
-            .map_err(|e| SniffError::file_system(&checkpoint_file, e))?;
+    }
 
-        info!("Created checkpoint '{}' with {} files", name, total_files);
+    /// Returns the line hashes and function scores a checkpoint recorded for
+    /// its files.
+    pub async fn recorded_files(&self, name: &str) -> Result<HashMap<PathBuf, RecordedFile>> {
+        let checkpoint = self.load_checkpoint(name).await?;
+        Ok(checkpoint
+            .files
+            .into_iter()
+            .map(|(path, snapshot)| {
+                let recorded = RecordedFile {
+                    line_hashes: snapshot.line_hashes,
+                    functions: snapshot.functions,
+                };
+                (PathBuf::from(path), recorded)
+            })
+            .collect())
+    }
+
+    /// Returns the directory holding the checkpoints.
+    #[must_use]
+    pub fn checkpoint_dir(&self) -> &Path {
+        &self.checkpoint_dir
+    }
+
+    /// Replaces the checkpoint `name` with a serialized checkpoint, such as one
+    /// downloaded from a remote store.
+    ///
+    /// Like local checkpoint writes, this takes the checkpoint lock and renames
+    /// a temp file over the checkpoint, so readers never see a partial file.
+    pub async fn write_checkpoint(&self, name: &str, body: &[u8]) -> Result<()> {
+        let _lock = self.lock(CHECKPOINT_LOCK_TIMEOUT).await?;
+        self.replace_checkpoint(name, body).await
+    }
+
+    /// Renames a temp file holding `body` over the checkpoint `name`. The
+    /// caller holds the checkpoint lock.
+    async fn replace_checkpoint(&self, name: &str, body: &[u8]) -> Result<()> {
+        let checkpoint_file = self.checkpoint_dir.join(format!("{name}.json"));
+        let temp_file = self
+            .checkpoint_dir
+            .join(format!(".{name}.json.{}.tmp", std::process::id()));
+        fs::write(&temp_file, body)
+            .await
+            .map_err(|e| SniffError::file_system(&temp_file, e))?;
+        if let Err(e) = fs::rename(&temp_file, &checkpoint_file).await {
+            let _ = fs::remove_file(&temp_file).await;
+            return Err(SniffError::file_system(&checkpoint_file, e));
+        }
         Ok(())
     }
 
+    /// Serializes a checkpoint and writes it with [`Self::write_checkpoint`].
+    async fn save_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
+        self.write_checkpoint(&checkpoint.name, checkpoint_json(checkpoint)?.as_bytes())
+            .await
+    }
+
     /// Lists all available checkpoints.
     pub async fn list_checkpoints(&self) -> Result<Vec<CheckpointInfo>> {
         if !self.checkpoint_dir.exists() {
@@ -610,6 +2047,7 @@ impl CheckpointManager {
                             description: checkpoint.description,
                             timestamp: checkpoint.timestamp,
                             file_count: checkpoint.file_count,
+                            scope: checkpoint.scope,
                         });
                     }
                 }
@@ -629,6 +2067,7 @@ impl CheckpointManager {
                 description: checkpoint.description,
                 timestamp: checkpoint.timestamp,
                 file_count: checkpoint.file_count,
+                scope: checkpoint.scope,
             })),
             Err(_) => Ok(None),
         }
@@ -653,13 +2092,32 @@ impl CheckpointManager {
     }
 
     /// Compares current file state against a checkpoint.
+    ///
+    /// Files are captured with the filters stored in the checkpoint, below
+    /// `paths` or, by default, below the paths the checkpoint was created with.
+    /// Checkpoints that predate stored scopes default to the project directory.
+    ///
+    /// With a mode other than [`ComparisonMode::Exact`], files whose content
+    /// differs only in formatting are listed in
+    /// [`FileComparison::formatting_only_files`] instead of as changed.
+    /// Checkpoints created before normalized hashes were recorded are compared
+    /// exactly.
     pub async fn compare_files(
         &self,
         checkpoint_name: &str,
-        paths: &[PathBuf],
+        paths: Option<&[PathBuf]>,
+        mode: ComparisonMode,
     ) -> Result<FileComparison> {
         let checkpoint = self.load_checkpoint(checkpoint_name).await?;
-        let current_files = self.capture_file_states_flat(paths).await?;
+        let scope = checkpoint
+            .scope
+            .clone()
+            .unwrap_or_else(|| CheckpointScope::new(vec![self.project_dir.clone()]));
+        let paths = paths.unwrap_or(&scope.paths);
+        let filter = scope.filter(checkpoint_name)?;
+        let current_files = self
+            .capture_file_states_flat(paths, filter.as_ref())
+            .await?;
 
         let checkpoint_paths: HashSet<_> = checkpoint.files.keys().cloned().collect();
         let current_paths: HashSet<_> = current_files.keys().cloned().collect();
@@ -675,27 +2133,41 @@ impl CheckpointManager {
             .collect();
 
         let mut changed_files = Vec::new();
+        let mut formatting_only_files = Vec::new();
         for path_str in checkpoint_paths.intersection(&current_paths) {
             if let (Some(checkpoint_snapshot), Some(current_snapshot)) =
                 (checkpoint.files.get(path_str), current_files.get(path_str))
             {
-                if checkpoint_snapshot.content_hash != current_snapshot.content_hash {
+                if checkpoint_snapshot.content_hash == current_snapshot.content_hash {
+                    continue;
+                }
+                let normalized = checkpoint_snapshot.normalized_hash(mode);
+                if normalized.is_some() && normalized == current_snapshot.normalized_hash(mode) {
+                    formatting_only_files.push(PathBuf::from(path_str));
+                } else {
                     changed_files.push(PathBuf::from(path_str));
                 }
             }
         }
 
-        Ok(FileComparison {
+        let mut comparison = FileComparison {
             new_files,
             changed_files,
             deleted_files,
-        })
+            formatting_only_files,
+        };
+        comparison.new_files.sort();
+        comparison.changed_files.sort();
+        comparison.deleted_files.sort();
+        comparison.formatting_only_files.sort();
+        Ok(comparison)
     }
 
     /// Deletes a checkpoint.
     pub async fn delete_checkpoint(&self, name: &str) -> Result<()> {
         let checkpoint_file = self.checkpoint_dir.join(format!("{name}.json"));
         if checkpoint_file.exists() {
+            let _lock = self.lock(CHECKPOINT_LOCK_TIMEOUT).await?;
             fs::remove_file(&checkpoint_file)
                 .await
                 .map_err(|e| SniffError::file_system(&checkpoint_file, e))?;
@@ -704,17 +2176,60 @@ impl CheckpointManager {
         Ok(())
     }
 
-    /// Captures the state of all files in the given paths.
-    async fn capture_file_states(&self, path: &Path) -> Result<HashMap<String, FileSnapshot>> {
+    /// Takes the exclusive lock on the checkpoint directory, waiting up to
+    /// `timeout` for another sniff process to release it.
+    ///
+    /// The lock is an advisory OS file lock, so it is released when the guard
+    /// is dropped or the holding process exits.
+    async fn lock(&self, timeout: Duration) -> Result<CheckpointLock> {
+        fs::create_dir_all(&self.checkpoint_dir)
+            .await
+            .map_err(|e| SniffError::file_system(&self.checkpoint_dir, e))?;
+        let lock_path = self.checkpoint_dir.join(CHECKPOINT_LOCK_FILE_NAME);
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&lock_path)
+            .map_err(|e| SniffError::file_system(&lock_path, e))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(CheckpointLock { _file: file }),
+                Err(std::fs::TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                Err(std::fs::TryLockError::WouldBlock) => {
+                    return Err(SniffError::locked("the checkpoints", lock_path));
+                }
+                Err(std::fs::TryLockError::Error(e)) => {
+                    return Err(SniffError::file_system(&lock_path, e));
+                }
+            }
+        }
+    }
+
+    /// Captures the state of all files in the given paths that pass the filter.
+    async fn capture_file_states(
+        &self,
+        path: &Path,
+        filter: Option<&PathFilter>,
+    ) -> Result<HashMap<String, FileSnapshot>> {
         let mut snapshots = HashMap::new();
+        let in_scope =
+            |file: &Path| filter.is_none_or(|filter| filter.matches(&glob_match_path(file)));
 
         if path.is_file() {
+            if !in_scope(path) {
+                return Ok(snapshots);
+            }
             if let Some(snapshot) = self.capture_single_file_state(path).await? {
                 snapshots.insert(path.to_string_lossy().to_string(), snapshot);
             }
         } else if path.is_dir() {
             let files = self.discover_all_files(path).await?;
-            for file_path in files {
+            for file_path in files.into_iter().filter(|file| in_scope(file)) {
                 if let Some(snapshot) = self.capture_single_file_state(&file_path).await? {
                     snapshots.insert(file_path.to_string_lossy().to_string(), snapshot);
                 }
@@ -728,11 +2243,12 @@ impl CheckpointManager {
     async fn capture_file_states_flat(
         &self,
         paths: &[PathBuf],
+        filter: Option<&PathFilter>,
     ) -> Result<HashMap<String, FileSnapshot>> {
         let mut all_snapshots = HashMap::new();
 
         for path in paths {
-            let snapshots = self.capture_file_states(path).await?;
+            let snapshots = self.capture_file_states(path, filter).await?;
             all_snapshots.extend(snapshots);
         }
 
@@ -756,11 +2272,14 @@ impl CheckpointManager {
         // Use a simple checksum for file content comparison (simplified from blake3)
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
-        
+
         let mut hasher = DefaultHasher::new();
         content.hash(&mut hasher);
         let content_hash = hasher.finish();
 
+        // Normalized hashes let diffs ignore formatting-only changes of text files
+        let text = std::str::from_utf8(&content).ok();
+
         Ok(Some(FileSnapshot {
             size: metadata.len(),
             modified_time: metadata
@@ -768,6 +2287,12 @@ impl CheckpointManager {
                 .map_err(|e| SniffError::file_system(file_path, e))?
                 .into(),
             content_hash: format!("{content_hash:x}"),
+            whitespace_hash: text
+                .map(|text| crate::hash::stable_hash([normalize_whitespace(text).as_str()])),
+            format_hash: text
+                .map(|text| crate::hash::stable_hash([normalize_format(text).as_str()])),
+            line_hashes: text.map(function_quality::line_hashes),
+            functions: Vec::new(),
         }))
     }
 
@@ -825,6 +2350,21 @@ impl CheckpointManager {
     }
 }
 
+/// Serializes a checkpoint as stored on disk.
+fn checkpoint_json(checkpoint: &Checkpoint) -> Result<String> {
+    serde_json::to_string_pretty(checkpoint)
+        .map_err(|e| SniffError::invalid_format("checkpoint".to_string(), e.to_string()))
+}
+
+/// What a checkpoint recorded about the content of a file.
+#[derive(Debug, Clone, Default)]
+pub struct RecordedFile {
+    /// Hash of every line, absent for binary files and older checkpoints.
+    pub line_hashes: Option<Vec<u64>>,
+    /// Function scores, empty unless the checkpoint was created by an analysis.
+    pub functions: Vec<FunctionScore>,
+}
+
 /// Information about a checkpoint.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CheckpointInfo {
@@ -836,6 +2376,42 @@ pub struct CheckpointInfo {
     pub timestamp: DateTime<Utc>,
     /// Number of files in the checkpoint.
     pub file_count: usize,
+    /// Paths and filters the checkpoint was created with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<CheckpointScope>,
+}
+
+/// The paths and filters a checkpoint captures.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointScope {
+    /// Files and directories captured.
+    pub paths: Vec<PathBuf>,
+    /// Globs of the files captured; empty captures every file below the paths.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Globs of files left out.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+impl CheckpointScope {
+    /// Creates a scope that captures every file below the paths.
+    #[must_use]
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        Self {
+            paths,
+            ..Self::default()
+        }
+    }
+
+    /// Compiles the include and exclude globs of the named checkpoint.
+    fn filter(&self, checkpoint_name: &str) -> Result<Option<PathFilter>> {
+        PathFilter::compile(
+            &self.include,
+            &self.exclude,
+            &format!("checkpoint '{checkpoint_name}'"),
+        )
+    }
 }
 
 /// Complete checkpoint data.
@@ -851,6 +2427,9 @@ struct Checkpoint {
     file_count: usize,
     /// File snapshots keyed by file path.
     files: HashMap<String, FileSnapshot>,
+    /// Paths and filters the checkpoint was created with; absent in older checkpoints.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<CheckpointScope>,
 }
 
 /// Snapshot of a file's state at a point in time.
@@ -862,6 +2441,99 @@ struct FileSnapshot {
     modified_time: DateTime<Utc>,
     /// Hash of file content.
     content_hash: String,
+    /// Hash of the content with whitespace normalized, for text files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    whitespace_hash: Option<String>,
+    /// Hash of the content with formatting normalized, for text files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    format_hash: Option<String>,
+    /// Hash of every line, for text files, to find the changed lines.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    line_hashes: Option<Vec<u64>>,
+    /// Function scores, recorded when the checkpoint was created by an analysis.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    functions: Vec<FunctionScore>,
+}
+
+impl FileSnapshot {
+    /// Returns the hash compared under a mode, if this snapshot recorded it.
+    fn normalized_hash(&self, mode: ComparisonMode) -> Option<&str> {
+        match mode {
+            ComparisonMode::Exact => None,
+            ComparisonMode::IgnoreWhitespace => self.whitespace_hash.as_deref(),
+            ComparisonMode::IgnoreFormat => self.format_hash.as_deref(),
+        }
+    }
+}
+
+/// How file contents are compared against a checkpoint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ComparisonMode {
+    /// Any change to the content counts.
+    #[default]
+    Exact,
+    /// Changes to indentation, trailing whitespace, blank lines and runs of
+    /// spaces between tokens are ignored.
+    IgnoreWhitespace,
+    /// Also ignores line breaks between tokens and trailing commas before
+    /// closing brackets, as rewritten by formatters such as `cargo fmt`.
+    ///
+    /// Indentation is ignored too, so re-indented Python blocks count as
+    /// formatting-only, as do whitespace changes inside single-quoted strings.
+    IgnoreFormat,
+}
+
+/// Trims each line, collapses runs of whitespace and drops blank lines.
+fn normalize_whitespace(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Reduces content to its tokens: whitespace outside double-quoted and
+/// backtick string literals is kept only where it separates two words, and
+/// commas before closing brackets are dropped.
+fn normalize_format(content: &str) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut normalized = String::with_capacity(content.len());
+    let mut separated = false;
+    let mut chars = content.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            separated = true;
+            continue;
+        }
+        if separated && normalized.ends_with(is_word) && is_word(c) {
+            normalized.push(' ');
+        }
+        separated = false;
+        if matches!(c, ')' | ']' | '}') && normalized.ends_with(',') {
+            normalized.pop();
+        }
+        normalized.push(c);
+
+        // Copy string literals verbatim. Single quotes are left out since they
+        // also start Rust lifetimes and labels.
+        if matches!(c, '"' | '`') {
+            let mut escaped = false;
+            for literal in chars.by_ref() {
+                normalized.push(literal);
+                if escaped {
+                    escaped = false;
+                } else if literal == '\\' {
+                    escaped = true;
+                } else if literal == c {
+                    break;
+                }
+            }
+        }
+    }
+
+    normalized
 }
 
 /// Information about a file.
@@ -886,11 +2558,16 @@ pub struct FileComparison {
     pub changed_files: Vec<PathBuf>,
     /// Files that existed in the checkpoint but not now.
     pub deleted_files: Vec<PathBuf>,
+    /// Files whose content differs only in formatting, when comparing with a
+    /// [`ComparisonMode`] that ignores it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub formatting_only_files: Vec<PathBuf>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::test_support;
     use tempfile::TempDir;
     use tokio::fs;
 
@@ -903,8 +2580,7 @@ mod tests {
     async fn create_analyzer_with_filter(filter: FileFilter) -> StandaloneAnalyzer {
         let config = AnalysisConfig {
             filter,
-            force_language: None,
-            detailed_analysis: false,
+            ..AnalysisConfig::default()
         };
         let analyzer = crate::analysis::MisalignmentAnalyzer::new().unwrap();
         StandaloneAnalyzer::new(analyzer, config)
@@ -1037,4 +2713,529 @@ fn test_helper() {
         // Should be excluded due to size, not test filtering
         assert!(!analyzer.should_analyze_file(&large_test_file).await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_discovery_respects_ignore_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in ["src", "target", "generated"] {
+            fs::create_dir_all(root.join(dir)).await.unwrap();
+            create_test_file(&root.join(dir), "lib.rs", "fn main() {}").await;
+        }
+        create_test_file(root, ".gitignore", "target/\n").await;
+        create_test_file(root, SNIFF_IGNORE_FILE_NAME, "generated/\n").await;
+
+        let discovered = |analyzer: StandaloneAnalyzer| async move {
+            analyzer
+                .discover_files(&[root.to_path_buf()])
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|path| path.strip_prefix(root).unwrap().to_path_buf())
+                .collect::<Vec<_>>()
+        };
+
+        let analyzer = create_analyzer_with_filter(FileFilter::default()).await;
+        assert_eq!(
+            discovered(analyzer).await,
+            vec![PathBuf::from("src/lib.rs")]
+        );
+
+        let analyzer = create_analyzer_with_filter(FileFilter {
+            respect_ignore_files: false,
+            ..FileFilter::default()
+        })
+        .await;
+        assert_eq!(
+            discovered(analyzer).await,
+            vec![
+                PathBuf::from("generated/lib.rs"),
+                PathBuf::from("src/lib.rs"),
+                PathBuf::from("target/lib.rs"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discovery_tracked_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in ["src", "node_modules/pkg"] {
+            fs::create_dir_all(root.join(dir)).await.unwrap();
+            create_test_file(&root.join(dir), "lib.rs", "fn main() {}").await;
+        }
+        create_test_file(root, "scratch.rs", "fn main() {}").await;
+
+        let analyzer = create_analyzer_with_filter(FileFilter {
+            tracked_only: true,
+            ..FileFilter::default()
+        })
+        .await;
+        // Not a git checkout yet
+        assert!(analyzer
+            .discover_files(&[root.to_path_buf()])
+            .await
+            .is_err());
+
+        for args in [&["init", "--quiet"][..], &["add", "src/lib.rs"]] {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        let discovered: Vec<PathBuf> = analyzer
+            .discover_files(&[root.to_path_buf()])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|path| path.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(discovered, vec![PathBuf::from("src/lib.rs")]);
+    }
+
+    #[test]
+    fn test_shard_parsing() {
+        let shard: Shard = "2/8".parse().unwrap();
+        assert_eq!(shard, Shard { index: 2, total: 8 });
+        assert_eq!(shard.to_string(), "2/8");
+
+        assert!("0/8".parse::<Shard>().is_err());
+        assert!("9/8".parse::<Shard>().is_err());
+        assert!("2".parse::<Shard>().is_err());
+    }
+
+    #[test]
+    fn test_shards_partition_files() {
+        let files: Vec<PathBuf> = (0..50)
+            .map(|i| PathBuf::from(format!("src/module_{i}.rs")))
+            .collect();
+
+        for file in &files {
+            let owners = (1..=4)
+                .filter(|&index| Shard { index, total: 4 }.contains(file))
+                .count();
+            assert_eq!(
+                owners,
+                1,
+                "{} must belong to exactly one shard",
+                file.display()
+            );
+
+            // Spellings of the same path land in the same shard
+            let dotted = Path::new(".").join(file);
+            let shard =
+                |path: &Path| (1..=4).find(|&index| Shard { index, total: 4 }.contains(path));
+            assert_eq!(shard(&dotted), shard(file));
+        }
+    }
+
+    #[test]
+    fn test_merge_results() {
+        let file_result = |path: &str, quality_score: f64| FileAnalysisResult {
+            quality_score,
+            ..test_support::file_result(path, Vec::new())
+        };
+
+        let function = |path: &str, name: &str, detections: usize| FileFunction {
+            file_path: PathBuf::from(path),
+            function: FunctionScore {
+                name: name.to_string(),
+                kind: "function".to_string(),
+                start_line: 1,
+                end_line: 10,
+                detections,
+                complexity: 1,
+                quality_score: 100.0 - 10.0 * detections as f64,
+            },
+        };
+        let changed = |path: &str, name: &str| ChangedFunction {
+            file_path: PathBuf::from(path),
+            function: function(path, name, 0).function,
+            previous_quality_score: None,
+            quality_delta: None,
+            changed_lines: 1,
+        };
+
+        let mut first = AnalysisResults::from_file_results(vec![
+            file_result("b.rs", 80.0),
+            file_result("c.rs", 50.0),
+        ]);
+        first.changed_functions = vec![changed("b.rs", "old"), changed("c.rs", "parse")];
+        first.worst_functions = vec![function("c.rs", "parse", 3), function("b.rs", "old", 1)];
+        let mut second = AnalysisResults::from_file_results(vec![
+            file_result("a.rs", 100.0),
+            file_result("b.rs", 90.0),
+        ]);
+        second.changed_functions = vec![changed("a.rs", "run")];
+        second.worst_functions = vec![function("a.rs", "run", 2)];
+
+        let merged = AnalysisResults::merge(vec![first, second]);
+        assert_eq!(merged.total_files, 3);
+        assert_eq!(merged.file_results[0].file_path, PathBuf::from("a.rs"));
+        assert!((merged.average_quality_score - 80.0).abs() < f64::EPSILON);
+
+        // b.rs comes from the second part, which changed none of its functions
+        let changed: Vec<_> = merged
+            .changed_functions
+            .iter()
+            .map(|c| &c.function.name)
+            .collect();
+        assert_eq!(changed, vec!["run", "parse"]);
+        let worst: Vec<_> = merged
+            .worst_functions
+            .iter()
+            .map(|w| &w.function.name)
+            .collect();
+        assert_eq!(worst, vec!["parse", "run"]);
+    }
+
+    #[tokio::test]
+    async fn test_reproducible_results() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_file(temp_dir.path(), "b.rs", "// TODO: one\n// FIXME: two\n").await;
+        create_test_file(temp_dir.path(), "a.rs", "fn f() { unimplemented!() }\n").await;
+        create_test_file(temp_dir.path(), "notes.xyz", "plain text").await;
+
+        let mut serialized = Vec::new();
+        for _ in 0..2 {
+            let config = AnalysisConfig {
+                reproducible: true,
+                ..AnalysisConfig::default()
+            };
+            let analyzer = crate::analysis::MisalignmentAnalyzer::new().unwrap();
+            let mut analyzer = StandaloneAnalyzer::new(analyzer, config);
+            let results = analyzer
+                .analyze_files(&[temp_dir.path().to_path_buf()])
+                .await
+                .unwrap();
+            assert_eq!(
+                results.run.as_ref().unwrap().timestamp,
+                reproducible_timestamp()
+            );
+            let paths: Vec<_> = results.file_results.iter().map(|r| &r.file_path).collect();
+            assert!(paths.windows(2).all(|pair| pair[0] <= pair[1]));
+            serialized.push(serde_json::to_string(&results).unwrap());
+        }
+        assert_eq!(serialized[0], serialized[1]);
+    }
+
+    #[test]
+    fn test_run_metadata_records_network_status() {
+        let mut run = RunMetadata::capture(Path::new("/work/app"), "hash".to_string());
+        run.network = NetworkStatus::Disabled;
+        let json = serde_json::to_value(&run).unwrap();
+        assert_eq!(json["network"], "disabled");
+
+        // Results written before the network status was recorded
+        let mut json = json;
+        json.as_object_mut().unwrap().remove("network");
+        let run: RunMetadata = serde_json::from_value(json).unwrap();
+        assert_eq!(run.network, NetworkStatus::Enabled);
+    }
+
+    #[test]
+    fn test_compliance_summary() {
+        let detection = |rule_id: &str, severity: Severity, cwe: &[&str]| MisalignmentDetection {
+            taxonomy: (!cwe.is_empty()).then(|| crate::playbook::SecurityTaxonomy {
+                cwe: cwe.iter().map(ToString::to_string).collect(),
+                owasp: vec!["A07:2021".to_string()],
+                references: Vec::new(),
+            }),
+            ..test_support::detection(rule_id, severity, 1)
+        };
+        let file_result = test_support::file_result;
+
+        let results = AnalysisResults::from_file_results(vec![
+            file_result(
+                "a.rs",
+                vec![
+                    detection("fake_auth", Severity::Critical, &["CWE-287"]),
+                    detection("fake_auth", Severity::Critical, &["CWE-287"]),
+                    detection("todo", Severity::Low, &[]),
+                ],
+            ),
+            file_result(
+                "b.rs",
+                vec![detection(
+                    "weak_check",
+                    Severity::Medium,
+                    &["CWE-287", "CWE-862"],
+                )],
+            ),
+        ]);
+
+        let summary: Vec<_> = results
+            .compliance
+            .iter()
+            .map(|group| {
+                (
+                    group.cwe.as_str(),
+                    group.detections,
+                    group.files,
+                    group.rules.len(),
+                )
+            })
+            .collect();
+        assert_eq!(summary, vec![("CWE-287", 3, 2, 2), ("CWE-862", 1, 1, 1)]);
+        assert_eq!(results.compliance[0].highest_severity, Severity::Critical);
+        assert_eq!(results.compliance[0].owasp, vec!["A07:2021"]);
+    }
+
+    #[tokio::test]
+    async fn test_nested_severity_overrides() {
+        use crate::project_config::CONFIG_FILE_NAME;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("legacy")).await.unwrap();
+        create_test_file(root, CONFIG_FILE_NAME, "root = true\n").await;
+        create_test_file(
+            &root.join("legacy"),
+            CONFIG_FILE_NAME,
+            "[rules.overrides]\nrust_unimplemented_macro = \"low\"\n",
+        )
+        .await;
+        let content = "fn f() { unimplemented!() }\n";
+        let src = create_test_file(root, "a.rs", content).await;
+        let legacy = create_test_file(&root.join("legacy"), "a.rs", content).await;
+
+        let mut analyzer = create_analyzer_with_filter(FileFilter::default()).await;
+        let results = analyzer.analyze_files(&[src, legacy]).await.unwrap();
+        let mut severities: Vec<_> = results
+            .file_results
+            .iter()
+            .map(|result| {
+                let detection = result
+                    .detections
+                    .iter()
+                    .find(|d| d.rule_id == "rust_unimplemented_macro")
+                    .unwrap();
+                (result.project_path.clone().unwrap(), detection.severity)
+            })
+            .collect();
+        severities.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            severities,
+            vec![
+                ("a.rs".to_string(), Severity::Critical),
+                ("legacy/a.rs".to_string(), Severity::Low),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_analysis_warnings() {
+        let temp_dir = TempDir::new().unwrap();
+        let unsupported = create_test_file(temp_dir.path(), "notes.xyz", "plain text").await;
+        let missing = temp_dir.path().join("missing.rs");
+
+        let mut analyzer = create_analyzer_with_filter(FileFilter::default()).await;
+        let results = analyzer
+            .analyze_files(&[unsupported.clone(), missing.clone()])
+            .await
+            .unwrap();
+
+        let warnings: Vec<_> = results
+            .warnings
+            .iter()
+            .map(|warning| (warning.kind, warning.path.clone()))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec![
+                (AnalysisWarningKind::MissingPath, Some(missing)),
+                (AnalysisWarningKind::UnsupportedLanguage, Some(unsupported)),
+            ]
+        );
+        let coverage = results.coverage.unwrap();
+        assert_eq!(coverage.discovered, 1);
+        assert_eq!(coverage.analyzed, 0);
+        assert_eq!(
+            coverage.skipped.get(&SkipReason::UnsupportedLanguage),
+            Some(&1)
+        );
+    }
+
+    #[test]
+    fn test_skipped_percentage_ignores_deliberate_skips() {
+        let mut coverage = FileCoverage {
+            discovered: 10,
+            analyzed: 5,
+            skipped: BTreeMap::new(),
+        };
+        for _ in 0..4 {
+            coverage.count_skipped(SkipReason::TestFile);
+        }
+        coverage.count_skipped(SkipReason::FileTooLarge);
+
+        // 6 files were selected, 1 of them could not be analyzed
+        assert!((coverage.skipped_percentage() - 100.0 / 6.0).abs() < 1e-9);
+        assert_eq!(coverage.total_skipped(), 5);
+    }
+
+    #[test]
+    fn test_formatting_normalization() {
+        let original = "fn run(a: u8, b: u8) -> u8 {\n    call(a, b)\n}\n";
+        let reindented = "fn run(a: u8, b: u8) -> u8 {\n\n  call(a,  b)  \n}";
+        let reformatted = "fn run(\n    a: u8,\n    b: u8,\n) -> u8 {\n    call(a, b)\n}\n";
+        assert_eq!(
+            normalize_whitespace(original),
+            normalize_whitespace(reindented)
+        );
+        assert_ne!(
+            normalize_whitespace(original),
+            normalize_whitespace(reformatted)
+        );
+        assert_eq!(normalize_format(original), normalize_format(reformatted));
+
+        // Words stay apart, and whitespace inside strings still counts
+        assert_ne!(
+            normalize_format("let x = 1;"),
+            normalize_format("letx = 1;")
+        );
+        assert_ne!(
+            normalize_format("f(\"a  b\")"),
+            normalize_format("f(\"a b\")")
+        );
+        assert_eq!(
+            normalize_format("fn f<'a>(x: &'a str) {}"),
+            normalize_format("fn f<'a>(x:&'a str){}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_scope_is_reused() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/generated"))
+            .await
+            .unwrap();
+        create_test_file(&root.join("src"), "lib.rs", "fn main() {}").await;
+        create_test_file(&root.join("src/generated"), "api.rs", "fn api() {}").await;
+        create_test_file(root, "notes.txt", "notes").await;
+
+        let manager = CheckpointManager::new(root).unwrap();
+        let scope = CheckpointScope {
+            paths: vec![root.join("src")],
+            include: vec!["**/*.rs".to_string()],
+            exclude: vec!["**/generated/**".to_string()],
+        };
+        manager
+            .create_checkpoint("scoped", &scope, None)
+            .await
+            .unwrap();
+        let info = manager.get_checkpoint("scoped").await.unwrap().unwrap();
+        assert_eq!(info.file_count, 1);
+        assert_eq!(info.scope, Some(scope));
+
+        // Without paths, the diff captures the stored paths with the stored filters
+        create_test_file(&root.join("src"), "new.rs", "fn new() {}").await;
+        create_test_file(&root.join("src/generated"), "more.rs", "fn more() {}").await;
+        create_test_file(&root.join("src"), "README.md", "docs").await;
+        let comparison = manager
+            .compare_files("scoped", None, ComparisonMode::Exact)
+            .await
+            .unwrap();
+        assert_eq!(comparison.new_files, vec![root.join("src/new.rs")]);
+        assert!(comparison.changed_files.is_empty());
+        assert!(comparison.deleted_files.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_writes_are_locked() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = create_test_file(temp_dir.path(), "lib.rs", "fn main() {}").await;
+        let manager = CheckpointManager::new(temp_dir.path()).unwrap();
+
+        let lock = manager.lock(CHECKPOINT_LOCK_TIMEOUT).await.unwrap();
+        let error = manager.lock(Duration::ZERO).await.err().unwrap();
+        assert!(matches!(error, SniffError::Locked { .. }));
+        drop(lock);
+
+        let scope = CheckpointScope::new(vec![file]);
+        manager
+            .create_checkpoint("first", &scope, None)
+            .await
+            .unwrap();
+        let mut names: Vec<_> = std::fs::read_dir(&manager.checkpoint_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        // No temp file is left behind
+        assert_eq!(names, vec![".lock", "first.json"]);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_records_lines_and_function_scores() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = create_test_file(temp_dir.path(), "lib.rs", "fn a() {\n    1\n}\n").await;
+        let manager = CheckpointManager::new(temp_dir.path()).unwrap();
+        manager
+            .create_checkpoint("before", &CheckpointScope::new(vec![file.clone()]), None)
+            .await
+            .unwrap();
+
+        let score = FunctionScore {
+            name: "a".to_string(),
+            kind: "function".to_string(),
+            start_line: 1,
+            end_line: 3,
+            detections: 0,
+            complexity: 1,
+            quality_score: 100.0,
+        };
+        manager
+            .record_function_scores(
+                "before",
+                HashMap::from([(file.clone(), vec![score.clone()])]),
+            )
+            .await
+            .unwrap();
+
+        let recorded = manager.recorded_files("before").await.unwrap();
+        let recorded = &recorded[&file];
+        assert_eq!(recorded.functions, vec![score]);
+        let line_hashes = recorded.line_hashes.as_deref().unwrap();
+        assert_eq!(
+            function_quality::changed_lines(line_hashes, "fn a() {\n    2\n}\n"),
+            BTreeSet::from([2])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compare_files_ignoring_formatting() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = create_test_file(temp_dir.path(), "lib.rs", "fn run() { call(1, 2) }\n").await;
+        let manager = CheckpointManager::new(temp_dir.path()).unwrap();
+        let paths = [file.clone()];
+        manager
+            .create_checkpoint("before", &CheckpointScope::new(paths.to_vec()), None)
+            .await
+            .unwrap();
+
+        fs::write(&file, "fn run() {\n    call(1, 2)\n}\n")
+            .await
+            .unwrap();
+        let exact = manager
+            .compare_files("before", Some(&paths), ComparisonMode::Exact)
+            .await
+            .unwrap();
+        assert_eq!(exact.changed_files, vec![file.clone()]);
+        let ignoring = manager
+            .compare_files("before", Some(&paths), ComparisonMode::IgnoreFormat)
+            .await
+            .unwrap();
+        assert!(ignoring.changed_files.is_empty());
+        assert_eq!(ignoring.formatting_only_files, vec![file.clone()]);
+
+        fs::write(&file, "fn run() { call(1, 3) }\n").await.unwrap();
+        let changed = manager
+            .compare_files("before", Some(&paths), ComparisonMode::IgnoreFormat)
+            .await
+            .unwrap();
+        assert_eq!(changed.changed_files, vec![file]);
+    }
 }
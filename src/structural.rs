@@ -0,0 +1,497 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Structural playbook rules evaluated over the symbol table of a file.
+//!
+//! Most analyses pick functions or types from the symbols reported by the
+//! codebase analyzer and inspect the source lines they span:
+//!
+//! - `uncalled_function`: functions never referenced elsewhere in the file.
+//!   Parameter `visibility` is `private` (default), `public` or `any`.
+//!   Callers are only looked up in the same file, so public functions are
+//!   reported only when asked for, e.g. for scripts.
+//! - `large_type`: structs and classes with more than `max_fields` fields
+//!   (default 15).
+//! - `trivial_body`: functions with at most `max_statements` statements
+//!   (default 1), optionally only those whose last statement is `returns`
+//!   (e.g. `Ok(())`). With `skip_trait_impls: "true"`, Rust trait impl
+//!   methods are skipped: the trait fixes what they return.
+//! - `duplicate_key`: keys repeated in the same mapping of a YAML or JSON
+//!   file, found by parsing it (see [`crate::config_keys`]).
+
+use crate::analysis::{is_comment_line, SupportedLanguage};
+use rust_tree_sitter::analyzer::Symbol;
+use std::collections::HashMap;
+
+/// Which functions an `uncalled_function` analysis considers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    /// Functions not visible outside their module or file.
+    Private,
+    /// Exported functions.
+    Public,
+    /// All functions.
+    Any,
+}
+
+/// A structural analysis with its parameters resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructuralAnalysis {
+    /// Functions without references elsewhere in the file.
+    UncalledFunction {
+        /// Functions to consider.
+        visibility: Visibility,
+    },
+    /// Structs and classes with too many fields.
+    LargeType {
+        /// Largest field count that is not reported.
+        max_fields: usize,
+    },
+    /// Functions whose body is (nearly) empty.
+    TrivialBody {
+        /// Largest statement count that is reported.
+        max_statements: usize,
+        /// Required last statement, with `return` and trailing `;` ignored.
+        returns: Option<String>,
+        /// Whether Rust methods in trait impls are skipped.
+        skip_trait_impls: bool,
+    },
+    /// Keys repeated in the same mapping of a config file.
+    DuplicateKey,
+}
+
+/// A symbol reported by a structural analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructuralMatch {
+    /// 1-based line of the symbol definition.
+    pub line: usize,
+    /// The definition line, trimmed.
+    pub snippet: String,
+    /// What was measured, e.g. `Class: Config (23 fields)`.
+    pub context: String,
+}
+
+impl StructuralAnalysis {
+    /// Resolves an analysis type and its parameters from a playbook rule.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the problem for unknown analysis types,
+    /// unknown parameters and parameter values that do not parse.
+    pub fn parse(
+        analysis_type: &str,
+        parameters: &HashMap<String, String>,
+    ) -> std::result::Result<Self, String> {
+        let known: &[&str] = match analysis_type {
+            "uncalled_function" => &["visibility"],
+            "large_type" => &["max_fields"],
+            "trivial_body" => &["max_statements", "returns", "skip_trait_impls"],
+            "duplicate_key" => &[],
+            other => {
+                return Err(format!(
+                    "unknown analysis type '{other}' \
+                     (expected uncalled_function, large_type, trivial_body or duplicate_key)"
+                ))
+            }
+        };
+        if let Some(unknown) = parameters.keys().find(|key| !known.contains(&key.as_str())) {
+            return Err(format!("unknown parameter '{unknown}' for {analysis_type}"));
+        }
+
+        let count = |name: &str, default: usize| {
+            parameters.get(name).map_or(Ok(default), |value| {
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("parameter '{name}' must be a number, got '{value}'"))
+            })
+        };
+
+        Ok(match analysis_type {
+            "uncalled_function" => Self::UncalledFunction {
+                visibility: match parameters.get("visibility").map(String::as_str) {
+                    None | Some("private") => Visibility::Private,
+                    Some("public") => Visibility::Public,
+                    Some("any") => Visibility::Any,
+                    Some(other) => {
+                        return Err(format!(
+                            "parameter 'visibility' must be private, public or any, got '{other}'"
+                        ))
+                    }
+                },
+            },
+            "large_type" => Self::LargeType {
+                max_fields: count("max_fields", 15)?,
+            },
+            "duplicate_key" => Self::DuplicateKey,
+            _ => Self::TrivialBody {
+                max_statements: count("max_statements", 1)?,
+                returns: parameters
+                    .get("returns")
+                    .map(|value| normalize_statement(value)),
+                skip_trait_impls: match parameters.get("skip_trait_impls").map(|v| v.trim()) {
+                    None | Some("false") => false,
+                    Some("true") => true,
+                    Some(other) => {
+                        return Err(format!(
+                            "parameter 'skip_trait_impls' must be true or false, got '{other}'"
+                        ))
+                    }
+                },
+            },
+        })
+    }
+
+    /// Runs the analysis over the symbols of a file.
+    #[must_use]
+    pub fn find(
+        &self,
+        language: SupportedLanguage,
+        symbol_table: &[Symbol],
+        content: &str,
+    ) -> Vec<StructuralMatch> {
+        let lines: Vec<&str> = content.lines().collect();
+        let span = |symbol: &Symbol| {
+            let start = symbol.start_line.saturating_sub(1).min(lines.len());
+            &lines[start..symbol.end_line.clamp(start, lines.len())]
+        };
+        let found = |symbol: &Symbol, context: String| StructuralMatch {
+            line: symbol.start_line,
+            snippet: span(symbol)
+                .first()
+                .map_or("", |line| line.trim())
+                .to_string(),
+            context,
+        };
+
+        let mut matches = Vec::new();
+        match self {
+            Self::UncalledFunction { visibility } => {
+                let words = word_lines(&lines);
+                for symbol in symbol_table.iter().filter(|symbol| is_function(symbol)) {
+                    let definition = span(symbol).first().copied().unwrap_or_default();
+                    let public = is_public(language, symbol, definition);
+                    let wanted = match visibility {
+                        Visibility::Private => !public,
+                        Visibility::Public => public,
+                        Visibility::Any => true,
+                    };
+                    if wanted
+                        && !is_entry_point(&symbol.name)
+                        && !is_trait_method(language, symbol, &lines)
+                        && !is_referenced(symbol, &words)
+                    {
+                        matches.push(found(symbol, format!("Function: {}", symbol.name)));
+                    }
+                }
+            }
+            Self::LargeType { max_fields } => {
+                for symbol in symbol_table.iter().filter(|symbol| is_type(symbol)) {
+                    let fields = count_fields(language, span(symbol));
+                    if fields > *max_fields {
+                        let measured = format!("Class: {} ({fields} fields)", symbol.name);
+                        matches.push(found(symbol, measured));
+                    }
+                }
+            }
+            Self::TrivialBody {
+                max_statements,
+                returns,
+                skip_trait_impls,
+            } => {
+                for symbol in symbol_table.iter().filter(|symbol| is_function(symbol)) {
+                    if *skip_trait_impls && is_trait_method(language, symbol, &lines) {
+                        continue;
+                    }
+                    let statements = body_statements(language, span(symbol));
+                    let returns_match = returns
+                        .as_ref()
+                        .is_none_or(|expected| statements.last() == Some(expected));
+                    if statements.len() <= *max_statements && returns_match {
+                        let measured = format!(
+                            "Function: {} ({} statements)",
+                            symbol.name,
+                            statements.len()
+                        );
+                        matches.push(found(symbol, measured));
+                    }
+                }
+            }
+            Self::DuplicateKey => {
+                for duplicate in crate::config_keys::find_duplicate_keys(content) {
+                    matches.push(StructuralMatch {
+                        line: duplicate.line,
+                        snippet: lines
+                            .get(duplicate.line.saturating_sub(1))
+                            .map_or("", |line| line.trim())
+                            .to_string(),
+                        context: format!("Key: {}", duplicate.key),
+                    });
+                }
+            }
+        }
+
+        matches
+    }
+}
+
+fn is_function(symbol: &Symbol) -> bool {
+    symbol.kind == "function" || symbol.kind == "method"
+}
+
+fn is_type(symbol: &Symbol) -> bool {
+    symbol.kind == "class" || symbol.kind == "struct"
+}
+
+/// Functions invoked by a runtime or test harness rather than by code.
+fn is_entry_point(name: &str) -> bool {
+    matches!(name, "main" | "init" | "new" | "default" | "constructor")
+        || name.starts_with("test")
+        || (name.starts_with("__") && name.ends_with("__"))
+}
+
+/// Whether a Rust function implements a trait method, which is called through the trait.
+fn is_trait_method(language: SupportedLanguage, symbol: &Symbol, lines: &[&str]) -> bool {
+    let start = symbol.start_line.saturating_sub(1);
+    let Some(definition) = lines.get(start) else {
+        return false;
+    };
+    if language != SupportedLanguage::Rust || indentation(definition) == 0 {
+        return false;
+    }
+    lines[..start]
+        .iter()
+        .rev()
+        .find(|line| !line.trim().is_empty() && indentation(line) < indentation(definition))
+        .is_some_and(|line| line.trim_start().starts_with("impl") && line.contains(" for "))
+}
+
+/// Whether a function is visible outside its file, judged from its definition line.
+fn is_public(language: SupportedLanguage, symbol: &Symbol, definition: &str) -> bool {
+    let definition = definition.trim_start();
+    match language {
+        SupportedLanguage::Rust => definition.starts_with("pub"),
+        SupportedLanguage::Python => !symbol.name.starts_with('_'),
+        SupportedLanguage::JavaScript | SupportedLanguage::TypeScript => {
+            if symbol.kind == "method" {
+                !definition.starts_with("private") && !symbol.name.starts_with('#')
+            } else {
+                definition.starts_with("export")
+            }
+        }
+        SupportedLanguage::Go => symbol.name.starts_with(|c: char| c.is_ascii_uppercase()),
+        SupportedLanguage::C | SupportedLanguage::Cpp => !definition.starts_with("static"),
+        SupportedLanguage::Swift => {
+            !definition.starts_with("private") && !definition.starts_with("fileprivate")
+        }
+        // Terraform, config and Markdown files have no functions
+        SupportedLanguage::Terraform | SupportedLanguage::Config | SupportedLanguage::Markdown => {
+            true
+        }
+        SupportedLanguage::Scala => {
+            !definition.starts_with("private") && !definition.starts_with("protected")
+        }
+    }
+}
+
+/// Returns the 0-based indexes of the lines each word occurs on, outside
+/// comment lines, so references are looked up without rescanning the file.
+fn word_lines<'a>(lines: &[&'a str]) -> HashMap<&'a str, Vec<usize>> {
+    let mut words: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, line) in lines.iter().enumerate() {
+        if is_comment_line(line) {
+            continue;
+        }
+        for word in line
+            .split(|c: char| !is_word_char(c))
+            .filter(|word| !word.is_empty())
+        {
+            let found = words.entry(word).or_default();
+            if found.last() != Some(&index) {
+                found.push(index);
+            }
+        }
+    }
+    words
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whether the function name occurs as a word outside its own definition.
+/// Names that are not a single word, such as JavaScript `#private` methods,
+/// count as referenced.
+///
+/// Recursive calls do not count: they do not make a function reachable.
+fn is_referenced(symbol: &Symbol, words: &HashMap<&str, Vec<usize>>) -> bool {
+    if symbol.name.is_empty() || !symbol.name.chars().all(is_word_char) {
+        return true;
+    }
+    let start = symbol.start_line.saturating_sub(1);
+    let own_span = start..symbol.end_line.max(start + 1);
+    words
+        .get(symbol.name.as_str())
+        .is_some_and(|found| found.iter().any(|index| !own_span.contains(index)))
+}
+
+/// Counts the field declarations directly inside a struct or class body.
+fn count_fields(language: SupportedLanguage, span: &[&str]) -> usize {
+    let is_member = |line: &str| {
+        !line.is_empty()
+            && !is_comment_line(line)
+            && !line.starts_with('@')
+            && !line.contains('(')
+            && !line.ends_with(':')
+    };
+
+    if language == SupportedLanguage::Python {
+        let body = span.get(1..).unwrap_or_default();
+        let Some(indent) = body
+            .iter()
+            .find(|line| !line.trim().is_empty() && !is_comment_line(line))
+            .map(|line| indentation(line))
+        else {
+            return 0;
+        };
+        return body
+            .iter()
+            .filter(|line| indentation(line) == indent)
+            .map(|line| line.trim())
+            .filter(|line| is_member(line) && (line.contains(':') || line.contains('=')))
+            .count();
+    }
+
+    // Brace languages: members are lines that stay at depth 1
+    let mut depth = 0usize;
+    let mut fields = 0;
+    for line in span {
+        let trimmed = line.trim();
+        let member_depth = depth == 1;
+        depth += trimmed.matches('{').count();
+        depth = depth.saturating_sub(trimmed.matches('}').count());
+        if member_depth && depth == 1 && is_member(trimmed) {
+            fields += 1;
+        }
+    }
+    fields
+}
+
+/// Returns the normalized statements of a function body.
+fn body_statements(language: SupportedLanguage, span: &[&str]) -> Vec<String> {
+    let body = if language == SupportedLanguage::Python {
+        // The signature may span lines; the body starts after the line ending in ':'
+        match span.iter().position(|line| line.trim_end().ends_with(':')) {
+            Some(index) => span[index + 1..].join("\n"),
+            None => span
+                .first()
+                .and_then(|line| line.rsplit_once(')').map(|(_, rest)| rest))
+                .and_then(|rest| rest.split_once(':').map(|(_, body)| body.to_string()))
+                .unwrap_or_default(),
+        }
+    } else {
+        let text = span.join("\n");
+        match (text.find('{'), text.rfind('}')) {
+            (Some(open), Some(close)) if open < close => text[open + 1..close].to_string(),
+            _ => return Vec::new(),
+        }
+    };
+
+    body.lines()
+        .map(normalize_statement)
+        .filter(|line| !line.is_empty() && !is_comment_line(line))
+        .filter(|line| !line.chars().all(|c| matches!(c, '{' | '}' | ')' | ']')))
+        .collect()
+}
+
+/// Strips `return`, trailing semicolons and surrounding whitespace.
+fn normalize_statement(statement: &str) -> String {
+    let statement = statement.trim();
+    let statement = statement.strip_prefix("return ").unwrap_or(statement);
+    statement.trim_end_matches(';').trim().to_string()
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_parameters() {
+        let parameters = HashMap::from([
+            ("max_statements".to_string(), "1".to_string()),
+            ("returns".to_string(), "Ok(());".to_string()),
+        ]);
+        assert_eq!(
+            StructuralAnalysis::parse("trivial_body", &parameters),
+            Ok(StructuralAnalysis::TrivialBody {
+                max_statements: 1,
+                returns: Some("Ok(())".to_string()),
+                skip_trait_impls: false,
+            })
+        );
+        let skip = HashMap::from([("skip_trait_impls".to_string(), "yes".to_string())]);
+        assert!(StructuralAnalysis::parse("trivial_body", &skip).is_err());
+        assert!(StructuralAnalysis::parse("large_type", &parameters).is_err());
+        assert!(StructuralAnalysis::parse("dead_code", &HashMap::new()).is_err());
+        assert_eq!(
+            StructuralAnalysis::parse("duplicate_key", &HashMap::new()),
+            Ok(StructuralAnalysis::DuplicateKey)
+        );
+    }
+
+    #[test]
+    fn test_word_lines() {
+        let lines = [
+            "fn load_config() {}",
+            "// load_config is unused",
+            "let config = load_config(); load_config();",
+            "let x = reload_config_now;",
+        ];
+        let words = word_lines(&lines);
+        // Comment lines are skipped and each line is listed once
+        assert_eq!(words["load_config"], vec![0, 2]);
+        assert_eq!(words["reload_config_now"], vec![3]);
+        assert!(!words.contains_key("config_now"));
+    }
+
+    #[test]
+    fn test_body_statements_and_fields() {
+        let function = [
+            "pub fn save(&self) -> Result<()> {",
+            "    // nothing yet",
+            "    Ok(())",
+            "}",
+        ];
+        assert_eq!(
+            body_statements(SupportedLanguage::Rust, &function),
+            ["Ok(())"]
+        );
+        let one_line = ["def ready(self) -> bool: return True"];
+        assert_eq!(
+            body_statements(SupportedLanguage::Python, &one_line),
+            ["True"]
+        );
+
+        let rust_struct = [
+            "pub struct Config {",
+            "    #[serde(default)]",
+            "    pub name: String,",
+            "    retries: u32,",
+            "}",
+        ];
+        assert_eq!(count_fields(SupportedLanguage::Rust, &rust_struct), 2);
+        let python_class = [
+            "class Config:",
+            "    \"\"\"Settings.\"\"\"",
+            "    name: str = \"\"",
+            "    retries = 3",
+            "    def load(self):",
+            "        self.cache = {}",
+        ];
+        assert_eq!(count_fields(SupportedLanguage::Python, &python_class), 2);
+    }
+}
@@ -0,0 +1,212 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Inline suppression comments.
+//!
+//! `sniff-ignore: <rule_id> reason` hides detections of a rule on the line of
+//! the comment, and `sniff-ignore-next-line: <rule_id> reason` on the line
+//! below. Several rule ids may be separated by commas; without a rule id the
+//! comment hides every rule. Directives are recognised after any common
+//! comment marker (`//`, `#`, `--`, `/*`, `<!--`, `;`), so they work in all
+//! supported languages.
+
+use crate::analysis::{MisalignmentDetection, SuppressedDetection};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// A comment marker followed by a suppression directive and its arguments.
+static DIRECTIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?://|#|--|/\*|<!--|;)\s*sniff-ignore(-next-line)?\b(.*)$")
+        .expect("suppression directive regex is valid")
+});
+
+/// An inline `sniff-ignore` comment and the detections it hid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InlineSuppression {
+    /// Line of the comment (1-based).
+    pub line_number: usize,
+    /// Line whose detections are hidden (1-based).
+    pub target_line: usize,
+    /// Rule ids hidden; empty means every rule.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rule_ids: Vec<String>,
+    /// Justification written in the comment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Number of detections hidden by this comment.
+    pub suppressed: usize,
+}
+
+impl InlineSuppression {
+    /// Returns true if the comment hides the detection.
+    fn covers(&self, detection: &MisalignmentDetection) -> bool {
+        detection.line_number == self.target_line
+            && (self.rule_ids.is_empty() || self.rule_ids.contains(&detection.rule_id))
+    }
+
+    /// Describes the suppression for a hidden detection.
+    fn describe(&self) -> String {
+        match &self.reason {
+            Some(reason) => format!("sniff-ignore on line {}: {reason}", self.line_number),
+            None => format!(
+                "sniff-ignore on line {} (no reason given)",
+                self.line_number
+            ),
+        }
+    }
+}
+
+/// Totals of the suppression comments across files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SuppressionSummary {
+    /// Number of suppression comments.
+    pub comments: usize,
+    /// Number of detections they hid.
+    pub suppressed: usize,
+    /// Comments without a reason.
+    pub without_reason: usize,
+    /// Comments that hid nothing.
+    pub unused: usize,
+}
+
+impl SuppressionSummary {
+    /// Totals the given suppression comments.
+    pub fn of<'a>(suppressions: impl IntoIterator<Item = &'a InlineSuppression>) -> Self {
+        let mut summary = Self::default();
+        for suppression in suppressions {
+            summary.comments += 1;
+            summary.suppressed += suppression.suppressed;
+            if suppression.reason.is_none() {
+                summary.without_reason += 1;
+            }
+            if suppression.suppressed == 0 {
+                summary.unused += 1;
+            }
+        }
+        summary
+    }
+}
+
+/// Finds the suppression comments in file content.
+#[must_use]
+pub fn parse(content: &str) -> Vec<InlineSuppression> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let captures = DIRECTIVE.captures(line)?;
+            let next_line = captures.get(1).is_some();
+            let arguments = captures[2]
+                .trim()
+                .trim_end_matches("*/")
+                .trim_end_matches("-->")
+                .trim();
+
+            // `: rule_a,rule_b reason` names rules; anything else is only a reason
+            let (rule_ids, reason) = match arguments.strip_prefix(':') {
+                Some(arguments) => {
+                    let arguments = arguments.trim_start();
+                    let (rules, reason) = arguments
+                        .split_once(char::is_whitespace)
+                        .unwrap_or((arguments, ""));
+                    let rule_ids = rules
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|rule_id| !rule_id.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                    (rule_ids, reason.trim())
+                }
+                None => (Vec::new(), arguments),
+            };
+
+            Some(InlineSuppression {
+                line_number: index + 1,
+                target_line: if next_line { index + 2 } else { index + 1 },
+                rule_ids,
+                reason: (!reason.is_empty()).then(|| reason.to_string()),
+                suppressed: 0,
+            })
+        })
+        .collect()
+}
+
+/// Removes the detections hidden by suppression comments, counting them on
+/// each comment. Returns the reported and the hidden detections.
+#[must_use]
+pub fn apply(
+    suppressions: &mut [InlineSuppression],
+    detections: Vec<MisalignmentDetection>,
+) -> (Vec<MisalignmentDetection>, Vec<SuppressedDetection>) {
+    let mut reported = Vec::new();
+    let mut hidden = Vec::new();
+    for detection in detections {
+        match suppressions.iter_mut().find(|s| s.covers(&detection)) {
+            Some(suppression) => {
+                suppression.suppressed += 1;
+                hidden.push(SuppressedDetection {
+                    suppression_reason: suppression.describe(),
+                    detection,
+                });
+            }
+            None => reported.push(detection),
+        }
+    }
+    (reported, hidden)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support::detection;
+    use crate::playbook::Severity;
+
+    #[test]
+    fn test_inline_suppressions() {
+        let content = "\
+value = data.unwrap()  # sniff-ignore: rust_unwrap_usage checked above
+// sniff-ignore-next-line
+todo!()
+/* sniff-ignore-next-line: a,b legacy API */
+call()
+let sniff_ignored = 1;
+";
+        let mut suppressions = parse(content);
+        assert_eq!(suppressions.len(), 3);
+        assert_eq!(suppressions[0].target_line, 1);
+        assert_eq!(suppressions[0].rule_ids, vec!["rust_unwrap_usage"]);
+        assert_eq!(suppressions[0].reason.as_deref(), Some("checked above"));
+        assert_eq!(suppressions[1].target_line, 3);
+        assert!(suppressions[1].rule_ids.is_empty());
+        assert_eq!(suppressions[1].reason, None);
+        assert_eq!(suppressions[2].rule_ids, vec!["a", "b"]);
+        assert_eq!(suppressions[2].reason.as_deref(), Some("legacy API"));
+
+        let (reported, hidden) = apply(
+            &mut suppressions,
+            vec![
+                detection("rust_unwrap_usage", Severity::High, 1),
+                detection("other_rule", Severity::High, 1),
+                detection("rust_todo", Severity::High, 3),
+                detection("c", Severity::High, 5),
+            ],
+        );
+        let reported: Vec<_> = reported.iter().map(|d| d.rule_id.as_str()).collect();
+        assert_eq!(reported, vec!["other_rule", "c"]);
+        assert_eq!(hidden.len(), 2);
+        assert_eq!(
+            hidden[1].suppression_reason,
+            "sniff-ignore on line 2 (no reason given)"
+        );
+        assert_eq!(
+            SuppressionSummary::of(&suppressions),
+            SuppressionSummary {
+                comments: 3,
+                suppressed: 2,
+                without_reason: 1,
+                unused: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! User-provided report templates.
+//!
+//! Renders [`AnalysisResults`] through a Jinja template (`MiniJinja` syntax),
+//! so teams can produce their own layouts, such as wiki pages or ticket
+//! bodies, without a built-in format for each. The template sees the fields
+//! of the JSON output at the top level, plus `detections`, every detection of
+//! every file in one list. Printing or looping over an unknown variable is an
+//! error rather than an empty string, so typos do not go unnoticed; `if`
+//! still treats a missing optional field as false. Templates whose name ends in
+//! `.html` or `.xml` have their output escaped.
+
+use crate::error::{Result, SniffError};
+use crate::standalone::AnalysisResults;
+use minijinja::{Environment, UndefinedBehavior};
+use serde_json::Value;
+use std::path::Path;
+
+/// Renders results with a template read from a file.
+///
+/// # Errors
+///
+/// Returns an error if the template cannot be read, does not parse, or
+/// fails to render.
+pub fn render_file(template_path: &Path, results: &AnalysisResults) -> Result<String> {
+    let source = std::fs::read_to_string(template_path)
+        .map_err(|e| SniffError::file_system(template_path, e))?;
+    let name = template_path.file_name().map_or_else(
+        || "template".to_string(),
+        |name| name.to_string_lossy().to_string(),
+    );
+    render(&name, &source, results)
+}
+
+/// Renders results with a template. The name selects escaping, by extension.
+///
+/// # Errors
+///
+/// Returns an error if the template does not parse or fails to render.
+pub fn render(name: &str, source: &str, results: &AnalysisResults) -> Result<String> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::SemiStrict);
+    let template = env
+        .template_from_named_str(name, source)
+        .map_err(|e| template_error(name, &e))?;
+    template
+        .render(context(results)?)
+        .map_err(|e| template_error(name, &e))
+}
+
+/// Builds the template context: the results as in the JSON output, plus a
+/// flat list of detections.
+fn context(results: &AnalysisResults) -> Result<Value> {
+    let mut context = serde_json::to_value(results)
+        .map_err(|e| SniffError::invalid_format("template context".to_string(), e.to_string()))?;
+    let detections = results
+        .file_results
+        .iter()
+        .flat_map(|file_result| &file_result.detections)
+        .map(serde_json::to_value)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| SniffError::invalid_format("template context".to_string(), e.to_string()))?;
+    if let Value::Object(fields) = &mut context {
+        fields.insert("detections".to_string(), Value::Array(detections));
+    }
+    Ok(context)
+}
+
+/// Wraps a template error, whose message includes the line.
+fn template_error(name: &str, error: &minijinja::Error) -> SniffError {
+    SniffError::invalid_format(format!("template {name}"), error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::test_support::{self, detection};
+    use crate::playbook::Severity;
+
+    fn results() -> AnalysisResults {
+        let mut detection = detection("rust_unimplemented", Severity::Critical, 3);
+        detection.rule_name = "Unimplemented <macro>".to_string();
+        test_support::results(vec![detection])
+    }
+
+    #[test]
+    fn test_render_template() {
+        let template = "{{ total_files }} files, {{ critical_issues }} critical\n\
+                        {% for d in detections %}- {{ d.file_path }}:{{ d.line_number }} \
+                        {{ d.rule_name }} ({{ d.severity }})\n{% endfor %}";
+        let rendered = render("report.md", template, &results()).unwrap();
+        assert_eq!(
+            rendered,
+            "1 files, 1 critical\n- src/lib.rs:3 Unimplemented <macro> (Critical)\n"
+        );
+
+        // HTML templates are escaped
+        let rendered = render("report.html", "{{ detections[0].rule_name }}", &results()).unwrap();
+        assert_eq!(rendered, "Unimplemented &lt;macro&gt;");
+    }
+
+    #[test]
+    fn test_unknown_variables_are_errors() {
+        let error = render("report.md", "{{ total_file }}", &results()).unwrap_err();
+        assert!(error.to_string().contains("template report.md"));
+    }
+}
@@ -0,0 +1,348 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Quality gate for agent-written commit messages and pull request descriptions.
+//!
+//! Text rules are regex playbook rules applied to the text as a single file.
+//! `multiline` rules match across lines, and `conditions` can require or rule
+//! out other wording, which is how a missing issue reference is detected.
+
+use crate::analysis::{retain_meeting_conditions, ConditionsCache, MisalignmentDetection};
+use crate::error::{Result, SniffError};
+use crate::playbook::{
+    DetectionRule, PatternScope, PatternType, Playbook, RuleConditions, Severity, BUILTIN_NAMESPACE,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Language of playbooks whose rules apply to commit messages and PR descriptions.
+pub const TEXT_PLAYBOOK_LANGUAGE: &str = "text";
+
+/// Kind of text being checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextKind {
+    /// A git commit message: a summary line, optionally followed by a body.
+    CommitMessage,
+    /// The body of a pull request.
+    PrDescription,
+}
+
+impl TextKind {
+    /// Returns the name used on the command line and in reports.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            TextKind::CommitMessage => "commit-message",
+            TextKind::PrDescription => "pr-description",
+        }
+    }
+}
+
+/// Creates the built-in text rules for a kind of text.
+#[must_use]
+pub fn default_playbook(kind: TextKind) -> Playbook {
+    let text_rule = |id: &str,
+                     name: &str,
+                     description: &str,
+                     severity,
+                     pattern: &str,
+                     multiline: bool,
+                     tags: &[&str],
+                     example: &str| DetectionRule {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        severity,
+        pattern_type: PatternType::Regex {
+            pattern: pattern.to_string(),
+            flags: None,
+            multiline,
+        },
+        scope: PatternScope::File,
+        enabled: true,
+        tags: tags.iter().map(ToString::to_string).collect(),
+        examples: vec![example.to_string()],
+        false_positives: vec![],
+        paths: Vec::new(),
+        exclude_paths: Vec::new(),
+        conditions: None,
+        deprecated: false,
+        replaced_by: None,
+        deprecated_since: None,
+        remediation: None,
+        cwe: Vec::new(),
+        owasp: Vec::new(),
+        references: Vec::new(),
+    };
+
+    let mut rules = vec![
+        text_rule(
+            "text_test_claim",
+            "Unverified Test Claim",
+            "Claims that tests pass, which the text itself cannot show",
+            Severity::High,
+            r"(?i)\b(?:all|every)\s+(?:the\s+)?tests?\s+(?:are\s+|now\s+)*(?:pass(?:es|ed|ing)?|green)\b|\btests?\s+(?:are\s+|now\s+)*(?:all\s+)?(?:passing|green)\b|\bfully\s+tested\b|\b100%\s+(?:test\s+)?coverage\b",
+            false,
+            &["claims", "fake_success"],
+            "Implement retry logic. All tests passing.",
+        ),
+        text_rule(
+            "text_completeness_claim",
+            "Completeness Claim",
+            "Claims the work is complete or production ready",
+            Severity::High,
+            r"(?i)\b(?:production[- ]ready|fully\s+(?:working|functional|implemented)|works\s+perfectly|no\s+(?:known\s+)?(?:bugs|issues)\s+remain)\b",
+            false,
+            &["claims", "fake_success"],
+            "Auth is now fully implemented and production-ready",
+        ),
+        text_rule(
+            "text_vague_scope",
+            "Vague Scope",
+            "Lumps unnamed changes together instead of describing them",
+            Severity::Low,
+            r"(?i)\b(?:and\s+other\s+(?:stuff|things|changes)|various\s+(?:fixes|changes|improvements)|some\s+(?:fixes|changes))\b",
+            false,
+            &["vague"],
+            "Update parser and other stuff",
+        ),
+    ];
+
+    match kind {
+        TextKind::CommitMessage => rules.push(text_rule(
+            "text_vague_summary",
+            "Vague Summary",
+            "Summary line does not say what changed",
+            Severity::Medium,
+            r"(?i)\A\s*(?:(?:misc(?:ellaneous)?|minor|various|small|some|more|other)\s+)?(?:fix(?:es)?|changes?|updates?|tweaks?|cleanups?|improvements?|wip|stuff)[.!]?[ \t]*(?:\n|\z)",
+            true,
+            &["vague"],
+            "misc fixes",
+        )),
+        TextKind::PrDescription => rules.push(text_rule(
+            "text_empty_description",
+            "Empty Description",
+            "Pull request has no description",
+            Severity::Medium,
+            r"\A\s*\z",
+            true,
+            &["vague"],
+            "",
+        )),
+    }
+
+    let mut missing_reference = text_rule(
+        "text_missing_issue_reference",
+        "Missing Issue Reference",
+        "Does not reference the issue or ticket it addresses",
+        Severity::Low,
+        r"\A",
+        true,
+        &["traceability"],
+        "Add retry logic",
+    );
+    missing_reference.conditions = Some(RuleConditions {
+        // Ticket keys only count in brackets, leading a line or after a
+        // trailer, so names such as UTF-8 and SHA-256 are not references
+        not: vec![concat!(
+            r"#\d+|/(?:issues|pull)/\d+|[\[(][A-Z][A-Z0-9]+-\d+[\])]|(?m:^[A-Z][A-Z0-9]+-\d+:)",
+            r"|(?i:refs?|issues?|tickets?|jira|closes|fixes|resolves)\s*:\s*[A-Z][A-Z0-9]+-\d+\b",
+        )
+        .to_string()],
+        ..RuleConditions::default()
+    });
+    rules.push(missing_reference);
+
+    Playbook {
+        name: "Text Default Patterns".to_string(),
+        namespace: Some(BUILTIN_NAMESPACE.to_string()),
+        version: "1.0.0".to_string(),
+        language: TEXT_PLAYBOOK_LANGUAGE.to_string(),
+        author: "Sniff Core Team".to_string(),
+        description: format!("Quality checks for {} text", kind.name()),
+        rules,
+        metadata: std::collections::HashMap::new(),
+    }
+}
+
+/// Loads a text playbook from a YAML file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed, or if it is not a
+/// `text` playbook.
+pub fn load_playbook(path: &Path) -> Result<Playbook> {
+    let content = std::fs::read_to_string(path).map_err(|e| SniffError::file_system(path, e))?;
+    let playbook: Playbook = serde_yaml::from_str(&content).map_err(|e| {
+        SniffError::invalid_format(
+            "playbook parsing".to_string(),
+            format!("Failed to parse playbook YAML: {e}"),
+        )
+    })?;
+    if playbook.language != TEXT_PLAYBOOK_LANGUAGE {
+        return Err(SniffError::invalid_format(
+            "playbook validation".to_string(),
+            format!(
+                "{} is a '{}' playbook, expected '{TEXT_PLAYBOOK_LANGUAGE}'",
+                path.display(),
+                playbook.language
+            ),
+        ));
+    }
+    Ok(playbook)
+}
+
+/// Applies the enabled rules of a text playbook to `text`.
+///
+/// Commit message lines starting with `#` are comments, which git strips
+/// before committing, and are ignored. Detections report the line they start
+/// on, with the whole line as the snippet.
+///
+/// # Errors
+///
+/// Returns an error if a rule is not a valid regex rule.
+pub fn check(
+    playbook: &Playbook,
+    kind: TextKind,
+    text: &str,
+) -> Result<Vec<MisalignmentDetection>> {
+    let text = match kind {
+        TextKind::CommitMessage => text
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        TextKind::PrDescription => text.to_string(),
+    };
+    let lines: Vec<&str> = text.lines().collect();
+
+    let conditions = ConditionsCache::default();
+    let mut detections = Vec::new();
+    for rule in playbook.rules.iter().filter(|rule| rule.enabled) {
+        let PatternType::Regex {
+            pattern, multiline, ..
+        } = &rule.pattern_type
+        else {
+            return Err(SniffError::invalid_format(
+                "text rule".to_string(),
+                format!("Text rule '{}' must be a regex rule", rule.id),
+            ));
+        };
+        let regex = Regex::new(pattern).map_err(|e| {
+            SniffError::invalid_format(
+                "regex validation".to_string(),
+                format!("Invalid regex in rule '{}': {}", rule.id, e),
+            )
+        })?;
+
+        let starts: Vec<(usize, usize)> = if *multiline {
+            regex
+                .find_iter(&text)
+                .map(|found| {
+                    let line = text[..found.start()].matches('\n').count();
+                    let line_start = text[..found.start()].rfind('\n').map_or(0, |i| i + 1);
+                    (line, found.start() - line_start)
+                })
+                .collect()
+        } else {
+            lines
+                .iter()
+                .enumerate()
+                .flat_map(|(line, content)| {
+                    regex
+                        .find_iter(content)
+                        .map(move |found| (line, found.start()))
+                })
+                .collect()
+        };
+
+        let mut rule_detections: Vec<MisalignmentDetection> = starts
+            .into_iter()
+            .map(|(line, column)| MisalignmentDetection {
+                rule_id: rule.id.clone(),
+                rule_name: rule.name.clone(),
+                description: rule.description.clone(),
+                severity: rule.severity,
+                file_path: kind.name().to_string(),
+                project_path: None,
+                line_number: line + 1,
+                column_number: column + 1,
+                code_snippet: lines.get(line).map_or("", |l| l.trim()).to_string(),
+                context_lines: None,
+                context: format!("Line {}", line + 1),
+                tags: rule.tags.clone(),
+                remediation: rule.remediation.clone(),
+                taxonomy: rule.taxonomy(),
+                performance_impact: None,
+                test_context: None,
+            })
+            .collect();
+        retain_meeting_conditions(rule, &conditions, &mut rule_detections, &[], &text)?;
+        detections.extend(rule_detections);
+    }
+
+    detections.sort_by_key(|detection| detection.line_number);
+    Ok(detections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule_ids(kind: TextKind, text: &str) -> Vec<String> {
+        let mut ids: Vec<String> = check(&default_playbook(kind), kind, text)
+            .unwrap()
+            .into_iter()
+            .map(|detection| detection.rule_id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_commit_message_rules() {
+        assert_eq!(
+            rule_ids(
+                TextKind::CommitMessage,
+                "misc fixes\n\nAll tests passing.\n"
+            ),
+            vec![
+                "text_missing_issue_reference",
+                "text_test_claim",
+                "text_vague_summary"
+            ]
+        );
+
+        // A specific summary with a reference passes; comment lines are ignored
+        let message =
+            "Retry failed uploads with backoff\n\nFixes.\nCloses #412\n# All tests pass\n";
+        assert!(rule_ids(TextKind::CommitMessage, message).is_empty());
+    }
+
+    #[test]
+    fn test_pr_description_rules() {
+        assert_eq!(
+            rule_ids(TextKind::PrDescription, "  \n"),
+            vec!["text_empty_description", "text_missing_issue_reference"]
+        );
+        let description = "Adds retry with backoff to uploads (PROJ-88).\n\nTested manually.";
+        assert!(rule_ids(TextKind::PrDescription, description).is_empty());
+    }
+
+    #[test]
+    fn test_issue_references() {
+        let missing = |text: &str| {
+            rule_ids(TextKind::PrDescription, text).contains(&"text_missing_issue_reference".into())
+        };
+        assert!(!missing("PROJ-12: Retry uploads"));
+        assert!(!missing("Retry uploads\n\nRefs: PROJ-12"));
+        assert!(!missing("[OPS-7] Retry uploads"));
+        assert!(!missing(
+            "Retry uploads, see https://github.com/o/r/issues/9"
+        ));
+        // Standards named like ticket keys are not references
+        assert!(missing("Verify UTF-8 names and SHA-256 checksums"));
+    }
+}
@@ -4,12 +4,40 @@
 //! TODO verification functionality with sniff analysis integration.
 
 use crate::analysis::MisalignmentAnalyzer;
+use crate::audit::{AuditEntry, AuditLog};
+use crate::criteria::{Criterion, CriterionResult};
 use crate::error::{Result, SniffError};
+use crate::playbook::Severity;
+use crate::project_config::ConfigResolver;
+use crate::scorecard::DeceptionIndicator;
 use crate::standalone::{AnalysisConfig, FileFilter, StandaloneAnalyzer};
-use std::path::PathBuf;
+use crate::waiver::{WaiverStatus, WaiverStore};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tracing::{info, warn};
 
+/// Version of the [`GateReport`] schema, bumped on breaking changes.
+pub const GATE_REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Exit code of a verification whose quality gate failed.
+pub const EXIT_GATE_FAILED: i32 = 1;
+
+/// Exit code of a verification that could not be completed (invalid input,
+/// unreadable files, analysis errors).
+pub const EXIT_TOOL_ERROR: i32 = 2;
+
+/// Rule tags marking code that pretends to work, reported as deception indicators.
+const DECEPTION_TAGS: [&str; 6] = [
+    "fake_success",
+    "fake_return",
+    "fake_logic",
+    "fake_async",
+    "mock_data",
+    "placeholder",
+];
+
 /// Configuration for TODO verification.
 #[derive(Debug, Clone)]
 pub struct VerificationConfig {
@@ -19,6 +47,12 @@ pub struct VerificationConfig {
     pub max_critical_issues: usize,
     /// Whether to include test files in verification.
     pub include_test_files: bool,
+    /// Never write to disk: skips learned pattern storage and the audit log.
+    pub read_only: bool,
+    /// Make the analysis results reproducible (fixed run timestamp, stable ordering).
+    pub reproducible: bool,
+    /// Acceptance criteria the TODO must meet in addition to the quality gate.
+    pub criteria: Vec<Criterion>,
 }
 
 impl Default for VerificationConfig {
@@ -27,6 +61,9 @@ impl Default for VerificationConfig {
             min_quality_score: 80.0,
             max_critical_issues: 0,
             include_test_files: false,
+            read_only: false,
+            reproducible: false,
+            criteria: Vec::new(),
         }
     }
 }
@@ -44,10 +81,279 @@ pub struct VerificationResult {
     pub total_detections: usize,
     /// Files that were analyzed.
     pub files_analyzed: usize,
+    /// Waivers that matched detections, including expired ones.
+    pub waivers: Vec<AppliedWaiver>,
+    /// Hash of the active ruleset, if rules were loaded.
+    pub ruleset_hash: Option<String>,
+    /// Outcome of each acceptance criterion.
+    pub criteria: Vec<CriterionResult>,
     /// Detailed analysis results.
     pub analysis_results: crate::standalone::AnalysisResults,
 }
 
+/// Machine-readable outcome of a TODO verification, printed by
+/// `verify-todo --format json`.
+///
+/// Fields are only added within a schema version; renames and removals bump
+/// [`GATE_REPORT_SCHEMA_VERSION`].
+#[derive(Debug, Clone, Serialize)]
+pub struct GateReport {
+    /// Version of this schema.
+    pub schema_version: u32,
+    /// Verified TODO.
+    pub todo_id: String,
+    /// Whether the gate passed.
+    pub pass: bool,
+    /// Why the gate failed; empty if it passed.
+    pub reasons: Vec<String>,
+    /// Average quality score of the analyzed files.
+    pub quality_score: f64,
+    /// Minimum quality score required.
+    pub min_quality_score: f64,
+    /// Number of critical issues found.
+    pub critical_issues: usize,
+    /// Maximum critical issues allowed.
+    pub max_critical_issues: usize,
+    /// Score of each analyzed file.
+    pub files: Vec<FileGateScore>,
+    /// Signs that the work differs from what was reported.
+    pub deception_indicators: Vec<DeceptionIndicator>,
+    /// Waivers that matched detections, including expired ones.
+    pub waivers: Vec<AppliedWaiver>,
+    /// Hash of the active ruleset, if rules were loaded.
+    pub ruleset_hash: Option<String>,
+    /// Outcome of each acceptance criterion; empty without `--criteria`.
+    pub criteria: Vec<CriterionResult>,
+}
+
+/// Score of a single file in a [`GateReport`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileGateScore {
+    /// Analyzed file.
+    pub path: PathBuf,
+    /// Quality score of the file (0-100).
+    pub quality_score: f64,
+    /// Number of critical issues in the file.
+    pub critical_issues: usize,
+    /// Number of detections in the file.
+    pub detections: usize,
+}
+
+impl GateReport {
+    /// Builds the report of a verification.
+    ///
+    /// `unreported_files` are changed files found by git discovery that were
+    /// not among the files reported for the TODO.
+    #[must_use]
+    pub fn new(
+        todo_id: &str,
+        result: &VerificationResult,
+        config: &VerificationConfig,
+        unreported_files: &[PathBuf],
+    ) -> Self {
+        let mut deception_indicators: Vec<DeceptionIndicator> = unreported_files
+            .iter()
+            .map(|path| DeceptionIndicator {
+                kind: "unreported_file".to_string(),
+                description: format!(
+                    "{} was changed but not reported for the TODO",
+                    path.display()
+                ),
+                file_path: Some(path.display().to_string()),
+            })
+            .collect();
+
+        for file in &result.analysis_results.file_results {
+            for detection in &file.detections {
+                if detection
+                    .tags
+                    .iter()
+                    .any(|tag| DECEPTION_TAGS.contains(&tag.as_str()))
+                {
+                    deception_indicators.push(DeceptionIndicator {
+                        kind: "deceptive_pattern".to_string(),
+                        description: format!(
+                            "{} at line {}",
+                            detection.rule_name, detection.line_number
+                        ),
+                        file_path: Some(detection.file_path.clone()),
+                    });
+                }
+            }
+        }
+
+        Self {
+            schema_version: GATE_REPORT_SCHEMA_VERSION,
+            todo_id: todo_id.to_string(),
+            pass: result.passed,
+            reasons: gate_reasons(result, config),
+            quality_score: result.quality_score,
+            min_quality_score: config.min_quality_score,
+            critical_issues: result.critical_issues,
+            max_critical_issues: config.max_critical_issues,
+            files: file_scores(result),
+            deception_indicators,
+            waivers: result.waivers.clone(),
+            ruleset_hash: result.ruleset_hash.clone(),
+            criteria: result.criteria.clone(),
+        }
+    }
+}
+
+/// Explains why a verification failed the gate; empty if it passed.
+fn gate_reasons(result: &VerificationResult, config: &VerificationConfig) -> Vec<String> {
+    let mut reasons = Vec::new();
+    if result.quality_score < config.min_quality_score {
+        reasons.push(format!(
+            "quality score {:.1}% is below the required {:.1}%",
+            result.quality_score, config.min_quality_score
+        ));
+    }
+    if result.critical_issues > config.max_critical_issues {
+        reasons.push(format!(
+            "{} critical issues exceed the allowed {}",
+            result.critical_issues, config.max_critical_issues
+        ));
+    }
+    for criterion in result.criteria.iter().filter(|criterion| !criterion.met) {
+        reasons.push(format!(
+            "acceptance criterion not met: {} ({})",
+            criterion.description, criterion.detail
+        ));
+    }
+    reasons
+}
+
+/// Scores each analyzed file of a verification.
+fn file_scores(result: &VerificationResult) -> Vec<FileGateScore> {
+    result
+        .analysis_results
+        .file_results
+        .iter()
+        .map(|file| FileGateScore {
+            path: file.file_path.clone(),
+            quality_score: file.quality_score,
+            critical_issues: file
+                .detections
+                .iter()
+                .filter(|d| d.severity == Severity::Critical)
+                .count(),
+            detections: file.detections.len(),
+        })
+        .collect()
+}
+
+/// A waiver that matched a detection during verification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedWaiver {
+    /// Fingerprint of the waived detection.
+    pub fingerprint: String,
+    /// Rule that triggered the detection.
+    pub rule_id: String,
+    /// File where the detection occurred.
+    pub file_path: String,
+    /// Line where the detection occurred.
+    pub line_number: usize,
+    /// Last day on which the waiver is honored.
+    pub until: NaiveDate,
+    /// Why the detection is waived.
+    pub reason: String,
+    /// Status of the waiver at verification time.
+    pub status: WaiverStatus,
+}
+
+/// Applies waivers to analysis results, checks them against the quality gate and
+/// records the decision in the audit log.
+///
+/// Detections covered by an active waiver are excluded from the gate; expired
+/// waivers are reported but not honored.
+fn evaluate_gate(
+    todo_id: &str,
+    files: &[PathBuf],
+    mut results: crate::standalone::AnalysisResults,
+    config: &VerificationConfig,
+    project_dir: &Path,
+    ruleset_hash: Option<String>,
+) -> Result<VerificationResult> {
+    let store = WaiverStore::load(project_dir)?;
+    let today = Utc::now().date_naive();
+    let mut waivers = Vec::new();
+
+    if !store.waivers().is_empty() {
+        let mut project_configs = ConfigResolver::new();
+        for file_result in &mut results.file_results {
+            let before = file_result.detections.len();
+            file_result.detections.retain(|detection| {
+                let fingerprint = detection.fingerprint();
+                let Some(waiver) = store.find(&fingerprint) else {
+                    return true;
+                };
+                let status = waiver.status_on(today);
+                waivers.push(AppliedWaiver {
+                    fingerprint,
+                    rule_id: detection.rule_id.clone(),
+                    file_path: detection.file_path.clone(),
+                    line_number: detection.line_number,
+                    until: waiver.until,
+                    reason: waiver.reason.clone(),
+                    status,
+                });
+                !status.is_honored()
+            });
+            if file_result.detections.len() != before {
+                let config = project_configs.resolve(&file_result.file_path)?;
+                file_result.quality_score = config
+                    .score_weights
+                    .penalties
+                    .score(&file_result.detections);
+            }
+        }
+        results = crate::standalone::AnalysisResults::from_file_results(results.file_results);
+    }
+
+    let quality_passed = results.average_quality_score >= config.min_quality_score;
+    let critical_passed = results.critical_issues <= config.max_critical_issues;
+    let criteria = crate::criteria::check(&config.criteria, files, project_dir)?;
+    let criteria_passed = criteria.iter().all(|criterion| criterion.met);
+
+    let result = VerificationResult {
+        passed: quality_passed && critical_passed && criteria_passed,
+        quality_score: results.average_quality_score,
+        critical_issues: results.critical_issues,
+        total_detections: results.total_detections,
+        files_analyzed: results.total_files,
+        waivers,
+        ruleset_hash,
+        criteria,
+        analysis_results: results,
+    };
+
+    if config.read_only {
+        warn!("Read-only mode: gate decision not recorded in the audit log");
+        return Ok(result);
+    }
+
+    AuditLog::new(project_dir).append(&AuditEntry {
+        timestamp: Utc::now(),
+        sniff_version: env!("CARGO_PKG_VERSION").to_string(),
+        todo_id: todo_id.to_string(),
+        files: files.iter().map(|f| f.display().to_string()).collect(),
+        min_quality_score: config.min_quality_score,
+        max_critical_issues: config.max_critical_issues,
+        quality_score: result.quality_score,
+        critical_issues: result.critical_issues,
+        total_detections: result.total_detections,
+        files_analyzed: result.files_analyzed,
+        passed: result.passed,
+        file_scores: file_scores(&result),
+        reasons: gate_reasons(&result, config),
+        waivers: result.waivers.clone(),
+        ruleset_hash: result.ruleset_hash.clone(),
+    })?;
+
+    Ok(result)
+}
+
 /// Verifies TODO completion with sniff analysis.
 pub async fn verify_todo(
     todo_id: &str,
@@ -73,26 +379,18 @@ pub async fn verify_todo(
     }
     info!("[VERIFY] Verifying TODO '{}' with sniff analysis", todo_id);
 
+    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+
     if files.is_empty() {
-        return Ok(VerificationResult {
-            passed: true,
-            quality_score: 100.0,
-            critical_issues: 0,
-            total_detections: 0,
-            files_analyzed: 0,
-            analysis_results: crate::standalone::AnalysisResults {
-                total_files: 0,
-                total_detections: 0,
-                critical_issues: 0,
-                average_quality_score: 100.0,
-                file_results: Vec::new(),
-            },
-        });
+        let results = crate::standalone::AnalysisResults::from_file_results(Vec::new());
+        return evaluate_gate(todo_id, files, results, &config, &current_dir, None);
     }
 
     // Configure analyzer for verification
     let filter = FileFilter {
         include_hidden: false,
+        respect_ignore_files: true,
+        tracked_only: false,
         allowed_extensions: None,
         exclude_pattern: None,
         max_file_size_bytes: 10 * 1024 * 1024, // 10MB
@@ -102,19 +400,23 @@ pub async fn verify_todo(
 
     let analysis_config = AnalysisConfig {
         filter,
-        force_language: None,
         detailed_analysis: true,
+        reproducible: config.reproducible,
+        ..AnalysisConfig::default()
     };
 
-    // Initialize analyzer with learned patterns
-    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
-    let mut misalignment_analyzer = match MisalignmentAnalyzer::new_with_learned_patterns(&current_dir) {
-        Ok(analyzer) => analyzer,
-        Err(e) => {
-            warn!("Failed to load learned patterns: {}, using default patterns", e);
-            MisalignmentAnalyzer::new()?
-        }
-    };
+    // Initialize analyzer with learned patterns (read-only runs never create their storage)
+    let mut misalignment_analyzer =
+        match MisalignmentAnalyzer::new_with_learned_patterns(&current_dir, config.read_only) {
+            Ok(analyzer) => analyzer,
+            Err(e) => {
+                warn!(
+                    "Failed to load learned patterns: {}, using default patterns",
+                    e
+                );
+                MisalignmentAnalyzer::new()?
+            }
+        };
     
     // Load playbooks
     let playbook_dir = current_dir.join("playbooks");
@@ -130,18 +432,15 @@ pub async fn verify_todo(
     let results = analyzer.analyze_files(files).await?;
 
     // Check quality gate
-    let quality_passed = results.average_quality_score >= config.min_quality_score;
-    let critical_passed = results.critical_issues <= config.max_critical_issues;
-    let verification_passed = quality_passed && critical_passed;
-
-    Ok(VerificationResult {
-        passed: verification_passed,
-        quality_score: results.average_quality_score,
-        critical_issues: results.critical_issues,
-        total_detections: results.total_detections,
-        files_analyzed: results.total_files,
-        analysis_results: results,
-    })
+    let ruleset_hash = analyzer.ruleset_hash();
+    evaluate_gate(
+        todo_id,
+        files,
+        results,
+        &config,
+        &current_dir,
+        Some(ruleset_hash),
+    )
 }
 
 /// Displays verification results in a human-readable format.
@@ -186,7 +485,59 @@ pub fn display_verification_result(
     );
     
     println!("│  └─ Total detections: {}", result.total_detections.to_string().yellow());
+
+    // Waivers matched during verification
+    if !result.waivers.is_empty() {
+        println!("├─ {}", "Waivers".dimmed());
+        for (idx, waiver) in result.waivers.iter().enumerate() {
+            let prefix = if idx == result.waivers.len() - 1 {
+                "└─"
+            } else {
+                "├─"
+            };
+            let status = match waiver.status {
+                WaiverStatus::Active => waiver.status.name().green(),
+                WaiverStatus::Expiring => waiver.status.name().yellow(),
+                WaiverStatus::Expired => waiver.status.name().red(),
+            };
+            println!(
+                "│  {} {} {} ({}:{}) until {} [{}] - {}",
+                prefix,
+                waiver.fingerprint.dimmed(),
+                waiver.rule_id,
+                waiver.file_path,
+                waiver.line_number,
+                waiver.until,
+                status,
+                waiver.reason.italic()
+            );
+        }
+    }
     
+    // Acceptance criteria
+    if !result.criteria.is_empty() {
+        println!("├─ {}", "Criteria".dimmed());
+        for (idx, criterion) in result.criteria.iter().enumerate() {
+            let prefix = if idx == result.criteria.len() - 1 {
+                "└─"
+            } else {
+                "├─"
+            };
+            let status = if criterion.met {
+                "●".green()
+            } else {
+                "●".red()
+            };
+            println!(
+                "│  {} {} {} - {}",
+                prefix,
+                status,
+                criterion.description,
+                criterion.detail.dimmed()
+            );
+        }
+    }
+
     // Verification result
     println!("├─ {}", "Result".dimmed());
     if result.passed {
@@ -209,6 +560,13 @@ pub fn display_verification_result(
                 config.max_critical_issues
             );
         }
+        for criterion in result.criteria.iter().filter(|criterion| !criterion.met) {
+            println!(
+                "│     └─ {} Acceptance criterion not met: {}",
+                "⚠".yellow(),
+                criterion.description
+            );
+        }
     }
 
     // Show detailed issues if verification failed
@@ -233,23 +591,31 @@ pub fn display_verification_result(
                 for (issue_idx, detection) in issues_to_show.enumerate() {
                     let is_last_issue = issue_idx == std::cmp::min(3, total_issues) - 1 && total_issues <= 3;
                     let detection_prefix = if is_last_issue { "└─" } else { "├─" };
-                    
-                    let severity_icon = match format!("{:?}", detection.severity).to_lowercase().as_str() {
-                        "critical" => "●".red().bold(),
-                        "high" => "●".red(),
-                        "medium" => "●".yellow(),
-                        "low" => "●".blue(),
-                        _ => "●".white(),
-                    };
-                    
-                    let code_snippet = detection.code_snippet.trim().chars().take(50).collect::<String>();
-                    
-                    println!("   {}   {} {} {} (line {}): {}", 
+
+                    let severity_icon =
+                        match format!("{:?}", detection.severity).to_lowercase().as_str() {
+                            "critical" => "●".red().bold(),
+                            "high" => "●".red(),
+                            "medium" => "●".yellow(),
+                            "low" => "●".blue(),
+                            _ => "●".white(),
+                        };
+
+                    let code_snippet = detection
+                        .code_snippet
+                        .trim()
+                        .chars()
+                        .take(50)
+                        .collect::<String>();
+
+                    println!(
+                        "   {}   {} {} {} (line {}, {}): {}",
                         issue_prefix,
                         detection_prefix,
                         severity_icon,
                         detection.rule_name.bold(),
                         detection.line_number.to_string().dimmed(),
+                        detection.fingerprint().dimmed(),
                         code_snippet.italic()
                     );
                 }
@@ -356,26 +722,18 @@ async fn verify_todo_with_files(
 ) -> Result<VerificationResult> {
     info!("Verifying TODO '{}' with {} files", todo_id, files.len());
 
+    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
+
     if files.is_empty() {
-        return Ok(VerificationResult {
-            passed: true,
-            quality_score: 100.0,
-            critical_issues: 0,
-            total_detections: 0,
-            files_analyzed: 0,
-            analysis_results: crate::standalone::AnalysisResults {
-                total_files: 0,
-                total_detections: 0,
-                critical_issues: 0,
-                average_quality_score: 100.0,
-                file_results: Vec::new(),
-            },
-        });
+        let results = crate::standalone::AnalysisResults::from_file_results(Vec::new());
+        return evaluate_gate(todo_id, files, results, &config, &current_dir, None);
     }
 
     // Configure analyzer for verification
     let filter = FileFilter {
         include_hidden: false,
+        respect_ignore_files: true,
+        tracked_only: false,
         allowed_extensions: None,
         exclude_pattern: None,
         max_file_size_bytes: 10 * 1024 * 1024, // 10MB
@@ -385,19 +743,23 @@ async fn verify_todo_with_files(
 
     let analysis_config = AnalysisConfig {
         filter,
-        force_language: None,
         detailed_analysis: true,
+        reproducible: config.reproducible,
+        ..AnalysisConfig::default()
     };
 
-    // Initialize analyzer with learned patterns
-    let current_dir = std::env::current_dir().map_err(|e| SniffError::file_system(".", e))?;
-    let mut misalignment_analyzer = match MisalignmentAnalyzer::new_with_learned_patterns(&current_dir) {
-        Ok(analyzer) => analyzer,
-        Err(e) => {
-            warn!("Failed to load learned patterns: {}, using default patterns", e);
-            MisalignmentAnalyzer::new()?
-        }
-    };
+    // Initialize analyzer with learned patterns (read-only runs never create their storage)
+    let mut misalignment_analyzer =
+        match MisalignmentAnalyzer::new_with_learned_patterns(&current_dir, config.read_only) {
+            Ok(analyzer) => analyzer,
+            Err(e) => {
+                warn!(
+                    "Failed to load learned patterns: {}, using default patterns",
+                    e
+                );
+                MisalignmentAnalyzer::new()?
+            }
+        };
     
     // Load playbooks
     let playbook_dir = current_dir.join("playbooks");
@@ -413,16 +775,56 @@ async fn verify_todo_with_files(
     let results = analyzer.analyze_files(files).await?;
 
     // Check quality gate
-    let quality_passed = results.average_quality_score >= config.min_quality_score;
-    let critical_passed = results.critical_issues <= config.max_critical_issues;
-    let verification_passed = quality_passed && critical_passed;
+    let ruleset_hash = analyzer.ruleset_hash();
+    evaluate_gate(
+        todo_id,
+        files,
+        results,
+        &config,
+        &current_dir,
+        Some(ruleset_hash),
+    )
+}
 
-    Ok(VerificationResult {
-        passed: verification_passed,
-        quality_score: results.average_quality_score,
-        critical_issues: results.critical_issues,
-        total_detections: results.total_detections,
-        files_analyzed: results.total_files,
-        analysis_results: results,
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gate_report_reasons_and_indicators() {
+        let result = VerificationResult {
+            passed: false,
+            quality_score: 72.0,
+            critical_issues: 2,
+            total_detections: 3,
+            files_analyzed: 0,
+            waivers: Vec::new(),
+            ruleset_hash: None,
+            criteria: Vec::new(),
+            analysis_results: crate::standalone::AnalysisResults::from_file_results(Vec::new()),
+        };
+        let report = GateReport::new(
+            "auth",
+            &result,
+            &VerificationConfig::default(),
+            &[PathBuf::from("src/secret.rs")],
+        );
+
+        assert_eq!(report.schema_version, GATE_REPORT_SCHEMA_VERSION);
+        assert!(!report.pass);
+        assert_eq!(
+            report.reasons,
+            vec![
+                "quality score 72.0% is below the required 80.0%",
+                "2 critical issues exceed the allowed 0",
+            ]
+        );
+        assert_eq!(report.deception_indicators.len(), 1);
+        assert_eq!(report.deception_indicators[0].kind, "unreported_file");
+
+        let json = serde_json::to_value(&report).unwrap();
+        for key in ["pass", "reasons", "files", "deception_indicators"] {
+            assert!(json.get(key).is_some(), "missing {key}");
+        }
+    }
 }
@@ -0,0 +1,210 @@
+// Copyright (c) 2025 Chetan Conikee <conikee@gmail.com>
+// Licensed under the MIT License
+
+//! Time-boxed waivers for quality gate detections.
+//!
+//! Waivers are stored in `.sniff/waivers.yaml` and keyed by detection fingerprint.
+//! Gates honor active waivers but keep reporting them, so teams can see which
+//! waivers are about to expire or already have.
+
+use crate::error::{Result, SniffError};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Number of days before expiry at which a waiver is reported as expiring.
+pub const EXPIRING_WINDOW_DAYS: i64 = 14;
+
+/// A time-boxed waiver for a single detection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waiver {
+    /// Fingerprint of the waived detection.
+    pub fingerprint: String,
+    /// Last day (inclusive) on which the waiver is honored.
+    pub until: NaiveDate,
+    /// Why the detection is waived.
+    pub reason: String,
+    /// When the waiver was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Status of a waiver on a given day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WaiverStatus {
+    /// The waiver is honored.
+    Active,
+    /// The waiver is honored but expires within [`EXPIRING_WINDOW_DAYS`].
+    Expiring,
+    /// The waiver is no longer honored.
+    Expired,
+}
+
+impl WaiverStatus {
+    /// Returns whether gates honor a waiver with this status.
+    #[must_use]
+    pub fn is_honored(self) -> bool {
+        !matches!(self, Self::Expired)
+    }
+
+    /// Returns a human-readable name for this status.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Expiring => "expiring",
+            Self::Expired => "expired",
+        }
+    }
+}
+
+impl Waiver {
+    /// Returns the status of this waiver on the given day.
+    #[must_use]
+    pub fn status_on(&self, today: NaiveDate) -> WaiverStatus {
+        let days_left = (self.until - today).num_days();
+        if days_left < 0 {
+            WaiverStatus::Expired
+        } else if days_left < EXPIRING_WINDOW_DAYS {
+            WaiverStatus::Expiring
+        } else {
+            WaiverStatus::Active
+        }
+    }
+}
+
+/// On-disk layout of `.sniff/waivers.yaml`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WaiverFile {
+    #[serde(default)]
+    waivers: Vec<Waiver>,
+}
+
+/// Waivers for a project, backed by `.sniff/waivers.yaml`.
+#[derive(Debug)]
+pub struct WaiverStore {
+    path: PathBuf,
+    waivers: Vec<Waiver>,
+}
+
+impl WaiverStore {
+    /// Loads the waivers for a project, returning an empty store if none exist.
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(".sniff").join("waivers.yaml");
+        let waivers = if path.exists() {
+            let content =
+                std::fs::read_to_string(&path).map_err(|e| SniffError::file_system(&path, e))?;
+            let file: WaiverFile = serde_yaml::from_str(&content).map_err(|e| {
+                SniffError::invalid_format(
+                    "waivers".to_string(),
+                    format!("Failed to parse {}: {e}", path.display()),
+                )
+            })?;
+            file.waivers
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self { path, waivers })
+    }
+
+    /// Saves the waivers back to `.sniff/waivers.yaml`.
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| SniffError::file_system(parent, e))?;
+        }
+
+        let file = WaiverFile {
+            waivers: self.waivers.clone(),
+        };
+        let content = serde_yaml::to_string(&file).map_err(|e| {
+            SniffError::invalid_format("waivers".to_string(), format!("Failed to serialize: {e}"))
+        })?;
+
+        std::fs::write(&self.path, content).map_err(|e| SniffError::file_system(&self.path, e))
+    }
+
+    /// Adds a waiver, replacing any existing waiver for the same fingerprint.
+    pub fn add(&mut self, waiver: Waiver) {
+        self.waivers.retain(|w| w.fingerprint != waiver.fingerprint);
+        self.waivers.push(waiver);
+    }
+
+    /// Removes the waiver for a fingerprint, returning whether one existed.
+    pub fn remove(&mut self, fingerprint: &str) -> bool {
+        let before = self.waivers.len();
+        self.waivers.retain(|w| w.fingerprint != fingerprint);
+        self.waivers.len() != before
+    }
+
+    /// Finds the waiver for a fingerprint, regardless of its status.
+    #[must_use]
+    pub fn find(&self, fingerprint: &str) -> Option<&Waiver> {
+        self.waivers.iter().find(|w| w.fingerprint == fingerprint)
+    }
+
+    /// Returns all waivers.
+    #[must_use]
+    pub fn waivers(&self) -> &[Waiver] {
+        &self.waivers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn waiver(fingerprint: &str, until: &str) -> Waiver {
+        Waiver {
+            fingerprint: fingerprint.to_string(),
+            until: NaiveDate::parse_from_str(until, "%Y-%m-%d").unwrap(),
+            reason: "tracked in issue".to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_waiver_status() {
+        let today = NaiveDate::from_ymd_opt(2025, 11, 1).unwrap();
+
+        assert_eq!(
+            waiver("a", "2025-12-31").status_on(today),
+            WaiverStatus::Active
+        );
+        assert_eq!(
+            waiver("a", "2025-11-10").status_on(today),
+            WaiverStatus::Expiring
+        );
+        assert_eq!(
+            waiver("a", "2025-11-01").status_on(today),
+            WaiverStatus::Expiring
+        );
+        assert_eq!(
+            waiver("a", "2025-10-31").status_on(today),
+            WaiverStatus::Expired
+        );
+        assert!(!WaiverStatus::Expired.is_honored());
+    }
+
+    #[test]
+    fn test_waiver_store_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let mut store = WaiverStore::load(temp_dir.path()).unwrap();
+        assert!(store.waivers().is_empty());
+
+        store.add(waiver("abc", "2025-12-01"));
+        store.add(waiver("abc", "2026-01-01"));
+        store.save().unwrap();
+
+        let mut store = WaiverStore::load(temp_dir.path()).unwrap();
+        assert_eq!(store.waivers().len(), 1);
+        assert_eq!(
+            store.find("abc").unwrap().until,
+            NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()
+        );
+        assert!(store.remove("abc"));
+        assert!(!store.remove("abc"));
+    }
+}
@@ -0,0 +1,25 @@
+"""Invoice helpers for the billing service."""
+
+import json
+
+
+def load_invoice(path):
+    with open(path) as handle:
+        return json.load(handle)
+
+
+def send_reminder(invoice):
+    raise NotImplementedError
+
+
+def parse_amount(text):
+    try:
+        return float(text)
+    except:
+        raise ValueError(f"invalid amount: {text!r}")
+
+
+def apply_discount(invoice, rate):
+    # TODO: cap the rate at the customer's plan limit
+    total = invoice["total"]
+    return total - total * rate
@@ -0,0 +1,7 @@
+# Comment-only input, so the expected detections do not depend on parsing.
+
+# TODO: handle pagination
+# FIXME: retry on timeout
+# For now, just return the cached value
+
+# Plain documentation comment that must not be flagged
@@ -0,0 +1,7 @@
+// Comment-only input, so the expected detections do not depend on parsing.
+
+// TODO: validate the checksum before trusting the header
+// For now, just return the first record
+// Workaround for issue #123
+
+// Plain documentation comment that must not be flagged
@@ -0,0 +1,26 @@
+//! Order intake for the storefront API.
+
+use std::collections::HashMap;
+
+pub struct Order {
+    pub id: u64,
+    pub items: Vec<String>,
+}
+
+pub fn parse_order_id(raw: &str) -> u64 {
+    raw.trim().parse().unwrap()
+}
+
+pub fn refund(order: &Order) -> Result<u64, String> {
+    unimplemented!()
+}
+
+pub fn total(prices: &HashMap<String, u64>, order: &Order) -> u64 {
+    // TODO: apply volume discounts
+    order.items.iter().filter_map(|item| prices.get(item)).sum()
+}
+
+/// Orders with no items are rejected before they reach the warehouse.
+pub fn is_empty(order: &Order) -> bool {
+    order.items.is_empty()
+}
@@ -0,0 +1,22 @@
+// HTTP client for the inventory service.
+
+export interface Item {
+  sku: string;
+  quantity: number;
+}
+
+async function fetchItems(baseUrl: string): Promise<Item[]> {
+  const response = await fetch(`${baseUrl}/items`);
+  return (await response.json()) as any;
+}
+
+function reserve(item: Item, quantity: number): void {
+  throw new Error("not implemented");
+}
+
+function restock(items: Item[], sku: string, amount: number): Item[] {
+  // TODO: reject negative amounts
+  return items.map((item) =>
+    item.sku === sku ? { ...item, quantity: item.quantity + amount } : item,
+  );
+}
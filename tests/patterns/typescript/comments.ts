@@ -0,0 +1,7 @@
+// Comment-only input, so the expected detections do not depend on parsing.
+
+// TODO: debounce the search input
+// @ts-ignore
+// For now, just return an empty list
+
+// Plain documentation comment that must not be flagged